@@ -0,0 +1,236 @@
+//! Replay of error findings through the official `datafusion-cli`.
+//!
+//! The fuzzer runs queries with its own session configuration, while users
+//! mostly meet DataFusion through `datafusion-cli` and its defaults. With
+//! `datafusion_cli` configured, the tables of a finding's round are exported
+//! as Arrow IPC files and a reproducer script registering them and running the
+//! finding's queries is executed by the CLI. Whether it fails the same way is
+//! recorded in the finding.
+
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::process::Command;
+use tracing::info;
+
+use crate::common::{LogicalTable, Result, TableNamespace, fuzzer_err};
+use crate::fuzz_context::GlobalContext;
+use crate::fuzz_runner::findings::{Finding, write_ipc_file};
+use crate::triage::{FailureCategory, classify_error};
+
+/// Whether a finding of this category can be checked in the CLI. Wrong
+/// results and inconsistent outcomes need the oracle to compare the results,
+/// the CLI only shows them. Memory leaks build up over whole rounds, and slow
+/// planning and planning timeouts are only measured by the fuzzer.
+pub fn is_replayable_in_cli(category: FailureCategory) -> bool {
+    !matches!(
+        category,
+        FailureCategory::WrongResult
+            | FailureCategory::InconsistentOutcome
+            | FailureCategory::MemoryLeak
+            | FailureCategory::SlowPlanning
+            | FailureCategory::PlanningTimeout
+    )
+}
+
+/// Run the queries of `finding` in `datafusion-cli` on the current tables,
+/// writing the reproducer to `cli/<query_id>/` in `output_dir`. Returns whether
+/// the CLI fails with the same category of error.
+pub async fn replay_in_datafusion_cli(
+    ctx: &Arc<GlobalContext>,
+    datafusion_cli: &Path,
+    output_dir: &Path,
+    finding: &Finding,
+) -> Result<bool> {
+    let reproducer_dir = output_dir.join("cli").join(finding.query_id.to_string());
+    std::fs::create_dir_all(&reproducer_dir)?;
+    // The script is run from another working directory
+    let reproducer_dir = std::path::absolute(&reproducer_dir)?;
+
+    let tables = export_tables(ctx, &reproducer_dir).await?;
+    let script_path = reproducer_dir.join("reproducer.sql");
+    std::fs::write(&script_path, reproducer_script(&tables, &finding.queries))?;
+
+    let timeout =
+        Duration::from_secs(ctx.runner_config.timeout_seconds * (finding.queries.len() as u64 + 1));
+    let output = Command::new(datafusion_cli)
+        .arg("--quiet")
+        .arg("--file")
+        .arg(&script_path)
+        .stdin(Stdio::null())
+        .kill_on_drop(true)
+        .output();
+    let (succeeded, output) = match tokio::time::timeout(timeout, output).await {
+        Ok(output) => {
+            let output = output.map_err(|e| {
+                fuzzer_err(&format!(
+                    "Failed to run '{}': {}",
+                    datafusion_cli.display(),
+                    e
+                ))
+            })?;
+            let text = format!(
+                "{}{}",
+                String::from_utf8_lossy(&output.stdout),
+                String::from_utf8_lossy(&output.stderr)
+            );
+            (output.status.success(), text)
+        }
+        Err(_) => (
+            false,
+            format!("datafusion-cli timed out after {:?}", timeout),
+        ),
+    };
+
+    let reproduced = cli_reproduces(finding.category, succeeded, &output);
+    info!(
+        "Finding {} {} in datafusion-cli, reproducer: {}",
+        finding.query_id,
+        if reproduced {
+            "reproduces"
+        } else {
+            "does not reproduce"
+        },
+        script_path.display()
+    );
+    Ok(reproduced)
+}
+
+/// Write every registered table to `<table>.arrow` in `dir`
+async fn export_tables(
+    ctx: &Arc<GlobalContext>,
+    dir: &Path,
+) -> Result<Vec<(Arc<LogicalTable>, PathBuf)>> {
+    let snapshot = ctx.runtime_context.snapshot();
+
+    let mut tables = Vec::with_capacity(snapshot.tables.len());
+    for (name, table) in snapshot.tables.iter() {
+        let df = snapshot
+            .session_context
+            .table(table.table_reference())
+            .await?;
+        let schema = df.schema().as_arrow().clone();
+        let batches = df.collect().await?;
+
+        let path = dir.join(format!("{}.arrow", name));
+        write_ipc_file(&path, &schema, &batches)?;
+        tables.push((Arc::clone(table), path));
+    }
+
+    Ok(tables)
+}
+
+/// SQL script creating the schemas and catalogs of the exported tables,
+/// registering the tables and running the queries
+fn reproducer_script(tables: &[(Arc<LogicalTable>, PathBuf)], queries: &[String]) -> String {
+    let mut script = String::new();
+    let namespaces: BTreeSet<&TableNamespace> = tables
+        .iter()
+        .filter_map(|(table, _)| table.namespace.as_ref())
+        .collect();
+    for namespace in namespaces {
+        for statement in namespace.create_statements() {
+            let _ = writeln!(script, "{};", statement);
+        }
+    }
+    for (table, path) in tables {
+        let location = path.display().to_string().replace('\'', "''");
+        let _ = writeln!(
+            script,
+            "CREATE EXTERNAL TABLE {} STORED AS ARROW LOCATION '{}';",
+            table.sql_name(),
+            location
+        );
+    }
+    for query in queries {
+        let _ = writeln!(script, "\n{};", query.trim_end().trim_end_matches(';'));
+    }
+    script
+}
+
+/// The CLI reproduces an error finding if it fails with an error of the same
+/// category. Its output contains the results of the other queries, only the
+/// error is classified.
+fn cli_reproduces(category: FailureCategory, succeeded: bool, output: &str) -> bool {
+    if succeeded && !output.contains("Error") && !output.contains("panicked") {
+        return false;
+    }
+    classify_error(output) == category
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn script_registers_tables_before_the_queries() {
+        let script = reproducer_script(
+            &[(
+                Arc::new(LogicalTable::new("t0".to_string())),
+                PathBuf::from("/tmp/cli/it's/t0.arrow"),
+            )],
+            &["SELECT * FROM t0;".to_string()],
+        );
+
+        assert_eq!(
+            script,
+            "CREATE EXTERNAL TABLE t0 STORED AS ARROW LOCATION '/tmp/cli/it''s/t0.arrow';\n\
+             \n\
+             SELECT * FROM t0;\n"
+        );
+    }
+
+    #[test]
+    fn script_creates_the_schemas_of_tables() {
+        let mut table = LogicalTable::new("t1".to_string());
+        table.namespace = Some(TableNamespace::new("c1", "s1"));
+        let script = reproducer_script(
+            &[(Arc::new(table), PathBuf::from("/tmp/cli/t1.arrow"))],
+            &[],
+        );
+
+        assert_eq!(
+            script,
+            "CREATE DATABASE IF NOT EXISTS c1;\n\
+             CREATE SCHEMA IF NOT EXISTS c1.s1;\n\
+             CREATE EXTERNAL TABLE c1.s1.t1 STORED AS ARROW LOCATION '/tmp/cli/t1.arrow';\n"
+        );
+    }
+
+    #[test]
+    fn only_the_same_error_category_reproduces() {
+        let internal_error = "Error: Internal error: Invalid aggregate state.";
+        assert!(cli_reproduces(
+            FailureCategory::InternalError,
+            false,
+            internal_error
+        ));
+        assert!(!cli_reproduces(
+            FailureCategory::Panic,
+            false,
+            internal_error
+        ));
+
+        // Some CLI versions report errors of a script without failing
+        assert!(cli_reproduces(
+            FailureCategory::InternalError,
+            true,
+            internal_error
+        ));
+        assert!(!cli_reproduces(
+            FailureCategory::Other,
+            true,
+            "+----+\n| c0 |\n+----+\n"
+        ));
+    }
+
+    #[test]
+    fn results_are_not_replayed() {
+        assert!(is_replayable_in_cli(FailureCategory::Panic));
+        assert!(!is_replayable_in_cli(FailureCategory::WrongResult));
+    }
+}
@@ -1,5 +1,13 @@
 use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::sync::LazyLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use chrono::NaiveDate;
+
+use crate::common::{Result, fuzzer_err};
 
 /// Error pattern matching strategies
 #[derive(Debug, Clone)]
@@ -15,7 +23,10 @@ pub enum ErrorPattern {
     },
 }
 
-/// Configuration for error whitelist patterns
+/// Built-in error whitelist patterns
+///
+/// Users can add more patterns without recompiling through the `[whitelist]`
+/// config section, see [`WhitelistConfig`].
 ///
 /// This module provides flexible error pattern matching for the fuzzer.
 /// You can whitelist errors using either exact string matching or regex patterns.
@@ -60,6 +71,11 @@ static ERROR_PATTERNS: LazyLock<Vec<ErrorPattern>> = LazyLock::new(|| {
 
         // select 1 / 0;
         ErrorPattern::Contains("Arrow error: Divide by zero error"),
+        // select CAST(2147483647 AS INT) + CAST(1 AS INT);
+        // Failing on overflow is valid, `OverflowOracle` checks the constant
+        // folded and the columnar paths agree on it
+        ErrorPattern::Contains("Overflow happened on"),
+        ErrorPattern::RegexMatch(r"is too large to store in a Decimal(128|256) of precision"),
         // select Null * Null;
         ErrorPattern::RegexMatch(
             r"Error during planning: Cannot coerce arithmetic expression (.+) to valid types",
@@ -121,6 +137,11 @@ static ERROR_PATTERNS: LazyLock<Vec<ErrorPattern>> = LazyLock::new(|| {
             query_sub: "to_timestamp",
             error_sub: "Error parsing timestamp from",
         },
+        // Queries nested deeper than `datafusion.sql_parser.recursion_limit`,
+        // which the DeepNesting oracle generates on purpose. DataFusion shows
+        // the parser error with its `Debug` format, the parser with `Display`.
+        ErrorPattern::Contains("RecursionLimitExceeded"),
+        ErrorPattern::Contains("recursion limit exceeded"),
         // =========================
         // Known Issues
         // =========================
@@ -153,28 +174,15 @@ static ERROR_PATTERNS: LazyLock<Vec<ErrorPattern>> = LazyLock::new(|| {
     ]
 });
 
-/// Compiled regex patterns cache
-static COMPILED_REGEXES: LazyLock<Vec<Option<Regex>>> = LazyLock::new(|| {
-    ERROR_PATTERNS
-        .iter()
-        .map(|pattern| match pattern {
-            ErrorPattern::Contains(_) => None, // No regex compilation needed for exact matches
-            ErrorPattern::RegexMatch(regex_str) => match Regex::new(regex_str) {
-                Ok(regex) => Some(regex),
-                Err(e) => {
-                    eprintln!("Warning: Invalid regex pattern '{}': {}", regex_str, e);
-                    None
-                }
-            },
-            ErrorPattern::QueryAndErrorContains { .. } => None,
-        })
-        .collect()
-});
+/// Built-in patterns compiled once, used by [`is_error_whitelisted`]
+static BUILTIN_WHITELIST: LazyLock<ErrorWhitelist> = LazyLock::new(ErrorWhitelist::builtin);
 
-/// Check if an error message matches any pattern in the whitelist
+/// Check if an error message matches any built-in whitelisted pattern
 ///
 /// This function checks both exact string patterns and regex patterns.
 /// It returns true if the error message matches any whitelisted pattern.
+/// Patterns added through the `[whitelist]` config section are only visible
+/// through [`ErrorWhitelist::is_whitelisted`].
 ///
 /// # Arguments
 /// * `error_msg` - The error message to check
@@ -196,59 +204,355 @@ static COMPILED_REGEXES: LazyLock<Vec<Option<Regex>>> = LazyLock::new(|| {
 /// assert!(!is_error_whitelisted("Unexpected segmentation fault", None));
 /// ```
 pub fn is_error_whitelisted(error_msg: &str, query_sql: Option<&str>) -> bool {
-    for (i, pattern) in ERROR_PATTERNS.iter().enumerate() {
-        match pattern {
-            ErrorPattern::Contains(exact_str) => {
-                if error_msg.contains(exact_str) {
-                    return true;
-                }
+    BUILTIN_WHITELIST.is_whitelisted(error_msg, query_sql)
+}
+
+/// Get a list of all built-in error patterns for debugging/logging
+pub fn get_configured_patterns() -> Vec<String> {
+    BUILTIN_WHITELIST.describe_patterns()
+}
+
+/// A user-provided whitelist pattern from the `[whitelist]` config section or a
+/// whitelist file.
+///
+/// Exactly one of `contains`/`regex` matches the error message; `query_contains`
+/// optionally narrows the pattern to queries whose SQL contains the substring.
+///
+/// `expires` (a `YYYY-MM-DD` date) and `issue` (e.g. the upstream issue URL)
+/// let stale suppressions for fixed upstream bugs be surfaced in the exit report.
+///
+/// ```toml
+/// [[whitelist.patterns]]
+/// contains = "Arrow error: Divide by zero error"
+///
+/// [[whitelist.patterns]]
+/// regex = "(?i)cast error: .* out of range"
+/// query_contains = "CAST("
+/// expires = "2025-06-01"
+/// issue = "https://github.com/apache/datafusion/issues/17387"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WhitelistPatternConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contains: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regex: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query_contains: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issue: Option<String>,
+}
+
+/// The `[whitelist]` config section
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WhitelistConfig {
+    /// Keep the built-in patterns from `ERROR_PATTERNS` (default: true)
+    #[serde(default = "WhitelistConfig::default_use_builtin")]
+    pub use_builtin: bool,
+    /// Optional separate TOML file with more `[[patterns]]` entries
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<PathBuf>,
+    /// Patterns defined inline in the config file
+    #[serde(default)]
+    pub patterns: Vec<WhitelistPatternConfig>,
+}
+
+impl WhitelistConfig {
+    fn default_use_builtin() -> bool {
+        true
+    }
+}
+
+impl Default for WhitelistConfig {
+    fn default() -> Self {
+        Self {
+            use_builtin: Self::default_use_builtin(),
+            file: None,
+            patterns: Vec::new(),
+        }
+    }
+}
+
+/// Layout of a standalone whitelist file referenced by `whitelist.file`
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WhitelistFile {
+    #[serde(default)]
+    patterns: Vec<WhitelistPatternConfig>,
+}
+
+#[derive(Debug)]
+enum ErrorMatcher {
+    Contains(String),
+    Regex(Regex),
+}
+
+#[derive(Debug)]
+struct CompiledPattern {
+    error_matcher: ErrorMatcher,
+    query_sub: Option<String>,
+    expires: Option<NaiveDate>,
+    issue: Option<String>,
+    /// Number of errors suppressed by this pattern during the run
+    hits: AtomicU64,
+}
+
+impl CompiledPattern {
+    fn new(error_matcher: ErrorMatcher, query_sub: Option<String>) -> Self {
+        Self {
+            error_matcher,
+            query_sub,
+            expires: None,
+            issue: None,
+            hits: AtomicU64::new(0),
+        }
+    }
+
+    fn matches(&self, error_msg: &str, query_sql: Option<&str>) -> bool {
+        if let Some(query_sub) = &self.query_sub {
+            match query_sql {
+                Some(sql) if sql.contains(query_sub.as_str()) => {}
+                _ => return false,
             }
-            ErrorPattern::RegexMatch(_) => {
-                if let Some(Some(regex)) = COMPILED_REGEXES.get(i) {
-                    if regex.is_match(error_msg) {
-                        return true;
+        }
+
+        match &self.error_matcher {
+            ErrorMatcher::Contains(sub) => error_msg.contains(sub.as_str()),
+            ErrorMatcher::Regex(regex) => regex.is_match(error_msg),
+        }
+    }
+
+    fn describe(&self) -> String {
+        let error_part = match &self.error_matcher {
+            ErrorMatcher::Contains(s) => format!("Exact: {}", s),
+            ErrorMatcher::Regex(r) => format!("Regex: {}", r.as_str()),
+        };
+
+        let mut description = match &self.query_sub {
+            Some(query_sub) => format!("{} (query contains '{}')", error_part, query_sub),
+            None => error_part,
+        };
+        if let Some(issue) = &self.issue {
+            description.push_str(&format!(" [{}]", issue));
+        }
+        if let Some(expires) = &self.expires {
+            description.push_str(&format!(" [expires {}]", expires));
+        }
+
+        description
+    }
+
+    fn is_expired(&self, today: NaiveDate) -> bool {
+        self.expires.is_some_and(|expires| expires < today)
+    }
+}
+
+/// Whitelist usage of a run, reported at exit
+#[derive(Debug, Clone, Default)]
+pub struct WhitelistHitReport {
+    /// Patterns that matched at least once, most hits first
+    pub top_matchers: Vec<(String, u64)>,
+    /// Patterns that never matched during the run
+    pub unused: Vec<String>,
+    /// Patterns whose `expires` date has passed
+    pub expired: Vec<String>,
+}
+
+/// The effective error whitelist of a run: built-in patterns merged with the
+/// ones from the `[whitelist]` config section and whitelist file.
+#[derive(Debug)]
+pub struct ErrorWhitelist {
+    patterns: Vec<CompiledPattern>,
+}
+
+impl ErrorWhitelist {
+    /// Whitelist with only the built-in `ERROR_PATTERNS`
+    pub fn builtin() -> Self {
+        let patterns = ERROR_PATTERNS
+            .iter()
+            .filter_map(|pattern| {
+                let compiled = match pattern {
+                    ErrorPattern::Contains(s) => {
+                        CompiledPattern::new(ErrorMatcher::Contains(s.to_string()), None)
                     }
-                }
+                    ErrorPattern::RegexMatch(regex_str) => match Regex::new(regex_str) {
+                        Ok(regex) => CompiledPattern::new(ErrorMatcher::Regex(regex), None),
+                        Err(e) => {
+                            eprintln!("Warning: Invalid regex pattern '{}': {}", regex_str, e);
+                            return None;
+                        }
+                    },
+                    ErrorPattern::QueryAndErrorContains {
+                        query_sub,
+                        error_sub,
+                    } => CompiledPattern::new(
+                        ErrorMatcher::Contains(error_sub.to_string()),
+                        Some(query_sub.to_string()),
+                    ),
+                };
+                Some(compiled)
+            })
+            .collect();
+
+        Self { patterns }
+    }
+
+    /// Build the whitelist from the `[whitelist]` config section, merging the
+    /// built-in patterns (unless disabled), the inline patterns and the patterns
+    /// of the optional whitelist file, in that order.
+    pub fn from_config(config: &WhitelistConfig) -> Result<Self> {
+        let mut whitelist = if config.use_builtin {
+            Self::builtin()
+        } else {
+            Self {
+                patterns: Vec::new(),
             }
-            ErrorPattern::QueryAndErrorContains {
-                query_sub,
-                error_sub,
-            } => {
-                if let Some(sql) = query_sql {
-                    if sql.contains(query_sub) && error_msg.contains(error_sub) {
-                        return true;
-                    }
-                }
+        };
+
+        for pattern in &config.patterns {
+            whitelist.patterns.push(compile_user_pattern(pattern)?);
+        }
+
+        if let Some(path) = &config.file {
+            for pattern in load_whitelist_file(path)? {
+                whitelist.patterns.push(compile_user_pattern(&pattern)?);
+            }
+        }
+
+        Ok(whitelist)
+    }
+
+    /// Check if an error message matches any pattern in this whitelist
+    ///
+    /// The first matching pattern gets its hit counter incremented.
+    pub fn is_whitelisted(&self, error_msg: &str, query_sql: Option<&str>) -> bool {
+        match self
+            .patterns
+            .iter()
+            .find(|pattern| pattern.matches(error_msg, query_sql))
+        {
+            Some(pattern) => {
+                pattern.hits.fetch_add(1, Ordering::Relaxed);
+                true
             }
+            None => false,
         }
     }
 
-    false
+    /// Descriptions of patterns whose `expires` date is before `today`
+    pub fn expired_patterns(&self, today: NaiveDate) -> Vec<String> {
+        self.patterns
+            .iter()
+            .filter(|pattern| pattern.is_expired(today))
+            .map(CompiledPattern::describe)
+            .collect()
+    }
+
+    /// Summarize pattern hits so far: top `top_n` matchers, unused and expired patterns
+    pub fn hit_report(&self, top_n: usize, today: NaiveDate) -> WhitelistHitReport {
+        let mut top_matchers: Vec<(String, u64)> = self
+            .patterns
+            .iter()
+            .map(|pattern| (pattern.describe(), pattern.hits.load(Ordering::Relaxed)))
+            .filter(|(_, hits)| *hits > 0)
+            .collect();
+        // Stable sort keeps config order for ties
+        top_matchers.sort_by(|a, b| b.1.cmp(&a.1));
+        top_matchers.truncate(top_n);
+
+        let unused = self
+            .patterns
+            .iter()
+            .filter(|pattern| pattern.hits.load(Ordering::Relaxed) == 0)
+            .map(CompiledPattern::describe)
+            .collect();
+
+        WhitelistHitReport {
+            top_matchers,
+            unused,
+            expired: self.expired_patterns(today),
+        }
+    }
+
+    /// Human-readable description of every pattern, for debugging/logging
+    pub fn describe_patterns(&self) -> Vec<String> {
+        self.patterns
+            .iter()
+            .map(CompiledPattern::describe)
+            .collect()
+    }
 }
 
-/// Get a list of all configured error patterns for debugging/logging
-pub fn get_configured_patterns() -> Vec<String> {
-    ERROR_PATTERNS
-        .iter()
-        .map(|pattern| match pattern {
-            ErrorPattern::Contains(s) => format!("Exact: {}", s),
-            ErrorPattern::RegexMatch(s) => format!("Regex: {}", s),
-            ErrorPattern::QueryAndErrorContains {
-                query_sub,
-                error_sub,
-            } => {
-                format!(
-                    "QueryAndError: query contains '{}' AND error contains '{}'",
-                    query_sub, error_sub
-                )
-            }
+impl Default for ErrorWhitelist {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+fn load_whitelist_file(path: &Path) -> Result<Vec<WhitelistPatternConfig>> {
+    let content = fs::read_to_string(path).map_err(|e| {
+        fuzzer_err(&format!(
+            "Failed to read whitelist file '{}': {}",
+            path.display(),
+            e
+        ))
+    })?;
+
+    let file: WhitelistFile = toml::from_str(&content).map_err(|e| {
+        fuzzer_err(&format!(
+            "Failed to parse whitelist file '{}': {}",
+            path.display(),
+            e
+        ))
+    })?;
+
+    Ok(file.patterns)
+}
+
+fn compile_user_pattern(pattern: &WhitelistPatternConfig) -> Result<CompiledPattern> {
+    let error_matcher = match (&pattern.contains, &pattern.regex) {
+        (Some(sub), None) => ErrorMatcher::Contains(sub.clone()),
+        (None, Some(regex_str)) => ErrorMatcher::Regex(Regex::new(regex_str).map_err(|e| {
+            fuzzer_err(&format!(
+                "Invalid whitelist regex pattern '{}': {}",
+                regex_str, e
+            ))
+        })?),
+        _ => {
+            return Err(fuzzer_err(&format!(
+                "Whitelist pattern must set exactly one of `contains` or `regex`: {:?}",
+                pattern
+            )));
+        }
+    };
+
+    let expires = pattern
+        .expires
+        .as_deref()
+        .map(|date| {
+            NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|e| {
+                fuzzer_err(&format!(
+                    "Invalid whitelist `expires` date '{}' (expected YYYY-MM-DD): {}",
+                    date, e
+                ))
+            })
         })
-        .collect()
+        .transpose()?;
+
+    let mut compiled = CompiledPattern::new(error_matcher, pattern.query_contains.clone());
+    compiled.expires = expires;
+    compiled.issue = pattern.issue.clone();
+
+    Ok(compiled)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::is_error_whitelisted;
+    use super::*;
 
     #[test]
     fn whitelists_timestamp_parse_errors_for_to_timestamp_queries() {
@@ -273,4 +577,146 @@ mod tests {
 
         assert!(is_error_whitelisted(error, Some(query)));
     }
+
+    #[test]
+    fn whitelists_arithmetic_overflow() {
+        assert!(is_error_whitelisted(
+            "Query execution failed: Arrow error: Arithmetic overflow: Overflow happened on: 2147483647 + 1",
+            None
+        ));
+        assert!(is_error_whitelisted(
+            "Query execution failed: Arrow error: Invalid argument error: 100000 is too large to store in a Decimal128 of precision 5. Max is 99999",
+            None
+        ));
+    }
+
+    #[test]
+    fn whitelists_parser_recursion_limit() {
+        assert!(is_error_whitelisted(
+            "Query planning failed: SQL error: RecursionLimitExceeded",
+            None
+        ));
+        assert!(is_error_whitelisted(
+            "Query planning failed: sql parser error: recursion limit exceeded",
+            None
+        ));
+    }
+
+    #[test]
+    fn merges_config_patterns_with_builtin_defaults() {
+        let config = WhitelistConfig {
+            patterns: vec![
+                WhitelistPatternConfig {
+                    contains: Some("my known issue".to_string()),
+                    ..Default::default()
+                },
+                WhitelistPatternConfig {
+                    regex: Some(r"(?i)flaky\s+error \d+".to_string()),
+                    query_contains: Some("JOIN".to_string()),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        let whitelist = ErrorWhitelist::from_config(&config).unwrap();
+
+        // Built-in pattern is still there
+        assert!(whitelist.is_whitelisted("Arrow error: Divide by zero error", None));
+        assert!(whitelist.is_whitelisted("Execution error: my known issue", None));
+        assert!(whitelist.is_whitelisted("FLAKY  error 42", Some("SELECT * FROM t0 JOIN t1")));
+        assert!(!whitelist.is_whitelisted("FLAKY  error 42", Some("SELECT 1")));
+        assert!(!whitelist.is_whitelisted("Unexpected segmentation fault", None));
+    }
+
+    #[test]
+    fn builtin_patterns_can_be_disabled() {
+        let config = WhitelistConfig {
+            use_builtin: false,
+            ..Default::default()
+        };
+        let whitelist = ErrorWhitelist::from_config(&config).unwrap();
+
+        assert!(!whitelist.is_whitelisted("Arrow error: Divide by zero error", None));
+    }
+
+    #[test]
+    fn rejects_invalid_config_patterns() {
+        let invalid_regex = WhitelistConfig {
+            patterns: vec![WhitelistPatternConfig {
+                regex: Some("(unclosed".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        assert!(ErrorWhitelist::from_config(&invalid_regex).is_err());
+
+        let no_matcher = WhitelistConfig {
+            patterns: vec![WhitelistPatternConfig {
+                query_contains: Some("JOIN".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        assert!(ErrorWhitelist::from_config(&no_matcher).is_err());
+    }
+
+    #[test]
+    fn reports_hits_unused_and_expired_patterns() {
+        let config = WhitelistConfig {
+            use_builtin: false,
+            patterns: vec![
+                WhitelistPatternConfig {
+                    contains: Some("frequent".to_string()),
+                    ..Default::default()
+                },
+                WhitelistPatternConfig {
+                    contains: Some("rare".to_string()),
+                    ..Default::default()
+                },
+                WhitelistPatternConfig {
+                    contains: Some("fixed upstream".to_string()),
+                    expires: Some("2025-06-01".to_string()),
+                    issue: Some("https://github.com/apache/datafusion/issues/1".to_string()),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        let whitelist = ErrorWhitelist::from_config(&config).unwrap();
+
+        for _ in 0..3 {
+            assert!(whitelist.is_whitelisted("a frequent error", None));
+        }
+        assert!(whitelist.is_whitelisted("a rare error", None));
+
+        let today = NaiveDate::from_ymd_opt(2025, 7, 1).unwrap();
+        let report = whitelist.hit_report(10, today);
+        assert_eq!(
+            report.top_matchers,
+            vec![
+                ("Exact: frequent".to_string(), 3),
+                ("Exact: rare".to_string(), 1)
+            ]
+        );
+        assert_eq!(report.unused.len(), 1);
+        assert!(report.unused[0].contains("fixed upstream"));
+        assert_eq!(report.expired, report.unused);
+
+        let before_expiry = NaiveDate::from_ymd_opt(2025, 5, 1).unwrap();
+        assert!(whitelist.expired_patterns(before_expiry).is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_expiry_date() {
+        let config = WhitelistConfig {
+            patterns: vec![WhitelistPatternConfig {
+                contains: Some("x".to_string()),
+                expires: Some("June 2025".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        assert!(ErrorWhitelist::from_config(&config).is_err());
+    }
 }
@@ -0,0 +1,176 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+/// Round currently being fuzzed (1-based), used to rotate the trace log per round
+static CURRENT_LOG_ROUND: AtomicU32 = AtomicU32::new(1);
+
+/// Tell round-rotated log writers that a new round started.
+pub fn set_log_round(round: u32) {
+    CURRENT_LOG_ROUND.store(round, Ordering::Relaxed);
+}
+
+/// How `trace.log` is split into multiple files during long campaigns.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogRotation {
+    /// A single `trace.log`
+    #[default]
+    Never,
+    /// One `trace.round-<N>.log` per fuzzing round
+    Round,
+    /// Start a new file (`trace.log.1`, `trace.log.2`, ...) once the current
+    /// one reaches `log_rotation_max_bytes`
+    Size,
+    /// `trace.log.<date>-<hour>`, rotated by tracing-appender
+    Hourly,
+    /// `trace.log.<date>`, rotated by tracing-appender
+    Daily,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum RotationTrigger {
+    Round,
+    Size(u64),
+}
+
+/// A `Write` implementation that switches to a new log file when the fuzzing
+/// round changes or the current file grows past a size limit.
+///
+/// Files are never renamed, so a file that is being inspected keeps its name.
+pub struct RotatingFileWriter {
+    dir: PathBuf,
+    file_name: String,
+    trigger: RotationTrigger,
+    file: File,
+    /// Round number for `Round`, segment number for `Size`
+    segment: u32,
+    bytes_written: u64,
+}
+
+impl RotatingFileWriter {
+    pub fn per_round(dir: &Path, file_name: &str) -> io::Result<Self> {
+        Self::new(dir, file_name, RotationTrigger::Round)
+    }
+
+    pub fn by_size(dir: &Path, file_name: &str, max_bytes: u64) -> io::Result<Self> {
+        Self::new(dir, file_name, RotationTrigger::Size(max_bytes.max(1)))
+    }
+
+    fn new(dir: &Path, file_name: &str, trigger: RotationTrigger) -> io::Result<Self> {
+        let segment = match trigger {
+            RotationTrigger::Round => CURRENT_LOG_ROUND.load(Ordering::Relaxed),
+            RotationTrigger::Size(_) => 0,
+        };
+        let path = segment_path(dir, file_name, trigger, segment);
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            file_name: file_name.to_string(),
+            trigger,
+            file: open_append(&path)?,
+            segment,
+            bytes_written: 0,
+        })
+    }
+
+    fn rotate_if_needed(&mut self) -> io::Result<()> {
+        let next_segment = match self.trigger {
+            RotationTrigger::Round => {
+                let round = CURRENT_LOG_ROUND.load(Ordering::Relaxed);
+                if round == self.segment {
+                    return Ok(());
+                }
+                round
+            }
+            RotationTrigger::Size(max_bytes) => {
+                if self.bytes_written < max_bytes {
+                    return Ok(());
+                }
+                self.segment + 1
+            }
+        };
+
+        self.file.flush()?;
+        let path = segment_path(&self.dir, &self.file_name, self.trigger, next_segment);
+        self.file = open_append(&path)?;
+        self.segment = next_segment;
+        self.bytes_written = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.rotate_if_needed()?;
+        let written = self.file.write(buf)?;
+        self.bytes_written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+fn segment_path(dir: &Path, file_name: &str, trigger: RotationTrigger, segment: u32) -> PathBuf {
+    match trigger {
+        RotationTrigger::Round => {
+            let path = Path::new(file_name);
+            let stem = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or(file_name);
+            match path.extension().and_then(|e| e.to_str()) {
+                Some(extension) => dir.join(format!("{}.round-{}.{}", stem, segment, extension)),
+                None => dir.join(format!("{}.round-{}", stem, segment)),
+            }
+        }
+        RotationTrigger::Size(_) if segment == 0 => dir.join(file_name),
+        RotationTrigger::Size(_) => dir.join(format!("{}.{}", file_name, segment)),
+    }
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn size_rotation_starts_new_segments() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut writer = RotatingFileWriter::by_size(dir.path(), "trace.log", 10).unwrap();
+
+        writer.write_all(b"0123456789").unwrap();
+        writer.write_all(b"abc").unwrap();
+        writer.flush().unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("trace.log")).unwrap(),
+            "0123456789"
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("trace.log.1")).unwrap(),
+            "abc"
+        );
+    }
+
+    #[test]
+    fn round_segment_names_keep_extension() {
+        let dir = Path::new("logs");
+        assert_eq!(
+            segment_path(dir, "trace.log", RotationTrigger::Round, 3),
+            dir.join("trace.round-3.log")
+        );
+        assert_eq!(
+            segment_path(dir, "trace", RotationTrigger::Round, 3),
+            dir.join("trace.round-3")
+        );
+    }
+}
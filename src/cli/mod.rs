@@ -1,22 +1,81 @@
+pub mod datafusion_cli;
 pub mod error_whitelist;
+pub mod log_rotation;
+pub mod panic_hook;
+pub mod plan_log;
+mod progress;
+pub mod reduce;
+pub mod replay;
+pub mod report;
 mod runner;
+pub mod summarize;
 mod tui;
 
-use clap::Parser;
-pub use runner::run_fuzzer;
+use clap::{Args, Parser, Subcommand};
+pub use log_rotation::{LogRotation, RotatingFileWriter, set_log_round};
+pub use panic_hook::install_panic_hook;
+pub use progress::spawn_progress_reporter;
+pub use runner::{prepare_round, replay_oracle_test, run_fuzzer};
 use std::path::PathBuf;
+
+use crate::datasource_generator::file_dataset::{DatasetFormat, DatasetStore};
+use crate::engine::ReferenceEngine;
+use crate::fuzz_context::adversarial_batches::AdversarialBatches;
+use crate::fuzz_context::profile::Profile;
+use crate::fuzz_runner::query_id::QueryId;
+use crate::oracle::ConfiguredOracle;
+use summarize::SummaryFormat;
 pub use tui::{TuiApp, init, restore};
 
 #[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
+#[command(author, version, about, long_about = None, args_conflicts_with_subcommands = true)]
 pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Arguments of `run`, which is the default when no subcommand is given
+    #[command(flatten)]
+    pub run: RunArgs,
+}
+
+impl Cli {
+    /// The selected mode, `run` if no subcommand was given
+    pub fn into_command(self) -> Command {
+        self.command.unwrap_or(Command::Run(self.run))
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Run a fuzzing campaign (default)
+    Run(RunArgs),
+    /// Re-run a single oracle test by its query ID
+    Replay(ReplayArgs),
+    /// Shrink the query of an error finding while it keeps failing the same way
+    Reduce(ReduceArgs),
+    /// Summarize a findings file
+    Report(ReportArgs),
+    /// Write a Markdown or HTML summary of a campaign's log directory, e.g. for a nightly job
+    Summarize(SummarizeArgs),
+    /// Re-execute the statements recorded in a query log
+    Corpus(CorpusArgs),
+    /// Report statements of a query log whose optimized plan changed since recorded plans
+    PlanDiff(PlanDiffArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct RunArgs {
     /// Path to config file
     #[arg(short, long, value_name = "FILE")]
     pub config: Option<PathBuf>,
 
-    /// Random seed
-    #[arg(short, long, default_value_t = 310104)]
-    pub seed: u64,
+    /// Random seed, overrides the config file [default: 42]
+    #[arg(short, long)]
+    pub seed: Option<u64>,
+
+    /// Preset oracles and generation settings for a focused campaign (joins, aggregates, temporal, parquet), overrides the config file
+    #[arg(long, value_name = "NAME", value_parser = parse_profile)]
+    pub profile: Option<Profile>,
 
     /// Number of rounds to run
     #[arg(short, long)]
@@ -30,6 +89,118 @@ pub struct Cli {
     #[arg(short, long)]
     pub timeout: Option<u64>,
 
+    /// Time out queries after a multiple of the p99 latency of recent queries, capped at --timeout
+    #[arg(long)]
+    pub adaptive_timeout: bool,
+
+    /// Multiple of the recent p99 latency used as the adaptive timeout [default: 5]
+    #[arg(long, value_name = "F")]
+    pub adaptive_timeout_factor: Option<f64>,
+
+    /// Lower bound of the adaptive timeout in milliseconds [default: 100]
+    #[arg(long, value_name = "MS")]
+    pub adaptive_timeout_floor_ms: Option<u64>,
+
+    /// Run timed out queries once more in isolation before counting them as slow
+    #[arg(long)]
+    pub retry_timeouts: bool,
+
+    /// Time out query planning after MS milliseconds, reported as a planner hang
+    #[arg(long, value_name = "MS")]
+    pub plan_timeout_ms: Option<u64>,
+
+    /// Time out query execution after MS milliseconds once the query is planned
+    #[arg(long, value_name = "MS")]
+    pub execution_timeout_ms: Option<u64>,
+
+    /// Stop after this many seconds; with `--rounds 0` run until the time is up
+    #[arg(long, value_name = "SECS")]
+    pub max_duration_secs: Option<u64>,
+
+    /// Memory limit of the DataFusion memory pool in MiB
+    #[arg(long, value_name = "MB")]
+    pub max_query_memory_mb: Option<u64>,
+
+    /// Keep only the first N rows of query results, counting the rest
+    #[arg(long, value_name = "N")]
+    pub max_result_rows: Option<usize>,
+
+    /// Spool results past --max-result-rows to disk and compare them in full
+    #[arg(long)]
+    pub spool_results: bool,
+
+    /// Measure peak memory and CPU time per query and report the top consumers
+    #[arg(long)]
+    pub measure_resources: bool,
+
+    /// Report a memory leak once process memory grew in N consecutive rounds
+    #[arg(long, value_name = "N")]
+    pub memory_leak_rounds: Option<u32>,
+
+    /// Report queries taking longer than MS milliseconds to plan
+    #[arg(long, value_name = "MS")]
+    pub planning_time_threshold_ms: Option<u64>,
+
+    /// Make the MemoryLimit oracle's limited query spill to disk
+    #[arg(long)]
+    pub spill_fuzzing: bool,
+
+    /// Directory for spill files [default: run workspace]
+    #[arg(long, value_name = "DIR")]
+    pub spill_dir: Option<PathBuf>,
+
+    /// Split (split) or also shuffle (reorder) operator output batches [default: off]
+    #[arg(long, value_name = "MODE", value_parser = parse_adversarial_batches)]
+    pub adversarial_batches: Option<AdversarialBatches>,
+
+    /// Generate this many oracle tests ahead while the current one executes
+    #[arg(long, value_name = "N")]
+    pub pipeline_depth: Option<usize>,
+
+    /// Generate tables once and reuse them in every round
+    #[arg(long)]
+    pub reuse_datasets: bool,
+
+    /// Keep tables across rounds, dropping some and adding new ones each round
+    #[arg(long)]
+    pub schema_evolution: bool,
+
+    /// Enable a random half of the feature families in every round
+    #[arg(long)]
+    pub swarm_testing: bool,
+
+    /// Seed pool file to schedule the base seeds of rounds from and keep productive seeds in
+    #[arg(long, value_name = "FILE")]
+    pub seed_pool: Option<PathBuf>,
+
+    /// Where generated tables are stored (memory, parquet, csv) [default: memory]
+    #[arg(long, value_name = "FORMAT", value_parser = parse_dataset_format)]
+    pub dataset_format: Option<DatasetFormat>,
+
+    /// Directory for the files of file-backed tables [default: run workspace]
+    #[arg(long, value_name = "DIR")]
+    pub dataset_dir: Option<PathBuf>,
+
+    /// Where file-backed tables are read from (local, memory) [default: local]
+    #[arg(long, value_name = "STORE", value_parser = parse_dataset_store)]
+    pub dataset_store: Option<DatasetStore>,
+
+    /// Write Parquet tables clustered by a column in tiny row groups and pages
+    #[arg(long)]
+    pub parquet_pruning_stress: bool,
+
+    /// Keep the run workspace of generated files even if the run had no findings
+    #[arg(long)]
+    pub keep_artifacts: bool,
+
+    /// Flight SQL endpoint of a remote DataFusion instance for the FlightSql oracle
+    #[arg(long, value_name = "URL")]
+    pub flight_sql_endpoint: Option<String>,
+
+    /// Embedded databases for the EngineDiff oracle, comma separated (DuckDb,Sqlite)
+    #[arg(long, value_name = "ENGINES", value_delimiter = ',', value_parser = parse_reference_engine)]
+    pub reference_engines: Option<Vec<ReferenceEngine>>,
+
     /// Path to log file
     #[arg(short, long)]
     pub log_path: Option<PathBuf>,
@@ -41,4 +212,330 @@ pub struct Cli {
     /// Enable TUI display
     #[arg(long)]
     pub enable_tui: bool,
+
+    /// Sample interval for the query shown in the TUI, in seconds
+    #[arg(long, value_name = "SECS")]
+    pub sample_interval_secs: Option<u64>,
+
+    /// Interval of the stderr progress line when the TUI is disabled, 0 to turn it off
+    #[arg(long, value_name = "SECS")]
+    pub progress_interval_secs: Option<u64>,
+
+    /// Maximum number of columns per generated table
+    #[arg(long, value_name = "N")]
+    pub max_column_count: Option<u64>,
+
+    /// Maximum number of rows per generated table
+    #[arg(long, value_name = "N")]
+    pub max_row_count: Option<u64>,
+
+    /// Maximum nesting level of generated expressions
+    #[arg(long, value_name = "N")]
+    pub max_expr_level: Option<u32>,
+
+    /// Maximum number of GROUP BY expressions
+    #[arg(long, value_name = "N")]
+    pub max_group_by_count: Option<u32>,
+
+    /// Maximum number of tables joined in one query
+    #[arg(long, value_name = "N")]
+    pub max_table_count: Option<u32>,
+
+    /// Maximum number of INSERT statements per generated table
+    #[arg(long, value_name = "N")]
+    pub max_insert_per_table: Option<u32>,
+
+    /// Maximum worst-case number of rows of the joins in one query [default: unlimited]
+    #[arg(long, value_name = "ROWS")]
+    pub join_row_budget: Option<u64>,
+
+    /// Nesting depth of the deepest expressions and subqueries of the DeepNesting oracle
+    #[arg(long, value_name = "N")]
+    pub max_nesting_depth: Option<u32>,
+
+    /// Number of expressions of the widest SELECT list of the WideProjection oracle
+    #[arg(long, value_name = "N")]
+    pub max_projection_width: Option<u32>,
+
+    /// Number of keys of the widest GROUP BY list of the WideProjection oracle
+    #[arg(long, value_name = "N")]
+    pub max_group_by_width: Option<u32>,
+
+    /// Probability of comparing a column with a value present in it instead of a random literal
+    #[arg(long, value_name = "P")]
+    pub predicate_literal_bias: Option<f64>,
+
+    /// Give generated timestamps time zones with DST transitions, minute offsets and unusual names
+    #[arg(long)]
+    pub extended_timezones: bool,
+
+    /// Give some generated columns names that need quoting (case-only differences, spaces, keywords, Unicode)
+    #[arg(long)]
+    pub tricky_identifiers: bool,
+
+    /// Name some generated tables and columns after SQL keywords (e.g. "select", "order")
+    #[arg(long)]
+    pub keyword_identifiers: bool,
+
+    /// Create some tables in other schemas and catalogs, and refer to tables with random qualification
+    #[arg(long)]
+    pub multi_schema: bool,
+
+    /// Oracles to choose from, comma separated (e.g. NoCrash,TlpWhere)
+    #[arg(long, value_name = "ORACLES", value_delimiter = ',', value_parser = parse_oracle)]
+    pub oracles: Option<Vec<ConfiguredOracle>>,
+
+    /// Stop with a non-zero exit code at the first finding
+    #[arg(long)]
+    pub stop_on_first_finding: bool,
+
+    /// Stop with a non-zero exit code after this many findings
+    #[arg(long, value_name = "N")]
+    pub max_findings: Option<u64>,
+
+    /// TOML file mapping finding fingerprints to upstream issues, matching findings are reported as known
+    #[arg(long, value_name = "FILE")]
+    pub known_issues: Option<PathBuf>,
+
+    /// Findings file of a previous run, exit with a non-zero code only on findings missing from it
+    #[arg(long, value_name = "FILE")]
+    pub baseline: Option<PathBuf>,
+
+    /// Write the result sets of wrong-result findings as Arrow IPC files
+    #[arg(long)]
+    pub dump_mismatch_ipc: bool,
+
+    /// Append the EXPLAIN output of every statement to `plans.log` in the log directory
+    #[arg(long)]
+    pub record_plans: bool,
+
+    /// Check error findings in this `datafusion-cli` binary and record whether they reproduce
+    #[arg(long, value_name = "PATH")]
+    pub datafusion_cli: Option<PathBuf>,
+}
+
+/// Where the configuration of a replayed test comes from
+#[derive(Args, Debug)]
+#[group(required = true, multiple = false)]
+pub struct ReplaySource {
+    /// Findings file of a previous run, the configuration is taken from the finding
+    #[arg(short, long, value_name = "FILE")]
+    pub findings: Option<PathBuf>,
+
+    /// Config file of the run (e.g. `effective-config.toml` in its log directory)
+    #[arg(short, long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct ReplayArgs {
+    #[command(flatten)]
+    pub source: ReplaySource,
+
+    /// ID of the oracle test to replay, e.g. 310104-r2-q5
+    #[arg(short, long)]
+    pub query_id: QueryId,
+
+    /// Display logs
+    #[arg(short, long)]
+    pub display_logs: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ReduceArgs {
+    /// Findings file of a previous run
+    #[arg(short, long, value_name = "FILE")]
+    pub findings: PathBuf,
+
+    /// ID of the finding to reduce, e.g. 310104-r2-q5
+    #[arg(short, long)]
+    pub query_id: QueryId,
+
+    /// Maximum number of candidate queries to execute
+    #[arg(long, value_name = "N", default_value_t = 500)]
+    pub max_attempts: u32,
+
+    /// Directory to write `<query_id>.reduced.sql` to
+    #[arg(short, long, value_name = "DIR")]
+    pub output: Option<PathBuf>,
+
+    /// Display logs
+    #[arg(short, long)]
+    pub display_logs: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ReportArgs {
+    /// Findings file to summarize
+    #[arg(value_name = "FILE")]
+    pub findings: PathBuf,
+
+    /// Only list findings of this category
+    #[arg(long)]
+    pub category: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct SummarizeArgs {
+    /// Log directory of the campaign, with `findings.jsonl` and `stats.json`
+    #[arg(value_name = "DIR")]
+    pub dir: PathBuf,
+
+    /// Findings file of earlier campaigns, their findings are listed as known (repeatable)
+    #[arg(long, value_name = "FILE")]
+    pub known: Vec<PathBuf>,
+
+    /// Known issues file, replaces the upstream issues recorded in the findings
+    #[arg(long, value_name = "FILE")]
+    pub known_issues: Option<PathBuf>,
+
+    /// Output format: markdown or html
+    #[arg(long, value_name = "FORMAT", default_value = "markdown", value_parser = parse_summary_format)]
+    pub format: SummaryFormat,
+
+    /// Write the summary to this file instead of stdout
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct CorpusArgs {
+    /// Query log of a previous run (`queries.log`)
+    #[arg(value_name = "FILE")]
+    pub query_log: PathBuf,
+
+    /// Config file of the run, defaults to `effective-config.toml` next to the query log
+    #[arg(short, long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Write the EXPLAIN output of every replayed statement to this file
+    #[arg(long, value_name = "FILE")]
+    pub plans: Option<PathBuf>,
+
+    /// Display logs
+    #[arg(short, long)]
+    pub display_logs: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct PlanDiffArgs {
+    /// Query log of a previous run (`queries.log`)
+    #[arg(value_name = "FILE")]
+    pub query_log: PathBuf,
+
+    /// Plans recorded with `corpus --plans`
+    #[arg(long, value_name = "FILE")]
+    pub plans: PathBuf,
+
+    /// Config file of the run, defaults to `effective-config.toml` next to the query log
+    #[arg(short, long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Display logs
+    #[arg(short, long)]
+    pub display_logs: bool,
+}
+
+/// Parse an oracle name the same way as the `oracles` config entry
+fn parse_oracle(name: &str) -> std::result::Result<ConfiguredOracle, String> {
+    parse_config_name(name)
+}
+
+/// Parse an engine name the same way as the `reference_engines` config entry
+fn parse_reference_engine(name: &str) -> std::result::Result<ReferenceEngine, String> {
+    parse_config_name(name)
+}
+
+/// Parse a dataset format the same way as the `dataset_format` config entry
+fn parse_dataset_format(name: &str) -> std::result::Result<DatasetFormat, String> {
+    parse_config_name(name)
+}
+
+/// Parse a batch mode the same way as the `adversarial_batches` config entry
+fn parse_adversarial_batches(name: &str) -> std::result::Result<AdversarialBatches, String> {
+    parse_config_name(name)
+}
+
+/// Parse a profile name, in snake case like config entries
+fn parse_profile(name: &str) -> std::result::Result<Profile, String> {
+    parse_config_name(name)
+}
+
+/// Parse a summary format, in snake case like config entries
+fn parse_summary_format(name: &str) -> std::result::Result<SummaryFormat, String> {
+    parse_config_name(name)
+}
+
+/// Parse a dataset store the same way as the `dataset_store` config entry
+fn parse_dataset_store(name: &str) -> std::result::Result<DatasetStore, String> {
+    parse_config_name(name)
+}
+
+fn parse_config_name<T: serde::de::DeserializeOwned>(name: &str) -> std::result::Result<T, String> {
+    use serde::de::IntoDeserializer;
+    use serde::de::value::{Error, StrDeserializer};
+
+    let deserializer: StrDeserializer<'_, Error> = name.trim().into_deserializer();
+    T::deserialize(deserializer).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_is_the_default_command() {
+        let cli = Cli::parse_from(["datafusion-fuzzer", "--config", "fuzzer.toml", "-r", "3"]);
+        let Command::Run(args) = cli.into_command() else {
+            panic!("expected the run command");
+        };
+        assert_eq!(args.config, Some(PathBuf::from("fuzzer.toml")));
+        assert_eq!(args.rounds, Some(3));
+    }
+
+    #[test]
+    fn parses_subcommands() {
+        let cli = Cli::parse_from([
+            "datafusion-fuzzer",
+            "replay",
+            "--findings",
+            "logs/findings.jsonl",
+            "--query-id",
+            "310104-r2-q5",
+        ]);
+        let Command::Replay(args) = cli.into_command() else {
+            panic!("expected the replay command");
+        };
+        assert_eq!(args.query_id, QueryId::new(310104, 1, 4));
+        assert_eq!(
+            args.source.findings,
+            Some(PathBuf::from("logs/findings.jsonl"))
+        );
+
+        // A replay needs exactly one configuration source
+        assert!(
+            Cli::try_parse_from(["datafusion-fuzzer", "replay", "--query-id", "1-r1-q1"]).is_err()
+        );
+        assert!(Cli::try_parse_from(["datafusion-fuzzer", "replay", "--query-id", "bad"]).is_err());
+
+        let cli = Cli::parse_from(["datafusion-fuzzer", "report", "logs/findings.jsonl"]);
+        assert!(matches!(cli.into_command(), Command::Report(_)));
+
+        let cli = Cli::parse_from([
+            "datafusion-fuzzer",
+            "summarize",
+            "logs",
+            "--known",
+            "nightly-1/findings.jsonl",
+            "--known",
+            "nightly-2/findings.jsonl",
+            "--format",
+            "html",
+        ]);
+        let Command::Summarize(args) = cli.into_command() else {
+            panic!("expected the summarize command");
+        };
+        assert_eq!(args.known.len(), 2);
+        assert_eq!(args.format, SummaryFormat::Html);
+    }
 }
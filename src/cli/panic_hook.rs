@@ -0,0 +1,140 @@
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::panic::{self, PanicHookInfo};
+use std::sync::Mutex;
+
+use tracing::error;
+
+/// The most recent panic on a worker thread, waiting to be attached to a finding
+static LAST_PANIC: Mutex<Option<CapturedPanic>> = Mutex::new(None);
+
+/// Name of the threads tokio runs spawned tasks (and so fuzzed queries) on
+const WORKER_THREAD_NAME: &str = "tokio-runtime-worker";
+
+/// A panic recorded by the fuzzer's panic hook
+#[derive(Debug, Clone)]
+pub struct CapturedPanic {
+    pub message: String,
+    /// `file:line:column` of the panic, if known
+    pub location: Option<String>,
+    pub thread: String,
+    pub backtrace: String,
+}
+
+impl CapturedPanic {
+    /// Error message of a query that panicked, classified as a `Panic` finding
+    pub fn error_message(&self) -> String {
+        match &self.location {
+            Some(location) => format!("Query task panicked at {}: {}", location, self.message),
+            None => format!("Query task panicked: {}", self.message),
+        }
+    }
+
+    /// Details for the finding report
+    pub fn report(&self) -> String {
+        format!(
+            "Panic in thread '{}': {}\nLocation: {}\n\nBacktrace:\n{}",
+            self.thread,
+            self.message,
+            self.location.as_deref().unwrap_or("unknown"),
+            self.backtrace
+        )
+    }
+}
+
+/// Install a panic hook that records panics with their backtrace, so a panic
+/// inside DataFusion becomes a finding with the details needed to fix it.
+///
+/// Panics on tokio worker threads are fuzzed queries failing: they are logged
+/// instead of printed, which would garble the TUI. Panics anywhere else are
+/// fuzzer bugs and still go through the previous hook.
+pub fn install_panic_hook() {
+    let previous_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let captured = capture(info);
+        let on_worker_thread = captured.thread == WORKER_THREAD_NAME;
+        if on_worker_thread {
+            error!("{}", captured.error_message());
+        }
+        *LAST_PANIC.lock().unwrap_or_else(|e| e.into_inner()) = Some(captured);
+
+        if !on_worker_thread {
+            previous_hook(info);
+        }
+    }));
+}
+
+/// Take the most recently recorded panic
+pub fn take_captured_panic() -> Option<CapturedPanic> {
+    LAST_PANIC.lock().unwrap_or_else(|e| e.into_inner()).take()
+}
+
+/// Message of a panic payload, which is a `&str` or `String` for `panic!`
+pub fn panic_payload_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "non-string panic payload".to_string()
+    }
+}
+
+fn capture(info: &PanicHookInfo<'_>) -> CapturedPanic {
+    CapturedPanic {
+        message: panic_payload_message(info.payload()),
+        location: info.location().map(|location| {
+            format!(
+                "{}:{}:{}",
+                location.file(),
+                location.line(),
+                location.column()
+            )
+        }),
+        thread: std::thread::current()
+            .name()
+            .unwrap_or("<unnamed>")
+            .to_string(),
+        backtrace: Backtrace::force_capture().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_captured_panics() {
+        let captured = CapturedPanic {
+            message: "boom 42".to_string(),
+            location: Some("src/lib.rs:1:2".to_string()),
+            thread: WORKER_THREAD_NAME.to_string(),
+            backtrace: "0: frame".to_string(),
+        };
+        assert_eq!(
+            captured.error_message(),
+            "Query task panicked at src/lib.rs:1:2: boom 42"
+        );
+        assert_eq!(
+            captured.report(),
+            "Panic in thread 'tokio-runtime-worker': boom 42\nLocation: src/lib.rs:1:2\n\nBacktrace:\n0: frame"
+        );
+
+        let unlocated = CapturedPanic {
+            location: None,
+            ..captured
+        };
+        assert_eq!(unlocated.error_message(), "Query task panicked: boom 42");
+        assert!(unlocated.report().contains("Location: unknown"));
+    }
+
+    #[test]
+    fn reads_panic_payload_messages() {
+        let payload: Box<dyn Any + Send> = Box::new("boom");
+        assert_eq!(panic_payload_message(&*payload), "boom");
+        let payload: Box<dyn Any + Send> = Box::new(format!("boom {}", 42));
+        assert_eq!(panic_payload_message(&*payload), "boom 42");
+        let payload: Box<dyn Any + Send> = Box::new(42);
+        assert_eq!(panic_payload_message(&*payload), "non-string panic payload");
+    }
+}
@@ -0,0 +1,240 @@
+//! Plans of the executed statements, for diffing DataFusion versions.
+//!
+//! With `record_plans` every statement of a run is explained with `EXPLAIN
+//! FORMAT indent` and the logical and physical plans are appended to
+//! `plans.log` in the log directory, under the query IDs of `queries.log`.
+//! Runs with the same seed generate the same statements, so diffing the plan
+//! logs of two DataFusion versions shows plan changes even when the results
+//! match. `corpus --plans` writes the same file while replaying a query log,
+//! and `plan-diff` compares the optimized logical plans of such a file with
+//! the plans of the current DataFusion.
+//!
+//! Physical plans depend on `target_partitions`, which defaults to the number
+//! of cores, and name the files of file-backed tables, so only compare logs
+//! written on the same machine with the same `dataset_dir`.
+
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+
+use datafusion::arrow::array::AsArray;
+use datafusion::prelude::SessionContext;
+
+use crate::common::{Result, fuzzer_err};
+use crate::fuzz_runner::query_id::QueryId;
+
+/// Name of the plan log in the log directory
+pub const PLAN_LOG_FILE: &str = "plans.log";
+
+/// `EXPLAIN FORMAT indent` output of `sql`, one `<plan_type>:` section per
+/// plan, or why it can't be explained
+pub async fn explain_indent(session_context: &SessionContext, sql: &str) -> String {
+    let explained = match session_context
+        .sql(&format!("EXPLAIN FORMAT indent {}", sql))
+        .await
+    {
+        Ok(df) => df.collect().await,
+        Err(e) => Err(e),
+    };
+    let batches = match explained {
+        Ok(batches) => batches,
+        Err(e) => return format!("EXPLAIN failed: {}\n", e),
+    };
+
+    let mut output = String::new();
+    for batch in &batches {
+        let plan_types = batch.column(0).as_string::<i32>();
+        let plans = batch.column(1).as_string::<i32>();
+        for row in 0..batch.num_rows() {
+            output.push_str(&format!(
+                "{}:\n{}\n",
+                plan_types.value(row),
+                plans.value(row)
+            ));
+        }
+    }
+    output
+}
+
+/// Append the plans of the statements of one oracle test to `path`. Statements
+/// without a session (run on another engine) are listed without a plan.
+pub async fn append_plans(
+    path: &Path,
+    query_id: QueryId,
+    statements: &[(&str, Option<&SessionContext>)],
+) -> Result<()> {
+    let mut log = format!("=== id={} ===\n", query_id);
+    for (statement_index, (sql, session_context)) in statements.iter().enumerate() {
+        log.push_str(&format!("--- statement={} ---\n", statement_index + 1));
+        match session_context {
+            Some(session_context) => log.push_str(&explain_indent(session_context, sql).await),
+            None => log.push_str("Not executed in the embedded session\n"),
+        }
+        log.push('\n');
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| {
+            fuzzer_err(&format!(
+                "Failed to open plan log '{}': {}",
+                path.display(),
+                e
+            ))
+        })?;
+    file.write_all(log.as_bytes())?;
+    Ok(())
+}
+
+/// Parse a plan log into the `EXPLAIN` output of each statement, by statement
+/// ID (e.g. `42-r1-q2-s1`)
+pub fn parse_plan_log(content: &str) -> Result<BTreeMap<String, String>> {
+    let mut plans = BTreeMap::new();
+    let mut query_id: Option<QueryId> = None;
+    let mut current: Option<(String, Vec<&str>)> = None;
+
+    let mut finish = |current: Option<(String, Vec<&str>)>| {
+        if let Some((statement_id, lines)) = current {
+            plans.insert(statement_id, lines.join("\n").trim_end().to_string());
+        }
+    };
+    for line in content.lines() {
+        if let Some(header) = line.strip_prefix("=== id=") {
+            finish(current.take());
+            let id = header.trim_end_matches(" ===").trim();
+            query_id = Some(id.parse()?);
+        } else if let Some(header) = line.strip_prefix("--- statement=") {
+            finish(current.take());
+            let query_id = query_id.ok_or_else(|| {
+                fuzzer_err(&format!("Plan log statement without a test: {}", line))
+            })?;
+            let index: usize = header
+                .trim_end_matches(" ---")
+                .trim()
+                .parse()
+                .map_err(|_| fuzzer_err(&format!("Invalid plan log statement header: {}", line)))?;
+            current = Some((query_id.statement_id(index), Vec::new()));
+        } else if let Some((_, lines)) = current.as_mut() {
+            lines.push(line);
+        }
+    }
+    finish(current.take());
+
+    Ok(plans)
+}
+
+/// The optimized logical plan in the output of [`explain_indent`], `None` if
+/// the statement couldn't be explained
+pub fn logical_plan(explained: &str) -> Option<String> {
+    let mut lines = explained
+        .lines()
+        .skip_while(|line| *line != "logical_plan:");
+    lines.next()?;
+    let plan: Vec<&str> = lines
+        .take_while(|line| !is_plan_type_header(line))
+        .collect();
+    Some(plan.join("\n").trim_end().to_string())
+}
+
+/// Whether `line` starts a plan section, like `physical_plan:`
+fn is_plan_type_header(line: &str) -> bool {
+    line.strip_suffix(':').is_some_and(|plan_type| {
+        !plan_type.is_empty()
+            && plan_type
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+    })
+}
+
+/// Line diff of two plans: unchanged lines prefixed with two spaces, removed
+/// lines with `- ` and added ones with `+ `
+pub fn diff_lines(old: &str, new: &str) -> String {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // Longest common subsequence of the suffixes, plans are short
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            diff.push_str(&format!("  {}\n", old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            diff.push_str(&format!("- {}\n", old[i]));
+            i += 1;
+        } else {
+            diff.push_str(&format!("+ {}\n", new[j]));
+            j += 1;
+        }
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn appends_logical_and_physical_plans() {
+        let session_context = SessionContext::new();
+        session_context.sql("CREATE TABLE t1(a INT)").await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("plans.log");
+
+        let query_id = QueryId::new(7, 0, 1);
+        append_plans(
+            &path,
+            query_id,
+            &[
+                ("SELECT a FROM t1 WHERE a > 1", Some(&session_context)),
+                ("SELECT nope FROM t1", Some(&session_context)),
+                ("SELECT 1", None),
+            ],
+        )
+        .await
+        .unwrap();
+
+        let log = std::fs::read_to_string(&path).unwrap();
+        assert!(log.starts_with("=== id=7-r1-q2 ===\n--- statement=1 ---\n"));
+        assert!(log.contains("logical_plan:\n"));
+        assert!(log.contains("physical_plan:\n"));
+        assert!(log.contains("--- statement=2 ---\nEXPLAIN failed: "));
+        assert!(log.contains("--- statement=3 ---\nNot executed in the embedded session\n"));
+
+        let plans = parse_plan_log(&log).unwrap();
+        assert_eq!(plans.len(), 3);
+        let plan = logical_plan(&plans["7-r1-q2-s1"]).unwrap();
+        assert!(plan.contains("Filter: t1.a > Int32(1)"));
+        assert!(!plan.contains("physical_plan"));
+        assert_eq!(logical_plan(&plans["7-r1-q2-s2"]), None);
+    }
+
+    #[test]
+    fn diffs_plan_lines() {
+        let old = "Projection: t1.a\n  Filter: t1.a > Int32(1)\n    TableScan: t1";
+        let new = "Projection: t1.a\n  TableScan: t1, full_filters=[t1.a > Int32(1)]";
+        let expected = [
+            "  Projection: t1.a",
+            "-   Filter: t1.a > Int32(1)",
+            "-     TableScan: t1",
+            "+   TableScan: t1, full_filters=[t1.a > Int32(1)]",
+        ];
+        assert_eq!(diff_lines(old, new), expected.join("\n") + "\n");
+        assert_eq!(diff_lines(old, old).lines().count(), 3);
+    }
+}
@@ -0,0 +1,32 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+
+use crate::fuzz_context::GlobalContext;
+use crate::fuzz_runner::{format_progress_line, get_tui_stats};
+
+/// Periodically print a single-line progress summary to stderr.
+///
+/// Used in headless runs (TUI disabled, e.g. CI) so there is visible progress
+/// without turning on full log verbosity. The returned task runs until aborted.
+pub fn spawn_progress_reporter(ctx: Arc<GlobalContext>) -> JoinHandle<()> {
+    let interval = Duration::from_secs(ctx.runner_config.progress_interval_secs.max(1));
+    let total_oracle_tests =
+        ctx.runner_config.rounds as u64 * ctx.runner_config.queries_per_round as u64;
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        // The first tick completes immediately, skip it to avoid an empty report
+        ticker.tick().await;
+
+        loop {
+            ticker.tick().await;
+            let stats = get_tui_stats(&ctx.fuzzer_stats);
+            eprintln!(
+                "[progress] {}",
+                format_progress_line(&stats, total_oracle_tests)
+            );
+        }
+    })
+}
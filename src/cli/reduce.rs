@@ -0,0 +1,292 @@
+//! `reduce` subcommand: shrink the query of an error finding while it keeps
+//! failing with the same kind of error.
+//!
+//! The reducer works on the parsed statement and greedily applies the first
+//! smaller variant that still fails: dropping ORDER BY, DISTINCT, WHERE,
+//! HAVING, GROUP BY expressions, projection items and joined tables, and
+//! replacing an expression by one of its operands.
+
+use std::sync::Arc;
+
+use datafusion::sql::sqlparser::ast::{Expr, GroupByExpr, Query, Select, SetExpr, Statement};
+use datafusion::sql::sqlparser::dialect::GenericDialect;
+use datafusion::sql::sqlparser::parser::Parser;
+use tracing::info;
+
+use crate::cli::runner::{execute_sql, prepare_round};
+use crate::common::{Result, fuzzer_err};
+use crate::fuzz_context::GlobalContext;
+use crate::fuzz_runner::findings::Finding;
+use crate::triage::{FailureCategory, classify_error};
+
+/// Reduce the query of `finding` in the tables of its round, executing at most
+/// `max_attempts` candidate queries. Returns the reduced SQL.
+pub async fn reduce_finding(
+    ctx: &Arc<GlobalContext>,
+    finding: &Finding,
+    max_attempts: u32,
+) -> Result<String> {
+    let [sql] = finding.queries.as_slice() else {
+        return Err(fuzzer_err(&format!(
+            "Only findings of a single failing query can be reduced, {} ({}) has {} queries",
+            finding.query_id,
+            finding.category,
+            finding.queries.len()
+        )));
+    };
+
+    prepare_round(ctx, finding.query_id.round).await?;
+
+    let Some(expected) = failure_signature(ctx, sql).await else {
+        return Err(fuzzer_err(&format!(
+            "Finding {} does not reproduce, the query no longer fails",
+            finding.query_id
+        )));
+    };
+    info!(
+        "Reducing {} ({}), original query has {} characters",
+        finding.query_id,
+        expected.0,
+        sql.len()
+    );
+
+    let mut current = parse_query(sql)?;
+    let mut current_sql = current.to_string();
+    let mut attempts = 0;
+
+    'reduce: loop {
+        for candidate in query_candidates(&current) {
+            let candidate_sql = candidate.to_string();
+            if candidate_sql.len() >= current_sql.len() {
+                continue;
+            }
+            if attempts >= max_attempts {
+                info!("Reached the maximum of {} attempts", max_attempts);
+                break 'reduce;
+            }
+
+            attempts += 1;
+            if failure_signature(ctx, &candidate_sql).await.as_ref() == Some(&expected) {
+                info!("Reduced to {} characters", candidate_sql.len());
+                current = candidate;
+                current_sql = candidate_sql;
+                continue 'reduce;
+            }
+        }
+        break;
+    }
+
+    Ok(current_sql)
+}
+
+/// Category and message shape of a non-whitelisted failure, `None` if the
+/// query succeeds or fails with a whitelisted error
+async fn failure_signature(
+    ctx: &Arc<GlobalContext>,
+    sql: &str,
+) -> Option<(FailureCategory, String)> {
+    let error_msg = execute_sql(ctx, sql).await.err()?.to_string();
+    if ctx.error_whitelist.is_whitelisted(&error_msg, Some(sql)) {
+        return None;
+    }
+
+    Some((classify_error(&error_msg), error_signature(&error_msg)))
+}
+
+/// First line of an error message without numbers, so the same failure on a
+/// smaller input (different row counts, column indices) still matches
+fn error_signature(error_msg: &str) -> String {
+    error_msg
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| !c.is_ascii_digit())
+        .collect()
+}
+
+fn parse_query(sql: &str) -> Result<Query> {
+    let statements = Parser::parse_sql(&GenericDialect {}, sql)
+        .map_err(|e| fuzzer_err(&format!("Failed to parse query to reduce: {}", e)))?;
+
+    match <[Statement; 1]>::try_from(statements) {
+        Ok([Statement::Query(query)]) => Ok(*query),
+        _ => Err(fuzzer_err("Only a single SELECT statement can be reduced")),
+    }
+}
+
+/// Smaller variants of a query, each differing in a single place
+fn query_candidates(query: &Query) -> Vec<Query> {
+    let mut candidates = Vec::new();
+
+    if query.order_by.is_some() {
+        let mut candidate = query.clone();
+        candidate.order_by = None;
+        candidates.push(candidate);
+    }
+
+    let with_body = |body: SetExpr| {
+        let mut candidate = query.clone();
+        candidate.body = Box::new(body);
+        candidate
+    };
+
+    match query.body.as_ref() {
+        SetExpr::SetOperation { left, right, .. } => {
+            candidates.push(with_body(left.as_ref().clone()));
+            candidates.push(with_body(right.as_ref().clone()));
+        }
+        SetExpr::Query(inner) => candidates.push(inner.as_ref().clone()),
+        SetExpr::Select(select) => {
+            if select.distinct.is_some() {
+                candidates.push(with_select(query, select, |select| select.distinct = None));
+            }
+
+            if let Some(selection) = &select.selection {
+                candidates.push(with_select(query, select, |select| select.selection = None));
+                for expr in expr_candidates(selection) {
+                    candidates.push(with_select(query, select, |select| {
+                        select.selection = Some(expr)
+                    }));
+                }
+            }
+
+            if let Some(having) = &select.having {
+                candidates.push(with_select(query, select, |select| select.having = None));
+                for expr in expr_candidates(having) {
+                    candidates.push(with_select(query, select, |select| {
+                        select.having = Some(expr)
+                    }));
+                }
+            }
+
+            if let GroupByExpr::Expressions(exprs, _) = &select.group_by {
+                for index in 0..exprs.len() {
+                    candidates.push(with_select(query, select, |select| {
+                        if let GroupByExpr::Expressions(exprs, _) = &mut select.group_by {
+                            exprs.remove(index);
+                        }
+                    }));
+                }
+            }
+
+            if select.projection.len() > 1 {
+                for index in 0..select.projection.len() {
+                    candidates.push(with_select(query, select, |select| {
+                        select.projection.remove(index);
+                    }));
+                }
+            }
+
+            if select.from.len() > 1 {
+                for index in 0..select.from.len() {
+                    candidates.push(with_select(query, select, |select| {
+                        select.from.remove(index);
+                    }));
+                }
+            }
+        }
+        _ => {}
+    }
+
+    candidates
+}
+
+/// Copy of `query` with its SELECT replaced by an edited copy of `select`
+fn with_select(query: &Query, select: &Select, edit: impl FnOnce(&mut Select)) -> Query {
+    let mut select = select.clone();
+    edit(&mut select);
+
+    let mut candidate = query.clone();
+    candidate.body = Box::new(SetExpr::Select(Box::new(select)));
+    candidate
+}
+
+/// Variants of an expression with one sub-expression replaced by one of its
+/// operands
+fn expr_candidates(expr: &Expr) -> Vec<Expr> {
+    let mut candidates = Vec::new();
+
+    match expr {
+        Expr::BinaryOp { left, op, right } => {
+            candidates.push(left.as_ref().clone());
+            candidates.push(right.as_ref().clone());
+            candidates.extend(
+                expr_candidates(left)
+                    .into_iter()
+                    .map(|left| Expr::BinaryOp {
+                        left: Box::new(left),
+                        op: op.clone(),
+                        right: right.clone(),
+                    }),
+            );
+            candidates.extend(
+                expr_candidates(right)
+                    .into_iter()
+                    .map(|right| Expr::BinaryOp {
+                        left: left.clone(),
+                        op: op.clone(),
+                        right: Box::new(right),
+                    }),
+            );
+        }
+        Expr::Nested(inner) => {
+            candidates.push(inner.as_ref().clone());
+            candidates.extend(
+                expr_candidates(inner)
+                    .into_iter()
+                    .map(|inner| Expr::Nested(Box::new(inner))),
+            );
+        }
+        Expr::UnaryOp { expr: inner, .. }
+        | Expr::Cast { expr: inner, .. }
+        | Expr::IsNull(inner)
+        | Expr::IsNotNull(inner) => {
+            candidates.push(inner.as_ref().clone());
+            candidates.extend(expr_candidates(inner));
+        }
+        _ => {}
+    }
+
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate_sqls(sql: &str) -> Vec<String> {
+        query_candidates(&parse_query(sql).unwrap())
+            .iter()
+            .map(|candidate| candidate.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn candidates_drop_clauses_and_operands() {
+        let candidates = candidate_sqls("SELECT a, b FROM t0 WHERE (a + 1) > b ORDER BY a");
+
+        for expected in [
+            "SELECT a, b FROM t0 WHERE (a + 1) > b",
+            "SELECT a, b FROM t0 ORDER BY a",
+            "SELECT a, b FROM t0 WHERE (a + 1) ORDER BY a",
+            "SELECT a, b FROM t0 WHERE a + 1 > b ORDER BY a",
+            "SELECT a, b FROM t0 WHERE (a) > b ORDER BY a",
+            "SELECT b FROM t0 WHERE (a + 1) > b ORDER BY a",
+        ] {
+            assert!(
+                candidates.iter().any(|candidate| candidate == expected),
+                "missing {expected} in {candidates:#?}"
+            );
+        }
+    }
+
+    #[test]
+    fn error_signature_ignores_numbers() {
+        assert_eq!(
+            error_signature("Arrow error: index 17 out of bounds: 12\nbacktrace"),
+            error_signature("Arrow error: index 3 out of bounds: 2")
+        );
+        assert!(parse_query("INSERT INTO t0 VALUES (1)").is_err());
+    }
+}
@@ -0,0 +1,345 @@
+//! Offline modes that re-run parts of a previous run: `replay` of a single
+//! oracle test, `corpus` replay of a whole query log and `plan-diff` of the
+//! plans of a query log.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use datafusion::prelude::SessionContext;
+use tracing::{error, info};
+
+use crate::cli::ReplaySource;
+use crate::cli::plan_log::{append_plans, diff_lines, explain_indent, logical_plan};
+use crate::cli::runner::{execute_sql, prepare_round};
+use crate::common::{Result, fuzzer_err};
+use crate::engine::connect_engines;
+use crate::fuzz_context::{GlobalContext, RunnerConfig, RuntimeContext};
+use crate::fuzz_runner::create_fuzzer_stats_with_timeout;
+use crate::fuzz_runner::findings::find_finding;
+use crate::fuzz_runner::query_id::QueryId;
+use crate::triage::classify_error;
+
+/// Configuration of the run that produced `query_id`, either embedded in its
+/// finding or read from a config file
+pub fn load_replay_config(source: &ReplaySource, query_id: QueryId) -> Result<RunnerConfig> {
+    match (&source.findings, &source.config) {
+        (Some(findings_path), _) => {
+            let finding = find_finding(findings_path, query_id)?;
+            if finding.config.is_empty() {
+                return Err(fuzzer_err(&format!(
+                    "Finding {} has no embedded config, pass --config instead",
+                    query_id
+                )));
+            }
+            RunnerConfig::from_toml_str(&finding.config)
+        }
+        (None, Some(config_path)) => RunnerConfig::from_file(config_path),
+        (None, None) => Err(fuzzer_err("Either --findings or --config is required")),
+    }
+}
+
+/// Adjust the config of a previous run for re-running some of its tests: no
+/// files are written to its log directory and the run is never stopped early
+pub fn offline_config(mut config: RunnerConfig, display_logs: bool) -> RunnerConfig {
+    config.log_path = None;
+    config.display_logs = display_logs;
+    config.enable_tui = false;
+    config.max_duration_secs = None;
+    config.fatal_categories = Vec::new();
+    config.stop_on_first_finding = false;
+    config.max_findings = None;
+    config
+}
+
+/// Global context for an offline mode, connected to the engines of the run
+pub async fn offline_context(config: RunnerConfig) -> Result<Arc<GlobalContext>> {
+    let engines = connect_engines(&config).await?;
+    let fuzzer_stats = create_fuzzer_stats_with_timeout(config.rounds, config.timeout_seconds);
+    Ok(Arc::new(
+        GlobalContext::new(config, RuntimeContext::default(), fuzzer_stats).with_engines(engines),
+    ))
+}
+
+/// An oracle test recorded in `queries.log`
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedTest {
+    pub query_id: QueryId,
+    pub statements: Vec<String>,
+}
+
+/// Parse the tests recorded in a `queries.log`
+pub fn parse_query_log(content: &str) -> Result<Vec<RecordedTest>> {
+    let mut tests: Vec<RecordedTest> = Vec::new();
+    let mut current_statement: Option<Vec<&str>> = None;
+
+    for line in content.lines() {
+        if let Some(header) = line.strip_prefix("=== id=") {
+            finish_statement(&mut tests, current_statement.take());
+            let query_id = header
+                .split_whitespace()
+                .next()
+                .ok_or_else(|| fuzzer_err(&format!("Invalid query log header: {}", line)))?
+                .parse()?;
+            tests.push(RecordedTest {
+                query_id,
+                statements: Vec::new(),
+            });
+        } else if line.starts_with("--- statement=") {
+            finish_statement(&mut tests, current_statement.take());
+            current_statement = Some(Vec::new());
+        } else if let Some(lines) = current_statement.as_mut() {
+            lines.push(line);
+        }
+    }
+    finish_statement(&mut tests, current_statement.take());
+
+    Ok(tests)
+}
+
+/// Add the statement collected so far to the last test
+fn finish_statement(tests: &mut [RecordedTest], statement: Option<Vec<&str>>) {
+    if let (Some(test), Some(lines)) = (tests.last_mut(), statement) {
+        let sql = lines.join("\n").trim().to_string();
+        if !sql.is_empty() {
+            test.statements.push(sql);
+        }
+    }
+}
+
+/// Config of the run that wrote a query log: `effective-config.toml` in the
+/// same directory, unless given explicitly
+pub fn load_corpus_config(query_log: &Path, config_path: Option<&Path>) -> Result<RunnerConfig> {
+    match config_path {
+        Some(config_path) => RunnerConfig::from_file(config_path),
+        None => {
+            let default_path = query_log
+                .parent()
+                .unwrap_or(Path::new("."))
+                .join("effective-config.toml");
+            if !default_path.exists() {
+                return Err(fuzzer_err(&format!(
+                    "'{}' not found, pass the config of the run with --config",
+                    default_path.display()
+                )));
+            }
+            RunnerConfig::from_file(&default_path)
+        }
+    }
+}
+
+/// Group recorded tests by round, checking they were generated with the seed
+/// of the config
+fn group_by_round<'a>(
+    ctx: &GlobalContext,
+    tests: &'a [RecordedTest],
+) -> Result<BTreeMap<u32, Vec<&'a RecordedTest>>> {
+    let mut tests_by_round: BTreeMap<u32, Vec<&RecordedTest>> = BTreeMap::new();
+    for test in tests {
+        if test.query_id.seed != ctx.runner_config.seed {
+            return Err(fuzzer_err(&format!(
+                "Query ID {} was generated with seed {}, but the config uses seed {}",
+                test.query_id, test.query_id.seed, ctx.runner_config.seed
+            )));
+        }
+        tests_by_round
+            .entry(test.query_id.round)
+            .or_default()
+            .push(test);
+    }
+    Ok(tests_by_round)
+}
+
+/// Re-execute every recorded statement against the tables of its round, and
+/// print the statements that now fail with a non-whitelisted error. With
+/// `plans`, the plans of the statements are written to that file.
+///
+/// Returns the number of such statements.
+pub async fn replay_corpus(
+    ctx: &Arc<GlobalContext>,
+    tests: &[RecordedTest],
+    plans: Option<&Path>,
+) -> Result<usize> {
+    let tests_by_round = group_by_round(ctx, tests)?;
+
+    if let Some(plans) = plans {
+        std::fs::write(plans, "")?;
+    }
+
+    let mut executed = 0;
+    let mut unexpected_errors = 0;
+    for (round, round_tests) in tests_by_round {
+        info!("Replaying {} tests of round {}", round_tests.len(), round);
+        prepare_round(ctx, round).await?;
+
+        for test in round_tests {
+            if let Some(plans) = plans {
+                let session_context = ctx.runtime_context.get_session_context();
+                let statements: Vec<(&str, Option<&SessionContext>)> = test
+                    .statements
+                    .iter()
+                    .map(|sql| (sql.as_str(), Some(session_context.as_ref())))
+                    .collect();
+                append_plans(plans, test.query_id, &statements).await?;
+            }
+
+            for (statement_index, sql) in test.statements.iter().enumerate() {
+                executed += 1;
+                let Err(e) = execute_sql(ctx, sql).await else {
+                    continue;
+                };
+
+                let error_msg = e.to_string();
+                if ctx.error_whitelist.is_whitelisted(&error_msg, Some(sql)) {
+                    continue;
+                }
+
+                unexpected_errors += 1;
+                let statement_id = test.query_id.statement_id(statement_index + 1);
+                error!(
+                    "Non-whitelisted error in {} ({}): {}",
+                    statement_id,
+                    classify_error(&error_msg),
+                    error_msg
+                );
+                println!(
+                    "❌ {} ({}): {}",
+                    statement_id,
+                    classify_error(&error_msg),
+                    error_msg
+                );
+                println!("{}\n", sql);
+            }
+        }
+    }
+
+    println!(
+        "Replayed {} statements of {} tests, {} non-whitelisted errors",
+        executed,
+        tests.len(),
+        unexpected_errors
+    );
+
+    Ok(unexpected_errors)
+}
+
+/// Explain every recorded statement against the tables of its round and print
+/// the statements whose optimized logical plan differs from `recorded`, the
+/// plans of a `corpus --plans` run by statement ID. Statements are executed
+/// after being explained like in [`replay_corpus`], so later statements see
+/// the same tables.
+///
+/// Returns the number of changed plans.
+pub async fn plan_diff(
+    ctx: &Arc<GlobalContext>,
+    tests: &[RecordedTest],
+    recorded: &BTreeMap<String, String>,
+) -> Result<usize> {
+    let tests_by_round = group_by_round(ctx, tests)?;
+
+    let mut compared = 0;
+    let mut unrecorded = 0;
+    let mut changed = 0;
+    for (round, round_tests) in tests_by_round {
+        info!("Explaining {} tests of round {}", round_tests.len(), round);
+        prepare_round(ctx, round).await?;
+
+        for test in round_tests {
+            let session_context = ctx.runtime_context.get_session_context();
+            for (statement_index, sql) in test.statements.iter().enumerate() {
+                let statement_id = test.query_id.statement_id(statement_index + 1);
+                let Some(recorded_explain) = recorded.get(&statement_id) else {
+                    unrecorded += 1;
+                    continue;
+                };
+
+                compared += 1;
+                let old_plan = logical_plan(recorded_explain);
+                let new_plan = logical_plan(&explain_indent(&session_context, sql).await);
+                if old_plan == new_plan {
+                    continue;
+                }
+
+                changed += 1;
+                info!("Plan of {} changed", statement_id);
+                println!("🔀 {}:", statement_id);
+                println!("{}\n", sql);
+                let describe =
+                    |plan: Option<String>| plan.unwrap_or_else(|| "<no logical plan>".to_string());
+                println!("{}", diff_lines(&describe(old_plan), &describe(new_plan)));
+            }
+
+            for sql in &test.statements {
+                let _ = execute_sql(ctx, sql).await;
+            }
+        }
+    }
+
+    println!(
+        "Compared {} plans of {} tests, {} changed, {} statements without a recorded plan",
+        compared,
+        tests.len(),
+        changed,
+        unrecorded
+    );
+
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_recorded_tests() {
+        let content = "\
+=== id=42-r1-q1 round=1 query=1 oracle=NoCrashOracle query_seed=242 ===
+--- statement=1 context=Random Query No-Crash Test ---
+SELECT 1
+FROM t0
+
+=== id=42-r2-q3 round=2 query=3 oracle=TlpWhereOracle query_seed=1244 ===
+--- statement=1 ---
+SELECT a FROM t1
+
+--- statement=2 ---
+SELECT a FROM t1 WHERE a
+
+";
+        let tests = parse_query_log(content).unwrap();
+
+        assert_eq!(
+            tests,
+            vec![
+                RecordedTest {
+                    query_id: QueryId::new(42, 0, 0),
+                    statements: vec!["SELECT 1\nFROM t0".to_string()],
+                },
+                RecordedTest {
+                    query_id: QueryId::new(42, 1, 2),
+                    statements: vec![
+                        "SELECT a FROM t1".to_string(),
+                        "SELECT a FROM t1 WHERE a".to_string()
+                    ],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn offline_config_never_writes_or_stops() {
+        let config = offline_config(
+            RunnerConfig {
+                log_path: Some("logs".into()),
+                enable_tui: true,
+                stop_on_first_finding: true,
+                ..RunnerConfig::default()
+            },
+            false,
+        );
+
+        assert_eq!(config.log_path, None);
+        assert!(!config.enable_tui);
+        assert!(!config.stop_on_first_finding);
+    }
+}
@@ -0,0 +1,210 @@
+//! `report` subcommand: a human readable summary of a `findings.jsonl`.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use crate::fuzz_runner::findings::Finding;
+use crate::fuzz_runner::known_issues::issue_label;
+
+/// Longest message shown in the findings list
+const MAX_MESSAGE_CHARS: usize = 120;
+
+/// Summarize findings by category, oracle, file format and compression of
+/// their tables, and known upstream issue, followed by one line per finding.
+///
+/// `category` restricts the list (not the summary) to one category, matched
+/// case-insensitively.
+pub fn format_findings_report(findings: &[Finding], category: Option<&str>) -> String {
+    let mut report = String::new();
+
+    let mut by_category: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut by_oracle: BTreeMap<&str, usize> = BTreeMap::new();
+    for finding in findings {
+        *by_category.entry(finding.category.as_str()).or_default() += 1;
+        *by_oracle.entry(finding.oracle.as_str()).or_default() += 1;
+    }
+
+    let _ = writeln!(report, "Findings: {}", findings.len());
+    if let Some(version) = findings.first().map(|f| f.datafusion_version.as_str())
+        && !version.is_empty()
+    {
+        let _ = writeln!(report, "DataFusion version: {}", version);
+    }
+
+    let _ = writeln!(report, "\nBy category:");
+    for (category, count) in &by_category {
+        let _ = writeln!(report, "  • {}: {}", category, count);
+    }
+
+    let _ = writeln!(report, "\nBy oracle:");
+    for (oracle, count) in &by_oracle {
+        let _ = writeln!(report, "  • {}: {}", oracle, count);
+    }
+
+    // A finding counts once for every file format and codec of its tables
+    let mut by_codec: BTreeMap<String, usize> = BTreeMap::new();
+    for finding in findings {
+        let codecs: BTreeSet<String> = finding
+            .table_files
+            .iter()
+            .map(|files| format!("{:?} {:?}", files.format, files.compression))
+            .collect();
+        for codec in codecs {
+            *by_codec.entry(codec).or_default() += 1;
+        }
+    }
+    if !by_codec.is_empty() {
+        let _ = writeln!(report, "\nBy file format and compression:");
+        for (codec, count) in &by_codec {
+            let _ = writeln!(report, "  • {}: {}", codec, count);
+        }
+    }
+
+    let mut by_known_issue: BTreeMap<String, usize> = BTreeMap::new();
+    for issue in findings.iter().filter_map(|f| f.known_issue.as_deref()) {
+        *by_known_issue.entry(issue_label(issue)).or_default() += 1;
+    }
+    if !by_known_issue.is_empty() {
+        let _ = writeln!(report, "\nBy known issue:");
+        for (issue, count) in &by_known_issue {
+            let _ = writeln!(report, "  • {}: {}", issue, count);
+        }
+    }
+
+    let _ = writeln!(report, "\nFindings:");
+    for finding in findings.iter().filter(|finding| {
+        category.is_none_or(|category| finding.category.as_str().eq_ignore_ascii_case(category))
+    }) {
+        let message = finding.message.lines().next().unwrap_or_default();
+        let message = match message.char_indices().nth(MAX_MESSAGE_CHARS) {
+            Some((end, _)) => format!("{}...", &message[..end]),
+            None => message.to_string(),
+        };
+        let cli_status = match finding.datafusion_cli_reproduced {
+            Some(true) => " (reproduces in datafusion-cli)",
+            Some(false) => " (does not reproduce in datafusion-cli)",
+            None => "",
+        };
+        let known_status = finding
+            .known_issue
+            .as_deref()
+            .map(|issue| format!(" - known (issue {})", issue_label(issue)))
+            .unwrap_or_default();
+        let _ = writeln!(
+            report,
+            "  {} [{}] {}: {}{}{}",
+            finding.query_id, finding.category, finding.oracle, message, cli_status, known_status
+        );
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::datasource_generator::file_dataset::{Compression, DatasetFormat};
+    use crate::fuzz_runner::findings::TableFiles;
+    use crate::fuzz_runner::query_id::QueryId;
+    use crate::triage::FailureCategory;
+
+    fn make_finding(index: u32, category: FailureCategory, oracle: &str) -> Finding {
+        Finding {
+            query_id: QueryId::new(42, 0, index),
+            round: 1,
+            query_index: index + 1,
+            query_seed: 242 + index as u64,
+            oracle: oracle.to_string(),
+            datafusion_version: "52.3.0".to_string(),
+            category,
+            message: format!("error {}\nwith details", index),
+            queries: vec!["SELECT 1".to_string()],
+            report: None,
+            result_files: Vec::new(),
+            datafusion_cli_reproduced: None,
+            config: String::new(),
+            table_files: Vec::new(),
+            timing: None,
+            swarm_features: None,
+            known_issue: None,
+        }
+    }
+
+    #[test]
+    fn summarizes_findings() {
+        let findings = vec![
+            make_finding(0, FailureCategory::Panic, "NoCrashOracle"),
+            make_finding(1, FailureCategory::WrongResult, "TlpWhereOracle"),
+            make_finding(2, FailureCategory::Panic, "TlpWhereOracle"),
+        ];
+
+        insta::assert_snapshot!(format_findings_report(&findings, Some("panic")), @r"
+        Findings: 3
+        DataFusion version: 52.3.0
+
+        By category:
+          • Panic: 2
+          • WrongResult: 1
+
+        By oracle:
+          • NoCrashOracle: 1
+          • TlpWhereOracle: 2
+
+        Findings:
+          42-r1-q1 [Panic] NoCrashOracle: error 0
+          42-r1-q3 [Panic] TlpWhereOracle: error 2
+        ");
+    }
+
+    #[test]
+    fn counts_findings_by_file_compression() {
+        let table_files = |table: &str, compression| TableFiles {
+            table: table.to_string(),
+            format: DatasetFormat::Parquet,
+            compression,
+            dir: PathBuf::from("/tmp"),
+        };
+        let mut first = make_finding(0, FailureCategory::WrongResult, "StatisticsOracle");
+        first.table_files = vec![
+            table_files("t0", Compression::Zstd),
+            table_files("t1", Compression::Zstd),
+        ];
+        let mut second = make_finding(1, FailureCategory::WrongResult, "StatisticsOracle");
+        second.table_files = vec![
+            table_files("t0", Compression::Zstd),
+            table_files("t1", Compression::Snappy),
+        ];
+
+        let report = format_findings_report(&[first, second], None);
+        assert!(report.contains(
+            "By file format and compression:\n  • Parquet Snappy: 1\n  • Parquet Zstd: 2\n"
+        ));
+    }
+
+    #[test]
+    fn marks_findings_checked_in_datafusion_cli() {
+        let mut finding = make_finding(0, FailureCategory::Panic, "NoCrashOracle");
+        finding.datafusion_cli_reproduced = Some(false);
+
+        let report = format_findings_report(&[finding], None);
+        assert!(report.contains(
+            "42-r1-q1 [Panic] NoCrashOracle: error 0 (does not reproduce in datafusion-cli)"
+        ));
+    }
+
+    #[test]
+    fn marks_findings_of_known_issues() {
+        let mut finding = make_finding(0, FailureCategory::Panic, "NoCrashOracle");
+        finding.known_issue = Some("https://github.com/apache/datafusion/issues/123".to_string());
+
+        let report = format_findings_report(&[finding], None);
+        assert!(
+            report.contains("By known issue:\n  • #123: 1\n"),
+            "{}",
+            report
+        );
+        assert!(report.contains("42-r1-q1 [Panic] NoCrashOracle: error 0 - known (issue #123)"));
+    }
+}
@@ -1,22 +1,46 @@
 use datafusion::arrow::record_batch::RecordBatch;
 use datafusion::common::instant::Instant;
+use datafusion::logical_expr::LogicalPlan;
+use datafusion::prelude::SessionContext;
 use rand::rngs::StdRng;
 use rand::{Rng, SeedableRng};
 use std::fs::OpenOptions;
 use std::io::Write;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
-use tracing::{error, info, warn};
+use tracing::{Instrument, error, info, info_span, warn};
 
-use crate::cli::error_whitelist::is_error_whitelisted;
+use crate::cli::datafusion_cli::{is_replayable_in_cli, replay_in_datafusion_cli};
+use crate::cli::panic_hook::{CapturedPanic, panic_payload_message, take_captured_panic};
+use crate::cli::plan_log::{PLAN_LOG_FILE, append_plans};
+use crate::cli::set_log_round;
 use crate::common::{InclusionConfig, LogicalTable, Result};
 use crate::datasource_generator::dataset_generator::DatasetGenerator;
-use crate::fuzz_context::{GlobalContext, ctx_observability::display_all_tables};
-use crate::fuzz_runner::{record_query_with_time, update_stat_for_round_completion};
-use crate::oracle::{Oracle, QueryContext, QueryExecutionResult};
+use crate::datasource_generator::dataset_snapshot::DatasetSnapshot;
+use crate::engine::{PlanningTimer, mirror_statements, with_planning_timer};
+use crate::fuzz_context::{
+    GlobalContext, RunnerConfig, ctx_observability::display_all_tables, has_memory_limit,
+    swarm::SwarmFeatures,
+};
+use crate::fuzz_runner::findings::{Finding, dump_result_sets, table_files};
+use crate::fuzz_runner::known_issues::issue_label;
+use crate::fuzz_runner::memory_trend::process_rss_bytes;
+use crate::fuzz_runner::phase_times::QueryTiming;
+use crate::fuzz_runner::plan_metrics::PlanMetrics;
+use crate::fuzz_runner::query_id::QueryId;
+use crate::fuzz_runner::query_shape::QueryShape;
+use crate::fuzz_runner::resource_usage::{ResourceMeter, ResourceUsage};
+use crate::fuzz_runner::{
+    record_coverage, record_finding, record_known_issue, record_latency, record_phase_times,
+    record_plan_metrics, record_quarantined, record_query_with_time, record_round_memory,
+    record_timeout_cleared_on_retry, record_truncated_result,
+    update_stat_for_oracle_test_completion, update_stat_for_round_completion,
+};
+use crate::oracle::oracle_common::validate_outcome_consistency;
+use crate::oracle::{Oracle, QueryContext, QueryExecutionResult, TruncatedResult};
 use crate::query_generator::stmt_select_def::SelectStatementBuilder;
-
-use super::error_whitelist;
+use crate::triage::{FailureCategory, classify_error};
 
 pub async fn run_fuzzer(ctx: Arc<GlobalContext>) -> Result<()> {
     info!("Starting fuzzer with seed: {}", ctx.runner_config.seed);
@@ -27,46 +51,336 @@ pub async fn run_fuzzer(ctx: Arc<GlobalContext>) -> Result<()> {
     // Create separate RNG instances for different phases, all seeded deterministically
     let base_seed = ctx.runner_config.seed;
 
-    for round in 0..ctx.runner_config.rounds {
-        info!("Starting round {}/{}", round + 1, ctx.runner_config.rounds);
+    // `rounds = 0` means no round limit, the run is bounded by `max_duration_secs`
+    let total_rounds = ctx.runner_config.rounds;
+    let deadline = ctx
+        .runner_config
+        .max_duration_secs
+        .map(|secs| Instant::now() + Duration::from_secs(secs));
+    let time_is_up = || deadline.is_some_and(|deadline| Instant::now() >= deadline);
+
+    // Tables of the first round, restored in later rounds with `reuse_datasets`
+    let mut dataset_snapshot: Option<DatasetSnapshot> = None;
+
+    let mut round = 0;
+    while total_rounds == 0 || round < total_rounds {
+        set_log_round(round + 1);
+        if total_rounds == 0 {
+            info!("Starting round {}", round + 1);
+        } else {
+            info!("Starting round {}/{}", round + 1, total_rounds);
+        }
+
+        // The seeds of the round derive from the base seed and the round, or
+        // with a seed pool from the base seed it picks, as its first round
+        let (round_base_seed, seed_round) = match &ctx.seed_scheduler {
+            Some(scheduler) => {
+                let seed = scheduler.lock().unwrap().next_seed();
+                info!("Running the tables and tests of seed {}", seed);
+                (seed, 0)
+            }
+            None => (base_seed, round),
+        };
+        let findings_before = ctx.findings.findings_count();
 
         // Create deterministic seeds for this round
-        let dataset_seed = base_seed.wrapping_add((round as u64) * 1000);
-        let view_seed = base_seed.wrapping_add((round as u64) * 1000 + 100);
-        let query_base_seed = base_seed.wrapping_add((round as u64) * 1000 + 200);
+        let dataset_seed = round_dataset_seed(round_base_seed, seed_round);
+        let view_seed = round_base_seed.wrapping_add((seed_round as u64) * 1000 + 100);
+        start_swarm_round(&ctx, round_base_seed, seed_round);
 
         // TODO: handle errors here in table/view creation, and catch potential bugs
-        generate_datasets_for_round(dataset_seed, &ctx).await?;
+        match &dataset_snapshot {
+            Some(snapshot) => snapshot.restore(&ctx)?,
+            None if ctx.runner_config.schema_evolution && round > 0 => {
+                evolve_datasets_for_round(dataset_seed, &ctx).await?
+            }
+            None => {
+                generate_datasets_for_round(dataset_seed, &ctx).await?;
+                if ctx.runner_config.reuse_datasets {
+                    dataset_snapshot = Some(DatasetSnapshot::capture(&ctx).await?);
+                }
+            }
+        }
         // generate_views_for_round(view_seed, &ctx).await?;
 
-        for i in 0..ctx.runner_config.queries_per_round {
-            // ==== Running round `round`, test case `i` ====
-            info!(
-                "Running oracle test {}/{}",
-                i + 1,
-                ctx.runner_config.queries_per_round
-            );
+        if ctx.runner_config.pipeline_depth == 0 {
+            for i in 0..ctx.runner_config.queries_per_round {
+                // ==== Running round `round`, test case `i` ====
+                info!(
+                    "Running oracle test {}/{}",
+                    i + 1,
+                    ctx.runner_config.queries_per_round
+                );
+
+                // Create deterministic seed for this specific query
+                let query_seed = oracle_test_seed(round_base_seed, seed_round, i);
+                let query_id = QueryId::new(round_base_seed, seed_round, i);
 
-            // Create deterministic seed for this specific query
-            let query_seed = query_base_seed.wrapping_add(i as u64);
+                // >>> CORE LOGIC <<<
+                // Every log line of the test carries its query ID
+                let _ = execute_oracle_test(seed_round, i, query_seed, query_id, &ctx)
+                    .instrument(info_span!("oracle_test", query_id = %query_id))
+                    .await?;
+                update_stat_for_oracle_test_completion(&ctx.fuzzer_stats);
 
-            // >>> CORE LOGIC <<<
-            let _ = execute_oracle_test(round, i, query_seed, &ctx).await?;
+                if ctx.findings.stop_reason().is_some() || time_is_up() {
+                    break;
+                }
+            }
+        } else {
+            run_round_pipelined(round_base_seed, seed_round, &ctx, &time_is_up).await?;
         }
 
         update_stat_for_round_completion(&ctx.fuzzer_stats);
+        steer_coverage(&ctx);
+        if let Some(scheduler) = &ctx.seed_scheduler {
+            let findings = ctx.findings.findings_count() - findings_before;
+            if let Err(e) = scheduler.lock().unwrap().finish_round(findings) {
+                warn!("Failed to save the seed pool: {}", e);
+            }
+        }
 
-        // Reset DataFusion context to drop all tables before the next round
-        if round < ctx.runner_config.rounds - 1 {
+        if let Some(reason) = ctx.findings.stop_reason() {
+            warn!("Stopping the run early: {}", reason);
+            break;
+        }
+
+        if time_is_up() {
+            info!("Reached max_duration_secs, stopping the run");
+            break;
+        }
+
+        // Reset DataFusion context to drop all tables before the next round,
+        // unless the tables evolve from round to round
+        if !ctx.runner_config.schema_evolution && (total_rounds == 0 || round < total_rounds - 1) {
             // Don't reset after the last round
             info!("Resetting DataFusion context for next round");
             ctx.reset_datafusion_context();
+
+            // With the tables dropped, memory should stop growing after the
+            // first rounds
+            if let Some(leak_rounds) = ctx.runner_config.memory_leak_rounds {
+                check_memory_trend(round, round_base_seed, seed_round, leak_rounds, &ctx).await;
+            }
         }
+
+        round += 1;
     }
 
     Ok(())
 }
 
+/// Record the memory of the process after `round`, and report a MemoryLeak
+/// finding once it grew in `leak_rounds` consecutive rounds. The seeds of the
+/// round's tests derive from `base_seed` and `seed_round`.
+async fn check_memory_trend(
+    round: u32,
+    base_seed: u64,
+    seed_round: u32,
+    leak_rounds: u32,
+    ctx: &Arc<GlobalContext>,
+) {
+    let Some(rss_bytes) = process_rss_bytes() else {
+        if round == 0 {
+            warn!("Can't read the process memory on this platform, memory_leak_rounds is ignored");
+        }
+        return;
+    };
+    let Some(message) = record_round_memory(&ctx.fuzzer_stats, round, rss_bytes, leak_rounds)
+    else {
+        return;
+    };
+
+    warn!("Possible memory leak: {}", message);
+    // The finding points at the first test of the round the leak showed in
+    let test_case = OracleTestCase {
+        query_id: QueryId::new(base_seed, seed_round, 0),
+        round: seed_round,
+        query_index: 0,
+        query_seed: oracle_test_seed(base_seed, seed_round, 0),
+        oracle_name: "MemoryTrend",
+    };
+    let report = ctx
+        .fuzzer_stats
+        .lock()
+        .unwrap()
+        .memory_trend
+        .format_display();
+    report_finding(
+        ctx,
+        &test_case,
+        FailureCategory::MemoryLeak,
+        message,
+        Vec::new(),
+        FindingEvidence {
+            report,
+            ..Default::default()
+        },
+    )
+    .await;
+}
+
+/// Run the oracle tests of a round, generating the queries of the next tests
+/// on a blocking thread while the current test executes.
+///
+/// At most `pipeline_depth` generated tests wait for execution. Generation
+/// only reads the tables of the round, so the tests and their queries are the
+/// same as when running them one after another.
+async fn run_round_pipelined(
+    base_seed: u64,
+    round: u32,
+    ctx: &Arc<GlobalContext>,
+    time_is_up: &impl Fn() -> bool,
+) -> Result<()> {
+    let queries_per_round = ctx.runner_config.queries_per_round;
+    let (sender, mut receiver) = tokio::sync::mpsc::channel(ctx.runner_config.pipeline_depth);
+
+    let generator_ctx = Arc::clone(ctx);
+    let generator = tokio::task::spawn_blocking(move || {
+        for i in 0..queries_per_round {
+            let query_id = QueryId::new(base_seed, round, i);
+            // Pauses while a timed out query is retried in isolation
+            let exclusive_execution = generator_ctx.exclusive_execution.blocking_read();
+            let generated = info_span!("oracle_test", query_id = %query_id).in_scope(|| {
+                let query_seed = oracle_test_seed(base_seed, round, i);
+                generate_oracle_test(round, i, query_seed, query_id, &generator_ctx)
+            });
+            drop(exclusive_execution);
+
+            // The runner stopped early and dropped the receiver
+            if sender.blocking_send((i, query_id, generated)).is_err() {
+                break;
+            }
+        }
+    });
+
+    while let Some((i, query_id, generated)) = receiver.recv().await {
+        info!("Running oracle test {}/{}", i + 1, queries_per_round);
+
+        if let Some(generated) = generated {
+            let _ = run_generated_oracle_test(generated, ctx)
+                .instrument(info_span!("oracle_test", query_id = %query_id))
+                .await?;
+        }
+        update_stat_for_oracle_test_completion(&ctx.fuzzer_stats);
+
+        if ctx.findings.stop_reason().is_some() || time_is_up() {
+            break;
+        }
+    }
+
+    drop(receiver);
+    generator
+        .await
+        .map_err(|e| crate::common::fuzzer_err(&format!("Query generation failed: {}", e)))
+}
+
+/// Seed of the tables generated for a 0-based round
+fn round_dataset_seed(base_seed: u64, round: u32) -> u64 {
+    base_seed.wrapping_add((round as u64) * 1000)
+}
+
+/// Seed of the swarm testing feature families of a 0-based round
+fn round_swarm_seed(base_seed: u64, round: u32) -> u64 {
+    round_dataset_seed(base_seed, round) ^ 0x5357_4152_4d00_0000
+}
+
+/// Seed of the tables a 0-based round runs its tests on: with `reuse_datasets`
+/// every round uses the tables of the first one
+fn round_tables_seed(config: &RunnerConfig, round: u32) -> u64 {
+    if config.reuse_datasets {
+        round_dataset_seed(config.seed, 0)
+    } else {
+        round_dataset_seed(config.seed, round)
+    }
+}
+
+/// Seed of a single oracle test, from its 0-based round and query index
+fn oracle_test_seed(base_seed: u64, round: u32, query_index: u32) -> u64 {
+    base_seed
+        .wrapping_add((round as u64) * 1000 + 200)
+        .wrapping_add(query_index as u64)
+}
+
+/// Recreate the tables of a round (1-based, as in query IDs) in a fresh
+/// DataFusion context.
+///
+/// Rounds don't share state, so this is all that is needed to run any test of
+/// the round again.
+pub async fn prepare_round(ctx: &Arc<GlobalContext>, round: u32) -> Result<()> {
+    if round == 0 {
+        return Err(crate::common::fuzzer_err("Rounds are numbered from 1"));
+    }
+
+    ctx.reset_datafusion_context();
+    set_log_round(round);
+    if ctx.runner_config.schema_evolution {
+        // The tables of a round are the result of all evolution steps before it
+        start_swarm_round(ctx, ctx.runner_config.seed, 0);
+        generate_datasets_for_round(round_dataset_seed(ctx.runner_config.seed, 0), ctx).await?;
+        for evolved_round in 1..round {
+            start_swarm_round(ctx, ctx.runner_config.seed, evolved_round);
+            evolve_datasets_for_round(
+                round_dataset_seed(ctx.runner_config.seed, evolved_round),
+                ctx,
+            )
+            .await?;
+        }
+    } else {
+        // With `reuse_datasets` the tables are those of the first round
+        let tables_round = if ctx.runner_config.reuse_datasets {
+            0
+        } else {
+            round - 1
+        };
+        start_swarm_round(ctx, ctx.runner_config.seed, tables_round);
+        generate_datasets_for_round(round_tables_seed(&ctx.runner_config, round - 1), ctx).await?;
+    }
+    start_swarm_round(ctx, ctx.runner_config.seed, round - 1);
+    Ok(())
+}
+
+/// Pick the feature families of a 0-based round of `base_seed` with
+/// `swarm_testing`
+fn start_swarm_round(ctx: &GlobalContext, base_seed: u64, round: u32) {
+    if !ctx.runner_config.swarm_testing {
+        return;
+    }
+    let features = SwarmFeatures::random(round_swarm_seed(base_seed, round));
+    info!(
+        "Swarm testing features of round {}: {}",
+        round + 1,
+        features
+    );
+    ctx.set_swarm_features(Some(features));
+}
+
+/// Run the oracle test identified by `query_id` again, with the same tables,
+/// oracle and queries as in the original run.
+///
+/// Returns whether the test produced a finding.
+pub async fn replay_oracle_test(ctx: Arc<GlobalContext>, query_id: QueryId) -> Result<bool> {
+    if query_id.seed != ctx.runner_config.seed {
+        return Err(crate::common::fuzzer_err(&format!(
+            "Query ID {} was generated with seed {}, but the config uses seed {}",
+            query_id, query_id.seed, ctx.runner_config.seed
+        )));
+    }
+
+    prepare_round(&ctx, query_id.round).await?;
+
+    let round = query_id.round - 1;
+    let query_index = query_id.index - 1;
+    let query_seed = oracle_test_seed(ctx.runner_config.seed, round, query_index);
+    let findings_before = ctx.findings.findings_count();
+
+    execute_oracle_test(round, query_index, query_seed, query_id, &ctx)
+        .instrument(info_span!("oracle_test", query_id = %query_id))
+        .await?;
+
+    Ok(ctx.findings.findings_count() > findings_before)
+}
+
 async fn generate_datasets_for_round(seed: u64, ctx: &Arc<GlobalContext>) -> Result<()> {
     // Create a deterministic RNG instance for this round
     let mut rng = StdRng::seed_from_u64(seed);
@@ -94,6 +408,50 @@ async fn generate_datasets_for_round(seed: u64, ctx: &Arc<GlobalContext>) -> Res
     Ok(())
 }
 
+/// Maximum number of tables added by one schema evolution step
+const MAX_EVOLUTION_NEW_TABLES: u32 = 3;
+
+/// Evolve the tables of the previous round instead of starting over: drop up
+/// to half of them and generate a few new ones, the rest persist. New tables
+/// continue the naming of the old ones, so catalogs mix old and new objects.
+async fn evolve_datasets_for_round(seed: u64, ctx: &Arc<GlobalContext>) -> Result<()> {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mut tables: Vec<_> = ctx.runtime_context.tables().values().cloned().collect();
+    let num_dropped = rng.random_range(0..=tables.len() / 2);
+    let session_context = ctx.runtime_context.get_session_context();
+    for _ in 0..num_dropped {
+        let table = tables.remove(rng.random_range(0..tables.len()));
+        info!("Dropping table {}", table.name);
+        let drop_table_sql = format!("DROP TABLE {}", table.sql_name());
+        session_context
+            .sql(&drop_table_sql)
+            .await?
+            .collect()
+            .await?;
+        mirror_statements(&ctx.engines, &[drop_table_sql.as_str()]).await?;
+        ctx.runtime_context.deregister_table(&table.name);
+    }
+
+    let num_new_tables = rng.random_range(1..=MAX_EVOLUTION_NEW_TABLES);
+    for i in 0..num_new_tables {
+        info!("Generating new table {}/{}", i + 1, num_new_tables);
+
+        let table_seed = seed.wrapping_add((i as u64) * 100);
+        let mut dataset_generator = DatasetGenerator::new(table_seed, Arc::clone(ctx));
+        match dataset_generator.generate_dataset().await {
+            Ok(table) => info!("Generated table: {}", table.name),
+            Err(e) => error!("Failed to generate table: {}", e),
+        }
+    }
+
+    if let Err(e) = display_all_tables(Arc::clone(ctx)).await {
+        error!("Failed to display tables: {}", e);
+    }
+
+    Ok(())
+}
+
 // TODO(coverage): support nested views like
 // create view v2 as select * from v1;
 async fn generate_views_for_round(seed: u64, ctx: &Arc<GlobalContext>) -> Result<()> {
@@ -101,9 +459,8 @@ async fn generate_views_for_round(seed: u64, ctx: &Arc<GlobalContext>) -> Result
     let mut rng = StdRng::seed_from_u64(seed);
 
     // Get all available tables (not views)
-    let tables_lock = ctx.runtime_context.registered_tables.read().unwrap();
-    let available_tables: Vec<Arc<LogicalTable>> = tables_lock.values().cloned().collect();
-    drop(tables_lock);
+    let available_tables: Vec<Arc<LogicalTable>> =
+        ctx.runtime_context.tables().values().cloned().collect();
 
     if available_tables.is_empty() {
         info!("No tables available for view generation");
@@ -122,9 +479,12 @@ async fn generate_views_for_round(seed: u64, ctx: &Arc<GlobalContext>) -> Result
         Arc::clone(ctx),
         InclusionConfig::Maybe(0.2),
         InclusionConfig::Maybe(0.2),
-    )
-    // Avoid large joins to slow down fuzzing
-    .with_max_table_count(3);
+    );
+    // Avoid large joins to slow down fuzzing, `join_row_budget` bounds them
+    // more precisely
+    if ctx.runner_config.join_row_budget.is_none() {
+        stmt_builder = stmt_builder.with_max_table_count(3);
+    }
 
     for i in 0..num_views {
         // Pick a random table to create a view from
@@ -137,7 +497,7 @@ async fn generate_views_for_round(seed: u64, ctx: &Arc<GlobalContext>) -> Result
             Ok(sql) => sql,
             Err(e) => {
                 let err_msg = format!("Failed to generate view SQL: {}", e);
-                if !is_error_whitelisted(&err_msg, None) {
+                if !ctx.error_whitelist.is_whitelisted(&err_msg, None) {
                     error!(err_msg);
                 }
                 continue; // Skip this view and try the next one
@@ -199,11 +559,7 @@ async fn create_and_register_view(
     // Register the view in our fuzzer context
     let logical_table = LogicalTable::new(view_name.to_string());
 
-    ctx.runtime_context
-        .registered_tables
-        .write()
-        .unwrap()
-        .insert(view_name.to_string(), Arc::new(logical_table));
+    ctx.runtime_context.register_table(Arc::new(logical_table));
 
     Ok(())
 }
@@ -212,9 +568,39 @@ async fn execute_oracle_test(
     round: u32,
     query_index: u32,
     seed: u64,
+    query_id: QueryId,
     ctx: &Arc<GlobalContext>,
 ) -> Result<bool> {
+    match generate_oracle_test(round, query_index, seed, query_id, ctx) {
+        Some(generated) => run_generated_oracle_test(generated, ctx).await,
+        None => Ok(false),
+    }
+}
+
+/// An oracle test whose queries are generated but not executed yet
+struct GeneratedOracleTest {
+    test_case: OracleTestCase,
+    oracle: Box<dyn Oracle + Send>,
+    query_group: Vec<QueryContext>,
+}
+
+/// Select the oracle of a test and generate its queries, `None` if the oracle
+/// could not generate any
+fn generate_oracle_test(
+    round: u32,
+    query_index: u32,
+    seed: u64,
+    query_id: QueryId,
+    ctx: &Arc<GlobalContext>,
+) -> Option<GeneratedOracleTest> {
     let mut randomly_selected_oracle = select_random_configured_oracle(seed, ctx);
+    let test_case = OracleTestCase {
+        query_id,
+        round,
+        query_index,
+        query_seed: seed,
+        oracle_name: randomly_selected_oracle.name(),
+    };
 
     info!("Selected oracle: {}", randomly_selected_oracle);
 
@@ -223,39 +609,109 @@ async fn execute_oracle_test(
         Ok(group) => group,
         Err(e) => {
             let err_msg = format!("Failed to generate query group: {}", e);
-            if !is_error_whitelisted(&err_msg, None) {
+            if !ctx.error_whitelist.is_whitelisted(&err_msg, None) {
                 error!(err_msg)
             }
-            return Ok(false);
+            return None;
         }
     };
 
     if query_group.is_empty() {
         warn!("Oracle generated empty query group");
-        return Ok(false);
+        return None;
     }
 
-    append_query_log(
-        ctx,
-        round,
-        query_index,
-        seed,
-        randomly_selected_oracle.name(),
-        &query_group,
-    )?;
+    Some(GeneratedOracleTest {
+        test_case,
+        oracle: randomly_selected_oracle,
+        query_group,
+    })
+}
+
+/// Execute the queries of a generated oracle test and validate the results.
+///
+/// Returns whether the test passed.
+async fn run_generated_oracle_test(
+    generated: GeneratedOracleTest,
+    ctx: &Arc<GlobalContext>,
+) -> Result<bool> {
+    let GeneratedOracleTest {
+        test_case,
+        oracle: randomly_selected_oracle,
+        query_group,
+    } = generated;
+    let query_id = test_case.query_id;
+
+    if !ctx.runner_config.quarantine.is_empty()
+        && is_quarantined(&test_case, &query_group, ctx).await
+    {
+        return Ok(true);
+    }
+
+    append_query_log(ctx, &test_case, &query_group)?;
+    if ctx.runner_config.record_plans
+        && let Some(log_dir) = &ctx.runner_config.log_path
+    {
+        let statements: Vec<(&str, Option<&SessionContext>)> = query_group
+            .iter()
+            .map(|query_context| {
+                let session_context = query_context
+                    .engine
+                    .is_none()
+                    .then_some(query_context.context.as_ref());
+                (query_context.query.as_str(), session_context)
+            })
+            .collect();
+        append_plans(&log_dir.join(PLAN_LOG_FILE), query_id, &statements).await?;
+    }
 
     // === Execute queries and collect results ===
     let mut execution_results = Vec::new();
-    for query_context in query_group {
-        info!("Query:\n{}", query_context.query);
+    for (statement_index, query_context) in query_group.into_iter().enumerate() {
+        let statement_id = query_id.statement_id(statement_index + 1);
+        info!("Query {}:\n{}", statement_id, query_context.query);
 
         let query_context_arc = Arc::new(query_context);
-        let execution_result = execute_single_query(Arc::clone(&query_context_arc), ctx).await;
+        let cached_result = randomly_selected_oracle
+            .reuses_identical_results()
+            .then(|| cached_group_result(&execution_results, &query_context_arc))
+            .flatten();
+        let execution_result = match cached_result {
+            Some((cached_index, result)) => {
+                info!(
+                    "Reusing the result of {}",
+                    query_id.statement_id(cached_index + 1)
+                );
+                result
+            }
+            None => execute_single_query(query_context_arc, &statement_id, &test_case, ctx).await,
+        };
 
-        execution_results.push(QueryExecutionResult {
-            query_context: query_context_arc,
-            result: execution_result,
-        });
+        execution_results.push(execution_result);
+    }
+
+    // === Check error consistency across the group ===
+    if execution_results.len() > 1
+        && randomly_selected_oracle.requires_consistent_outcomes()
+        && let Err(e) =
+            validate_outcome_consistency(&execution_results, randomly_selected_oracle.name())
+    {
+        error!("Oracle test failed: {}", e);
+
+        let queries = execution_results
+            .iter()
+            .map(|result| result.query_context.query.clone())
+            .collect();
+        report_finding(
+            ctx,
+            &test_case,
+            FailureCategory::InconsistentOutcome,
+            e.to_string(),
+            queries,
+            FindingEvidence::default(),
+        )
+        .await;
+        return Ok(false);
     }
 
     // === Validate execution results ===
@@ -271,37 +727,271 @@ async fn execute_oracle_test(
             error!("Oracle test failed: {}", e);
 
             // Log error report if available
-            if let Ok(error_report) =
-                randomly_selected_oracle.create_error_report(&execution_results)
-            {
+            let error_report = randomly_selected_oracle
+                .create_error_report(&execution_results)
+                .ok();
+            if let Some(error_report) = &error_report {
                 error!("Error Report:\n{}", error_report);
             }
+
+            let result_files = match &ctx.runner_config.log_path {
+                Some(log_dir) if ctx.runner_config.dump_mismatch_ipc => {
+                    dump_result_sets(log_dir, query_id, &execution_results).unwrap_or_else(|e| {
+                        warn!("Failed to dump result sets: {}", e);
+                        Vec::new()
+                    })
+                }
+                _ => Vec::new(),
+            };
+
+            let queries = execution_results
+                .iter()
+                .map(|result| result.query_context.query.clone())
+                .collect();
+            report_finding(
+                ctx,
+                &test_case,
+                FailureCategory::WrongResult,
+                e.to_string(),
+                queries,
+                FindingEvidence {
+                    report: error_report,
+                    result_files,
+                    timing: None,
+                },
+            )
+            .await;
             Ok(false)
         }
     }
 }
 
-fn select_random_configured_oracle(seed: u64, ctx: &Arc<GlobalContext>) -> Box<dyn Oracle + Send> {
-    // Randomly pick one oracle for this query; the configured oracle set bounds the choice.
-    let available_oracles: Vec<Box<dyn Oracle + Send>> = ctx
+/// Whether an oracle test is skipped because a query of its group matches a
+/// `quarantine` pattern. The first matching pattern decides, and every test
+/// it matches is counted whether it runs or not.
+async fn is_quarantined(
+    test_case: &OracleTestCase,
+    query_group: &[QueryContext],
+    ctx: &Arc<GlobalContext>,
+) -> bool {
+    // Queries on other engines are mirrors of queries of the embedded session
+    let mut shapes = Vec::new();
+    for query_context in query_group.iter().filter(|q| q.engine.is_none()) {
+        if let Ok(plan) = query_context
+            .context
+            .state()
+            .create_logical_plan(&query_context.query)
+            .await
+        {
+            shapes.push(QueryShape::from_plan(&plan));
+        }
+    }
+    let Some(pattern) = ctx
         .runner_config
-        .oracles
+        .quarantine
         .iter()
-        .copied()
-        .map(|oracle| oracle.build(seed, Arc::clone(ctx)))
-        .collect();
+        .find(|pattern| shapes.iter().any(|shape| pattern.matches(shape)))
+    else {
+        return false;
+    };
 
-    let mut rng = StdRng::seed_from_u64(seed);
-    let oracle_index = rng.random_range(0..available_oracles.len());
-    available_oracles.into_iter().nth(oracle_index).unwrap()
+    // Seeded by the test, so a replay makes the same choice
+    let mut rng = StdRng::seed_from_u64(test_case.query_seed);
+    let skipped = !rng.random_bool(pattern.weight);
+    record_quarantined(&ctx.fuzzer_stats, &pattern.name, skipped);
+    if skipped {
+        info!(
+            "Skipping oracle test, a query matches the quarantined shape '{}'",
+            pattern.name
+        );
+    }
+    skipped
 }
 
-fn append_query_log(
-    ctx: &Arc<GlobalContext>,
+/// Result of an earlier statement of the group with the same query in the same
+/// session and engine, with its index. Executing the query again would only
+/// reproduce it, and report its error a second time.
+fn cached_group_result(
+    results: &[QueryExecutionResult],
+    query_context: &Arc<QueryContext>,
+) -> Option<(usize, QueryExecutionResult)> {
+    let (index, cached) = results.iter().enumerate().find(|(_, result)| {
+        let cached_context = &result.query_context;
+        let same_engine = match (&cached_context.engine, &query_context.engine) {
+            (None, None) => true,
+            (Some(cached_engine), Some(engine)) => Arc::ptr_eq(cached_engine, engine),
+            _ => false,
+        };
+        cached_context.query == query_context.query
+            && Arc::ptr_eq(&cached_context.context, &query_context.context)
+            && same_engine
+    })?;
+
+    let result = match &cached.result {
+        Ok(batches) => Ok(batches.clone()),
+        Err(e) => Err(crate::common::fuzzer_err(&e.to_string())),
+    };
+    Some((
+        index,
+        QueryExecutionResult {
+            query_context: Arc::clone(query_context),
+            result,
+            truncated: cached.truncated.clone(),
+        },
+    ))
+}
+
+/// Identifies the oracle test a query belongs to, for findings
+struct OracleTestCase {
+    query_id: QueryId,
     round: u32,
     query_index: u32,
     query_seed: u64,
-    oracle_name: &str,
+    oracle_name: &'static str,
+}
+
+/// What a finding carries besides its message and queries
+#[derive(Default)]
+struct FindingEvidence {
+    /// Oracle error report or panic details
+    report: Option<String>,
+    result_files: Vec<PathBuf>,
+    /// Planning and execution time of the query, for findings of one query
+    timing: Option<QueryTiming>,
+}
+
+/// Record a finding in the stats and the findings log
+async fn report_finding(
+    ctx: &Arc<GlobalContext>,
+    test_case: &OracleTestCase,
+    category: FailureCategory,
+    message: String,
+    queries: Vec<String>,
+    evidence: FindingEvidence,
+) {
+    let FindingEvidence {
+        report,
+        result_files,
+        timing,
+    } = evidence;
+    record_finding(&ctx.fuzzer_stats);
+
+    let mut finding = Finding {
+        query_id: test_case.query_id,
+        round: test_case.round + 1,
+        query_index: test_case.query_index + 1,
+        query_seed: test_case.query_seed,
+        oracle: test_case.oracle_name.to_string(),
+        datafusion_version: ctx.runner_config.datafusion_version(),
+        category,
+        message,
+        queries,
+        report,
+        result_files,
+        datafusion_cli_reproduced: None,
+        config: finding_config(ctx, test_case).to_toml().unwrap_or_default(),
+        table_files: table_files(ctx),
+        timing,
+        swarm_features: ctx.swarm_features().map(|features| features.enabled()),
+        known_issue: None,
+    };
+    match ctx.known_issues.issue_of(&finding) {
+        Some(issue) => {
+            info!("Known finding (issue {}): {}", issue_label(issue), issue);
+            record_known_issue(&ctx.fuzzer_stats, issue);
+            finding.known_issue = Some(issue.to_string());
+        }
+        None if ctx.findings.in_baseline(&finding) => {
+            info!("Finding in the baseline: {}", finding.fingerprint())
+        }
+        None => info!("New finding, fingerprint: {}", finding.fingerprint()),
+    }
+
+    if let (Some(datafusion_cli), Some(output_dir)) =
+        (&ctx.runner_config.datafusion_cli, ctx.reproducers_dir())
+        && is_replayable_in_cli(category)
+    {
+        match replay_in_datafusion_cli(ctx, datafusion_cli, &output_dir, &finding).await {
+            Ok(reproduced) => finding.datafusion_cli_reproduced = Some(reproduced),
+            Err(e) => warn!("Failed to replay finding in datafusion-cli: {}", e),
+        }
+    }
+
+    if let Err(e) = ctx.findings.record(&finding) {
+        warn!("Failed to record finding: {}", e);
+    }
+}
+
+/// Configuration recorded in a finding of `test_case`. With a seed pool, the
+/// seed is the base seed of the test's round, so the finding replays without
+/// the pool.
+fn finding_config(ctx: &GlobalContext, test_case: &OracleTestCase) -> RunnerConfig {
+    let mut config = ctx.finding_config();
+    if config.seed_pool.take().is_some() {
+        config.seed = test_case.query_id.seed;
+    }
+    config
+}
+
+fn select_random_configured_oracle(seed: u64, ctx: &Arc<GlobalContext>) -> Box<dyn Oracle + Send> {
+    // Randomly pick one oracle for this query; the configured oracle set bounds the choice.
+    // Only the picked oracle is built, building all of them for every query costs throughput.
+    let available_oracles = &ctx.runner_config.oracles;
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let weights = &ctx.runner_config.oracle_weights;
+    let oracle_index = if weights.is_empty() {
+        rng.random_range(0..available_oracles.len())
+    } else {
+        weighted_index(&mut rng, weights)
+    };
+    available_oracles[oracle_index].build(seed, Arc::clone(ctx))
+}
+
+/// Index into `weights` picked with a probability proportional to its weight
+fn weighted_index(rng: &mut StdRng, weights: &[f64]) -> usize {
+    let mut remaining = rng.random_range(0.0..weights.iter().sum::<f64>());
+    for (index, weight) in weights.iter().enumerate() {
+        if remaining < *weight {
+            return index;
+        }
+        remaining -= weight;
+    }
+    // Rounding can leave a tiny remainder, it belongs to the last weighted oracle
+    weights
+        .iter()
+        .rposition(|weight| *weight > 0.0)
+        .unwrap_or_default()
+}
+
+/// Steer the optional clauses the generator adds in the next round towards
+/// `coverage_targets`, see [`crate::fuzz_runner::coverage`]
+fn steer_coverage(ctx: &Arc<GlobalContext>) {
+    let targets = &ctx.runner_config.coverage_targets;
+    if targets.is_empty() {
+        return;
+    }
+
+    let boosts = ctx
+        .fuzzer_stats
+        .lock()
+        .unwrap()
+        .coverage
+        .steered_boosts(targets);
+    info!(
+        "Clauses boosted towards the coverage targets: {}",
+        boosts
+            .iter()
+            .map(|(clause, boost)| format!("{} {:.2}", clause.name(), boost))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    ctx.set_coverage_boosts(boosts);
+}
+
+fn append_query_log(
+    ctx: &Arc<GlobalContext>,
+    test_case: &OracleTestCase,
     query_group: &[QueryContext],
 ) -> Result<()> {
     let Some(log_dir) = &ctx.runner_config.log_path else {
@@ -323,11 +1013,12 @@ fn append_query_log(
 
     writeln!(
         file,
-        "=== round={} query={} oracle={} query_seed={} ===",
-        round + 1,
-        query_index + 1,
-        oracle_name,
-        query_seed
+        "=== id={} round={} query={} oracle={} query_seed={} ===",
+        test_case.query_id,
+        test_case.round + 1,
+        test_case.query_index + 1,
+        test_case.oracle_name,
+        test_case.query_seed
     )?;
 
     for (statement_index, query_context) in query_group.iter().enumerate() {
@@ -352,104 +1043,470 @@ fn append_query_log(
 #[derive(Debug)]
 struct QueryExecutionOutcome {
     result: Result<Vec<RecordBatch>>,
+    /// Set if only the first `max_result_rows` rows were kept
+    truncated: Option<TruncatedResult>,
     timed_out: bool,
     execution_time: Duration,
+    /// Time until the query was planned, for engines reporting it (the
+    /// embedded session). The whole `execution_time` if it timed out while
+    /// planning.
+    planning_time: Option<Duration>,
+    /// Memory and CPU time used by the query, with `measure_resources`
+    resource_usage: Option<ResourceUsage>,
+    /// Details of the panic if the query panicked
+    panic: Option<CapturedPanic>,
+    /// Logical plan the query was executed with, for engines reporting it
+    /// (the embedded session)
+    logical_plan: Option<LogicalPlan>,
+}
+
+impl QueryExecutionOutcome {
+    /// Planning and execution time, if the planning was timed
+    fn timing(&self) -> Option<QueryTiming> {
+        self.planning_time
+            .map(|planning_time| QueryTiming::new(planning_time, self.execution_time))
+    }
 }
 
 /// We make sure error message is in 'whitelist'.
-/// Error consistency check can be done later: all query in the group should all succeed
-/// or all fail. (TODO: implement this)
+/// Error consistency across the group (all queries succeed or all fail) is checked
+/// by the caller once the whole group has been executed.
 async fn execute_single_query(
     query_context: Arc<QueryContext>,
+    statement_id: &str,
+    test_case: &OracleTestCase,
     ctx: &Arc<GlobalContext>,
-) -> Result<Vec<RecordBatch>> {
-    let timeout_duration = Duration::from_secs(ctx.runner_config.timeout_seconds);
+) -> QueryExecutionResult {
+    let timeout = query_timeout(ctx);
+    let result_limit = ctx
+        .runner_config
+        .max_result_rows
+        .map(|max_rows| ResultLimit {
+            max_rows,
+            spool_dir: ctx
+                .runner_config
+                .spool_results
+                .then(|| ctx.result_spool_dir().unwrap_or_else(std::env::temp_dir)),
+        });
+
+    // Only queries in the embedded session can be measured
+    let measure_resources = ctx.runner_config.measure_resources && query_context.engine.is_none();
+    let execute = |result_limit| {
+        let query_context = &query_context;
+        async move {
+            if measure_resources {
+                execute_query_measured(query_context, timeout, result_limit).await
+            } else {
+                execute_query_with_timeout(query_context, timeout, result_limit).await
+            }
+        }
+    };
 
     // Execute query with timeout tracking
-    let outcome = execute_query_with_timeout(&query_context, timeout_duration).await;
+    let mut outcome = execute(result_limit.clone()).await;
+
+    // A busy machine also makes queries time out, run the query once more
+    // with nothing else running before counting it as slow
+    if outcome.timed_out && ctx.runner_config.retry_timeouts {
+        info!(
+            "Query timed out after {:.2}ms, retrying it in isolation",
+            outcome.execution_time.as_secs_f64() * 1000.0
+        );
+        let _exclusive = ctx.exclusive_execution.write().await;
+        outcome = execute(result_limit.clone()).await;
+        if !outcome.timed_out {
+            info!(
+                "Query finished in {:.2}ms when retried in isolation",
+                outcome.execution_time.as_secs_f64() * 1000.0
+            );
+            record_timeout_cleared_on_retry(&ctx.fuzzer_stats);
+        }
+    }
 
     // Log timeout queries specifically
     if outcome.timed_out {
         warn!(
-            "Query timed out after {:.2}ms (timeout: {}s):\n{}\n\
-             Note: Query execution task has been dropped. Use Ctrl+C if the fuzzer appears stuck.",
+            "Query timed out after {:.2}ms (timeout: {:.2}s):\n{}\n\
+             Note: Query execution has been cancelled. Use Ctrl+C if the fuzzer appears stuck.",
             outcome.execution_time.as_secs_f64() * 1000.0,
-            ctx.runner_config.timeout_seconds,
+            timeout.total.as_secs_f64(),
             query_context.query
         );
+    } else {
+        record_latency(&ctx.fuzzer_stats, outcome.execution_time);
     }
 
-    // Check if error is whitelisted using the dedicated error_whitelist module
+    // Check if error is whitelisted (built-in patterns merged with the configured ones)
     if let Err(ref e) = outcome.result {
         let error_msg = e.to_string();
-        if !error_whitelist::is_error_whitelisted(&error_msg, Some(&query_context.query)) {
-            // Log non-whitelisted errors
-            error!("Non-whitelisted error encountered: {}", error_msg);
-            error!("Query that caused the error: {}", query_context.query);
+        if query_context
+            .tolerated_error
+            .is_some_and(|tolerated_error| tolerated_error(&error_msg))
+        {
+            info!("Expected error encountered: {}", error_msg);
+        } else if !ctx
+            .error_whitelist
+            .is_whitelisted(&error_msg, Some(&query_context.query))
+        {
+            let category = classify_error(&error_msg);
+            if category == FailureCategory::ResourceExhausted
+                && has_memory_limit(&query_context.context)
+            {
+                // Running out of a configured memory budget is the graceful outcome
+                info!("Query exceeded the memory limit: {}", error_msg);
+            } else {
+                // Log non-whitelisted errors
+                error!(
+                    "Non-whitelisted error encountered ({}): {}",
+                    category, error_msg
+                );
+                error!("Query that caused the error: {}", query_context.query);
+                let report = outcome.panic.as_ref().map(|panic| {
+                    match ctx.findings.replay_command(test_case.query_id) {
+                        Some(command) => {
+                            format!("{}\n\nReproduce with: {}", panic.report(), command)
+                        }
+                        None => panic.report(),
+                    }
+                });
+                report_finding(
+                    ctx,
+                    test_case,
+                    category,
+                    error_msg,
+                    vec![query_context.query.clone()],
+                    FindingEvidence {
+                        report,
+                        result_files: Vec::new(),
+                        timing: outcome.timing(),
+                    },
+                )
+                .await;
+            }
         } else {
             info!("Whitelisted error encountered: {}", error_msg);
         }
     }
 
+    if let Some(timing) = outcome.timing() {
+        record_phase_times(&ctx.fuzzer_stats, timing, statement_id);
+
+        if let Some(threshold_ms) = ctx.runner_config.planning_time_threshold_ms
+            && timing.planning_ms > threshold_ms as f64
+        {
+            warn!(
+                "Query took {:.2}ms to plan:\n{}",
+                timing.planning_ms, query_context.query
+            );
+            report_finding(
+                ctx,
+                test_case,
+                FailureCategory::SlowPlanning,
+                format!(
+                    "Planning took {:.2}ms, more than planning_time_threshold_ms = {}",
+                    timing.planning_ms, threshold_ms
+                ),
+                vec![query_context.query.clone()],
+                FindingEvidence {
+                    timing: Some(timing),
+                    ..Default::default()
+                },
+            )
+            .await;
+        }
+    }
+
     record_query_with_time(
         &ctx.fuzzer_stats,
         &query_context.query,
+        statement_id,
         outcome.result.is_ok(),
         outcome.execution_time.into(),
+        outcome.resource_usage,
         ctx.runner_config.sample_interval_secs,
     );
 
-    outcome.result
+    // The plan the embedded session executed, other engines plan
+    // differently, and failed queries have no plan to compare
+    if outcome.result.is_ok()
+        && let Some(plan) = &outcome.logical_plan
+    {
+        record_plan_metrics(
+            &ctx.fuzzer_stats,
+            PlanMetrics::from_plan(plan),
+            outcome.execution_time,
+        );
+        let shape = QueryShape::from_plan(plan);
+        record_coverage(&ctx.fuzzer_stats, &shape);
+        if let Some(scheduler) = &ctx.seed_scheduler {
+            scheduler.lock().unwrap().record_shape(&shape);
+        }
+    }
+
+    if let Some(truncated) = &outcome.truncated {
+        match &truncated.spooled {
+            Some(spooled) => info!(
+                "Query returned {} rows, spooled to {}",
+                truncated.total_rows,
+                spooled.path().display()
+            ),
+            None => info!(
+                "Query returned {} rows, only the first {} are compared",
+                truncated.total_rows,
+                ctx.runner_config.max_result_rows.unwrap_or_default()
+            ),
+        }
+        record_truncated_result(&ctx.fuzzer_stats);
+    }
+
+    QueryExecutionResult {
+        query_context,
+        result: outcome.result,
+        truncated: outcome.truncated,
+    }
+}
+
+/// How long a query may take in total, and optionally to plan and to execute
+/// once planned
+#[derive(Debug, Clone, Copy)]
+struct QueryTimeout {
+    total: Duration,
+    /// See `plan_timeout_ms`
+    planning: Option<Duration>,
+    /// See `execution_timeout_ms`
+    execution: Option<Duration>,
+}
+
+impl From<Duration> for QueryTimeout {
+    fn from(total: Duration) -> Self {
+        Self {
+            total,
+            planning: None,
+            execution: None,
+        }
+    }
+}
+
+/// Error of a query cancelled while executing, whitelisted
+const EXECUTION_TIMED_OUT: &str = "Query execution timed out";
+/// Error of a query cancelled after `plan_timeout_ms` while planning
+const PLANNING_TIMED_OUT: &str = "Query planning timed out";
+
+impl QueryTimeout {
+    /// Wait until a query started at `start_time` times out, returning the
+    /// error to report. With a `timer`, planning and execution are timed out
+    /// apart.
+    async fn expired(&self, start_time: Instant, timer: Option<&PlanningTimer>) -> &'static str {
+        let deadline = tokio::time::Instant::from_std(start_time + self.total);
+        let Some(timer) = timer else {
+            tokio::time::sleep_until(deadline).await;
+            return EXECUTION_TIMED_OUT;
+        };
+
+        // Hitting the overall timeout while planning is not a planner hang
+        let (planning_deadline, planning_error) = match self.planning {
+            Some(planning) if start_time + planning < deadline.into_std() => (
+                tokio::time::Instant::from_std(start_time + planning),
+                PLANNING_TIMED_OUT,
+            ),
+            _ => (deadline, EXECUTION_TIMED_OUT),
+        };
+        let planned_at = tokio::select! {
+            planned_at = timer.planned() => planned_at,
+            _ = tokio::time::sleep_until(planning_deadline) => return planning_error,
+        };
+
+        let execution_deadline = self.execution.map_or(deadline, |execution| {
+            tokio::time::Instant::from_std(planned_at + execution).min(deadline)
+        });
+        tokio::time::sleep_until(execution_deadline).await;
+        EXECUTION_TIMED_OUT
+    }
 }
 
-/// Execute a query with proper timeout and cancellation
+/// Timeout of the next query: `timeout_seconds`, or with `adaptive_timeout` a
+/// multiple of the p99 latency of recent queries, capped at `timeout_seconds`.
+/// `plan_timeout_ms` and `execution_timeout_ms` bound the phases within it.
+fn query_timeout(ctx: &GlobalContext) -> QueryTimeout {
+    let config = &ctx.runner_config;
+    let ceiling = Duration::from_secs(config.timeout_seconds);
+    let total = if config.adaptive_timeout {
+        ctx.fuzzer_stats.lock().unwrap().recent_latencies.timeout(
+            config.adaptive_timeout_factor,
+            Duration::from_millis(config.adaptive_timeout_floor_ms),
+            ceiling,
+        )
+    } else {
+        ceiling
+    };
+
+    QueryTimeout {
+        total,
+        planning: config.plan_timeout_ms.map(Duration::from_millis),
+        execution: config.execution_timeout_ms.map(Duration::from_millis),
+    }
+}
+
+/// Execute a standalone SQL statement in the current DataFusion context, with
+/// the configured timeout. No stats or findings are recorded.
+pub(crate) async fn execute_sql(ctx: &Arc<GlobalContext>, sql: &str) -> Result<Vec<RecordBatch>> {
+    let query_context =
+        QueryContext::new(sql.to_string(), ctx.runtime_context.get_session_context());
+    let timeout = Duration::from_secs(ctx.runner_config.timeout_seconds);
+
+    execute_query_with_timeout(&query_context, timeout.into(), None)
+        .await
+        .result
+}
+
+/// How much of a query result to keep, see `max_result_rows` and
+/// `spool_results`
+#[derive(Clone)]
+struct ResultLimit {
+    max_rows: usize,
+    /// Where to spool results with more than `max_rows` rows
+    spool_dir: Option<PathBuf>,
+}
+
+/// Like [`execute_query_with_timeout`], running the query in a session of its
+/// own to measure its peak memory and CPU time. Timed out queries are not
+/// measured, they stopped early.
+async fn execute_query_measured(
+    query_context: &QueryContext,
+    timeout: QueryTimeout,
+    result_limit: Option<ResultLimit>,
+) -> QueryExecutionOutcome {
+    let (session_context, meter) = match ResourceMeter::session_context(&query_context.context) {
+        Ok(measured) => measured,
+        Err(e) => {
+            warn!(
+                "Failed to measure resource usage, running the query unmeasured: {}",
+                e
+            );
+            return execute_query_with_timeout(query_context, timeout, result_limit).await;
+        }
+    };
+    let measured_context = QueryContext {
+        context: session_context,
+        ..query_context.clone()
+    };
+
+    let mut outcome = execute_query_with_timeout(&measured_context, timeout, result_limit).await;
+    if !outcome.timed_out {
+        outcome.resource_usage = Some(meter.usage());
+    }
+    outcome
+}
+
+/// How long to wait for a timed out query to stop after it was cancelled
+const CANCELLATION_GRACE_PERIOD: Duration = Duration::from_secs(1);
+
+/// Execute a query with proper timeout and cancellation. With a
+/// `result_limit`, only the first rows of the result are kept.
 async fn execute_query_with_timeout(
     query_context: &QueryContext,
-    timeout_duration: Duration,
+    timeout: QueryTimeout,
+    result_limit: Option<ResultLimit>,
 ) -> QueryExecutionOutcome {
     let start_time = Instant::now();
 
     // Clone the necessary data to avoid lifetime issues
-    let context = Arc::clone(&query_context.context);
+    let engine = query_context.engine_under_test();
     let query = query_context.query.clone();
+    let timer = Arc::new(PlanningTimer::default());
 
     // Spawn the query execution in a separate task
-    let query_task = tokio::spawn(async move {
-        context
-            .sql(&query)
-            .await
-            .map_err(|e| crate::common::fuzzer_err(&format!("Query planning failed: {}", e)))?
-            .collect()
-            .await
-            .map_err(|e| crate::common::fuzzer_err(&format!("Query execution failed: {}", e)))
-    });
+    let mut query_task = tokio::spawn(with_planning_timer(Arc::clone(&timer), async move {
+        match result_limit {
+            Some(limit) => engine
+                .execute_sql_limited(&query, limit.max_rows, limit.spool_dir.as_deref())
+                .await
+                .map(|limited| {
+                    let truncated = limited.is_truncated().then(|| TruncatedResult {
+                        total_rows: limited.total_rows,
+                        spooled: limited.spooled.map(Arc::new),
+                    });
+                    (limited.batches, truncated)
+                }),
+            None => engine
+                .execute_sql(&query)
+                .await
+                .map(|batches| (batches, None)),
+        }
+    }));
+
+    // Only the embedded session reports the end of planning
+    let reports_planning = query_context.engine.is_none();
 
     // Use tokio::select! to handle timeout properly
-    let result = tokio::select! {
-        result = query_task => {
-            match result {
+    let mut panic = None;
+    let (result, execution_time) = tokio::select! {
+        result = &mut query_task => {
+            let result = match result {
                 Ok(query_result) => query_result,
+                // The task boundary catches panics inside DataFusion, turn them
+                // into errors carrying the panic message and location
+                Err(join_error) if join_error.is_panic() => {
+                    let payload_message = panic_payload_message(&*join_error.into_panic());
+                    panic = take_captured_panic().filter(|p| p.message == payload_message);
+                    let error_msg = match &panic {
+                        Some(captured) => captured.error_message(),
+                        None => format!("Query task panicked: {}", payload_message),
+                    };
+                    Err(crate::common::fuzzer_err(&error_msg))
+                }
                 Err(_) => Err(crate::common::fuzzer_err("Query task failed")),
-            }
+            };
+            (result, start_time.elapsed())
         }
-        _ = tokio::time::sleep(timeout_duration) => {
-            // Query timed out - the task will be dropped when we return
-            Err(crate::common::fuzzer_err("Query execution timed out"))
+        error_msg = timeout.expired(start_time, reports_planning.then_some(&*timer)) => {
+            let execution_time = start_time.elapsed();
+
+            // Dropping the handle would leave the query running in the
+            // background, competing with the next queries for CPU. Abort it
+            // instead: DataFusion streams stop at their next yield point.
+            query_task.abort();
+            if tokio::time::timeout(CANCELLATION_GRACE_PERIOD, &mut query_task)
+                .await
+                .is_err()
+            {
+                warn!(
+                    "Timed out query did not stop within {}ms after cancellation",
+                    CANCELLATION_GRACE_PERIOD.as_millis()
+                );
+            }
+
+            (Err(crate::common::fuzzer_err(error_msg)), execution_time)
         }
     };
 
-    let execution_time = start_time.elapsed();
     let timed_out = result.is_err()
         && result
             .as_ref()
             .unwrap_err()
             .to_string()
             .contains("timed out");
+    let (result, truncated) = match result {
+        Ok((batches, truncated)) => (Ok(batches), truncated),
+        Err(e) => (Err(e), None),
+    };
+    // A query of the embedded session that timed out before it was planned
+    // spent all of its time planning
+    let planning_time = match timer.planned_at() {
+        Some(planned_at) => Some(planned_at.duration_since(start_time)),
+        None if timed_out && reports_planning => Some(execution_time),
+        None => None,
+    };
 
     QueryExecutionOutcome {
         result,
+        truncated,
         timed_out,
         execution_time,
+        planning_time,
+        resource_usage: None,
+        panic,
+        logical_plan: timer.logical_plan().cloned(),
     }
 }
 
@@ -472,18 +1529,14 @@ mod tests {
             seed,
             rounds: 2,
             queries_per_round: 3,
-            timeout_seconds: 2,
             log_path: None, // Disable file logging for tests
-            display_logs: false,
+            log_rotation_max_bytes: 1024,
             enable_tui: false,
-            sample_interval_secs: 5,
             max_column_count: 3,
             max_row_count: 10,
             max_expr_level: 2,
             max_group_by_count: 2,
-            max_table_count: 3,
-            max_insert_per_table: 20,
-            oracles: vec![crate::oracle::ConfiguredOracle::NoCrash],
+            ..RunnerConfig::default()
         };
 
         // Collect results from multiple runs
@@ -561,15 +1614,12 @@ mod tests {
 
         // Create a simple query context
         let context = Arc::new(SessionContext::new());
-        let query_context = QueryContext {
-            query: "SELECT 1".to_string(),
-            context,
-            context_description: None,
-        };
+        let query_context = QueryContext::new("SELECT 1".to_string(), context);
 
         // Test with a reasonable timeout
         let timeout_duration = Duration::from_millis(100);
-        let outcome = execute_query_with_timeout(&query_context, timeout_duration).await;
+        let outcome =
+            execute_query_with_timeout(&query_context, timeout_duration.into(), None).await;
 
         // The query should complete quickly and not timeout
         assert!(
@@ -581,6 +1631,73 @@ mod tests {
             outcome.execution_time < Duration::from_millis(50),
             "Query should complete quickly"
         );
+
+        // Planning is timed apart from execution
+        let planning_time = outcome.planning_time.expect("Planning should be timed");
+        assert!(planning_time <= outcome.execution_time);
+        let timing = outcome.timing().unwrap();
+        assert!(timing.planning_ms + timing.execution_ms <= 50.0);
+    }
+
+    /// Test that a timed out query is cancelled instead of running on in the background
+    #[tokio::test]
+    async fn test_timed_out_query_is_cancelled() {
+        use datafusion::prelude::SessionContext;
+
+        let query_context = QueryContext::new(
+            "SELECT count(*) FROM generate_series(1, 100000000000)".to_string(),
+            Arc::new(SessionContext::new()),
+        );
+
+        let start = Instant::now();
+        let outcome =
+            execute_query_with_timeout(&query_context, Duration::from_millis(100).into(), None)
+                .await;
+
+        assert!(outcome.timed_out, "Query should time out");
+        assert!(outcome.execution_time < Duration::from_secs(1));
+        assert!(
+            start.elapsed() < Duration::from_millis(100) + CANCELLATION_GRACE_PERIOD,
+            "Cancelled query should stop within the grace period"
+        );
+        // The query was planned before it timed out while executing
+        assert!(outcome.planning_time.unwrap() < outcome.execution_time);
+    }
+
+    /// Test that planning and execution are timed out apart, and classified apart
+    #[tokio::test]
+    async fn test_planning_and_execution_time_out_apart() {
+        use datafusion::prelude::SessionContext;
+
+        let query_context = QueryContext::new(
+            "SELECT count(*) FROM generate_series(1, 100000000000)".to_string(),
+            Arc::new(SessionContext::new()),
+        );
+        let timeout = QueryTimeout {
+            total: Duration::from_secs(60),
+            planning: None,
+            execution: Some(Duration::from_millis(100)),
+        };
+
+        let start = Instant::now();
+        let outcome = execute_query_with_timeout(&query_context, timeout, None).await;
+        assert!(outcome.timed_out, "Query should time out");
+        let error_msg = outcome.result.unwrap_err().to_string();
+        assert!(error_msg.contains(EXECUTION_TIMED_OUT), "{}", error_msg);
+        assert!(start.elapsed() < Duration::from_millis(100) + CANCELLATION_GRACE_PERIOD);
+
+        // The task doesn't run before the timeout is first checked
+        let timeout = QueryTimeout {
+            planning: Some(Duration::ZERO),
+            execution: None,
+            ..timeout
+        };
+        let outcome = execute_query_with_timeout(&query_context, timeout, None).await;
+        assert!(outcome.timed_out, "Planning should time out");
+        let error_msg = outcome.result.unwrap_err().to_string();
+        assert!(error_msg.contains(PLANNING_TIMED_OUT), "{}", error_msg);
+        assert_eq!(classify_error(&error_msg), FailureCategory::PlanningTimeout);
+        assert_eq!(outcome.planning_time, Some(outcome.execution_time));
     }
 
     /// Test that different seeds produce different results
@@ -592,18 +1709,14 @@ mod tests {
             seed: 42, // Default seed, will be overridden
             rounds: 1,
             queries_per_round: 2,
-            timeout_seconds: 2,
             log_path: None,
-            display_logs: false,
+            log_rotation_max_bytes: 1024,
             enable_tui: false,
-            sample_interval_secs: 5,
             max_column_count: 3,
             max_row_count: 10,
             max_expr_level: 2,
             max_group_by_count: 2,
-            max_table_count: 3,
-            max_insert_per_table: 20,
-            oracles: vec![crate::oracle::ConfiguredOracle::NoCrash],
+            ..RunnerConfig::default()
         };
 
         let mut results_by_seed = Vec::new();
@@ -669,6 +1782,160 @@ mod tests {
         println!("✅ Table counter reset test passed!");
     }
 
+    /// Test that a repeated query of a group reuses the earlier result only
+    /// in the same session
+    #[test]
+    fn test_cached_group_result() {
+        use datafusion::prelude::SessionContext;
+
+        let session = Arc::new(SessionContext::new());
+        let results = vec![QueryExecutionResult {
+            query_context: Arc::new(QueryContext::new(
+                "SELECT 1".to_string(),
+                Arc::clone(&session),
+            )),
+            result: Err(crate::common::fuzzer_err("Query execution failed: boom")),
+            truncated: None,
+        }];
+
+        let same = Arc::new(QueryContext::new(
+            "SELECT 1".to_string(),
+            Arc::clone(&session),
+        ));
+        let (index, cached) = cached_group_result(&results, &same).unwrap();
+        assert_eq!(index, 0);
+        assert!(Arc::ptr_eq(&cached.query_context, &same));
+        assert_eq!(
+            cached.result.unwrap_err().to_string(),
+            "Query execution failed: boom"
+        );
+
+        let other_session = Arc::new(QueryContext::new(
+            "SELECT 1".to_string(),
+            Arc::new(SessionContext::new()),
+        ));
+        assert!(cached_group_result(&results, &other_session).is_none());
+        let other_query = Arc::new(QueryContext::new("SELECT 2".to_string(), session));
+        assert!(cached_group_result(&results, &other_query).is_none());
+    }
+
+    /// Test that weighted oracle selection never picks oracles without weight
+    #[test]
+    fn test_weighted_oracle_selection() {
+        let mut picked = [0; 3];
+        for seed in 0..200 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            picked[weighted_index(&mut rng, &[0.0, 1.0, 3.0])] += 1;
+        }
+        assert_eq!(picked[0], 0);
+        assert!(picked[2] > picked[1] * 2, "{:?}", picked);
+    }
+
+    /// Test that schema evolution keeps some tables, adds new ones, and that
+    /// preparing a round replays the evolution deterministically
+    #[tokio::test]
+    async fn test_schema_evolution_across_rounds() {
+        init_available_data_types();
+        let ctx = Arc::new(GlobalContext::new(
+            RunnerConfig {
+                schema_evolution: true,
+                log_path: None,
+                ..RunnerConfig::default()
+            },
+            RuntimeContext::default(),
+            crate::fuzz_runner::create_fuzzer_stats(3),
+        ));
+        let table_names = |ctx: &Arc<GlobalContext>| -> Vec<String> {
+            ctx.runtime_context.tables().keys().cloned().collect()
+        };
+
+        let seed = ctx.runner_config.seed;
+        generate_datasets_for_round(round_dataset_seed(seed, 0), &ctx)
+            .await
+            .unwrap();
+        let first_round_tables = table_names(&ctx);
+        evolve_datasets_for_round(round_dataset_seed(seed, 1), &ctx)
+            .await
+            .unwrap();
+        let second_round_tables = table_names(&ctx);
+
+        let kept = second_round_tables
+            .iter()
+            .filter(|name| first_round_tables.contains(name))
+            .count();
+        assert!(kept >= first_round_tables.len() - first_round_tables.len() / 2);
+        assert!(second_round_tables.len() > kept);
+        for name in &second_round_tables {
+            assert!(
+                ctx.runtime_context
+                    .get_session_context()
+                    .table_exist(name.as_str())
+                    .unwrap()
+            );
+        }
+
+        prepare_round(&ctx, 2).await.unwrap();
+        assert_eq!(table_names(&ctx), second_round_tables);
+    }
+
+    /// Test that swarm testing generates the tables of a round with its
+    /// column type families only, and a replay picks the same families
+    #[tokio::test]
+    async fn test_swarm_testing_restricts_round_features() {
+        init_available_data_types();
+        let ctx = Arc::new(GlobalContext::new(
+            RunnerConfig {
+                swarm_testing: true,
+                log_path: None,
+                ..RunnerConfig::default()
+            },
+            RuntimeContext::default(),
+            crate::fuzz_runner::create_fuzzer_stats(3),
+        ));
+
+        let seed = ctx.runner_config.seed;
+        start_swarm_round(&ctx, ctx.runner_config.seed, 1);
+        let features = ctx.swarm_features().unwrap();
+        generate_datasets_for_round(round_dataset_seed(seed, 1), &ctx)
+            .await
+            .unwrap();
+        for table in ctx.runtime_context.tables().values() {
+            for column in &table.columns {
+                assert!(features.allows_type(&column.data_type), "{:?}", column);
+            }
+        }
+
+        ctx.set_swarm_features(None);
+        prepare_round(&ctx, 2).await.unwrap();
+        assert_eq!(ctx.swarm_features(), Some(features));
+    }
+
+    /// Test that findings of a seed pool run record the base seed of their
+    /// round as the seed, so they replay without the pool
+    #[test]
+    fn test_seed_pool_findings_replay_without_pool() {
+        let ctx = GlobalContext::new(
+            RunnerConfig {
+                seed_pool: Some(PathBuf::from("seed-pool.json")),
+                log_path: None,
+                ..RunnerConfig::default()
+            },
+            RuntimeContext::default(),
+            crate::fuzz_runner::create_fuzzer_stats(3),
+        );
+        let test_case = OracleTestCase {
+            query_id: QueryId::new(7, 0, 1),
+            round: 0,
+            query_index: 1,
+            query_seed: oracle_test_seed(7, 0, 1),
+            oracle_name: "NoCrash",
+        };
+
+        let config = finding_config(&ctx, &test_case);
+        assert_eq!(config.seed, 7);
+        assert_eq!(config.seed_pool, None);
+    }
+
     /// Helper function that runs the fuzzer and captures generated queries and table names
     async fn run_fuzzer_and_capture_results(ctx: Arc<GlobalContext>) -> (Vec<String>, Vec<String>) {
         // Use interior mutability to capture results during execution
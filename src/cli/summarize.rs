@@ -0,0 +1,541 @@
+//! `summarize` subcommand: a Markdown or HTML summary of a campaign directory,
+//! its `findings.jsonl` and `stats.json` (see `stats_export`), e.g. posted by
+//! a nightly job.
+//!
+//! A finding is new unless it has a known upstream issue (see
+//! `known_issues`) or a finding of an earlier campaign (`--known`) has the
+//! same fingerprint.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use serde::Deserialize;
+
+use crate::fuzz_runner::findings::Finding;
+use crate::fuzz_runner::known_issues::issue_label;
+use crate::fuzz_runner::query_shape::QueryFeature;
+use crate::fuzz_runner::stats_export::CampaignStats;
+
+/// Longest message shown in the findings table
+const MAX_MESSAGE_CHARS: usize = 120;
+/// Longest query shown in the slowest queries table
+const MAX_QUERY_CHARS: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SummaryFormat {
+    Markdown,
+    Html,
+}
+
+/// A section of the summary: a title, an optional sentence and a table
+struct Section {
+    title: &'static str,
+    intro: Option<String>,
+    header: &'static [&'static str],
+    rows: Vec<Vec<String>>,
+    /// Column of the table holding SQL, shown as code
+    code_column: Option<usize>,
+}
+
+/// Summary of a campaign from its `stats` and `findings`, rendered in `format`.
+/// `known` holds the fingerprints of the findings of earlier campaigns.
+pub fn format_campaign_summary(
+    stats: &CampaignStats,
+    findings: &[Finding],
+    known: &BTreeSet<String>,
+    format: SummaryFormat,
+) -> String {
+    let title = "DataFusion Fuzzer Campaign Summary";
+    let subtitle = format!(
+        "DataFusion {}, seed {}",
+        stats.datafusion_version, stats.seed
+    );
+    let sections = [
+        findings_section(findings, known),
+        known_issues_section(findings),
+        throughput_section(stats),
+        memory_section(stats),
+        feature_coverage_section(stats),
+        column_types_section(stats),
+        slowest_queries_section(stats),
+    ];
+
+    match format {
+        SummaryFormat::Markdown => render_markdown(title, &subtitle, &sections),
+        SummaryFormat::Html => render_html(title, &subtitle, &sections),
+    }
+}
+
+fn findings_section(findings: &[Finding], known: &BTreeSet<String>) -> Section {
+    // New findings first, each group in the order they were found
+    let mut rows: Vec<(bool, Vec<String>)> = findings
+        .iter()
+        .map(|finding| {
+            let status = match &finding.known_issue {
+                Some(issue) => Some(format!("known (issue {})", issue_label(issue))),
+                None => known
+                    .contains(&finding.fingerprint())
+                    .then(|| "known".to_string()),
+            };
+            let message = finding.message.lines().next().unwrap_or_default();
+            (
+                status.is_some(),
+                vec![
+                    status.unwrap_or_else(|| "new".to_string()),
+                    finding.query_id.to_string(),
+                    finding.category.to_string(),
+                    finding.oracle.clone(),
+                    truncate(message, MAX_MESSAGE_CHARS),
+                ],
+            )
+        })
+        .collect();
+    rows.sort_by_key(|(is_known, _)| *is_known);
+    let new_count = rows.iter().filter(|(is_known, _)| !is_known).count();
+
+    let intro = if findings.is_empty() {
+        "No findings.".to_string()
+    } else {
+        format!(
+            "{} findings: {} new, {} known.",
+            findings.len(),
+            new_count,
+            findings.len() - new_count
+        )
+    };
+    Section {
+        title: "Findings",
+        intro: Some(intro),
+        header: &["Status", "Query ID", "Category", "Oracle", "Message"],
+        rows: rows.into_iter().map(|(_, row)| row).collect(),
+        code_column: None,
+    }
+}
+
+fn known_issues_section(findings: &[Finding]) -> Section {
+    let mut hits: BTreeMap<&str, usize> = BTreeMap::new();
+    for issue in findings.iter().filter_map(|f| f.known_issue.as_deref()) {
+        *hits.entry(issue).or_default() += 1;
+    }
+    let mut hits: Vec<(&str, usize)> = hits.into_iter().collect();
+    hits.sort_by(|a, b| b.1.cmp(&a.1));
+    Section {
+        title: "Known Issues",
+        intro: hits
+            .is_empty()
+            .then(|| "No finding has a known upstream issue.".to_string()),
+        header: &["Issue", "URL", "Findings"],
+        rows: hits
+            .into_iter()
+            .map(|(issue, count)| vec![issue_label(issue), issue.to_string(), count.to_string()])
+            .collect(),
+        code_column: None,
+    }
+}
+
+fn throughput_section(stats: &CampaignStats) -> Section {
+    let percent = |count: u64| {
+        if stats.queries_executed > 0 {
+            count as f64 / stats.queries_executed as f64 * 100.0
+        } else {
+            0.0
+        }
+    };
+    let mut rows = vec![
+        vec![
+            "Running time".to_string(),
+            format_duration(stats.running_time_secs),
+        ],
+        vec!["Rounds".to_string(), stats.rounds_completed.to_string()],
+        vec![
+            "Oracle tests".to_string(),
+            stats.oracle_tests_completed.to_string(),
+        ],
+        vec!["Queries".to_string(), stats.queries_executed.to_string()],
+        vec![
+            "Queries per second".to_string(),
+            format!("{:.2}", stats.queries_per_second),
+        ],
+        vec![
+            "Success rate".to_string(),
+            format!("{:.2}%", percent(stats.queries_succeeded)),
+        ],
+        vec![
+            "Slow queries (>=90% of timeout)".to_string(),
+            format!(
+                "{} ({:.2}%)",
+                stats.queries_slow,
+                percent(stats.queries_slow)
+            ),
+        ],
+    ];
+    if let Some(runtime) = &stats.runtime {
+        rows.push(vec![
+            "Query runtime".to_string(),
+            format!(
+                "avg {:.2}ms, p90 {:.2}ms, p99 {:.2}ms",
+                runtime.avg_ms, runtime.p90_ms, runtime.p99_ms
+            ),
+        ]);
+    }
+
+    Section {
+        title: "Throughput",
+        intro: None,
+        header: &["Metric", "Value"],
+        rows,
+        code_column: None,
+    }
+}
+
+fn memory_section(stats: &CampaignStats) -> Section {
+    Section {
+        title: "Process Memory",
+        intro: stats
+            .memory_by_round
+            .is_empty()
+            .then(|| "No process memory was recorded.".to_string()),
+        header: &["Round", "RSS", "Elapsed"],
+        rows: stats
+            .memory_by_round
+            .iter()
+            .map(|round| {
+                vec![
+                    round.round.to_string(),
+                    format!("{:.2} MiB", round.rss_bytes as f64 / (1024.0 * 1024.0)),
+                    format_duration(round.elapsed_secs),
+                ]
+            })
+            .collect(),
+        code_column: None,
+    }
+}
+
+fn feature_coverage_section(stats: &CampaignStats) -> Section {
+    let rows: Vec<Vec<String>> = QueryFeature::ALL
+        .into_iter()
+        .filter_map(|feature| {
+            let share = stats.feature_coverage.get(feature.name())?;
+            Some(vec![
+                feature.name().to_string(),
+                format!("{:.1}%", share * 100.0),
+            ])
+        })
+        .collect();
+    Section {
+        title: "Feature Coverage",
+        intro: rows
+            .is_empty()
+            .then(|| "No query shapes were recorded.".to_string()),
+        header: &["Feature", "Share of queries"],
+        rows,
+        code_column: None,
+    }
+}
+
+fn column_types_section(stats: &CampaignStats) -> Section {
+    let mut types: Vec<(&String, &u64)> = stats.column_types.iter().collect();
+    types.sort_by(|a, b| b.1.cmp(a.1));
+    Section {
+        title: "Column Types",
+        intro: types
+            .is_empty()
+            .then(|| "No tables were generated.".to_string()),
+        header: &["Type", "Columns"],
+        rows: types
+            .into_iter()
+            .map(|(data_type, count)| vec![data_type.clone(), count.to_string()])
+            .collect(),
+        code_column: None,
+    }
+}
+
+fn slowest_queries_section(stats: &CampaignStats) -> Section {
+    Section {
+        title: "Slowest Queries",
+        intro: stats
+            .slowest_queries
+            .is_empty()
+            .then(|| "No queries were executed.".to_string()),
+        header: &["Query ID", "Time", "Query"],
+        rows: stats
+            .slowest_queries
+            .iter()
+            .map(|query| {
+                let sql = query.query.split_whitespace().collect::<Vec<_>>().join(" ");
+                vec![
+                    query.query_id.clone(),
+                    format!("{:.2}ms", query.execution_ms),
+                    truncate(&sql, MAX_QUERY_CHARS),
+                ]
+            })
+            .collect(),
+        code_column: Some(2),
+    }
+}
+
+fn render_markdown(title: &str, subtitle: &str, sections: &[Section]) -> String {
+    let mut summary = String::new();
+    let _ = writeln!(summary, "# {}\n\n{}", title, subtitle);
+    for section in sections {
+        let _ = writeln!(summary, "\n## {}\n", section.title);
+        if let Some(intro) = &section.intro {
+            let _ = writeln!(summary, "{}", intro);
+        }
+        if section.rows.is_empty() {
+            continue;
+        }
+        if section.intro.is_some() {
+            summary.push('\n');
+        }
+        let _ = writeln!(summary, "| {} |", section.header.join(" | "));
+        let _ = writeln!(summary, "|{}", "---|".repeat(section.header.len()));
+        for row in &section.rows {
+            let cells: Vec<String> = row
+                .iter()
+                .enumerate()
+                .map(|(index, cell)| {
+                    let cell = cell.replace('|', "\\|");
+                    if section.code_column == Some(index) {
+                        format!("`{}`", cell)
+                    } else {
+                        cell
+                    }
+                })
+                .collect();
+            let _ = writeln!(summary, "| {} |", cells.join(" | "));
+        }
+    }
+    summary
+}
+
+fn render_html(title: &str, subtitle: &str, sections: &[Section]) -> String {
+    let mut summary = String::new();
+    let _ = writeln!(
+        summary,
+        "<h1>{}</h1>\n<p>{}</p>",
+        escape_html(title),
+        escape_html(subtitle)
+    );
+    for section in sections {
+        let _ = writeln!(summary, "<h2>{}</h2>", escape_html(section.title));
+        if let Some(intro) = &section.intro {
+            let _ = writeln!(summary, "<p>{}</p>", escape_html(intro));
+        }
+        if section.rows.is_empty() {
+            continue;
+        }
+        summary.push_str("<table>\n<tr>");
+        for column in section.header {
+            let _ = write!(summary, "<th>{}</th>", escape_html(column));
+        }
+        summary.push_str("</tr>\n");
+        for row in &section.rows {
+            summary.push_str("<tr>");
+            for (index, cell) in row.iter().enumerate() {
+                if section.code_column == Some(index) {
+                    let _ = write!(summary, "<td><code>{}</code></td>", escape_html(cell));
+                } else {
+                    let _ = write!(summary, "<td>{}</td>", escape_html(cell));
+                }
+            }
+            summary.push_str("</tr>\n");
+        }
+        summary.push_str("</table>\n");
+    }
+    summary
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+fn format_duration(total_secs: f64) -> String {
+    let hours = (total_secs / 3600.0) as u64;
+    let minutes = ((total_secs % 3600.0) / 60.0) as u64;
+    let seconds = total_secs % 60.0;
+    if hours > 0 {
+        format!("{}h {}m {:.0}s", hours, minutes, seconds)
+    } else if minutes > 0 {
+        format!("{}m {:.0}s", minutes, seconds)
+    } else {
+        format!("{:.2}s", seconds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::fuzz_runner::query_id::QueryId;
+    use crate::fuzz_runner::stats_export::{RoundMemoryStats, SlowQuery};
+    use crate::triage::FailureCategory;
+
+    fn make_finding(index: u32, category: FailureCategory, message: &str) -> Finding {
+        Finding {
+            query_id: QueryId::new(42, 0, index),
+            round: 1,
+            query_index: index + 1,
+            query_seed: 242 + index as u64,
+            oracle: "TlpWhereOracle".to_string(),
+            datafusion_version: "52.3.0".to_string(),
+            category,
+            message: message.to_string(),
+            queries: vec!["SELECT 1".to_string()],
+            report: None,
+            result_files: Vec::new(),
+            datafusion_cli_reproduced: None,
+            config: String::new(),
+            table_files: Vec::new(),
+            timing: None,
+            swarm_features: None,
+            known_issue: None,
+        }
+    }
+
+    fn make_stats() -> CampaignStats {
+        CampaignStats {
+            datafusion_version: "52.3.0".to_string(),
+            seed: 42,
+            rounds_completed: 3,
+            oracle_tests_completed: 30,
+            queries_executed: 200,
+            queries_succeeded: 150,
+            queries_slow: 2,
+            findings: 2,
+            running_time_secs: 3725.0,
+            queries_per_second: 0.05,
+            runtime: None,
+            slowest_queries: vec![SlowQuery {
+                query_id: "42-r2-q7-s1".to_string(),
+                query: "SELECT a || b\nFROM t1 WHERE a < 'x'".to_string(),
+                execution_ms: 812.5,
+            }],
+            feature_coverage: BTreeMap::from([
+                ("joins".to_string(), 0.25),
+                ("tables".to_string(), 1.0),
+            ]),
+            column_types: BTreeMap::from([("Boolean".to_string(), 2), ("Int64".to_string(), 7)]),
+            memory_by_round: vec![RoundMemoryStats {
+                round: 1,
+                rss_bytes: 96 * 1024 * 1024,
+                elapsed_secs: 90.0,
+            }],
+        }
+    }
+
+    #[test]
+    fn summarizes_campaign_as_markdown() {
+        let mut findings = vec![
+            make_finding(0, FailureCategory::WrongResult, "Row count 3 != 5 for t1"),
+            make_finding(1, FailureCategory::Panic, "index out of bounds: 12\nat t2"),
+            make_finding(2, FailureCategory::InternalError, "Failed to coerce"),
+        ];
+        findings[2].known_issue =
+            Some("https://github.com/apache/datafusion/issues/123".to_string());
+        // A campaign found the same wrong result with other tables and counts
+        let known = BTreeSet::from([make_finding(
+            7,
+            FailureCategory::WrongResult,
+            "Row count 10 != 2 for t3",
+        )
+        .fingerprint()]);
+
+        let summary =
+            format_campaign_summary(&make_stats(), &findings, &known, SummaryFormat::Markdown);
+        assert!(
+            summary.starts_with(
+                "# DataFusion Fuzzer Campaign Summary\n\nDataFusion 52.3.0, seed 42\n"
+            )
+        );
+        assert!(
+            summary.contains("3 findings: 1 new, 2 known."),
+            "{}",
+            summary
+        );
+        // New findings come first
+        let new_row = summary
+            .find("| new | 42-r1-q2 | Panic | TlpWhereOracle | index out of bounds: 12 |")
+            .unwrap();
+        let known_row = summary.find("| known | 42-r1-q1 | WrongResult |").unwrap();
+        assert!(new_row < known_row, "{}", summary);
+        assert!(
+            summary.contains("| known (issue #123) | 42-r1-q3 | InternalError |"),
+            "{}",
+            summary
+        );
+        assert!(
+            summary.contains("| #123 | https://github.com/apache/datafusion/issues/123 | 1 |"),
+            "{}",
+            summary
+        );
+
+        assert!(
+            summary.contains("| Running time | 1h 2m 5s |"),
+            "{}",
+            summary
+        );
+        assert!(summary.contains("| Success rate | 75.00% |"), "{}", summary);
+        assert!(
+            summary.contains("| 1 | 96.00 MiB | 1m 30s |"),
+            "{}",
+            summary
+        );
+        // Features in the order of the config, types by their column count
+        let tables = summary.find("| tables | 100.0% |").unwrap();
+        let joins = summary.find("| joins | 25.0% |").unwrap();
+        assert!(tables < joins, "{}", summary);
+        let int64 = summary.find("| Int64 | 7 |").unwrap();
+        let boolean = summary.find("| Boolean | 2 |").unwrap();
+        assert!(int64 < boolean, "{}", summary);
+        assert!(
+            summary
+                .contains("| 42-r2-q7-s1 | 812.50ms | `SELECT a \\|\\| b FROM t1 WHERE a < 'x'` |"),
+            "{}",
+            summary
+        );
+    }
+
+    #[test]
+    fn summarizes_campaign_as_html() {
+        let summary = format_campaign_summary(
+            &CampaignStats::default(),
+            &[],
+            &BTreeSet::new(),
+            SummaryFormat::Html,
+        );
+        assert!(
+            summary.contains("<h2>Findings</h2>\n<p>No findings.</p>"),
+            "{}",
+            summary
+        );
+        assert!(
+            summary.contains("<p>No query shapes were recorded.</p>"),
+            "{}",
+            summary
+        );
+
+        let summary =
+            format_campaign_summary(&make_stats(), &[], &BTreeSet::new(), SummaryFormat::Html);
+        assert!(
+            summary.contains(
+                "<tr><td>42-r2-q7-s1</td><td>812.50ms</td>\
+                 <td><code>SELECT a || b FROM t1 WHERE a &lt; 'x'</code></td></tr>"
+            ),
+            "{}",
+            summary
+        );
+    }
+}
@@ -85,7 +85,12 @@ impl Widget for &TuiApp {
         let mut lines = vec![
             Line::from(vec![
                 "Rounds: ".into(),
-                format!("{}/{}", stats.rounds_completed, stats.total_rounds).yellow(),
+                if stats.total_rounds == 0 {
+                    // Time-limited run without a round limit
+                    stats.rounds_completed.to_string().yellow()
+                } else {
+                    format!("{}/{}", stats.rounds_completed, stats.total_rounds).yellow()
+                },
             ]),
             Line::from(vec![
                 "Queries Executed: ".into(),
@@ -108,6 +113,10 @@ impl Widget for &TuiApp {
                 )
                 .yellow(),
             ]),
+            Line::from(vec![
+                "Findings: ".into(),
+                stats.findings.to_string().yellow(),
+            ]),
             Line::from(vec![
                 "Queries Per Second: ".into(),
                 format!("{:.2}", stats.queries_per_second).yellow(),
@@ -1,20 +1,31 @@
 use core::fmt;
 use std::io;
-use std::sync::OnceLock;
+use std::sync::{Arc, OnceLock};
 
+use datafusion::catalog::{CatalogProvider, MemoryCatalogProvider, MemorySchemaProvider};
+use datafusion::common::TableReference;
+use datafusion::prelude::SessionContext;
 use datafusion::{arrow::datatypes::DataType, error::DataFusionError};
+use rand::Rng;
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
+
+use crate::datasource_generator::file_dataset::FileDataset;
+use value_summary::ColumnValueSummary;
 
 pub mod rng;
 pub mod util;
 pub mod value_generator;
+pub mod value_summary;
 
 // How to add a new data type:
 // 1. Add enum variant and update all match statements
 //    --> src/common/mod.rs
-// 2. Add SQL value generation
-//    --> src/datasource_generator/dataset_generator.rs
-// 3. Add scalar literal generation
-//    --> src/query_generator/expr_literal_gen.rs
+// 2. Add value generation, used for both table rows and scalar literals
+//    --> src/common/value_generator.rs
+// 3. Map the Arrow type back to the fuzzer type, so literals can be generated
+//    wherever an expression of that type is expected
+//    --> `FuzzerDataType::from_datafusion_type` in src/common/mod.rs
 // 4. Add new operators (if needed)
 //    --> src/query_generator/expr_impl.rs
 // 5. Add operator enum variants (if needed)
@@ -30,7 +41,8 @@ pub mod value_generator;
 /// FuzzerDataType is a logical type, it won't include detail value like the
 /// timezone string inside `Timestamp` type. Those details will be specified
 /// inside `GeneartedValue` type.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum FuzzerDataType {
     Int32,
     Int64,
@@ -261,16 +273,79 @@ pub fn get_time_data_types() -> Vec<FuzzerDataType> {
         .collect()
 }
 
+/// Catalog of tables that aren't given one, see `multi_schema`
+pub const DEFAULT_CATALOG: &str = "datafusion";
+/// Schema of tables that aren't given one
+pub const DEFAULT_SCHEMA: &str = "public";
+
+/// Catalog and schema a table is created in
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TableNamespace {
+    pub catalog: String,
+    pub schema: String,
+}
+
+impl TableNamespace {
+    pub fn new(catalog: &str, schema: &str) -> Self {
+        Self {
+            catalog: catalog.to_string(),
+            schema: schema.to_string(),
+        }
+    }
+
+    /// Statements creating the catalog and the schema if they don't exist
+    pub fn create_statements(&self) -> Vec<String> {
+        let mut statements = Vec::new();
+        if self.catalog != DEFAULT_CATALOG {
+            statements.push(format!(
+                "CREATE DATABASE IF NOT EXISTS {}",
+                util::quote_identifier(&self.catalog)
+            ));
+        }
+        if self.schema != DEFAULT_SCHEMA || self.catalog != DEFAULT_CATALOG {
+            statements.push(format!(
+                "CREATE SCHEMA IF NOT EXISTS {}.{}",
+                util::quote_identifier(&self.catalog),
+                util::quote_identifier(&self.schema)
+            ));
+        }
+        statements
+    }
+
+    /// Register the catalog and the schema in `session_context` if they don't
+    /// exist, like [`Self::create_statements`] without going through SQL
+    pub fn register(&self, session_context: &SessionContext) -> datafusion::error::Result<()> {
+        let catalog = session_context.catalog(&self.catalog).unwrap_or_else(|| {
+            let catalog: Arc<dyn CatalogProvider> = Arc::new(MemoryCatalogProvider::new());
+            session_context.register_catalog(&self.catalog, Arc::clone(&catalog));
+            catalog
+        });
+        if catalog.schema(&self.schema).is_none() {
+            catalog.register_schema(&self.schema, Arc::new(MemorySchemaProvider::new()))?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct LogicalTable {
     pub name: String,
     pub columns: Vec<LogicalColumn>,
+    /// Files the table is read from, `None` for in-memory tables and views
+    pub files: Option<FileDataset>,
+    /// Catalog and schema of the table, `None` for the default ones. Table
+    /// names are unique across namespaces.
+    pub namespace: Option<TableNamespace>,
 }
 
 #[derive(Debug, Clone)]
 pub struct LogicalColumn {
     pub name: String,
     pub data_type: FuzzerDataType,
+    /// Values inserted into the column, `None` if they are unknown (e.g. for
+    /// views). Shared, as generators copy the columns of every table they
+    /// read.
+    pub value_summary: Option<Arc<ColumnValueSummary>>,
 }
 
 #[derive(Debug, Clone)]
@@ -285,11 +360,90 @@ impl LogicalTable {
         Self {
             name,
             columns: Vec::new(),
+            files: None,
+            namespace: None,
         }
     }
 
     pub fn with_columns(name: String, columns: Vec<LogicalColumn>) -> Self {
-        Self { name, columns }
+        Self {
+            name,
+            columns,
+            files: None,
+            namespace: None,
+        }
+    }
+
+    /// Number of rows inserted into the table, if known
+    pub fn row_count(&self) -> Option<u64> {
+        self.columns
+            .first()?
+            .value_summary
+            .as_deref()
+            .map(|summary| summary.row_count)
+    }
+
+    /// Shortest reference to the table from the default catalog and schema:
+    /// `t`, `schema.t` or `catalog.schema.t`
+    pub fn table_reference(&self) -> TableReference {
+        match &self.namespace {
+            Some(namespace) if namespace.catalog != DEFAULT_CATALOG => TableReference::full(
+                namespace.catalog.as_str(),
+                namespace.schema.as_str(),
+                self.name.as_str(),
+            ),
+            Some(namespace) if namespace.schema != DEFAULT_SCHEMA => {
+                TableReference::partial(namespace.schema.as_str(), self.name.as_str())
+            }
+            _ => TableReference::bare(self.name.as_str()),
+        }
+    }
+
+    /// [`Self::table_reference`] as SQL, with quoted identifiers
+    pub fn sql_name(&self) -> String {
+        match self.table_reference() {
+            TableReference::Bare { table } => util::quote_identifier(&table),
+            TableReference::Partial { schema, table } => format!(
+                "{}.{}",
+                util::quote_identifier(&schema),
+                util::quote_identifier(&table)
+            ),
+            TableReference::Full {
+                catalog,
+                schema,
+                table,
+            } => format!(
+                "{}.{}.{}",
+                util::quote_identifier(&catalog),
+                util::quote_identifier(&schema),
+                util::quote_identifier(&table)
+            ),
+        }
+    }
+
+    /// A random reference to the table as SQL, qualified with as many levels
+    /// as resolve from the default catalog and schema, e.g. `t`, `public.t`
+    /// or `datafusion.public.t` for a table in the default schema
+    pub fn random_sql_name(&self, rng: &mut StdRng) -> String {
+        let namespace = self
+            .namespace
+            .clone()
+            .unwrap_or_else(|| TableNamespace::new(DEFAULT_CATALOG, DEFAULT_SCHEMA));
+        let min_levels = match self.table_reference() {
+            TableReference::Bare { .. } => 1,
+            TableReference::Partial { .. } => 2,
+            TableReference::Full { .. } => 3,
+        };
+        let parts = [
+            namespace.catalog.as_str(),
+            namespace.schema.as_str(),
+            self.name.as_str(),
+        ];
+        parts[3 - rng.random_range(min_levels..=3)..]
+            .iter()
+            .map(|part| util::quote_identifier(part))
+            .collect::<Vec<_>>()
+            .join(".")
     }
 }
 
@@ -377,6 +531,34 @@ mod tests {
     use super::*;
     use crate::common::rng::rng_from_seed;
 
+    #[test]
+    fn qualifies_tables_outside_the_default_schema() {
+        let mut table = LogicalTable::new("t1".to_string());
+        assert_eq!(table.sql_name(), "t1");
+
+        table.namespace = Some(TableNamespace::new(DEFAULT_CATALOG, "s1"));
+        assert_eq!(table.sql_name(), "s1.t1");
+        assert_eq!(table.table_reference(), TableReference::partial("s1", "t1"));
+
+        table.namespace = Some(TableNamespace::new("c1", "select"));
+        assert_eq!(table.sql_name(), "c1.\"select\".t1");
+        assert_eq!(
+            table.namespace.as_ref().unwrap().create_statements(),
+            [
+                "CREATE DATABASE IF NOT EXISTS c1",
+                "CREATE SCHEMA IF NOT EXISTS c1.\"select\""
+            ]
+        );
+
+        // Tables in the default schema are referred to at every level
+        let table = LogicalTable::new("t2".to_string());
+        let mut rng = rng_from_seed(0);
+        let names: std::collections::HashSet<String> =
+            (0..32).map(|_| table.random_sql_name(&mut rng)).collect();
+        assert_eq!(names.len(), 3);
+        assert!(names.contains("datafusion.public.t2"));
+    }
+
     #[test]
     fn test_simplified_decimal_type() {
         // Test that the simplified Decimal type works correctly
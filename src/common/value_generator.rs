@@ -34,6 +34,8 @@ pub struct ValueGenerationConfig {
     pub int_range: (i32, i32),
     pub uint_range: (u32, u32),
     pub float_range: (f64, f64),
+    /// Pick timestamp time zones from the extended corpus
+    pub extended_timezones: bool,
 }
 
 impl Default for ValueGenerationConfig {
@@ -44,10 +46,45 @@ impl Default for ValueGenerationConfig {
             int_range: (-100, 100),
             uint_range: (0, 200),
             float_range: (-100.0, 100.0),
+            extended_timezones: false,
         }
     }
 }
 
+/// Zones with DST transitions, including the 30 minute shift of
+/// `Australia/Lord_Howe`, and named zones with minute offsets
+pub const DST_TIMEZONES: [&str; 8] = [
+    "America/New_York",
+    "Europe/Berlin",
+    "Europe/London",
+    "America/Santiago",
+    "Australia/Lord_Howe",
+    "Pacific/Chatham",
+    "Asia/Kathmandu",
+    "America/St_Johns",
+];
+
+/// Fixed offsets with minutes, at the extremes, and in every accepted format
+pub const OFFSET_TIMEZONES: [&str; 7] = [
+    "+05:45", "-03:30", "+12:45", "+14:00", "-12:00", "+0530", "-09",
+];
+
+/// Unusual names that still parse: `Etc/GMT+5` is UTC-5, legacy POSIX-style
+/// zones and aliases of UTC
+pub const UNUSUAL_TIMEZONES: [&str; 5] = ["Etc/GMT+5", "EST5EDT", "Zulu", "GMT0", "-00:00"];
+
+/// A time zone from the extended corpus, or none
+fn extended_timezone(rng: &mut StdRng) -> Option<String> {
+    let corpus: Vec<&str> = DST_TIMEZONES
+        .iter()
+        .chain(&OFFSET_TIMEZONES)
+        .chain(&UNUSUAL_TIMEZONES)
+        .copied()
+        .collect();
+    let index = rng.random_range(0..=corpus.len());
+    corpus.get(index).map(|tz| tz.to_string())
+}
+
 /// Core value generation logic shared by both functions
 pub fn generate_value(
     rng: &mut StdRng,
@@ -131,6 +168,10 @@ pub fn generate_value(
             let max_nanoseconds = nanoseconds_per_day * days_in_100_years;
             let nanoseconds_since_epoch = rng.random_range(0..=max_nanoseconds);
 
+            if config.extended_timezones {
+                return GeneratedValue::Timestamp(nanoseconds_since_epoch, extended_timezone(rng));
+            }
+
             // Randomly decide whether to add a timezone
             let timezones = vec![
                 None,
@@ -693,6 +734,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_extended_timezones() {
+        let config = ValueGenerationConfig {
+            nullable: false,
+            extended_timezones: true,
+            ..Default::default()
+        };
+        let corpus: Vec<&str> = DST_TIMEZONES
+            .iter()
+            .chain(&OFFSET_TIMEZONES)
+            .chain(&UNUSUAL_TIMEZONES)
+            .copied()
+            .collect();
+
+        let mut rng = rng_from_seed(42);
+        let mut with_timezone = 0;
+        for _ in 0..200 {
+            match generate_value(&mut rng, &FuzzerDataType::Timestamp, &config) {
+                GeneratedValue::Timestamp(_, Some(tz)) => {
+                    assert!(corpus.contains(&tz.as_str()), "unexpected time zone {tz}");
+                    with_timezone += 1;
+                }
+                GeneratedValue::Timestamp(_, None) => {}
+                value => panic!("Expected Timestamp value, got: {:?}", value),
+            }
+        }
+        assert!(with_timezone > 100);
+    }
+
     #[test]
     fn test_timestamp_tz_type_conversions() {
         // Test that TimestampTz type conversions work correctly
@@ -740,6 +810,7 @@ mod tests {
             int_range: (-100, 100),
             uint_range: (0, 200),
             float_range: (-100.0, 100.0),
+            extended_timezones: false,
         };
 
         // Generate multiple dates and verify they are valid
@@ -802,6 +873,7 @@ mod tests {
             int_range: (-100, 100),
             uint_range: (0, 200),
             float_range: (-100.0, 100.0),
+            extended_timezones: false,
         };
 
         // Generate multiple timestamps and verify they are valid
@@ -1061,6 +1133,7 @@ mod tests {
             int_range: (-100, 100),
             uint_range: (0, 200),
             float_range: (-100.0, 100.0),
+            extended_timezones: false,
         };
 
         // Generate multiple strings and verify they are SQL-safe
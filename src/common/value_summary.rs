@@ -0,0 +1,96 @@
+use std::cmp::Ordering;
+
+use datafusion::scalar::ScalarValue;
+
+/// Maximum number of distinct values of a column kept as samples
+pub const MAX_SAMPLE_VALUES: usize = 16;
+
+/// Summary of the values inserted into a column, recorded while its table is
+/// generated.
+///
+/// The expression generator builds predicates that select some rows from it
+/// (e.g. `c0 BETWEEN <min> AND <sample>`), and the Cardinality oracle computes
+/// the rows matching a predicate without running a query.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ColumnValueSummary {
+    pub row_count: u64,
+    pub null_count: u64,
+    pub min: Option<ScalarValue>,
+    pub max: Option<ScalarValue>,
+    /// Up to `MAX_SAMPLE_VALUES` distinct non-null values, in insertion order
+    pub samples: Vec<ScalarValue>,
+    /// All inserted values including NULLs, the n-th value is in the n-th row
+    pub values: Vec<ScalarValue>,
+}
+
+impl ColumnValueSummary {
+    pub fn record(&mut self, value: ScalarValue) {
+        self.row_count += 1;
+        self.values.push(value.clone());
+        if value.is_null() {
+            self.null_count += 1;
+            return;
+        }
+
+        if self
+            .min
+            .as_ref()
+            .is_none_or(|min| value.partial_cmp(min) == Some(Ordering::Less))
+        {
+            self.min = Some(value.clone());
+        }
+        if self
+            .max
+            .as_ref()
+            .is_none_or(|max| value.partial_cmp(max) == Some(Ordering::Greater))
+        {
+            self.max = Some(value.clone());
+        }
+        if self.samples.len() < MAX_SAMPLE_VALUES && !self.samples.contains(&value) {
+            self.samples.push(value);
+        }
+    }
+
+    pub fn non_null_count(&self) -> u64 {
+        self.row_count - self.null_count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_counts_bounds_and_distinct_samples() {
+        let mut summary = ColumnValueSummary::default();
+        for value in [Some(3), None, Some(-1), Some(3), Some(7)] {
+            summary.record(ScalarValue::Int64(value));
+        }
+
+        assert_eq!(summary.row_count, 5);
+        assert_eq!(summary.null_count, 1);
+        assert_eq!(summary.non_null_count(), 4);
+        assert_eq!(summary.values[1], ScalarValue::Int64(None));
+        assert_eq!(summary.min, Some(ScalarValue::Int64(Some(-1))));
+        assert_eq!(summary.max, Some(ScalarValue::Int64(Some(7))));
+        assert_eq!(
+            summary.samples,
+            vec![
+                ScalarValue::Int64(Some(3)),
+                ScalarValue::Int64(Some(-1)),
+                ScalarValue::Int64(Some(7)),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_a_bounded_number_of_samples() {
+        let mut summary = ColumnValueSummary::default();
+        for value in 0..100 {
+            summary.record(ScalarValue::Int32(Some(value)));
+        }
+
+        assert_eq!(summary.samples.len(), MAX_SAMPLE_VALUES);
+        assert_eq!(summary.max, Some(ScalarValue::Int32(Some(99))));
+    }
+}
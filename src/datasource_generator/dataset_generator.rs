@@ -5,10 +5,32 @@ use rand::Rng;
 use rand::rngs::StdRng;
 use tracing::info;
 
-use crate::common::value_generator::generate_value;
-use crate::common::{FuzzerDataType, LogicalColumn, LogicalTable, get_available_data_types};
+use crate::common::util::quote_identifier;
+use crate::common::value_generator::{GeneratedValue, generate_value};
+use crate::common::value_summary::ColumnValueSummary;
+use crate::common::{FuzzerDataType, LogicalColumn, LogicalTable, TableNamespace};
+use crate::datasource_generator::file_dataset::{
+    DatasetFormat, DatasetStore, FileDataset, ParquetLayout, csv_supports, parquet_supports,
+};
+use crate::engine::mirror_statements;
+use crate::fuzz_runner::record_column_types;
 use crate::{common::rng::rng_from_seed, fuzz_context::GlobalContext};
 
+/// SQL keywords tables and columns are named after, see `keyword_identifiers`
+/// and `tricky_identifiers`
+const KEYWORD_NAMES: [&str; 10] = [
+    "select", "from", "where", "order", "group", "limit", "user", "table", "join", "values",
+];
+
+/// `(catalog, schema)` tables are created in besides the default ones, see
+/// `multi_schema`
+const OTHER_NAMESPACES: [(&str, &str); 4] = [
+    ("datafusion", "s1"),
+    ("datafusion", "s2"),
+    ("c1", "public"),
+    ("c1", "s1"),
+];
+
 pub struct DatasetGenerator {
     rng: StdRng,
     ctx: Arc<GlobalContext>,
@@ -25,33 +47,59 @@ impl DatasetGenerator {
     // TODO(coverage): support NULLs in data generation
     pub async fn generate_dataset(&mut self) -> Result<LogicalTable> {
         // ==== Generate schema ====
-        let table_name = self.ctx.runtime_context.next_table_name(); // t1, t2, ...
+        let mut table_name = self.ctx.runtime_context.next_table_name(); // t1, t2, ...
+        if self.ctx.runner_config.keyword_identifiers && self.rng.random_bool(0.2) {
+            let keyword = self.random_keyword();
+            if self.ctx.runtime_context.get_table(keyword).is_none() {
+                table_name = keyword.to_string();
+            }
+        }
+        let mut logical_table = LogicalTable::new(table_name.clone());
+        if self.ctx.runner_config.multi_schema && self.rng.random_bool(0.5) {
+            let (catalog, schema) =
+                OTHER_NAMESPACES[self.rng.random_range(0..OTHER_NAMESPACES.len())];
+            logical_table.namespace = Some(TableNamespace::new(catalog, schema));
+        }
+        let table_sql = logical_table.sql_name();
         let cfg_max_col_count = self.ctx.runner_config.max_column_count;
 
         let num_columns = self.rng.random_range(1..=cfg_max_col_count);
         let mut column_definitions = Vec::new();
+        let mut column_names: Vec<String> = Vec::new();
         let mut column_fuzzer_types = Vec::new();
-        let available_types = get_available_data_types();
+        let available_types = self.ctx.generated_data_types();
 
         // Generate column definitions
         for i in 0..num_columns {
             let fuzzer_column_type =
                 &available_types[self.rng.random_range(0..available_types.len())];
-            let column_name = format!(
+            let mut column_name = format!(
                 "col_{table_name}_{}_{}",
                 i + 1,
                 fuzzer_column_type.display_name()
             );
+            if self.ctx.runner_config.tricky_identifiers && self.rng.random_bool(0.5) {
+                column_name = self.tricky_column_name(&column_name, &column_names);
+            } else if self.ctx.runner_config.keyword_identifiers && self.rng.random_bool(0.2) {
+                let keyword = self.random_keyword();
+                if !column_names
+                    .iter()
+                    .any(|name| name.eq_ignore_ascii_case(keyword))
+                {
+                    column_name = keyword.to_string();
+                }
+            }
             let sql_type = fuzzer_column_type.to_sql_type();
             // TODO(coverage): enforce `NOT NULL` somethimes.
-            column_definitions.push(format!("{} {}", column_name, sql_type));
+            column_definitions.push(format!("{} {}", quote_identifier(&column_name), sql_type));
+            column_names.push(column_name);
             column_fuzzer_types.push(fuzzer_column_type.clone());
         }
 
         // Generate CREATE TABLE SQL
         let create_table_sql = format!(
             "CREATE TABLE {} (\n    {}\n);",
-            table_name,
+            table_sql,
             column_definitions.join(",\n    ")
         );
 
@@ -66,13 +114,15 @@ impl DatasetGenerator {
             std::cmp::min(actual_row_count, cfg_max_insert_per_table as u64);
 
         let mut insert_statements = Vec::new();
+        let mut value_summaries = vec![ColumnValueSummary::default(); column_fuzzer_types.len()];
         for _ in 0..num_insert_statements {
             let mut values = Vec::new();
-            for fuzzer_type in &column_fuzzer_types {
-                let value = self.generate_sql_value(fuzzer_type);
-                values.push(value);
+            for (fuzzer_type, summary) in column_fuzzer_types.iter().zip(&mut value_summaries) {
+                let value = self.generate_value(fuzzer_type);
+                summary.record(value.to_scalar_value());
+                values.push(value.to_sql_string());
             }
-            let insert_sql = format!("INSERT INTO {} VALUES ({});", table_name, values.join(", "));
+            let insert_sql = format!("INSERT INTO {} VALUES ({});", table_sql, values.join(", "));
             insert_statements.push(insert_sql);
         }
 
@@ -84,6 +134,14 @@ impl DatasetGenerator {
         // ==== Execute SQL statements ====
         let df_ctx = self.ctx.runtime_context.get_session_context();
 
+        // Create the table's catalog and schema
+        if let Some(namespace) = &logical_table.namespace {
+            for create_sql in namespace.create_statements() {
+                info!("Executing SQL: {}", create_sql);
+                df_ctx.sql(&create_sql).await?.collect().await?;
+            }
+        }
+
         // Execute CREATE TABLE
         let create_result = df_ctx.sql(&create_table_sql).await?.collect().await;
 
@@ -104,39 +162,335 @@ impl DatasetGenerator {
             }
         }
 
+        // Sanity check: every INSERT adds exactly one row
+        let row_count = df_ctx
+            .table(logical_table.table_reference())
+            .await?
+            .count()
+            .await?;
+        if row_count as u64 != num_insert_statements {
+            return Err(datafusion::error::DataFusionError::External(
+                format!(
+                    "Table {} has {} rows after {} single-row INSERTs",
+                    table_name, row_count, num_insert_statements
+                )
+                .into(),
+            ));
+        }
+
+        // ==== Move the table to files ====
+        let files = match self.ctx.runner_config.dataset_format {
+            DatasetFormat::Memory => None,
+            format => {
+                self.move_to_files(&logical_table, &column_definitions, format)
+                    .await?
+            }
+        };
+
+        // ==== Mirror the table to the other engines ====
+        let drop_table_sql = format!("DROP TABLE IF EXISTS {};", quote_identifier(&table_name));
+        let mirrored_statements: Vec<&str> = std::iter::once(drop_table_sql.as_str())
+            .chain(std::iter::once(create_table_sql.as_str()))
+            .chain(insert_statements.iter().map(String::as_str))
+            .collect();
+        mirror_statements(&self.ctx.engines, &mirrored_statements)
+            .await
+            .map_err(|e| {
+                datafusion::error::DataFusionError::External(
+                    format!("Failed to mirror table {}: {}", table_name, e).into(),
+                )
+            })?;
+
         // ==== Register table in fuzzer context ====
-        let logical_columns: Vec<LogicalColumn> = column_fuzzer_types
-            .iter()
-            .enumerate()
-            .map(|(i, fuzzer_type)| {
-                let column_name =
-                    format!("col_{table_name}_{}_{}", i + 1, fuzzer_type.display_name());
-                LogicalColumn {
-                    name: column_name,
-                    data_type: fuzzer_type.clone(),
-                }
+        let logical_columns: Vec<LogicalColumn> = column_names
+            .into_iter()
+            .zip(column_fuzzer_types)
+            .zip(value_summaries)
+            .map(|((name, data_type), value_summary)| LogicalColumn {
+                name,
+                data_type,
+                value_summary: Some(Arc::new(value_summary)),
             })
             .collect();
 
-        let logical_table = LogicalTable::with_columns(table_name.clone(), logical_columns);
+        record_column_types(&self.ctx.fuzzer_stats, &logical_columns);
+        logical_table.columns = logical_columns;
+        logical_table.files = files;
         self.ctx
             .runtime_context
-            .registered_tables
-            .write()
-            .unwrap()
-            .insert(table_name, Arc::new(logical_table.clone()));
+            .register_table(Arc::new(logical_table.clone()));
 
         Ok(logical_table)
     }
 
+    /// Write the in-memory table `table` to a file of `format` and replace it
+    /// with a listing table over the file, registered through the API or with
+    /// `CREATE EXTERNAL TABLE` and the table's `column_definitions`. Tables
+    /// with columns the format can't store stay in memory.
+    async fn move_to_files(
+        &mut self,
+        table: &LogicalTable,
+        column_definitions: &[String],
+        format: DatasetFormat,
+    ) -> Result<Option<FileDataset>> {
+        let df_ctx = self.ctx.runtime_context.get_session_context();
+        let table_name = table.name.as_str();
+        let table_ref = table.table_reference();
+        let df = df_ctx.table(table_ref.clone()).await?;
+        let schema = Arc::new(df.schema().as_arrow().clone());
+        let base_dir = self.ctx.dataset_dir();
+        let base_dir = base_dir.as_deref();
+
+        let mut dataset = match format {
+            DatasetFormat::Csv => {
+                if !csv_supports(&schema) {
+                    return Ok(None);
+                }
+                FileDataset::write_csv(&mut self.rng, base_dir, table_name, df).await?
+            }
+            _ => {
+                if !parquet_supports(&schema) {
+                    return Ok(None);
+                }
+                let batches = df.collect().await?;
+                let layout = if self.ctx.runner_config.parquet_pruning_stress {
+                    ParquetLayout::pruning_stress(&mut self.rng, schema.fields().len())
+                } else {
+                    ParquetLayout::default()
+                };
+                FileDataset::write_parquet(
+                    &mut self.rng,
+                    base_dir,
+                    table_name,
+                    schema,
+                    &batches,
+                    layout,
+                )?
+            }
+        };
+        if self.ctx.runner_config.dataset_store == DatasetStore::Memory {
+            dataset.copy_to_memory_store().await?;
+        }
+
+        df_ctx.deregister_table(table_ref)?;
+        if self.rng.random_bool(0.5) {
+            dataset.register_store(&df_ctx);
+            let create_external_table_sql =
+                dataset.create_external_table_sql(&table.sql_name(), column_definitions);
+            info!(
+                "Executing CREATE EXTERNAL TABLE SQL: {}",
+                create_external_table_sql
+            );
+            let create_result = df_ctx
+                .sql(&create_external_table_sql)
+                .await?
+                .collect()
+                .await;
+            if let Err(e) = create_result {
+                return Err(datafusion::error::DataFusionError::External(
+                    format!("Failed to create external table {}: {}", table_name, e).into(),
+                ));
+            }
+        } else {
+            dataset.register(&df_ctx, table.table_reference(), true)?;
+        }
+        info!(
+            "Moved table {} to {} ({:?}, {:?} compression, {:?} statistics, {:?}, {:?})",
+            table_name,
+            dataset.location(),
+            dataset.format,
+            dataset.compression,
+            dataset.statistics,
+            dataset.layout,
+            dataset.dialect
+        );
+        Ok(Some(dataset))
+    }
+
+    /// A name for the column named `base_name` that must be quoted: an earlier
+    /// column's name in different case, `base_name` with spaces or a double
+    /// quote, a keyword, or Unicode. Falls back to `base_name` if the name is
+    /// taken.
+    fn tricky_column_name(&mut self, base_name: &str, previous_names: &[String]) -> String {
+        // Reference engines compare names case-insensitively and would reject
+        // names differing only by case
+        let allow_case_variants = self.ctx.engines.is_empty();
+        let candidate = match self.rng.random_range(0..5) {
+            0 if allow_case_variants && !previous_names.is_empty() => {
+                let previous = &previous_names[self.rng.random_range(0..previous_names.len())];
+                if self.rng.random_bool(0.5) {
+                    previous.to_uppercase()
+                } else {
+                    let mut chars = previous.chars();
+                    chars
+                        .next()
+                        .map(|first| first.to_uppercase().chain(chars).collect())
+                        .unwrap_or_default()
+                }
+            }
+            1 => base_name.replace('_', " "),
+            2 => format!("{}\"q", base_name),
+            3 => {
+                let keyword = self.random_keyword();
+                if allow_case_variants && self.rng.random_bool(0.5) {
+                    keyword.to_uppercase()
+                } else {
+                    keyword.to_string()
+                }
+            }
+            _ => format!("{}_c\u{f6}l_\u{5217}", base_name),
+        };
+
+        let taken = if allow_case_variants {
+            previous_names.contains(&candidate)
+        } else {
+            previous_names
+                .iter()
+                .any(|name| name.eq_ignore_ascii_case(&candidate))
+        };
+        if taken || candidate.is_empty() {
+            base_name.to_string()
+        } else {
+            candidate
+        }
+    }
+
+    fn random_keyword(&mut self) -> &'static str {
+        KEYWORD_NAMES[self.rng.random_range(0..KEYWORD_NAMES.len())]
+    }
+
     // TODO(coverage): Now we only use simple values to prevent overflow.
     // switch to full range with edge cases like min/max for more coverage.
-    fn generate_sql_value(&mut self, fuzzer_type: &FuzzerDataType) -> String {
-        let value = generate_value(
+    fn generate_value(&mut self, fuzzer_type: &FuzzerDataType) -> GeneratedValue {
+        generate_value(
             &mut self.rng,
             fuzzer_type,
             &self.ctx.runtime_context.value_generation_config,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::init_available_data_types;
+    use crate::common::util::to_sql_string;
+    use crate::fuzz_context::RunnerConfig;
+    use crate::oracle::test_helpers::context_with_config;
+    use datafusion::common::{Column, TableReference};
+    use datafusion::prelude::Expr;
+
+    #[tokio::test]
+    async fn tricky_column_names_can_be_queried() {
+        init_available_data_types();
+        let ctx = context_with_config(RunnerConfig {
+            tricky_identifiers: true,
+            keyword_identifiers: true,
+            max_column_count: 8,
+            ..Default::default()
+        });
+
+        let mut quoted_names = 0;
+        for seed in 0..8 {
+            let table = DatasetGenerator::new(seed, Arc::clone(&ctx))
+                .generate_dataset()
+                .await
+                .unwrap();
+            for column in &table.columns {
+                if quote_identifier(&column.name) != column.name {
+                    quoted_names += 1;
+                }
+                // Generated queries reference columns through the unparser
+                let expr = Expr::Column(Column::new(
+                    Some(TableReference::bare(table.name.clone())),
+                    column.name.clone(),
+                ));
+                let query = format!(
+                    "SELECT {} FROM {}",
+                    to_sql_string(&expr).unwrap(),
+                    quote_identifier(&table.name)
+                );
+                let session_context = ctx.runtime_context.get_session_context();
+                let df = session_context.sql(&query).await.unwrap();
+                assert_eq!(df.schema().field(0).name(), &column.name, "{query}");
+            }
+        }
+        assert!(quoted_names > 0);
+    }
+
+    #[tokio::test]
+    async fn names_some_tables_after_keywords() {
+        init_available_data_types();
+        let ctx = context_with_config(RunnerConfig {
+            keyword_identifiers: true,
+            ..Default::default()
+        });
+
+        for seed in 0..32 {
+            DatasetGenerator::new(seed, Arc::clone(&ctx))
+                .generate_dataset()
+                .await
+                .unwrap();
+        }
+        let tables = ctx.runtime_context.tables();
+        assert!(
+            tables
+                .keys()
+                .any(|name| KEYWORD_NAMES.contains(&name.as_str()))
         );
-        value.to_sql_string()
+        for name in tables.keys() {
+            let query = format!("SELECT * FROM {}", quote_identifier(name));
+            ctx.runtime_context
+                .get_session_context()
+                .sql(&query)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn creates_tables_in_other_schemas_and_catalogs() {
+        init_available_data_types();
+        let ctx = context_with_config(RunnerConfig {
+            multi_schema: true,
+            ..Default::default()
+        });
+
+        for seed in 0..16 {
+            DatasetGenerator::new(seed, Arc::clone(&ctx))
+                .generate_dataset()
+                .await
+                .unwrap();
+        }
+        let tables = ctx.runtime_context.tables();
+        assert!(tables.values().any(|table| table.namespace.is_some()));
+        assert!(tables.values().any(|table| table.namespace.is_none()));
+
+        let session_context = ctx.runtime_context.get_session_context();
+        let mut rng = rng_from_seed(0);
+        for table in tables.values() {
+            for _ in 0..4 {
+                // Columns stay qualified by the bare table name
+                let column = &table.columns[0];
+                let expr = Expr::Column(Column::new(
+                    Some(TableReference::bare(table.name.clone())),
+                    column.name.clone(),
+                ));
+                let query = format!(
+                    "SELECT {} FROM {}",
+                    to_sql_string(&expr).unwrap(),
+                    table.random_sql_name(&mut rng)
+                );
+                let batches = session_context
+                    .sql(&query)
+                    .await
+                    .unwrap()
+                    .collect()
+                    .await
+                    .unwrap();
+                let rows: usize = batches.iter().map(|batch| batch.num_rows()).sum();
+                assert_eq!(Some(rows as u64), table.row_count(), "{query}");
+            }
+        }
     }
 }
@@ -0,0 +1,135 @@
+use std::sync::Arc;
+
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::datasource::MemTable;
+use datafusion::error::Result;
+use tracing::info;
+
+use crate::common::LogicalTable;
+use crate::fuzz_context::GlobalContext;
+
+/// Contents of one generated table
+struct TableSnapshot {
+    table: Arc<LogicalTable>,
+    schema: SchemaRef,
+    batches: Vec<RecordBatch>,
+}
+
+/// The tables of a round, captured once so later rounds can restore them by
+/// registering in-memory tables, or listing tables over the files of
+/// file-backed ones, instead of replaying `CREATE TABLE` and `INSERT`
+/// statements through the SQL engine.
+pub struct DatasetSnapshot {
+    tables: Vec<TableSnapshot>,
+}
+
+impl DatasetSnapshot {
+    /// Capture the schema and rows of every registered table
+    pub async fn capture(ctx: &Arc<GlobalContext>) -> Result<Self> {
+        let snapshot = ctx.runtime_context.snapshot();
+
+        let mut tables = Vec::with_capacity(snapshot.tables.len());
+        for table in snapshot.tables.values() {
+            let df = snapshot
+                .session_context
+                .table(table.table_reference())
+                .await?;
+            let schema = Arc::new(df.schema().as_arrow().clone());
+            let batches = df.collect().await?;
+            tables.push(TableSnapshot {
+                table: Arc::clone(table),
+                schema,
+                batches,
+            });
+        }
+
+        Ok(Self { tables })
+    }
+
+    /// Register the captured tables in the current session, which is expected
+    /// to be fresh (see [`GlobalContext::reset_datafusion_context`])
+    pub fn restore(&self, ctx: &Arc<GlobalContext>) -> Result<()> {
+        let session_context = ctx.runtime_context.get_session_context();
+
+        for snapshot in &self.tables {
+            if let Some(namespace) = &snapshot.table.namespace {
+                namespace.register(&session_context)?;
+            }
+            // File-backed tables are read from their files again
+            if let Some(files) = &snapshot.table.files {
+                files.register(&session_context, snapshot.table.table_reference(), true)?;
+            } else {
+                let mem_table = MemTable::try_new(
+                    Arc::clone(&snapshot.schema),
+                    vec![snapshot.batches.clone()],
+                )?;
+                session_context
+                    .register_table(snapshot.table.table_reference(), Arc::new(mem_table))?;
+            }
+
+            // Keep the name counter where generating the tables would leave it
+            let _ = ctx.runtime_context.next_table_name();
+            ctx.runtime_context
+                .register_table(Arc::clone(&snapshot.table));
+        }
+
+        info!(
+            "Restored {} tables from the dataset snapshot",
+            self.tables.len()
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::init_available_data_types;
+    use crate::datasource_generator::dataset_generator::DatasetGenerator;
+    use datafusion::arrow::util::pretty::pretty_format_batches;
+
+    /// Rows of a table in a stable order, partitions may be read in any order
+    async fn table_contents(ctx: &Arc<GlobalContext>, name: &str) -> Vec<String> {
+        let batches = ctx
+            .runtime_context
+            .get_session_context()
+            .sql(&format!(
+                "SELECT * FROM {}",
+                crate::common::util::quote_identifier(name)
+            ))
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        let mut rows: Vec<String> = pretty_format_batches(&batches)
+            .unwrap()
+            .to_string()
+            .lines()
+            .map(str::to_string)
+            .collect();
+        rows.sort();
+        rows
+    }
+
+    #[tokio::test]
+    async fn restores_captured_tables() {
+        init_available_data_types();
+        let ctx = Arc::new(GlobalContext::default());
+        let table = DatasetGenerator::new(7, Arc::clone(&ctx))
+            .generate_dataset()
+            .await
+            .unwrap();
+        let expected = table_contents(&ctx, &table.name).await;
+
+        let snapshot = DatasetSnapshot::capture(&ctx).await.unwrap();
+        ctx.reset_datafusion_context();
+        assert!(ctx.runtime_context.tables().is_empty());
+
+        snapshot.restore(&ctx).unwrap();
+        assert!(ctx.runtime_context.get_table(&table.name).is_some());
+        assert_eq!(table_contents(&ctx, &table.name).await, expected);
+        assert_eq!(ctx.runtime_context.next_table_name(), "t1");
+    }
+}
@@ -0,0 +1,823 @@
+//! Generated tables backed by files instead of memory.
+//!
+//! With `dataset_format = "parquet"` or `"csv"` a generated table is written
+//! to a file after its INSERTs, and the in-memory table is replaced by a
+//! listing table over the file, so queries read it through the Parquet or CSV
+//! reader. The listing table is registered through the API or with a
+//! `CREATE EXTERNAL TABLE` statement. Files are compressed with a random
+//! codec, CSV files get a random delimiter and header. The Parquet writer
+//! randomly stores no, row group, page level or truncated column statistics,
+//! so DataFusion plans with absent, exact or inexact statistics.
+//!
+//! Files are written to a local directory. With `dataset_store = "memory"`
+//! they are copied to an in-memory `ObjectStore`, and listing tables read them
+//! through the object store API, with range reads, instead of from the local
+//! file system.
+
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use datafusion::arrow::compute::{concat_batches, sort_to_indices, take_record_batch};
+use datafusion::arrow::datatypes::{DataType, Schema, SchemaRef};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::common::TableReference;
+use datafusion::common::config::CsvOptions;
+use datafusion::common::parsers::CompressionTypeVariant;
+use datafusion::dataframe::{DataFrame, DataFrameWriteOptions};
+use datafusion::datasource::file_format::FileFormat;
+use datafusion::datasource::file_format::csv::CsvFormat;
+use datafusion::datasource::file_format::file_compression_type::FileCompressionType;
+use datafusion::datasource::file_format::parquet::ParquetFormat;
+use datafusion::datasource::listing::{
+    ListingOptions, ListingTable, ListingTableConfig, ListingTableUrl,
+};
+use datafusion::error::Result;
+use datafusion::execution::object_store::ObjectStoreUrl;
+use datafusion::parquet::arrow::ArrowWriter;
+use datafusion::parquet::basic::{Compression as ParquetCompression, GzipLevel, ZstdLevel};
+use datafusion::parquet::file::properties::{EnabledStatistics, WriterProperties};
+use datafusion::prelude::{SessionConfig, SessionContext};
+use object_store::memory::InMemory;
+use object_store::path::Path as ObjectStorePath;
+use object_store::{ObjectStore, PutPayload};
+use rand::Rng;
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
+
+/// Numbers the dataset directories of this process
+static NEXT_DATASET_ID: AtomicU64 = AtomicU64::new(0);
+
+/// How NULL is written to CSV files, so it differs from an empty string
+const CSV_NULL_VALUE: &str = "\\N";
+
+/// Reads [`CSV_NULL_VALUE`] back as NULL
+const CSV_NULL_REGEX: &str = "^\\\\N$";
+
+/// Where generated tables are stored
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DatasetFormat {
+    /// Tables created with `CREATE TABLE` and filled with `INSERT`
+    #[default]
+    Memory,
+    /// Tables written to Parquet files and read through a listing table
+    Parquet,
+    /// Tables written to CSV files with a header and read through a listing
+    /// table. Tables with columns CSV can't store exactly stay in memory.
+    Csv,
+}
+
+/// Where listing tables read the files of generated tables from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DatasetStore {
+    /// The local file system
+    #[default]
+    Local,
+    /// An in-memory object store holding copies of the files
+    Memory,
+}
+
+/// In-memory object store holding copies of a table's files
+#[derive(Debug, Clone)]
+pub struct MemoryStore {
+    /// `memory://dataset-<n>/`, the files are at its root
+    pub url: ObjectStoreUrl,
+    pub store: Arc<InMemory>,
+}
+
+/// Compression codec of a table's files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Compression {
+    Uncompressed,
+    Snappy,
+    Zstd,
+    Gzip,
+}
+
+impl Compression {
+    /// A random codec supported by `format`, CSV files can't be Snappy
+    /// compressed
+    fn random(rng: &mut StdRng, format: DatasetFormat) -> Self {
+        let codecs: &[Self] = match format {
+            DatasetFormat::Csv => &[Self::Uncompressed, Self::Zstd, Self::Gzip],
+            _ => &[Self::Uncompressed, Self::Snappy, Self::Zstd, Self::Gzip],
+        };
+        codecs[rng.random_range(0..codecs.len())]
+    }
+
+    fn parquet(self) -> ParquetCompression {
+        match self {
+            Self::Uncompressed => ParquetCompression::UNCOMPRESSED,
+            Self::Snappy => ParquetCompression::SNAPPY,
+            Self::Zstd => ParquetCompression::ZSTD(ZstdLevel::default()),
+            Self::Gzip => ParquetCompression::GZIP(GzipLevel::default()),
+        }
+    }
+
+    fn csv(self) -> CompressionTypeVariant {
+        match self {
+            Self::Zstd => CompressionTypeVariant::ZSTD,
+            Self::Gzip => CompressionTypeVariant::GZIP,
+            Self::Uncompressed | Self::Snappy => CompressionTypeVariant::UNCOMPRESSED,
+        }
+    }
+}
+
+/// How CSV files are delimited
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvDialect {
+    pub delimiter: u8,
+    /// Whether the first line holds the column names
+    pub has_header: bool,
+}
+
+impl Default for CsvDialect {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            has_header: true,
+        }
+    }
+}
+
+impl CsvDialect {
+    fn random(rng: &mut StdRng) -> Self {
+        let delimiters = [b',', b';', b'|', b'\t'];
+        Self {
+            delimiter: delimiters[rng.random_range(0..delimiters.len())],
+            has_header: rng.random_bool(0.5),
+        }
+    }
+}
+
+/// Column statistics stored by the Parquet writer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriterStatistics {
+    /// No min/max, only row counts
+    None,
+    /// Min/max per row group
+    RowGroup,
+    /// Min/max per row group and page
+    Page,
+    /// Min/max per row group, truncated to a few bytes so they are only
+    /// bounds for long strings
+    Truncated,
+}
+
+impl WriterStatistics {
+    fn random(rng: &mut StdRng) -> Self {
+        match rng.random_range(0..4) {
+            0 => Self::None,
+            1 => Self::RowGroup,
+            2 => Self::Page,
+            _ => Self::Truncated,
+        }
+    }
+
+    fn writer_properties(
+        self,
+        layout: &ParquetLayout,
+        compression: Compression,
+    ) -> WriterProperties {
+        let mut builder = WriterProperties::builder().set_compression(compression.parquet());
+        if let Some(rows) = layout.max_row_group_rows {
+            builder = builder.set_max_row_group_size(rows);
+        }
+        if let Some(rows) = layout.max_page_rows {
+            // Page sizes are only checked between write batches
+            builder = builder
+                .set_data_page_row_count_limit(rows)
+                .set_write_batch_size(rows);
+        }
+        match self {
+            Self::None => builder.set_statistics_enabled(EnabledStatistics::None),
+            Self::RowGroup => builder.set_statistics_enabled(EnabledStatistics::Chunk),
+            Self::Page => builder.set_statistics_enabled(EnabledStatistics::Page),
+            Self::Truncated => builder
+                .set_statistics_enabled(EnabledStatistics::Chunk)
+                .set_statistics_truncate_length(Some(2)),
+        }
+        .build()
+    }
+}
+
+/// How rows are laid out in a Parquet file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParquetLayout {
+    /// Column the rows are sorted by, so row groups and pages cover narrow
+    /// value ranges
+    pub sort_column: Option<usize>,
+    /// Maximum rows per row group, the writer's default if not set
+    pub max_row_group_rows: Option<usize>,
+    /// Maximum rows per data page, the writer's default if not set
+    pub max_page_rows: Option<usize>,
+}
+
+impl ParquetLayout {
+    /// Rows clustered by a random one of `num_columns` columns, in row groups
+    /// and pages of a few rows each, so min/max pruning can skip most of them
+    pub fn pruning_stress(rng: &mut StdRng, num_columns: usize) -> Self {
+        let max_page_rows = rng.random_range(1..=2);
+        Self {
+            sort_column: Some(rng.random_range(0..num_columns)),
+            max_row_group_rows: Some(max_page_rows * rng.random_range(1..=3)),
+            max_page_rows: Some(max_page_rows),
+        }
+    }
+}
+
+/// Files a generated table is stored in
+#[derive(Debug, Clone)]
+pub struct FileDataset {
+    pub format: DatasetFormat,
+    /// Directory holding the table's files and nothing else
+    pub dir: PathBuf,
+    pub schema: SchemaRef,
+    pub compression: Compression,
+    /// Statistics of Parquet files
+    pub statistics: WriterStatistics,
+    /// Layout of Parquet files
+    pub layout: ParquetLayout,
+    /// Dialect of CSV files
+    pub dialect: CsvDialect,
+    /// Store the listing table reads copies of the files from instead of
+    /// `dir`, see `dataset_store`
+    pub memory_store: Option<MemoryStore>,
+}
+
+impl FileDataset {
+    /// Write `batches` of `table_name` to a Parquet file with `layout` in a
+    /// new directory below `base_dir`, or the OS temp directory. Layouts with
+    /// small pages always get page statistics, which column indexes are built
+    /// from.
+    pub fn write_parquet(
+        rng: &mut StdRng,
+        base_dir: Option<&Path>,
+        table_name: &str,
+        schema: SchemaRef,
+        batches: &[RecordBatch],
+        layout: ParquetLayout,
+    ) -> Result<Self> {
+        let dir = new_dataset_dir(base_dir, table_name)?;
+        let compression = Compression::random(rng, DatasetFormat::Parquet);
+        let statistics = if layout.max_page_rows.is_some() {
+            WriterStatistics::Page
+        } else {
+            WriterStatistics::random(rng)
+        };
+
+        let mut batch = concat_batches(&schema, batches)?;
+        if let Some(sort_column) = layout.sort_column {
+            let indices = sort_to_indices(batch.column(sort_column), None, None)?;
+            batch = take_record_batch(&batch, &indices)?;
+        }
+
+        write_parquet_file(
+            &dir.join("part-0.parquet"),
+            &batch,
+            statistics.writer_properties(&layout, compression),
+        )?;
+
+        Ok(Self {
+            format: DatasetFormat::Parquet,
+            dir,
+            schema,
+            compression,
+            statistics,
+            layout,
+            dialect: CsvDialect::default(),
+            memory_store: None,
+        })
+    }
+
+    /// Write each of `files` to its own Parquet file in a new directory below
+    /// `base_dir`, or the OS temp directory, for the table `table_name` with
+    /// `schema`. The files may have other schemas than the table, e.g. miss
+    /// columns, which the listing table adapts to `schema` when reading.
+    pub fn write_parquet_files(
+        rng: &mut StdRng,
+        base_dir: Option<&Path>,
+        table_name: &str,
+        schema: SchemaRef,
+        files: &[RecordBatch],
+    ) -> Result<Self> {
+        let dir = new_dataset_dir(base_dir, table_name)?;
+        let compression = Compression::random(rng, DatasetFormat::Parquet);
+        let statistics = WriterStatistics::random(rng);
+        let layout = ParquetLayout::default();
+
+        for (i, batch) in files.iter().enumerate() {
+            write_parquet_file(
+                &dir.join(format!("part-{}.parquet", i)),
+                batch,
+                statistics.writer_properties(&layout, compression),
+            )?;
+        }
+
+        Ok(Self {
+            format: DatasetFormat::Parquet,
+            dir,
+            schema,
+            compression,
+            statistics,
+            layout,
+            dialect: CsvDialect::default(),
+            memory_store: None,
+        })
+    }
+
+    /// Write the rows of `df`, the table `table_name`, to a CSV file with a
+    /// random delimiter, with or without a header, in a new directory below
+    /// `base_dir`, or the OS temp directory
+    pub async fn write_csv(
+        rng: &mut StdRng,
+        base_dir: Option<&Path>,
+        table_name: &str,
+        df: DataFrame,
+    ) -> Result<Self> {
+        let dir = new_dataset_dir(base_dir, table_name)?;
+        let dataset = Self {
+            format: DatasetFormat::Csv,
+            dir,
+            schema: Arc::new(df.schema().as_arrow().clone()),
+            compression: Compression::random(rng, DatasetFormat::Csv),
+            statistics: WriterStatistics::None,
+            layout: ParquetLayout::default(),
+            dialect: CsvDialect::random(rng),
+            memory_store: None,
+        };
+
+        let mut csv_options = CsvOptions::default()
+            .with_has_header(dataset.dialect.has_header)
+            .with_delimiter(dataset.dialect.delimiter)
+            .with_file_compression_type(dataset.compression.csv());
+        csv_options.null_value = Some(CSV_NULL_VALUE.to_string());
+        let path = dataset
+            .dir
+            .join(format!("part-0{}", dataset.file_extension()?));
+        df.write_csv(
+            &path.to_string_lossy(),
+            DataFrameWriteOptions::new().with_single_file_output(true),
+            Some(csv_options),
+        )
+        .await?;
+        Ok(dataset)
+    }
+
+    fn csv_format(&self) -> CsvFormat {
+        CsvFormat::default()
+            .with_has_header(self.dialect.has_header)
+            .with_delimiter(self.dialect.delimiter)
+            .with_newlines_in_values(true)
+            .with_null_regex(Some(CSV_NULL_REGEX.to_string()))
+            .with_file_compression_type(FileCompressionType::from(self.compression.csv()))
+    }
+
+    /// Extension of the files, with the one of the codec for CSV files
+    fn file_extension(&self) -> Result<String> {
+        match self.format {
+            DatasetFormat::Csv => self
+                .csv_format()
+                .get_ext_with_compression(&FileCompressionType::from(self.compression.csv())),
+            _ => Ok(".parquet".to_string()),
+        }
+    }
+
+    /// Copy the files to a new in-memory object store, which listing tables
+    /// then read them from instead of the local files
+    pub async fn copy_to_memory_store(&mut self) -> Result<()> {
+        let id = NEXT_DATASET_ID.fetch_add(1, Ordering::Relaxed);
+        let url = ObjectStoreUrl::parse(format!("memory://dataset-{}", id))?;
+        let store = Arc::new(InMemory::new());
+        for entry in fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let location = ObjectStorePath::from(entry.file_name().to_string_lossy().as_ref());
+            store
+                .put(&location, PutPayload::from(fs::read(entry.path())?))
+                .await?;
+        }
+        self.memory_store = Some(MemoryStore { url, store });
+        Ok(())
+    }
+
+    /// URL of the directory listing tables read the files from
+    pub fn location(&self) -> String {
+        match &self.memory_store {
+            Some(memory_store) => memory_store.url.as_str().to_string(),
+            None => format!("{}/", self.dir.display()),
+        }
+    }
+
+    /// Make the object store holding the files available to
+    /// `session_context`, a no-op for local files
+    pub fn register_store(&self, session_context: &SessionContext) {
+        if let Some(memory_store) = &self.memory_store {
+            session_context.register_object_store(
+                memory_store.url.as_ref(),
+                Arc::clone(&memory_store.store) as Arc<dyn ObjectStore>,
+            );
+        }
+    }
+
+    /// Register a listing table `table` over the files in `session_context`,
+    /// gathering statistics from the file footers if `collect_statistics`
+    pub fn register(
+        &self,
+        session_context: &SessionContext,
+        table: impl Into<TableReference>,
+        collect_statistics: bool,
+    ) -> Result<()> {
+        let file_format: Arc<dyn FileFormat> = match self.format {
+            DatasetFormat::Csv => Arc::new(self.csv_format()),
+            _ => Arc::new(ParquetFormat::default()),
+        };
+        let listing_options = ListingOptions::new(file_format)
+            .with_file_extension(self.file_extension()?)
+            .with_collect_stat(collect_statistics);
+        self.register_store(session_context);
+        let table_url = ListingTableUrl::parse(self.location())?;
+        let config = ListingTableConfig::new(table_url)
+            .with_listing_options(listing_options)
+            .with_schema(Arc::clone(&self.schema));
+
+        session_context.register_table(table, Arc::new(ListingTable::try_new(config)?))?;
+        Ok(())
+    }
+
+    /// A `CREATE EXTERNAL TABLE` statement registering the table `table_sql`
+    /// (a quoted, possibly qualified name) with `column_definitions` over the
+    /// files, with the options they were written with
+    pub fn create_external_table_sql(
+        &self,
+        table_sql: &str,
+        column_definitions: &[String],
+    ) -> String {
+        let (stored_as, options) = match self.format {
+            DatasetFormat::Csv => (
+                "CSV",
+                vec![
+                    ("format.has_header", self.dialect.has_header.to_string()),
+                    (
+                        "format.delimiter",
+                        (self.dialect.delimiter as char).to_string(),
+                    ),
+                    (
+                        "format.compression",
+                        format!("{:?}", self.compression.csv()),
+                    ),
+                    ("format.null_regex", CSV_NULL_REGEX.to_string()),
+                    ("format.newlines_in_values", "true".to_string()),
+                ],
+            ),
+            _ => ("PARQUET", Vec::new()),
+        };
+
+        let mut sql = format!(
+            "CREATE EXTERNAL TABLE {} (\n    {}\n)\nSTORED AS {}\nLOCATION '{}'",
+            table_sql,
+            column_definitions.join(",\n    "),
+            stored_as,
+            self.location().replace('\'', "''")
+        );
+        if !options.is_empty() {
+            let options: Vec<String> = options
+                .iter()
+                .map(|(key, value)| format!("'{}' '{}'", key, value.replace('\'', "''")))
+                .collect();
+            sql.push_str(&format!("\nOPTIONS ({})", options.join(", ")));
+        }
+        sql.push(';');
+        sql
+    }
+
+    /// A session with `config` where the table `name` is registered over
+    /// these files, for oracles comparing scan options
+    pub fn session(
+        &self,
+        config: SessionConfig,
+        name: &str,
+        collect_statistics: bool,
+    ) -> Result<Arc<SessionContext>> {
+        let session_context = SessionContext::new_with_config(config);
+        self.register(
+            &session_context,
+            TableReference::bare(name),
+            collect_statistics,
+        )?;
+        Ok(Arc::new(session_context))
+    }
+}
+
+/// Whether Parquet can store every column of `schema`, which rules out
+/// intervals
+pub fn parquet_supports(schema: &Schema) -> bool {
+    !schema
+        .fields()
+        .iter()
+        .any(|field| matches!(field.data_type(), DataType::Interval(_)))
+}
+
+/// Whether CSV files can store every column of `schema` exactly. Temporal
+/// types other than dates depend on the text formats of the writer and the
+/// reader, and are left out.
+pub fn csv_supports(schema: &Schema) -> bool {
+    schema.fields().iter().all(|field| {
+        matches!(
+            field.data_type(),
+            DataType::Int32
+                | DataType::Int64
+                | DataType::UInt32
+                | DataType::UInt64
+                | DataType::Float32
+                | DataType::Float64
+                | DataType::Boolean
+                | DataType::Decimal128(_, _)
+                | DataType::Decimal256(_, _)
+                | DataType::Date32
+                | DataType::Utf8
+        )
+    })
+}
+
+/// Write `batch` to a Parquet file at `path` with its own schema
+fn write_parquet_file(
+    path: &Path,
+    batch: &RecordBatch,
+    properties: WriterProperties,
+) -> Result<()> {
+    let mut writer = ArrowWriter::try_new(File::create(path)?, batch.schema(), Some(properties))?;
+    writer.write(batch)?;
+    writer.close()?;
+    Ok(())
+}
+
+/// An empty directory for the files of the next table,
+/// `<base_dir>/datafusion-fuzzer-<pid>/<table>-<n>`
+fn new_dataset_dir(base_dir: Option<&Path>, table_name: &str) -> Result<PathBuf> {
+    let base_dir = base_dir.map_or_else(std::env::temp_dir, Path::to_path_buf);
+    let id = NEXT_DATASET_ID.fetch_add(1, Ordering::Relaxed);
+    let dir = base_dir
+        .join(format!("datafusion-fuzzer-{}", std::process::id()))
+        .join(format!("{}-{}", table_name, id));
+    if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+    }
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::rng::rng_from_seed;
+    use datafusion::arrow::array::{Int64Array, StringArray};
+    use datafusion::arrow::datatypes::{Field, IntervalUnit};
+    use datafusion::scalar::ScalarValue;
+
+    #[tokio::test]
+    async fn parquet_tables_return_the_written_rows() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::Utf8, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![
+                Arc::new(Int64Array::from(vec![Some(1), None, Some(3)])),
+                Arc::new(StringArray::from(vec![
+                    Some("long string"),
+                    Some("x"),
+                    None,
+                ])),
+            ],
+        )
+        .unwrap();
+
+        let mut rng = rng_from_seed(1);
+        for pruning_stress in [false, false, true, true] {
+            let layout = if pruning_stress {
+                ParquetLayout::pruning_stress(&mut rng, schema.fields().len())
+            } else {
+                ParquetLayout::default()
+            };
+            let dataset = FileDataset::write_parquet(
+                &mut rng,
+                None,
+                "t",
+                Arc::clone(&schema),
+                &[batch.clone()],
+                layout,
+            )
+            .unwrap();
+            for collect_statistics in [true, false] {
+                let session_context = dataset
+                    .session(SessionConfig::new(), "t", collect_statistics)
+                    .unwrap();
+                let batches = session_context
+                    .sql("SELECT COUNT(*), MAX(a), MIN(b) FROM t")
+                    .await
+                    .unwrap()
+                    .collect()
+                    .await
+                    .unwrap();
+                let row: Vec<String> = batches[0]
+                    .columns()
+                    .iter()
+                    .map(|column| ScalarValue::try_from_array(column, 0).unwrap().to_string())
+                    .collect();
+                assert_eq!(row, vec!["3", "3", "long string"]);
+            }
+            fs::remove_dir_all(&dataset.dir).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn csv_tables_keep_nulls_and_empty_strings() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::Utf8, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![
+                Arc::new(Int64Array::from(vec![Some(1), None, Some(3)])),
+                Arc::new(StringArray::from(vec![Some(""), Some("x, \"y\""), None])),
+            ],
+        )
+        .unwrap();
+        assert!(csv_supports(&schema));
+
+        let mut rng = rng_from_seed(1);
+        for _ in 0..4 {
+            let df = SessionContext::new().read_batch(batch.clone()).unwrap();
+            let dataset = FileDataset::write_csv(&mut rng, None, "t", df)
+                .await
+                .unwrap();
+            assert_ne!(dataset.compression, Compression::Snappy);
+            let session_context = dataset.session(SessionConfig::new(), "t", true).unwrap();
+            let batches = session_context
+                .sql("SELECT COUNT(a), COUNT(b), MIN(b), MAX(b) FROM t")
+                .await
+                .unwrap()
+                .collect()
+                .await
+                .unwrap();
+            let row: Vec<String> = batches[0]
+                .columns()
+                .iter()
+                .map(|column| ScalarValue::try_from_array(column, 0).unwrap().to_string())
+                .collect();
+            assert_eq!(row, vec!["2", "2", "", "x, \"y\""]);
+            fs::remove_dir_all(&dataset.dir).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn external_table_ddl_reads_the_files() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::Utf8, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![
+                Arc::new(Int64Array::from(vec![Some(1), None, Some(3)])),
+                Arc::new(StringArray::from(vec![Some("a;b|c"), None, Some("d")])),
+            ],
+        )
+        .unwrap();
+
+        let mut rng = rng_from_seed(2);
+        let mut datasets = vec![
+            FileDataset::write_parquet(
+                &mut rng,
+                None,
+                "t",
+                Arc::clone(&schema),
+                &[batch.clone()],
+                ParquetLayout::default(),
+            )
+            .unwrap(),
+        ];
+        for _ in 0..4 {
+            let df = SessionContext::new().read_batch(batch.clone()).unwrap();
+            datasets.push(
+                FileDataset::write_csv(&mut rng, None, "t", df)
+                    .await
+                    .unwrap(),
+            );
+        }
+
+        let column_definitions = ["a BIGINT".to_string(), "b VARCHAR".to_string()];
+        for dataset in datasets {
+            let sql = dataset.create_external_table_sql("t", &column_definitions);
+            assert_eq!(
+                sql.contains("'format.delimiter'"),
+                dataset.format == DatasetFormat::Csv
+            );
+            let session_context = SessionContext::new();
+            session_context
+                .sql(&sql)
+                .await
+                .unwrap()
+                .collect()
+                .await
+                .unwrap();
+            let batches = session_context
+                .sql("SELECT COUNT(*), COUNT(a), MAX(b) FROM t")
+                .await
+                .unwrap()
+                .collect()
+                .await
+                .unwrap();
+            let row: Vec<String> = batches[0]
+                .columns()
+                .iter()
+                .map(|column| ScalarValue::try_from_array(column, 0).unwrap().to_string())
+                .collect();
+            assert_eq!(row, vec!["3", "2", "d"], "{}", sql);
+            fs::remove_dir_all(&dataset.dir).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn memory_store_tables_read_copies_of_the_files() {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, true)]));
+        let batch = RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![Arc::new(Int64Array::from(vec![Some(1), None, Some(3)]))],
+        )
+        .unwrap();
+
+        let mut rng = rng_from_seed(3);
+        let df = SessionContext::new().read_batch(batch.clone()).unwrap();
+        let datasets = vec![
+            FileDataset::write_parquet(
+                &mut rng,
+                None,
+                "t",
+                Arc::clone(&schema),
+                &[batch],
+                ParquetLayout::default(),
+            )
+            .unwrap(),
+            FileDataset::write_csv(&mut rng, None, "t", df)
+                .await
+                .unwrap(),
+        ];
+
+        for mut dataset in datasets {
+            dataset.copy_to_memory_store().await.unwrap();
+            // Only the copies are left
+            fs::remove_dir_all(&dataset.dir).unwrap();
+            assert!(dataset.location().starts_with("memory://dataset-"));
+
+            let registered = dataset.session(SessionConfig::new(), "t", true).unwrap();
+            let created = SessionContext::new();
+            dataset.register_store(&created);
+            created
+                .sql(&dataset.create_external_table_sql("t", &["a BIGINT".to_string()]))
+                .await
+                .unwrap()
+                .collect()
+                .await
+                .unwrap();
+
+            for session_context in [registered.as_ref(), &created] {
+                let batches = session_context
+                    .sql("SELECT COUNT(*), SUM(a) FROM t")
+                    .await
+                    .unwrap()
+                    .collect()
+                    .await
+                    .unwrap();
+                let row: Vec<String> = batches[0]
+                    .columns()
+                    .iter()
+                    .map(|column| ScalarValue::try_from_array(column, 0).unwrap().to_string())
+                    .collect();
+                assert_eq!(row, vec!["3", "4"]);
+            }
+        }
+    }
+
+    #[test]
+    fn intervals_stay_in_memory() {
+        let interval = Schema::new(vec![Field::new(
+            "i",
+            DataType::Interval(IntervalUnit::MonthDayNano),
+            true,
+        )]);
+        assert!(!parquet_supports(&interval));
+        assert!(!csv_supports(&interval));
+        assert!(parquet_supports(&Schema::new(vec![Field::new(
+            "a",
+            DataType::Int64,
+            true
+        )])));
+    }
+}
@@ -1 +1,3 @@
 pub mod dataset_generator;
+pub mod dataset_snapshot;
+pub mod file_dataset;
@@ -0,0 +1,197 @@
+//! Execution of a batch of queries at the same time in one session.
+
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::prelude::SessionContext;
+use futures::FutureExt;
+use tokio::sync::OnceCell;
+use tokio::task::JoinSet;
+
+use super::{EmbeddedDataFusion, EngineKind, EngineUnderTest};
+use crate::cli::panic_hook::panic_payload_message;
+use crate::common::{Result, fuzzer_err};
+
+/// Result of one query of a batch, with the error as its message so it can be
+/// handed out to every engine of the batch
+type BatchResult = std::result::Result<Vec<RecordBatch>, String>;
+
+/// Queries run concurrently in one session, each on a task of its own. The
+/// whole batch runs the first time the result of any of its queries is
+/// needed, and only once.
+///
+/// A task can also run a script, statements one after the other, e.g. DDL
+/// changing the catalog while the other tasks query it.
+pub struct ConcurrentBatch {
+    session_context: Arc<SessionContext>,
+    scripts: Vec<Vec<String>>,
+    /// Errors a script goes on after, as expected outcomes of its statements
+    tolerated_error: fn(&str) -> bool,
+    results: OnceCell<Vec<BatchResult>>,
+}
+
+impl ConcurrentBatch {
+    pub fn new(session_context: Arc<SessionContext>, queries: Vec<String>) -> Arc<Self> {
+        let scripts = queries.into_iter().map(|query| vec![query]).collect();
+        Self::with_scripts(session_context, scripts, |_| false)
+    }
+
+    /// Batch running each script on a task of its own. The result of a script
+    /// is the result of its last statement, empty if that statement failed
+    /// with a tolerated error, or the first error that isn't tolerated.
+    pub fn with_scripts(
+        session_context: Arc<SessionContext>,
+        scripts: Vec<Vec<String>>,
+        tolerated_error: fn(&str) -> bool,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            session_context,
+            scripts,
+            tolerated_error,
+            results: OnceCell::new(),
+        })
+    }
+
+    /// Engine returning the result of the `index`-th query or script of the
+    /// batch
+    pub fn engine(self: &Arc<Self>, index: usize) -> ConcurrentQueryEngine {
+        ConcurrentQueryEngine {
+            batch: Arc::clone(self),
+            index,
+        }
+    }
+
+    async fn results(&self) -> &[BatchResult] {
+        self.results.get_or_init(|| self.run()).await
+    }
+
+    /// Run all scripts at once. Dropping the future (e.g. when the runner
+    /// times out) aborts the scripts still running.
+    async fn run(&self) -> Vec<BatchResult> {
+        let mut tasks = JoinSet::new();
+        for (index, script) in self.scripts.iter().enumerate() {
+            let engine = EmbeddedDataFusion::new(Arc::clone(&self.session_context));
+            let script = script.clone();
+            let tolerated_error = self.tolerated_error;
+            tasks.spawn(async move {
+                // Catch panics here, the task's panic wouldn't tell which
+                // query it was
+                let run_script = run_script(&engine, &script, tolerated_error);
+                let result = match AssertUnwindSafe(run_script).catch_unwind().await {
+                    Ok(result) => result,
+                    Err(payload) => Err(format!(
+                        "Query task panicked: {}",
+                        panic_payload_message(&*payload)
+                    )),
+                };
+                (index, result)
+            });
+        }
+
+        let mut results = vec![Err("Query task failed".to_string()); self.scripts.len()];
+        while let Some(joined) = tasks.join_next().await {
+            if let Ok((index, result)) = joined {
+                results[index] = result;
+            }
+        }
+        results
+    }
+}
+
+async fn run_script(
+    engine: &EmbeddedDataFusion,
+    script: &[String],
+    tolerated_error: fn(&str) -> bool,
+) -> BatchResult {
+    let mut batches = Vec::new();
+    for (index, sql) in script.iter().enumerate() {
+        batches = match engine.execute_sql(sql).await {
+            Ok(batches) => batches,
+            Err(e) if tolerated_error(&e.to_string()) => Vec::new(),
+            Err(e) if script.len() > 1 => {
+                return Err(format!("{} (statement {}: {})", e, index + 1, sql));
+            }
+            Err(e) => return Err(e.to_string()),
+        };
+    }
+    Ok(batches)
+}
+
+/// Executes one query or script of a [`ConcurrentBatch`], ignoring the SQL it
+/// is given, which is the same query
+pub struct ConcurrentQueryEngine {
+    batch: Arc<ConcurrentBatch>,
+    index: usize,
+}
+
+#[async_trait::async_trait]
+impl EngineUnderTest for ConcurrentQueryEngine {
+    fn name(&self) -> &str {
+        "concurrent"
+    }
+
+    fn kind(&self) -> EngineKind {
+        EngineKind::DataFusion
+    }
+
+    async fn execute_sql(&self, _sql: &str) -> Result<Vec<RecordBatch>> {
+        self.batch.results().await[self.index]
+            .clone()
+            .map_err(|e| fuzzer_err(&e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn runs_the_batch_once_and_hands_out_each_result() {
+        let session_context = Arc::new(SessionContext::new());
+        session_context
+            .sql("CREATE TABLE t1(c0 BIGINT) AS VALUES (1), (2), (3)")
+            .await
+            .unwrap();
+
+        let batch = ConcurrentBatch::new(
+            session_context,
+            vec![
+                "SELECT count(*) FROM t1".to_string(),
+                "SELECT * FROM missing".to_string(),
+                "SELECT c0 FROM t1 WHERE c0 > 1".to_string(),
+            ],
+        );
+        let first = batch.engine(0).execute_sql("").await.unwrap();
+        assert_eq!(first[0].num_rows(), 1);
+        assert!(batch.engine(1).execute_sql("").await.is_err());
+        let third = batch.engine(2).execute_sql("").await.unwrap();
+        assert_eq!(third.iter().map(|b| b.num_rows()).sum::<usize>(), 2);
+        assert_eq!(batch.results.get().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn scripts_go_on_after_tolerated_errors() {
+        let session_context = Arc::new(SessionContext::new());
+        let script = |statements: &[&str]| -> Vec<String> {
+            statements.iter().map(|s| s.to_string()).collect()
+        };
+        let batch = ConcurrentBatch::with_scripts(
+            session_context,
+            vec![
+                script(&[
+                    "SELECT * FROM missing",
+                    "CREATE TABLE t1(c0 BIGINT) AS VALUES (1), (2)",
+                    "SELECT c0 FROM t1",
+                ]),
+                script(&["SELECT 1", "SELECT 1 / 0", "SELECT 2"]),
+            ],
+            |error| error.contains("not found"),
+        );
+
+        let rows = batch.engine(0).execute_sql("").await.unwrap();
+        assert_eq!(rows.iter().map(|b| b.num_rows()).sum::<usize>(), 2);
+        let err = batch.engine(1).execute_sql("").await.unwrap_err();
+        assert!(err.to_string().contains("statement 2: SELECT 1 / 0"));
+    }
+}
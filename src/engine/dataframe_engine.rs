@@ -0,0 +1,52 @@
+//! Execution of queries built without SQL text, through the `DataFrame` API.
+
+use std::sync::Arc;
+
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::prelude::SessionContext;
+
+use super::{EngineKind, EngineUnderTest};
+use crate::common::{Result, fuzzer_err};
+
+/// A query built with DataFusion's programmatic APIs, e.g. a logical plan or
+/// a chain of `DataFrame` operations
+#[async_trait::async_trait]
+pub trait DataFrameQuery: Send + Sync {
+    /// Build the query over the tables of `session_context` and collect its
+    /// result
+    async fn execute(&self, session_context: &SessionContext) -> Result<Vec<RecordBatch>>;
+}
+
+/// Executes a [`DataFrameQuery`] in a session, ignoring the SQL it is
+/// equivalent to. The SQL is only recorded for logs and reproducers.
+pub struct DataFrameEngine<Q> {
+    session_context: Arc<SessionContext>,
+    query: Q,
+}
+
+impl<Q: DataFrameQuery> DataFrameEngine<Q> {
+    pub fn new(session_context: Arc<SessionContext>, query: Q) -> Self {
+        Self {
+            session_context,
+            query,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<Q: DataFrameQuery> EngineUnderTest for DataFrameEngine<Q> {
+    fn name(&self) -> &str {
+        "dataframe"
+    }
+
+    fn kind(&self) -> EngineKind {
+        EngineKind::DataFusion
+    }
+
+    async fn execute_sql(&self, _sql: &str) -> Result<Vec<RecordBatch>> {
+        self.query
+            .execute(&self.session_context)
+            .await
+            .map_err(|e| fuzzer_err(&format!("DataFrame execution failed: {}", e)))
+    }
+}
@@ -0,0 +1,149 @@
+use std::sync::{Arc, Mutex};
+
+use datafusion::arrow::record_batch::RecordBatch;
+use duckdb::Connection;
+use duckdb::types::{TimeUnit, Value};
+
+use super::row_batches::{SqlValue, rows_to_batch};
+use super::{EngineKind, EngineUnderTest};
+use crate::common::{Result, fuzzer_err};
+
+/// An in-memory DuckDB database
+pub struct DuckDbEngine {
+    // DuckDB calls block, they run on the blocking thread pool
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl DuckDbEngine {
+    pub fn open_in_memory() -> Result<Self> {
+        let connection = Connection::open_in_memory()
+            .map_err(|e| fuzzer_err(&format!("Failed to open DuckDB database: {}", e)))?;
+        Ok(Self {
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    async fn with_connection<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&Connection) -> duckdb::Result<T> + Send + 'static,
+    ) -> Result<T> {
+        let connection = Arc::clone(&self.connection);
+        tokio::task::spawn_blocking(move || {
+            let connection = connection
+                .lock()
+                .map_err(|_| fuzzer_err("DuckDB connection lock poisoned"))?;
+            f(&connection).map_err(|e| fuzzer_err(&format!("DuckDB error: {}", e)))
+        })
+        .await
+        .map_err(|e| fuzzer_err(&format!("DuckDB task failed: {}", e)))?
+    }
+}
+
+#[async_trait::async_trait]
+impl EngineUnderTest for DuckDbEngine {
+    fn name(&self) -> &str {
+        "duckdb"
+    }
+
+    fn kind(&self) -> EngineKind {
+        EngineKind::DuckDb
+    }
+
+    async fn execute_sql(&self, sql: &str) -> Result<Vec<RecordBatch>> {
+        let sql = sql.to_string();
+        let (column_names, rows) = self
+            .with_connection(move |connection| {
+                let mut stmt = connection.prepare(&sql)?;
+                let mut rows = stmt.query([])?;
+                let column_names = rows
+                    .as_ref()
+                    .map(|stmt| stmt.column_names())
+                    .unwrap_or_default();
+
+                let mut values = Vec::new();
+                while let Some(row) = rows.next()? {
+                    let row_values = (0..column_names.len())
+                        .map(|idx| row.get::<_, Value>(idx))
+                        .collect::<duckdb::Result<Vec<_>>>()?;
+                    values.push(row_values);
+                }
+                Ok((column_names, values))
+            })
+            .await?;
+
+        let rows = rows
+            .into_iter()
+            .map(|row| row.into_iter().map(sql_value).collect())
+            .collect::<Result<Vec<_>>>()?;
+        Ok(vec![rows_to_batch(column_names, rows)?])
+    }
+
+    async fn execute_statement(&self, sql: &str) -> Result<()> {
+        let sql = sql.to_string();
+        self.with_connection(move |connection| connection.execute_batch(&sql))
+            .await
+    }
+}
+
+/// Convert a DuckDB value, types without a counterpart in the fuzzer's tables
+/// fail the query so it isn't compared
+fn sql_value(value: Value) -> Result<SqlValue> {
+    Ok(match value {
+        Value::Null => SqlValue::Null,
+        Value::Boolean(v) => SqlValue::Boolean(v),
+        Value::TinyInt(v) => SqlValue::Integer(v.into()),
+        Value::SmallInt(v) => SqlValue::Integer(v.into()),
+        Value::Int(v) => SqlValue::Integer(v.into()),
+        Value::BigInt(v) => SqlValue::Integer(v.into()),
+        Value::HugeInt(v) => SqlValue::Integer(v),
+        Value::UTinyInt(v) => SqlValue::Integer(v.into()),
+        Value::USmallInt(v) => SqlValue::Integer(v.into()),
+        Value::UInt(v) => SqlValue::Integer(v.into()),
+        Value::UBigInt(v) => SqlValue::Integer(v.into()),
+        Value::Float(v) => SqlValue::Float(v.into()),
+        Value::Double(v) => SqlValue::Float(v),
+        Value::Decimal(v) => SqlValue::Decimal {
+            mantissa: v.mantissa(),
+            scale: v.scale() as i8,
+        },
+        Value::Date32(v) => SqlValue::Date32(v),
+        Value::Timestamp(unit, v) => SqlValue::Timestamp(match unit {
+            TimeUnit::Second => v.saturating_mul(1_000_000),
+            TimeUnit::Millisecond => v.saturating_mul(1_000),
+            TimeUnit::Microsecond => v,
+            TimeUnit::Nanosecond => v / 1_000,
+        }),
+        Value::Text(v) => SqlValue::Text(v),
+        Value::Blob(v) => SqlValue::Blob(v),
+        other => {
+            return Err(fuzzer_err(&format!(
+                "Unsupported DuckDB result value: {:?}",
+                other
+            )));
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn executes_sql_on_duckdb() {
+        let engine = DuckDbEngine::open_in_memory().unwrap();
+        engine
+            .execute_statement("CREATE TABLE t0 (c0 INT, c1 DECIMAL(10, 2))")
+            .await
+            .unwrap();
+        engine
+            .execute_statement("INSERT INTO t0 VALUES (1, 1.50), (NULL, 2.25)")
+            .await
+            .unwrap();
+
+        let batches = engine.execute_sql("SELECT c0, c1 FROM t0").await.unwrap();
+        assert_eq!(batches[0].num_rows(), 2);
+        assert_eq!(batches[0].schema().field(0).name(), "c0");
+
+        assert!(engine.execute_sql("SELECT * FROM missing").await.is_err());
+    }
+}
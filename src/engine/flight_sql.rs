@@ -0,0 +1,90 @@
+use arrow_flight::sql::client::FlightSqlServiceClient;
+use datafusion::arrow::record_batch::RecordBatch;
+use futures::TryStreamExt;
+use tokio::sync::Mutex;
+use tonic::transport::{Channel, Endpoint};
+use tracing::info;
+
+use super::{EngineKind, EngineUnderTest};
+use crate::common::{Result, fuzzer_err};
+
+/// A remote DataFusion instance reached over Arrow Flight SQL
+pub struct FlightSqlEngine {
+    endpoint: String,
+    // The client needs `&mut self` for every call
+    client: Mutex<FlightSqlServiceClient<Channel>>,
+}
+
+impl FlightSqlEngine {
+    /// Connect to a Flight SQL server, e.g. `http://localhost:50051`
+    pub async fn connect(endpoint: &str) -> Result<Self> {
+        let channel = Endpoint::from_shared(endpoint.to_string())
+            .map_err(|e| {
+                fuzzer_err(&format!(
+                    "Invalid Flight SQL endpoint '{}': {}",
+                    endpoint, e
+                ))
+            })?
+            .connect()
+            .await
+            .map_err(|e| {
+                fuzzer_err(&format!(
+                    "Failed to connect to Flight SQL endpoint '{}': {}",
+                    endpoint, e
+                ))
+            })?;
+        info!("Connected to Flight SQL endpoint {}", endpoint);
+
+        Ok(Self {
+            endpoint: endpoint.to_string(),
+            client: Mutex::new(FlightSqlServiceClient::new(channel)),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl EngineUnderTest for FlightSqlEngine {
+    fn name(&self) -> &str {
+        &self.endpoint
+    }
+
+    fn kind(&self) -> EngineKind {
+        EngineKind::FlightSql
+    }
+
+    async fn execute_sql(&self, sql: &str) -> Result<Vec<RecordBatch>> {
+        let mut client = self.client.lock().await;
+        let flight_info = client
+            .execute(sql.to_string(), None)
+            .await
+            .map_err(|e| fuzzer_err(&format!("Flight SQL execution failed: {}", e)))?;
+
+        let mut batches = Vec::new();
+        for endpoint in flight_info.endpoint {
+            let Some(ticket) = endpoint.ticket else {
+                continue;
+            };
+            let stream = client
+                .do_get(ticket)
+                .await
+                .map_err(|e| fuzzer_err(&format!("Flight SQL execution failed: {}", e)))?;
+            let endpoint_batches: Vec<RecordBatch> = stream
+                .try_collect()
+                .await
+                .map_err(|e| fuzzer_err(&format!("Flight SQL execution failed: {}", e)))?;
+            batches.extend(endpoint_batches);
+        }
+
+        Ok(batches)
+    }
+
+    async fn execute_statement(&self, sql: &str) -> Result<()> {
+        self.client
+            .lock()
+            .await
+            .execute_update(sql.to_string(), None)
+            .await
+            .map_err(|e| fuzzer_err(&format!("Flight SQL statement failed: {}", e)))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,537 @@
+//! Engines generated SQL is executed on.
+//!
+//! Queries normally run in the embedded DataFusion session of the fuzzer.
+//! Differential oracles additionally run them on other engines and compare the
+//! results:
+//! - a remote DataFusion instance reached over Arrow Flight SQL (`flight-sql`
+//!   feature), to compare embedded and server deployments and to fuzz the
+//!   Flight SQL layer itself
+//! - embedded DuckDB (`duckdb` feature) and SQLite (`sqlite` feature)
+//!   databases, as reference implementations of SQL semantics
+//!
+//! Generated tables are mirrored to every configured engine, see
+//! [`mirror_statements`].
+//!
+//! Queries generated without SQL text run on a [`dataframe_engine`] wrapping
+//! the embedded session, queries run at the same time on a
+//! [`concurrent_engine`], several statements in one SQL string on a
+//! [`statement_batch_engine`], and queries only planned, to time the planning,
+//! on a [`planning_time_engine`].
+//!
+//! The embedded session marks the end of planning with [`record_planned`], so
+//! the runner can time planning and execution apart and reuse the plan for its
+//! statistics, see [`with_planning_timer`].
+
+pub mod concurrent_engine;
+pub mod dataframe_engine;
+#[cfg(feature = "duckdb")]
+pub mod duckdb_engine;
+#[cfg(feature = "flight-sql")]
+pub mod flight_sql;
+pub mod planning_time_engine;
+pub mod result_spool;
+#[cfg(any(feature = "duckdb", feature = "sqlite"))]
+mod row_batches;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_engine;
+pub mod statement_batch_engine;
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
+
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::execution::TaskContext;
+use datafusion::logical_expr::LogicalPlan;
+use datafusion::physical_plan;
+use datafusion::prelude::SessionContext;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+use tracing::warn;
+
+use crate::common::{Result, fuzzer_err, util};
+use crate::fuzz_context::RunnerConfig;
+use result_spool::{ResultSpooler, SpooledResult};
+
+/// When a query finished planning, and its logical plan, see
+/// [`with_planning_timer`]
+#[derive(Debug, Default)]
+pub struct PlanningTimer {
+    planned_at: OnceLock<Instant>,
+    logical_plan: OnceLock<LogicalPlan>,
+    planned: Notify,
+}
+
+impl PlanningTimer {
+    /// When the query finished planning, `None` while it is planned or if the
+    /// engine doesn't report it
+    pub fn planned_at(&self) -> Option<Instant> {
+        self.planned_at.get().copied()
+    }
+
+    /// Logical plan of the query, `None` while it is planned or if the engine
+    /// doesn't report it
+    pub fn logical_plan(&self) -> Option<&LogicalPlan> {
+        self.logical_plan.get()
+    }
+
+    /// Wait until the query finished planning
+    pub async fn planned(&self) -> Instant {
+        loop {
+            // Registered before checking, so a concurrent `record` wakes it
+            let planned = self.planned.notified();
+            if let Some(planned_at) = self.planned_at() {
+                return planned_at;
+            }
+            planned.await;
+        }
+    }
+
+    fn record(&self, logical_plan: &LogicalPlan) {
+        if self.planned_at.set(Instant::now()).is_ok() {
+            let _ = self.logical_plan.set(logical_plan.clone());
+            self.planned.notify_waiters();
+        }
+    }
+}
+
+tokio::task_local! {
+    static PLANNING_TIMER: Arc<PlanningTimer>;
+}
+
+/// Run `future`, recording in `timer` when the query it executes finishes
+/// planning. Engines that don't report it leave `timer` unset.
+pub async fn with_planning_timer<F: std::future::Future>(
+    timer: Arc<PlanningTimer>,
+    future: F,
+) -> F::Output {
+    PLANNING_TIMER.scope(timer, future).await
+}
+
+/// Mark the end of planning (parsing, logical and physical planning) of the
+/// query executed in the current task, with its `logical_plan`. Does nothing
+/// outside of [`with_planning_timer`], and only the first call of a query
+/// counts.
+pub fn record_planned(logical_plan: &LogicalPlan) {
+    let _ = PLANNING_TIMER.try_with(|timer| timer.record(logical_plan));
+}
+
+/// Kind of an [`EngineUnderTest`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EngineKind {
+    DataFusion,
+    FlightSql,
+    DuckDb,
+    Sqlite,
+}
+
+impl EngineKind {
+    /// Whether the engine is DataFusion itself, so it accepts every generated
+    /// statement and must behave exactly like the embedded session
+    pub fn speaks_datafusion_sql(self) -> bool {
+        matches!(self, Self::DataFusion | Self::FlightSql)
+    }
+}
+
+/// The first rows of a query result, and the number of rows of the whole
+/// result
+#[derive(Debug)]
+pub struct LimitedResult {
+    pub batches: Vec<RecordBatch>,
+    pub total_rows: usize,
+    /// The whole result, when it was truncated and spooling was requested
+    pub spooled: Option<SpooledResult>,
+}
+
+impl LimitedResult {
+    /// Whether rows were dropped
+    pub fn is_truncated(&self) -> bool {
+        util::count_total_rows(&self.batches) < self.total_rows
+    }
+}
+
+/// Collects a [`LimitedResult`] batch by batch
+pub struct ResultLimiter {
+    max_rows: usize,
+    /// Where to spool the result once it outgrows `max_rows`
+    spool_dir: Option<PathBuf>,
+    spooler: Option<ResultSpooler>,
+    batches: Vec<RecordBatch>,
+    total_rows: usize,
+}
+
+impl ResultLimiter {
+    pub fn new(max_rows: usize, spool_dir: Option<&Path>) -> Self {
+        Self {
+            max_rows,
+            spool_dir: spool_dir.map(Path::to_path_buf),
+            spooler: None,
+            batches: Vec::new(),
+            total_rows: 0,
+        }
+    }
+
+    /// Count the rows of `batch`, keeping the ones within the first `max_rows`
+    /// rows of the result
+    pub fn push(&mut self, batch: RecordBatch) -> Result<()> {
+        let kept = self.total_rows.min(self.max_rows);
+        let keep = batch.num_rows().min(self.max_rows - kept);
+        self.total_rows += batch.num_rows();
+
+        if self.spooler.is_none()
+            && self.total_rows > self.max_rows
+            && let Some(spool_dir) = &self.spool_dir
+        {
+            // The kept batches are all rows before `batch`
+            let mut spooler = ResultSpooler::create(spool_dir, &batch.schema())?;
+            for kept_batch in &self.batches {
+                spooler.write(kept_batch)?;
+            }
+            self.spooler = Some(spooler);
+        }
+        if let Some(spooler) = self.spooler.as_mut() {
+            spooler.write(&batch)?;
+        }
+
+        if keep == batch.num_rows() {
+            self.batches.push(batch);
+        } else if keep > 0 {
+            self.batches.push(batch.slice(0, keep));
+        }
+        Ok(())
+    }
+
+    pub fn finish(self) -> Result<LimitedResult> {
+        Ok(LimitedResult {
+            batches: self.batches,
+            total_rows: self.total_rows,
+            spooled: self.spooler.map(ResultSpooler::finish).transpose()?,
+        })
+    }
+}
+
+/// An engine executing generated SQL.
+///
+/// Oracles are written against this trait, so a differential oracle works
+/// with every engine implementing it.
+#[async_trait::async_trait]
+pub trait EngineUnderTest: Send + Sync {
+    /// Name for logs and reports
+    fn name(&self) -> &str;
+
+    fn kind(&self) -> EngineKind;
+
+    /// Execute a query and collect its result
+    async fn execute_sql(&self, sql: &str) -> Result<Vec<RecordBatch>>;
+
+    /// Execute a query, keeping only the first `max_rows` rows of its result,
+    /// and with `spool_dir` writing a larger result to a file there. Engines
+    /// that can stream the result override this to never hold more than
+    /// `max_rows` rows.
+    async fn execute_sql_limited(
+        &self,
+        sql: &str,
+        max_rows: usize,
+        spool_dir: Option<&Path>,
+    ) -> Result<LimitedResult> {
+        let mut limiter = ResultLimiter::new(max_rows, spool_dir);
+        for batch in self.execute_sql(sql).await? {
+            limiter.push(batch)?;
+        }
+        limiter.finish()
+    }
+
+    /// Execute a statement without result rows, e.g. DDL or `INSERT`
+    async fn execute_statement(&self, sql: &str) -> Result<()> {
+        self.execute_sql(sql).await.map(|_| ())
+    }
+}
+
+/// An in-process DataFusion session
+pub struct EmbeddedDataFusion {
+    session_context: Arc<SessionContext>,
+}
+
+impl EmbeddedDataFusion {
+    pub fn new(session_context: Arc<SessionContext>) -> Self {
+        Self { session_context }
+    }
+
+    /// Plan a query into a physical plan and the context to execute it in,
+    /// recording the end of planning. Physical planning errors are reported as
+    /// execution errors, like when `DataFrame::collect` plans the query.
+    async fn plan(
+        &self,
+        sql: &str,
+    ) -> Result<(Arc<dyn physical_plan::ExecutionPlan>, Arc<TaskContext>)> {
+        let dataframe = self
+            .session_context
+            .sql(sql)
+            .await
+            .map_err(|e| fuzzer_err(&format!("Query planning failed: {}", e)))?;
+        let task_ctx = Arc::new(dataframe.task_ctx());
+        let logical_plan = dataframe.logical_plan().clone();
+        let plan = dataframe
+            .create_physical_plan()
+            .await
+            .map_err(|e| fuzzer_err(&format!("Query execution failed: {}", e)))?;
+        record_planned(&logical_plan);
+        Ok((plan, task_ctx))
+    }
+}
+
+#[async_trait::async_trait]
+impl EngineUnderTest for EmbeddedDataFusion {
+    fn name(&self) -> &str {
+        "embedded"
+    }
+
+    fn kind(&self) -> EngineKind {
+        EngineKind::DataFusion
+    }
+
+    async fn execute_sql(&self, sql: &str) -> Result<Vec<RecordBatch>> {
+        let (plan, task_ctx) = self.plan(sql).await?;
+        physical_plan::collect(plan, task_ctx)
+            .await
+            .map_err(|e| fuzzer_err(&format!("Query execution failed: {}", e)))
+    }
+
+    /// Consume the result stream, only counting (or spooling) the rows past
+    /// `max_rows`, so huge results (e.g. of cross joins) never need to fit in
+    /// memory
+    async fn execute_sql_limited(
+        &self,
+        sql: &str,
+        max_rows: usize,
+        spool_dir: Option<&Path>,
+    ) -> Result<LimitedResult> {
+        let (plan, task_ctx) = self.plan(sql).await?;
+        let mut stream = physical_plan::execute_stream(plan, task_ctx)
+            .map_err(|e| fuzzer_err(&format!("Query execution failed: {}", e)))?;
+
+        let mut limiter = ResultLimiter::new(max_rows, spool_dir);
+        while let Some(batch) = stream.next().await {
+            let batch = batch.map_err(|e| fuzzer_err(&format!("Query execution failed: {}", e)))?;
+            limiter.push(batch)?;
+        }
+        limiter.finish()
+    }
+}
+
+/// Embedded reference engine configured with `reference_engines`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReferenceEngine {
+    #[serde(rename = "DuckDb", alias = "DuckDB", alias = "duckdb")]
+    DuckDb,
+    #[serde(rename = "Sqlite", alias = "SQLite", alias = "sqlite")]
+    Sqlite,
+}
+
+impl ReferenceEngine {
+    fn open(self) -> Result<Arc<dyn EngineUnderTest>> {
+        match self {
+            Self::DuckDb => {
+                #[cfg(feature = "duckdb")]
+                {
+                    Ok(Arc::new(duckdb_engine::DuckDbEngine::open_in_memory()?))
+                }
+
+                #[cfg(not(feature = "duckdb"))]
+                {
+                    Err(fuzzer_err(
+                        "reference engine DuckDb requires building with `--features duckdb`",
+                    ))
+                }
+            }
+            Self::Sqlite => {
+                #[cfg(feature = "sqlite")]
+                {
+                    Ok(Arc::new(sqlite_engine::SqliteEngine::open_in_memory()?))
+                }
+
+                #[cfg(not(feature = "sqlite"))]
+                {
+                    Err(fuzzer_err(
+                        "reference engine Sqlite requires building with `--features sqlite`",
+                    ))
+                }
+            }
+        }
+    }
+}
+
+/// Connect to the engines configured with `flight_sql_endpoint` and
+/// `reference_engines`, in that order
+pub async fn connect_engines(config: &RunnerConfig) -> Result<Vec<Arc<dyn EngineUnderTest>>> {
+    let mut engines: Vec<Arc<dyn EngineUnderTest>> = Vec::new();
+
+    if let Some(endpoint) = &config.flight_sql_endpoint {
+        #[cfg(feature = "flight-sql")]
+        {
+            let engine = flight_sql::FlightSqlEngine::connect(endpoint).await?;
+            engines.push(Arc::new(engine));
+        }
+
+        #[cfg(not(feature = "flight-sql"))]
+        {
+            return Err(fuzzer_err(&format!(
+                "flight_sql_endpoint = '{}' requires building with `--features flight-sql`",
+                endpoint
+            )));
+        }
+    }
+
+    for reference_engine in &config.reference_engines {
+        engines.push(reference_engine.open()?);
+    }
+
+    Ok(engines)
+}
+
+/// Execute statements the fuzzer already ran in its own session on every
+/// engine, so they hold the same tables.
+///
+/// An engine with another SQL dialect may reject a statement, e.g. a type it
+/// doesn't support. The remaining statements are skipped for it, which only
+/// leaves the table out of its catalog: queries on it fail there and are not
+/// compared. DataFusion engines must accept every statement.
+pub async fn mirror_statements(
+    engines: &[Arc<dyn EngineUnderTest>],
+    statements: &[&str],
+) -> Result<()> {
+    for engine in engines {
+        for sql in statements {
+            if let Err(e) = engine.execute_statement(sql).await {
+                if engine.kind().speaks_datafusion_sql() {
+                    return Err(fuzzer_err(&format!(
+                        "Failed to mirror statement to {}: {}",
+                        engine.name(),
+                        e
+                    )));
+                }
+                warn!(
+                    "{} rejected mirrored statement {}: {}",
+                    engine.name(),
+                    sql,
+                    e
+                );
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn embedded_engine_executes_sql() {
+        let engine = EmbeddedDataFusion::new(Arc::new(SessionContext::new()));
+        engine
+            .execute_statement("CREATE TABLE t0 (c0 INT)")
+            .await
+            .unwrap();
+        engine
+            .execute_statement("INSERT INTO t0 VALUES (1), (2)")
+            .await
+            .unwrap();
+
+        let batches = engine.execute_sql("SELECT c0 FROM t0").await.unwrap();
+        let rows: usize = batches.iter().map(RecordBatch::num_rows).sum();
+        assert_eq!(rows, 2);
+
+        let err = engine
+            .execute_sql("SELECT * FROM missing")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Query planning failed"));
+    }
+
+    #[tokio::test]
+    async fn limited_execution_keeps_the_first_rows_and_counts_all() {
+        let engine = EmbeddedDataFusion::new(Arc::new(SessionContext::new()));
+        let sql = "SELECT a.value FROM generate_series(1, 100) AS a \
+                   CROSS JOIN generate_series(1, 100) AS b";
+
+        let limited = engine.execute_sql_limited(sql, 10, None).await.unwrap();
+        let rows: usize = limited.batches.iter().map(RecordBatch::num_rows).sum();
+        assert_eq!(rows, 10);
+        assert_eq!(limited.total_rows, 10_000);
+        assert!(limited.is_truncated());
+        assert!(limited.spooled.is_none());
+
+        let spool_dir = std::env::temp_dir();
+        let limited = engine
+            .execute_sql_limited(sql, 10, Some(&spool_dir))
+            .await
+            .unwrap();
+        let spooled = limited.spooled.unwrap();
+        assert_eq!(spooled.total_rows(), 10_000);
+        let spooled_rows: usize = spooled
+            .batches()
+            .unwrap()
+            .map(|batch| batch.unwrap().num_rows())
+            .sum();
+        assert_eq!(spooled_rows, 10_000);
+
+        let limited = engine
+            .execute_sql_limited("SELECT 1", 10, None)
+            .await
+            .unwrap();
+        assert_eq!(limited.total_rows, 1);
+        assert!(!limited.is_truncated());
+    }
+
+    #[tokio::test]
+    async fn embedded_engine_records_the_end_of_planning() {
+        let engine = EmbeddedDataFusion::new(Arc::new(SessionContext::new()));
+        let start = Instant::now();
+        let timer = Arc::new(PlanningTimer::default());
+        with_planning_timer(Arc::clone(&timer), engine.execute_sql("SELECT 1"))
+            .await
+            .unwrap();
+        let planned_at = timer.planned_at().unwrap();
+        assert!(planned_at >= start);
+        assert_eq!(timer.planned().await, planned_at);
+        assert!(timer.logical_plan().is_some());
+
+        let timer = Arc::new(PlanningTimer::default());
+        let _ = with_planning_timer(
+            Arc::clone(&timer),
+            engine.execute_sql("SELECT * FROM missing"),
+        )
+        .await;
+        assert!(timer.planned_at().is_none());
+
+        // Outside of a timer the engine runs as before
+        engine.execute_sql("SELECT 1").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn mirroring_to_datafusion_engines_must_succeed() {
+        let engine: Arc<dyn EngineUnderTest> =
+            Arc::new(EmbeddedDataFusion::new(Arc::new(SessionContext::new())));
+        mirror_statements(&[Arc::clone(&engine)], &["CREATE TABLE t0 (c0 INT)"])
+            .await
+            .unwrap();
+
+        let err = mirror_statements(&[engine], &["CREATE TABLE t0 (c0 INT)"])
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Failed to mirror statement"));
+    }
+
+    #[test]
+    fn parses_reference_engines() {
+        let engines: Vec<ReferenceEngine> =
+            serde_json::from_str(r#"["DuckDb", "sqlite"]"#).unwrap();
+        assert_eq!(
+            engines,
+            vec![ReferenceEngine::DuckDb, ReferenceEngine::Sqlite]
+        );
+    }
+}
@@ -0,0 +1,86 @@
+//! Planning of queries without executing them, timed.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use datafusion::arrow::array::Int64Array;
+use datafusion::arrow::datatypes::{DataType, Field, Schema};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::prelude::SessionContext;
+
+use super::{EngineKind, EngineUnderTest};
+use crate::common::{Result, fuzzer_err};
+
+/// Name of the only column of the results of [`PlanningTimeEngine`]
+pub const PLANNING_TIME_COLUMN: &str = "planning_us";
+
+/// Parses, plans and optimizes queries into physical plans in a session, and
+/// returns the time it took in microseconds as a single row instead of the
+/// rows of the query.
+pub struct PlanningTimeEngine {
+    session_context: Arc<SessionContext>,
+}
+
+impl PlanningTimeEngine {
+    pub fn new(session_context: Arc<SessionContext>) -> Self {
+        Self { session_context }
+    }
+}
+
+#[async_trait::async_trait]
+impl EngineUnderTest for PlanningTimeEngine {
+    fn name(&self) -> &str {
+        "planning-time"
+    }
+
+    fn kind(&self) -> EngineKind {
+        EngineKind::DataFusion
+    }
+
+    async fn execute_sql(&self, sql: &str) -> Result<Vec<RecordBatch>> {
+        let start = Instant::now();
+        let dataframe = self
+            .session_context
+            .sql(sql)
+            .await
+            .map_err(|e| fuzzer_err(&format!("Query planning failed: {}", e)))?;
+        dataframe
+            .create_physical_plan()
+            .await
+            .map_err(|e| fuzzer_err(&format!("Query planning failed: {}", e)))?;
+        let elapsed_us = start.elapsed().as_micros() as i64;
+
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            PLANNING_TIME_COLUMN,
+            DataType::Int64,
+            false,
+        )]));
+        let batch =
+            RecordBatch::try_new(schema, vec![Arc::new(Int64Array::from(vec![elapsed_us]))])
+                .map_err(|e| fuzzer_err(&format!("Failed to build planning time: {}", e)))?;
+        Ok(vec![batch])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::util::count_total_rows;
+
+    #[tokio::test]
+    async fn returns_the_planning_time_instead_of_rows() {
+        let engine = PlanningTimeEngine::new(Arc::new(SessionContext::new()));
+        let batches = engine
+            .execute_sql("SELECT v FROM generate_series(1, 1000000) AS s(v) ORDER BY v")
+            .await
+            .unwrap();
+        assert_eq!(count_total_rows(&batches), 1);
+        assert_eq!(batches[0].schema().field(0).name(), PLANNING_TIME_COLUMN);
+
+        let err = engine
+            .execute_sql("SELECT * FROM missing")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Query planning failed"));
+    }
+}
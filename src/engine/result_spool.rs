@@ -0,0 +1,141 @@
+//! Query results spooled to temporary Arrow IPC files.
+//!
+//! With `max_result_rows`, only the first rows of a large result stay in
+//! memory. With `spool_results` the whole result is also written to an IPC
+//! file while it streams in, so oracles can still compare it in full, reading
+//! both sides chunk by chunk instead of holding them in memory together.
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::arrow::ipc::reader::FileReader;
+use datafusion::arrow::ipc::writer::FileWriter;
+use datafusion::arrow::record_batch::RecordBatch;
+
+use crate::common::{Result, fuzzer_err};
+
+/// Distinguishes the spool files of one process
+static NEXT_SPOOL_FILE: AtomicU64 = AtomicU64::new(0);
+
+/// Writes the batches of one result to a new IPC file in a directory
+pub struct ResultSpooler {
+    path: PathBuf,
+    writer: FileWriter<BufWriter<File>>,
+    total_rows: usize,
+}
+
+impl ResultSpooler {
+    pub fn create(dir: &Path, schema: &SchemaRef) -> Result<Self> {
+        let path = dir.join(format!(
+            "result-{}-{}.arrow",
+            std::process::id(),
+            NEXT_SPOOL_FILE.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = File::create(&path).map_err(|e| {
+            fuzzer_err(&format!(
+                "Failed to create result spool file '{}': {}",
+                path.display(),
+                e
+            ))
+        })?;
+        let writer = FileWriter::try_new(BufWriter::new(file), schema)
+            .map_err(|e| fuzzer_err(&format!("Failed to spool result: {}", e)))?;
+
+        Ok(Self {
+            path,
+            writer,
+            total_rows: 0,
+        })
+    }
+
+    pub fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        self.total_rows += batch.num_rows();
+        self.writer
+            .write(batch)
+            .map_err(|e| fuzzer_err(&format!("Failed to spool result: {}", e)))
+    }
+
+    pub fn finish(mut self) -> Result<SpooledResult> {
+        self.writer
+            .finish()
+            .map_err(|e| fuzzer_err(&format!("Failed to spool result: {}", e)))?;
+        Ok(SpooledResult {
+            path: self.path,
+            total_rows: self.total_rows,
+        })
+    }
+}
+
+/// A whole query result in an IPC file, deleted when dropped
+#[derive(Debug)]
+pub struct SpooledResult {
+    path: PathBuf,
+    total_rows: usize,
+}
+
+impl SpooledResult {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn total_rows(&self) -> usize {
+        self.total_rows
+    }
+
+    /// Read the batches back one at a time
+    pub fn batches(&self) -> Result<impl Iterator<Item = Result<RecordBatch>> + use<>> {
+        let file = File::open(&self.path).map_err(|e| {
+            fuzzer_err(&format!(
+                "Failed to open result spool file '{}': {}",
+                self.path.display(),
+                e
+            ))
+        })?;
+        let reader = FileReader::try_new(BufReader::new(file), None)
+            .map_err(|e| fuzzer_err(&format!("Failed to read spooled result: {}", e)))?;
+
+        Ok(reader.map(|batch| {
+            batch.map_err(|e| fuzzer_err(&format!("Failed to read spooled result: {}", e)))
+        }))
+    }
+}
+
+impl Drop for SpooledResult {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::arrow::array::{ArrayRef, Int64Array};
+    use std::sync::Arc;
+
+    #[test]
+    fn spooled_batches_read_back_and_the_file_is_deleted() {
+        let batch = RecordBatch::try_from_iter([(
+            "c0",
+            Arc::new(Int64Array::from(vec![1, 2, 3])) as ArrayRef,
+        )])
+        .unwrap();
+
+        let mut spooler = ResultSpooler::create(&std::env::temp_dir(), &batch.schema()).unwrap();
+        spooler.write(&batch).unwrap();
+        spooler.write(&batch.slice(1, 2)).unwrap();
+        let spooled = spooler.finish().unwrap();
+        assert_eq!(spooled.total_rows(), 5);
+
+        let batches: Vec<RecordBatch> = spooled.batches().unwrap().map(Result::unwrap).collect();
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[1], batch.slice(1, 2));
+
+        let path = spooled.path().to_path_buf();
+        assert!(path.exists());
+        drop(spooled);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,302 @@
+//! Conversion of rows read through a row-based client API into Arrow batches,
+//! so results of embedded reference engines can be compared with DataFusion's.
+
+use std::sync::Arc;
+
+use datafusion::arrow::array::{
+    ArrayRef, BinaryArray, BooleanArray, Date32Array, Decimal128Array, Float64Array, Int64Array,
+    NullArray, StringArray, TimestampMicrosecondArray, UInt64Array,
+};
+use datafusion::arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::error::DataFusionError;
+
+use crate::common::{Result, fuzzer_err};
+
+/// A value read from a reference engine
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum SqlValue {
+    Null,
+    Boolean(bool),
+    Integer(i128),
+    Float(f64),
+    Decimal {
+        mantissa: i128,
+        scale: i8,
+    },
+    /// Days since the epoch
+    Date32(i32),
+    /// Microseconds since the epoch, in UTC
+    Timestamp(i64),
+    Text(String),
+    Blob(Vec<u8>),
+}
+
+impl SqlValue {
+    fn data_type(&self) -> DataType {
+        match self {
+            Self::Null => DataType::Null,
+            Self::Boolean(_) => DataType::Boolean,
+            Self::Integer(_) => DataType::Int64,
+            Self::Float(_) => DataType::Float64,
+            Self::Decimal { scale, .. } => DataType::Decimal128(38, *scale),
+            Self::Date32(_) => DataType::Date32,
+            Self::Timestamp(_) => DataType::Timestamp(TimeUnit::Microsecond, None),
+            Self::Text(_) => DataType::Utf8,
+            Self::Blob(_) => DataType::Binary,
+        }
+    }
+
+    fn to_text(&self) -> Option<String> {
+        match self {
+            Self::Null => None,
+            Self::Boolean(v) => Some(v.to_string()),
+            Self::Integer(v) => Some(v.to_string()),
+            Self::Float(v) => Some(v.to_string()),
+            Self::Decimal { mantissa, scale } => Some(decimal_to_string(*mantissa, *scale)),
+            Self::Date32(v) => Some(v.to_string()),
+            Self::Timestamp(v) => Some(v.to_string()),
+            Self::Text(v) => Some(v.clone()),
+            Self::Blob(v) => Some(v.iter().map(|byte| format!("{:02x}", byte)).collect()),
+        }
+    }
+}
+
+fn decimal_to_string(mantissa: i128, scale: i8) -> String {
+    if scale <= 0 {
+        return mantissa.to_string();
+    }
+    let digits = format!(
+        "{:0>width$}",
+        mantissa.unsigned_abs(),
+        width = scale as usize + 1
+    );
+    let (integer, fraction) = digits.split_at(digits.len() - scale as usize);
+    let sign = if mantissa < 0 { "-" } else { "" };
+    format!("{}{}.{}", sign, integer, fraction)
+}
+
+/// Type of a column holding `values`. Engines with dynamic typing like SQLite
+/// may return different types in one column: integers mixed with floats become
+/// floats, any other mix becomes text.
+fn column_type(values: &[&SqlValue]) -> DataType {
+    let mut column_type = DataType::Null;
+    for value in values {
+        column_type = match (column_type, value.data_type()) {
+            (DataType::Null, value_type) | (value_type, DataType::Null) => value_type,
+            (column_type, value_type) if column_type == value_type => column_type,
+            (DataType::Int64, DataType::Float64) | (DataType::Float64, DataType::Int64) => {
+                DataType::Float64
+            }
+            (DataType::Decimal128(_, left), DataType::Decimal128(_, right)) => {
+                DataType::Decimal128(38, left.max(right))
+            }
+            _ => DataType::Utf8,
+        };
+    }
+    column_type
+}
+
+fn build_column(values: &[&SqlValue]) -> Result<ArrayRef> {
+    let array: ArrayRef = match column_type(values) {
+        DataType::Null => Arc::new(NullArray::new(values.len())),
+        DataType::Boolean => Arc::new(
+            values
+                .iter()
+                .map(|value| match value {
+                    SqlValue::Boolean(v) => Some(*v),
+                    _ => None,
+                })
+                .collect::<BooleanArray>(),
+        ),
+        DataType::Int64 => build_integer_column(values),
+        DataType::Float64 => Arc::new(
+            values
+                .iter()
+                .map(|value| match value {
+                    SqlValue::Float(v) => Some(*v),
+                    SqlValue::Integer(v) => Some(*v as f64),
+                    _ => None,
+                })
+                .collect::<Float64Array>(),
+        ),
+        DataType::Decimal128(precision, scale) => Arc::new(
+            values
+                .iter()
+                .map(|value| match value {
+                    SqlValue::Decimal {
+                        mantissa,
+                        scale: value_scale,
+                    } => Some(mantissa * 10_i128.pow((scale - value_scale) as u32)),
+                    _ => None,
+                })
+                .collect::<Decimal128Array>()
+                .with_precision_and_scale(precision, scale)
+                .map_err(DataFusionError::from)?,
+        ),
+        DataType::Date32 => Arc::new(
+            values
+                .iter()
+                .map(|value| match value {
+                    SqlValue::Date32(v) => Some(*v),
+                    _ => None,
+                })
+                .collect::<Date32Array>(),
+        ),
+        DataType::Timestamp(_, _) => Arc::new(
+            values
+                .iter()
+                .map(|value| match value {
+                    SqlValue::Timestamp(v) => Some(*v),
+                    _ => None,
+                })
+                .collect::<TimestampMicrosecondArray>(),
+        ),
+        DataType::Binary => Arc::new(
+            values
+                .iter()
+                .map(|value| match value {
+                    SqlValue::Blob(v) => Some(v.as_slice()),
+                    _ => None,
+                })
+                .collect::<BinaryArray>(),
+        ),
+        _ => Arc::new(
+            values
+                .iter()
+                .map(|value| value.to_text())
+                .collect::<StringArray>(),
+        ),
+    };
+    Ok(array)
+}
+
+/// Integers are returned as `Int64` if they fit, as `UInt64` or `Decimal128`
+/// otherwise, e.g. for DuckDB's `UBIGINT` and `HUGEINT`
+fn build_integer_column(values: &[&SqlValue]) -> ArrayRef {
+    let integers: Vec<Option<i128>> = values
+        .iter()
+        .map(|value| match value {
+            SqlValue::Integer(v) => Some(*v),
+            _ => None,
+        })
+        .collect();
+
+    if let Ok(array) = integers
+        .iter()
+        .map(|v| v.map(i64::try_from).transpose())
+        .collect::<std::result::Result<Int64Array, _>>()
+    {
+        return Arc::new(array);
+    }
+    if let Ok(array) = integers
+        .iter()
+        .map(|v| v.map(u64::try_from).transpose())
+        .collect::<std::result::Result<UInt64Array, _>>()
+    {
+        return Arc::new(array);
+    }
+    Arc::new(
+        integers
+            .into_iter()
+            .collect::<Decimal128Array>()
+            .with_precision_and_scale(38, 0)
+            .expect("scale 0 is valid for every precision"),
+    )
+}
+
+/// Build a batch from rows of `column_names.len()` values each
+pub(crate) fn rows_to_batch(
+    column_names: Vec<String>,
+    rows: Vec<Vec<SqlValue>>,
+) -> Result<RecordBatch> {
+    let mut fields = Vec::with_capacity(column_names.len());
+    let mut columns = Vec::with_capacity(column_names.len());
+    for (column_idx, column_name) in column_names.into_iter().enumerate() {
+        let values = rows
+            .iter()
+            .map(|row| {
+                row.get(column_idx)
+                    .ok_or_else(|| fuzzer_err(&format!("Row is missing column {}", column_name)))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let column = build_column(&values)?;
+        fields.push(Field::new(column_name, column.data_type().clone(), true));
+        columns.push(column);
+    }
+
+    let batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)
+        .map_err(DataFusionError::from)?;
+    Ok(batch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::arrow::util::pretty::pretty_format_batches;
+
+    #[test]
+    fn builds_typed_columns() {
+        let batch = rows_to_batch(
+            vec!["i".to_string(), "d".to_string(), "n".to_string()],
+            vec![
+                vec![
+                    SqlValue::Integer(1),
+                    SqlValue::Decimal {
+                        mantissa: 15,
+                        scale: 1,
+                    },
+                    SqlValue::Null,
+                ],
+                vec![
+                    SqlValue::Null,
+                    SqlValue::Decimal {
+                        mantissa: 125,
+                        scale: 2,
+                    },
+                    SqlValue::Null,
+                ],
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(batch.column(0).data_type(), &DataType::Int64);
+        assert_eq!(batch.column(1).data_type(), &DataType::Decimal128(38, 2));
+        assert_eq!(batch.column(2).data_type(), &DataType::Null);
+        let formatted = pretty_format_batches(&[batch]).unwrap().to_string();
+        assert!(formatted.contains("1.50"));
+        assert!(formatted.contains("1.25"));
+    }
+
+    #[test]
+    fn mixed_column_types_are_widened() {
+        let batch = rows_to_batch(
+            vec!["num".to_string(), "mixed".to_string(), "big".to_string()],
+            vec![
+                vec![
+                    SqlValue::Integer(1),
+                    SqlValue::Integer(1),
+                    SqlValue::Integer(u64::MAX as i128),
+                ],
+                vec![
+                    SqlValue::Float(0.5),
+                    SqlValue::Text("a".to_string()),
+                    SqlValue::Integer(0),
+                ],
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(batch.column(0).data_type(), &DataType::Float64);
+        assert_eq!(batch.column(1).data_type(), &DataType::Utf8);
+        assert_eq!(batch.column(2).data_type(), &DataType::UInt64);
+    }
+
+    #[test]
+    fn formats_decimals_as_text() {
+        assert_eq!(decimal_to_string(-5, 2), "-0.05");
+        assert_eq!(decimal_to_string(1234, 1), "123.4");
+        assert_eq!(decimal_to_string(7, 0), "7");
+    }
+}
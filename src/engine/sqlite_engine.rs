@@ -0,0 +1,119 @@
+use std::sync::{Arc, Mutex};
+
+use datafusion::arrow::record_batch::RecordBatch;
+use rusqlite::Connection;
+use rusqlite::types::Value;
+
+use super::row_batches::{SqlValue, rows_to_batch};
+use super::{EngineKind, EngineUnderTest};
+use crate::common::{Result, fuzzer_err};
+
+/// An in-memory SQLite database.
+///
+/// SQLite is dynamically typed: booleans are integers, and dates and
+/// timestamps are text, so only queries over numbers and strings compare
+/// meaningfully with DataFusion.
+pub struct SqliteEngine {
+    // SQLite calls block, they run on the blocking thread pool
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl SqliteEngine {
+    pub fn open_in_memory() -> Result<Self> {
+        let connection = Connection::open_in_memory()
+            .map_err(|e| fuzzer_err(&format!("Failed to open SQLite database: {}", e)))?;
+        Ok(Self {
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    async fn with_connection<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&Connection) -> rusqlite::Result<T> + Send + 'static,
+    ) -> Result<T> {
+        let connection = Arc::clone(&self.connection);
+        tokio::task::spawn_blocking(move || {
+            let connection = connection
+                .lock()
+                .map_err(|_| fuzzer_err("SQLite connection lock poisoned"))?;
+            f(&connection).map_err(|e| fuzzer_err(&format!("SQLite error: {}", e)))
+        })
+        .await
+        .map_err(|e| fuzzer_err(&format!("SQLite task failed: {}", e)))?
+    }
+}
+
+#[async_trait::async_trait]
+impl EngineUnderTest for SqliteEngine {
+    fn name(&self) -> &str {
+        "sqlite"
+    }
+
+    fn kind(&self) -> EngineKind {
+        EngineKind::Sqlite
+    }
+
+    async fn execute_sql(&self, sql: &str) -> Result<Vec<RecordBatch>> {
+        let sql = sql.to_string();
+        let (column_names, rows) = self
+            .with_connection(move |connection| {
+                let mut stmt = connection.prepare(&sql)?;
+                let column_names: Vec<String> =
+                    stmt.column_names().into_iter().map(String::from).collect();
+                let column_count = column_names.len();
+
+                let mut rows = stmt.query([])?;
+                let mut values = Vec::new();
+                while let Some(row) = rows.next()? {
+                    let row_values = (0..column_count)
+                        .map(|idx| row.get::<_, Value>(idx).map(sql_value))
+                        .collect::<rusqlite::Result<Vec<_>>>()?;
+                    values.push(row_values);
+                }
+                Ok((column_names, values))
+            })
+            .await?;
+
+        Ok(vec![rows_to_batch(column_names, rows)?])
+    }
+
+    async fn execute_statement(&self, sql: &str) -> Result<()> {
+        let sql = sql.to_string();
+        self.with_connection(move |connection| connection.execute_batch(&sql))
+            .await
+    }
+}
+
+fn sql_value(value: Value) -> SqlValue {
+    match value {
+        Value::Null => SqlValue::Null,
+        Value::Integer(v) => SqlValue::Integer(v.into()),
+        Value::Real(v) => SqlValue::Float(v),
+        Value::Text(v) => SqlValue::Text(v),
+        Value::Blob(v) => SqlValue::Blob(v),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn executes_sql_on_sqlite() {
+        let engine = SqliteEngine::open_in_memory().unwrap();
+        engine
+            .execute_statement("CREATE TABLE t0 (c0 INT, c1 VARCHAR)")
+            .await
+            .unwrap();
+        engine
+            .execute_statement("INSERT INTO t0 VALUES (1, 'a'), (NULL, 'b')")
+            .await
+            .unwrap();
+
+        let batches = engine.execute_sql("SELECT c0, c1 FROM t0").await.unwrap();
+        assert_eq!(batches[0].num_rows(), 2);
+        assert_eq!(batches[0].schema().field(1).name(), "c1");
+
+        assert!(engine.execute_sql("SELECT * FROM missing").await.is_err());
+    }
+}
@@ -0,0 +1,89 @@
+//! Execution of several statements in one SQL string, the way clients such as
+//! `datafusion-cli` run scripts.
+
+use std::sync::Arc;
+
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::prelude::SessionContext;
+use datafusion::sql::parser::DFParser;
+
+use super::{EngineKind, EngineUnderTest};
+use crate::common::{Result, fuzzer_err};
+
+/// Splits the SQL it is given into statements with DataFusion's parser and
+/// executes them in order in a session. [`SessionContext::sql`] only accepts
+/// a single statement.
+pub struct StatementBatchEngine {
+    session_context: Arc<SessionContext>,
+}
+
+impl StatementBatchEngine {
+    pub fn new(session_context: Arc<SessionContext>) -> Self {
+        Self { session_context }
+    }
+}
+
+#[async_trait::async_trait]
+impl EngineUnderTest for StatementBatchEngine {
+    fn name(&self) -> &str {
+        "statement-batch"
+    }
+
+    fn kind(&self) -> EngineKind {
+        EngineKind::DataFusion
+    }
+
+    /// Execute every statement of `sql`, returning the result of the last one
+    async fn execute_sql(&self, sql: &str) -> Result<Vec<RecordBatch>> {
+        let statements = DFParser::parse_sql(sql)
+            .map_err(|e| fuzzer_err(&format!("Query planning failed: {}", e)))?;
+
+        let mut batches = Vec::new();
+        for statement in statements {
+            // Each statement is planned with the catalog left by the previous
+            // ones
+            let plan = self
+                .session_context
+                .state()
+                .statement_to_plan(statement)
+                .await
+                .map_err(|e| fuzzer_err(&format!("Query planning failed: {}", e)))?;
+            batches = self
+                .session_context
+                .execute_logical_plan(plan)
+                .await
+                .map_err(|e| fuzzer_err(&format!("Query planning failed: {}", e)))?
+                .collect()
+                .await
+                .map_err(|e| fuzzer_err(&format!("Query execution failed: {}", e)))?;
+        }
+        Ok(batches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::util::count_total_rows;
+
+    #[tokio::test]
+    async fn runs_the_statements_in_order() {
+        let engine = StatementBatchEngine::new(Arc::new(SessionContext::new()));
+        let batches = engine
+            .execute_sql(
+                "CREATE TABLE t1 (c0 BIGINT, c1 VARCHAR) AS VALUES (1, 'a;b');\n\
+                 -- a comment; with a semicolon\n\
+                 INSERT INTO t1 VALUES (2, ';');;\
+                 SELECT c0 FROM t1 WHERE c1 LIKE '%;%';",
+            )
+            .await
+            .unwrap();
+        assert_eq!(count_total_rows(&batches), 2);
+
+        let err = engine
+            .execute_sql("SELECT 1; SELECT * FROM missing")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("missing"));
+    }
+}
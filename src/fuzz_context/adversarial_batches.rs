@@ -0,0 +1,381 @@
+//! Physical plan hooks that feed execution operators adversarial batches.
+//!
+//! Operators mostly see batches of `batch_size` rows in a stable order, so
+//! bugs at batch boundaries (e.g. a group or a join key split across batches,
+//! or an empty batch) rarely show up. With `adversarial_batches`, a physical
+//! optimizer rule wraps every operator of a plan in an
+//! [`AdversarialBatchExec`], which re-slices the batches the operator returns
+//! into random sizes, including empty batches, and with `reorder` also
+//! shuffles them when the operator promises no output order.
+//!
+//! The `TinyBatches` oracle uses the same wrapper to cut every batch into
+//! slices of 1 to 3 rows, see [`AdversarialBatchesRule::tiny_batches`].
+//!
+//! Other rules can be registered in the fuzzed sessions the same way with
+//! `GlobalContext::with_physical_optimizer_rule`.
+
+use std::any::Any;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Arc;
+
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::common::tree_node::{Transformed, TreeNode};
+use datafusion::config::ConfigOptions;
+use datafusion::error::Result;
+use datafusion::execution::{SendableRecordBatchStream, TaskContext};
+use datafusion::physical_optimizer::PhysicalOptimizerRule;
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::physical_plan::{DisplayAs, DisplayFormatType, ExecutionPlan, PlanProperties};
+use futures::StreamExt;
+use rand::seq::SliceRandom;
+use rand::{Rng, rngs::StdRng};
+use serde::{Deserialize, Serialize};
+
+use crate::common::rng::rng_from_seed;
+
+/// Number of batches shuffled together when reordering
+const REORDER_WINDOW: usize = 8;
+
+/// Largest batch of [`AdversarialBatchesRule::tiny_batches`]
+pub const TINY_BATCH_MAX_ROWS: usize = 3;
+
+/// Probability of an empty batch before each slice of a batch
+const EMPTY_BATCH_PROBABILITY: f64 = 0.05;
+
+/// How operators of fuzzed queries see the batches of their inputs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdversarialBatches {
+    /// Batches as the operators produce them
+    #[default]
+    Off,
+    /// Batches re-sliced at random boundaries
+    Split,
+    /// Batches re-sliced, and shuffled where no output order is promised
+    Reorder,
+}
+
+/// Physical optimizer rule wrapping every operator in an
+/// [`AdversarialBatchExec`]. Runs after the built-in rules, so they never see
+/// the wrappers.
+#[derive(Debug)]
+pub struct AdversarialBatchesRule {
+    seed: u64,
+    reorder: bool,
+    max_batch_rows: Option<usize>,
+}
+
+impl AdversarialBatchesRule {
+    /// The rule for `mode`, `None` for [`AdversarialBatches::Off`]
+    pub fn new(mode: AdversarialBatches, seed: u64) -> Option<Self> {
+        match mode {
+            AdversarialBatches::Off => None,
+            AdversarialBatches::Split => Some(Self {
+                seed,
+                reorder: false,
+                max_batch_rows: None,
+            }),
+            AdversarialBatches::Reorder => Some(Self {
+                seed,
+                reorder: true,
+                max_batch_rows: None,
+            }),
+        }
+    }
+
+    /// Rule cutting every batch into slices of 1 to [`TINY_BATCH_MAX_ROWS`]
+    /// rows in order, so operators carrying state across batches (partial
+    /// groups, join probes, window frames) cross a batch boundary at almost
+    /// every row
+    pub fn tiny_batches(seed: u64) -> Self {
+        Self {
+            seed,
+            reorder: false,
+            max_batch_rows: Some(TINY_BATCH_MAX_ROWS),
+        }
+    }
+}
+
+impl PhysicalOptimizerRule for AdversarialBatchesRule {
+    fn optimize(
+        &self,
+        plan: Arc<dyn ExecutionPlan>,
+        _config: &ConfigOptions,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let mut operator_index = 0u64;
+        plan.transform_up(|operator| {
+            if operator.as_any().is::<AdversarialBatchExec>() {
+                return Ok(Transformed::no(operator));
+            }
+            operator_index += 1;
+            // Reordering the output of an operator promising an order would
+            // change the result
+            let reorder = self.reorder && operator.output_ordering().is_none();
+            let seed = self.seed.wrapping_add(operator_index);
+            Ok(Transformed::yes(Arc::new(
+                AdversarialBatchExec::new(operator, seed, reorder)
+                    .with_max_batch_rows(self.max_batch_rows),
+            )))
+        })
+        .map(|transformed| transformed.data)
+    }
+
+    fn name(&self) -> &str {
+        "adversarial_batches"
+    }
+
+    fn schema_check(&self) -> bool {
+        true
+    }
+}
+
+/// Passes the batches of its input on re-sliced at random boundaries, and
+/// with `reorder` shuffled within windows of `REORDER_WINDOW` batches
+#[derive(Debug)]
+pub struct AdversarialBatchExec {
+    input: Arc<dyn ExecutionPlan>,
+    seed: u64,
+    reorder: bool,
+    /// Upper bound of the slice sizes, the size of the batch if not set
+    max_batch_rows: Option<usize>,
+}
+
+impl AdversarialBatchExec {
+    pub fn new(input: Arc<dyn ExecutionPlan>, seed: u64, reorder: bool) -> Self {
+        Self {
+            input,
+            seed,
+            reorder,
+            max_batch_rows: None,
+        }
+    }
+
+    pub fn with_max_batch_rows(mut self, max_batch_rows: Option<usize>) -> Self {
+        self.max_batch_rows = max_batch_rows;
+        self
+    }
+}
+
+impl DisplayAs for AdversarialBatchExec {
+    fn fmt_as(&self, _t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "AdversarialBatchExec: reorder={}", self.reorder)?;
+        if let Some(max_batch_rows) = self.max_batch_rows {
+            write!(f, ", max_batch_rows={}", max_batch_rows)?;
+        }
+        Ok(())
+    }
+}
+
+impl ExecutionPlan for AdversarialBatchExec {
+    fn name(&self) -> &str {
+        "AdversarialBatchExec"
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn properties(&self) -> &PlanProperties {
+        self.input.properties()
+    }
+
+    fn children(&self) -> Vec<&Arc<dyn ExecutionPlan>> {
+        vec![&self.input]
+    }
+
+    fn maintains_input_order(&self) -> Vec<bool> {
+        vec![!self.reorder]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        mut children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        Ok(Arc::new(
+            Self::new(children.swap_remove(0), self.seed, self.reorder)
+                .with_max_batch_rows(self.max_batch_rows),
+        ))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> Result<SendableRecordBatchStream> {
+        let input = self.input.execute(partition, context)?;
+        let schema = input.schema();
+        let state = AdversarialStream {
+            input,
+            rng: rng_from_seed(self.seed.wrapping_add(partition as u64)),
+            window: if self.reorder { REORDER_WINDOW } else { 1 },
+            max_batch_rows: self.max_batch_rows.unwrap_or(usize::MAX),
+            pending: VecDeque::new(),
+            input_done: false,
+        };
+
+        let stream = futures::stream::unfold(state, |mut state| async move {
+            let next = state.next_batch().await?;
+            Some((next, state))
+        });
+        Ok(Box::pin(RecordBatchStreamAdapter::new(schema, stream)))
+    }
+}
+
+struct AdversarialStream {
+    input: SendableRecordBatchStream,
+    rng: StdRng,
+    /// Number of input batches shuffled together, 1 to keep their order
+    window: usize,
+    max_batch_rows: usize,
+    /// Slices of the current window not returned yet
+    pending: VecDeque<RecordBatch>,
+    input_done: bool,
+}
+
+impl AdversarialStream {
+    async fn next_batch(&mut self) -> Option<Result<RecordBatch>> {
+        loop {
+            if let Some(batch) = self.pending.pop_front() {
+                return Some(Ok(batch));
+            }
+            if self.input_done {
+                return None;
+            }
+
+            let mut window = Vec::with_capacity(self.window);
+            while window.len() < self.window {
+                match self.input.next().await {
+                    Some(Ok(batch)) => window.push(batch),
+                    Some(Err(e)) => {
+                        self.input_done = true;
+                        return Some(Err(e));
+                    }
+                    None => {
+                        self.input_done = true;
+                        break;
+                    }
+                }
+            }
+
+            window.shuffle(&mut self.rng);
+            for batch in window {
+                self.pending
+                    .extend(split_batch(&batch, self.max_batch_rows, &mut self.rng));
+            }
+        }
+    }
+}
+
+/// `batch` as consecutive slices of 1 to `max_rows` rows, with an occasional
+/// empty slice in between
+fn split_batch(batch: &RecordBatch, max_rows: usize, rng: &mut StdRng) -> Vec<RecordBatch> {
+    let mut slices = Vec::new();
+    let mut offset = 0;
+    while offset < batch.num_rows() {
+        if rng.random_bool(EMPTY_BATCH_PROBABILITY) {
+            slices.push(batch.slice(offset, 0));
+        }
+        let len = rng.random_range(1..=max_rows.min(batch.num_rows() - offset));
+        slices.push(batch.slice(offset, len));
+        offset += len;
+    }
+    if slices.is_empty() {
+        slices.push(batch.clone());
+    }
+    slices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::arrow::array::{ArrayRef, Int64Array};
+    use datafusion::arrow::util::pretty::pretty_format_batches;
+    use datafusion::execution::SessionStateBuilder;
+    use datafusion::prelude::SessionContext;
+
+    #[test]
+    fn splits_batches_into_consecutive_slices() {
+        let values: Vec<i64> = (0..100).collect();
+        let batch = RecordBatch::try_from_iter([(
+            "c0",
+            Arc::new(Int64Array::from(values.clone())) as ArrayRef,
+        )])
+        .unwrap();
+
+        let mut rng = rng_from_seed(7);
+        let slices = split_batch(&batch, usize::MAX, &mut rng);
+        assert!(slices.len() > 1);
+        let concatenated: Vec<i64> = slices
+            .iter()
+            .flat_map(|slice| {
+                let column = slice.column(0).as_any().downcast_ref::<Int64Array>();
+                column.unwrap().values().to_vec()
+            })
+            .collect();
+        assert_eq!(concatenated, values);
+    }
+
+    #[test]
+    fn tiny_batches_have_at_most_three_rows() {
+        let batch = RecordBatch::try_from_iter([(
+            "c0",
+            Arc::new(Int64Array::from_iter_values(0..50)) as ArrayRef,
+        )])
+        .unwrap();
+
+        let mut rng = rng_from_seed(3);
+        let slices = split_batch(&batch, TINY_BATCH_MAX_ROWS, &mut rng);
+        assert!(
+            slices
+                .iter()
+                .all(|slice| slice.num_rows() <= TINY_BATCH_MAX_ROWS)
+        );
+        assert_eq!(
+            slices.iter().map(|slice| slice.num_rows()).sum::<usize>(),
+            50
+        );
+    }
+
+    #[tokio::test]
+    async fn wrapped_plans_return_the_same_rows() {
+        let rule = AdversarialBatchesRule::new(AdversarialBatches::Reorder, 1).unwrap();
+        let plain = SessionContext::new();
+        let state = SessionStateBuilder::new_from_existing(plain.state())
+            .with_physical_optimizer_rule(Arc::new(rule))
+            .build();
+        let adversarial = SessionContext::new_with_state(state);
+
+        let sql = "SELECT column1 % 3 AS k, count(*) AS n, sum(column1) AS s \
+                   FROM (SELECT * FROM generate_series(1, 1000) AS t(column1)) \
+                   GROUP BY column1 % 3 ORDER BY k";
+        let mut results = Vec::new();
+        for session_context in [&plain, &adversarial] {
+            let batches = session_context
+                .sql(sql)
+                .await
+                .unwrap()
+                .collect()
+                .await
+                .unwrap();
+            results.push(pretty_format_batches(&batches).unwrap().to_string());
+        }
+        assert_eq!(results[0], results[1]);
+
+        let explained = adversarial
+            .sql(&format!("EXPLAIN {}", sql))
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        let explained = pretty_format_batches(&explained).unwrap().to_string();
+        assert!(explained.contains("AdversarialBatchExec: reorder=false"));
+        assert!(explained.contains("AdversarialBatchExec: reorder=true"));
+    }
+
+    #[test]
+    fn off_registers_no_rule() {
+        assert!(AdversarialBatchesRule::new(AdversarialBatches::Off, 1).is_none());
+    }
+}
@@ -7,11 +7,11 @@ use super::GlobalContext;
 
 /// Display the contents of all registered tables, showing up to 3 rows each
 pub async fn display_all_tables(ctx: Arc<GlobalContext>) -> Result<()> {
-    let tables = ctx.runtime_context.registered_tables.read().unwrap();
+    let snapshot = ctx.runtime_context.snapshot();
 
-    for (table_name, _) in tables.iter() {
-        let sql = format!("SELECT * FROM {} LIMIT 3", table_name);
-        let df_ctx = ctx.runtime_context.get_session_context();
+    for (table_name, table) in snapshot.tables.iter() {
+        let sql = format!("SELECT * FROM {} LIMIT 3", table.sql_name());
+        let df_ctx = &snapshot.session_context;
 
         match df_ctx.sql(&sql).await {
             Ok(df) => match df.collect().await {
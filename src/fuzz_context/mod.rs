@@ -1,83 +1,440 @@
+pub mod adversarial_batches;
 pub mod ctx_observability;
+pub mod profile;
 mod runner_config;
+pub mod swarm;
 
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::sync::{
     Arc, Mutex, RwLock,
-    atomic::{AtomicU32, Ordering},
+    atomic::{AtomicU32, AtomicU64, Ordering},
 };
 
-use datafusion::{common::HashMap, prelude::SessionContext};
+use datafusion::execution::SessionStateBuilder;
+use datafusion::execution::disk_manager::{DiskManagerBuilder, DiskManagerMode};
+use datafusion::execution::memory_pool::MemoryLimit;
+use datafusion::execution::runtime_env::RuntimeEnvBuilder;
+use datafusion::physical_optimizer::PhysicalOptimizerRule;
+use datafusion::prelude::{SessionConfig, SessionContext};
 
-use crate::common::LogicalTable;
+use crate::cli::error_whitelist::ErrorWhitelist;
 use crate::common::value_generator::ValueGenerationConfig;
+use crate::common::{FuzzerDataType, LogicalTable, Result, get_available_data_types};
+use crate::engine::EngineUnderTest;
 use crate::fuzz_runner::FuzzerStats;
+use crate::fuzz_runner::findings::FindingsRecorder;
+use crate::fuzz_runner::known_issues::KnownIssues;
+use crate::fuzz_runner::seed_pool::SeedScheduler;
+use crate::workspace::Workspace;
+use adversarial_batches::AdversarialBatchesRule;
+use swarm::{SwarmFeature, SwarmFeatures};
 
 pub use runner_config::RunnerConfig;
 
 /// Create a default DataFusion SessionContext with standard configuration
-/// This ensures consistency between initial creation and reset operations
+/// This ensures consistency between initial creation and reset operations.
+/// `information_schema` is enabled for the Catalog oracle.
 fn default_df_session_context() -> Arc<SessionContext> {
-    Arc::new(SessionContext::new())
+    let config = SessionConfig::new().with_information_schema(true);
+    Arc::new(SessionContext::new_with_config(config))
+}
+
+/// Physical optimizer rules registered in fuzzed sessions
+pub type PhysicalOptimizerRules = Vec<Arc<dyn PhysicalOptimizerRule + Send + Sync>>;
+
+/// The default SessionContext with the configured memory limit and the extra
+/// physical optimizer rules applied
+fn configured_df_session_context(
+    config: &RunnerConfig,
+    physical_optimizer_rules: &PhysicalOptimizerRules,
+) -> Arc<SessionContext> {
+    let mut session_context = default_df_session_context();
+    if !physical_optimizer_rules.is_empty() {
+        let state = physical_optimizer_rules.iter().fold(
+            SessionStateBuilder::new_from_existing(session_context.state()),
+            |builder, rule| builder.with_physical_optimizer_rule(Arc::clone(rule)),
+        );
+        session_context = Arc::new(SessionContext::new_with_state(state.build()));
+    }
+    let Some(limit_bytes) = config.max_query_memory_bytes() else {
+        return session_context;
+    };
+
+    memory_limited_session_context(&session_context, limit_bytes).unwrap_or_else(|e| {
+        eprintln!("Warning: {}, running without a memory limit", e);
+        session_context
+    })
+}
+
+/// A SessionContext sharing the catalog (and so the registered tables) of
+/// `session_context`, whose queries fail with a resources exhausted error once
+/// their memory reservations exceed `limit_bytes`
+pub fn memory_limited_session_context(
+    session_context: &SessionContext,
+    limit_bytes: usize,
+) -> Result<Arc<SessionContext>> {
+    let runtime_env = RuntimeEnvBuilder::new()
+        .with_memory_limit(limit_bytes, 1.0)
+        .build_arc()?;
+    let state = SessionStateBuilder::new_from_existing(session_context.state())
+        .with_runtime_env(runtime_env)
+        .build();
+
+    Ok(Arc::new(SessionContext::new_with_state(state)))
+}
+
+/// A SessionContext sharing the catalog of `session_context` whose operators
+/// only ever see batches of 1 to 3 rows, see
+/// [`AdversarialBatchesRule::tiny_batches`]
+pub fn tiny_batch_session_context(
+    session_context: &SessionContext,
+    seed: u64,
+) -> Arc<SessionContext> {
+    let state = SessionStateBuilder::new_from_existing(session_context.state())
+        .with_physical_optimizer_rule(Arc::new(AdversarialBatchesRule::tiny_batches(seed)))
+        .build();
+
+    Arc::new(SessionContext::new_with_state(state))
+}
+
+/// A SessionContext sharing the catalog of `session_context` whose SQL parser
+/// accepts statements nested up to `recursion_limit` levels deep, instead of
+/// `datafusion.sql_parser.recursion_limit`
+pub fn deep_recursion_session_context(
+    session_context: &SessionContext,
+    recursion_limit: usize,
+) -> Arc<SessionContext> {
+    let mut state = session_context.state();
+    state.config_mut().options_mut().sql_parser.recursion_limit = recursion_limit;
+
+    Arc::new(SessionContext::new_with_state(state))
+}
+
+/// Like [`memory_limited_session_context`], but tuned so that sorts, joins and
+/// aggregations spill to disk instead of failing: sort reservations are tiny
+/// and batches small, so memory fills up after a few batches even on small
+/// generated tables. Spill files go to `spill_dir`, or the OS temp directory.
+pub fn spilling_session_context(
+    session_context: &SessionContext,
+    limit_bytes: usize,
+    spill_dir: Option<&Path>,
+) -> Result<Arc<SessionContext>> {
+    let disk_manager_mode = match spill_dir {
+        Some(dir) => DiskManagerMode::Directories(vec![dir.to_path_buf()]),
+        None => DiskManagerMode::OsTmpDirectory,
+    };
+    let runtime_env = RuntimeEnvBuilder::new()
+        .with_memory_limit(limit_bytes, 1.0)
+        .with_disk_manager_builder(DiskManagerBuilder::default().with_mode(disk_manager_mode))
+        .build_arc()?;
+
+    let state = session_context.state();
+    let mut config = state.config().clone().with_batch_size(16);
+    config.options_mut().execution.sort_spill_reservation_bytes = 1024;
+    config.options_mut().execution.sort_in_place_threshold_bytes = 1024;
+
+    let state = SessionStateBuilder::new_from_existing(state)
+        .with_config(config)
+        .with_runtime_env(runtime_env)
+        .build();
+
+    Ok(Arc::new(SessionContext::new_with_state(state)))
+}
+
+/// Whether queries in `session_context` run with a bounded memory pool
+pub fn has_memory_limit(session_context: &SessionContext) -> bool {
+    matches!(
+        session_context.runtime_env().memory_pool.memory_limit(),
+        MemoryLimit::Finite(_)
+    )
 }
 
 pub struct GlobalContext {
     pub runner_config: RunnerConfig,
     pub runtime_context: RuntimeContext,
     pub fuzzer_stats: Arc<Mutex<FuzzerStats>>,
+    pub error_whitelist: ErrorWhitelist,
+    pub findings: FindingsRecorder,
+    /// Upstream issues of known findings, see `known_issues`
+    pub known_issues: KnownIssues,
+    /// Engines besides the embedded session the generated tables are mirrored
+    /// to, see `flight_sql_endpoint` and `reference_engines`
+    pub engines: Vec<Arc<dyn EngineUnderTest>>,
+    /// Temporary directory of the run for generated files, `None` outside of
+    /// fuzzing runs
+    pub workspace: Option<Arc<Workspace>>,
+    /// Held shared by work running next to the queries (pipelined query
+    /// generation), and exclusively to retry a timed out query with nothing
+    /// else running, see `retry_timeouts`
+    pub exclusive_execution: tokio::sync::RwLock<()>,
+    /// Physical optimizer rules added to the built-in ones of every fuzzed
+    /// session, e.g. to wrap operators (see `adversarial_batches`)
+    pub physical_optimizer_rules: PhysicalOptimizerRules,
+    /// Boosts of the generator's optional clauses, `coverage_boosts` steered
+    /// towards `coverage_targets` after every round
+    coverage_boosts: RwLock<BTreeMap<SwarmFeature, f64>>,
+    /// Feature families of the current round with `swarm_testing`, `None`
+    /// enables all of them
+    swarm_features: RwLock<Option<SwarmFeatures>>,
+    /// Picks the base seed of every round with `seed_pool`
+    pub seed_scheduler: Option<Mutex<SeedScheduler>>,
 }
 
 impl GlobalContext {
     pub fn new(
         runner_config: RunnerConfig,
-        runtime_context: RuntimeContext,
+        mut runtime_context: RuntimeContext,
         fuzzer_stats: Arc<Mutex<FuzzerStats>>,
     ) -> Self {
+        // The whitelist config was already validated while loading the config,
+        // fall back to the built-in patterns if it became unreadable since
+        let error_whitelist =
+            ErrorWhitelist::from_config(&runner_config.whitelist).unwrap_or_else(|e| {
+                eprintln!("Warning: {}, using the built-in error whitelist", e);
+                ErrorWhitelist::builtin()
+            });
+
+        let findings = FindingsRecorder::new(&runner_config);
+        // Validated while loading the config as well
+        let known_issues = match &runner_config.known_issues {
+            Some(path) => KnownIssues::load(path).unwrap_or_else(|e| {
+                eprintln!("Warning: {}, all findings are reported as new", e);
+                KnownIssues::default()
+            }),
+            None => KnownIssues::default(),
+        };
+
+        runtime_context.value_generation_config.extended_timezones =
+            runner_config.extended_timezones;
+
+        let mut physical_optimizer_rules: PhysicalOptimizerRules = Vec::new();
+        if let Some(rule) =
+            AdversarialBatchesRule::new(runner_config.adversarial_batches, runner_config.seed)
+        {
+            physical_optimizer_rules.push(Arc::new(rule));
+        }
+
+        if runner_config.max_query_memory_mb.is_some() || !physical_optimizer_rules.is_empty() {
+            runtime_context.reset(configured_df_session_context(
+                &runner_config,
+                &physical_optimizer_rules,
+            ));
+        }
+
+        let coverage_boosts = RwLock::new(runner_config.coverage_boosts.clone());
+
         Self {
             runner_config,
             runtime_context,
             fuzzer_stats,
+            error_whitelist,
+            findings,
+            known_issues,
+            engines: Vec::new(),
+            workspace: None,
+            exclusive_execution: tokio::sync::RwLock::new(()),
+            physical_optimizer_rules,
+            coverage_boosts,
+            swarm_features: RwLock::new(None),
+            seed_scheduler: None,
         }
     }
 
+    /// Mirror generated tables to other engines, and make them available to oracles
+    pub fn with_engines(mut self, engines: Vec<Arc<dyn EngineUnderTest>>) -> Self {
+        self.engines = engines;
+        self
+    }
+
+    /// Run `rule` after the built-in physical optimizer rules in every fuzzed
+    /// session. Resets the session, so add rules before generating tables.
+    pub fn with_physical_optimizer_rule(
+        mut self,
+        rule: Arc<dyn PhysicalOptimizerRule + Send + Sync>,
+    ) -> Self {
+        self.physical_optimizer_rules.push(rule);
+        self.reset_datafusion_context();
+        self
+    }
+
+    /// Put generated files without a configured directory into `workspace`
+    pub fn with_workspace(mut self, workspace: Arc<Workspace>) -> Self {
+        self.workspace = Some(workspace);
+        self
+    }
+
+    /// Run the rounds with base seeds picked by `scheduler`
+    pub fn with_seed_scheduler(mut self, scheduler: SeedScheduler) -> Self {
+        self.seed_scheduler = Some(Mutex::new(scheduler));
+        self
+    }
+
+    /// Generate tables and queries with the feature families of `features`
+    /// only, or with all of them for `None`
+    pub fn set_swarm_features(&self, features: Option<SwarmFeatures>) {
+        *self.swarm_features.write().unwrap() = features;
+    }
+
+    /// Feature families of the current round, `None` without swarm testing
+    pub fn swarm_features(&self) -> Option<SwarmFeatures> {
+        self.swarm_features.read().unwrap().clone()
+    }
+
+    /// Whether the current round generates `feature`, always true without
+    /// swarm testing
+    pub fn swarm_allows(&self, feature: SwarmFeature) -> bool {
+        self.swarm_features
+            .read()
+            .unwrap()
+            .as_ref()
+            .is_none_or(|features| features.allows(feature))
+    }
+
+    /// Column and expression types the current round generates: the available
+    /// types in `data_types` (all if empty), and of those the ones of the
+    /// enabled swarm testing families
+    pub fn generated_data_types(&self) -> Vec<FuzzerDataType> {
+        let configured: Vec<FuzzerDataType> = get_available_data_types()
+            .iter()
+            .filter(|data_type| {
+                let data_types = &self.runner_config.data_types;
+                data_types.is_empty() || data_types.contains(data_type)
+            })
+            .cloned()
+            .collect();
+
+        let swarm_features = self.swarm_features.read().unwrap();
+        let Some(features) = swarm_features.as_ref() else {
+            return configured;
+        };
+        let swarm: Vec<FuzzerDataType> = configured
+            .iter()
+            .filter(|data_type| features.allows_type(data_type))
+            .cloned()
+            .collect();
+        // The round may only enable type families `data_types` leaves out
+        if swarm.is_empty() { configured } else { swarm }
+    }
+
+    /// Configuration recorded in findings: the run's configuration with the
+    /// clause boosts currently in use, so a replay generates the same queries
+    pub fn finding_config(&self) -> RunnerConfig {
+        let mut config = self.runner_config.clone();
+        config.coverage_boosts = self.coverage_boosts.read().unwrap().clone();
+        config
+    }
+
+    /// Generate the optional clauses more often by their boost in `boosts`
+    pub fn set_coverage_boosts(&self, boosts: BTreeMap<SwarmFeature, f64>) {
+        *self.coverage_boosts.write().unwrap() = boosts;
+    }
+
+    /// Probability of generating an optional clause of `feature` an oracle
+    /// generates with `probability`: raised towards 1 by the clause's boost,
+    /// see [`crate::fuzz_runner::coverage`]
+    pub fn steered_probability(&self, feature: SwarmFeature, probability: f64) -> f64 {
+        let boost = self
+            .coverage_boosts
+            .read()
+            .unwrap()
+            .get(&feature)
+            .copied()
+            .unwrap_or_default();
+        probability + (1.0 - probability) * boost
+    }
+
+    /// Base directory for the files of file-backed tables: `dataset_dir`, the
+    /// workspace, or `None` for the OS temp directory
+    pub fn dataset_dir(&self) -> Option<PathBuf> {
+        self.runner_config
+            .dataset_dir
+            .clone()
+            .or_else(|| self.workspace.as_ref().map(|w| w.datasets_dir()))
+    }
+
+    /// Directory for spill files: `spill_dir`, the workspace, or `None` for
+    /// the OS temp directory
+    pub fn spill_dir(&self) -> Option<PathBuf> {
+        self.runner_config
+            .spill_dir
+            .clone()
+            .or_else(|| self.workspace.as_ref().map(|w| w.spill_dir()))
+    }
+
+    /// Directory for spooled query results: the workspace, or `None` for the
+    /// OS temp directory
+    pub fn result_spool_dir(&self) -> Option<PathBuf> {
+        self.workspace.as_ref().map(|w| w.spooled_results_dir())
+    }
+
+    /// Directory for `datafusion-cli` reproducers: the log directory, or the
+    /// workspace
+    pub fn reproducers_dir(&self) -> Option<PathBuf> {
+        self.runner_config
+            .log_path
+            .clone()
+            .or_else(|| self.workspace.as_ref().map(|w| w.reproducers_dir()))
+    }
+
     pub fn default() -> Self {
         let default_config = RunnerConfig::default();
         let fuzzer_stats = Arc::new(Mutex::new(FuzzerStats::new(default_config.rounds)));
 
+        let findings = FindingsRecorder::new(&default_config);
+
         Self {
             runner_config: default_config,
             runtime_context: RuntimeContext::default(),
             fuzzer_stats,
+            error_whitelist: ErrorWhitelist::builtin(),
+            findings,
+            known_issues: KnownIssues::default(),
+            engines: Vec::new(),
+            workspace: None,
+            exclusive_execution: tokio::sync::RwLock::new(()),
+            physical_optimizer_rules: Vec::new(),
+            coverage_boosts: RwLock::new(BTreeMap::new()),
+            swarm_features: RwLock::new(None),
+            seed_scheduler: None,
         }
     }
 
     /// Reset the DataFusion context to drop all registered tables
     /// This creates a fresh SessionContext and clears all table registrations
     pub fn reset_datafusion_context(&self) {
-        // Create a new SessionContext to completely reset the DataFusion state
-        let new_session_context = default_df_session_context();
-
-        // Replace the existing SessionContext with a new one
-        {
-            let mut df_ctx = self.runtime_context.df_ctx.write().unwrap();
-            *df_ctx = new_session_context;
-        }
+        // Create a new SessionContext to completely reset the DataFusion state,
+        // swapped in together with the empty table registry
+        self.runtime_context.reset(configured_df_session_context(
+            &self.runner_config,
+            &self.physical_optimizer_rules,
+        ));
+    }
+}
 
-        // Clear the fuzzer's table registry
-        {
-            let mut tables = self.runtime_context.registered_tables.write().unwrap();
-            tables.clear();
-        }
+/// Tables registered in the fuzzer, by name.
+///
+/// Ordered, so iterating it is deterministic and a seed fully determines the
+/// generated SQL.
+pub type TableMap = BTreeMap<String, Arc<LogicalTable>>;
 
-        // Reset the table counter
-        self.runtime_context
-            .current_table_idx
-            .store(0, Ordering::Relaxed);
-    }
+/// An immutable view of the DataFusion session and the tables registered in it.
+///
+/// Readers take a snapshot and keep using it without holding any lock, so code
+/// running across `.await` points never blocks writers, and a snapshot never
+/// mixes the session of one round with the tables of another.
+#[derive(Clone)]
+pub struct RuntimeSnapshot {
+    pub session_context: Arc<SessionContext>,
+    pub tables: Arc<TableMap>,
 }
 
 pub struct RuntimeContext {
-    pub df_ctx: Arc<RwLock<Arc<SessionContext>>>,
-    pub registered_tables: Arc<RwLock<HashMap<String, Arc<LogicalTable>>>>,
+    /// Current snapshot, replaced as a whole on every change (copy-on-write).
+    /// The lock is only held to clone or swap the `Arc`, never across `.await`.
+    state: RwLock<Arc<RuntimeSnapshot>>,
+    /// Bumped on every change of the registered tables
+    tables_version: AtomicU64,
     current_table_idx: AtomicU32,
     // Cached value generation config for performance (nullable by default)
     pub value_generation_config: ValueGenerationConfig,
@@ -86,20 +443,73 @@ pub struct RuntimeContext {
 impl RuntimeContext {
     pub fn new(df_ctx: Arc<SessionContext>) -> Self {
         Self {
-            df_ctx: Arc::new(RwLock::new(df_ctx)),
-            registered_tables: Arc::new(RwLock::new(HashMap::new())),
+            state: RwLock::new(Arc::new(RuntimeSnapshot {
+                session_context: df_ctx,
+                tables: Arc::new(TableMap::new()),
+            })),
+            tables_version: AtomicU64::new(0),
             current_table_idx: AtomicU32::new(0),
             value_generation_config: ValueGenerationConfig::default(), // Nullable by default
         }
     }
 
     pub fn default() -> Self {
-        Self {
-            df_ctx: Arc::new(RwLock::new(default_df_session_context())),
-            registered_tables: Arc::new(RwLock::new(HashMap::new())),
-            current_table_idx: AtomicU32::new(0),
-            value_generation_config: ValueGenerationConfig::default(), // Nullable by default
-        }
+        Self::new(default_df_session_context())
+    }
+
+    /// The current session and tables
+    pub fn snapshot(&self) -> Arc<RuntimeSnapshot> {
+        Arc::clone(&self.state.read().unwrap())
+    }
+
+    /// The currently registered tables
+    pub fn tables(&self) -> Arc<TableMap> {
+        Arc::clone(&self.snapshot().tables)
+    }
+
+    /// Version of the registered tables, for caches derived from them to
+    /// detect changes without taking the lock
+    pub fn tables_version(&self) -> u64 {
+        self.tables_version.load(Ordering::Acquire)
+    }
+
+    /// Look up a registered table by name
+    pub fn get_table(&self, name: &str) -> Option<Arc<LogicalTable>> {
+        self.snapshot().tables.get(name).cloned()
+    }
+
+    /// Register a table in the fuzzer; existing snapshots are not affected
+    pub fn register_table(&self, table: Arc<LogicalTable>) {
+        let mut state = self.state.write().unwrap();
+        let mut tables = TableMap::clone(&state.tables);
+        tables.insert(table.name.clone(), table);
+        *state = Arc::new(RuntimeSnapshot {
+            session_context: Arc::clone(&state.session_context),
+            tables: Arc::new(tables),
+        });
+        self.tables_version.fetch_add(1, Ordering::Release);
+    }
+
+    /// Remove a table from the fuzzer; existing snapshots are not affected
+    pub fn deregister_table(&self, name: &str) {
+        let mut state = self.state.write().unwrap();
+        let mut tables = TableMap::clone(&state.tables);
+        tables.remove(name);
+        *state = Arc::new(RuntimeSnapshot {
+            session_context: Arc::clone(&state.session_context),
+            tables: Arc::new(tables),
+        });
+        self.tables_version.fetch_add(1, Ordering::Release);
+    }
+
+    /// Switch to a new session without tables and restart table naming
+    pub fn reset(&self, session_context: Arc<SessionContext>) {
+        *self.state.write().unwrap() = Arc::new(RuntimeSnapshot {
+            session_context,
+            tables: Arc::new(TableMap::new()),
+        });
+        self.tables_version.fetch_add(1, Ordering::Release);
+        self.reset_table_counter();
     }
 
     pub fn next_table_name(&self) -> String {
@@ -116,7 +526,37 @@ impl RuntimeContext {
 
     /// Get a clone of the current DataFusion SessionContext
     pub fn get_session_context(&self) -> Arc<SessionContext> {
-        let df_ctx = self.df_ctx.read().unwrap();
-        Arc::clone(&*df_ctx)
+        Arc::clone(&self.snapshot().session_context)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that table registration doesn't change snapshots taken before it
+    #[test]
+    fn test_runtime_snapshots_are_isolated() {
+        let runtime_context = RuntimeContext::default();
+        runtime_context.register_table(Arc::new(LogicalTable::new("t0".to_string())));
+
+        let before = runtime_context.snapshot();
+        runtime_context.register_table(Arc::new(LogicalTable::new("t1".to_string())));
+
+        assert_eq!(before.tables.len(), 1);
+        assert_eq!(runtime_context.tables().len(), 2);
+        assert!(runtime_context.get_table("t1").is_some());
+        assert_eq!(
+            runtime_context.tables().keys().collect::<Vec<_>>(),
+            vec!["t0", "t1"]
+        );
+
+        runtime_context.reset(Arc::new(SessionContext::new()));
+        assert!(runtime_context.tables().is_empty());
+        assert!(!Arc::ptr_eq(
+            &before.session_context,
+            &runtime_context.get_session_context()
+        ));
+        assert_eq!(runtime_context.next_table_name(), "t0");
     }
 }
@@ -0,0 +1,159 @@
+//! Built-in profiles for focused campaigns.
+//!
+//! A profile (`--profile joins`) presets the oracles, their weights, the
+//! generated types and the generation settings that matter for one area of
+//! DataFusion. It is applied on top of the config file, and the other CLI
+//! options override it.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use strum::EnumIter;
+
+use super::RunnerConfig;
+use crate::common::FuzzerDataType;
+use crate::datasource_generator::file_dataset::DatasetFormat;
+use crate::fuzz_runner::query_shape::QueryFeature;
+use crate::oracle::ConfiguredOracle;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, EnumIter)]
+#[serde(rename_all = "snake_case")]
+pub enum Profile {
+    /// Joins of up to 4 tables with selective predicates, checked against
+    /// joins computed in Rust and by query partitioning
+    Joins,
+    /// GROUP BY and aggregates, checked against aggregates computed in Rust,
+    /// HAVING partitioning and NaN/-0.0 group keys
+    Aggregates,
+    /// Date, time, timestamp and interval columns with time zones that have
+    /// DST transitions
+    Temporal,
+    /// Tables in Parquet files with small row groups and pages, so row group
+    /// and page pruning, statistics and filter pushdown actually skip data
+    Parquet,
+}
+
+impl Profile {
+    /// Preset the settings of the profile in `config`
+    pub fn apply(self, config: &mut RunnerConfig) {
+        match self {
+            Self::Joins => {
+                config.oracles = vec![
+                    ConfiguredOracle::Join,
+                    ConfiguredOracle::TlpWhere,
+                    ConfiguredOracle::NoCrash,
+                    ConfiguredOracle::NestedQueries,
+                ];
+                config.oracle_weights = vec![3.0, 2.0, 2.0, 1.0];
+                config.max_table_count = 4;
+                config.join_row_budget = Some(1_000_000);
+                config.predicate_literal_bias = 0.5;
+                config.coverage_targets = BTreeMap::from([(QueryFeature::Joins, 0.5)]);
+            }
+            Self::Aggregates => {
+                config.oracles = vec![
+                    ConfiguredOracle::Aggregate,
+                    ConfiguredOracle::TlpHaving,
+                    ConfiguredOracle::FloatGroupBy,
+                    ConfiguredOracle::NoCrash,
+                ];
+                config.oracle_weights = vec![3.0, 3.0, 1.0, 1.0];
+                config.max_group_by_count = 5;
+                config.predicate_literal_bias = 0.3;
+                config.coverage_targets = BTreeMap::from([(QueryFeature::Aggregates, 0.6)]);
+            }
+            Self::Temporal => {
+                config.oracles = vec![
+                    ConfiguredOracle::Timezone,
+                    ConfiguredOracle::Interval,
+                    ConfiguredOracle::TlpWhere,
+                    ConfiguredOracle::NoCrash,
+                ];
+                config.oracle_weights = vec![2.0, 2.0, 1.0, 1.0];
+                // Integers and strings are arguments of the date and time
+                // functions, e.g. `to_timestamp`
+                config.data_types = vec![
+                    FuzzerDataType::Date32,
+                    FuzzerDataType::Time64Nanosecond,
+                    FuzzerDataType::Timestamp,
+                    FuzzerDataType::IntervalMonthDayNano,
+                    FuzzerDataType::Int64,
+                    FuzzerDataType::String,
+                ];
+                config.extended_timezones = true;
+            }
+            Self::Parquet => {
+                config.oracles = vec![
+                    ConfiguredOracle::ParquetPruning,
+                    ConfiguredOracle::FilterPushdown,
+                    ConfiguredOracle::Statistics,
+                    ConfiguredOracle::FileSchemaEvolution,
+                    ConfiguredOracle::NoCrash,
+                ];
+                config.oracle_weights = vec![3.0, 3.0, 2.0, 1.0, 1.0];
+                config.dataset_format = DatasetFormat::Parquet;
+                config.parquet_pruning_stress = true;
+                config.predicate_literal_bias = 0.5;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{Cli, Command};
+    use crate::common::init_available_data_types;
+    use crate::fuzz_context::{GlobalContext, RuntimeContext};
+    use clap::Parser;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn profiles_are_valid_configs() {
+        for profile in Profile::iter() {
+            let mut config = RunnerConfig::default();
+            profile.apply(&mut config);
+            let config = config.validate().unwrap();
+            assert_eq!(config.oracle_weights.len(), config.oracles.len());
+        }
+    }
+
+    #[test]
+    fn temporal_profile_generates_temporal_types() {
+        init_available_data_types();
+        let mut config = RunnerConfig::default();
+        Profile::Temporal.apply(&mut config);
+        let ctx = GlobalContext::new(
+            config,
+            RuntimeContext::default(),
+            crate::fuzz_runner::create_fuzzer_stats(1),
+        );
+
+        let data_types = ctx.generated_data_types();
+        assert_eq!(data_types.len(), 6);
+        assert!(data_types.contains(&FuzzerDataType::Timestamp));
+        assert!(!data_types.contains(&FuzzerDataType::Boolean));
+    }
+
+    #[test]
+    fn cli_options_override_the_profile() {
+        let cli = Cli::parse_from([
+            "datafusion-fuzzer",
+            "--profile",
+            "joins",
+            "--max-table-count",
+            "2",
+            "--oracles",
+            "NoCrash",
+        ]);
+        let Command::Run(args) = cli.into_command() else {
+            panic!("expected the run command");
+        };
+        let config = RunnerConfig::from_cli(&args).unwrap();
+        assert_eq!(config.max_table_count, 2);
+        assert_eq!(config.oracles, vec![ConfiguredOracle::NoCrash]);
+        // The weights of the profile's oracles don't apply to others
+        assert!(config.oracle_weights.is_empty());
+        assert_eq!(config.join_row_budget, Some(1_000_000));
+    }
+}
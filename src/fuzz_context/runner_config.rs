@@ -1,10 +1,25 @@
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
 
-use crate::common::{Result, fuzzer_err};
+use crate::cli::LogRotation;
+use crate::cli::error_whitelist::{ErrorWhitelist, WhitelistConfig};
+use crate::common::{FuzzerDataType, Result, fuzzer_err};
+use crate::datasource_generator::file_dataset::{DatasetFormat, DatasetStore};
+use crate::engine::ReferenceEngine;
+use crate::fuzz_context::adversarial_batches::AdversarialBatches;
+use crate::fuzz_context::swarm::SwarmFeature;
+use crate::fuzz_runner::coverage::steered_clause;
+use crate::fuzz_runner::findings::load_findings;
+use crate::fuzz_runner::known_issues::KnownIssues;
+use crate::fuzz_runner::quarantine::QuarantinePattern;
+use crate::fuzz_runner::query_shape::QueryFeature;
 use crate::oracle::ConfiguredOracle;
+use crate::oracle::result_compare::{CompareMode, CompareOptions};
+use crate::query_generator::query_template::QueryTemplate;
+use crate::triage::FailureCategory;
 
 /// Unified configuration for the DataFusion fuzzer.
 ///
@@ -12,51 +27,266 @@ use crate::oracle::ConfiguredOracle;
 /// 1. The overall fuzzing process (rounds, queries, timeout)
 /// 2. The table and query generation parameters
 /// 3. UI and display parameters
+///
+/// This is the only configuration type: config files, CLI overrides and the
+/// runner all go through it. Options missing from a config file take their
+/// value from [`RunnerConfig::default`], so a file only needs to list what it
+/// changes.
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
 pub struct RunnerConfig {
     // General fuzzing parameters
     pub seed: u64,
     pub rounds: u32,
     pub queries_per_round: u32,
     pub timeout_seconds: u64,
+    /// Time out queries after `adaptive_timeout_factor` times the p99 latency
+    /// of recent queries, at least `adaptive_timeout_floor_ms` and at most
+    /// `timeout_seconds`, instead of always after `timeout_seconds`
+    pub adaptive_timeout: bool,
+    pub adaptive_timeout_factor: f64,
+    pub adaptive_timeout_floor_ms: u64,
+    /// Run timed out queries once more with nothing else running, so only
+    /// queries that also time out alone count as slow
+    pub retry_timeouts: bool,
+    /// Time out the planning of queries in the embedded session after this
+    /// long, reported as a PlanningTimeout finding. The timeout of the whole
+    /// query still applies.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plan_timeout_ms: Option<u64>,
+    /// Time out the execution of planned queries in the embedded session after
+    /// this long, within the timeout of the whole query
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execution_timeout_ms: Option<u64>,
+    /// Stop the campaign after this many seconds; with `rounds = 0` rounds are
+    /// run until the time is up
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_duration_secs: Option<u64>,
+    /// Size of the DataFusion memory pool in MiB; queries exceeding it fail
+    /// with a resources exhausted error, which is expected and not a finding
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_query_memory_mb: Option<u64>,
+    /// Keep only the first rows of larger query results, counting the rest
+    /// while streaming them, so huge results (e.g. of cross joins) never
+    /// have to fit in memory. Truncated results are compared by row count,
+    /// and ordered results also by their first rows.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_result_rows: Option<usize>,
+    /// Write results with more than `max_result_rows` rows to temporary Arrow
+    /// IPC files, so they are still compared in full, chunk by chunk
+    pub spool_results: bool,
+    /// Measure the peak memory reservation and the operator CPU time of every
+    /// query in the embedded session, and report the top consumers
+    pub measure_resources: bool,
+    /// Record the memory of the process after every round, and report a
+    /// MemoryLeak finding once it grew in this many consecutive rounds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_leak_rounds: Option<u32>,
+    /// Report a SlowPlanning finding for queries of the embedded session that
+    /// take longer than this to plan
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub planning_time_threshold_ms: Option<u64>,
+    /// Run the limited query of the MemoryLimit oracle with tiny sort
+    /// reservations and small batches, so sorts and aggregations spill to disk
+    pub spill_fuzzing: bool,
+    /// Directory for spill files, the run's workspace if not set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spill_dir: Option<PathBuf>,
+    /// Wrap every operator of the fuzzed queries so its output batches are
+    /// split at random boundaries ("split"), and also shuffled where no order
+    /// is promised ("reorder")
+    pub adversarial_batches: AdversarialBatches,
+    /// Number of oracle tests generated ahead while the current one executes;
+    /// 0 generates and executes one test at a time
+    pub pipeline_depth: usize,
+    /// Generate the tables once and restore them in every later round instead
+    /// of generating new ones, which is faster with few queries per round
+    pub reuse_datasets: bool,
+    /// Keep the tables between rounds, dropping some and adding new ones each
+    /// round, instead of starting every round with new tables
+    pub schema_evolution: bool,
+    /// Generate every round with a random half of the feature families
+    /// (joins, GROUP BY, operator groups, column types), see `swarm`
+    pub swarm_testing: bool,
+    /// Seed pool file: run every round with a base seed from the pool, seeds
+    /// whose rounds produced findings or new query shapes first, or with a
+    /// fresh one, and keep the productive seeds in the file for the next
+    /// campaigns, see `seed_pool`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed_pool: Option<PathBuf>,
+    /// Where generated tables are stored: "memory", or "parquet" or "csv"
+    /// files read through listing tables
+    pub dataset_format: DatasetFormat,
+    /// Directory for the files of file-backed tables, the run's workspace if
+    /// not set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dataset_dir: Option<PathBuf>,
+    /// Where listing tables read the files of file-backed tables from:
+    /// "local" files, or copies in a "memory" object store
+    pub dataset_store: DatasetStore,
+    /// Write Parquet tables sorted by a random column, in row groups and
+    /// pages of a few rows with page indexes, so min/max pruning skips data
+    pub parquet_pruning_stress: bool,
+    /// Keep the run's workspace of generated tables, spill files and
+    /// reproducers after a run without findings, which deletes it otherwise
+    pub keep_artifacts: bool,
+    /// Flight SQL endpoint of a remote DataFusion instance (e.g.
+    /// `http://localhost:50051`) the tables are mirrored to, for the FlightSql
+    /// and EngineDiff oracles. Requires the `flight-sql` feature.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flight_sql_endpoint: Option<String>,
+    /// Embedded databases the tables are mirrored to, for the EngineDiff
+    /// oracle. Each requires the feature of the same name.
+    pub reference_engines: Vec<ReferenceEngine>,
     pub log_path: Option<PathBuf>,
+    /// How `trace.log` in `log_path` is split: never, round, size, hourly or daily
+    pub log_rotation: LogRotation,
+    /// Size limit of one trace log file for `log_rotation = "size"`
+    pub log_rotation_max_bytes: u64,
 
     // UI and display parameters
     pub display_logs: bool,
     pub enable_tui: bool,
     pub sample_interval_secs: u64,
+    /// Interval of the single-line progress summary printed to stderr when the
+    /// TUI is disabled; 0 turns it off
+    pub progress_interval_secs: u64,
 
     // Table and query generation parameters
     pub max_column_count: u64,
     pub max_row_count: u64,
     pub max_expr_level: u32,
-    #[serde(default = "RunnerConfig::default_max_group_by_count")]
     pub max_group_by_count: u32,
     pub max_table_count: u32,
     pub max_insert_per_table: u32,
-    #[serde(default = "RunnerConfig::default_oracles", alias = "oracle")]
+    /// Maximum worst-case number of rows of the joins in one query, the
+    /// product of the row counts of its tables. Queries over more rows get
+    /// other tables, or fewer of them. Unlimited if not set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub join_row_budget: Option<u64>,
+    /// Nesting depth of the deepest expressions and subqueries of the
+    /// DeepNesting oracle, which stress recursion in the parser, planner and
+    /// optimizer
+    pub max_nesting_depth: u32,
+    /// Number of expressions of the widest SELECT list of the WideProjection
+    /// oracle
+    pub max_projection_width: u32,
+    /// Number of keys of the widest GROUP BY list of the WideProjection oracle
+    pub max_group_by_width: u32,
+    /// SQL templates of the Template oracle, with `{table}`, `{column}`,
+    /// `{expr}` and `{pred}` holes filled by the generator, see
+    /// `query_template`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub query_templates: Vec<String>,
+    /// Column and expression types to generate, e.g. `["timestamp", "int64"]`,
+    /// all of them if empty
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub data_types: Vec<FuzzerDataType>,
+    /// Probability that the literal of a `column <op> literal` comparison is
+    /// replaced by a value present in the column, so predicates are selective
+    /// instead of almost always false. Half of it is the probability that a
+    /// boolean leaf becomes a `BETWEEN` or `IN` predicate over stored values.
+    pub predicate_literal_bias: f64,
+    /// Give generated timestamps time zones from a larger corpus: zones with
+    /// DST transitions, offsets with minutes and unusual but parsable names
+    pub extended_timezones: bool,
+    /// Give some generated columns names that must be quoted: names differing
+    /// only by case, with spaces or quotes, keywords and Unicode
+    pub tricky_identifiers: bool,
+    /// Name some generated tables and columns after SQL keywords, which every
+    /// statement and query must quote
+    pub keyword_identifiers: bool,
+    /// Create some tables in other schemas and catalogs, and refer to tables
+    /// with random levels of qualification (`t`, `public.t`,
+    /// `datafusion.public.t`). Not supported with other engines, which don't
+    /// share DataFusion's catalogs.
+    pub multi_schema: bool,
+    #[serde(alias = "oracle")]
     pub oracles: Vec<ConfiguredOracle>,
+    /// Selection weight of every oracle in `oracles`, selected uniformly when
+    /// empty
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub oracle_weights: Vec<f64>,
+    /// Oracles that compare results with at least `checksum_min_rows` rows by row
+    /// count and per-column checksums instead of row by row
+    pub checksum_oracles: Vec<ConfiguredOracle>,
+    pub checksum_min_rows: usize,
+
+    /// Finding categories that stop the run (e.g. `["Panic", "InternalError"]`),
+    /// all other categories are informational
+    pub fatal_categories: Vec<FailureCategory>,
+    /// Stop the campaign with a non-zero exit code at the first finding, or once
+    /// `max_findings` findings were recorded
+    pub stop_on_first_finding: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_findings: Option<u64>,
+    /// TOML file mapping finding fingerprints to upstream issues. Matching
+    /// findings are reported as known and don't stop the run, see `known_issues`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub known_issues: Option<PathBuf>,
+    /// Findings file of a previous run. Findings with a fingerprint of the
+    /// baseline don't stop the run, any other finding makes the run exit with a
+    /// non-zero code, e.g. to gate CI on regressions only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub baseline: Option<PathBuf>,
+    /// Write the result sets of wrong-result findings as Arrow IPC files to
+    /// `results/<query_id>/` in the log directory
+    pub dump_mismatch_ipc: bool,
+    /// Append the `EXPLAIN FORMAT indent` output of every statement to
+    /// `plans.log` in the log directory, for diffing plans between versions
+    pub record_plans: bool,
+    /// `datafusion-cli` binary error findings are replayed in, to record whether
+    /// they reproduce with the CLI's default configuration. Reproducers are
+    /// written to `cli/<query_id>/` in the log directory, or the run's workspace.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub datafusion_cli: Option<PathBuf>,
+
+    /// Upstream DataFusion commit under test, recorded next to the crate version
+    /// in findings, logs and the final report
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub datafusion_git_sha: Option<String>,
+
+    /// Share of queries (0 to 1) that should contain each feature, e.g.
+    /// `aggregates = 0.1`. The optional clauses generating the features missing
+    /// (JOIN, GROUP BY) are generated more often, see `coverage`
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub coverage_targets: BTreeMap<QueryFeature, f64>,
+    /// Share (0 to 1) of the gap to probability 1 added to the probability of
+    /// each optional clause, steered by `coverage_targets` during the run.
+    /// Findings record the boosts in use, so a replay generates the same queries.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub coverage_boosts: BTreeMap<SwarmFeature, f64>,
+
+    /// Known-slow query shapes whose oracle tests are skipped or run less
+    /// often, see `quarantine`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub quarantine: Vec<QuarantinePattern>,
+
+    /// Error whitelist patterns merged with the built-in defaults
+    pub whitelist: WhitelistConfig,
 }
 
 impl RunnerConfig {
     pub fn from_file(path: &Path) -> Result<Self> {
-        let content = fs::read_to_string(path)
-            .map_err(|e| fuzzer_err(&format!("Failed to read config file: {}", e)))?;
-
-        Self::from_toml_str(&content)
+        Self::parse_file(path)?.validate()
     }
 
-    pub fn from_cli(cli: &crate::cli::Cli) -> Result<Self> {
+    pub fn from_cli(cli: &crate::cli::RunArgs) -> Result<Self> {
         // Start with default or config file if provided
         let mut config = if let Some(config_path) = &cli.config {
-            Self::from_file(config_path)?
+            Self::parse_file(config_path)?
         } else {
             Self::default()
         };
 
+        // The profile presets settings, the other CLI arguments override them
+        if let Some(profile) = cli.profile {
+            profile.apply(&mut config);
+        }
+
         // Override with CLI arguments if provided
-        if cli.seed != 42 {
-            config.seed = cli.seed;
+        if let Some(seed) = cli.seed {
+            config.seed = seed;
         }
 
         if let Some(rounds) = cli.rounds {
@@ -71,10 +301,226 @@ impl RunnerConfig {
             config.timeout_seconds = timeout;
         }
 
+        if cli.adaptive_timeout {
+            config.adaptive_timeout = true;
+        }
+
+        if let Some(adaptive_timeout_factor) = cli.adaptive_timeout_factor {
+            config.adaptive_timeout_factor = adaptive_timeout_factor;
+        }
+
+        if let Some(adaptive_timeout_floor_ms) = cli.adaptive_timeout_floor_ms {
+            config.adaptive_timeout_floor_ms = adaptive_timeout_floor_ms;
+        }
+
+        if cli.retry_timeouts {
+            config.retry_timeouts = true;
+        }
+
+        if let Some(plan_timeout_ms) = cli.plan_timeout_ms {
+            config.plan_timeout_ms = Some(plan_timeout_ms);
+        }
+
+        if let Some(execution_timeout_ms) = cli.execution_timeout_ms {
+            config.execution_timeout_ms = Some(execution_timeout_ms);
+        }
+
+        if let Some(max_duration_secs) = cli.max_duration_secs {
+            config.max_duration_secs = Some(max_duration_secs);
+        }
+
+        if let Some(max_query_memory_mb) = cli.max_query_memory_mb {
+            config.max_query_memory_mb = Some(max_query_memory_mb);
+        }
+
+        if let Some(max_result_rows) = cli.max_result_rows {
+            config.max_result_rows = Some(max_result_rows);
+        }
+
+        if cli.spool_results {
+            config.spool_results = true;
+        }
+
+        if cli.measure_resources {
+            config.measure_resources = true;
+        }
+
+        if let Some(memory_leak_rounds) = cli.memory_leak_rounds {
+            config.memory_leak_rounds = Some(memory_leak_rounds);
+        }
+
+        if let Some(planning_time_threshold_ms) = cli.planning_time_threshold_ms {
+            config.planning_time_threshold_ms = Some(planning_time_threshold_ms);
+        }
+
+        if cli.spill_fuzzing {
+            config.spill_fuzzing = true;
+        }
+
+        if let Some(spill_dir) = &cli.spill_dir {
+            config.spill_dir = Some(spill_dir.clone());
+        }
+
+        if let Some(adversarial_batches) = cli.adversarial_batches {
+            config.adversarial_batches = adversarial_batches;
+        }
+
+        if let Some(pipeline_depth) = cli.pipeline_depth {
+            config.pipeline_depth = pipeline_depth;
+        }
+
+        if cli.reuse_datasets {
+            config.reuse_datasets = true;
+        }
+
+        if cli.schema_evolution {
+            config.schema_evolution = true;
+        }
+
+        if cli.swarm_testing {
+            config.swarm_testing = true;
+        }
+
+        if let Some(seed_pool) = &cli.seed_pool {
+            config.seed_pool = Some(seed_pool.clone());
+        }
+
+        if let Some(dataset_format) = cli.dataset_format {
+            config.dataset_format = dataset_format;
+        }
+
+        if let Some(dataset_dir) = &cli.dataset_dir {
+            config.dataset_dir = Some(dataset_dir.clone());
+        }
+
+        if let Some(dataset_store) = cli.dataset_store {
+            config.dataset_store = dataset_store;
+        }
+
+        if cli.parquet_pruning_stress {
+            config.parquet_pruning_stress = true;
+        }
+
+        if cli.keep_artifacts {
+            config.keep_artifacts = true;
+        }
+
+        if let Some(flight_sql_endpoint) = &cli.flight_sql_endpoint {
+            config.flight_sql_endpoint = Some(flight_sql_endpoint.clone());
+        }
+
+        if let Some(reference_engines) = &cli.reference_engines {
+            config.reference_engines = reference_engines.clone();
+        }
+
         if let Some(log_path) = &cli.log_path {
             config.log_path = Some(log_path.clone());
         }
 
+        if let Some(sample_interval_secs) = cli.sample_interval_secs {
+            config.sample_interval_secs = sample_interval_secs;
+        }
+
+        if let Some(progress_interval_secs) = cli.progress_interval_secs {
+            config.progress_interval_secs = progress_interval_secs;
+        }
+
+        if let Some(max_column_count) = cli.max_column_count {
+            config.max_column_count = max_column_count;
+        }
+
+        if let Some(max_row_count) = cli.max_row_count {
+            config.max_row_count = max_row_count;
+        }
+
+        if let Some(max_expr_level) = cli.max_expr_level {
+            config.max_expr_level = max_expr_level;
+        }
+
+        if let Some(max_group_by_count) = cli.max_group_by_count {
+            config.max_group_by_count = max_group_by_count;
+        }
+
+        if let Some(max_table_count) = cli.max_table_count {
+            config.max_table_count = max_table_count;
+        }
+
+        if let Some(max_insert_per_table) = cli.max_insert_per_table {
+            config.max_insert_per_table = max_insert_per_table;
+        }
+
+        if let Some(join_row_budget) = cli.join_row_budget {
+            config.join_row_budget = Some(join_row_budget);
+        }
+
+        if let Some(max_nesting_depth) = cli.max_nesting_depth {
+            config.max_nesting_depth = max_nesting_depth;
+        }
+
+        if let Some(max_projection_width) = cli.max_projection_width {
+            config.max_projection_width = max_projection_width;
+        }
+
+        if let Some(max_group_by_width) = cli.max_group_by_width {
+            config.max_group_by_width = max_group_by_width;
+        }
+
+        if let Some(predicate_literal_bias) = cli.predicate_literal_bias {
+            config.predicate_literal_bias = predicate_literal_bias;
+        }
+
+        if cli.extended_timezones {
+            config.extended_timezones = true;
+        }
+
+        if cli.tricky_identifiers {
+            config.tricky_identifiers = true;
+        }
+
+        if cli.keyword_identifiers {
+            config.keyword_identifiers = true;
+        }
+
+        if cli.multi_schema {
+            config.multi_schema = true;
+        }
+
+        if let Some(oracles) = &cli.oracles {
+            config.oracles = oracles.clone();
+            // The weights of the profile belong to its oracles
+            if cli.profile.is_some() {
+                config.oracle_weights.clear();
+            }
+        }
+
+        if cli.stop_on_first_finding {
+            config.stop_on_first_finding = true;
+        }
+
+        if let Some(max_findings) = cli.max_findings {
+            config.max_findings = Some(max_findings);
+        }
+
+        if let Some(known_issues) = &cli.known_issues {
+            config.known_issues = Some(known_issues.clone());
+        }
+
+        if let Some(baseline) = &cli.baseline {
+            config.baseline = Some(baseline.clone());
+        }
+
+        if cli.dump_mismatch_ipc {
+            config.dump_mismatch_ipc = true;
+        }
+
+        if cli.record_plans {
+            config.record_plans = true;
+        }
+
+        if let Some(datafusion_cli) = &cli.datafusion_cli {
+            config.datafusion_cli = Some(datafusion_cli.clone());
+        }
+
         // Set display_logs from CLI argument
         config.display_logs = cli.display_logs;
 
@@ -84,27 +530,359 @@ impl RunnerConfig {
         config.validate()
     }
 
-    fn from_toml_str(content: &str) -> Result<Self> {
-        let config: Self = toml::from_str(content)
-            .map_err(|e| fuzzer_err(&format!("Failed to parse config file: {}", e)))?;
+    pub fn from_toml_str(content: &str) -> Result<Self> {
+        Self::parse_toml(content)?.validate()
+    }
 
-        config.validate()
+    /// Read a config file without validating it, the CLI may still override
+    /// its settings
+    fn parse_file(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| fuzzer_err(&format!("Failed to read config file: {}", e)))?;
+
+        Self::parse_toml(&content)
+    }
+
+    fn parse_toml(content: &str) -> Result<Self> {
+        toml::from_str(content)
+            .map_err(|e| fuzzer_err(&format!("Failed to parse config file: {}", e)))
     }
 
-    fn validate(self) -> Result<Self> {
+    /// The fully resolved configuration (defaults, config file and CLI merged)
+    /// as TOML, which can be passed back with `--config` to reproduce the run.
+    pub fn to_toml(&self) -> Result<String> {
+        toml::to_string(self).map_err(|e| fuzzer_err(&format!("Failed to serialize config: {}", e)))
+    }
+
+    /// Reject nonsensical settings up front with a message naming the offending
+    /// option, instead of failing deep inside generation. All problems are
+    /// reported at once, one per line.
+    pub fn validate(self) -> Result<Self> {
+        let mut errors = Vec::new();
+
         if self.oracles.is_empty() {
-            return Err(fuzzer_err("At least one oracle must be configured"));
+            errors.push("At least one oracle must be configured".to_string());
+        }
+        if self.rounds == 0 && self.max_duration_secs.is_none() {
+            errors.push(
+                "rounds = 0 runs nothing: set rounds (--rounds) or a time limit with \
+                 max_duration_secs (--max-duration-secs)"
+                    .to_string(),
+            );
+        }
+        if self.max_duration_secs == Some(0) {
+            errors.push("max_duration_secs must be at least 1".to_string());
+        }
+        if self.max_query_memory_mb == Some(0) {
+            errors.push("max_query_memory_mb must be at least 1".to_string());
+        }
+        if self.max_result_rows == Some(0) {
+            errors.push("max_result_rows must be at least 1".to_string());
+        }
+        if self.spool_results && self.max_result_rows.is_none() {
+            errors.push(
+                "spool_results requires max_result_rows, results are only spooled past it"
+                    .to_string(),
+            );
+        }
+        if self.memory_leak_rounds.is_some_and(|rounds| rounds < 2) {
+            errors.push(
+                "memory_leak_rounds must be at least 2, memory also grows in single rounds \
+                 while caches warm up"
+                    .to_string(),
+            );
+        }
+        if self.planning_time_threshold_ms == Some(0) {
+            errors.push("planning_time_threshold_ms must be at least 1".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.predicate_literal_bias) {
+            errors.push("predicate_literal_bias must be between 0 and 1".to_string());
+        }
+        if self.reuse_datasets && self.schema_evolution {
+            errors.push(
+                "reuse_datasets and schema_evolution can't be combined, the first restores \
+                 the same tables every round and the second changes them"
+                    .to_string(),
+            );
+        }
+        if self.seed_pool.is_some() && (self.reuse_datasets || self.schema_evolution) {
+            errors.push(
+                "seed_pool can't be combined with reuse_datasets or schema_evolution, every \
+                 round of the pool generates the tables of its own seed"
+                    .to_string(),
+            );
+        }
+        if self.multi_schema
+            && (self.flight_sql_endpoint.is_some() || !self.reference_engines.is_empty())
+        {
+            errors.push(
+                "multi_schema can't be combined with flight_sql_endpoint or reference_engines, \
+                 the tables in other schemas and catalogs aren't mirrored"
+                    .to_string(),
+            );
+        }
+        if self.oracles.contains(&ConfiguredOracle::FlightSql) && self.flight_sql_endpoint.is_none()
+        {
+            errors.push(
+                "The FlightSql oracle requires flight_sql_endpoint (--flight-sql-endpoint)"
+                    .to_string(),
+            );
+        }
+        if self.oracles.contains(&ConfiguredOracle::Template) && self.query_templates.is_empty() {
+            errors.push("The Template oracle requires query_templates".to_string());
+        }
+        for (index, template) in self.query_templates.iter().enumerate() {
+            if let Err(e) = QueryTemplate::parse(template) {
+                errors.push(format!("query_templates[{}]: {}", index, e));
+            }
+        }
+        if self.oracles.contains(&ConfiguredOracle::EngineDiff)
+            && self.flight_sql_endpoint.is_none()
+            && self.reference_engines.is_empty()
+        {
+            errors.push(
+                "The EngineDiff oracle requires flight_sql_endpoint or reference_engines \
+                 (--reference-engines)"
+                    .to_string(),
+            );
+        }
+        if self.dataset_format != DatasetFormat::Parquet {
+            for oracle in self
+                .oracles
+                .iter()
+                .filter(|oracle| oracle.requires_parquet_datasets())
+            {
+                errors.push(format!(
+                    "The {:?} oracle requires Parquet-backed tables, set dataset_format \
+                     (--dataset-format) to parquet",
+                    oracle
+                ));
+            }
+        }
+        if self.queries_per_round == 0 {
+            errors.push("queries_per_round must be at least 1".to_string());
+        }
+        if self.timeout_seconds == 0 {
+            errors
+                .push("timeout_seconds must be at least 1, every query would time out".to_string());
+        }
+        if self.adaptive_timeout_factor < 1.0 {
+            errors.push(
+                "adaptive_timeout_factor must be at least 1, the timeout would cut off \
+                 typical queries"
+                    .to_string(),
+            );
+        }
+        if self.adaptive_timeout_floor_ms > self.timeout_seconds * 1000 {
+            errors.push(
+                "adaptive_timeout_floor_ms must not exceed timeout_seconds, which caps the \
+                 adaptive timeout"
+                    .to_string(),
+            );
+        }
+        for (name, phase_timeout_ms) in [
+            ("plan_timeout_ms", self.plan_timeout_ms),
+            ("execution_timeout_ms", self.execution_timeout_ms),
+        ] {
+            match phase_timeout_ms {
+                Some(0) => errors.push(format!(
+                    "{} must be at least 1, every query would time out",
+                    name
+                )),
+                Some(ms) if ms > self.timeout_seconds * 1000 => errors.push(format!(
+                    "{} must not exceed timeout_seconds, which caps the whole query",
+                    name
+                )),
+                _ => {}
+            }
+        }
+        if self.max_expr_level == 0 {
+            errors.push(
+                "max_expr_level must be at least 1, no expression can be generated".to_string(),
+            );
+        }
+        if self.max_table_count == 0 {
+            errors
+                .push("max_table_count must be at least 1, queries need a FROM table".to_string());
+        }
+        if self.join_row_budget == Some(0) {
+            errors.push("join_row_budget must be at least 1".to_string());
+        }
+        if self.max_nesting_depth < 4 {
+            errors.push(
+                "max_nesting_depth must be at least 4, the DeepNesting oracle compares a \
+                 quarter, half and all of it"
+                    .to_string(),
+            );
+        }
+        if self.max_projection_width < 4 || self.max_group_by_width < 4 {
+            errors.push(
+                "max_projection_width and max_group_by_width must be at least 4, the \
+                 WideProjection oracle compares a quarter and all of them"
+                    .to_string(),
+            );
+        }
+        if self.max_column_count == 0 {
+            errors.push("max_column_count must be at least 1".to_string());
+        }
+        if self.max_findings == Some(0) {
+            errors.push("max_findings must be at least 1".to_string());
+        }
+        if !self.oracle_weights.is_empty() {
+            if self.oracle_weights.len() != self.oracles.len() {
+                errors.push(format!(
+                    "oracle_weights must have one weight per oracle, got {} weights for {} oracles",
+                    self.oracle_weights.len(),
+                    self.oracles.len()
+                ));
+            }
+            if self
+                .oracle_weights
+                .iter()
+                .any(|weight| !weight.is_finite() || *weight < 0.0)
+            {
+                errors.push("oracle_weights must not be negative".to_string());
+            } else if self.oracle_weights.iter().sum::<f64>() <= 0.0 {
+                errors.push("oracle_weights must have at least one positive weight".to_string());
+            }
+        }
+        for (feature, target) in &self.coverage_targets {
+            if !(*target > 0.0 && *target <= 1.0) {
+                errors.push(format!(
+                    "coverage target of {} must be above 0 and at most 1",
+                    feature.name()
+                ));
+            }
+        }
+        for (clause, boost) in &self.coverage_boosts {
+            if !(0.0..=1.0).contains(boost) {
+                errors.push(format!(
+                    "coverage boost of {} must be between 0 and 1",
+                    clause.name()
+                ));
+            }
+        }
+        for pattern in &self.quarantine {
+            errors.extend(pattern.validate());
+        }
+
+        // Surface invalid patterns or an unreadable whitelist file at startup
+        if let Err(e) = ErrorWhitelist::from_config(&self.whitelist) {
+            errors.push(e.to_string());
+        }
+        if let Some(path) = &self.known_issues
+            && let Err(e) = KnownIssues::load(path)
+        {
+            errors.push(e.to_string());
+        }
+        if let Some(path) = &self.baseline
+            && let Err(e) = load_findings(path)
+        {
+            errors.push(e.to_string());
+        }
+
+        if !errors.is_empty() {
+            return Err(fuzzer_err(&errors.join("\n")));
         }
 
         Ok(self)
     }
 
-    fn default_oracles() -> Vec<ConfiguredOracle> {
-        vec![ConfiguredOracle::NoCrash]
+    /// Settings that are valid but likely make the campaign slow or ineffective.
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+
+        // A cross join of the largest tables can produce this many rows
+        let max_join_rows = (self.max_row_count as f64)
+            .powi(self.max_table_count as i32)
+            .min(
+                self.join_row_budget
+                    .map_or(f64::INFINITY, |budget| budget as f64),
+            );
+        if self.max_table_count >= 4 && max_join_rows >= 1e8 && self.timeout_seconds <= 2 {
+            warnings.push(format!(
+                "max_table_count = {} with max_row_count = {} can produce joins of up to {:.0e} rows, \
+                 most of them will hit timeout_seconds = {}; set join_row_budget to avoid them",
+                self.max_table_count, self.max_row_count, max_join_rows, self.timeout_seconds
+            ));
+        }
+
+        if self.max_expr_level >= 8 {
+            warnings.push(format!(
+                "max_expr_level = {} generates very deep expressions, planning may dominate the run time",
+                self.max_expr_level
+            ));
+        }
+
+        if self.spill_fuzzing && !self.oracles.contains(&ConfiguredOracle::MemoryLimit) {
+            warnings.push(
+                "spill_fuzzing only affects the MemoryLimit oracle, which is not in oracles"
+                    .to_string(),
+            );
+        }
+
+        if self.memory_leak_rounds.is_some() && self.schema_evolution {
+            warnings.push(
+                "memory_leak_rounds has no effect with schema_evolution, whose tables grow \
+                 from round to round"
+                    .to_string(),
+            );
+        }
+
+        if !self.query_templates.is_empty() && !self.oracles.contains(&ConfiguredOracle::Template) {
+            warnings.push(
+                "query_templates are only used by the Template oracle, which is not in oracles"
+                    .to_string(),
+            );
+        }
+
+        for feature in self.coverage_targets.keys() {
+            if steered_clause(*feature).is_none() {
+                warnings.push(format!(
+                    "the coverage target of {} is only reported, no generated clause is steered \
+                     towards it",
+                    feature.name()
+                ));
+            }
+        }
+
+        for oracle in &self.checksum_oracles {
+            if !self.oracles.contains(oracle) {
+                warnings.push(format!(
+                    "checksum_oracles contains {:?}, which is not in oracles",
+                    oracle
+                ));
+            }
+        }
+
+        warnings
+    }
+
+    /// DataFusion crate version, followed by the configured git SHA if any
+    /// (e.g. `52.3.0 (git a1b2c3d)`).
+    pub fn datafusion_version(&self) -> String {
+        match &self.datafusion_git_sha {
+            Some(sha) => format!("{} (git {})", datafusion::DATAFUSION_VERSION, sha),
+            None => datafusion::DATAFUSION_VERSION.to_string(),
+        }
     }
 
-    fn default_max_group_by_count() -> u32 {
-        3
+    /// The configured memory pool size in bytes, `None` for no limit
+    pub fn max_query_memory_bytes(&self) -> Option<usize> {
+        self.max_query_memory_mb
+            .map(|mb| (mb as usize).saturating_mul(1024 * 1024))
+    }
+
+    /// Result comparison options for the given oracle.
+    pub fn compare_options(&self, oracle: ConfiguredOracle) -> CompareOptions {
+        let mode = if self.checksum_oracles.contains(&oracle) {
+            CompareMode::Checksum {
+                min_rows: self.checksum_min_rows,
+            }
+        } else {
+            CompareMode::Full
+        };
+        CompareOptions::default().with_mode(mode)
     }
 }
 
@@ -115,17 +893,75 @@ impl Default for RunnerConfig {
             rounds: 3,
             queries_per_round: 10,
             timeout_seconds: 2,
+            adaptive_timeout: false,
+            adaptive_timeout_factor: 5.0,
+            adaptive_timeout_floor_ms: 100,
+            retry_timeouts: false,
+            plan_timeout_ms: None,
+            execution_timeout_ms: None,
+            max_duration_secs: None,
+            max_query_memory_mb: None,
+            max_result_rows: None,
+            spool_results: false,
+            measure_resources: false,
+            memory_leak_rounds: None,
+            planning_time_threshold_ms: None,
+            spill_fuzzing: false,
+            spill_dir: None,
+            adversarial_batches: AdversarialBatches::Off,
+            pipeline_depth: 0,
+            reuse_datasets: false,
+            schema_evolution: false,
+            swarm_testing: false,
+            seed_pool: None,
+            dataset_format: DatasetFormat::Memory,
+            dataset_dir: None,
+            dataset_store: DatasetStore::Local,
+            parquet_pruning_stress: false,
+            keep_artifacts: false,
+            flight_sql_endpoint: None,
+            reference_engines: Vec::new(),
             log_path: Some(PathBuf::from("logs")),
+            log_rotation: LogRotation::Never,
+            log_rotation_max_bytes: 256 * 1024 * 1024,
             display_logs: false,
             enable_tui: true,
             sample_interval_secs: 5,
+            progress_interval_secs: 10,
             max_column_count: 5,
             max_row_count: 100,
             max_expr_level: 3,
-            max_group_by_count: Self::default_max_group_by_count(),
+            max_group_by_count: 3,
             max_table_count: 3,
             max_insert_per_table: 20,
-            oracles: Self::default_oracles(),
+            join_row_budget: None,
+            max_nesting_depth: 2000,
+            max_projection_width: 2000,
+            max_group_by_width: 200,
+            query_templates: Vec::new(),
+            data_types: Vec::new(),
+            predicate_literal_bias: 0.0,
+            extended_timezones: false,
+            tricky_identifiers: false,
+            keyword_identifiers: false,
+            multi_schema: false,
+            oracles: vec![ConfiguredOracle::NoCrash],
+            oracle_weights: Vec::new(),
+            checksum_oracles: Vec::new(),
+            checksum_min_rows: 10_000,
+            fatal_categories: Vec::new(),
+            stop_on_first_finding: false,
+            max_findings: None,
+            known_issues: None,
+            baseline: None,
+            dump_mismatch_ipc: false,
+            record_plans: false,
+            datafusion_cli: None,
+            datafusion_git_sha: None,
+            coverage_targets: BTreeMap::new(),
+            coverage_boosts: BTreeMap::new(),
+            quarantine: Vec::new(),
+            whitelist: WhitelistConfig::default(),
         }
     }
 }
@@ -219,4 +1055,470 @@ oracles = []
 
         assert_eq!(error.to_string(), "At least one oracle must be configured");
     }
+
+    #[test]
+    fn parses_whitelist_section() {
+        let config = RunnerConfig::from_toml_str(
+            r#"
+seed = 42
+rounds = 1
+queries_per_round = 1
+timeout_seconds = 2
+log_path = "logs"
+display_logs = false
+enable_tui = false
+sample_interval_secs = 5
+max_column_count = 5
+max_row_count = 100
+max_expr_level = 3
+max_group_by_count = 3
+max_table_count = 3
+max_insert_per_table = 20
+oracles = ["NoCrash"]
+
+[whitelist]
+[[whitelist.patterns]]
+contains = "my known issue"
+
+[[whitelist.patterns]]
+regex = "(?i)flaky"
+query_contains = "JOIN"
+"#,
+        )
+        .unwrap();
+
+        assert!(config.whitelist.use_builtin);
+        assert_eq!(config.whitelist.patterns.len(), 2);
+        assert_eq!(
+            config.whitelist.patterns[1].query_contains.as_deref(),
+            Some("JOIN")
+        );
+    }
+
+    #[test]
+    fn selects_checksum_compare_per_oracle() {
+        let config = RunnerConfig {
+            checksum_oracles: vec![ConfiguredOracle::TlpWhere],
+            checksum_min_rows: 500,
+            ..RunnerConfig::default()
+        };
+
+        assert_eq!(
+            config.compare_options(ConfiguredOracle::TlpWhere).mode,
+            CompareMode::Checksum { min_rows: 500 }
+        );
+        assert_eq!(
+            config.compare_options(ConfiguredOracle::TlpHaving).mode,
+            CompareMode::Full
+        );
+    }
+
+    #[test]
+    fn datafusion_version_includes_configured_git_sha() {
+        let config = RunnerConfig {
+            datafusion_git_sha: Some("a1b2c3d".to_string()),
+            ..RunnerConfig::default()
+        };
+
+        assert_eq!(
+            config.datafusion_version(),
+            format!("{} (git a1b2c3d)", datafusion::DATAFUSION_VERSION)
+        );
+        assert_eq!(
+            RunnerConfig::default().datafusion_version(),
+            datafusion::DATAFUSION_VERSION
+        );
+    }
+
+    #[test]
+    fn missing_options_use_defaults() {
+        let config = RunnerConfig::from_toml_str(
+            r#"
+seed = 7
+oracles = ["TlpWhere"]
+"#,
+        )
+        .unwrap();
+        let defaults = RunnerConfig::default();
+
+        assert_eq!(config.seed, 7);
+        assert_eq!(config.oracles, vec![ConfiguredOracle::TlpWhere]);
+        assert_eq!(config.rounds, defaults.rounds);
+        assert_eq!(config.timeout_seconds, defaults.timeout_seconds);
+        assert_eq!(config.log_path, defaults.log_path);
+        assert_eq!(config.max_insert_per_table, defaults.max_insert_per_table);
+    }
+
+    #[test]
+    fn cli_arguments_override_config() {
+        use clap::Parser;
+
+        let cli = crate::cli::Cli::parse_from([
+            "datafusion-fuzzer",
+            "--seed",
+            "42",
+            "--max-table-count",
+            "1",
+            "--max-expr-level",
+            "5",
+            "--oracles",
+            "NoCrash,TlpWhereOracle",
+        ]);
+        let crate::cli::Command::Run(args) = cli.into_command() else {
+            panic!("expected the run command");
+        };
+        let config = RunnerConfig::from_cli(&args).unwrap();
+
+        assert_eq!(config.seed, 42);
+        assert_eq!(config.max_table_count, 1);
+        assert_eq!(config.max_expr_level, 5);
+        assert_eq!(
+            config.oracles,
+            vec![ConfiguredOracle::NoCrash, ConfiguredOracle::TlpWhere]
+        );
+        // Not given on the command line, keeps the default
+        assert_eq!(
+            config.max_insert_per_table,
+            RunnerConfig::default().max_insert_per_table
+        );
+    }
+
+    #[test]
+    fn cli_overrides_apply_before_validating_the_config_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fuzzer.toml");
+        fs::write(&path, "rounds = 0\n").unwrap();
+        // Invalid on its own: nothing bounds the run
+        assert!(RunnerConfig::from_file(&path).is_err());
+
+        let cli = crate::cli::Cli::parse_from([
+            "datafusion-fuzzer",
+            "--config",
+            path.to_str().unwrap(),
+            "--max-duration-secs",
+            "60",
+        ]);
+        let crate::cli::Command::Run(args) = cli.into_command() else {
+            panic!("expected the run command");
+        };
+        let config = RunnerConfig::from_cli(&args).unwrap();
+
+        assert_eq!(config.rounds, 0);
+        assert_eq!(config.max_duration_secs, Some(60));
+    }
+
+    #[test]
+    fn reports_all_invalid_settings() {
+        let config = RunnerConfig {
+            rounds: 0,
+            timeout_seconds: 0,
+            max_expr_level: 0,
+            max_table_count: 0,
+            ..RunnerConfig::default()
+        };
+
+        let message = config.validate().unwrap_err().to_string();
+        assert_eq!(message.lines().count(), 4);
+        assert!(message.contains("rounds = 0 runs nothing"));
+        assert!(message.contains("timeout_seconds must be at least 1"));
+        assert!(message.contains("max_expr_level must be at least 1"));
+        assert!(message.contains("max_table_count must be at least 1"));
+    }
+
+    #[test]
+    fn rejects_reusing_evolving_datasets() {
+        let config = RunnerConfig {
+            reuse_datasets: true,
+            schema_evolution: true,
+            ..RunnerConfig::default()
+        };
+
+        let message = config.validate().unwrap_err().to_string();
+        assert!(message.contains("reuse_datasets and schema_evolution"));
+
+        let config = RunnerConfig {
+            seed_pool: Some(PathBuf::from("seeds.json")),
+            reuse_datasets: true,
+            ..RunnerConfig::default()
+        };
+        let message = config.validate().unwrap_err().to_string();
+        assert!(message.contains("seed_pool can't be combined"));
+    }
+
+    #[test]
+    fn rejects_multi_schema_with_other_engines() {
+        let config = RunnerConfig {
+            multi_schema: true,
+            flight_sql_endpoint: Some("http://localhost:50051".to_string()),
+            ..RunnerConfig::default()
+        };
+        let message = config.clone().validate().unwrap_err().to_string();
+        assert!(message.contains("multi_schema can't be combined"));
+
+        let config = RunnerConfig {
+            flight_sql_endpoint: None,
+            ..config
+        };
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn flight_sql_oracle_requires_an_endpoint() {
+        let config = RunnerConfig {
+            oracles: vec![ConfiguredOracle::FlightSql],
+            ..RunnerConfig::default()
+        };
+        let message = config.clone().validate().unwrap_err().to_string();
+        assert!(message.contains("requires flight_sql_endpoint"));
+
+        let config = RunnerConfig {
+            flight_sql_endpoint: Some("http://localhost:50051".to_string()),
+            ..config
+        };
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn template_oracle_requires_valid_templates() {
+        let config = RunnerConfig {
+            oracles: vec![ConfiguredOracle::Template],
+            ..RunnerConfig::default()
+        };
+        let message = config.clone().validate().unwrap_err().to_string();
+        assert!(message.contains("requires query_templates"));
+
+        let config = RunnerConfig {
+            query_templates: vec![
+                "SELECT {expr} FROM {table} WHERE {pred}".to_string(),
+                "SELECT {exp} FROM {table}".to_string(),
+            ],
+            ..config
+        };
+        let message = config.clone().validate().unwrap_err().to_string();
+        assert_eq!(message.lines().count(), 1);
+        assert!(message.contains("query_templates[1]: "), "{}", message);
+
+        let config = RunnerConfig {
+            oracles: vec![ConfiguredOracle::NoCrash],
+            query_templates: vec!["SELECT {expr} FROM {table}".to_string()],
+            ..config
+        };
+        assert!(config.clone().validate().is_ok());
+        assert!(
+            config
+                .warnings()
+                .iter()
+                .any(|warning| warning.contains("only used by the Template oracle"))
+        );
+    }
+
+    #[test]
+    fn parquet_oracles_require_parquet_datasets() {
+        let config = RunnerConfig {
+            oracles: vec![ConfiguredOracle::Statistics],
+            ..RunnerConfig::default()
+        };
+        let message = config.clone().validate().unwrap_err().to_string();
+        assert!(message.contains("The Statistics oracle requires Parquet-backed tables"));
+
+        let csv_config = RunnerConfig {
+            dataset_format: DatasetFormat::Csv,
+            ..config.clone()
+        };
+        assert!(csv_config.validate().is_err());
+
+        let config = RunnerConfig {
+            dataset_format: DatasetFormat::Parquet,
+            ..config
+        };
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn rejects_predicate_literal_bias_outside_unit_interval() {
+        let config = RunnerConfig {
+            predicate_literal_bias: 1.5,
+            ..RunnerConfig::default()
+        };
+
+        let message = config.validate().unwrap_err().to_string();
+        assert!(message.contains("predicate_literal_bias"));
+    }
+
+    #[test]
+    fn rejects_adaptive_timeout_bounds_outside_timeout_seconds() {
+        let config = RunnerConfig {
+            adaptive_timeout: true,
+            adaptive_timeout_factor: 0.5,
+            adaptive_timeout_floor_ms: 5000,
+            ..RunnerConfig::default()
+        };
+
+        let message = config.validate().unwrap_err().to_string();
+        assert!(message.contains("adaptive_timeout_factor must be at least 1"));
+        assert!(message.contains("adaptive_timeout_floor_ms must not exceed timeout_seconds"));
+    }
+
+    #[test]
+    fn rejects_phase_timeouts_outside_timeout_seconds() {
+        let config = RunnerConfig {
+            plan_timeout_ms: Some(0),
+            execution_timeout_ms: Some(5000),
+            ..RunnerConfig::default()
+        };
+
+        let message = config.validate().unwrap_err().to_string();
+        assert!(message.contains("plan_timeout_ms must be at least 1"));
+        assert!(message.contains("execution_timeout_ms must not exceed timeout_seconds"));
+    }
+
+    #[test]
+    fn parses_coverage_targets_and_checks_oracle_weights() {
+        let config = RunnerConfig {
+            oracles: vec![ConfiguredOracle::NoCrash, ConfiguredOracle::Aggregate],
+            oracle_weights: vec![1.0, 3.0],
+            coverage_targets: BTreeMap::from([(QueryFeature::Aggregates, 0.1)]),
+            coverage_boosts: BTreeMap::from([(SwarmFeature::GroupBy, 0.5)]),
+            ..RunnerConfig::default()
+        };
+        let toml = config.to_toml().unwrap();
+        assert!(
+            toml.contains("[coverage_targets]\naggregates = 0.1"),
+            "{}",
+            toml
+        );
+        let parsed = RunnerConfig::from_toml_str(&toml).unwrap();
+        assert_eq!(parsed.oracle_weights, vec![1.0, 3.0]);
+        assert_eq!(parsed.coverage_targets, config.coverage_targets);
+        assert_eq!(parsed.coverage_boosts, config.coverage_boosts);
+        assert!(parsed.warnings().is_empty());
+
+        let config = RunnerConfig {
+            oracle_weights: vec![0.0, -1.0],
+            coverage_targets: BTreeMap::from([(QueryFeature::WindowFunctions, 0.0)]),
+            coverage_boosts: BTreeMap::from([(SwarmFeature::Joins, 1.5)]),
+            ..config
+        };
+        assert!(
+            config
+                .warnings()
+                .iter()
+                .any(|warning| warning.contains("window_functions is only reported"))
+        );
+        let message = config.validate().unwrap_err().to_string();
+        assert!(message.contains("oracle_weights must not be negative"));
+        assert!(message.contains("coverage target of window_functions must be above 0"));
+        assert!(message.contains("coverage boost of joins must be between 0 and 1"));
+
+        let config = RunnerConfig {
+            oracle_weights: vec![1.0],
+            ..RunnerConfig::default()
+        };
+        assert!(config.clone().validate().is_ok());
+        let config = RunnerConfig {
+            oracle_weights: vec![1.0, 1.0],
+            ..config
+        };
+        let message = config.validate().unwrap_err().to_string();
+        assert!(message.contains("got 2 weights for 1 oracles"));
+    }
+
+    #[test]
+    fn engine_diff_oracle_requires_an_engine() {
+        let config = RunnerConfig {
+            oracles: vec![ConfiguredOracle::EngineDiff],
+            ..RunnerConfig::default()
+        };
+        let message = config.clone().validate().unwrap_err().to_string();
+        assert!(message.contains("requires flight_sql_endpoint or reference_engines"));
+
+        let config = RunnerConfig::from_toml_str(
+            r#"
+seed = 42
+rounds = 1
+queries_per_round = 1
+timeout_seconds = 2
+log_path = "logs"
+display_logs = false
+enable_tui = false
+sample_interval_secs = 5
+max_column_count = 5
+max_row_count = 100
+max_expr_level = 3
+max_group_by_count = 3
+max_table_count = 3
+max_insert_per_table = 20
+oracles = ["EngineDiff"]
+reference_engines = ["DuckDb", "Sqlite"]
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.reference_engines,
+            vec![ReferenceEngine::DuckDb, ReferenceEngine::Sqlite]
+        );
+    }
+
+    #[test]
+    fn zero_rounds_are_allowed_with_a_time_limit() {
+        let config = RunnerConfig {
+            rounds: 0,
+            max_duration_secs: Some(60),
+            ..RunnerConfig::default()
+        };
+
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn warns_on_large_joins_with_a_tiny_timeout() {
+        let config = RunnerConfig {
+            max_table_count: 5,
+            max_row_count: 1000,
+            timeout_seconds: 1,
+            ..RunnerConfig::default()
+        };
+
+        let warnings = config.warnings();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("max_table_count = 5"));
+        assert!(RunnerConfig::default().warnings().is_empty());
+
+        let config = RunnerConfig {
+            join_row_budget: Some(1_000_000),
+            ..config
+        };
+        assert!(config.warnings().is_empty());
+    }
+
+    #[test]
+    fn warns_when_spill_fuzzing_has_no_effect() {
+        let config = RunnerConfig {
+            spill_fuzzing: true,
+            ..RunnerConfig::default()
+        };
+        assert_eq!(config.warnings().len(), 1);
+
+        let config = RunnerConfig {
+            spill_fuzzing: true,
+            oracles: vec![ConfiguredOracle::MemoryLimit],
+            ..RunnerConfig::default()
+        };
+        assert!(config.warnings().is_empty());
+    }
+
+    #[test]
+    fn effective_config_round_trips_through_toml() {
+        let config = RunnerConfig {
+            seed: 7,
+            max_findings: Some(3),
+            oracles: vec![ConfiguredOracle::NoCrash, ConfiguredOracle::TlpWhere],
+            ..RunnerConfig::default()
+        };
+
+        let parsed = RunnerConfig::from_toml_str(&config.to_toml().unwrap()).unwrap();
+        assert_eq!(parsed.seed, 7);
+        assert_eq!(parsed.max_findings, Some(3));
+        assert_eq!(parsed.oracles, config.oracles);
+    }
 }
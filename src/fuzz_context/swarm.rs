@@ -0,0 +1,226 @@
+//! Swarm testing: a random subset of the generator's features per round.
+//!
+//! Mixing all features uniformly into every query makes some bugs unlikely:
+//! a feature can suppress another (every query with a join also filters
+//! away the rows a buggy aggregation would need), or crowd it out of the
+//! expressions of bounded depth. With `swarm_testing`, every round enables
+//! each feature family with probability 1/2 and generates tables and queries
+//! with the enabled families only. Optional clauses an oracle requires (e.g.
+//! GROUP BY of the Aggregate oracle) are always generated.
+//!
+//! The families are picked from the seed and the round, so a round always
+//! gets the same ones. Findings record the families of their round.
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use strum::{EnumIter, IntoEnumIterator};
+
+use crate::common::FuzzerDataType;
+use crate::common::rng::rng_from_seed;
+use crate::query_generator::expr_def::BaseExpr;
+
+/// A family of generated features swarm testing turns on or off as a whole
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, EnumIter,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum SwarmFeature {
+    /// JOIN clauses, and more than one table in FROM
+    Joins,
+    Where,
+    /// GROUP BY and HAVING clauses
+    GroupBy,
+    /// `+`, `-`, `*`, `/` and `%`
+    Arithmetic,
+    /// `AND` and `OR`
+    Logical,
+    /// `=`, `<`, `IS DISTINCT FROM` and the other comparisons
+    Comparison,
+    /// `LIKE`, `ILIKE` and regular expression matches
+    PatternMatching,
+    /// Date and time functions such as `to_timestamp` and `now`
+    DateTimeFunctions,
+    /// Signed and unsigned 32 and 64 bit integer columns
+    IntegerTypes,
+    FloatTypes,
+    DecimalType,
+    BooleanType,
+    /// Date, time and timestamp columns
+    TemporalTypes,
+    IntervalType,
+    StringType,
+}
+
+impl SwarmFeature {
+    /// Name of the feature in logs and findings
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Joins => "joins",
+            Self::Where => "where",
+            Self::GroupBy => "group_by",
+            Self::Arithmetic => "arithmetic",
+            Self::Logical => "logical",
+            Self::Comparison => "comparison",
+            Self::PatternMatching => "pattern_matching",
+            Self::DateTimeFunctions => "date_time_functions",
+            Self::IntegerTypes => "integer_types",
+            Self::FloatTypes => "float_types",
+            Self::DecimalType => "decimal_type",
+            Self::BooleanType => "boolean_type",
+            Self::TemporalTypes => "temporal_types",
+            Self::IntervalType => "interval_type",
+            Self::StringType => "string_type",
+        }
+    }
+
+    /// The family of columns of `data_type`
+    pub fn for_type(data_type: &FuzzerDataType) -> Self {
+        match data_type {
+            FuzzerDataType::Int32
+            | FuzzerDataType::Int64
+            | FuzzerDataType::UInt32
+            | FuzzerDataType::UInt64 => Self::IntegerTypes,
+            FuzzerDataType::Float32 | FuzzerDataType::Float64 => Self::FloatTypes,
+            FuzzerDataType::Decimal => Self::DecimalType,
+            FuzzerDataType::Boolean => Self::BooleanType,
+            FuzzerDataType::Date32
+            | FuzzerDataType::Time64Nanosecond
+            | FuzzerDataType::Timestamp => Self::TemporalTypes,
+            FuzzerDataType::IntervalMonthDayNano => Self::IntervalType,
+            FuzzerDataType::String => Self::StringType,
+        }
+    }
+
+    /// The family of the operator or function `expr`
+    pub fn for_expr(expr: &BaseExpr) -> Self {
+        match expr {
+            BaseExpr::Add | BaseExpr::Sub | BaseExpr::Mul | BaseExpr::Div | BaseExpr::Mod => {
+                Self::Arithmetic
+            }
+            BaseExpr::And | BaseExpr::Or => Self::Logical,
+            BaseExpr::Like
+            | BaseExpr::ILike
+            | BaseExpr::NotLike
+            | BaseExpr::NotILike
+            | BaseExpr::RegexMatch
+            | BaseExpr::RegexIMatch
+            | BaseExpr::RegexNotMatch
+            | BaseExpr::RegexNotIMatch => Self::PatternMatching,
+            _ if expr.is_comparison() => Self::Comparison,
+            _ => Self::DateTimeFunctions,
+        }
+    }
+
+    fn is_type(self) -> bool {
+        matches!(
+            self,
+            Self::IntegerTypes
+                | Self::FloatTypes
+                | Self::DecimalType
+                | Self::BooleanType
+                | Self::TemporalTypes
+                | Self::IntervalType
+                | Self::StringType
+        )
+    }
+}
+
+/// Feature families enabled in a round of swarm testing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwarmFeatures {
+    enabled: BTreeSet<SwarmFeature>,
+}
+
+impl SwarmFeatures {
+    /// Enable every family with probability 1/2, and at least one family of
+    /// column types, so tables can still be generated
+    pub fn random(seed: u64) -> Self {
+        let mut rng = rng_from_seed(seed);
+        let mut enabled: BTreeSet<SwarmFeature> = SwarmFeature::iter()
+            .filter(|_| rng.random_bool(0.5))
+            .collect();
+        if !enabled.iter().any(|feature| feature.is_type()) {
+            let types: Vec<SwarmFeature> = SwarmFeature::iter()
+                .filter(|feature| feature.is_type())
+                .collect();
+            enabled.insert(types[rng.random_range(0..types.len())]);
+        }
+        Self { enabled }
+    }
+
+    pub fn allows(&self, feature: SwarmFeature) -> bool {
+        self.enabled.contains(&feature)
+    }
+
+    pub fn allows_type(&self, data_type: &FuzzerDataType) -> bool {
+        self.allows(SwarmFeature::for_type(data_type))
+    }
+
+    pub fn allows_expr(&self, expr: &BaseExpr) -> bool {
+        self.allows(SwarmFeature::for_expr(expr))
+    }
+
+    /// The enabled families, in declaration order
+    pub fn enabled(&self) -> Vec<SwarmFeature> {
+        self.enabled.iter().copied().collect()
+    }
+}
+
+impl fmt::Display for SwarmFeatures {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<&str> = self.enabled.iter().map(|feature| feature.name()).collect();
+        write!(f, "{}", names.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rounds_enable_some_families_and_at_least_one_type() {
+        let mut seen = BTreeSet::new();
+        for seed in 0..100 {
+            let features = SwarmFeatures::random(seed);
+            assert_eq!(features, SwarmFeatures::random(seed));
+            assert!(features.enabled().iter().any(|feature| feature.is_type()));
+            seen.insert(features.enabled());
+        }
+        // Rounds differ in their families
+        assert!(seen.len() > 90, "{}", seen.len());
+    }
+
+    #[test]
+    fn maps_types_and_operators_to_their_family() {
+        assert_eq!(
+            SwarmFeature::for_type(&FuzzerDataType::UInt64),
+            SwarmFeature::IntegerTypes
+        );
+        assert_eq!(
+            SwarmFeature::for_type(&FuzzerDataType::Time64Nanosecond),
+            SwarmFeature::TemporalTypes
+        );
+        assert_eq!(
+            SwarmFeature::for_expr(&BaseExpr::IsDistinctFrom),
+            SwarmFeature::Comparison
+        );
+        assert_eq!(
+            SwarmFeature::for_expr(&BaseExpr::RegexNotIMatch),
+            SwarmFeature::PatternMatching
+        );
+        assert_eq!(
+            SwarmFeature::for_expr(&BaseExpr::ToUnixtime),
+            SwarmFeature::DateTimeFunctions
+        );
+
+        let features = SwarmFeatures {
+            enabled: BTreeSet::from([SwarmFeature::Joins, SwarmFeature::StringType]),
+        };
+        assert!(features.allows_type(&FuzzerDataType::String));
+        assert!(!features.allows_expr(&BaseExpr::Mod));
+        assert_eq!(features.to_string(), "joins, string_type");
+    }
+}
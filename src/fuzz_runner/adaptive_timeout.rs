@@ -0,0 +1,99 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use super::percentile;
+
+/// Number of recent query latencies the adaptive timeout is derived from
+pub const LATENCY_WINDOW: usize = 500;
+
+/// Latencies needed before the adaptive timeout replaces `timeout_seconds`,
+/// the p99 of fewer queries is mostly noise
+pub const MIN_LATENCY_SAMPLES: usize = 50;
+
+/// Execution times of the last `LATENCY_WINDOW` queries that finished before
+/// their timeout.
+///
+/// With `adaptive_timeout` the query timeout follows the p99 of these
+/// latencies, so it tracks how expensive the generated queries currently are
+/// (e.g. after changing `max_table_count` or `max_expr_level`) instead of a
+/// fixed limit tuned for other settings.
+#[derive(Debug, Clone, Default)]
+pub struct LatencyWindow {
+    latencies: VecDeque<Duration>,
+}
+
+impl LatencyWindow {
+    pub fn record(&mut self, latency: Duration) {
+        if self.latencies.len() == LATENCY_WINDOW {
+            self.latencies.pop_front();
+        }
+        self.latencies.push_back(latency);
+    }
+
+    pub fn len(&self) -> usize {
+        self.latencies.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.latencies.is_empty()
+    }
+
+    /// `factor` times the p99 latency of the window, clamped to
+    /// `[floor, ceiling]`. `ceiling` until the window has
+    /// `MIN_LATENCY_SAMPLES` latencies.
+    pub fn timeout(&self, factor: f64, floor: Duration, ceiling: Duration) -> Duration {
+        if self.latencies.len() < MIN_LATENCY_SAMPLES {
+            return ceiling;
+        }
+
+        let mut latencies_ms: Vec<f64> = self
+            .latencies
+            .iter()
+            .map(|latency| latency.as_secs_f64() * 1000.0)
+            .collect();
+        latencies_ms.sort_by(|a, b| a.total_cmp(b));
+        let p99 = Duration::from_secs_f64(percentile(&latencies_ms, 99.0) * factor / 1000.0);
+        p99.clamp(floor.min(ceiling), ceiling)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FLOOR: Duration = Duration::from_millis(100);
+    const CEILING: Duration = Duration::from_secs(2);
+
+    fn assert_millis(timeout: Duration, expected_ms: f64) {
+        assert!((timeout.as_secs_f64() * 1000.0 - expected_ms).abs() < 1e-3);
+    }
+
+    #[test]
+    fn uses_the_ceiling_until_enough_latencies_are_recorded() {
+        let mut window = LatencyWindow::default();
+        for _ in 0..MIN_LATENCY_SAMPLES - 1 {
+            window.record(Duration::from_millis(10));
+        }
+        assert_eq!(window.timeout(5.0, FLOOR, CEILING), CEILING);
+
+        window.record(Duration::from_millis(10));
+        assert_eq!(window.timeout(5.0, FLOOR, CEILING), FLOOR);
+    }
+
+    #[test]
+    fn follows_the_p99_of_recent_latencies() {
+        let mut window = LatencyWindow::default();
+        for ms in 1..=100 {
+            window.record(Duration::from_millis(ms));
+        }
+        assert_millis(window.timeout(5.0, FLOOR, CEILING), 495.0);
+        assert_eq!(window.timeout(50.0, FLOOR, CEILING), CEILING);
+
+        // Old latencies leave the window
+        for _ in 0..LATENCY_WINDOW {
+            window.record(Duration::from_millis(40));
+        }
+        assert_eq!(window.len(), LATENCY_WINDOW);
+        assert_millis(window.timeout(5.0, FLOOR, CEILING), 200.0);
+    }
+}
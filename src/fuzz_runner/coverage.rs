@@ -0,0 +1,187 @@
+//! Coverage of query features, steered towards `coverage_targets`.
+//!
+//! Targets declare the share of queries that should contain a feature, e.g.
+//! 10% of queries with an aggregate. After every round, the optional clauses
+//! of the generator producing the features below their target (JOIN for
+//! joins, GROUP BY for aggregates) are generated more often, see
+//! [`SelectStatementBuilder`]. The final report lists the achieved share of
+//! every targeted feature, the stats export (see `stats_export`) the share of
+//! every feature.
+//!
+//! [`SelectStatementBuilder`]: crate::query_generator::stmt_select_def::SelectStatementBuilder
+
+use std::collections::BTreeMap;
+
+use super::query_shape::{QueryFeature, QueryShape};
+use crate::fuzz_context::swarm::SwarmFeature;
+
+/// The optional clause of the generator producing `feature`, `None` for
+/// features no clause can be steered towards
+pub fn steered_clause(feature: QueryFeature) -> Option<SwarmFeature> {
+    match feature {
+        QueryFeature::Joins | QueryFeature::CrossJoins => Some(SwarmFeature::Joins),
+        QueryFeature::Aggregates => Some(SwarmFeature::GroupBy),
+        _ => None,
+    }
+}
+
+/// Features of the successful queries of the embedded session, out of
+/// `queries`
+#[derive(Debug, Clone, Default)]
+pub struct FeatureCoverage {
+    queries: u64,
+    containing: BTreeMap<QueryFeature, u64>,
+}
+
+impl FeatureCoverage {
+    pub fn record(&mut self, shape: &QueryShape) {
+        self.queries += 1;
+        for feature in QueryFeature::ALL {
+            if shape.contains(feature) {
+                *self.containing.entry(feature).or_default() += 1;
+            }
+        }
+    }
+
+    /// Number of recorded queries
+    pub fn queries(&self) -> u64 {
+        self.queries
+    }
+
+    /// Share of all recorded queries containing `feature`, 0 without queries
+    pub fn achieved(&self, feature: QueryFeature) -> f64 {
+        if self.queries == 0 {
+            return 0.0;
+        }
+        self.containing.get(&feature).copied().unwrap_or_default() as f64 / self.queries as f64
+    }
+
+    /// Boosts of the optional clauses steered towards `targets`.
+    ///
+    /// A clause is boosted by how far below its target the feature it
+    /// produces is, relative to the target, the largest deficit of all its
+    /// features. A boost of 1 generates the clause whenever an oracle allows
+    /// it, see [`crate::fuzz_context::GlobalContext::steered_probability`].
+    /// Empty before any query was recorded.
+    pub fn steered_boosts(
+        &self,
+        targets: &BTreeMap<QueryFeature, f64>,
+    ) -> BTreeMap<SwarmFeature, f64> {
+        let mut boosts = BTreeMap::new();
+        if self.queries == 0 {
+            return boosts;
+        }
+
+        for (feature, target) in targets {
+            let Some(clause) = steered_clause(*feature) else {
+                continue;
+            };
+            let deficit = (target - self.achieved(*feature)) / target;
+            if deficit > 0.0 {
+                let boost: &mut f64 = boosts.entry(clause).or_default();
+                *boost = boost.max(deficit);
+            }
+        }
+        boosts
+    }
+
+    /// Final report section: achieved share of every feature in `targets`.
+    /// `None` without targets or before any query was recorded.
+    pub fn format_display(&self, targets: &BTreeMap<QueryFeature, f64>) -> Option<String> {
+        if targets.is_empty() || self.queries == 0 {
+            return None;
+        }
+
+        let mut report = format!("🎯 Feature Coverage ({} queries):", self.queries);
+        for (feature, target) in targets {
+            let achieved = self.achieved(*feature);
+            let status = if achieved >= *target {
+                "✓"
+            } else if achieved == 0.0 {
+                "✗ (no configured oracle generated it)"
+            } else {
+                "✗"
+            };
+            report.push_str(&format!(
+                "\n  • {}: {:.1}% (target {:.1}%) {}",
+                feature.name(),
+                achieved * 100.0,
+                target * 100.0,
+                status
+            ));
+        }
+        Some(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::prelude::SessionContext;
+
+    async fn shape_of(ctx: &SessionContext, sql: &str) -> QueryShape {
+        let plan = ctx.state().create_logical_plan(sql).await.unwrap();
+        QueryShape::from_plan(&plan)
+    }
+
+    #[tokio::test]
+    async fn boosts_clauses_generating_missing_features() {
+        let ctx = SessionContext::new();
+        ctx.sql("CREATE TABLE t1(a INT)").await.unwrap();
+        let plain = shape_of(&ctx, "SELECT a FROM t1").await;
+        let aggregate = shape_of(&ctx, "SELECT a, count(*) FROM t1 GROUP BY a").await;
+
+        let mut coverage = FeatureCoverage::default();
+        let targets = BTreeMap::from([
+            (QueryFeature::Aggregates, 0.4),
+            (QueryFeature::WindowFunctions, 0.1),
+        ]);
+        assert!(coverage.steered_boosts(&targets).is_empty());
+
+        for _ in 0..9 {
+            coverage.record(&plain);
+        }
+        coverage.record(&aggregate);
+        assert_eq!(coverage.achieved(QueryFeature::Aggregates), 0.1);
+
+        // A deficit of 75%, no clause generates window functions
+        let boosts = coverage.steered_boosts(&targets);
+        assert_eq!(boosts.len(), 1);
+        assert!(
+            (boosts[&SwarmFeature::GroupBy] - 0.75).abs() < 1e-9,
+            "{:?}",
+            boosts
+        );
+
+        // Met targets leave the clauses alone
+        let targets = BTreeMap::from([(QueryFeature::Aggregates, 0.1)]);
+        assert!(coverage.steered_boosts(&targets).is_empty());
+    }
+
+    #[tokio::test]
+    async fn reports_achieved_shares_against_targets() {
+        let ctx = SessionContext::new();
+        let mut coverage = FeatureCoverage::default();
+        let targets = BTreeMap::from([
+            (QueryFeature::PlanNodes, 0.5),
+            (QueryFeature::WindowFunctions, 0.05),
+        ]);
+        assert!(coverage.format_display(&targets).is_none());
+
+        coverage.record(&shape_of(&ctx, "SELECT 1").await);
+        assert!(coverage.format_display(&BTreeMap::new()).is_none());
+
+        let report = coverage.format_display(&targets).unwrap();
+        assert!(report.contains("(1 queries)"), "{}", report);
+        assert!(
+            report.contains("plan_nodes: 100.0% (target 50.0%) ✓"),
+            "{}",
+            report
+        );
+        assert!(
+            report.contains("window_functions: 0.0% (target 5.0%) ✗ (no configured oracle"),
+            "{}",
+            report
+        );
+    }
+}
@@ -0,0 +1,559 @@
+use std::collections::BTreeSet;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use datafusion::arrow::datatypes::Schema;
+use datafusion::arrow::ipc::writer::FileWriter;
+use datafusion::arrow::record_batch::RecordBatch;
+use serde::{Deserialize, Serialize};
+
+use crate::common::{Result, fuzzer_err};
+use crate::datasource_generator::file_dataset::{Compression, DatasetFormat};
+use crate::fuzz_context::swarm::SwarmFeature;
+use crate::fuzz_context::{GlobalContext, RunnerConfig};
+use crate::fuzz_runner::phase_times::QueryTiming;
+use crate::fuzz_runner::query_id::QueryId;
+use crate::oracle::QueryExecutionResult;
+use crate::triage::FailureCategory;
+
+/// A single issue found by the fuzzer: a non-whitelisted error or an oracle
+/// consistency violation.
+///
+/// Findings are appended as JSON lines to `findings.jsonl` in the log directory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Finding {
+    /// Stable ID of the oracle test, see [`QueryId`]
+    pub query_id: QueryId,
+    /// 1-based round number
+    pub round: u32,
+    /// 1-based index of the oracle test within the round
+    pub query_index: u32,
+    pub query_seed: u64,
+    pub oracle: String,
+    /// DataFusion version under test, with the configured upstream git SHA if any
+    #[serde(default)]
+    pub datafusion_version: String,
+    pub category: FailureCategory,
+    /// Error message or oracle failure reason
+    pub message: String,
+    /// The queries involved, in execution order
+    pub queries: Vec<String>,
+    /// Oracle error report, if available
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub report: Option<String>,
+    /// Result sets of the queries as Arrow IPC files, see [`dump_result_sets`]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub result_files: Vec<PathBuf>,
+    /// Whether the queries fail the same way in `datafusion-cli`, see
+    /// `datafusion_cli`. `None` if not checked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub datafusion_cli_reproduced: Option<bool>,
+    /// Effective configuration of the run as TOML, enough to reproduce it
+    #[serde(default)]
+    pub config: String,
+    /// Files of the file-backed tables when the finding was recorded
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub table_files: Vec<TableFiles>,
+    /// Planning and execution time of the query, for findings of a single
+    /// query timed in both phases
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timing: Option<QueryTiming>,
+    /// Feature families enabled in the finding's round, with `swarm_testing`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub swarm_features: Option<Vec<SwarmFeature>>,
+    /// Upstream issue of the finding's fingerprint, see `known_issues`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub known_issue: Option<String>,
+}
+
+impl Finding {
+    /// Category, oracle and first message line of the finding, with numbers
+    /// masked so the same bug hit through other tables, columns or values has
+    /// the same fingerprint
+    pub fn fingerprint(&self) -> String {
+        let message = self.message.lines().next().unwrap_or_default();
+        let mut masked = String::with_capacity(message.len());
+        let mut in_number = false;
+        for c in message.chars() {
+            if c.is_ascii_digit() {
+                if !in_number {
+                    masked.push('N');
+                }
+                in_number = true;
+            } else {
+                masked.push(c);
+                in_number = false;
+            }
+        }
+        format!("{} {}: {}", self.category.as_str(), self.oracle, masked)
+    }
+}
+
+/// Format and compression codec of a file-backed table, so reader bugs
+/// specific to a codec can be attributed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableFiles {
+    pub table: String,
+    pub format: DatasetFormat,
+    pub compression: Compression,
+    pub dir: PathBuf,
+}
+
+/// The files of every registered file-backed table, ordered by table name
+pub fn table_files(ctx: &GlobalContext) -> Vec<TableFiles> {
+    ctx.runtime_context
+        .tables()
+        .values()
+        .filter_map(|table| {
+            let files = table.files.as_ref()?;
+            Some(TableFiles {
+                table: table.name.clone(),
+                format: files.format,
+                compression: files.compression,
+                dir: files.dir.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Read all findings from a `findings.jsonl` file
+pub fn load_findings(path: &Path) -> Result<Vec<Finding>> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        fuzzer_err(&format!(
+            "Failed to read findings file '{}': {}",
+            path.display(),
+            e
+        ))
+    })?;
+
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(line_index, line)| {
+            serde_json::from_str(line).map_err(|e| {
+                fuzzer_err(&format!(
+                    "Invalid finding at {}:{}: {}",
+                    path.display(),
+                    line_index + 1,
+                    e
+                ))
+            })
+        })
+        .collect()
+}
+
+/// Look up the finding of an oracle test in a `findings.jsonl` file
+pub fn find_finding(path: &Path, query_id: QueryId) -> Result<Finding> {
+    load_findings(path)?
+        .into_iter()
+        .find(|finding| finding.query_id == query_id)
+        .ok_or_else(|| {
+            fuzzer_err(&format!(
+                "No finding with query ID {} in '{}'",
+                query_id,
+                path.display()
+            ))
+        })
+}
+
+/// Write the result set of every successful query of an oracle test to
+/// `results/<query_id>/statement-<n>.arrow` in the log directory, so the exact
+/// mismatching data can be loaded into other tools.
+///
+/// Returns the written files. A query without result batches is written with
+/// an empty schema.
+pub fn dump_result_sets(
+    log_dir: &Path,
+    query_id: QueryId,
+    results: &[QueryExecutionResult],
+) -> Result<Vec<PathBuf>> {
+    let results_dir = log_dir.join("results").join(query_id.to_string());
+    std::fs::create_dir_all(&results_dir)?;
+
+    let mut result_files = Vec::new();
+    for (statement_index, result) in results.iter().enumerate() {
+        let Ok(batches) = &result.result else {
+            continue;
+        };
+
+        let path = results_dir.join(format!("statement-{}.arrow", statement_index + 1));
+        let schema = batches
+            .first()
+            .map(|batch| batch.schema())
+            .unwrap_or_else(|| std::sync::Arc::new(Schema::empty()));
+        write_ipc_file(&path, &schema, batches)?;
+
+        result_files.push(path);
+    }
+
+    Ok(result_files)
+}
+
+/// Write batches to an Arrow IPC file
+pub(crate) fn write_ipc_file(path: &Path, schema: &Schema, batches: &[RecordBatch]) -> Result<()> {
+    let write_err = |e| {
+        fuzzer_err(&format!(
+            "Failed to write Arrow IPC file '{}': {}",
+            path.display(),
+            e
+        ))
+    };
+
+    let mut writer = FileWriter::try_new(File::create(path)?, schema).map_err(write_err)?;
+    for batch in batches {
+        writer.write(batch).map_err(write_err)?;
+    }
+    writer.finish().map_err(write_err)
+}
+
+/// Records findings of a run and decides whether the run should stop early.
+#[derive(Debug)]
+pub struct FindingsRecorder {
+    findings_path: Option<PathBuf>,
+    fatal_categories: Vec<FailureCategory>,
+    stop_on_first_finding: bool,
+    max_findings: Option<u64>,
+    /// Fingerprints of the findings in `baseline`, `None` without a baseline
+    baseline: Option<BTreeSet<String>>,
+    state: Mutex<RecorderState>,
+}
+
+#[derive(Debug, Default)]
+struct RecorderState {
+    findings_count: u64,
+    /// Findings without a known upstream issue and not in the baseline
+    new_findings_count: u64,
+    stop_reason: Option<String>,
+}
+
+impl FindingsRecorder {
+    pub fn new(config: &RunnerConfig) -> Self {
+        // The baseline was already validated while loading the config
+        let baseline = config.baseline.as_ref().map(|path| {
+            load_findings(path)
+                .map(|findings| findings.iter().map(Finding::fingerprint).collect())
+                .unwrap_or_else(|e| {
+                    eprintln!("Warning: {}, all findings are new", e);
+                    BTreeSet::new()
+                })
+        });
+
+        Self {
+            findings_path: config
+                .log_path
+                .as_ref()
+                .map(|log_dir| log_dir.join("findings.jsonl")),
+            fatal_categories: config.fatal_categories.clone(),
+            stop_on_first_finding: config.stop_on_first_finding,
+            max_findings: config.max_findings,
+            baseline,
+            state: Mutex::new(RecorderState::default()),
+        }
+    }
+
+    /// Whether a finding with the fingerprint of `finding` is in the baseline
+    pub fn in_baseline(&self, finding: &Finding) -> bool {
+        self.baseline
+            .as_ref()
+            .is_some_and(|baseline| baseline.contains(&finding.fingerprint()))
+    }
+
+    /// Command replaying the finding of `query_id` from the findings file,
+    /// `None` when findings are not written (no `log_path`)
+    pub fn replay_command(&self, query_id: QueryId) -> Option<String> {
+        let path = self.findings_path.as_ref()?;
+        Some(format!(
+            "datafusion-fuzzer replay --findings {} --query-id {}",
+            path.display(),
+            query_id
+        ))
+    }
+
+    /// Persist a finding, and request the run to stop if its category is fatal
+    /// or a findings threshold is reached. Findings of known upstream issues
+    /// or in the baseline never stop the run.
+    pub fn record(&self, finding: &Finding) -> Result<()> {
+        let is_new = finding.known_issue.is_none() && !self.in_baseline(finding);
+        let new_findings_count = {
+            let mut state = self.state.lock().unwrap();
+            state.findings_count += 1;
+            if is_new {
+                state.new_findings_count += 1;
+            }
+            state.new_findings_count
+        };
+
+        if is_new {
+            self.check_stop(finding, new_findings_count);
+        }
+
+        let Some(path) = &self.findings_path else {
+            return Ok(());
+        };
+
+        let line = serde_json::to_string(finding)
+            .map_err(|e| fuzzer_err(&format!("Failed to serialize finding: {}", e)))?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| {
+                fuzzer_err(&format!(
+                    "Failed to open findings file '{}': {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+        writeln!(file, "{}", line)?;
+
+        Ok(())
+    }
+
+    /// Request the run to stop if the new `finding` is fatal or the
+    /// `new_findings_count`-th new finding reaches a threshold
+    fn check_stop(&self, finding: &Finding, new_findings_count: u64) {
+        if self.fatal_categories.contains(&finding.category) {
+            self.request_stop(format!(
+                "fatal {} finding in round {} query {}",
+                finding.category, finding.round, finding.query_index
+            ));
+        } else if self.stop_on_first_finding {
+            self.request_stop(format!(
+                "stop_on_first_finding: {} finding in round {} query {}",
+                finding.category, finding.round, finding.query_index
+            ));
+        } else if let Some(max_findings) = self.max_findings
+            && new_findings_count >= max_findings
+        {
+            self.request_stop(format!("max_findings ({}) reached", max_findings));
+        }
+    }
+
+    /// Ask the runner to stop after the current oracle test; the first reason wins
+    pub fn request_stop(&self, reason: String) {
+        let mut state = self.state.lock().unwrap();
+        if state.stop_reason.is_none() {
+            state.stop_reason = Some(reason);
+        }
+    }
+
+    /// Why the run should stop early, `None` to keep going
+    pub fn stop_reason(&self) -> Option<String> {
+        self.state.lock().unwrap().stop_reason.clone()
+    }
+
+    /// Number of findings recorded so far
+    pub fn findings_count(&self) -> u64 {
+        self.state.lock().unwrap().findings_count
+    }
+
+    /// Number of findings recorded so far without a known upstream issue and
+    /// not in the baseline
+    pub fn new_findings_count(&self) -> u64 {
+        self.state.lock().unwrap().new_findings_count
+    }
+
+    /// Whether the run has a baseline, see `baseline`
+    pub fn has_baseline(&self) -> bool {
+        self.baseline.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_finding(category: FailureCategory) -> Finding {
+        Finding {
+            query_id: QueryId::new(42, 0, 1),
+            round: 1,
+            query_index: 2,
+            query_seed: 42,
+            oracle: "NoCrashOracle".to_string(),
+            datafusion_version: "52.3.0".to_string(),
+            category,
+            message: "boom".to_string(),
+            queries: vec!["SELECT 1".to_string()],
+            report: None,
+            result_files: Vec::new(),
+            datafusion_cli_reproduced: None,
+            config: String::new(),
+            table_files: Vec::new(),
+            timing: None,
+            swarm_features: None,
+            known_issue: None,
+        }
+    }
+
+    #[test]
+    fn only_fatal_categories_stop_the_run() {
+        let config = RunnerConfig {
+            log_path: None,
+            fatal_categories: vec![FailureCategory::Panic],
+            ..RunnerConfig::default()
+        };
+        let recorder = FindingsRecorder::new(&config);
+        assert_eq!(recorder.replay_command(QueryId::new(42, 0, 1)), None);
+
+        recorder
+            .record(&make_finding(FailureCategory::PlannerError))
+            .unwrap();
+        assert_eq!(recorder.stop_reason(), None);
+
+        recorder
+            .record(&make_finding(FailureCategory::Panic))
+            .unwrap();
+        assert_eq!(
+            recorder.stop_reason().as_deref(),
+            Some("fatal Panic finding in round 1 query 2")
+        );
+    }
+
+    #[test]
+    fn stops_on_first_finding_or_max_findings() {
+        let first = FindingsRecorder::new(&RunnerConfig {
+            log_path: None,
+            stop_on_first_finding: true,
+            ..RunnerConfig::default()
+        });
+        first.record(&make_finding(FailureCategory::Other)).unwrap();
+        assert!(first.stop_reason().is_some());
+
+        let max = FindingsRecorder::new(&RunnerConfig {
+            log_path: None,
+            max_findings: Some(2),
+            ..RunnerConfig::default()
+        });
+        max.record(&make_finding(FailureCategory::Other)).unwrap();
+        assert_eq!(max.stop_reason(), None);
+        max.record(&make_finding(FailureCategory::Other)).unwrap();
+        assert_eq!(
+            max.stop_reason().as_deref(),
+            Some("max_findings (2) reached")
+        );
+        assert_eq!(max.findings_count(), 2);
+
+        // Findings of known issues are recorded, but don't stop the run
+        let known = FindingsRecorder::new(&RunnerConfig {
+            log_path: None,
+            stop_on_first_finding: true,
+            fatal_categories: vec![FailureCategory::Panic],
+            ..RunnerConfig::default()
+        });
+        let mut finding = make_finding(FailureCategory::Panic);
+        finding.known_issue = Some("https://github.com/apache/datafusion/issues/1".to_string());
+        known.record(&finding).unwrap();
+        assert_eq!(known.stop_reason(), None);
+        assert_eq!(known.findings_count(), 1);
+    }
+
+    #[test]
+    fn only_findings_missing_from_the_baseline_stop_the_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let baseline_path = dir.path().join("baseline.jsonl");
+        let mut baseline_finding = make_finding(FailureCategory::Panic);
+        baseline_finding.message = "index out of bounds: 12".to_string();
+        std::fs::write(
+            &baseline_path,
+            serde_json::to_string(&baseline_finding).unwrap() + "\n",
+        )
+        .unwrap();
+
+        let recorder = FindingsRecorder::new(&RunnerConfig {
+            log_path: None,
+            stop_on_first_finding: true,
+            baseline: Some(baseline_path),
+            ..RunnerConfig::default()
+        });
+        assert!(recorder.has_baseline());
+
+        // The same panic with another index is in the baseline
+        let mut finding = make_finding(FailureCategory::Panic);
+        finding.message = "index out of bounds: 7".to_string();
+        assert!(recorder.in_baseline(&finding));
+        recorder.record(&finding).unwrap();
+        assert_eq!(recorder.stop_reason(), None);
+        assert_eq!(recorder.new_findings_count(), 0);
+
+        recorder
+            .record(&make_finding(FailureCategory::Panic))
+            .unwrap();
+        assert!(recorder.stop_reason().is_some());
+        assert_eq!(recorder.new_findings_count(), 1);
+        assert_eq!(recorder.findings_count(), 2);
+    }
+
+    #[test]
+    fn finding_round_trips_through_json() {
+        let finding = make_finding(FailureCategory::WrongResult);
+        let line = serde_json::to_string(&finding).unwrap();
+        assert!(line.contains(r#""category":"WrongResult""#));
+
+        let parsed: Finding = serde_json::from_str(&line).unwrap();
+        assert_eq!(parsed.category, FailureCategory::WrongResult);
+        assert_eq!(parsed.queries, finding.queries);
+    }
+
+    #[test]
+    fn dumps_result_sets_as_arrow_ipc() {
+        use crate::oracle::test_helpers;
+        use datafusion::arrow::ipc::reader::FileReader;
+
+        let dir = tempfile::tempdir().unwrap();
+        let log_dir = dir.path();
+        let results = vec![
+            test_helpers::make_success_result("q1", "c", vec![1, 2, 3]),
+            test_helpers::make_error_result("q2"),
+            test_helpers::make_success_result("q3", "c", vec![1]),
+        ];
+
+        let files = dump_result_sets(log_dir, QueryId::new(42, 0, 1), &results).unwrap();
+        assert_eq!(
+            files,
+            vec![
+                log_dir.join("results/42-r1-q2/statement-1.arrow"),
+                log_dir.join("results/42-r1-q2/statement-3.arrow"),
+            ]
+        );
+
+        let reader = FileReader::try_new(File::open(&files[0]).unwrap(), None).unwrap();
+        let rows: usize = reader.map(|batch| batch.unwrap().num_rows()).sum();
+        assert_eq!(rows, 3);
+    }
+
+    #[test]
+    fn loads_recorded_findings() {
+        let dir = tempfile::tempdir().unwrap();
+        let log_dir = dir.path();
+        let recorder = FindingsRecorder::new(&RunnerConfig {
+            log_path: Some(log_dir.to_path_buf()),
+            ..RunnerConfig::default()
+        });
+        recorder
+            .record(&make_finding(FailureCategory::Other))
+            .unwrap();
+        recorder
+            .record(&make_finding(FailureCategory::Panic))
+            .unwrap();
+
+        let path = log_dir.join("findings.jsonl");
+        let findings = load_findings(&path).unwrap();
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[1].category, FailureCategory::Panic);
+
+        let found = find_finding(&path, QueryId::new(42, 0, 1)).unwrap();
+        assert_eq!(found.category, FailureCategory::Other);
+        assert!(find_finding(&path, QueryId::new(42, 5, 5)).is_err());
+        assert_eq!(
+            recorder.replay_command(QueryId::new(42, 0, 1)).unwrap(),
+            format!(
+                "datafusion-fuzzer replay --findings {} --query-id {}",
+                path.display(),
+                QueryId::new(42, 0, 1)
+            )
+        );
+    }
+}
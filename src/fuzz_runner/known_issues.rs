@@ -0,0 +1,215 @@
+//! Findings of known upstream issues.
+//!
+//! `known_issues` points to a TOML file mapping finding fingerprints (see
+//! [`Finding::fingerprint`]) to upstream GitHub issues:
+//!
+//! ```toml
+//! [[issues]]
+//! fingerprint = "InternalError NoCrashOracle: Internal error: Failed to coerce types IntN and UtfN"
+//! issue = "https://github.com/apache/datafusion/issues/12345"
+//! ```
+//!
+//! Matching findings are still recorded, with their issue, but reported as
+//! known instead of new and never stop the run, so triaging a finding doesn't
+//! require a whitelist pattern that would also hide it. The final report and
+//! `summarize` count the findings of every issue.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::findings::Finding;
+use crate::common::{Result, fuzzer_err};
+
+/// An entry of the known issues file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct KnownIssue {
+    /// Fingerprint of the findings, as logged when they are recorded
+    pub fingerprint: String,
+    /// URL of the upstream issue
+    pub issue: String,
+}
+
+/// Layout of the file referenced by `known_issues`
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct KnownIssuesFile {
+    #[serde(default)]
+    issues: Vec<KnownIssue>,
+}
+
+/// Upstream issues by finding fingerprint
+#[derive(Debug, Clone, Default)]
+pub struct KnownIssues {
+    by_fingerprint: BTreeMap<String, String>,
+}
+
+impl KnownIssues {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path).map_err(|e| {
+            fuzzer_err(&format!(
+                "Failed to read known issues file '{}': {}",
+                path.display(),
+                e
+            ))
+        })?;
+        Self::from_toml_str(&content).map_err(|e| {
+            fuzzer_err(&format!(
+                "Invalid known issues file '{}': {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+
+    pub fn from_toml_str(content: &str) -> Result<Self> {
+        let file: KnownIssuesFile =
+            toml::from_str(content).map_err(|e| fuzzer_err(&e.to_string()))?;
+
+        let mut by_fingerprint = BTreeMap::new();
+        for KnownIssue { fingerprint, issue } in file.issues {
+            if let Some(other) = by_fingerprint.get(&fingerprint)
+                && *other != issue
+            {
+                return Err(fuzzer_err(&format!(
+                    "fingerprint '{}' is mapped to both {} and {}",
+                    fingerprint, other, issue
+                )));
+            }
+            by_fingerprint.insert(fingerprint, issue);
+        }
+        Ok(Self { by_fingerprint })
+    }
+
+    /// Upstream issue of `finding`, `None` for a new finding
+    pub fn issue_of(&self, finding: &Finding) -> Option<&str> {
+        self.by_fingerprint
+            .get(&finding.fingerprint())
+            .map(String::as_str)
+    }
+}
+
+/// Short name of an upstream issue: `#NNNN` for GitHub issues and pull
+/// requests, the URL otherwise
+pub fn issue_label(issue: &str) -> String {
+    let number = issue.trim_end_matches('/').rsplit('/').next();
+    match number {
+        Some(number)
+            if (issue.contains("/issues/") || issue.contains("/pull/"))
+                && !number.is_empty()
+                && number.chars().all(|c| c.is_ascii_digit()) =>
+        {
+            format!("#{}", number)
+        }
+        _ => issue.to_string(),
+    }
+}
+
+/// Findings of every known issue in a run
+#[derive(Debug, Clone, Default)]
+pub struct KnownIssueStats {
+    hits: BTreeMap<String, u64>,
+}
+
+impl KnownIssueStats {
+    pub fn record(&mut self, issue: &str) {
+        *self.hits.entry(issue.to_string()).or_default() += 1;
+    }
+
+    /// Final report section: findings of every issue hit, most hits first.
+    /// `None` if no finding had a known issue.
+    pub fn format_display(&self) -> Option<String> {
+        if self.hits.is_empty() {
+            return None;
+        }
+
+        let mut hits: Vec<(&String, &u64)> = self.hits.iter().collect();
+        hits.sort_by(|a, b| b.1.cmp(a.1));
+        let mut report = format!(
+            "📌 Known Issues ({} findings):",
+            self.hits.values().sum::<u64>()
+        );
+        for (issue, count) in hits {
+            report.push_str(&format!(
+                "\n  • {}: {} findings ({})",
+                issue_label(issue),
+                count,
+                issue
+            ));
+        }
+        Some(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuzz_runner::query_id::QueryId;
+    use crate::triage::FailureCategory;
+
+    #[test]
+    fn matches_findings_by_fingerprint() {
+        let finding = |message: &str| Finding {
+            query_id: QueryId::new(42, 0, 0),
+            round: 1,
+            query_index: 1,
+            query_seed: 42,
+            oracle: "NoCrashOracle".to_string(),
+            datafusion_version: "52.3.0".to_string(),
+            category: FailureCategory::InternalError,
+            message: message.to_string(),
+            queries: vec!["SELECT 1".to_string()],
+            report: None,
+            result_files: Vec::new(),
+            datafusion_cli_reproduced: None,
+            config: String::new(),
+            table_files: Vec::new(),
+            timing: None,
+            swarm_features: None,
+            known_issue: None,
+        };
+        let known_issues = KnownIssues::from_toml_str(
+            r#"
+            [[issues]]
+            fingerprint = "InternalError NoCrashOracle: Internal error: Failed to coerce types IntN and UtfN"
+            issue = "https://github.com/apache/datafusion/issues/12345"
+            "#,
+        )
+        .unwrap();
+
+        // Numbers in the message don't change the fingerprint
+        let known = finding("Internal error: Failed to coerce types Int64 and Utf8\nat t1.c0");
+        assert_eq!(
+            known_issues.issue_of(&known),
+            Some("https://github.com/apache/datafusion/issues/12345")
+        );
+        assert_eq!(
+            known_issues.issue_of(&finding("Internal error: Failed to coerce types")),
+            None
+        );
+
+        assert_eq!(
+            issue_label("https://github.com/apache/datafusion/issues/12345"),
+            "#12345"
+        );
+        assert_eq!(
+            issue_label("https://example.com/bugs/1"),
+            "https://example.com/bugs/1"
+        );
+
+        let conflicting = KnownIssues::from_toml_str(
+            r#"
+            [[issues]]
+            fingerprint = "Panic NoCrashOracle: boom"
+            issue = "https://github.com/apache/datafusion/issues/1"
+            [[issues]]
+            fingerprint = "Panic NoCrashOracle: boom"
+            issue = "https://github.com/apache/datafusion/issues/2"
+            "#,
+        );
+        assert!(conflicting.is_err());
+    }
+}
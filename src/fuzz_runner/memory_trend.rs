@@ -0,0 +1,156 @@
+//! Process memory across rounds, see `memory_leak_rounds`.
+//!
+//! Every round drops its tables when the DataFusion context is reset, so the
+//! memory of the process should level off after the first rounds. Memory that
+//! keeps growing round after round, with the same configuration and only
+//! other seeds, points at a leak in the engine (or the fuzzer).
+
+use std::time::Duration;
+
+/// Smallest growth over the checked rounds that is reported as a leak, so
+/// allocator noise and the fuzzer's own bookkeeping don't count
+const MIN_LEAK_GROWTH_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Resident set size of the process, `None` where it can't be read (only
+/// Linux is supported)
+pub fn process_rss_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kilobytes: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kilobytes * 1024)
+}
+
+/// Memory of the process at the end of a round
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoundMemory {
+    /// 1-based round number
+    pub round: u32,
+    pub rss_bytes: u64,
+    /// Time since the start of the run
+    pub elapsed: Duration,
+}
+
+/// Memory at the end of every round, for leak detection and the final report
+#[derive(Debug, Clone, Default)]
+pub struct MemoryTrend {
+    samples: Vec<RoundMemory>,
+    /// Whether a leak was already reported, it is only reported once a run
+    leak_reported: bool,
+}
+
+impl MemoryTrend {
+    pub fn record(&mut self, sample: RoundMemory) {
+        self.samples.push(sample);
+    }
+
+    /// The recorded rounds, in order
+    pub fn samples(&self) -> &[RoundMemory] {
+        &self.samples
+    }
+
+    /// Description of a leak if memory grew in each of the last `rounds`
+    /// rounds, by at least `MIN_LEAK_GROWTH_BYTES` in total. Only the first
+    /// leak of a run is returned.
+    pub fn detect_leak(&mut self, rounds: usize) -> Option<String> {
+        if self.leak_reported || rounds == 0 || self.samples.len() <= rounds {
+            return None;
+        }
+
+        let window = &self.samples[self.samples.len() - rounds - 1..];
+        let growing = window
+            .windows(2)
+            .all(|pair| pair[1].rss_bytes > pair[0].rss_bytes);
+        let (first, last) = (window[0], window[rounds]);
+        let growth = last.rss_bytes - first.rss_bytes;
+        if !growing || growth < MIN_LEAK_GROWTH_BYTES {
+            return None;
+        }
+
+        self.leak_reported = true;
+        Some(format!(
+            "Process memory grew in each of {} rounds, from {} after round {} to {} after round {}",
+            rounds,
+            format_mib(first.rss_bytes),
+            first.round,
+            format_mib(last.rss_bytes),
+            last.round
+        ))
+    }
+
+    /// Final report section: the memory after every round. `None` before any
+    /// round was recorded.
+    pub fn format_display(&self) -> Option<String> {
+        if self.samples.is_empty() {
+            return None;
+        }
+
+        let mut report = String::from("💾 Process Memory by Round:");
+        let mut previous: Option<u64> = None;
+        for sample in &self.samples {
+            let change = match previous {
+                Some(previous) => format!(
+                    " ({:+.2} MiB)",
+                    (sample.rss_bytes as f64 - previous as f64) / (1024.0 * 1024.0)
+                ),
+                None => String::new(),
+            };
+            report.push_str(&format!(
+                "\n  • Round {}: {}{} at {:.1}s",
+                sample.round,
+                format_mib(sample.rss_bytes),
+                change,
+                sample.elapsed.as_secs_f64()
+            ));
+            previous = Some(sample.rss_bytes);
+        }
+        Some(report)
+    }
+}
+
+fn format_mib(bytes: u64) -> String {
+    format!("{:.2} MiB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trend(rss_mib: &[u64]) -> MemoryTrend {
+        let mut trend = MemoryTrend::default();
+        for (i, mib) in rss_mib.iter().enumerate() {
+            trend.record(RoundMemory {
+                round: i as u32 + 1,
+                rss_bytes: mib * 1024 * 1024,
+                elapsed: Duration::from_secs(i as u64),
+            });
+        }
+        trend
+    }
+
+    #[test]
+    fn reports_steady_growth_once() {
+        let mut growing = trend(&[100, 90, 100, 110, 120]);
+        assert!(growing.detect_leak(4).is_none());
+        let leak = growing.detect_leak(3).unwrap();
+        assert!(leak.contains("from 90.00 MiB after round 2 to 120.00 MiB after round 5"));
+        assert!(growing.detect_leak(3).is_none());
+
+        let report = growing.format_display().unwrap();
+        assert!(report.contains("Round 1: 100.00 MiB at 0.0s"));
+        assert!(report.contains("Round 2: 90.00 MiB (-10.00 MiB)"));
+    }
+
+    #[test]
+    fn ignores_flat_or_small_growth() {
+        assert!(trend(&[100, 110, 110, 120]).detect_leak(3).is_none());
+        assert!(trend(&[100, 101, 102, 103]).detect_leak(3).is_none());
+        assert!(MemoryTrend::default().format_display().is_none());
+    }
+
+    #[test]
+    fn reads_the_process_memory() {
+        if cfg!(target_os = "linux") {
+            assert!(process_rss_bytes().unwrap() > 0);
+        }
+    }
+}
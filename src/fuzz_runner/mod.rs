@@ -1,11 +1,44 @@
+pub mod adaptive_timeout;
+pub mod coverage;
+pub mod findings;
+pub mod known_issues;
+pub mod memory_trend;
+pub mod phase_times;
+pub mod plan_metrics;
+pub mod quarantine;
+pub mod query_id;
+pub mod query_shape;
+pub mod resource_usage;
+pub mod seed_pool;
+pub mod stats_export;
+
+use std::collections::BTreeMap;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
-/// A query execution record containing both the query text and its execution time
+use adaptive_timeout::LatencyWindow;
+use coverage::FeatureCoverage;
+use known_issues::KnownIssueStats;
+use memory_trend::{MemoryTrend, RoundMemory};
+use phase_times::{PhaseTimeStats, QueryTiming};
+use plan_metrics::{PlanComplexityStats, PlanMetrics};
+use quarantine::QuarantineStats;
+use query_shape::QueryShape;
+use resource_usage::{ResourceUsage, ResourceUsageStats};
+
+use crate::common::LogicalColumn;
+
+/// Number of slowest queries kept for the stats export
+const SLOWEST_QUERIES: usize = 5;
+
+/// A query execution record containing the query text, its ID, its execution
+/// time and, with `measure_resources`, the resources it used
 #[derive(Debug, Clone)]
 struct QueryExecutionRecord {
     query: String,
+    query_id: String,
     execution_time: Duration,
+    resource_usage: Option<ResourceUsage>,
 }
 
 /// Query runtime statistics for the 5 key metrics
@@ -17,30 +50,44 @@ pub struct QueryRuntimeStats {
     pub p90_ms: f64,
     pub p99_ms: f64,
     pub slowest_query: String,
+    pub slowest_query_id: String,
 }
 
 impl QueryRuntimeStats {
     /// Create runtime statistics from a collection of execution records
     pub fn from_records(records: &[QueryExecutionRecord]) -> Option<Self> {
-        if records.is_empty() {
+        let slowest = records.iter().max_by_key(|record| record.execution_time)?;
+        let execution_times: Vec<Duration> =
+            records.iter().map(|record| record.execution_time).collect();
+
+        Self::from_execution_times(&execution_times, slowest)
+    }
+
+    /// Create runtime statistics from the execution times of all queries and
+    /// the record of the slowest one
+    fn from_execution_times(
+        execution_times: &[Duration],
+        slowest: &QueryExecutionRecord,
+    ) -> Option<Self> {
+        if execution_times.is_empty() {
             return None;
         }
 
-        // Convert to milliseconds for easier reading and create (time, query) pairs
-        let mut time_query_pairs: Vec<(f64, &String)> = records
+        // Convert to milliseconds for easier reading
+        let mut times_ms: Vec<f64> = execution_times
             .iter()
-            .map(|record| (record.execution_time.as_secs_f64() * 1000.0, &record.query))
+            .map(|time| time.as_secs_f64() * 1000.0)
             .collect();
 
         // Sort by execution time for percentile calculations
-        time_query_pairs.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        times_ms.sort_by(|a, b| a.partial_cmp(b).unwrap());
 
-        let times_ms: Vec<f64> = time_query_pairs.iter().map(|(time, _)| *time).collect();
         let count = times_ms.len();
         let avg_ms = times_ms.iter().sum::<f64>() / count as f64;
         let fastest_ms = times_ms[0];
         let slowest_ms = times_ms[count - 1];
-        let slowest_query = time_query_pairs[count - 1].1.clone();
+        let slowest_query = slowest.query.clone();
+        let slowest_query_id = slowest.query_id.clone();
 
         // Calculate percentiles using the nearest-rank method
         let p90_ms = percentile(&times_ms, 90.0);
@@ -53,6 +100,7 @@ impl QueryRuntimeStats {
             p90_ms,
             p99_ms,
             slowest_query,
+            slowest_query_id,
         })
     }
 
@@ -100,6 +148,14 @@ pub struct FuzzerStats {
     pub queries_executed: u64,
     pub queries_succeeded: u64,
     pub queries_slow: u64,
+    pub oracle_tests_completed: u64,
+    pub findings: u64,
+    /// Timed out queries that finished when retried in isolation, see
+    /// `retry_timeouts`
+    pub timeouts_cleared_on_retry: u64,
+    /// Queries returning more than `max_result_rows` rows, compared by their
+    /// first rows and row count
+    pub truncated_results: u64,
 
     // Timers
     pub start_time: Instant,
@@ -108,8 +164,32 @@ pub struct FuzzerStats {
     pub last_sample_time: Instant,
     pub recent_query: String,
 
-    // Query execution records for runtime statistics
-    query_execution_records: Vec<QueryExecutionRecord>,
+    // Query execution times for runtime statistics. Only the text of the
+    // `SLOWEST_QUERIES` slowest queries is kept, slowest first, copying every
+    // query slows down long runs.
+    query_execution_times: Vec<Duration>,
+    slowest_queries: Vec<QueryExecutionRecord>,
+    // Latencies of the last queries that finished before their timeout, for
+    // `adaptive_timeout`
+    pub recent_latencies: LatencyWindow,
+    // Plan metrics of the queries executed in the embedded session, with
+    // their latencies
+    pub plan_complexity: PlanComplexityStats,
+    // Planning and execution time of the queries executed in the embedded
+    // session
+    pub phase_times: PhaseTimeStats,
+    // Oracle tests matching a `quarantine` pattern, and how many were skipped
+    pub quarantine: QuarantineStats,
+    // Findings of every upstream issue in `known_issues`
+    pub known_issues: KnownIssueStats,
+    // Features of the queries executed in the embedded session
+    pub coverage: FeatureCoverage,
+    // Columns of the generated tables by type
+    pub column_types: BTreeMap<&'static str, u64>,
+    // Queries using the most memory and CPU time, with `measure_resources`
+    pub resource_usage: ResourceUsageStats,
+    // Process memory after every round, with `memory_leak_rounds`
+    pub memory_trend: MemoryTrend,
 
     // Slow query tracking
     pub slow_query_threshold_ms: f64,
@@ -123,6 +203,8 @@ pub struct TuiStats {
     pub queries_executed: u64,
     pub queries_succeeded: u64,
     pub queries_slow: u64,
+    pub oracle_tests_completed: u64,
+    pub findings: u64,
     pub success_rate: f64,
     pub queries_per_second: f64,
     pub running_time_secs: f64,
@@ -144,10 +226,24 @@ impl FuzzerStats {
             queries_executed: 0,
             queries_succeeded: 0,
             queries_slow: 0,
+            oracle_tests_completed: 0,
+            findings: 0,
+            timeouts_cleared_on_retry: 0,
+            truncated_results: 0,
             start_time: Instant::now(),
             last_sample_time: Instant::now(),
             recent_query: String::new(),
-            query_execution_records: Vec::new(),
+            query_execution_times: Vec::new(),
+            slowest_queries: Vec::new(),
+            recent_latencies: LatencyWindow::default(),
+            plan_complexity: PlanComplexityStats::default(),
+            phase_times: PhaseTimeStats::default(),
+            quarantine: QuarantineStats::default(),
+            known_issues: KnownIssueStats::default(),
+            coverage: FeatureCoverage::default(),
+            column_types: BTreeMap::new(),
+            resource_usage: ResourceUsageStats::default(),
+            memory_trend: MemoryTrend::default(),
             slow_query_threshold_ms,
         }
     }
@@ -193,24 +289,40 @@ impl FuzzerStats {
     ///
     /// # Arguments
     /// * `query` - The SQL query string to display and track
+    /// * `query_id` - Stable ID of the query, see [`query_id::QueryId`]
     /// * `success` - Whether the query validation/execution succeeded
     /// * `execution_time` - How long the query took to execute
+    /// * `resource_usage` - Memory and CPU time used by the query, if measured
     /// * `sample_interval_secs` - The interval in seconds for sampling queries for display
     pub fn record_query_with_time(
         &mut self,
         query: &str,
+        query_id: &str,
         success: bool,
         execution_time: Duration,
+        resource_usage: Option<ResourceUsage>,
         sample_interval_secs: u64,
     ) {
         // Record the basic query stats
         self.record_query(query, success, sample_interval_secs);
 
         // Store the execution record for runtime statistics
-        self.query_execution_records.push(QueryExecutionRecord {
+        self.query_execution_times.push(execution_time);
+        let record = QueryExecutionRecord {
             query: query.to_string(),
+            query_id: query_id.to_string(),
             execution_time,
-        });
+            resource_usage,
+        };
+        self.resource_usage.record(&record);
+        // Of queries taking the same time, the latest is listed first
+        let position = self
+            .slowest_queries
+            .partition_point(|slower| slower.execution_time > execution_time);
+        if position < SLOWEST_QUERIES {
+            self.slowest_queries.insert(position, record);
+            self.slowest_queries.truncate(SLOWEST_QUERIES);
+        }
 
         // Check if this is a slow query (queries that took close to or exceed the timeout)
         let execution_time_ms = execution_time.as_secs_f64() * 1000.0;
@@ -226,6 +338,16 @@ impl FuzzerStats {
         self.rounds_completed += 1;
     }
 
+    /// Complete one oracle test (a generated query group, executed and validated)
+    pub fn complete_oracle_test(&mut self) {
+        self.oracle_tests_completed += 1;
+    }
+
+    /// Record a finding: a non-whitelisted error or an oracle consistency violation
+    pub fn record_finding(&mut self) {
+        self.findings += 1;
+    }
+
     /// Get statistics formatted for display in a TUI
     pub fn get_tui_stats(&self) -> TuiStats {
         let elapsed = self.start_time.elapsed();
@@ -249,11 +371,15 @@ impl FuzzerStats {
             queries_executed: self.queries_executed,
             queries_succeeded: self.queries_succeeded,
             queries_slow: self.queries_slow,
+            oracle_tests_completed: self.oracle_tests_completed,
+            findings: self.findings,
             success_rate,
             queries_per_second: qps,
             running_time_secs: elapsed_secs,
             recent_query: self.recent_query.clone(),
-            query_runtime_stats: QueryRuntimeStats::from_records(&self.query_execution_records),
+            query_runtime_stats: self.slowest_queries.first().and_then(|slowest| {
+                QueryRuntimeStats::from_execution_times(&self.query_execution_times, slowest)
+            }),
         }
     }
 }
@@ -290,12 +416,106 @@ pub fn record_query(
 pub fn record_query_with_time(
     stats: &Arc<Mutex<FuzzerStats>>,
     query: &str,
+    query_id: &str,
     success: bool,
     execution_time: Duration,
+    resource_usage: Option<ResourceUsage>,
     sample_interval_secs: u64,
 ) {
     let mut stats_guard = stats.lock().unwrap();
-    stats_guard.record_query_with_time(query, success, execution_time, sample_interval_secs);
+    stats_guard.record_query_with_time(
+        query,
+        query_id,
+        success,
+        execution_time,
+        resource_usage,
+        sample_interval_secs,
+    );
+}
+
+/// Helper function to record the latency of a query that finished before its
+/// timeout
+pub fn record_latency(stats: &Arc<Mutex<FuzzerStats>>, latency: Duration) {
+    let mut stats_guard = stats.lock().unwrap();
+    stats_guard.recent_latencies.record(latency);
+}
+
+/// Helper function to record the plan metrics of a query with its latency
+pub fn record_plan_metrics(
+    stats: &Arc<Mutex<FuzzerStats>>,
+    metrics: PlanMetrics,
+    execution_time: Duration,
+) {
+    let mut stats_guard = stats.lock().unwrap();
+    stats_guard.plan_complexity.record(metrics, execution_time);
+}
+
+/// Helper function to record the planning and execution time of a query
+pub fn record_phase_times(stats: &Arc<Mutex<FuzzerStats>>, timing: QueryTiming, query_id: &str) {
+    let mut stats_guard = stats.lock().unwrap();
+    stats_guard.phase_times.record(timing, query_id);
+}
+
+/// Helper function to record an oracle test matching the quarantine pattern
+/// `pattern`, and whether it was skipped
+pub fn record_quarantined(stats: &Arc<Mutex<FuzzerStats>>, pattern: &str, skipped: bool) {
+    let mut stats_guard = stats.lock().unwrap();
+    stats_guard.quarantine.record(pattern, skipped);
+}
+
+/// Helper function to record a finding of the known upstream issue `issue`
+pub fn record_known_issue(stats: &Arc<Mutex<FuzzerStats>>, issue: &str) {
+    let mut stats_guard = stats.lock().unwrap();
+    stats_guard.known_issues.record(issue);
+}
+
+/// Helper function to record the features of an executed query
+pub fn record_coverage(stats: &Arc<Mutex<FuzzerStats>>, shape: &QueryShape) {
+    let mut stats_guard = stats.lock().unwrap();
+    stats_guard.coverage.record(shape);
+}
+
+/// Helper function to record the column types of a generated table
+pub fn record_column_types(stats: &Arc<Mutex<FuzzerStats>>, columns: &[LogicalColumn]) {
+    let mut stats_guard = stats.lock().unwrap();
+    for column in columns {
+        *stats_guard
+            .column_types
+            .entry(column.data_type.display_name())
+            .or_default() += 1;
+    }
+}
+
+/// Helper function to record a timed out query that finished when retried in
+/// isolation
+pub fn record_timeout_cleared_on_retry(stats: &Arc<Mutex<FuzzerStats>>) {
+    let mut stats_guard = stats.lock().unwrap();
+    stats_guard.timeouts_cleared_on_retry += 1;
+}
+
+/// Helper function to record a query result truncated to `max_result_rows`
+pub fn record_truncated_result(stats: &Arc<Mutex<FuzzerStats>>) {
+    let mut stats_guard = stats.lock().unwrap();
+    stats_guard.truncated_results += 1;
+}
+
+/// Helper function to record the process memory after a 0-based round.
+/// Returns a description of a leak once memory grew in `leak_rounds`
+/// consecutive rounds.
+pub fn record_round_memory(
+    stats: &Arc<Mutex<FuzzerStats>>,
+    round: u32,
+    rss_bytes: u64,
+    leak_rounds: u32,
+) -> Option<String> {
+    let mut stats_guard = stats.lock().unwrap();
+    let elapsed = stats_guard.start_time.elapsed();
+    stats_guard.memory_trend.record(RoundMemory {
+        round: round + 1,
+        rss_bytes,
+        elapsed,
+    });
+    stats_guard.memory_trend.detect_leak(leak_rounds as usize)
 }
 
 /// Helper function to complete a fuzzing round
@@ -304,12 +524,90 @@ pub fn update_stat_for_round_completion(stats: &Arc<Mutex<FuzzerStats>>) {
     stats_guard.complete_round();
 }
 
+/// Helper function to complete an oracle test
+pub fn update_stat_for_oracle_test_completion(stats: &Arc<Mutex<FuzzerStats>>) {
+    let mut stats_guard = stats.lock().unwrap();
+    stats_guard.complete_oracle_test();
+}
+
+/// Helper function to record a finding
+pub fn record_finding(stats: &Arc<Mutex<FuzzerStats>>) {
+    let mut stats_guard = stats.lock().unwrap();
+    stats_guard.record_finding();
+}
+
 /// Helper function to get TUI stats
 pub fn get_tui_stats(stats: &Arc<Mutex<FuzzerStats>>) -> TuiStats {
     let stats_guard = stats.lock().unwrap();
     stats_guard.get_tui_stats()
 }
 
+/// Format a single-line progress summary for headless (non-TUI) runs, e.g.
+/// `round 2/3 | tests 14/30 | queries 25 | 12.50 qps | findings 1 | ETA 1m 04s`
+///
+/// The ETA extrapolates the average time per completed oracle test over the
+/// remaining tests, and is unknown until the first test finishes. Time-limited
+/// runs without a round limit have `total_rounds == 0` and show no totals.
+pub fn format_progress_line(stats: &TuiStats, total_oracle_tests: u64) -> String {
+    if stats.total_rounds == 0 {
+        return format!(
+            "round {} | tests {} | queries {} | {:.2} qps | findings {}",
+            stats.rounds_completed + 1,
+            stats.oracle_tests_completed,
+            stats.queries_executed,
+            stats.queries_per_second,
+            stats.findings
+        );
+    }
+
+    let current_round = (stats.rounds_completed + 1).min(stats.total_rounds);
+    let eta = estimate_remaining_secs(
+        stats.running_time_secs,
+        stats.oracle_tests_completed,
+        total_oracle_tests,
+    )
+    .map(format_duration_secs)
+    .unwrap_or_else(|| "--".to_string());
+
+    format!(
+        "round {}/{} | tests {}/{} | queries {} | {:.2} qps | findings {} | ETA {}",
+        current_round,
+        stats.total_rounds,
+        stats.oracle_tests_completed,
+        total_oracle_tests,
+        stats.queries_executed,
+        stats.queries_per_second,
+        stats.findings,
+        eta
+    )
+}
+
+/// Estimate the remaining running time in seconds, `None` if nothing has completed yet
+fn estimate_remaining_secs(elapsed_secs: f64, completed: u64, total: u64) -> Option<f64> {
+    if completed == 0 {
+        return None;
+    }
+
+    let remaining = total.saturating_sub(completed);
+    Some(elapsed_secs / completed as f64 * remaining as f64)
+}
+
+/// Format seconds as `1h 02m 03s`, `2m 03s` or `3s`
+fn format_duration_secs(total_secs: f64) -> String {
+    let total_secs = total_secs.round() as u64;
+    let hours = total_secs / 3600;
+    let minutes = (total_secs % 3600) / 60;
+    let seconds = total_secs % 60;
+
+    if hours > 0 {
+        format!("{}h {:02}m {:02}s", hours, minutes, seconds)
+    } else if minutes > 0 {
+        format!("{}m {:02}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -329,23 +627,33 @@ mod tests {
         let records = vec![
             QueryExecutionRecord {
                 query: "SELECT 1".to_string(),
+                query_id: "1-r1-q1-s1".to_string(),
                 execution_time: Duration::from_millis(10),
+                resource_usage: None,
             },
             QueryExecutionRecord {
                 query: "SELECT 2".to_string(),
+                query_id: "1-r1-q2-s1".to_string(),
                 execution_time: Duration::from_millis(20),
+                resource_usage: None,
             },
             QueryExecutionRecord {
                 query: "SELECT 3".to_string(),
+                query_id: "1-r1-q3-s1".to_string(),
                 execution_time: Duration::from_millis(30),
+                resource_usage: None,
             },
             QueryExecutionRecord {
                 query: "SELECT 4".to_string(),
+                query_id: "1-r1-q4-s1".to_string(),
                 execution_time: Duration::from_millis(100),
+                resource_usage: None,
             },
             QueryExecutionRecord {
                 query: "SELECT 5 -- slowest".to_string(),
+                query_id: "1-r1-q5-s1".to_string(),
                 execution_time: Duration::from_millis(200),
+                resource_usage: None,
             },
         ];
 
@@ -355,6 +663,7 @@ mod tests {
         assert_eq!(stats.slowest_ms, 200.0);
         assert_eq!(stats.avg_ms, 72.0); // (10+20+30+100+200)/5
         assert_eq!(stats.slowest_query, "SELECT 5 -- slowest");
+        assert_eq!(stats.slowest_query_id, "1-r1-q5-s1");
     }
 
     #[test]
@@ -368,8 +677,22 @@ mod tests {
         let mut stats = FuzzerStats::new(1);
 
         // Record some queries with execution times
-        stats.record_query_with_time("SELECT 1", true, Duration::from_millis(10), 5);
-        stats.record_query_with_time("SELECT 2 -- slowest", true, Duration::from_millis(20), 5);
+        stats.record_query_with_time(
+            "SELECT 1",
+            "1-r1-q1-s1",
+            true,
+            Duration::from_millis(10),
+            None,
+            5,
+        );
+        stats.record_query_with_time(
+            "SELECT 2 -- slowest",
+            "1-r1-q2-s1",
+            true,
+            Duration::from_millis(20),
+            None,
+            5,
+        );
 
         let tui_stats = stats.get_tui_stats();
         assert_eq!(tui_stats.queries_executed, 2);
@@ -380,5 +703,31 @@ mod tests {
         assert_eq!(runtime_stats.slowest_ms, 20.0);
         assert_eq!(runtime_stats.avg_ms, 15.0);
         assert_eq!(runtime_stats.slowest_query, "SELECT 2 -- slowest");
+        assert_eq!(runtime_stats.slowest_query_id, "1-r1-q2-s1");
+    }
+
+    #[test]
+    fn test_progress_line_eta() {
+        let mut stats = FuzzerStats::new(3);
+        let line = format_progress_line(&stats.get_tui_stats(), 30);
+        assert!(line.starts_with("round 1/3 | tests 0/30 | queries 0 |"));
+        assert!(line.ends_with("findings 0 | ETA --"));
+
+        stats.complete_oracle_test();
+        stats.record_finding();
+        let line = format_progress_line(&stats.get_tui_stats(), 30);
+        assert!(line.contains("tests 1/30"));
+        assert!(line.contains("findings 1"));
+        assert!(!line.ends_with("ETA --"));
+
+        assert_eq!(estimate_remaining_secs(10.0, 5, 20), Some(30.0));
+        assert_eq!(estimate_remaining_secs(10.0, 0, 20), None);
+        assert_eq!(format_duration_secs(3723.0), "1h 02m 03s");
+        assert_eq!(format_duration_secs(65.0), "1m 05s");
+        assert_eq!(format_duration_secs(4.4), "4s");
+
+        let unlimited = FuzzerStats::new(0);
+        let line = format_progress_line(&unlimited.get_tui_stats(), 0);
+        assert!(line.starts_with("round 1 | tests 0 | queries 0 |"));
     }
 }
@@ -0,0 +1,108 @@
+//! Planning and execution time of queries, measured apart.
+//!
+//! Queries of the embedded session are planned (parsed, planned, optimized
+//! and turned into a physical plan) before they execute, see
+//! [`crate::engine::record_planned`]. Slow planning is its own kind of
+//! performance bug, hidden in the latency of a query that also executes.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::percentile;
+
+/// Planning and execution time of a query
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct QueryTiming {
+    pub planning_ms: f64,
+    pub execution_ms: f64,
+}
+
+impl QueryTiming {
+    /// Split `latency`, the whole time of a query, after `planning_time`
+    pub fn new(planning_time: Duration, latency: Duration) -> Self {
+        Self {
+            planning_ms: planning_time.as_secs_f64() * 1000.0,
+            execution_ms: latency.saturating_sub(planning_time).as_secs_f64() * 1000.0,
+        }
+    }
+}
+
+/// Planning and execution times of the queries timed in both phases, for the
+/// final report
+#[derive(Debug, Clone, Default)]
+pub struct PhaseTimeStats {
+    planning_ms: Vec<f64>,
+    execution_ms: Vec<f64>,
+    /// Planning time and ID of the query that took longest to plan
+    slowest_planning: Option<(f64, String)>,
+}
+
+impl PhaseTimeStats {
+    pub fn record(&mut self, timing: QueryTiming, query_id: &str) {
+        self.planning_ms.push(timing.planning_ms);
+        self.execution_ms.push(timing.execution_ms);
+        if self
+            .slowest_planning
+            .as_ref()
+            .is_none_or(|(slowest_ms, _)| timing.planning_ms >= *slowest_ms)
+        {
+            self.slowest_planning = Some((timing.planning_ms, query_id.to_string()));
+        }
+    }
+
+    /// Final report section: planning and execution time of the timed
+    /// queries. `None` before any query was timed.
+    pub fn format_display(&self) -> Option<String> {
+        let (slowest_planning_ms, slowest_planning_id) = self.slowest_planning.as_ref()?;
+
+        let mut report = format!(
+            "🧭 Planning vs Execution Time ({} queries):",
+            self.planning_ms.len()
+        );
+        for (phase, times_ms) in [
+            ("Planning", &self.planning_ms),
+            ("Execution", &self.execution_ms),
+        ] {
+            let mut sorted_ms = times_ms.clone();
+            sorted_ms.sort_by(|a, b| a.total_cmp(b));
+            report.push_str(&format!(
+                "\n  • {}: avg {:.2}ms, p90 {:.2}ms, p99 {:.2}ms, max {:.2}ms",
+                phase,
+                sorted_ms.iter().sum::<f64>() / sorted_ms.len() as f64,
+                percentile(&sorted_ms, 90.0),
+                percentile(&sorted_ms, 99.0),
+                sorted_ms[sorted_ms.len() - 1]
+            ));
+        }
+        report.push_str(&format!(
+            "\n  • Slowest planning: {:.2}ms (id {})",
+            slowest_planning_ms, slowest_planning_id
+        ));
+        Some(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_both_phases_and_the_slowest_planning() {
+        let mut stats = PhaseTimeStats::default();
+        assert!(stats.format_display().is_none());
+
+        stats.record(
+            QueryTiming::new(Duration::from_millis(2), Duration::from_millis(10)),
+            "1-r1-q1-s1",
+        );
+        stats.record(
+            QueryTiming::new(Duration::from_millis(40), Duration::from_millis(50)),
+            "1-r1-q2-s1",
+        );
+        let report = stats.format_display().unwrap();
+        assert!(report.contains("Planning: avg 21.00ms"), "{}", report);
+        assert!(report.contains("Execution: avg 9.00ms"), "{}", report);
+        assert!(report.contains("Slowest planning: 40.00ms (id 1-r1-q2-s1)"));
+    }
+}
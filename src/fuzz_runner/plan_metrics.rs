@@ -0,0 +1,178 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use datafusion::common::tree_node::{TreeNode, TreeNodeRecursion};
+use datafusion::logical_expr::LogicalPlan;
+use datafusion::prelude::Expr;
+
+use super::percentile;
+
+/// Size of the logical plan of a query, before optimization
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PlanMetrics {
+    /// Number of plan nodes, including the plans of subqueries
+    pub nodes: usize,
+    pub joins: usize,
+    /// Depth of the deepest expression of any node, 1 for a column or literal
+    pub max_expr_depth: usize,
+}
+
+impl PlanMetrics {
+    pub fn from_plan(plan: &LogicalPlan) -> Self {
+        let mut metrics = Self::default();
+        let _ = plan.apply_with_subqueries(|node| {
+            metrics.nodes += 1;
+            if matches!(node, LogicalPlan::Join(_)) {
+                metrics.joins += 1;
+            }
+            node.apply_expressions(|expr| {
+                metrics.max_expr_depth = metrics.max_expr_depth.max(expr_depth(expr));
+                Ok(TreeNodeRecursion::Continue)
+            })
+        });
+        metrics
+    }
+}
+
+fn expr_depth(expr: &Expr) -> usize {
+    let mut max_child_depth = 0;
+    let _ = expr.apply_children(|child| {
+        max_child_depth = max_child_depth.max(expr_depth(child));
+        Ok(TreeNodeRecursion::Continue)
+    });
+    max_child_depth + 1
+}
+
+/// Pearson correlation of `xs` and `ys`, `None` if either doesn't vary
+fn correlation(xs: &[f64], ys: &[f64]) -> Option<f64> {
+    let n = xs.len() as f64;
+    let mean_x = xs.iter().sum::<f64>() / n;
+    let mean_y = ys.iter().sum::<f64>() / n;
+    let (mut covariance, mut variance_x, mut variance_y) = (0.0, 0.0, 0.0);
+    for (x, y) in xs.iter().zip(ys) {
+        covariance += (x - mean_x) * (y - mean_y);
+        variance_x += (x - mean_x).powi(2);
+        variance_y += (y - mean_y).powi(2);
+    }
+    if variance_x == 0.0 || variance_y == 0.0 {
+        return None;
+    }
+    Some(covariance / (variance_x * variance_y).sqrt())
+}
+
+/// Plan metrics of the executed queries with their latencies, to show which
+/// kinds of generated queries are expensive
+#[derive(Debug, Clone, Default)]
+pub struct PlanComplexityStats {
+    samples: Vec<(PlanMetrics, Duration)>,
+}
+
+impl PlanComplexityStats {
+    pub fn record(&mut self, metrics: PlanMetrics, latency: Duration) {
+        self.samples.push((metrics, latency));
+    }
+
+    /// Final report section: each metric with its correlation to latency, and
+    /// latencies by join count. `None` before any query was recorded.
+    pub fn format_display(&self) -> Option<String> {
+        if self.samples.is_empty() {
+            return None;
+        }
+
+        let latencies_ms: Vec<f64> = self
+            .samples
+            .iter()
+            .map(|(_, latency)| latency.as_secs_f64() * 1000.0)
+            .collect();
+        let metrics: [(&str, fn(&PlanMetrics) -> usize); 3] = [
+            ("Plan nodes", |m| m.nodes),
+            ("Joins", |m| m.joins),
+            ("Max expression depth", |m| m.max_expr_depth),
+        ];
+
+        let mut report = String::from("🧮 Plan Complexity vs Latency:");
+        for (name, metric) in metrics {
+            let values: Vec<f64> = self.samples.iter().map(|(m, _)| metric(m) as f64).collect();
+            let max = self.samples.iter().map(|(m, _)| metric(m)).max();
+            let correlation = match correlation(&values, &latencies_ms) {
+                Some(r) => format!("{:.2}", r),
+                None => "n/a".to_string(),
+            };
+            report.push_str(&format!(
+                "\n  • {}: avg {:.1}, max {}, correlation with latency {}",
+                name,
+                values.iter().sum::<f64>() / values.len() as f64,
+                max.unwrap_or_default(),
+                correlation
+            ));
+        }
+
+        let mut by_joins: BTreeMap<usize, Vec<f64>> = BTreeMap::new();
+        for ((m, _), latency_ms) in self.samples.iter().zip(&latencies_ms) {
+            by_joins.entry(m.joins).or_default().push(*latency_ms);
+        }
+        report.push_str("\n  • Latency by join count:");
+        for (joins, mut latencies_ms) in by_joins {
+            latencies_ms.sort_by(|a, b| a.total_cmp(b));
+            report.push_str(&format!(
+                "\n      {} join(s): {} queries, avg {:.2}ms, p90 {:.2}ms",
+                joins,
+                latencies_ms.len(),
+                latencies_ms.iter().sum::<f64>() / latencies_ms.len() as f64,
+                percentile(&latencies_ms, 90.0)
+            ));
+        }
+        Some(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::prelude::SessionContext;
+
+    #[tokio::test]
+    async fn counts_nodes_joins_and_expression_depth() {
+        let ctx = SessionContext::new();
+        ctx.sql("CREATE TABLE t1(a INT, b INT)").await.unwrap();
+        ctx.sql("CREATE TABLE t2(c INT)").await.unwrap();
+
+        let plan = ctx
+            .state()
+            .create_logical_plan("SELECT a + b * 2 FROM t1 JOIN t2 ON t1.a = t2.c")
+            .await
+            .unwrap();
+        let metrics = PlanMetrics::from_plan(&plan);
+        assert_eq!(metrics.joins, 1);
+        assert!(metrics.nodes >= 4);
+        assert!(metrics.max_expr_depth >= 3);
+
+        let plan = ctx
+            .state()
+            .create_logical_plan(
+                "SELECT c FROM t2 WHERE c IN (SELECT a FROM t1 JOIN t2 AS u ON t1.a = u.c)",
+            )
+            .await
+            .unwrap();
+        assert_eq!(PlanMetrics::from_plan(&plan).joins, 1);
+    }
+
+    #[test]
+    fn correlates_metrics_with_latency() {
+        let mut stats = PlanComplexityStats::default();
+        assert!(stats.format_display().is_none());
+        for joins in 0..4 {
+            let metrics = PlanMetrics {
+                nodes: 3,
+                joins,
+                max_expr_depth: 2,
+            };
+            stats.record(metrics, Duration::from_millis(10 * (joins as u64 + 1)));
+        }
+
+        let report = stats.format_display().unwrap();
+        assert!(report.contains("Joins: avg 1.5, max 3, correlation with latency 1.00"));
+        assert!(report.contains("Plan nodes: avg 3.0, max 3, correlation with latency n/a"));
+        assert!(report.contains("3 join(s): 1 queries, avg 40.00ms"));
+    }
+}
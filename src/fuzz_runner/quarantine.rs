@@ -0,0 +1,179 @@
+//! Quarantine of known-slow query shapes.
+//!
+//! Long campaigns lose most of their throughput to a few shapes of generated
+//! queries, e.g. cross joins of many tables. `[[quarantine]]` patterns match
+//! oracle tests by the shape of their queries' logical plans, and skip them or
+//! run only a fraction of them. How many matching tests were skipped is part of
+//! the final report, so the lost coverage stays visible.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::query_shape::{QueryFeature, QueryShape};
+
+/// A `[[quarantine]]` pattern, matching queries that have at least the given
+/// count of every listed feature.
+///
+/// ```toml
+/// [[quarantine]]
+/// name = "cross join of more than 2 tables"
+/// min = { cross_joins = 2 }
+/// # Run 5% of the matching tests instead of skipping all of them
+/// weight = 0.05
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct QuarantinePattern {
+    /// Name of the shape in logs and the final report
+    pub name: String,
+    pub min: BTreeMap<QueryFeature, usize>,
+    /// Fraction of the matching tests that still run, 0 skips all of them
+    #[serde(default)]
+    pub weight: f64,
+}
+
+impl QuarantinePattern {
+    pub fn matches(&self, shape: &QueryShape) -> bool {
+        self.min
+            .iter()
+            .all(|(feature, min)| shape.count(*feature) >= *min)
+    }
+
+    /// Problems with the pattern, for `RunnerConfig::validate`
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if self.min.is_empty() {
+            errors.push(format!(
+                "quarantine pattern '{}' needs at least one feature in `min`, it would match \
+                 every query",
+                self.name
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.weight) {
+            errors.push(format!(
+                "quarantine pattern '{}' must have a weight between 0 and 1",
+                self.name
+            ));
+        }
+        errors
+    }
+}
+
+/// Oracle tests that matched each quarantine pattern, and how many of them
+/// were skipped
+#[derive(Debug, Clone, Default)]
+pub struct QuarantineStats {
+    /// Matched and skipped tests by pattern name
+    by_pattern: BTreeMap<String, (u64, u64)>,
+}
+
+impl QuarantineStats {
+    pub fn record(&mut self, pattern: &str, skipped: bool) {
+        let (matched, skipped_count) = self.by_pattern.entry(pattern.to_string()).or_default();
+        *matched += 1;
+        if skipped {
+            *skipped_count += 1;
+        }
+    }
+
+    /// Total number of skipped tests
+    pub fn skipped(&self) -> u64 {
+        self.by_pattern.values().map(|(_, skipped)| skipped).sum()
+    }
+
+    /// Final report section: matched and skipped tests of every pattern that
+    /// matched. `None` before any test matched.
+    pub fn format_display(&self) -> Option<String> {
+        if self.by_pattern.is_empty() {
+            return None;
+        }
+
+        let mut report = format!(
+            "🚧 Quarantined Query Shapes ({} tests skipped):",
+            self.skipped()
+        );
+        for (pattern, (matched, skipped)) in &self.by_pattern {
+            report.push_str(&format!(
+                "\n  • {}: {} of {} matching tests skipped",
+                pattern, skipped, matched
+            ));
+        }
+        Some(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::prelude::SessionContext;
+
+    #[tokio::test]
+    async fn counts_cross_joins_and_subqueries() {
+        let ctx = SessionContext::new();
+        ctx.sql("CREATE TABLE t1(a INT)").await.unwrap();
+        ctx.sql("CREATE TABLE t2(b INT)").await.unwrap();
+
+        let plan = ctx
+            .state()
+            .create_logical_plan(
+                "SELECT count(*) FROM t1, t2, t1 AS t3 \
+                 WHERE a IN (SELECT b FROM t2 JOIN t1 ON a = b)",
+            )
+            .await
+            .unwrap();
+        let shape = QueryShape::from_plan(&plan);
+        assert_eq!(shape.count(QueryFeature::Tables), 5);
+        assert_eq!(shape.count(QueryFeature::Joins), 3);
+        assert_eq!(shape.count(QueryFeature::CrossJoins), 2);
+        assert_eq!(shape.count(QueryFeature::Subqueries), 1);
+        assert_eq!(shape.count(QueryFeature::Aggregates), 1);
+        assert_eq!(shape.count(QueryFeature::Sorts), 0);
+
+        let pattern: QuarantinePattern = toml::from_str(
+            r#"
+            name = "cross join of more than 2 tables"
+            min = { cross_joins = 2 }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(pattern.weight, 0.0);
+        assert!(pattern.validate().is_empty());
+        assert!(pattern.matches(&shape));
+
+        let plan = ctx
+            .state()
+            .create_logical_plan("SELECT * FROM t1 CROSS JOIN t2")
+            .await
+            .unwrap();
+        assert!(!pattern.matches(&QueryShape::from_plan(&plan)));
+    }
+
+    #[test]
+    fn rejects_patterns_matching_everything() {
+        let pattern = QuarantinePattern {
+            name: "everything".to_string(),
+            min: BTreeMap::new(),
+            weight: 2.0,
+        };
+        let errors = pattern.validate();
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].contains("would match every query"));
+
+        assert!(toml::from_str::<QuarantinePattern>("name = \"x\"\nmin = { cross = 1 }").is_err());
+    }
+
+    #[test]
+    fn reports_skipped_tests_by_pattern() {
+        let mut stats = QuarantineStats::default();
+        assert!(stats.format_display().is_none());
+
+        stats.record("cross joins", true);
+        stats.record("cross joins", false);
+        stats.record("deep expressions", true);
+        assert_eq!(stats.skipped(), 2);
+        let report = stats.format_display().unwrap();
+        assert!(report.contains("(2 tests skipped)"));
+        assert!(report.contains("cross joins: 1 of 2 matching tests skipped"));
+    }
+}
@@ -0,0 +1,121 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::common::{FuzzerError, fuzzer_err};
+
+/// Stable identifier of one oracle test, derived from the run seed and the
+/// position of the test in the run.
+///
+/// The same config and seed always produce the same IDs, so an ID found in
+/// `error.log` or `findings.jsonl` points back to the exact generation state.
+/// Formatted as `<seed>-r<round>-q<index>` (e.g. `310104-r2-q5`), which is also
+/// safe to use in file names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct QueryId {
+    /// Base seed of the run
+    pub seed: u64,
+    /// 1-based round number
+    pub round: u32,
+    /// 1-based index of the oracle test within the round
+    pub index: u32,
+}
+
+impl QueryId {
+    /// Create the ID from the 0-based round and query index used by the runner
+    pub fn new(seed: u64, round: u32, query_index: u32) -> Self {
+        Self {
+            seed,
+            round: round + 1,
+            index: query_index + 1,
+        }
+    }
+
+    /// ID of a single statement in the query group of this test (1-based)
+    pub fn statement_id(&self, statement_index: usize) -> String {
+        format!("{}-s{}", self, statement_index)
+    }
+}
+
+impl fmt::Display for QueryId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-r{}-q{}", self.seed, self.round, self.index)
+    }
+}
+
+impl FromStr for QueryId {
+    type Err = FuzzerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            fuzzer_err(&format!(
+                "Invalid query ID '{}', expected <seed>-r<round>-q<index>",
+                s
+            ))
+        };
+
+        let mut parts = s.split('-');
+        let (Some(seed), Some(round), Some(index), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+
+        let seed = seed.parse().map_err(|_| invalid())?;
+        let round = round
+            .strip_prefix('r')
+            .and_then(|r| r.parse().ok())
+            .filter(|r| *r > 0)
+            .ok_or_else(invalid)?;
+        let index = index
+            .strip_prefix('q')
+            .and_then(|q| q.parse().ok())
+            .filter(|q| *q > 0)
+            .ok_or_else(invalid)?;
+
+        Ok(Self { seed, round, index })
+    }
+}
+
+impl From<QueryId> for String {
+    fn from(id: QueryId) -> Self {
+        id.to_string()
+    }
+}
+
+impl TryFrom<String> for QueryId {
+    type Error = FuzzerError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_id_round_trips_through_string() {
+        let id = QueryId::new(310104, 1, 4);
+        assert_eq!(id.to_string(), "310104-r2-q5");
+        assert_eq!(id.statement_id(2), "310104-r2-q5-s2");
+        assert_eq!("310104-r2-q5".parse::<QueryId>().unwrap(), id);
+    }
+
+    #[test]
+    fn query_id_rejects_malformed_input() {
+        for input in [
+            "",
+            "310104",
+            "310104-2-5",
+            "310104-r0-q1",
+            "x-r1-q1",
+            "1-r1-q1-s1",
+        ] {
+            assert!(input.parse::<QueryId>().is_err(), "{input}");
+        }
+    }
+}
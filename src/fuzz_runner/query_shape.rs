@@ -0,0 +1,126 @@
+//! Shape of generated queries, the features found in their logical plans.
+
+use std::collections::BTreeMap;
+
+use datafusion::common::tree_node::{TreeNode, TreeNodeRecursion};
+use datafusion::logical_expr::{JoinType, LogicalPlan};
+use datafusion::prelude::Expr;
+use serde::{Deserialize, Serialize};
+
+use super::plan_metrics::PlanMetrics;
+
+/// A feature of the shape of a query, counted in its logical plan before
+/// optimization, including the plans of subqueries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryFeature {
+    /// Table scans
+    Tables,
+    Joins,
+    /// Joins without a join condition
+    CrossJoins,
+    /// Scalar, `EXISTS` and `IN` subqueries
+    Subqueries,
+    Aggregates,
+    WindowFunctions,
+    Sorts,
+    Unions,
+    PlanNodes,
+    /// Depth of the deepest expression, 1 for a column or literal
+    ExprDepth,
+}
+
+impl QueryFeature {
+    pub const ALL: [QueryFeature; 10] = [
+        Self::Tables,
+        Self::Joins,
+        Self::CrossJoins,
+        Self::Subqueries,
+        Self::Aggregates,
+        Self::WindowFunctions,
+        Self::Sorts,
+        Self::Unions,
+        Self::PlanNodes,
+        Self::ExprDepth,
+    ];
+
+    /// Name of the feature in the config and reports
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Tables => "tables",
+            Self::Joins => "joins",
+            Self::CrossJoins => "cross_joins",
+            Self::Subqueries => "subqueries",
+            Self::Aggregates => "aggregates",
+            Self::WindowFunctions => "window_functions",
+            Self::Sorts => "sorts",
+            Self::Unions => "unions",
+            Self::PlanNodes => "plan_nodes",
+            Self::ExprDepth => "expr_depth",
+        }
+    }
+}
+
+/// Features of the shape of a query
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueryShape {
+    counts: BTreeMap<QueryFeature, usize>,
+}
+
+impl QueryShape {
+    pub fn from_plan(plan: &LogicalPlan) -> Self {
+        let metrics = PlanMetrics::from_plan(plan);
+        let mut shape = Self::default();
+        shape.counts.insert(QueryFeature::PlanNodes, metrics.nodes);
+        shape.counts.insert(QueryFeature::Joins, metrics.joins);
+        shape
+            .counts
+            .insert(QueryFeature::ExprDepth, metrics.max_expr_depth);
+
+        let _ = plan.apply_with_subqueries(|node| {
+            let feature = match node {
+                LogicalPlan::TableScan(_) => Some(QueryFeature::Tables),
+                LogicalPlan::Join(join)
+                    if join.join_type == JoinType::Inner
+                        && join.on.is_empty()
+                        && join.filter.is_none() =>
+                {
+                    Some(QueryFeature::CrossJoins)
+                }
+                LogicalPlan::Aggregate(_) => Some(QueryFeature::Aggregates),
+                LogicalPlan::Window(_) => Some(QueryFeature::WindowFunctions),
+                LogicalPlan::Sort(_) => Some(QueryFeature::Sorts),
+                LogicalPlan::Union(_) => Some(QueryFeature::Unions),
+                _ => None,
+            };
+            if let Some(feature) = feature {
+                shape.add(feature, 1);
+            }
+
+            node.apply_expressions(|expr| {
+                expr.apply(|expr| {
+                    if matches!(
+                        expr,
+                        Expr::ScalarSubquery(_) | Expr::Exists(_) | Expr::InSubquery(_)
+                    ) {
+                        shape.add(QueryFeature::Subqueries, 1);
+                    }
+                    Ok(TreeNodeRecursion::Continue)
+                })
+            })
+        });
+        shape
+    }
+
+    fn add(&mut self, feature: QueryFeature, count: usize) {
+        *self.counts.entry(feature).or_default() += count;
+    }
+
+    pub fn count(&self, feature: QueryFeature) -> usize {
+        self.counts.get(&feature).copied().unwrap_or_default()
+    }
+
+    pub fn contains(&self, feature: QueryFeature) -> bool {
+        self.count(feature) > 0
+    }
+}
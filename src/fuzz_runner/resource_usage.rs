@@ -0,0 +1,341 @@
+//! Per-query resource usage, see `measure_resources`.
+//!
+//! A query measured in the embedded session runs in a session of its own,
+//! whose memory pool wraps the configured one and remembers the peak
+//! reservation, and whose last physical optimizer rule keeps the executed
+//! plan, so the `elapsed_compute` metrics of its operators can be summed
+//! after the query finished. The top consumers of a run show resource bugs
+//! that don't change any result, e.g. an operator whose memory grows
+//! quadratically with its input.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use datafusion::common::tree_node::{TreeNode, TreeNodeRecursion};
+use datafusion::config::ConfigOptions;
+use datafusion::execution::SessionStateBuilder;
+use datafusion::execution::memory_pool::{
+    MemoryConsumer, MemoryLimit, MemoryPool, MemoryReservation,
+};
+use datafusion::execution::runtime_env::RuntimeEnvBuilder;
+use datafusion::physical_optimizer::PhysicalOptimizerRule;
+use datafusion::physical_plan::ExecutionPlan;
+use datafusion::prelude::SessionContext;
+
+use super::QueryExecutionRecord;
+use crate::common::Result;
+
+/// Number of queries listed per resource in the final report
+const TOP_RESOURCE_CONSUMERS: usize = 5;
+
+/// Resources used by one query
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResourceUsage {
+    /// Largest total memory reservation of the query's operators
+    pub peak_memory_bytes: usize,
+    /// Sum of the `elapsed_compute` metrics of the executed plan
+    pub cpu_time: Duration,
+}
+
+/// Memory pool passing reservations on to `inner`, remembering the largest
+/// total reservation made through it
+#[derive(Debug)]
+pub struct PeakMemoryPool {
+    inner: Arc<dyn MemoryPool>,
+    reserved: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+impl PeakMemoryPool {
+    pub fn new(inner: Arc<dyn MemoryPool>) -> Self {
+        Self {
+            inner,
+            reserved: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn peak(&self) -> usize {
+        self.peak.load(Ordering::Relaxed)
+    }
+
+    fn record_growth(&self, additional: usize) {
+        let reserved = self.reserved.fetch_add(additional, Ordering::Relaxed) + additional;
+        self.peak.fetch_max(reserved, Ordering::Relaxed);
+    }
+}
+
+impl MemoryPool for PeakMemoryPool {
+    fn register(&self, consumer: &MemoryConsumer) {
+        self.inner.register(consumer)
+    }
+
+    fn unregister(&self, consumer: &MemoryConsumer) {
+        self.inner.unregister(consumer)
+    }
+
+    fn grow(&self, reservation: &MemoryReservation, additional: usize) {
+        self.inner.grow(reservation, additional);
+        self.record_growth(additional);
+    }
+
+    fn shrink(&self, reservation: &MemoryReservation, shrink: usize) {
+        self.inner.shrink(reservation, shrink);
+        self.reserved.fetch_sub(shrink, Ordering::Relaxed);
+    }
+
+    fn try_grow(
+        &self,
+        reservation: &MemoryReservation,
+        additional: usize,
+    ) -> datafusion::error::Result<()> {
+        self.inner.try_grow(reservation, additional)?;
+        self.record_growth(additional);
+        Ok(())
+    }
+
+    fn reserved(&self) -> usize {
+        self.reserved.load(Ordering::Relaxed)
+    }
+
+    fn memory_limit(&self) -> MemoryLimit {
+        self.inner.memory_limit()
+    }
+}
+
+/// Physical optimizer rule keeping the last plan it saw, registered last so
+/// that's the plan which gets executed
+#[derive(Debug, Default)]
+struct CapturePlanRule {
+    plan: Mutex<Option<Arc<dyn ExecutionPlan>>>,
+}
+
+impl PhysicalOptimizerRule for CapturePlanRule {
+    fn optimize(
+        &self,
+        plan: Arc<dyn ExecutionPlan>,
+        _config: &ConfigOptions,
+    ) -> datafusion::error::Result<Arc<dyn ExecutionPlan>> {
+        *self.plan.lock().unwrap() = Some(Arc::clone(&plan));
+        Ok(plan)
+    }
+
+    fn name(&self) -> &str {
+        "capture_plan"
+    }
+
+    fn schema_check(&self) -> bool {
+        true
+    }
+}
+
+/// Measures the resources of the queries run in the session context of
+/// [`ResourceMeter::session_context`]
+pub struct ResourceMeter {
+    memory_pool: Arc<PeakMemoryPool>,
+    capture_plan: Arc<CapturePlanRule>,
+}
+
+impl ResourceMeter {
+    /// A SessionContext sharing the catalog, configuration and memory limit
+    /// of `session_context`, with a meter for the queries run in it
+    pub fn session_context(
+        session_context: &SessionContext,
+    ) -> Result<(Arc<SessionContext>, Self)> {
+        let state = session_context.state();
+        let memory_pool = Arc::new(PeakMemoryPool::new(Arc::clone(
+            &state.runtime_env().memory_pool,
+        )));
+        let runtime_env = RuntimeEnvBuilder::from_runtime_env(state.runtime_env())
+            .with_memory_pool(Arc::clone(&memory_pool) as Arc<dyn MemoryPool>)
+            .build_arc()?;
+        let capture_plan = Arc::new(CapturePlanRule::default());
+
+        let state = SessionStateBuilder::new_from_existing(state)
+            .with_runtime_env(runtime_env)
+            .with_physical_optimizer_rule(Arc::clone(&capture_plan) as _)
+            .build();
+        let meter = Self {
+            memory_pool,
+            capture_plan,
+        };
+
+        Ok((Arc::new(SessionContext::new_with_state(state)), meter))
+    }
+
+    /// Resources used so far. The CPU time is 0 if no plan was executed.
+    pub fn usage(&self) -> ResourceUsage {
+        let cpu_time_nanos = self
+            .capture_plan
+            .plan
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(elapsed_compute_nanos)
+            .unwrap_or_default();
+
+        ResourceUsage {
+            peak_memory_bytes: self.memory_pool.peak(),
+            cpu_time: Duration::from_nanos(cpu_time_nanos as u64),
+        }
+    }
+}
+
+/// Sum of the `elapsed_compute` metrics of all operators of `plan`
+fn elapsed_compute_nanos(plan: &Arc<dyn ExecutionPlan>) -> usize {
+    let mut nanos = 0;
+    let _ = plan.apply(|operator| {
+        nanos += operator
+            .metrics()
+            .and_then(|metrics| metrics.elapsed_compute())
+            .unwrap_or_default();
+        Ok(TreeNodeRecursion::Continue)
+    });
+    nanos
+}
+
+/// The queries with the largest peak memory and CPU time of a run
+#[derive(Debug, Clone, Default)]
+pub struct ResourceUsageStats {
+    by_memory: Vec<QueryExecutionRecord>,
+    by_cpu_time: Vec<QueryExecutionRecord>,
+}
+
+impl ResourceUsageStats {
+    /// Keep `record` if it is among the top consumers of either resource.
+    /// Records without resource usage are ignored.
+    pub(super) fn record(&mut self, record: &QueryExecutionRecord) {
+        if record.resource_usage.is_none() {
+            return;
+        }
+        insert_top(&mut self.by_memory, record, |usage| {
+            usage.peak_memory_bytes as u128
+        });
+        insert_top(&mut self.by_cpu_time, record, |usage| {
+            usage.cpu_time.as_nanos()
+        });
+    }
+
+    /// Final report section: the top consumers of each resource. `None`
+    /// before any query was measured.
+    pub fn format_display(&self) -> Option<String> {
+        if self.by_memory.is_empty() {
+            return None;
+        }
+
+        let mut report = String::from("🧠 Top Resource Consumers:");
+        report.push_str("\n  • By peak memory:");
+        for record in &self.by_memory {
+            report.push_str(&format_consumer(record));
+        }
+        report.push_str("\n  • By CPU time:");
+        for record in &self.by_cpu_time {
+            report.push_str(&format_consumer(record));
+        }
+        Some(report)
+    }
+}
+
+/// Insert `record` into `top`, sorted by descending `key`, if it is among
+/// the `TOP_RESOURCE_CONSUMERS` largest
+fn insert_top(
+    top: &mut Vec<QueryExecutionRecord>,
+    record: &QueryExecutionRecord,
+    key: impl Fn(&ResourceUsage) -> u128,
+) {
+    let key_of = |record: &QueryExecutionRecord| {
+        record.resource_usage.as_ref().map(&key).unwrap_or_default()
+    };
+    let value = key_of(record);
+    let position = top.partition_point(|kept| key_of(kept) >= value);
+    if position < TOP_RESOURCE_CONSUMERS {
+        top.insert(position, record.clone());
+        top.truncate(TOP_RESOURCE_CONSUMERS);
+    }
+}
+
+fn format_consumer(record: &QueryExecutionRecord) -> String {
+    let usage = record.resource_usage.unwrap_or_default();
+    let query = record
+        .query
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let query = match query.char_indices().nth(60) {
+        Some((end, _)) => format!("{}...", &query[..end]),
+        None => query,
+    };
+    format!(
+        "\n      {:.2} MiB, {:.2}ms CPU, {:.2}ms wall (id {}): {}",
+        usage.peak_memory_bytes as f64 / (1024.0 * 1024.0),
+        usage.cpu_time.as_secs_f64() * 1000.0,
+        record.execution_time.as_secs_f64() * 1000.0,
+        record.query_id,
+        query
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn measures_memory_and_cpu_time_of_a_query() {
+        let ctx = SessionContext::new();
+        ctx.sql("CREATE TABLE t1(c0 BIGINT) AS VALUES (3), (1), (2), (1)")
+            .await
+            .unwrap();
+
+        let (measured, meter) = ResourceMeter::session_context(&ctx).unwrap();
+        assert_eq!(meter.usage(), ResourceUsage::default());
+
+        let batches = measured
+            .sql("SELECT c0, count(*) FROM t1 GROUP BY c0 ORDER BY c0")
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 3);
+
+        let usage = meter.usage();
+        assert!(usage.peak_memory_bytes > 0);
+        assert!(usage.cpu_time > Duration::ZERO);
+        // All reservations are released once the query finished
+        assert_eq!(meter.memory_pool.reserved(), 0);
+    }
+
+    #[test]
+    fn keeps_the_top_consumers_of_each_resource() {
+        let mut stats = ResourceUsageStats::default();
+        assert!(stats.format_display().is_none());
+
+        for i in 0..8u64 {
+            stats.record(&QueryExecutionRecord {
+                query: format!("SELECT {}", i),
+                query_id: format!("1-r1-q{}-s1", i),
+                execution_time: Duration::from_millis(1),
+                resource_usage: Some(ResourceUsage {
+                    peak_memory_bytes: i as usize * 1024 * 1024,
+                    cpu_time: Duration::from_millis(10 - i),
+                }),
+            });
+        }
+        stats.record(&QueryExecutionRecord {
+            query: "SELECT unmeasured".to_string(),
+            query_id: "1-r1-q9-s1".to_string(),
+            execution_time: Duration::from_secs(1),
+            resource_usage: None,
+        });
+
+        assert_eq!(stats.by_memory.len(), TOP_RESOURCE_CONSUMERS);
+        assert_eq!(stats.by_memory[0].query, "SELECT 7");
+        assert_eq!(stats.by_cpu_time[0].query, "SELECT 0");
+        assert_eq!(stats.by_cpu_time[4].query, "SELECT 4");
+
+        let report = stats.format_display().unwrap();
+        assert!(report.contains("7.00 MiB, 3.00ms CPU, 1.00ms wall (id 1-r1-q7-s1): SELECT 7"));
+        assert!(!report.contains("unmeasured"));
+    }
+}
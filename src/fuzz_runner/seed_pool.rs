@@ -0,0 +1,318 @@
+//! Seed pool: base seeds of rounds worth running again, kept across campaigns.
+//!
+//! Without a pool, the seeds of the tables and tests of every round derive
+//! from `seed` and the round number. With `seed_pool`, every round runs the
+//! tables and tests of a single base seed instead (as the first round of that
+//! seed), either a seed from the pool or a fresh one. Pool seeds are scheduled
+//! by their energy: seeds whose rounds produced findings or query shapes
+//! never seen before go first, and every run lowers the energy. Fresh seeds
+//! whose round produced findings or new shapes join the pool.
+//!
+//! The pool is saved to its file after every round, so the next campaign,
+//! e.g. on a newer DataFusion version, starts with the productive seeds.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
+
+use super::query_shape::{QueryFeature, QueryShape};
+use crate::common::rng::rng_from_seed;
+use crate::common::{Result, fuzzer_err};
+
+/// Seeds kept in the pool, the ones with the least energy are dropped
+pub const MAX_POOL_SEEDS: usize = 256;
+
+/// Energy of a finding, relative to a new query shape
+const FINDING_ENERGY: f64 = 10.0;
+
+/// Share of the rounds that run a fresh seed while pool seeds are left
+const FRESH_SEED_PROBABILITY: f64 = 0.25;
+
+/// A seed of the pool, with what its rounds produced over all campaigns
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PoolSeed {
+    pub seed: u64,
+    pub runs: u64,
+    pub findings: u64,
+    /// Query shapes first seen in rounds of the seed
+    pub new_shapes: u64,
+}
+
+impl PoolSeed {
+    fn energy(&self) -> f64 {
+        (1.0 + FINDING_ENERGY * self.findings as f64 + self.new_shapes as f64)
+            / (1 + self.runs) as f64
+    }
+}
+
+/// Contents of the seed pool file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct SeedPool {
+    seeds: Vec<PoolSeed>,
+    /// Keys of the query shapes seen so far, see [`shape_key`]
+    seen_shapes: BTreeSet<String>,
+}
+
+/// The round currently running
+#[derive(Debug, Clone)]
+struct ScheduledRound {
+    seed: u64,
+    new_shapes: u64,
+}
+
+/// Picks the base seed of every round from the pool in `path`
+#[derive(Debug)]
+pub struct SeedScheduler {
+    path: PathBuf,
+    pool: SeedPool,
+    /// Picks fresh seeds, seeded with `seed` so a campaign picks the same ones
+    rng: StdRng,
+    /// Pool seeds already run in this campaign
+    scheduled: BTreeSet<u64>,
+    current: Option<ScheduledRound>,
+    /// Seeds that joined the pool in this campaign
+    added: usize,
+}
+
+impl SeedScheduler {
+    /// Load the pool in `path`, or start an empty one if the file doesn't exist
+    pub fn open(path: &Path, seed: u64) -> Result<Self> {
+        let pool = if path.exists() {
+            let content = fs::read_to_string(path).map_err(|e| {
+                fuzzer_err(&format!(
+                    "Failed to read seed pool '{}': {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+            serde_json::from_str(&content).map_err(|e| {
+                fuzzer_err(&format!(
+                    "Failed to parse seed pool '{}': {}",
+                    path.display(),
+                    e
+                ))
+            })?
+        } else {
+            SeedPool::default()
+        };
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            pool,
+            rng: rng_from_seed(seed),
+            scheduled: BTreeSet::new(),
+            current: None,
+            added: 0,
+        })
+    }
+
+    /// Base seed of the next round: the pool seed with the most energy not
+    /// run in this campaign yet, or a fresh seed
+    pub fn next_seed(&mut self) -> u64 {
+        let fresh = self.rng.next_u64();
+        let pool_seed = self
+            .pool
+            .seeds
+            .iter()
+            .filter(|seed| !self.scheduled.contains(&seed.seed))
+            .max_by(|a, b| a.energy().total_cmp(&b.energy()))
+            .map(|seed| seed.seed);
+
+        let seed = match pool_seed {
+            Some(seed) if !self.rng.random_bool(FRESH_SEED_PROBABILITY) => {
+                self.scheduled.insert(seed);
+                seed
+            }
+            _ => fresh,
+        };
+        self.current = Some(ScheduledRound {
+            seed,
+            new_shapes: 0,
+        });
+        seed
+    }
+
+    /// Record the shape of a query of the current round
+    pub fn record_shape(&mut self, shape: &QueryShape) {
+        if self.pool.seen_shapes.insert(shape_key(shape))
+            && let Some(current) = &mut self.current
+        {
+            current.new_shapes += 1;
+        }
+    }
+
+    /// Credit the current round's seed with its findings and new shapes, and
+    /// save the pool
+    pub fn finish_round(&mut self, findings: u64) -> Result<()> {
+        let Some(round) = self.current.take() else {
+            return Ok(());
+        };
+
+        match self
+            .pool
+            .seeds
+            .iter_mut()
+            .find(|seed| seed.seed == round.seed)
+        {
+            Some(seed) => {
+                seed.runs += 1;
+                seed.findings += findings;
+                seed.new_shapes += round.new_shapes;
+            }
+            None if findings > 0 || round.new_shapes > 0 => {
+                self.pool.seeds.push(PoolSeed {
+                    seed: round.seed,
+                    runs: 1,
+                    findings,
+                    new_shapes: round.new_shapes,
+                });
+                self.added += 1;
+                // Fresh seeds run once per campaign too
+                self.scheduled.insert(round.seed);
+            }
+            None => {}
+        }
+
+        if self.pool.seeds.len() > MAX_POOL_SEEDS {
+            self.pool
+                .seeds
+                .sort_by(|a, b| b.energy().total_cmp(&a.energy()));
+            self.pool.seeds.truncate(MAX_POOL_SEEDS);
+        }
+        self.save()
+    }
+
+    fn save(&self) -> Result<()> {
+        let content = serde_json::to_string_pretty(&self.pool)
+            .map_err(|e| fuzzer_err(&format!("Failed to serialize seed pool: {}", e)))?;
+        // Write a copy first, an interrupted write must not lose the pool
+        let tmp_path = self.path.with_extension("tmp");
+        fs::write(&tmp_path, content)
+            .and_then(|_| fs::rename(&tmp_path, &self.path))
+            .map_err(|e| {
+                fuzzer_err(&format!(
+                    "Failed to write seed pool '{}': {}",
+                    self.path.display(),
+                    e
+                ))
+            })
+    }
+
+    /// Final report section: size of the pool and its seeds with the most
+    /// energy. `None` while the pool is empty.
+    pub fn format_display(&self) -> Option<String> {
+        if self.pool.seeds.is_empty() {
+            return None;
+        }
+
+        let mut seeds: Vec<&PoolSeed> = self.pool.seeds.iter().collect();
+        seeds.sort_by(|a, b| b.energy().total_cmp(&a.energy()));
+        let mut report = format!(
+            "🌱 Seed Pool ({} seeds, {} added in this campaign):",
+            seeds.len(),
+            self.added
+        );
+        for seed in seeds.iter().take(5) {
+            report.push_str(&format!(
+                "\n  • seed {}: {} runs, {} findings, {} new query shapes",
+                seed.seed, seed.runs, seed.findings, seed.new_shapes
+            ));
+        }
+        Some(report)
+    }
+}
+
+/// The features of `shape` with their counts rounded down to a power of 2,
+/// e.g. `tables=2,joins=1,aggregates=1,plan_nodes=8,expr_depth=2`
+fn shape_key(shape: &QueryShape) -> String {
+    QueryFeature::ALL
+        .iter()
+        .filter(|feature| shape.contains(**feature))
+        .map(|feature| {
+            let count = shape.count(*feature);
+            format!("{}={}", feature.name(), 1usize << count.ilog2())
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::prelude::SessionContext;
+
+    #[tokio::test]
+    async fn keeps_productive_seeds_across_campaigns() {
+        let ctx = SessionContext::new();
+        ctx.sql("CREATE TABLE t1(a INT)").await.unwrap();
+        let plan = ctx
+            .state()
+            .create_logical_plan("SELECT a, count(*) FROM t1 GROUP BY a")
+            .await
+            .unwrap();
+        let shape = QueryShape::from_plan(&plan);
+        let key = shape_key(&shape);
+        assert!(
+            key.starts_with("tables=1,aggregates=1,plan_nodes="),
+            "{}",
+            key
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("seed-pool.json");
+        let mut scheduler = SeedScheduler::open(&path, 42).unwrap();
+        assert!(scheduler.format_display().is_none());
+
+        // Nothing to schedule yet, seeds are fresh. Only the seeds finding
+        // something join the pool.
+        let with_finding = scheduler.next_seed();
+        scheduler.finish_round(2).unwrap();
+        let with_new_shape = scheduler.next_seed();
+        scheduler.record_shape(&shape);
+        scheduler.finish_round(0).unwrap();
+        scheduler.next_seed();
+        scheduler.record_shape(&shape);
+        scheduler.finish_round(0).unwrap();
+        assert!(
+            scheduler
+                .format_display()
+                .unwrap()
+                .contains("(2 seeds, 2 added in this campaign)")
+        );
+
+        // The next campaign runs every pool seed once, the one with the
+        // finding first
+        let mut scheduler = SeedScheduler::open(&path, 43).unwrap();
+        let mut pool_seeds = Vec::new();
+        for _ in 0..20 {
+            let seed = scheduler.next_seed();
+            if seed == with_finding || seed == with_new_shape {
+                pool_seeds.push(seed);
+            }
+            scheduler.finish_round(0).unwrap();
+        }
+        assert_eq!(pool_seeds, vec![with_finding, with_new_shape]);
+        let report = scheduler.format_display().unwrap();
+        assert!(
+            report.contains(&format!(
+                "seed {}: 2 runs, 2 findings, 0 new query shapes",
+                with_finding
+            )),
+            "{}",
+            report
+        );
+    }
+
+    #[test]
+    fn rejects_unreadable_pools() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("seed-pool.json");
+        fs::write(&path, "not json").unwrap();
+        let message = SeedScheduler::open(&path, 42).unwrap_err().to_string();
+        assert!(message.contains("Failed to parse seed pool"), "{}", message);
+    }
+}
@@ -0,0 +1,241 @@
+//! Statistics of a campaign, exported to `stats.json` in the log directory at
+//! the end of a run.
+//!
+//! Together with `findings.jsonl` the export is the input of the `summarize`
+//! subcommand, so a nightly job can post a summary of its campaign without
+//! parsing the final report.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::FuzzerStats;
+use super::query_shape::QueryFeature;
+use crate::common::{Result, fuzzer_err};
+use crate::fuzz_context::RunnerConfig;
+
+/// File name of the export in the log directory
+pub const STATS_FILE: &str = "stats.json";
+
+/// A query of the campaign with its execution time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SlowQuery {
+    pub query_id: String,
+    pub query: String,
+    pub execution_ms: f64,
+}
+
+/// Query runtime percentiles in milliseconds
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RuntimePercentiles {
+    pub avg_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+}
+
+/// Process memory at the end of a round
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RoundMemoryStats {
+    pub round: u32,
+    pub rss_bytes: u64,
+    /// Time since the start of the run
+    pub elapsed_secs: f64,
+}
+
+/// Statistics of a campaign, see the module documentation
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CampaignStats {
+    /// DataFusion version under test, with the configured upstream git SHA if any
+    pub datafusion_version: String,
+    pub seed: u64,
+    pub rounds_completed: u32,
+    pub oracle_tests_completed: u64,
+    pub queries_executed: u64,
+    pub queries_succeeded: u64,
+    /// Queries taking 90% or more of the timeout
+    pub queries_slow: u64,
+    pub findings: u64,
+    pub running_time_secs: f64,
+    pub queries_per_second: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub runtime: Option<RuntimePercentiles>,
+    /// Slowest queries, slowest first
+    #[serde(default)]
+    pub slowest_queries: Vec<SlowQuery>,
+    /// Share of the queries of the embedded session containing each feature,
+    /// by feature name
+    #[serde(default)]
+    pub feature_coverage: BTreeMap<String, f64>,
+    /// Columns of the generated tables by type
+    #[serde(default)]
+    pub column_types: BTreeMap<String, u64>,
+    /// Process memory after every round, see `memory_trend`
+    #[serde(default)]
+    pub memory_by_round: Vec<RoundMemoryStats>,
+}
+
+impl CampaignStats {
+    pub fn from_stats(stats: &FuzzerStats, config: &RunnerConfig) -> Self {
+        let tui_stats = stats.get_tui_stats();
+        let feature_coverage = if stats.coverage.queries() > 0 {
+            QueryFeature::ALL
+                .into_iter()
+                .map(|feature| (feature.name().to_string(), stats.coverage.achieved(feature)))
+                .collect()
+        } else {
+            BTreeMap::new()
+        };
+
+        Self {
+            datafusion_version: config.datafusion_version(),
+            seed: config.seed,
+            rounds_completed: tui_stats.rounds_completed,
+            oracle_tests_completed: tui_stats.oracle_tests_completed,
+            queries_executed: tui_stats.queries_executed,
+            queries_succeeded: tui_stats.queries_succeeded,
+            queries_slow: tui_stats.queries_slow,
+            findings: tui_stats.findings,
+            running_time_secs: tui_stats.running_time_secs,
+            queries_per_second: tui_stats.queries_per_second,
+            runtime: tui_stats
+                .query_runtime_stats
+                .map(|runtime| RuntimePercentiles {
+                    avg_ms: runtime.avg_ms,
+                    p90_ms: runtime.p90_ms,
+                    p99_ms: runtime.p99_ms,
+                }),
+            slowest_queries: stats
+                .slowest_queries
+                .iter()
+                .map(|record| SlowQuery {
+                    query_id: record.query_id.clone(),
+                    query: record.query.clone(),
+                    execution_ms: record.execution_time.as_secs_f64() * 1000.0,
+                })
+                .collect(),
+            feature_coverage,
+            column_types: stats
+                .column_types
+                .iter()
+                .map(|(data_type, count)| (data_type.to_string(), *count))
+                .collect(),
+            memory_by_round: stats
+                .memory_trend
+                .samples()
+                .iter()
+                .map(|sample| RoundMemoryStats {
+                    round: sample.round,
+                    rss_bytes: sample.rss_bytes,
+                    elapsed_secs: sample.elapsed.as_secs_f64(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Write the export to `STATS_FILE` in `dir`
+    pub fn write(&self, dir: &Path) -> Result<()> {
+        let path = dir.join(STATS_FILE);
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| fuzzer_err(&format!("Failed to serialize stats: {}", e)))?;
+        fs::write(&path, content).map_err(|e| {
+            fuzzer_err(&format!(
+                "Failed to write stats export '{}': {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+
+    /// Read the export in `dir`
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join(STATS_FILE);
+        let content = fs::read_to_string(&path).map_err(|e| {
+            fuzzer_err(&format!(
+                "Failed to read stats export '{}': {}",
+                path.display(),
+                e
+            ))
+        })?;
+        serde_json::from_str(&content).map_err(|e| {
+            fuzzer_err(&format!(
+                "Failed to parse stats export '{}': {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use super::*;
+    use crate::common::{FuzzerDataType, LogicalColumn};
+    use crate::fuzz_runner::memory_trend::RoundMemory;
+    use crate::fuzz_runner::record_column_types;
+
+    #[test]
+    fn exports_stats_to_the_log_directory() {
+        let mut stats = FuzzerStats::new(1);
+        for (index, millis) in [10, 30, 20, 5, 40, 1, 50].into_iter().enumerate() {
+            stats.record_query_with_time(
+                &format!("SELECT {}", index),
+                &format!("1-r1-q{}-s1", index + 1),
+                true,
+                Duration::from_millis(millis),
+                None,
+                5,
+            );
+        }
+        let column = |data_type| LogicalColumn {
+            name: "c0".to_string(),
+            data_type,
+            value_summary: None,
+        };
+        stats.memory_trend.record(RoundMemory {
+            round: 1,
+            rss_bytes: 64 * 1024 * 1024,
+            elapsed: Duration::from_millis(1500),
+        });
+        let stats = Arc::new(Mutex::new(stats));
+        record_column_types(
+            &stats,
+            &[
+                column(FuzzerDataType::Int64),
+                column(FuzzerDataType::Int64),
+                column(FuzzerDataType::Boolean),
+            ],
+        );
+
+        let exported = CampaignStats::from_stats(&stats.lock().unwrap(), &RunnerConfig::default());
+        let slowest: Vec<f64> = exported
+            .slowest_queries
+            .iter()
+            .map(|query| query.execution_ms)
+            .collect();
+        assert_eq!(slowest, vec![50.0, 40.0, 30.0, 20.0, 10.0]);
+        assert_eq!(exported.slowest_queries[0].query, "SELECT 6");
+        assert_eq!(exported.column_types.values().sum::<u64>(), 3);
+        // Without recorded query shapes there is no feature coverage
+        assert!(exported.feature_coverage.is_empty());
+        assert_eq!(
+            exported.memory_by_round,
+            vec![RoundMemoryStats {
+                round: 1,
+                rss_bytes: 64 * 1024 * 1024,
+                elapsed_secs: 1.5,
+            }]
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        exported.write(dir.path()).unwrap();
+        let loaded = CampaignStats::load(dir.path()).unwrap();
+        assert_eq!(loaded.slowest_queries, exported.slowest_queries);
+        assert_eq!(loaded.column_types, exported.column_types);
+        assert_eq!(loaded.memory_by_round, exported.memory_by_round);
+    }
+}
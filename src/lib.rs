@@ -1,7 +1,10 @@
 pub mod cli;
 pub mod common;
 pub mod datasource_generator;
+pub mod engine;
 pub mod fuzz_context;
 pub mod fuzz_runner;
 pub mod oracle;
 pub mod query_generator;
+pub mod triage;
+pub mod workspace;
@@ -1,32 +1,96 @@
 use clap::Parser;
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::path::Path;
 use std::sync::Arc;
-use tracing::info;
+use tracing::{info, warn};
 use tracing_subscriber::{EnvFilter, filter::LevelFilter, fmt, prelude::*};
 
 use datafusion_fuzzer::{
-    cli::{Cli, TuiApp, init, restore, run_fuzzer},
+    cli::{
+        Cli, Command, CorpusArgs, LogRotation, PlanDiffArgs, ReduceArgs, ReplayArgs, ReportArgs,
+        RotatingFileWriter, RunArgs, SummarizeArgs, TuiApp, init, install_panic_hook,
+        plan_log::parse_plan_log,
+        reduce::reduce_finding,
+        replay::{
+            load_corpus_config, load_replay_config, offline_config, offline_context,
+            parse_query_log, plan_diff, replay_corpus,
+        },
+        replay_oracle_test,
+        report::format_findings_report,
+        restore, run_fuzzer, spawn_progress_reporter,
+        summarize::format_campaign_summary,
+    },
     common::{Result, init_available_data_types},
+    engine::connect_engines,
     fuzz_context::{GlobalContext, RunnerConfig, RuntimeContext},
-    fuzz_runner::{FuzzerStats, create_fuzzer_stats_with_timeout, get_tui_stats},
+    fuzz_runner::{
+        create_fuzzer_stats_with_timeout,
+        findings::{Finding, find_finding, load_findings},
+        get_tui_stats,
+        known_issues::KnownIssues,
+        seed_pool::SeedScheduler,
+        stats_export::CampaignStats,
+    },
+    workspace::Workspace,
 };
 
 #[tokio::main]
 async fn main() -> Result<()> {
     // Initialize available data types early
     init_available_data_types();
+    // Turn panics inside DataFusion into findings with backtraces
+    install_panic_hook();
+
+    match Cli::parse().into_command() {
+        Command::Run(args) => run(&args).await,
+        Command::Replay(args) => replay(&args).await,
+        Command::Reduce(args) => reduce(&args).await,
+        Command::Report(args) => report(&args),
+        Command::Summarize(args) => summarize(&args),
+        Command::Corpus(args) => corpus(&args).await,
+        Command::PlanDiff(args) => plan_diff_corpus(&args).await,
+    }
+}
 
-    let cli = Cli::parse();
-    let runner_config = RunnerConfig::from_cli(&cli)?;
-    let _log_guards = setup_logging(&runner_config)?;
+/// Run a fuzzing campaign
+async fn run(args: &RunArgs) -> Result<()> {
+    let runner_config = RunnerConfig::from_cli(args)?;
+    let log_guards = setup_logging(&runner_config)?;
+    info!("DataFusion version: {}", runner_config.datafusion_version());
+    log_effective_config(&runner_config)?;
+    if let Some(spill_dir) = &runner_config.spill_dir {
+        std::fs::create_dir_all(spill_dir)?;
+    }
+    for warning in runner_config.warnings() {
+        eprintln!("Warning: {}", warning);
+        warn!("Config warning: {}", warning);
+    }
 
     // Create global context with all state
     let fuzzer_stats =
         create_fuzzer_stats_with_timeout(runner_config.rounds, runner_config.timeout_seconds);
-    let global_context = Arc::new(GlobalContext::new(
+    let engines = connect_engines(&runner_config).await?;
+    let workspace = Arc::new(Workspace::create(None, runner_config.keep_artifacts)?);
+    info!("Workspace: {}", workspace.root().display());
+    let mut global_context = GlobalContext::new(
         runner_config.clone(),
         RuntimeContext::default(),
         fuzzer_stats,
-    ));
+    )
+    .with_engines(engines)
+    .with_workspace(Arc::clone(&workspace));
+    if let Some(seed_pool) = &runner_config.seed_pool {
+        global_context =
+            global_context.with_seed_scheduler(SeedScheduler::open(seed_pool, runner_config.seed)?);
+    }
+    let global_context = Arc::new(global_context);
+
+    // Stale suppressions for fixed upstream bugs should be cleaned up
+    let today = chrono::Local::now().date_naive();
+    for pattern in global_context.error_whitelist.expired_patterns(today) {
+        warn!("Expired error whitelist pattern: {}", pattern);
+    }
 
     // Spawn TUI in a separate thread (if enabled)
     if runner_config.enable_tui {
@@ -38,10 +102,190 @@ async fn main() -> Result<()> {
         });
     }
 
+    // Headless runs get a periodic one-line progress summary on stderr instead
+    let progress_reporter = if !runner_config.enable_tui && runner_config.progress_interval_secs > 0
+    {
+        Some(spawn_progress_reporter(Arc::clone(&global_context)))
+    } else {
+        None
+    };
+
     // Run the fuzzer
-    run_fuzzer(global_context.clone()).await?;
+    let fuzz_result = run_fuzzer(global_context.clone()).await;
+
+    if let Some(progress_reporter) = progress_reporter {
+        progress_reporter.abort();
+    }
+
+    // The generated files of a failed run are kept to investigate the findings
+    let successful =
+        fuzz_result.is_ok() && get_tui_stats(&global_context.fuzzer_stats).findings == 0;
+    match workspace.finish(successful) {
+        Ok(true) => {
+            eprintln!("Kept artifacts in {}", workspace.root().display());
+            info!("Kept artifacts in {}", workspace.root().display());
+        }
+        Ok(false) => {}
+        Err(e) => warn!("Failed to clean up workspace: {}", e),
+    }
+    fuzz_result?;
+
+    print_final_stats(&global_context);
+
+    if let Some(log_dir) = &runner_config.log_path {
+        let stats =
+            CampaignStats::from_stats(&global_context.fuzzer_stats.lock().unwrap(), &runner_config);
+        if let Err(e) = stats.write(log_dir) {
+            warn!("Failed to export stats: {}", e);
+        }
+    }
+
+    // With a baseline, findings missing from it fail the run even if they
+    // didn't stop it
+    let new_findings_missing_from_baseline =
+        global_context.findings.has_baseline() && global_context.findings.new_findings_count() > 0;
+    if global_context.findings.stop_reason().is_some() || new_findings_missing_from_baseline {
+        // Flush the log writers, `exit` skips destructors
+        drop(log_guards);
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// Re-run a single oracle test of a previous run, exits with 1 if it still
+/// produces a finding
+async fn replay(args: &ReplayArgs) -> Result<()> {
+    let config = offline_config(
+        load_replay_config(&args.source, args.query_id)?,
+        args.display_logs,
+    );
+    let _log_guards = setup_logging(&config)?;
+    info!("DataFusion version: {}", config.datafusion_version());
+
+    let ctx = offline_context(config).await?;
+    let reproduced = replay_oracle_test(Arc::clone(&ctx), args.query_id).await?;
+
+    if reproduced {
+        println!("❌ {} reproduced", args.query_id);
+        std::process::exit(1);
+    }
+
+    println!("✅ {} passed", args.query_id);
+    Ok(())
+}
+
+/// Shrink the query of an error finding
+async fn reduce(args: &ReduceArgs) -> Result<()> {
+    let finding = find_finding(&args.findings, args.query_id)?;
+    let config = offline_config(
+        RunnerConfig::from_toml_str(&finding.config)?,
+        args.display_logs,
+    );
+    let _log_guards = setup_logging(&config)?;
+
+    let ctx = offline_context(config).await?;
+    let reduced = reduce_finding(&ctx, &finding, args.max_attempts).await?;
+
+    println!(
+        "Reduced {} ({}) from {} to {} characters:\n{}",
+        finding.query_id,
+        finding.category,
+        finding.queries[0].len(),
+        reduced.len(),
+        reduced
+    );
+
+    if let Some(output_dir) = &args.output {
+        std::fs::create_dir_all(output_dir)?;
+        let path = output_dir.join(format!("{}.reduced.sql", finding.query_id));
+        let message = finding.message.lines().next().unwrap_or_default();
+        std::fs::write(
+            &path,
+            format!(
+                "-- Reduced from finding {} ({}), run on the tables of round {}\n-- {}\n{};\n",
+                finding.query_id, finding.category, finding.round, message, reduced
+            ),
+        )?;
+        println!("Written to {}", path.display());
+    }
+
+    Ok(())
+}
+
+/// Print a summary of a findings file
+fn report(args: &ReportArgs) -> Result<()> {
+    let findings = load_findings(&args.findings)?;
+    print!(
+        "{}",
+        format_findings_report(&findings, args.category.as_deref())
+    );
+    Ok(())
+}
+
+/// Write a summary of a campaign's log directory
+fn summarize(args: &SummarizeArgs) -> Result<()> {
+    let stats = CampaignStats::load(&args.dir)?;
+    let findings_path = args.dir.join("findings.jsonl");
+    // A campaign without findings doesn't create the file
+    let mut findings = if findings_path.exists() {
+        load_findings(&findings_path)?
+    } else {
+        Vec::new()
+    };
+    // Issues triaged since the campaign ran replace the recorded ones
+    if let Some(path) = &args.known_issues {
+        let known_issues = KnownIssues::load(path)?;
+        for finding in &mut findings {
+            finding.known_issue = known_issues.issue_of(finding).map(str::to_string);
+        }
+    }
+    let mut known = BTreeSet::new();
+    for path in &args.known {
+        known.extend(load_findings(path)?.iter().map(Finding::fingerprint));
+    }
+
+    let summary = format_campaign_summary(&stats, &findings, &known, args.format);
+    match &args.output {
+        Some(path) => std::fs::write(path, summary)?,
+        None => print!("{}", summary),
+    }
+    Ok(())
+}
+
+/// Re-execute the statements of a query log, exits with 1 if any fails with a
+/// non-whitelisted error
+async fn corpus(args: &CorpusArgs) -> Result<()> {
+    let config = offline_config(
+        load_corpus_config(&args.query_log, args.config.as_deref())?,
+        args.display_logs,
+    );
+    let _log_guards = setup_logging(&config)?;
 
-    print_final_stats(&global_context.fuzzer_stats);
+    let tests = parse_query_log(&std::fs::read_to_string(&args.query_log)?)?;
+    let ctx = offline_context(config).await?;
+    if replay_corpus(&ctx, &tests, args.plans.as_deref()).await? > 0 {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// Compare the optimized plans of a query log with plans recorded by `corpus
+/// --plans`, e.g. before and after an optimizer change
+async fn plan_diff_corpus(args: &PlanDiffArgs) -> Result<()> {
+    let config = offline_config(
+        load_corpus_config(&args.query_log, args.config.as_deref())?,
+        args.display_logs,
+    );
+    let _log_guards = setup_logging(&config)?;
+
+    let tests = parse_query_log(&std::fs::read_to_string(&args.query_log)?)?;
+    let recorded = parse_plan_log(&std::fs::read_to_string(&args.plans)?)?;
+    let ctx = offline_context(config).await?;
+    if plan_diff(&ctx, &tests, &recorded).await? > 0 {
+        std::process::exit(1);
+    }
 
     Ok(())
 }
@@ -57,7 +301,8 @@ struct LogGuards {
 /// - For system under test (DataFusion)'s found bugs, use `error!` macro
 ///
 /// When configured with a log directory (e.g., `logs/`), the system creates two log files:
-/// - `logs/trace.log`: Contains all logs generated using the `info!` macro
+/// - `logs/trace.log`: Contains all logs generated using the `info!` macro, split
+/// into multiple files if `log_rotation` is configured
 /// - `logs/error.log`: Contains logs specifically related to system under test
 /// bugs using the `error!` macro
 fn setup_logging(config: &RunnerConfig) -> Result<LogGuards> {
@@ -82,14 +327,10 @@ fn setup_logging(config: &RunnerConfig) -> Result<LogGuards> {
         }
 
         // Create the log files
-        let trace_path = log_dir.join("trace.log");
         let error_path = log_dir.join("error.log");
 
         // Create appenders for the log files
-        let trace_file = tracing_appender::rolling::never(
-            trace_path.parent().unwrap(),
-            trace_path.file_name().unwrap(),
-        );
+        let trace_file = create_trace_appender(config, log_dir)?;
         let error_file = tracing_appender::rolling::never(
             error_path.parent().unwrap(),
             error_path.file_name().unwrap(),
@@ -164,9 +405,39 @@ fn setup_logging(config: &RunnerConfig) -> Result<LogGuards> {
     Ok(log_guards)
 }
 
+/// Log the fully resolved configuration, and save it as `effective-config.toml`
+/// in the log directory so the run can be reproduced with `--config`
+fn log_effective_config(config: &RunnerConfig) -> Result<()> {
+    let effective_config = config.to_toml()?;
+    info!("Effective configuration:\n{}", effective_config);
+
+    if let Some(log_dir) = &config.log_path {
+        std::fs::write(log_dir.join("effective-config.toml"), &effective_config)?;
+    }
+
+    Ok(())
+}
+
+/// Appender for `trace.log`, split according to `log_rotation` so multi-day
+/// campaigns don't end up with a single multi-gigabyte file
+fn create_trace_appender(config: &RunnerConfig, log_dir: &Path) -> Result<Box<dyn Write + Send>> {
+    let appender: Box<dyn Write + Send> = match config.log_rotation {
+        LogRotation::Never => Box::new(tracing_appender::rolling::never(log_dir, "trace.log")),
+        LogRotation::Hourly => Box::new(tracing_appender::rolling::hourly(log_dir, "trace.log")),
+        LogRotation::Daily => Box::new(tracing_appender::rolling::daily(log_dir, "trace.log")),
+        LogRotation::Round => Box::new(RotatingFileWriter::per_round(log_dir, "trace.log")?),
+        LogRotation::Size => Box::new(RotatingFileWriter::by_size(
+            log_dir,
+            "trace.log",
+            config.log_rotation_max_bytes,
+        )?),
+    };
+    Ok(appender)
+}
+
 /// Print final statistics when the fuzzer completes
-fn print_final_stats(fuzzer_stats: &Arc<std::sync::Mutex<FuzzerStats>>) {
-    let stats = get_tui_stats(fuzzer_stats);
+fn print_final_stats(ctx: &GlobalContext) {
+    let stats = get_tui_stats(&ctx.fuzzer_stats);
 
     println!("\n{}", "=".repeat(60));
     println!("🎯 DataFusion Fuzzer - Final Statistics");
@@ -177,6 +448,13 @@ fn print_final_stats(fuzzer_stats: &Arc<std::sync::Mutex<FuzzerStats>>) {
     println!("  • Queries Executed: {}", stats.queries_executed);
     println!("  • Query Success Rate: {:.2}%", stats.success_rate);
     println!("  • Queries Per Second: {:.2}", stats.queries_per_second);
+    println!("  • Findings: {}", stats.findings);
+    if ctx.findings.has_baseline() {
+        println!(
+            "  • Findings Not in the Baseline: {}",
+            ctx.findings.new_findings_count()
+        );
+    }
     println!(
         "  • Slow Queries (>=90% of timeout): {} ({:.2}%)",
         stats.queries_slow,
@@ -187,6 +465,21 @@ fn print_final_stats(fuzzer_stats: &Arc<std::sync::Mutex<FuzzerStats>>) {
         }
     );
 
+    if ctx.runner_config.retry_timeouts {
+        println!(
+            "  • Timeouts Cleared on Retry: {}",
+            ctx.fuzzer_stats.lock().unwrap().timeouts_cleared_on_retry
+        );
+    }
+
+    if let Some(max_result_rows) = ctx.runner_config.max_result_rows {
+        println!(
+            "  • Results Truncated to {} Rows: {}",
+            max_result_rows,
+            ctx.fuzzer_stats.lock().unwrap().truncated_results
+        );
+    }
+
     let total_secs = stats.running_time_secs;
     let hours = (total_secs / 3600.0) as u64;
     let minutes = ((total_secs % 3600.0) / 60.0) as u64;
@@ -199,6 +492,10 @@ fn print_final_stats(fuzzer_stats: &Arc<std::sync::Mutex<FuzzerStats>>) {
     } else {
         println!("  • Total Runtime: {:.2}s", seconds);
     }
+    println!(
+        "  • DataFusion Version: {}",
+        ctx.runner_config.datafusion_version()
+    );
 
     // Display query runtime statistics if available
     if let Some(ref runtime_stats) = stats.query_runtime_stats {
@@ -210,7 +507,10 @@ fn print_final_stats(fuzzer_stats: &Arc<std::sync::Mutex<FuzzerStats>>) {
         println!("  • 99th percentile: {:.2}ms", runtime_stats.p99_ms);
 
         // Display the slowest query
-        println!("\n🐌 Slowest Query ({:.2}ms):", runtime_stats.slowest_ms);
+        println!(
+            "\n🐌 Slowest Query ({:.2}ms, id {}):",
+            runtime_stats.slowest_ms, runtime_stats.slowest_query_id
+        );
         println!("{}", "-".repeat(40));
         for line in runtime_stats.slowest_query.lines() {
             println!("  {}", line);
@@ -218,6 +518,78 @@ fn print_final_stats(fuzzer_stats: &Arc<std::sync::Mutex<FuzzerStats>>) {
         println!("{}", "-".repeat(40));
     }
 
+    if let Some(report) = ctx
+        .fuzzer_stats
+        .lock()
+        .unwrap()
+        .plan_complexity
+        .format_display()
+    {
+        println!("\n{}", report);
+    }
+
+    if let Some(report) = ctx
+        .fuzzer_stats
+        .lock()
+        .unwrap()
+        .phase_times
+        .format_display()
+    {
+        println!("\n{}", report);
+    }
+
+    if let Some(report) = ctx.fuzzer_stats.lock().unwrap().quarantine.format_display() {
+        println!("\n{}", report);
+    }
+
+    if let Some(report) = ctx
+        .fuzzer_stats
+        .lock()
+        .unwrap()
+        .known_issues
+        .format_display()
+    {
+        println!("\n{}", report);
+    }
+
+    if let Some(report) = ctx
+        .fuzzer_stats
+        .lock()
+        .unwrap()
+        .coverage
+        .format_display(&ctx.runner_config.coverage_targets)
+    {
+        println!("\n{}", report);
+    }
+
+    if let Some(report) = ctx
+        .fuzzer_stats
+        .lock()
+        .unwrap()
+        .resource_usage
+        .format_display()
+    {
+        println!("\n{}", report);
+    }
+
+    if let Some(report) = ctx
+        .fuzzer_stats
+        .lock()
+        .unwrap()
+        .memory_trend
+        .format_display()
+    {
+        println!("\n{}", report);
+    }
+
+    if let Some(report) = ctx
+        .seed_scheduler
+        .as_ref()
+        .and_then(|scheduler| scheduler.lock().unwrap().format_display())
+    {
+        println!("\n{}", report);
+    }
+
     if !stats.recent_query.is_empty() {
         println!("\n🔍 Most Recent Query:");
         println!("{}", "-".repeat(40));
@@ -227,9 +599,43 @@ fn print_final_stats(fuzzer_stats: &Arc<std::sync::Mutex<FuzzerStats>>) {
         println!("{}", "-".repeat(40));
     }
 
+    print_whitelist_report(ctx);
+
     println!("{}", "=".repeat(60));
-    println!("✅ Fuzzing completed successfully!");
+    match ctx.findings.stop_reason() {
+        Some(reason) => println!("⛔ Fuzzing stopped early: {}", reason),
+        None => println!("✅ Fuzzing completed successfully!"),
+    }
     println!(
         "⚠️  The program might be stuck if there are timed out queries. Please press Ctrl+C to exit."
     );
 }
+
+/// Print how often each error whitelist pattern matched, so unused or expired
+/// suppressions can be cleaned up
+fn print_whitelist_report(ctx: &GlobalContext) {
+    let today = chrono::Local::now().date_naive();
+    let report = ctx.error_whitelist.hit_report(5, today);
+
+    println!("\n🧹 Error Whitelist:");
+    if report.top_matchers.is_empty() {
+        println!("  • No whitelisted errors");
+    } else {
+        println!("  • Top matching patterns:");
+        for (pattern, hits) in &report.top_matchers {
+            println!("    - {} hits: {}", hits, pattern);
+        }
+    }
+
+    println!("  • Unused patterns: {}", report.unused.len());
+    for pattern in &report.unused {
+        println!("    - {}", pattern);
+    }
+
+    if !report.expired.is_empty() {
+        println!("  • Expired patterns (upstream issue may be fixed):");
+        for pattern in &report.expired {
+            println!("    - {}", pattern);
+        }
+    }
+}
@@ -0,0 +1,363 @@
+//! A reference interpreter for a subset of the fuzzer's `BaseExpr` trees.
+//!
+//! Expressions combine `BIGINT` and `BOOLEAN` columns and literals with the
+//! arithmetic, comparison and logical `BaseExpr`s. Semantics follow SQL:
+//!
+//! - NULL operands make arithmetic and comparisons NULL, except for
+//!   `IS [NOT] DISTINCT FROM`, which treat NULL as a value.
+//! - AND, OR and NOT use three-valued logic, `IS NULL` is never NULL.
+//! - Division and modulo truncate toward zero, the sign of `%` is the sign of
+//!   the dividend.
+//! - Overflow and division by zero have no specified result: DataFusion may
+//!   wrap, fail, or fold the expression away. The whole expression is then
+//!   undefined and not checked.
+
+use datafusion::common::Column;
+use datafusion::prelude::Expr;
+use datafusion::scalar::ScalarValue;
+use rand::Rng;
+use rand::rngs::StdRng;
+
+use crate::common::FuzzerDataType;
+use crate::common::value_generator::{GeneratedValue, ValueGenerationConfig, generate_value};
+use crate::query_generator::expr_def::BaseExpr;
+
+/// Types of interpreted expressions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ValueType {
+    Int64,
+    Boolean,
+}
+
+impl ValueType {
+    fn fuzzer_type(self) -> FuzzerDataType {
+        match self {
+            Self::Int64 => FuzzerDataType::Int64,
+            Self::Boolean => FuzzerDataType::Boolean,
+        }
+    }
+
+    pub(crate) fn sql_type(self) -> &'static str {
+        match self {
+            Self::Int64 => "BIGINT",
+            Self::Boolean => "BOOLEAN",
+        }
+    }
+}
+
+/// Value of an interpreted expression, `None` inside is NULL
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Value {
+    Int64(Option<i64>),
+    Boolean(Option<bool>),
+}
+
+impl Value {
+    fn from_generated(value: &GeneratedValue, value_type: ValueType) -> Self {
+        match (value, value_type) {
+            (GeneratedValue::Int64(v), _) => Self::Int64(Some(*v)),
+            (GeneratedValue::Boolean(v), _) => Self::Boolean(Some(*v)),
+            (_, ValueType::Int64) => Self::Int64(None),
+            (_, ValueType::Boolean) => Self::Boolean(None),
+        }
+    }
+
+    pub(crate) fn to_scalar_value(self) -> ScalarValue {
+        match self {
+            Self::Int64(v) => ScalarValue::Int64(v),
+            Self::Boolean(v) => ScalarValue::Boolean(v),
+        }
+    }
+
+    fn is_null(self) -> bool {
+        matches!(self, Self::Int64(None) | Self::Boolean(None))
+    }
+}
+
+/// An expression the interpreter can evaluate
+#[derive(Debug, Clone)]
+pub(crate) enum InterpretedExpr {
+    /// Column `c<idx>` of the input row
+    Column(usize),
+    Literal(Value),
+    Not(Box<InterpretedExpr>),
+    IsNull(Box<InterpretedExpr>),
+    Binary(BaseExpr, Box<InterpretedExpr>, Box<InterpretedExpr>),
+}
+
+impl InterpretedExpr {
+    /// The DataFusion expression, built like the expression generator does
+    pub(crate) fn to_expr(&self) -> Expr {
+        match self {
+            Self::Column(idx) => Expr::Column(Column::new_unqualified(column_name(*idx))),
+            Self::Literal(value) => Expr::Literal(value.to_scalar_value(), None),
+            Self::Not(expr) => Expr::Not(Box::new(expr.to_expr())),
+            Self::IsNull(expr) => expr.to_expr().is_null(),
+            Self::Binary(base_expr, left, right) => base_expr
+                .to_impl()
+                .build_expr(&[left.to_expr(), right.to_expr()]),
+        }
+    }
+
+    /// Evaluate on `row`, `None` if the result is undefined
+    pub(crate) fn evaluate(&self, row: &[Value]) -> Option<Value> {
+        match self {
+            Self::Column(idx) => row.get(*idx).copied(),
+            Self::Literal(value) => Some(*value),
+            Self::Not(expr) => match expr.evaluate(row)? {
+                Value::Boolean(value) => Some(Value::Boolean(value.map(|value| !value))),
+                Value::Int64(_) => None,
+            },
+            Self::IsNull(expr) => Some(Value::Boolean(Some(expr.evaluate(row)?.is_null()))),
+            Self::Binary(base_expr, left, right) => {
+                evaluate_binary(base_expr, left.evaluate(row)?, right.evaluate(row)?)
+            }
+        }
+    }
+}
+
+pub(crate) fn column_name(idx: usize) -> String {
+    format!("c{}", idx)
+}
+
+fn evaluate_binary(base_expr: &BaseExpr, left: Value, right: Value) -> Option<Value> {
+    use Value::{Boolean, Int64};
+
+    match (base_expr, left, right) {
+        (BaseExpr::And, Boolean(l), Boolean(r)) => Some(Boolean(match (l, r) {
+            (Some(false), _) | (_, Some(false)) => Some(false),
+            (Some(true), Some(true)) => Some(true),
+            _ => None,
+        })),
+        (BaseExpr::Or, Boolean(l), Boolean(r)) => Some(Boolean(match (l, r) {
+            (Some(true), _) | (_, Some(true)) => Some(true),
+            (Some(false), Some(false)) => Some(false),
+            _ => None,
+        })),
+        (BaseExpr::IsDistinctFrom, l, r) => Some(Boolean(Some(l != r))),
+        (BaseExpr::IsNotDistinctFrom, l, r) => Some(Boolean(Some(l == r))),
+        (_, l, r) if l.is_null() || r.is_null() => Some(match base_expr {
+            BaseExpr::Add | BaseExpr::Sub | BaseExpr::Mul | BaseExpr::Div | BaseExpr::Mod => {
+                Int64(None)
+            }
+            _ => Boolean(None),
+        }),
+        (BaseExpr::Add, Int64(Some(l)), Int64(Some(r))) => Some(Int64(Some(l.checked_add(r)?))),
+        (BaseExpr::Sub, Int64(Some(l)), Int64(Some(r))) => Some(Int64(Some(l.checked_sub(r)?))),
+        (BaseExpr::Mul, Int64(Some(l)), Int64(Some(r))) => Some(Int64(Some(l.checked_mul(r)?))),
+        // `checked_div` and `checked_rem` are `None` for division by zero and
+        // `i64::MIN / -1`
+        (BaseExpr::Div, Int64(Some(l)), Int64(Some(r))) => Some(Int64(Some(l.checked_div(r)?))),
+        (BaseExpr::Mod, Int64(Some(l)), Int64(Some(r))) => Some(Int64(Some(l.checked_rem(r)?))),
+        (base_expr, l, r) => {
+            let ordering = match (l, r) {
+                (Int64(Some(l)), Int64(Some(r))) => l.cmp(&r),
+                (Boolean(Some(l)), Boolean(Some(r))) => l.cmp(&r),
+                _ => return None,
+            };
+            let result = match base_expr {
+                BaseExpr::Eq => ordering.is_eq(),
+                BaseExpr::NotEq => ordering.is_ne(),
+                BaseExpr::Lt => ordering.is_lt(),
+                BaseExpr::LtEq => ordering.is_le(),
+                BaseExpr::Gt => ordering.is_gt(),
+                BaseExpr::GtEq => ordering.is_ge(),
+                _ => return None,
+            };
+            Some(Boolean(Some(result)))
+        }
+    }
+}
+
+/// Generator of random interpretable expressions over a row of `columns`
+pub(crate) struct InterpretedExprGenerator<'a> {
+    pub(crate) rng: &'a mut StdRng,
+    pub(crate) columns: &'a [ValueType],
+    pub(crate) value_config: &'a ValueGenerationConfig,
+    pub(crate) max_level: u32,
+    /// Also generate NOT, IS NULL and explicit NULL literals
+    pub(crate) three_valued: bool,
+}
+
+impl InterpretedExprGenerator<'_> {
+    /// A random value of `value_type`, possibly NULL
+    pub(crate) fn generate_value(&mut self, value_type: ValueType) -> Value {
+        let generated = generate_value(self.rng, &value_type.fuzzer_type(), self.value_config);
+        Value::from_generated(&generated, value_type)
+    }
+
+    pub(crate) fn generate_expr(&mut self, value_type: ValueType, level: u32) -> InterpretedExpr {
+        if level >= self.max_level || self.rng.random_bool(0.3) {
+            return self.generate_leaf(value_type);
+        }
+
+        if self.three_valued && value_type == ValueType::Boolean && self.rng.random_bool(0.25) {
+            return if self.rng.random_bool(0.5) {
+                InterpretedExpr::Not(Box::new(self.generate_expr(ValueType::Boolean, level + 1)))
+            } else {
+                let operand_type = if self.rng.random_bool(0.5) {
+                    ValueType::Int64
+                } else {
+                    ValueType::Boolean
+                };
+                InterpretedExpr::IsNull(Box::new(self.generate_expr(operand_type, level + 1)))
+            };
+        }
+
+        let (base_expr, operand_type) = match value_type {
+            ValueType::Int64 => {
+                let ops = [
+                    BaseExpr::Add,
+                    BaseExpr::Sub,
+                    BaseExpr::Mul,
+                    BaseExpr::Div,
+                    BaseExpr::Mod,
+                ];
+                (
+                    ops[self.rng.random_range(0..ops.len())].clone(),
+                    ValueType::Int64,
+                )
+            }
+            ValueType::Boolean => {
+                let ops = [
+                    BaseExpr::And,
+                    BaseExpr::Or,
+                    BaseExpr::Eq,
+                    BaseExpr::NotEq,
+                    BaseExpr::Lt,
+                    BaseExpr::LtEq,
+                    BaseExpr::Gt,
+                    BaseExpr::GtEq,
+                    BaseExpr::IsDistinctFrom,
+                    BaseExpr::IsNotDistinctFrom,
+                ];
+                let base_expr = ops[self.rng.random_range(0..ops.len())].clone();
+                let operand_type = match base_expr {
+                    BaseExpr::And | BaseExpr::Or => ValueType::Boolean,
+                    _ if self.rng.random_bool(0.8) => ValueType::Int64,
+                    _ => ValueType::Boolean,
+                };
+                (base_expr, operand_type)
+            }
+        };
+
+        InterpretedExpr::Binary(
+            base_expr,
+            Box::new(self.generate_expr(operand_type, level + 1)),
+            Box::new(self.generate_expr(operand_type, level + 1)),
+        )
+    }
+
+    fn generate_leaf(&mut self, value_type: ValueType) -> InterpretedExpr {
+        let columns: Vec<usize> = (0..self.columns.len())
+            .filter(|&idx| self.columns[idx] == value_type)
+            .collect();
+        if self.three_valued && self.rng.random_bool(0.2) {
+            return InterpretedExpr::Literal(match value_type {
+                ValueType::Int64 => Value::Int64(None),
+                ValueType::Boolean => Value::Boolean(None),
+            });
+        }
+        if !columns.is_empty() && self.rng.random_bool(0.6) {
+            return InterpretedExpr::Column(columns[self.rng.random_range(0..columns.len())]);
+        }
+        InterpretedExpr::Literal(self.generate_value(value_type))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::util::to_sql_string;
+
+    fn int(value: i64) -> Box<InterpretedExpr> {
+        Box::new(InterpretedExpr::Literal(Value::Int64(Some(value))))
+    }
+
+    fn null_int() -> Box<InterpretedExpr> {
+        Box::new(InterpretedExpr::Literal(Value::Int64(None)))
+    }
+
+    fn binary(
+        base_expr: BaseExpr,
+        left: Box<InterpretedExpr>,
+        right: Box<InterpretedExpr>,
+    ) -> InterpretedExpr {
+        InterpretedExpr::Binary(base_expr, left, right)
+    }
+
+    #[test]
+    fn evaluates_with_sql_semantics() {
+        let eval = |expr: InterpretedExpr| expr.evaluate(&[]);
+
+        assert_eq!(
+            eval(binary(BaseExpr::Mod, int(-7), int(3))),
+            Some(Value::Int64(Some(-1)))
+        );
+        assert_eq!(
+            eval(binary(BaseExpr::Div, int(-7), int(2))),
+            Some(Value::Int64(Some(-3)))
+        );
+        assert_eq!(
+            eval(binary(BaseExpr::Add, int(1), null_int())),
+            Some(Value::Int64(None))
+        );
+        assert_eq!(
+            eval(binary(BaseExpr::Lt, null_int(), int(1))),
+            Some(Value::Boolean(None))
+        );
+        assert_eq!(
+            eval(binary(BaseExpr::IsDistinctFrom, null_int(), null_int())),
+            Some(Value::Boolean(Some(false)))
+        );
+
+        let unknown = Box::new(binary(BaseExpr::Eq, null_int(), int(1)));
+        let false_value = Box::new(binary(BaseExpr::Eq, int(0), int(1)));
+        assert_eq!(
+            eval(binary(BaseExpr::And, unknown.clone(), false_value.clone())),
+            Some(Value::Boolean(Some(false)))
+        );
+        assert_eq!(
+            eval(binary(BaseExpr::Or, unknown, false_value)),
+            Some(Value::Boolean(None))
+        );
+    }
+
+    #[test]
+    fn not_and_is_null_follow_three_valued_logic() {
+        let unknown = Box::new(binary(BaseExpr::Lt, null_int(), int(1)));
+        assert_eq!(
+            InterpretedExpr::Not(unknown.clone()).evaluate(&[]),
+            Some(Value::Boolean(None))
+        );
+        assert_eq!(
+            InterpretedExpr::IsNull(unknown).evaluate(&[]),
+            Some(Value::Boolean(Some(true)))
+        );
+        assert_eq!(
+            InterpretedExpr::IsNull(int(0)).evaluate(&[]),
+            Some(Value::Boolean(Some(false)))
+        );
+    }
+
+    #[test]
+    fn overflow_and_division_by_zero_are_undefined() {
+        let overflow = binary(BaseExpr::Mul, int(i64::MAX), int(2));
+        assert_eq!(overflow.evaluate(&[]), None);
+        assert_eq!(binary(BaseExpr::Mod, int(1), int(0)).evaluate(&[]), None);
+
+        // Undefined operands make the whole expression undefined
+        let compared = binary(BaseExpr::IsNotDistinctFrom, Box::new(overflow), null_int());
+        assert_eq!(compared.evaluate(&[]), None);
+    }
+
+    #[test]
+    fn builds_the_generators_expressions() {
+        let expr = binary(BaseExpr::Add, Box::new(InterpretedExpr::Column(0)), int(1));
+        assert!(to_sql_string(&expr.to_expr()).unwrap().contains("c0 + 1"));
+        assert_eq!(
+            expr.evaluate(&[Value::Int64(Some(2))]),
+            Some(Value::Int64(Some(3)))
+        );
+    }
+}
@@ -0,0 +1,317 @@
+//! Ground truth for oracles: predicates over tables whose inserted values are
+//! known, evaluated in Rust instead of by DataFusion.
+
+use std::cmp::Ordering;
+use std::sync::Arc;
+
+use datafusion::common::Column;
+use datafusion::logical_expr::{Between, BinaryExpr, Operator};
+use datafusion::prelude::Expr;
+use datafusion::scalar::ScalarValue;
+use datafusion::sql::TableReference;
+use rand::Rng;
+use rand::rngs::StdRng;
+
+use crate::common::value_summary::ColumnValueSummary;
+use crate::common::{FuzzerDataType, LogicalColumn, LogicalTable, Result, fuzzer_err};
+use crate::datasource_generator::file_dataset::DatasetFormat;
+use crate::fuzz_context::GlobalContext;
+
+/// Maximum number of simple predicates combined with AND/OR
+const MAX_PREDICATE_TERMS: usize = 3;
+
+/// Registered tables whose inserted values are known, i.e. no views
+pub(crate) fn tables_with_known_values(ctx: &GlobalContext) -> Vec<Arc<LogicalTable>> {
+    ctx.runtime_context
+        .tables()
+        .values()
+        .filter(|table| table.row_count().is_some())
+        .cloned()
+        .collect()
+}
+
+/// Registered tables with known values that are read from Parquet files, see
+/// `dataset_format`
+pub(crate) fn parquet_backed_tables(ctx: &GlobalContext) -> Vec<Arc<LogicalTable>> {
+    tables_with_known_values(ctx)
+        .into_iter()
+        .filter(|table| {
+            table
+                .files
+                .as_ref()
+                .is_some_and(|files| files.format == DatasetFormat::Parquet)
+        })
+        .collect()
+}
+
+/// Whether comparisons of values of `data_type` in Rust agree with SQL.
+/// Floats (NaN, -0.0), decimals and temporal types with time zones are left
+/// out.
+pub(crate) fn is_comparable(data_type: &FuzzerDataType) -> bool {
+    matches!(
+        data_type,
+        FuzzerDataType::Int32
+            | FuzzerDataType::Int64
+            | FuzzerDataType::UInt32
+            | FuzzerDataType::UInt64
+            | FuzzerDataType::Boolean
+            | FuzzerDataType::Date32
+            | FuzzerDataType::String
+    )
+}
+
+/// Whether values of `data_type` have a total order that both `MIN`/`MAX`
+/// and `ORDER BY` use. Intervals are left out, `1 month` and `30 days` are
+/// incomparable.
+pub(crate) fn is_orderable(data_type: &FuzzerDataType) -> bool {
+    !matches!(data_type, FuzzerDataType::IntervalMonthDayNano)
+}
+
+/// Whether `SUM` and `AVG` accept values of `data_type`
+pub(crate) fn is_numeric(data_type: &FuzzerDataType) -> bool {
+    matches!(
+        data_type,
+        FuzzerDataType::Int32
+            | FuzzerDataType::Int64
+            | FuzzerDataType::UInt32
+            | FuzzerDataType::UInt64
+            | FuzzerDataType::Float32
+            | FuzzerDataType::Float64
+            | FuzzerDataType::Decimal
+    )
+}
+
+pub(crate) fn generate_predicate(rng: &mut StdRng, table: &LogicalTable) -> Expr {
+    let num_terms = rng.random_range(1..=MAX_PREDICATE_TERMS);
+    let mut predicate = generate_simple_predicate(rng, table);
+    for _ in 1..num_terms {
+        let term = generate_simple_predicate(rng, table);
+        predicate = if rng.random_bool(0.5) {
+            predicate.and(term)
+        } else {
+            predicate.or(term)
+        };
+    }
+    if rng.random_bool(0.2) {
+        predicate = Expr::Not(Box::new(predicate));
+    }
+    predicate
+}
+
+/// Reference to `column` of `table`
+pub(crate) fn column_expr(table: &LogicalTable, column: &LogicalColumn) -> Expr {
+    Expr::Column(Column::new(
+        Some(TableReference::bare(table.name.as_str())),
+        column.name.as_str(),
+    ))
+}
+
+/// A predicate on a single column
+fn generate_simple_predicate(rng: &mut StdRng, table: &LogicalTable) -> Expr {
+    let logical_column = &table.columns[rng.random_range(0..table.columns.len())];
+    let column = column_expr(table, logical_column);
+    let samples = logical_column
+        .value_summary
+        .as_deref()
+        .map(|summary| summary.samples.as_slice())
+        .unwrap_or_default();
+
+    if samples.is_empty() || !is_comparable(&logical_column.data_type) || rng.random_bool(0.2) {
+        return if rng.random_bool(0.5) {
+            column.is_null()
+        } else {
+            column.is_not_null()
+        };
+    }
+
+    let pick_sample =
+        |rng: &mut StdRng| Expr::Literal(samples[rng.random_range(0..samples.len())].clone(), None);
+    match rng.random_range(0..3) {
+        0 => {
+            let ops = [
+                Operator::Eq,
+                Operator::NotEq,
+                Operator::Lt,
+                Operator::LtEq,
+                Operator::Gt,
+                Operator::GtEq,
+            ];
+            let op = ops[rng.random_range(0..ops.len())];
+            Expr::BinaryExpr(BinaryExpr::new(
+                Box::new(column),
+                op,
+                Box::new(pick_sample(rng)),
+            ))
+        }
+        1 => {
+            let low = pick_sample(rng);
+            let high = pick_sample(rng);
+            column.between(low, high)
+        }
+        _ => {
+            let list_len = rng.random_range(1..=samples.len().min(3));
+            let list = (0..list_len).map(|_| pick_sample(rng)).collect();
+            column.in_list(list, rng.random_bool(0.3))
+        }
+    }
+}
+
+/// Rows of `table` for which `predicate` is true
+pub(crate) fn matching_rows(predicate: &Expr, table: &LogicalTable) -> Result<Vec<usize>> {
+    let summaries: Vec<(&str, &ColumnValueSummary)> = table
+        .columns
+        .iter()
+        .map(|column| {
+            column
+                .value_summary
+                .as_deref()
+                .map(|summary| (column.name.as_str(), summary))
+                .ok_or_else(|| fuzzer_err(&format!("Values of {} are unknown", column.name)))
+        })
+        .collect::<Result<_>>()?;
+    let row_count = table.row_count().unwrap_or_default() as usize;
+
+    let mut rows = Vec::new();
+    for row in 0..row_count {
+        let value_of = |name: &str| {
+            summaries
+                .iter()
+                .find(|(column_name, _)| *column_name == name)
+                .and_then(|(_, summary)| summary.values.get(row))
+                .ok_or_else(|| fuzzer_err(&format!("Row {} of {} is unknown", row, name)))
+        };
+        if evaluate(predicate, &value_of)? == Some(true) {
+            rows.push(row);
+        }
+    }
+    Ok(rows)
+}
+
+/// Evaluate a predicate built by `generate_predicate` on one row, `None`
+/// is SQL's NULL
+fn evaluate<'a>(
+    predicate: &Expr,
+    value_of: &impl Fn(&str) -> Result<&'a ScalarValue>,
+) -> Result<Option<bool>> {
+    let operand = |expr: &Expr| -> Result<ScalarValue> {
+        match expr {
+            Expr::Column(column) => value_of(&column.name).cloned(),
+            Expr::Literal(value, _) => Ok(value.clone()),
+            other => Err(fuzzer_err(&format!("Unexpected operand {}", other))),
+        }
+    };
+
+    Ok(match predicate {
+        Expr::Not(expr) => evaluate(expr, value_of)?.map(|value| !value),
+        Expr::IsNull(expr) => Some(operand(expr)?.is_null()),
+        Expr::IsNotNull(expr) => Some(!operand(expr)?.is_null()),
+        Expr::BinaryExpr(BinaryExpr { left, op, right }) => match op {
+            Operator::And => match (evaluate(left, value_of)?, evaluate(right, value_of)?) {
+                (Some(false), _) | (_, Some(false)) => Some(false),
+                (Some(true), Some(true)) => Some(true),
+                _ => None,
+            },
+            Operator::Or => match (evaluate(left, value_of)?, evaluate(right, value_of)?) {
+                (Some(true), _) | (_, Some(true)) => Some(true),
+                (Some(false), Some(false)) => Some(false),
+                _ => None,
+            },
+            Operator::Eq
+            | Operator::NotEq
+            | Operator::Lt
+            | Operator::LtEq
+            | Operator::Gt
+            | Operator::GtEq => {
+                compare(&operand(left)?, &operand(right)?)?.map(|ordering| match op {
+                    Operator::Eq => ordering == Ordering::Equal,
+                    Operator::NotEq => ordering != Ordering::Equal,
+                    Operator::Lt => ordering == Ordering::Less,
+                    Operator::LtEq => ordering != Ordering::Greater,
+                    Operator::Gt => ordering == Ordering::Greater,
+                    _ => ordering != Ordering::Less,
+                })
+            }
+            other => {
+                return Err(fuzzer_err(&format!(
+                    "Cannot evaluate operator {} in Rust",
+                    other
+                )));
+            }
+        },
+        Expr::Between(Between {
+            expr,
+            negated,
+            low,
+            high,
+        }) => {
+            let value = operand(expr)?;
+            let above_low = compare(&value, &operand(low)?)?.map(|o| o != Ordering::Less);
+            let below_high = compare(&value, &operand(high)?)?.map(|o| o != Ordering::Greater);
+            let between = match (above_low, below_high) {
+                (Some(false), _) | (_, Some(false)) => Some(false),
+                (Some(true), Some(true)) => Some(true),
+                _ => None,
+            };
+            between.map(|between| between != *negated)
+        }
+        Expr::InList(in_list) => {
+            let value = operand(&in_list.expr)?;
+            let mut found = Some(false);
+            for item in &in_list.list {
+                match compare(&value, &operand(item)?)? {
+                    Some(Ordering::Equal) => {
+                        found = Some(true);
+                        break;
+                    }
+                    Some(_) => {}
+                    None => found = None,
+                }
+            }
+            found.map(|found| found != in_list.negated)
+        }
+        other => {
+            return Err(fuzzer_err(&format!(
+                "Cannot evaluate predicate {} in Rust",
+                other
+            )));
+        }
+    })
+}
+
+/// `None` if either value is NULL
+pub(crate) fn compare(left: &ScalarValue, right: &ScalarValue) -> Result<Option<Ordering>> {
+    if left.is_null() || right.is_null() {
+        return Ok(None);
+    }
+    left.partial_cmp(right)
+        .map(Some)
+        .ok_or_else(|| fuzzer_err(&format!("Cannot compare {} with {}", left, right)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracle::test_helpers::table_with_int_values;
+
+    fn c0() -> Expr {
+        Expr::Column(Column::new(Some(TableReference::bare("t1")), "c0"))
+    }
+
+    fn int(value: i64) -> Expr {
+        Expr::Literal(ScalarValue::Int64(Some(value)), None)
+    }
+
+    #[test]
+    fn evaluates_predicates_with_three_valued_logic() {
+        let table = table_with_int_values(&[Some(1), Some(2), Some(3), None]);
+
+        let rows = |predicate: Expr| matching_rows(&predicate, &table).unwrap();
+        assert_eq!(rows(c0().gt(int(1))), vec![1, 2]);
+        assert_eq!(rows(Expr::Not(Box::new(c0().gt(int(1))))), vec![0]);
+        assert_eq!(rows(c0().is_null()), vec![3]);
+        assert_eq!(rows(c0().between(int(2), int(3))), vec![1, 2]);
+        assert_eq!(rows(c0().in_list(vec![int(1), int(3)], true)), vec![1]);
+        assert_eq!(rows(c0().lt(int(2)).or(c0().is_null())), vec![0, 3]);
+        assert!(rows(c0().eq(int(9)).and(c0().is_null())).is_empty());
+    }
+}
@@ -1,11 +1,51 @@
 // Oracle module - provides testing oracles for query consistency and correctness
 
+pub(crate) mod expr_interpreter;
+pub(crate) mod ground_truth;
 pub(crate) mod oracle_common;
+pub mod oracle_impl_aggregate;
+pub mod oracle_impl_array_function;
+pub mod oracle_impl_cardinality;
+pub mod oracle_impl_catalog;
+pub mod oracle_impl_collation;
+pub mod oracle_impl_concurrent_ddl;
+pub mod oracle_impl_concurrent_queries;
+pub mod oracle_impl_count_consistency;
+pub mod oracle_impl_dataframe;
+pub mod oracle_impl_dataframe_chain;
+pub mod oracle_impl_deep_nesting;
+pub mod oracle_impl_engine_diff;
+pub mod oracle_impl_file_schema_evolution;
+pub mod oracle_impl_filter_pushdown;
+pub mod oracle_impl_float_group_by;
+pub mod oracle_impl_grouping_sets;
+pub mod oracle_impl_interpreter;
+pub mod oracle_impl_interval;
+pub mod oracle_impl_join;
+pub mod oracle_impl_memory_limit;
+pub mod oracle_impl_metadata;
+pub mod oracle_impl_min_max_order_by;
 pub mod oracle_impl_nested_queries;
 pub mod oracle_impl_no_crash;
+pub mod oracle_impl_null_propagation;
+pub mod oracle_impl_overflow;
+pub mod oracle_impl_parquet_pruning;
+pub mod oracle_impl_session_isolation;
+pub mod oracle_impl_sql_mutation;
+pub mod oracle_impl_statement_batch;
+pub mod oracle_impl_statistics;
+pub mod oracle_impl_sum_avg;
+pub mod oracle_impl_template;
+pub mod oracle_impl_three_valued_logic;
+pub mod oracle_impl_timezone;
+pub mod oracle_impl_tiny_batches;
 pub mod oracle_impl_tlp_having;
 pub mod oracle_impl_tlp_where;
+pub mod oracle_impl_unparser_dialect;
+pub mod oracle_impl_wide_projection;
+pub mod oracle_impl_window_aggregate;
 pub mod oracle_trait;
+pub mod result_compare;
 #[cfg(test)]
 pub(crate) mod test_helpers;
 
@@ -16,11 +56,48 @@ use serde::{Deserialize, Serialize};
 use crate::fuzz_context::GlobalContext;
 
 // Re-export main types and traits
+pub use oracle_impl_aggregate::AggregateOracle;
+pub use oracle_impl_array_function::ArrayFunctionOracle;
+pub use oracle_impl_cardinality::CardinalityOracle;
+pub use oracle_impl_catalog::CatalogOracle;
+pub use oracle_impl_collation::CollationOracle;
+pub use oracle_impl_concurrent_ddl::ConcurrentDdlOracle;
+pub use oracle_impl_concurrent_queries::ConcurrentQueriesOracle;
+pub use oracle_impl_count_consistency::CountConsistencyOracle;
+pub use oracle_impl_dataframe::DataFrameOracle;
+pub use oracle_impl_dataframe_chain::DataFrameChainOracle;
+pub use oracle_impl_deep_nesting::DeepNestingOracle;
+pub use oracle_impl_engine_diff::EngineDiffOracle;
+pub use oracle_impl_file_schema_evolution::FileSchemaEvolutionOracle;
+pub use oracle_impl_filter_pushdown::FilterPushdownOracle;
+pub use oracle_impl_float_group_by::FloatGroupByOracle;
+pub use oracle_impl_grouping_sets::GroupingSetsOracle;
+pub use oracle_impl_interpreter::InterpreterOracle;
+pub use oracle_impl_interval::IntervalOracle;
+pub use oracle_impl_join::JoinOracle;
+pub use oracle_impl_memory_limit::MemoryLimitOracle;
+pub use oracle_impl_metadata::MetadataOracle;
+pub use oracle_impl_min_max_order_by::MinMaxOrderByOracle;
 pub use oracle_impl_nested_queries::NestedQueriesOracle;
 pub use oracle_impl_no_crash::NoCrashOracle;
+pub use oracle_impl_null_propagation::NullPropagationOracle;
+pub use oracle_impl_overflow::OverflowOracle;
+pub use oracle_impl_parquet_pruning::ParquetPruningOracle;
+pub use oracle_impl_session_isolation::SessionIsolationOracle;
+pub use oracle_impl_sql_mutation::SqlMutationOracle;
+pub use oracle_impl_statement_batch::StatementBatchOracle;
+pub use oracle_impl_statistics::StatisticsOracle;
+pub use oracle_impl_sum_avg::SumAvgOracle;
+pub use oracle_impl_template::TemplateOracle;
+pub use oracle_impl_three_valued_logic::ThreeValuedLogicOracle;
+pub use oracle_impl_timezone::TimezoneOracle;
+pub use oracle_impl_tiny_batches::TinyBatchesOracle;
 pub use oracle_impl_tlp_having::TlpHavingOracle;
 pub use oracle_impl_tlp_where::TlpWhereOracle;
-pub use oracle_trait::{Oracle, QueryContext, QueryExecutionResult};
+pub use oracle_impl_unparser_dialect::UnparserDialectOracle;
+pub use oracle_impl_wide_projection::WideProjectionOracle;
+pub use oracle_impl_window_aggregate::WindowAggregateOracle;
+pub use oracle_trait::{Oracle, QueryContext, QueryExecutionResult, TruncatedResult};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ConfiguredOracle {
@@ -32,15 +109,138 @@ pub enum ConfiguredOracle {
     TlpWhere,
     #[serde(rename = "TlpHaving", alias = "TlpHavingOracle")]
     TlpHaving,
+    #[serde(rename = "MemoryLimit", alias = "MemoryLimitOracle")]
+    MemoryLimit,
+    #[serde(rename = "FlightSql", alias = "FlightSqlOracle")]
+    FlightSql,
+    #[serde(rename = "EngineDiff", alias = "EngineDiffOracle")]
+    EngineDiff,
+    #[serde(rename = "Cardinality", alias = "CardinalityOracle")]
+    Cardinality,
+    #[serde(rename = "CountConsistency", alias = "CountConsistencyOracle")]
+    CountConsistency,
+    #[serde(rename = "Aggregate", alias = "AggregateOracle")]
+    Aggregate,
+    #[serde(rename = "MinMaxOrderBy", alias = "MinMaxOrderByOracle")]
+    MinMaxOrderBy,
+    #[serde(rename = "SumAvg", alias = "SumAvgOracle")]
+    SumAvg,
+    #[serde(rename = "WindowAggregate", alias = "WindowAggregateOracle")]
+    WindowAggregate,
+    #[serde(rename = "GroupingSets", alias = "GroupingSetsOracle")]
+    GroupingSets,
+    #[serde(rename = "Join", alias = "JoinOracle")]
+    Join,
+    #[serde(rename = "Interpreter", alias = "InterpreterOracle")]
+    Interpreter,
+    #[serde(rename = "ThreeValuedLogic", alias = "ThreeValuedLogicOracle")]
+    ThreeValuedLogic,
+    #[serde(rename = "Overflow", alias = "OverflowOracle")]
+    Overflow,
+    #[serde(rename = "NullPropagation", alias = "NullPropagationOracle")]
+    NullPropagation,
+    #[serde(rename = "ArrayFunction", alias = "ArrayFunctionOracle")]
+    ArrayFunction,
+    #[serde(rename = "Timezone", alias = "TimezoneOracle")]
+    Timezone,
+    #[serde(rename = "Interval", alias = "IntervalOracle")]
+    Interval,
+    #[serde(rename = "Collation", alias = "CollationOracle")]
+    Collation,
+    #[serde(rename = "FloatGroupBy", alias = "FloatGroupByOracle")]
+    FloatGroupBy,
+    #[serde(rename = "Metadata", alias = "MetadataOracle")]
+    Metadata,
+    #[serde(rename = "Statistics", alias = "StatisticsOracle")]
+    Statistics,
+    #[serde(rename = "ParquetPruning", alias = "ParquetPruningOracle")]
+    ParquetPruning,
+    #[serde(rename = "FilterPushdown", alias = "FilterPushdownOracle")]
+    FilterPushdown,
+    #[serde(rename = "FileSchemaEvolution", alias = "FileSchemaEvolutionOracle")]
+    FileSchemaEvolution,
+    #[serde(rename = "DataFrame", alias = "DataFrameOracle")]
+    DataFrame,
+    #[serde(rename = "DataFrameChain", alias = "DataFrameChainOracle")]
+    DataFrameChain,
+    #[serde(rename = "TinyBatches", alias = "TinyBatchesOracle")]
+    TinyBatches,
+    #[serde(rename = "Catalog", alias = "CatalogOracle")]
+    Catalog,
+    #[serde(rename = "SessionIsolation", alias = "SessionIsolationOracle")]
+    SessionIsolation,
+    #[serde(rename = "ConcurrentQueries", alias = "ConcurrentQueriesOracle")]
+    ConcurrentQueries,
+    #[serde(rename = "ConcurrentDdl", alias = "ConcurrentDdlOracle")]
+    ConcurrentDdl,
+    #[serde(rename = "StatementBatch", alias = "StatementBatchOracle")]
+    StatementBatch,
+    #[serde(rename = "UnparserDialect", alias = "UnparserDialectOracle")]
+    UnparserDialect,
+    #[serde(rename = "SqlMutation", alias = "SqlMutationOracle")]
+    SqlMutation,
+    #[serde(rename = "DeepNesting", alias = "DeepNestingOracle")]
+    DeepNesting,
+    #[serde(rename = "WideProjection", alias = "WideProjectionOracle")]
+    WideProjection,
+    #[serde(rename = "Template", alias = "TemplateOracle")]
+    Template,
 }
 
 impl ConfiguredOracle {
+    /// Whether the oracle only tests tables read from Parquet files, see
+    /// `dataset_format`
+    pub fn requires_parquet_datasets(self) -> bool {
+        matches!(
+            self,
+            Self::Statistics | Self::ParquetPruning | Self::FilterPushdown
+        )
+    }
+
     pub fn build(self, seed: u64, ctx: Arc<GlobalContext>) -> Box<dyn Oracle + Send> {
         match self {
             Self::NoCrash => Box::new(NoCrashOracle::new(seed, ctx)),
             Self::NestedQueries => Box::new(NestedQueriesOracle::new(seed, ctx)),
             Self::TlpWhere => Box::new(TlpWhereOracle::new(seed, ctx)),
             Self::TlpHaving => Box::new(TlpHavingOracle::new(seed, ctx)),
+            Self::MemoryLimit => Box::new(MemoryLimitOracle::new(seed, ctx)),
+            Self::FlightSql => Box::new(EngineDiffOracle::flight_sql(seed, ctx)),
+            Self::EngineDiff => Box::new(EngineDiffOracle::new(seed, ctx)),
+            Self::Cardinality => Box::new(CardinalityOracle::new(seed, ctx)),
+            Self::CountConsistency => Box::new(CountConsistencyOracle::new(seed, ctx)),
+            Self::Aggregate => Box::new(AggregateOracle::new(seed, ctx)),
+            Self::MinMaxOrderBy => Box::new(MinMaxOrderByOracle::new(seed, ctx)),
+            Self::SumAvg => Box::new(SumAvgOracle::new(seed, ctx)),
+            Self::WindowAggregate => Box::new(WindowAggregateOracle::new(seed, ctx)),
+            Self::GroupingSets => Box::new(GroupingSetsOracle::new(seed, ctx)),
+            Self::Join => Box::new(JoinOracle::new(seed, ctx)),
+            Self::Interpreter => Box::new(InterpreterOracle::new(seed, ctx)),
+            Self::ThreeValuedLogic => Box::new(ThreeValuedLogicOracle::new(seed, ctx)),
+            Self::Overflow => Box::new(OverflowOracle::new(seed, ctx)),
+            Self::NullPropagation => Box::new(NullPropagationOracle::new(seed, ctx)),
+            Self::ArrayFunction => Box::new(ArrayFunctionOracle::new(seed, ctx)),
+            Self::Timezone => Box::new(TimezoneOracle::new(seed, ctx)),
+            Self::Interval => Box::new(IntervalOracle::new(seed, ctx)),
+            Self::Collation => Box::new(CollationOracle::new(seed, ctx)),
+            Self::FloatGroupBy => Box::new(FloatGroupByOracle::new(seed, ctx)),
+            Self::Metadata => Box::new(MetadataOracle::new(seed, ctx)),
+            Self::Statistics => Box::new(StatisticsOracle::new(seed, ctx)),
+            Self::ParquetPruning => Box::new(ParquetPruningOracle::new(seed, ctx)),
+            Self::FilterPushdown => Box::new(FilterPushdownOracle::new(seed, ctx)),
+            Self::FileSchemaEvolution => Box::new(FileSchemaEvolutionOracle::new(seed, ctx)),
+            Self::DataFrame => Box::new(DataFrameOracle::new(seed, ctx)),
+            Self::DataFrameChain => Box::new(DataFrameChainOracle::new(seed, ctx)),
+            Self::TinyBatches => Box::new(TinyBatchesOracle::new(seed, ctx)),
+            Self::Catalog => Box::new(CatalogOracle::new(seed, ctx)),
+            Self::SessionIsolation => Box::new(SessionIsolationOracle::new(seed, ctx)),
+            Self::ConcurrentQueries => Box::new(ConcurrentQueriesOracle::new(seed, ctx)),
+            Self::ConcurrentDdl => Box::new(ConcurrentDdlOracle::new(seed, ctx)),
+            Self::StatementBatch => Box::new(StatementBatchOracle::new(seed, ctx)),
+            Self::UnparserDialect => Box::new(UnparserDialectOracle::new(seed, ctx)),
+            Self::SqlMutation => Box::new(SqlMutationOracle::new(seed, ctx)),
+            Self::DeepNesting => Box::new(DeepNestingOracle::new(seed, ctx)),
+            Self::WideProjection => Box::new(WideProjectionOracle::new(seed, ctx)),
+            Self::Template => Box::new(TemplateOracle::new(seed, ctx)),
         }
     }
 }
@@ -1,9 +1,26 @@
 use crate::common::{Result, fuzzer_err, util};
-use crate::oracle::QueryExecutionResult;
+use crate::oracle::result_compare::{
+    CompareOptions, ResultDiff, compare_batch_streams, compare_batches,
+};
+use crate::oracle::{QueryExecutionResult, TruncatedResult};
+use datafusion::arrow::array::AsArray;
+use datafusion::arrow::datatypes::Int64Type;
+use datafusion::arrow::record_batch::RecordBatch;
+
+/// Number of mismatched rows shown in the side-by-side diff of an error report
+const REPORT_DIFF_ROWS: usize = 10;
+
+/// Planning time may grow at most this many times faster than the size of a
+/// query, to leave room for timer noise
+const PLANNING_TIME_NOISE_FACTOR: f64 = 2.0;
+
+/// Planning times below this are never reported, they are too noisy to compare
+const MIN_REPORTED_PLANNING_US: i64 = 250_000;
 
 pub(crate) fn validate_binary_tlp_consistency(
     results: &[QueryExecutionResult],
     oracle_name: &str,
+    options: &CompareOptions,
 ) -> Result<()> {
     let result_count = results.len();
     if result_count != 2 {
@@ -16,7 +33,7 @@ pub(crate) fn validate_binary_tlp_consistency(
     let num_err = result_count - num_ok;
 
     match num_ok {
-        2 => validate_value_equivalence(results, 0, 1, oracle_name),
+        2 => validate_value_equivalence(results, 0, 1, oracle_name, options),
         0 => Ok(()),
         _ => Err(fuzzer_err(&format!(
             "{oracle_name} consistency requires all queries to either succeed or fail; got mixed outcomes (ok={num_ok}, err={num_err})"
@@ -24,11 +41,45 @@ pub(crate) fn validate_binary_tlp_consistency(
     }
 }
 
+/// Check that the queries of an equivalent group either all succeed or all fail.
+///
+/// Errors are already checked against the whitelist one by one, so a group that
+/// fails everywhere is fine; a group with mixed outcomes means one variant errors
+/// while an equivalent one silently returns results.
+pub(crate) fn validate_outcome_consistency(
+    results: &[QueryExecutionResult],
+    oracle_name: &str,
+) -> Result<()> {
+    let num_ok = results.iter().filter(|r| r.result.is_ok()).count();
+    if num_ok == 0 || num_ok == results.len() {
+        return Ok(());
+    }
+
+    let mut message = format!(
+        "{oracle_name} expects all queries of the group to either succeed or fail; got mixed outcomes (ok={}, err={})",
+        num_ok,
+        results.len() - num_ok
+    );
+    for (idx, result) in results.iter().enumerate() {
+        match &result.result {
+            Ok(batches) => message.push_str(&format!(
+                "\n  Q{}: ok, rows={}",
+                idx + 1,
+                util::count_total_rows(batches)
+            )),
+            Err(e) => message.push_str(&format!("\n  Q{}: error, {}", idx + 1, e)),
+        }
+    }
+
+    Err(fuzzer_err(&message))
+}
+
 pub(crate) fn validate_value_equivalence(
     results: &[QueryExecutionResult],
     left_idx: usize,
     right_idx: usize,
     oracle_name: &str,
+    options: &CompareOptions,
 ) -> Result<()> {
     let left_result = results
         .get(left_idx)
@@ -46,7 +97,141 @@ pub(crate) fn validate_value_equivalence(
         .as_ref()
         .map_err(|e| fuzzer_err(&e.to_string()))?;
 
-    util::validate_batches_value_equivalence(left_batches, right_batches, oracle_name)
+    let violated = |diff: ResultDiff| {
+        Err(fuzzer_err(&format!(
+            "{} value equivalence violated:\n{}",
+            oracle_name, diff
+        )))
+    };
+
+    let left_truncated = left_result.truncated.as_ref();
+    let right_truncated = right_result.truncated.as_ref();
+    if left_truncated.is_some() || right_truncated.is_some() {
+        // Spooled results are compared in full, reading them chunk by chunk
+        if let (Some(left_all), Some(right_all)) = (
+            whole_result(left_batches, left_truncated)?,
+            whole_result(right_batches, right_truncated)?,
+        ) {
+            return match compare_batch_streams(left_all, right_all, options)? {
+                None => Ok(()),
+                Some(diff) => violated(diff),
+            };
+        }
+
+        // Past `max_result_rows` only the row counts, and for ordered results
+        // the first rows, can be compared
+        let left_rows =
+            left_truncated.map_or_else(|| util::count_total_rows(left_batches), |t| t.total_rows);
+        let right_rows =
+            right_truncated.map_or_else(|| util::count_total_rows(right_batches), |t| t.total_rows);
+        if left_rows != right_rows {
+            return Err(fuzzer_err(&format!(
+                "{} row count differs on truncated results: {} vs {} rows",
+                oracle_name, left_rows, right_rows
+            )));
+        }
+        if !options.ordered {
+            return Ok(());
+        }
+    }
+
+    match compare_batches(left_batches, right_batches, options)? {
+        None => Ok(()),
+        Some(diff) => violated(diff),
+    }
+}
+
+/// The single value of a `count(*)` result
+pub(crate) fn count_value(batches: &[RecordBatch]) -> Result<i64> {
+    let mut values = batches
+        .iter()
+        .filter(|batch| batch.num_rows() > 0)
+        .flat_map(|batch| {
+            batch
+                .column(0)
+                .as_primitive_opt::<Int64Type>()
+                .map(|array| array.values().to_vec())
+                .unwrap_or_default()
+        });
+    match (values.next(), values.next()) {
+        (Some(value), None) => Ok(value),
+        _ => Err(fuzzer_err("Expected a single count(*) value")),
+    }
+}
+
+/// The planning time in microseconds of a result of a
+/// [`crate::engine::planning_time_engine::PlanningTimeEngine`], `None` if
+/// planning failed
+pub(crate) fn planning_time_us(result: &QueryExecutionResult) -> Result<Option<i64>> {
+    match &result.result {
+        Ok(batches) => count_value(batches).map(Some),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Check that the planning time of the query of `results[large]` grows about
+/// linearly from the one of `results[small]`, both
+/// [`crate::engine::planning_time_engine::PlanningTimeEngine`] results.
+/// `sizes` are the sizes of both queries and `size_name` what they measure,
+/// e.g. "nesting depth".
+pub(crate) fn validate_planning_time_growth(
+    results: &[QueryExecutionResult],
+    small: usize,
+    large: usize,
+    sizes: (usize, usize),
+    size_name: &str,
+) -> Result<()> {
+    let (Some(small_us), Some(large_us)) = (
+        planning_time_us(&results[small])?,
+        planning_time_us(&results[large])?,
+    ) else {
+        return Ok(());
+    };
+    let max_growth = PLANNING_TIME_NOISE_FACTOR * sizes.1 as f64 / sizes.0.max(1) as f64;
+    if large_us >= MIN_REPORTED_PLANNING_US && large_us as f64 > max_growth * small_us.max(1) as f64
+    {
+        return Err(fuzzer_err(&format!(
+            "Planning time grows non-linearly with the {}: {} us at {}, {} us at {}",
+            size_name, small_us, sizes.0, large_us, sizes.1
+        )));
+    }
+    Ok(())
+}
+
+/// Append the planning times of the
+/// [`crate::engine::planning_time_engine::PlanningTimeEngine`] results at
+/// `indices` to an error report
+pub(crate) fn append_planning_times(
+    report: &mut String,
+    results: &[QueryExecutionResult],
+    indices: &[usize],
+) {
+    for &index in indices {
+        if let Some(Ok(Some(us))) = results.get(index).map(planning_time_us) {
+            report.push_str(&format!(
+                "Planning time of Q{}: {:.3} ms\n",
+                index + 1,
+                us as f64 / 1000.0
+            ));
+        }
+    }
+    report.push('\n');
+}
+
+/// All rows of a result: `batches`, or the spool file of a truncated result.
+/// `None` if rows of the result were dropped.
+fn whole_result<'a>(
+    batches: &'a [RecordBatch],
+    truncated: Option<&TruncatedResult>,
+) -> Result<Option<Box<dyn Iterator<Item = Result<RecordBatch>> + 'a>>> {
+    match truncated {
+        None => Ok(Some(Box::new(batches.iter().cloned().map(Ok)))),
+        Some(TruncatedResult {
+            spooled: Some(spooled),
+            ..
+        }) => Ok(Some(Box::new(spooled.batches()?))),
+        Some(_) => Ok(None),
+    }
 }
 
 pub(crate) fn append_labeled_query_results(
@@ -63,16 +248,58 @@ pub(crate) fn append_labeled_query_results(
             result.query_context.query
         ));
 
-        match &result.result {
-            Ok(batches) => report.push_str(&format!(
+        match (&result.result, &result.truncated) {
+            (Ok(batches), Some(truncated)) => report.push_str(&format!(
+                "  status: ok, rows={} (first {} kept)\n\n",
+                truncated.total_rows,
+                util::count_total_rows(batches)
+            )),
+            (Ok(batches), None) => report.push_str(&format!(
                 "  status: ok, rows={}\n\n",
                 util::count_total_rows(batches)
             )),
-            Err(e) => report.push_str(&format!("  status: error, details={}\n\n", e)),
+            (Err(e), _) => report.push_str(&format!("  status: error, details={}\n\n", e)),
+        }
+    }
+
+    // Every other complete result side by side with the first one
+    let mut complete = results
+        .iter()
+        .enumerate()
+        .filter(|(_, result)| result.truncated.is_none())
+        .filter_map(|(idx, result)| result.result.as_ref().ok().map(|batches| (idx, batches)));
+    if let Some((base_idx, base_batches)) = complete.next() {
+        for (idx, batches) in complete {
+            if let Ok(Some(diff)) =
+                compare_batches(base_batches, batches, &CompareOptions::default())
+            {
+                report.push_str(&format!(
+                    "Mismatched rows of Q{} and Q{}:\n",
+                    base_idx + 1,
+                    idx + 1
+                ));
+                append_side_by_side(
+                    report,
+                    &diff,
+                    &format!("Q{}", base_idx + 1),
+                    &format!("Q{}", idx + 1),
+                );
+                report.push('\n');
+            }
         }
     }
 }
 
+fn append_side_by_side(
+    report: &mut String,
+    diff: &ResultDiff,
+    left_label: &str,
+    right_label: &str,
+) {
+    report.push_str(&diff.format_side_by_side(left_label, right_label, REPORT_DIFF_ROWS));
+    report.push('\n');
+}
+
 pub(crate) fn append_binary_value_equivalence_report(
     report: &mut String,
     results: &[QueryExecutionResult],
@@ -90,25 +317,122 @@ pub(crate) fn append_binary_value_equivalence_report(
         .as_ref()
         .map_err(|e| fuzzer_err(&e.to_string()))?;
 
-    report.push_str(&format!(
-        "Row counts: all={}, partition_union={}\n",
-        util::count_total_rows(q_all_batches),
-        util::count_total_rows(q_union_batches)
-    ));
+    match compare_batches(q_all_batches, q_union_batches, &CompareOptions::default())? {
+        Some(diff) => {
+            report.push_str("Mismatched rows:\n");
+            append_side_by_side(report, &diff, "all", "partition_union");
+        }
+        None => report.push_str("Multiset equivalence: true\n"),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracle::test_helpers;
 
-    let all_multiset = util::batches_to_row_multiset(q_all_batches)?;
-    let partition_multiset = util::batches_to_row_multiset(q_union_batches)?;
+    #[test]
+    fn outcome_consistency_allows_uniform_outcomes() {
+        let all_ok = vec![
+            test_helpers::make_success_result("q1", "c", vec![1]),
+            test_helpers::make_success_result("q2", "c", vec![2]),
+        ];
+        assert!(validate_outcome_consistency(&all_ok, "Test").is_ok());
 
-    if all_multiset != partition_multiset {
-        report.push_str("\nTop multiset differences:\n");
-        report.push_str(&util::format_row_multiset_diff(
-            &all_multiset,
-            &partition_multiset,
-        ));
-        report.push('\n');
-    } else {
-        report.push_str("Multiset equivalence: true\n");
+        let all_err = vec![
+            test_helpers::make_error_result("q1"),
+            test_helpers::make_error_result("q2"),
+        ];
+        assert!(validate_outcome_consistency(&all_err, "Test").is_ok());
     }
 
-    Ok(())
+    #[test]
+    fn outcome_consistency_rejects_mixed_outcomes() {
+        let mixed = vec![
+            test_helpers::make_success_result("q1", "c", vec![1, 2]),
+            test_helpers::make_error_result("q2"),
+        ];
+        let err = validate_outcome_consistency(&mixed, "Test")
+            .unwrap_err()
+            .to_string();
+
+        assert!(err.contains("got mixed outcomes (ok=1, err=1)"));
+        assert!(err.contains("Q1: ok, rows=2"));
+        assert!(err.contains("Q2: error"));
+    }
+
+    #[test]
+    fn truncated_results_compare_row_counts() {
+        let options = CompareOptions::default();
+        let mut truncated = vec![
+            test_helpers::make_success_result("q1", "c", vec![1, 2]),
+            test_helpers::make_success_result("q2", "c", vec![3, 4]),
+        ];
+        let truncated_to = |total_rows| {
+            Some(TruncatedResult {
+                total_rows,
+                spooled: None,
+            })
+        };
+        truncated[0].truncated = truncated_to(1000);
+        truncated[1].truncated = truncated_to(1000);
+        // Unordered prefixes of the same rows may differ
+        assert!(validate_value_equivalence(&truncated, 0, 1, "Test", &options).is_ok());
+
+        let ordered = options.with_ordered(true);
+        assert!(validate_value_equivalence(&truncated, 0, 1, "Test", &ordered).is_err());
+
+        truncated[1].truncated = truncated_to(999);
+        let err = validate_value_equivalence(&truncated, 0, 1, "Test", &options).unwrap_err();
+        assert!(err.to_string().contains("1000 vs 999 rows"));
+    }
+
+    #[test]
+    fn spooled_results_compare_in_full() {
+        use crate::engine::result_spool::ResultSpooler;
+        use std::sync::Arc;
+
+        let spool = |values: Vec<i64>| {
+            let batch = test_helpers::make_one_col_batch(values);
+            let mut spooler =
+                ResultSpooler::create(&std::env::temp_dir(), &batch.schema()).unwrap();
+            spooler.write(&batch).unwrap();
+            let spooled = spooler.finish().unwrap();
+            Some(TruncatedResult {
+                total_rows: spooled.total_rows(),
+                spooled: Some(Arc::new(spooled)),
+            })
+        };
+        let options = CompareOptions::default();
+
+        // The first rows kept in memory match, the whole results don't
+        let mut results = vec![
+            test_helpers::make_success_result("q1", "c", vec![1]),
+            test_helpers::make_success_result("q2", "c", vec![1, 2, 4]),
+        ];
+        results[0].truncated = spool(vec![1, 2, 3]);
+        assert!(validate_value_equivalence(&results, 0, 1, "Test", &options).is_err());
+
+        results[1] = test_helpers::make_success_result("q2", "c", vec![1]);
+        results[1].truncated = spool(vec![3, 2, 1]);
+        assert!(validate_value_equivalence(&results, 0, 1, "Test", &options).is_ok());
+    }
+
+    #[test]
+    fn labeled_report_shows_mismatched_rows() {
+        let results = vec![
+            test_helpers::make_success_result("q1", "c", vec![1, 2]),
+            test_helpers::make_error_result("q2"),
+            test_helpers::make_success_result("q3", "c", vec![1]),
+            test_helpers::make_success_result("q4", "c", vec![2, 1]),
+        ];
+        let mut report = String::new();
+        append_labeled_query_results(&mut report, &results, &["a", "b", "c", "d"]);
+
+        assert!(report.contains("Mismatched rows of Q1 and Q3:\nRow counts: Q1=2, Q3=1"));
+        assert!(!report.contains("Q1 and Q2"));
+        assert!(!report.contains("Q1 and Q4"));
+    }
 }
@@ -0,0 +1,373 @@
+use std::cmp::Ordering;
+use std::sync::Arc;
+
+use datafusion::arrow::array::RecordBatch;
+use datafusion::scalar::ScalarValue;
+use rand::Rng;
+
+use crate::common::rng::rng_from_seed;
+use crate::common::util::to_sql_string;
+use crate::common::{FuzzerDataType, Result, fuzzer_err};
+use crate::oracle::ground_truth::{
+    column_expr, compare, generate_predicate, is_comparable, matching_rows,
+    tables_with_known_values,
+};
+use crate::oracle::{Oracle, QueryContext, QueryExecutionResult, oracle_common};
+
+/// Result of an aggregate function, computed from the inserted values
+#[derive(Debug, Clone, PartialEq)]
+enum ExpectedAggregate {
+    Count(u64),
+    /// `None` if no non-null value is summed
+    Sum(Option<i128>),
+    /// MIN or MAX, `None` if there is no non-null value
+    Extreme(Option<ScalarValue>),
+}
+
+impl ExpectedAggregate {
+    fn matches(&self, actual: &ScalarValue) -> Result<bool> {
+        Ok(match self {
+            Self::Count(count) => actual == &ScalarValue::Int64(Some(*count as i64)),
+            Self::Sum(None) | Self::Extreme(None) => actual.is_null(),
+            Self::Sum(Some(sum)) => integer_value(actual) == Some(*sum),
+            Self::Extreme(Some(expected)) => {
+                // e.g. MIN of a Utf8 column may return Utf8View
+                !actual.is_null() && &actual.cast_to(&expected.data_type())? == expected
+            }
+        })
+    }
+}
+
+impl std::fmt::Display for ExpectedAggregate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Count(count) => write!(f, "{}", count),
+            Self::Sum(Some(sum)) => write!(f, "{}", sum),
+            Self::Extreme(Some(value)) => write!(f, "{}", value),
+            Self::Sum(None) | Self::Extreme(None) => write!(f, "NULL"),
+        }
+    }
+}
+
+fn integer_value(value: &ScalarValue) -> Option<i128> {
+    match value {
+        ScalarValue::Int32(Some(v)) => Some((*v).into()),
+        ScalarValue::Int64(Some(v)) => Some((*v).into()),
+        ScalarValue::UInt32(Some(v)) => Some((*v).into()),
+        ScalarValue::UInt64(Some(v)) => Some((*v).into()),
+        ScalarValue::Decimal128(Some(v), _, 0) => Some(*v),
+        _ => None,
+    }
+}
+
+/// Ground-truth aggregate oracle.
+///
+/// Runs `COUNT(*)`, `COUNT`, `MIN`, `MAX` and `SUM` over a column of a table
+/// whose inserted values are known, optionally filtered by a predicate, and
+/// compares the results with the aggregates computed in Rust. Like the
+/// Cardinality oracle, this catches wrong results without a second engine.
+///
+/// `SUM` is only checked for integer columns whose sum fits the result type,
+/// where it is exact.
+///
+/// ### Example:
+///
+/// SELECT COUNT(*), COUNT(t1.c0), MIN(t1.c0), MAX(t1.c0), SUM(t1.c0)
+/// FROM t1
+/// WHERE t1.c1 IS NOT NULL;
+pub struct AggregateOracle {
+    seed: u64,
+    ctx: Arc<crate::fuzz_context::GlobalContext>,
+    /// Expected value of each aggregate in the select list
+    expected: Vec<(String, ExpectedAggregate)>,
+}
+
+impl AggregateOracle {
+    pub fn new(seed: u64, ctx: Arc<crate::fuzz_context::GlobalContext>) -> Self {
+        Self {
+            seed,
+            ctx,
+            expected: Vec::new(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Oracle for AggregateOracle {
+    fn name(&self) -> &'static str {
+        "AggregateOracle"
+    }
+
+    fn generate_query_group(&mut self) -> Result<Vec<QueryContext>> {
+        let candidates: Vec<_> = tables_with_known_values(&self.ctx)
+            .into_iter()
+            .flat_map(|table| {
+                (0..table.columns.len())
+                    .filter(|&idx| is_comparable(&table.columns[idx].data_type))
+                    .map(move |idx| (Arc::clone(&table), idx))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        if candidates.is_empty() {
+            return Err(fuzzer_err(
+                "Aggregate oracle requires an integer, boolean, date or string column with known values",
+            ));
+        }
+
+        let mut rng = rng_from_seed(self.seed);
+        let (table, column_idx) = &candidates[rng.random_range(0..candidates.len())];
+        let logical_column = &table.columns[*column_idx];
+        let summary = logical_column
+            .value_summary
+            .as_deref()
+            .ok_or_else(|| fuzzer_err("Aggregate oracle picked a column with unknown values"))?;
+
+        let predicate = rng
+            .random_bool(0.5)
+            .then(|| generate_predicate(&mut rng, table));
+        let rows = match &predicate {
+            Some(predicate) => matching_rows(predicate, table)?,
+            None => (0..summary.values.len()).collect(),
+        };
+        let values: Vec<&ScalarValue> = rows
+            .iter()
+            .map(|&row| &summary.values[row])
+            .filter(|value| !value.is_null())
+            .collect();
+
+        let column_sql = to_sql_string(&column_expr(table, logical_column))?;
+        let extreme = |ordering: Ordering| {
+            let mut extreme: Option<&ScalarValue> = None;
+            for &value in &values {
+                if extreme
+                    .is_none_or(|current| compare(value, current).ok().flatten() == Some(ordering))
+                {
+                    extreme = Some(value);
+                }
+            }
+            ExpectedAggregate::Extreme(extreme.cloned())
+        };
+
+        let mut expected = vec![
+            (
+                "COUNT(*)".to_string(),
+                ExpectedAggregate::Count(rows.len() as u64),
+            ),
+            (
+                format!("COUNT({})", column_sql),
+                ExpectedAggregate::Count(values.len() as u64),
+            ),
+            (format!("MIN({})", column_sql), extreme(Ordering::Less)),
+            (format!("MAX({})", column_sql), extreme(Ordering::Greater)),
+        ];
+        if let Some(sum) = exact_sum(&logical_column.data_type, &values) {
+            expected.push((format!("SUM({})", column_sql), ExpectedAggregate::Sum(sum)));
+        }
+
+        let select_list: Vec<&str> = expected.iter().map(|(sql, _)| sql.as_str()).collect();
+        let mut query = format!(
+            "SELECT {}\nFROM {}",
+            select_list.join(", "),
+            table.sql_name()
+        );
+        if let Some(predicate) = &predicate {
+            query.push_str(&format!("\nWHERE {}", to_sql_string(predicate)?));
+        }
+        self.expected = expected;
+
+        Ok(vec![QueryContext::with_description(
+            query,
+            self.ctx.runtime_context.get_session_context(),
+            "Aggregates computed in Rust".to_string(),
+        )])
+    }
+
+    async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
+        let [result] = results else {
+            return Err(fuzzer_err(&format!(
+                "Aggregate oracle expects 1 query result, got {}",
+                results.len()
+            )));
+        };
+        let Ok(batches) = &result.result else {
+            return Ok(());
+        };
+
+        let row = single_row(batches)?;
+        if row.len() != self.expected.len() {
+            return Err(fuzzer_err(&format!(
+                "Aggregate query returned {} columns, expected {}",
+                row.len(),
+                self.expected.len()
+            )));
+        }
+
+        let mismatches: Vec<String> = self
+            .expected
+            .iter()
+            .zip(&row)
+            .map(|((sql, expected), actual)| {
+                Ok((!expected.matches(actual)?)
+                    .then(|| format!("{} returned {}, expected {}", sql, actual, expected)))
+            })
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .flatten()
+            .collect();
+        if !mismatches.is_empty() {
+            return Err(fuzzer_err(&format!(
+                "Aggregate ground truth violated: {}",
+                mismatches.join("; ")
+            )));
+        }
+        Ok(())
+    }
+
+    fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
+        let mut report = String::new();
+        report.push_str("Aggregate Oracle Test Failed\n");
+        report.push_str("============================\n\n");
+
+        oracle_common::append_labeled_query_results(&mut report, results, &["aggregates"]);
+
+        report.push_str("Expected, computed from the inserted rows:\n");
+        for (sql, expected) in &self.expected {
+            report.push_str(&format!("  {} = {}\n", sql, expected));
+        }
+
+        Ok(report)
+    }
+}
+
+/// Sum of the values of an integer column, `None` inside if there are no
+/// values. `None` if the type isn't an integer or the sum overflows the type
+/// of `SUM`'s result, where DataFusion's behavior isn't ground truth.
+fn exact_sum(data_type: &FuzzerDataType, values: &[&ScalarValue]) -> Option<Option<i128>> {
+    let range = match data_type {
+        FuzzerDataType::Int32 | FuzzerDataType::Int64 => i64::MIN as i128..=i64::MAX as i128,
+        FuzzerDataType::UInt32 | FuzzerDataType::UInt64 => 0..=u64::MAX as i128,
+        _ => return None,
+    };
+    if values.is_empty() {
+        return Some(None);
+    }
+
+    let mut sum: i128 = 0;
+    for value in values {
+        sum = sum.checked_add(integer_value(value)?)?;
+        // Intermediate sums overflow as well
+        if !range.contains(&sum) {
+            return None;
+        }
+    }
+    Some(Some(sum))
+}
+
+/// Values of the single row of an aggregate result
+fn single_row(batches: &[RecordBatch]) -> Result<Vec<ScalarValue>> {
+    let batches: Vec<&RecordBatch> = batches
+        .iter()
+        .filter(|batch| batch.num_rows() > 0)
+        .collect();
+    match batches.as_slice() {
+        [batch] if batch.num_rows() == 1 => batch
+            .columns()
+            .iter()
+            .map(|column| ScalarValue::try_from_array(column, 0).map_err(Into::into))
+            .collect(),
+        _ => Err(fuzzer_err(&format!(
+            "Aggregate query returned {} rows instead of 1",
+            batches.iter().map(|batch| batch.num_rows()).sum::<usize>()
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracle::test_helpers;
+    use crate::oracle::test_helpers::table_with_int_values;
+
+    fn context_with_table(values: &[Option<i64>]) -> Arc<crate::fuzz_context::GlobalContext> {
+        let ctx = Arc::new(crate::fuzz_context::GlobalContext::default());
+        ctx.runtime_context
+            .register_table(Arc::new(table_with_int_values(values)));
+        ctx
+    }
+
+    #[test]
+    fn computes_aggregates_of_the_table() {
+        let ctx = context_with_table(&[Some(4), None, Some(-2), Some(7)]);
+
+        // Find a test without a predicate, over all rows
+        let oracle = (0..64)
+            .map(|seed| {
+                let mut oracle = AggregateOracle::new(seed, Arc::clone(&ctx));
+                let query_group = oracle.generate_query_group().unwrap();
+                (oracle, query_group[0].query.clone())
+            })
+            .find(|(_, query)| !query.contains("WHERE"))
+            .unwrap()
+            .0;
+
+        let expected: Vec<_> = oracle.expected.iter().map(|(_, e)| e.clone()).collect();
+        assert_eq!(
+            expected,
+            vec![
+                ExpectedAggregate::Count(4),
+                ExpectedAggregate::Count(3),
+                ExpectedAggregate::Extreme(Some(ScalarValue::Int64(Some(-2)))),
+                ExpectedAggregate::Extreme(Some(ScalarValue::Int64(Some(7)))),
+                ExpectedAggregate::Sum(Some(9)),
+            ]
+        );
+    }
+
+    #[test]
+    fn sums_are_only_checked_without_overflow() {
+        let big = ScalarValue::Int64(Some(i64::MAX));
+        let one = ScalarValue::Int64(Some(1));
+        assert_eq!(
+            exact_sum(&FuzzerDataType::Int64, &[&big]),
+            Some(Some(i64::MAX as i128))
+        );
+        assert_eq!(exact_sum(&FuzzerDataType::Int64, &[&big, &one]), None);
+        assert_eq!(exact_sum(&FuzzerDataType::Int64, &[]), Some(None));
+        assert_eq!(exact_sum(&FuzzerDataType::String, &[]), None);
+    }
+
+    #[tokio::test]
+    async fn results_must_match_the_ground_truth() {
+        let mut oracle = AggregateOracle::new(1, context_with_table(&[]));
+        oracle.expected = vec![("COUNT(*)".to_string(), ExpectedAggregate::Count(2))];
+
+        let matching = vec![test_helpers::make_success_result(
+            "aggregates",
+            "count(*)",
+            vec![2],
+        )];
+        assert!(oracle.validate_consistency(&matching).await.is_ok());
+
+        let wrong = vec![test_helpers::make_success_result(
+            "aggregates",
+            "count(*)",
+            vec![1],
+        )];
+        let err = oracle.validate_consistency(&wrong).await.unwrap_err();
+        assert!(err.to_string().contains("COUNT(*) returned 1, expected 2"));
+
+        let failed = vec![test_helpers::make_error_result("aggregates")];
+        assert!(oracle.validate_consistency(&failed).await.is_ok());
+    }
+
+    #[test]
+    fn extremes_compare_across_string_types() {
+        let expected = ExpectedAggregate::Extreme(Some(ScalarValue::Utf8(Some("a".to_string()))));
+        assert!(
+            expected
+                .matches(&ScalarValue::Utf8View(Some("a".to_string())))
+                .unwrap()
+        );
+        assert!(!expected.matches(&ScalarValue::Utf8View(None)).unwrap());
+    }
+}
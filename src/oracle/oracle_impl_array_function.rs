@@ -0,0 +1,262 @@
+use std::sync::Arc;
+
+use datafusion::arrow::array::RecordBatch;
+use datafusion::error::DataFusionError;
+use rand::Rng;
+
+use crate::common::rng::rng_from_seed;
+use crate::common::util::to_sql_string;
+use crate::common::{Result, fuzzer_err};
+use crate::oracle::ground_truth::column_expr;
+use crate::oracle::result_compare::compare_batches;
+use crate::oracle::{ConfiguredOracle, Oracle, QueryContext, QueryExecutionResult, oracle_common};
+
+/// Two expressions over lists built from the columns `{x}` and `{y}` that
+/// must be equal on every row
+struct ArrayRelation {
+    name: &'static str,
+    left: &'static str,
+    right: &'static str,
+}
+
+const ARRAY_RELATIONS: &[ArrayRelation] = &[
+    ArrayRelation {
+        name: "array_concat length",
+        left: "array_length(array_concat(make_array({x}, {y}), make_array({x})))",
+        right: "array_length(make_array({x}, {y})) + array_length(make_array({x}))",
+    },
+    ArrayRelation {
+        name: "array_append length",
+        left: "array_length(array_append(make_array({x}, {y}), {y}))",
+        right: "array_length(make_array({x}, {y})) + 1",
+    },
+    ArrayRelation {
+        name: "make_array element",
+        left: "array_element(make_array({x}), 1)",
+        right: "{x}",
+    },
+    ArrayRelation {
+        name: "make_array last element",
+        left: "array_element(make_array({x}, {y}), 2)",
+        right: "{y}",
+    },
+    ArrayRelation {
+        name: "array_concat element",
+        left: "array_element(array_concat(make_array({x}), make_array({y})), 2)",
+        right: "{y}",
+    },
+    ArrayRelation {
+        name: "array_prepend element",
+        left: "array_element(array_prepend({y}, make_array({x})), 2)",
+        right: "{x}",
+    },
+    ArrayRelation {
+        name: "array_reverse element",
+        left: "array_element(array_reverse(make_array({x}, {y})), 1)",
+        right: "{y}",
+    },
+    ArrayRelation {
+        name: "array_slice element",
+        left: "array_element(array_slice(make_array({x}, {y}), 2, 2), 1)",
+        right: "{y}",
+    },
+];
+
+/// Array function oracle.
+///
+/// Builds lists from the values of two columns of the same type with
+/// `make_array`, and evaluates pairs of expressions over them that must be
+/// equal whatever the values: lengths of combined lists are sums of lengths,
+/// and elements are found back at their position. Each pair is computed side
+/// by side over every row of a table.
+///
+/// ### Example:
+///
+/// SELECT array_length(array_concat(make_array(t1.c0, t1.c1), make_array(t1.c0))),
+///        array_length(make_array(t1.c0, t1.c1)) + array_length(make_array(t1.c0))
+/// FROM t1;
+/// SELECT array_element(make_array(t1.c0), 1), t1.c0 FROM t1;
+///
+/// Both columns of every query must hold the same values on every row, NULL
+/// elements included.
+pub struct ArrayFunctionOracle {
+    seed: u64,
+    ctx: Arc<crate::fuzz_context::GlobalContext>,
+}
+
+impl ArrayFunctionOracle {
+    pub fn new(seed: u64, ctx: Arc<crate::fuzz_context::GlobalContext>) -> Self {
+        Self { seed, ctx }
+    }
+}
+
+#[async_trait::async_trait]
+impl Oracle for ArrayFunctionOracle {
+    fn name(&self) -> &'static str {
+        "ArrayFunctionOracle"
+    }
+
+    fn generate_query_group(&mut self) -> Result<Vec<QueryContext>> {
+        let tables: Vec<_> = self
+            .ctx
+            .runtime_context
+            .tables()
+            .values()
+            .filter(|table| !table.columns.is_empty())
+            .cloned()
+            .collect();
+        if tables.is_empty() {
+            return Err(fuzzer_err(
+                "Array function oracle requires a table with columns",
+            ));
+        }
+
+        let mut rng = rng_from_seed(self.seed);
+        let table = &tables[rng.random_range(0..tables.len())];
+        let x = &table.columns[rng.random_range(0..table.columns.len())];
+        // `make_array` coerces its arguments to a common type, keep the
+        // elements of their own type
+        let same_type: Vec<_> = table
+            .columns
+            .iter()
+            .filter(|column| column.data_type == x.data_type)
+            .collect();
+        let y = same_type[rng.random_range(0..same_type.len())];
+        let x_sql = to_sql_string(&column_expr(table, x))?;
+        let y_sql = to_sql_string(&column_expr(table, y))?;
+
+        let session_context = self.ctx.runtime_context.get_session_context();
+        Ok(ARRAY_RELATIONS
+            .iter()
+            .map(|relation| {
+                let fill = |template: &str| template.replace("{x}", &x_sql).replace("{y}", &y_sql);
+                QueryContext::with_description(
+                    format!(
+                        "SELECT {}, {}\nFROM {}",
+                        fill(relation.left),
+                        fill(relation.right),
+                        table.sql_name()
+                    ),
+                    Arc::clone(&session_context),
+                    relation.name.to_string(),
+                )
+            })
+            .collect())
+    }
+
+    async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
+        if results.len() != ARRAY_RELATIONS.len() {
+            return Err(fuzzer_err(&format!(
+                "Array function oracle expects {} query results, got {}",
+                ARRAY_RELATIONS.len(),
+                results.len()
+            )));
+        }
+
+        let options = self
+            .ctx
+            .runner_config
+            .compare_options(ConfiguredOracle::ArrayFunction)
+            .with_ordered(true);
+        for (relation, result) in ARRAY_RELATIONS.iter().zip(results) {
+            // Relations rejecting the column type are skipped
+            let Ok(batches) = &result.result else {
+                continue;
+            };
+            let left = project_column(batches, 0)?;
+            let right = project_column(batches, 1)?;
+            if let Some(diff) = compare_batches(&left, &right, &options)? {
+                return Err(fuzzer_err(&format!(
+                    "Array function relation '{}' violated, the columns of\n{}\ndiffer:\n{}",
+                    relation.name, result.query_context.query, diff
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
+        let mut report = String::new();
+        report.push_str("Array Function Oracle Test Failed\n");
+        report.push_str("=================================\n\n");
+
+        let labels: Vec<&str> = ARRAY_RELATIONS
+            .iter()
+            .map(|relation| relation.name)
+            .collect();
+        oracle_common::append_labeled_query_results(&mut report, results, &labels);
+        report.push_str("Expected: both columns of every query equal on every row\n");
+
+        Ok(report)
+    }
+
+    fn requires_consistent_outcomes(&self) -> bool {
+        // Each relation is a query of its own, some array functions may
+        // reject the column type
+        false
+    }
+}
+
+/// Column `index` of every batch of a result
+fn project_column(batches: &[RecordBatch], index: usize) -> Result<Vec<RecordBatch>> {
+    batches
+        .iter()
+        .map(|batch| Ok(batch.project(&[index]).map_err(DataFusionError::from)?))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracle::test_helpers;
+    use crate::oracle::test_helpers::table_with_int_values;
+
+    fn pair_result(label: &str, left: Vec<i64>, right: Vec<i64>) -> QueryExecutionResult {
+        QueryExecutionResult {
+            result: Ok(vec![test_helpers::make_two_col_batch(left, right)]),
+            ..test_helpers::make_error_result(label)
+        }
+    }
+
+    #[test]
+    fn builds_lists_from_the_columns() {
+        let ctx = Arc::new(crate::fuzz_context::GlobalContext::default());
+        ctx.runtime_context
+            .register_table(Arc::new(table_with_int_values(&[Some(1), None])));
+
+        let mut oracle = ArrayFunctionOracle::new(1, ctx);
+        let query_group = oracle.generate_query_group().unwrap();
+        assert_eq!(query_group.len(), ARRAY_RELATIONS.len());
+        assert_eq!(
+            query_group[2].query,
+            "SELECT array_element(make_array(t1.c0), 1), t1.c0\nFROM t1"
+        );
+        assert!(query_group.iter().all(|query| !query.query.contains('{')));
+    }
+
+    #[tokio::test]
+    async fn both_sides_must_match_on_every_row() {
+        let oracle =
+            ArrayFunctionOracle::new(1, Arc::new(crate::fuzz_context::GlobalContext::default()));
+
+        let mut results: Vec<QueryExecutionResult> = ARRAY_RELATIONS
+            .iter()
+            .map(|relation| pair_result(relation.name, vec![3, 3], vec![3, 3]))
+            .collect();
+        results[1] = test_helpers::make_error_result("array_append length");
+        assert!(oracle.validate_consistency(&results).await.is_ok());
+
+        assert!(!oracle.requires_consistent_outcomes());
+
+        results[3] = pair_result(
+            "array_element(make_array(t1.c0, t1.c1), 2), t1.c1",
+            vec![1, 2],
+            vec![1, 5],
+        );
+        let err = oracle.validate_consistency(&results).await.unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("SELECT array_element(make_array(t1.c0, t1.c1), 2), t1.c1")
+        );
+    }
+}
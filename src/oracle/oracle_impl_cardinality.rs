@@ -0,0 +1,208 @@
+use std::sync::Arc;
+
+use datafusion::arrow::array::{Array, Int64Array, RecordBatch};
+use rand::Rng;
+
+use crate::common::rng::rng_from_seed;
+use crate::common::util::to_sql_string;
+use crate::common::{Result, fuzzer_err};
+use crate::oracle::ground_truth::{generate_predicate, matching_rows, tables_with_known_values};
+use crate::oracle::{Oracle, QueryContext, QueryExecutionResult, oracle_common};
+
+/// Expected-cardinality oracle.
+///
+/// Generates a predicate over a table whose inserted values are known, and
+/// computes the number of rows it matches in Rust. DataFusion's `COUNT(*)`
+/// must return exactly that number: unlike the other oracles, the expected
+/// result doesn't come from DataFusion itself.
+///
+/// Predicates are comparisons, `BETWEEN`, `IN` and `IS [NOT] NULL` over
+/// stored values, combined with AND, OR and NOT under SQL's three-valued
+/// logic. Comparisons only use types whose ordering in Rust matches SQL's.
+///
+/// ### Example:
+///
+/// SELECT COUNT(*) FROM t1
+/// WHERE (t1.col_t1_1_int32 > 5) OR (t1.col_t1_2_string IS NULL);
+///
+/// must return the number of rows the fuzzer inserted that satisfy it.
+pub struct CardinalityOracle {
+    seed: u64,
+    ctx: Arc<crate::fuzz_context::GlobalContext>,
+    /// Rows matched by the predicate of the generated query
+    expected_count: Option<u64>,
+}
+
+impl CardinalityOracle {
+    pub fn new(seed: u64, ctx: Arc<crate::fuzz_context::GlobalContext>) -> Self {
+        Self {
+            seed,
+            ctx,
+            expected_count: None,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Oracle for CardinalityOracle {
+    fn name(&self) -> &'static str {
+        "CardinalityOracle"
+    }
+
+    fn generate_query_group(&mut self) -> Result<Vec<QueryContext>> {
+        let tables = tables_with_known_values(&self.ctx);
+        if tables.is_empty() {
+            return Err(fuzzer_err(
+                "Cardinality oracle requires a table with known values",
+            ));
+        }
+
+        let mut rng = rng_from_seed(self.seed);
+        let table = &tables[rng.random_range(0..tables.len())];
+        let predicate = generate_predicate(&mut rng, table);
+
+        let expected_count = matching_rows(&predicate, table)?.len() as u64;
+        self.expected_count = Some(expected_count);
+
+        let query = format!(
+            "SELECT COUNT(*)\nFROM {}\nWHERE {}",
+            table.sql_name(),
+            to_sql_string(&predicate)?
+        );
+        Ok(vec![QueryContext::with_description(
+            query,
+            self.ctx.runtime_context.get_session_context(),
+            format!("COUNT(*) expecting {} rows", expected_count),
+        )])
+    }
+
+    async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
+        let expected_count = self
+            .expected_count
+            .ok_or_else(|| fuzzer_err("Cardinality oracle validated before generating"))?;
+        let [result] = results else {
+            return Err(fuzzer_err(&format!(
+                "Cardinality oracle expects 1 query result, got {}",
+                results.len()
+            )));
+        };
+        let Ok(batches) = &result.result else {
+            return Ok(());
+        };
+
+        let actual_count = single_count(batches)?;
+        if actual_count != expected_count {
+            return Err(fuzzer_err(&format!(
+                "Cardinality violated: COUNT(*) returned {}, but {} rows match the predicate",
+                actual_count, expected_count
+            )));
+        }
+        Ok(())
+    }
+
+    fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
+        let mut report = String::new();
+        report.push_str("Cardinality Oracle Test Failed\n");
+        report.push_str("==============================\n\n");
+
+        oracle_common::append_labeled_query_results(&mut report, results, &["count"]);
+
+        if let Some(expected_count) = self.expected_count {
+            report.push_str(&format!(
+                "Expected: COUNT(*) = {}, computed from the inserted rows\n",
+                expected_count
+            ));
+        }
+
+        Ok(report)
+    }
+}
+
+/// The value of a `COUNT(*)` result
+fn single_count(batches: &[RecordBatch]) -> Result<u64> {
+    let counts: Vec<i64> = batches
+        .iter()
+        .filter(|batch| batch.num_rows() > 0)
+        .map(|batch| {
+            let column = batch
+                .column(0)
+                .as_any()
+                .downcast_ref::<Int64Array>()
+                .ok_or_else(|| fuzzer_err("COUNT(*) did not return an Int64 column"))?;
+            Ok(column.iter().flatten().collect::<Vec<_>>())
+        })
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .flatten()
+        .collect();
+
+    match counts.as_slice() {
+        [count] => Ok(*count as u64),
+        _ => Err(fuzzer_err(&format!(
+            "COUNT(*) returned {} rows instead of 1",
+            counts.len()
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::LogicalTable;
+    use crate::oracle::test_helpers;
+    use crate::oracle::test_helpers::table_with_int_values;
+
+    #[test]
+    fn generates_countable_queries() {
+        let ctx = Arc::new(crate::fuzz_context::GlobalContext::default());
+        ctx.runtime_context
+            .register_table(Arc::new(table_with_int_values(&[Some(1), None, Some(5)])));
+
+        for seed in 0..32 {
+            let mut oracle = CardinalityOracle::new(seed, Arc::clone(&ctx));
+            let query_group = oracle.generate_query_group().unwrap();
+            assert_eq!(query_group.len(), 1);
+            assert!(
+                query_group[0]
+                    .query
+                    .starts_with("SELECT COUNT(*)\nFROM t1\nWHERE ")
+            );
+            assert!(oracle.expected_count.unwrap() <= 3);
+        }
+    }
+
+    #[tokio::test]
+    async fn count_must_match_expected_rows() {
+        let mut oracle =
+            CardinalityOracle::new(1, Arc::new(crate::fuzz_context::GlobalContext::default()));
+        oracle.expected_count = Some(2);
+
+        let matching = vec![test_helpers::make_success_result(
+            "count",
+            "count(*)",
+            vec![2],
+        )];
+        assert!(oracle.validate_consistency(&matching).await.is_ok());
+
+        let wrong = vec![test_helpers::make_success_result(
+            "count",
+            "count(*)",
+            vec![3],
+        )];
+        let err = oracle.validate_consistency(&wrong).await.unwrap_err();
+        assert!(err.to_string().contains("Cardinality violated"));
+
+        let failed = vec![test_helpers::make_error_result("count")];
+        assert!(oracle.validate_consistency(&failed).await.is_ok());
+    }
+
+    #[test]
+    fn tables_without_known_values_are_skipped() {
+        let ctx = Arc::new(crate::fuzz_context::GlobalContext::default());
+        ctx.runtime_context
+            .register_table(Arc::new(LogicalTable::new("v0".to_string())));
+
+        let mut oracle = CardinalityOracle::new(1, ctx);
+        assert!(oracle.generate_query_group().is_err());
+    }
+}
@@ -0,0 +1,366 @@
+use std::sync::Arc;
+
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::arrow::util::display::array_value_to_string;
+use rand::Rng;
+
+use crate::common::rng::rng_from_seed;
+use crate::common::{
+    DEFAULT_CATALOG, DEFAULT_SCHEMA, InclusionConfig, Result, TableNamespace, fuzzer_err,
+};
+use crate::oracle::{Oracle, QueryContext, QueryExecutionResult, oracle_common};
+use crate::query_generator::stmt_select_def::SelectStatementBuilder;
+
+/// Catalog queries run after the checked ones, only checked not to crash
+const OTHER_CATALOG_QUERIES: [&str; 8] = [
+    "SHOW TABLES",
+    "SELECT table_schema, table_name, table_type\nFROM information_schema.tables\nORDER BY table_schema, table_name",
+    "SELECT table_name, count(*), max(ordinal_position)\nFROM information_schema.columns\nGROUP BY table_name\nORDER BY table_name",
+    "SELECT table_name, definition\nFROM information_schema.views\nORDER BY table_name",
+    "SHOW ALL",
+    "SELECT name, value\nFROM information_schema.df_settings\nWHERE name LIKE 'datafusion.execution.%'\nORDER BY name",
+    "SELECT routine_name, routine_type\nFROM information_schema.routines\nORDER BY routine_name\nLIMIT 10",
+    "SHOW datafusion.execution.batch_size",
+];
+
+/// Positions of the checked queries in the query group
+const COLUMNS_QUERY: usize = 0;
+const SHOW_COLUMNS_QUERY: usize = 1;
+const DESCRIBE_QUERY: usize = 2;
+const TABLES_QUERY: usize = 3;
+
+/// Catalog and `information_schema` oracle.
+///
+/// Catalog views and `SHOW`/`DESCRIBE` statements have planner paths of their
+/// own, and are easily broken by tables changing between rounds (see
+/// `schema_evolution`). The oracle picks a registered table and lists its
+/// columns through `information_schema.columns`, `SHOW COLUMNS` and
+/// `DESCRIBE`: all three must list the columns the fuzzer created, with the
+/// same nullability, and `information_schema.tables` must list the table
+/// once. A regular generated query and other catalog queries (`SHOW TABLES`,
+/// `SHOW ALL`, `information_schema.views`, ...) run in the same group, only
+/// checked not to crash.
+///
+/// ### Example:
+///
+/// SELECT column_name, is_nullable FROM information_schema.columns
+/// WHERE table_catalog = 'datafusion' AND table_schema = 'public' AND table_name = 't0'
+/// ORDER BY ordinal_position;
+///
+/// SHOW COLUMNS FROM t0;
+///
+/// DESCRIBE t0;
+pub struct CatalogOracle {
+    seed: u64,
+    ctx: Arc<crate::fuzz_context::GlobalContext>,
+    /// The checked table and its columns, set when generating the group
+    expected: Option<(String, Vec<String>)>,
+}
+
+impl CatalogOracle {
+    pub fn new(seed: u64, ctx: Arc<crate::fuzz_context::GlobalContext>) -> Self {
+        Self {
+            seed,
+            ctx,
+            expected: None,
+        }
+    }
+}
+
+/// The values of the first `columns` columns of every row, as strings
+fn result_rows(batches: &[RecordBatch], columns: usize) -> Result<Vec<Vec<String>>> {
+    let mut rows = Vec::new();
+    for batch in batches {
+        if batch.num_columns() < columns {
+            return Err(fuzzer_err(&format!(
+                "Expected at least {} columns, got {}",
+                columns,
+                batch.num_columns()
+            )));
+        }
+        for row in 0..batch.num_rows() {
+            let values = (0..columns)
+                .map(|column| array_value_to_string(batch.column(column), row))
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(|e| fuzzer_err(&format!("Failed to read catalog result: {}", e)))?;
+            rows.push(values);
+        }
+    }
+    Ok(rows)
+}
+
+/// `(column_name, is_nullable)` of every row of a column listing, `None` if
+/// the query failed
+fn listed_columns(
+    result: &QueryExecutionResult,
+    name_column: usize,
+    nullable_column: usize,
+) -> Result<Option<Vec<(String, String)>>> {
+    let Ok(batches) = &result.result else {
+        return Ok(None);
+    };
+    let rows = result_rows(batches, name_column.max(nullable_column) + 1)?;
+    Ok(Some(
+        rows.into_iter()
+            .map(|row| (row[name_column].clone(), row[nullable_column].clone()))
+            .collect(),
+    ))
+}
+
+#[async_trait::async_trait]
+impl Oracle for CatalogOracle {
+    fn name(&self) -> &'static str {
+        "CatalogOracle"
+    }
+
+    fn generate_query_group(&mut self) -> Result<Vec<QueryContext>> {
+        // The session and its tables as of now, tables may change later
+        let snapshot = self.ctx.runtime_context.snapshot();
+        let tables: Vec<_> = snapshot.tables.values().collect();
+        if tables.is_empty() {
+            return Err(fuzzer_err("Catalog oracle requires a registered table"));
+        }
+
+        let mut rng = rng_from_seed(self.seed);
+        let table = tables[rng.random_range(0..tables.len())];
+        let quoted = table.sql_name();
+        let namespace = table
+            .namespace
+            .clone()
+            .unwrap_or_else(|| TableNamespace::new(DEFAULT_CATALOG, DEFAULT_SCHEMA));
+        let literal = |value: &str| format!("'{}'", value.replace('\'', "''"));
+        let table_filter = format!(
+            "table_catalog = {} AND table_schema = {} AND table_name = {}",
+            literal(&namespace.catalog),
+            literal(&namespace.schema),
+            literal(&table.name)
+        );
+        self.expected = Some((
+            table.name.clone(),
+            table.columns.iter().map(|c| c.name.clone()).collect(),
+        ));
+
+        let mut stmt_builder = SelectStatementBuilder::new(
+            self.seed,
+            Arc::clone(&self.ctx),
+            InclusionConfig::Maybe(0.5),
+            InclusionConfig::Maybe(0.5),
+        )
+        .with_allow_derived_tables(false);
+        let regular_query = stmt_builder.generate_stmt()?.to_sql_string()?;
+
+        let session_context = &snapshot.session_context;
+        let query = |sql: String, description: &str| {
+            QueryContext::with_description(
+                sql,
+                Arc::clone(session_context),
+                description.to_string(),
+            )
+        };
+        let mut query_group = vec![
+            query(
+                format!(
+                    "SELECT column_name, is_nullable\nFROM information_schema.columns\n\
+                     WHERE {}\nORDER BY ordinal_position",
+                    table_filter
+                ),
+                "information_schema.columns",
+            ),
+            query(format!("SHOW COLUMNS FROM {}", quoted), "SHOW COLUMNS"),
+            query(format!("DESCRIBE {}", quoted), "DESCRIBE"),
+            query(
+                format!(
+                    "SELECT table_type\nFROM information_schema.tables\nWHERE {}",
+                    table_filter
+                ),
+                "information_schema.tables",
+            ),
+            query(regular_query, "Regular query"),
+        ];
+        for _ in 0..rng.random_range(1..=2) {
+            let sql = OTHER_CATALOG_QUERIES[rng.random_range(0..OTHER_CATALOG_QUERIES.len())];
+            query_group.push(query(sql.to_string(), "Catalog query"));
+        }
+
+        Ok(query_group)
+    }
+
+    async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
+        let (table, expected_columns) = self
+            .expected
+            .as_ref()
+            .ok_or_else(|| fuzzer_err("Catalog oracle validated before generating"))?;
+        if results.len() <= TABLES_QUERY {
+            return Err(fuzzer_err(&format!(
+                "Catalog expects at least {} query results, got {}",
+                TABLES_QUERY + 1,
+                results.len()
+            )));
+        }
+
+        // SHOW COLUMNS lists catalog, schema and table before the column
+        let listings = [
+            (COLUMNS_QUERY, 0, 1),
+            (SHOW_COLUMNS_QUERY, 3, 5),
+            (DESCRIBE_QUERY, 0, 2),
+        ];
+        let mut reference: Option<(usize, Vec<(String, String)>)> = None;
+        for (index, name_column, nullable_column) in listings {
+            let Some(mut listed) = listed_columns(&results[index], name_column, nullable_column)?
+            else {
+                continue;
+            };
+            // SHOW COLUMNS has no defined order
+            if index == SHOW_COLUMNS_QUERY {
+                let position = |name: &str| expected_columns.iter().position(|c| c == name);
+                listed.sort_by_key(|(name, _)| position(name));
+            }
+
+            let names: Vec<&String> = listed.iter().map(|(name, _)| name).collect();
+            if names.iter().copied().ne(expected_columns.iter()) {
+                return Err(fuzzer_err(&format!(
+                    "{} lists columns {:?} of table {}, expected {:?}",
+                    results[index].query_context.display_description(),
+                    names,
+                    table,
+                    expected_columns
+                )));
+            }
+            match &reference {
+                Some((reference_index, reference_listed)) if *reference_listed != listed => {
+                    return Err(fuzzer_err(&format!(
+                        "Column nullability of table {} differs: {:?} in query {}, {:?} in query {}",
+                        table,
+                        reference_listed,
+                        reference_index + 1,
+                        listed,
+                        index + 1
+                    )));
+                }
+                Some(_) => {}
+                None => reference = Some((index, listed)),
+            }
+        }
+
+        if let Ok(batches) = &results[TABLES_QUERY].result {
+            let rows = result_rows(batches, 1)?;
+            if rows.len() != 1 {
+                return Err(fuzzer_err(&format!(
+                    "information_schema.tables lists table {} {} times, expected once",
+                    table,
+                    rows.len()
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
+        let mut report = String::new();
+        report.push_str("Catalog Oracle Test Failed\n");
+        report.push_str("==========================\n\n");
+
+        let labels: Vec<String> = results
+            .iter()
+            .map(|result| {
+                result
+                    .query_context
+                    .context_description
+                    .clone()
+                    .unwrap_or_else(|| "query".to_string())
+            })
+            .collect();
+        let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+        oracle_common::append_labeled_query_results(&mut report, results, &labels);
+
+        report.push_str(
+            "Expected: information_schema.columns, SHOW COLUMNS and DESCRIBE list the \
+             columns of the table, and information_schema.tables lists it once\n",
+        );
+
+        Ok(report)
+    }
+
+    fn requires_consistent_outcomes(&self) -> bool {
+        // The regular query may fail with a whitelisted error while the
+        // catalog queries succeed
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuzz_context::GlobalContext;
+    use crate::oracle::test_helpers::{register_int_table, run_group, table_with_int_values};
+
+    /// Context with table `t1(c0)`, registered in the fuzzer and the session
+    async fn context_with_table() -> Arc<GlobalContext> {
+        let ctx = Arc::new(GlobalContext::default());
+        register_int_table(&ctx, &[Some(1), None]).await;
+        ctx
+    }
+
+    #[tokio::test]
+    async fn catalog_views_list_the_table_columns() {
+        let ctx = context_with_table().await;
+
+        for seed in 0..4 {
+            let mut oracle = CatalogOracle::new(seed, Arc::clone(&ctx));
+            let results = run_group(oracle.generate_query_group().unwrap()).await;
+            assert!(results.len() > TABLES_QUERY + 1);
+            for result in &results {
+                assert!(
+                    result.result.is_ok(),
+                    "{}: {:?}",
+                    result.query_context.query,
+                    result.result
+                );
+            }
+            oracle.validate_consistency(&results).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn lists_tables_in_other_catalogs() {
+        let ctx = Arc::new(GlobalContext::default());
+        let mut table = table_with_int_values(&[Some(1)]);
+        table.namespace = Some(TableNamespace::new("c1", "s1"));
+        ctx.runtime_context.register_table(Arc::new(table));
+        let session_context = ctx.runtime_context.get_session_context();
+        for sql in [
+            "CREATE DATABASE c1",
+            "CREATE SCHEMA c1.s1",
+            "CREATE TABLE c1.s1.t1(c0 BIGINT) AS VALUES (1)",
+        ] {
+            session_context.sql(sql).await.unwrap();
+        }
+
+        let mut oracle = CatalogOracle::new(0, Arc::clone(&ctx));
+        let results = run_group(oracle.generate_query_group().unwrap()).await;
+        assert!(
+            results[SHOW_COLUMNS_QUERY]
+                .query_context
+                .query
+                .contains("c1.s1.t1")
+        );
+        for result in &results[..=TABLES_QUERY] {
+            assert!(result.result.is_ok(), "{:?}", result.result);
+        }
+        oracle.validate_consistency(&results).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn reports_columns_missing_from_the_catalog() {
+        let ctx = context_with_table().await;
+
+        let mut oracle = CatalogOracle::new(1, Arc::clone(&ctx));
+        let results = run_group(oracle.generate_query_group().unwrap()).await;
+        if let Some((_, columns)) = oracle.expected.as_mut() {
+            columns.push("missing".to_string());
+        }
+        let err = oracle.validate_consistency(&results).await.unwrap_err();
+        assert!(err.to_string().contains("lists columns"));
+    }
+}
@@ -0,0 +1,301 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{Array, Int64Array, RecordBatch};
+use datafusion::scalar::ScalarValue;
+use rand::Rng;
+
+use crate::common::rng::rng_from_seed;
+use crate::common::{Result, fuzzer_err};
+use crate::oracle::{Oracle, QueryContext, QueryExecutionResult, oracle_common};
+
+/// Strings whose order is easy to get wrong: precomposed and decomposed
+/// forms of the same character, lookalikes, case, trailing spaces, embedded
+/// NULs and characters outside the Basic Multilingual Plane
+const TRICKY_STRINGS: [&str; 20] = [
+    "",
+    " ",
+    "a",
+    "a ",
+    "A",
+    "ab",
+    "a\0",
+    "a\0b",
+    "\u{e1}",   // á, precomposed
+    "a\u{301}", // á, a + combining acute accent
+    "\u{c5}",   // Å, Latin capital A with ring above
+    "\u{212b}", // Å, Angstrom sign
+    "\u{fb01}", // ﬁ ligature
+    "fi",
+    "\u{df}", // ß
+    "ss",
+    "\u{130}",   // İ, capital I with dot above
+    "\u{1f600}", // 😀
+    "\u{ffff}",
+    "z",
+];
+
+/// Maximum number of rows of the input table
+const MAX_ROW_COUNT: usize = 10;
+
+/// SQL expression of `value`. Strings with NULs are written as hex binary
+/// literals, which SQL string literals can't contain.
+fn string_sql(value: &str) -> String {
+    if value.contains('\0') {
+        let hex: String = value.bytes().map(|byte| format!("{:02X}", byte)).collect();
+        format!("CAST(X'{}' AS VARCHAR)", hex)
+    } else {
+        format!("'{}'", value.replace('\'', "''"))
+    }
+}
+
+/// String ordering consistency oracle.
+///
+/// Fills a small table with tricky strings and checks that `ORDER BY`,
+/// `MIN`/`MAX` and `<` agree with each other:
+///
+/// SELECT id FROM t WHERE s IS NOT NULL ORDER BY s, id;
+/// SELECT MIN(s), MAX(s) FROM t;
+/// SELECT l.id, r.id FROM t AS l, t AS r WHERE l.s < r.s;
+///
+/// No row may be `<` a row sorted before it, `MIN` and `MAX` must be the
+/// strings sorted first and last. Rows not ordered by `<` either way must
+/// hold the same string, so e.g. precomposed and decomposed `á` can't be
+/// equal for one operator and different for another.
+pub struct CollationOracle {
+    seed: u64,
+    ctx: Arc<crate::fuzz_context::GlobalContext>,
+    /// Strings of the table by id, `None` is NULL
+    rows: Vec<Option<&'static str>>,
+}
+
+impl CollationOracle {
+    pub fn new(seed: u64, ctx: Arc<crate::fuzz_context::GlobalContext>) -> Self {
+        Self {
+            seed,
+            ctx,
+            rows: Vec::new(),
+        }
+    }
+
+    fn string_of(&self, id: i64) -> Result<Option<&'static str>> {
+        usize::try_from(id)
+            .ok()
+            .and_then(|id| self.rows.get(id).copied())
+            .ok_or_else(|| fuzzer_err(&format!("Collation query returned unknown id {}", id)))
+    }
+
+    /// Check the `ORDER BY` ids against the `<` relation
+    fn check_order(&self, sorted_ids: &[i64], less: &HashSet<(i64, i64)>) -> Result<()> {
+        for (i, &earlier) in sorted_ids.iter().enumerate() {
+            for &later in &sorted_ids[i + 1..] {
+                if less.contains(&(later, earlier)) {
+                    return Err(fuzzer_err(&format!(
+                        "Collation inconsistent: {:?} sorts before {:?}, but {:?} < {:?}",
+                        self.string_of(earlier)?,
+                        self.string_of(later)?,
+                        self.string_of(later)?,
+                        self.string_of(earlier)?
+                    )));
+                }
+                let unordered = !less.contains(&(earlier, later));
+                if unordered && self.string_of(earlier)? != self.string_of(later)? {
+                    return Err(fuzzer_err(&format!(
+                        "Collation inconsistent: {:?} and {:?} differ, but neither is < the other",
+                        self.string_of(earlier)?,
+                        self.string_of(later)?
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl Oracle for CollationOracle {
+    fn name(&self) -> &'static str {
+        "CollationOracle"
+    }
+
+    fn generate_query_group(&mut self) -> Result<Vec<QueryContext>> {
+        let mut rng = rng_from_seed(self.seed);
+        let row_count = rng.random_range(2..=MAX_ROW_COUNT);
+        self.rows = (0..row_count)
+            .map(|_| {
+                if rng.random_bool(0.1) {
+                    None
+                } else {
+                    Some(TRICKY_STRINGS[rng.random_range(0..TRICKY_STRINGS.len())])
+                }
+            })
+            .collect();
+
+        let values: Vec<String> = self
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(id, value)| {
+                let value = value.map_or("CAST(NULL AS VARCHAR)".to_string(), string_sql);
+                format!("({}, {})", id, value)
+            })
+            .collect();
+        let table = format!("(VALUES {}) AS t(id, s)", values.join(", "));
+
+        let session_context = self.ctx.runtime_context.get_session_context();
+        let queries = [
+            (
+                format!(
+                    "SELECT id\nFROM {}\nWHERE s IS NOT NULL\nORDER BY s, id",
+                    table
+                ),
+                "ORDER BY",
+            ),
+            (format!("SELECT MIN(s), MAX(s)\nFROM {}", table), "MIN/MAX"),
+            (
+                format!(
+                    "WITH strings AS (SELECT * FROM {})\nSELECT l.id, r.id\nFROM strings AS l, strings AS r\nWHERE l.s < r.s",
+                    table
+                ),
+                "less than",
+            ),
+        ];
+        Ok(queries
+            .into_iter()
+            .map(|(query, description)| {
+                QueryContext::with_description(
+                    query,
+                    Arc::clone(&session_context),
+                    description.to_string(),
+                )
+            })
+            .collect())
+    }
+
+    async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
+        let [order_by, min_max, less_than] = results else {
+            return Err(fuzzer_err(&format!(
+                "Collation oracle expects 3 query results, got {}",
+                results.len()
+            )));
+        };
+        let (Ok(order_by), Ok(min_max), Ok(less_than)) =
+            (&order_by.result, &min_max.result, &less_than.result)
+        else {
+            return Ok(());
+        };
+
+        let sorted_ids = int_column(order_by, 0)?;
+        let less: HashSet<(i64, i64)> = int_column(less_than, 0)?
+            .into_iter()
+            .zip(int_column(less_than, 1)?)
+            .collect();
+        self.check_order(&sorted_ids, &less)?;
+
+        let (min, max) = min_max_strings(min_max)?;
+        let first = sorted_ids
+            .first()
+            .map(|&id| self.string_of(id))
+            .transpose()?;
+        let last = sorted_ids
+            .last()
+            .map(|&id| self.string_of(id))
+            .transpose()?;
+        if min.as_deref() != first.flatten() || max.as_deref() != last.flatten() {
+            return Err(fuzzer_err(&format!(
+                "Collation inconsistent: MIN/MAX returned {:?}/{:?}, ORDER BY sorted {:?} first and {:?} last",
+                min,
+                max,
+                first.flatten(),
+                last.flatten()
+            )));
+        }
+        Ok(())
+    }
+
+    fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
+        let mut report = String::new();
+        report.push_str("Collation Oracle Test Failed\n");
+        report.push_str("============================\n\n");
+
+        oracle_common::append_labeled_query_results(
+            &mut report,
+            results,
+            &["order by", "min/max", "less than"],
+        );
+
+        report.push_str(&format!("Strings by id: {:?}\n", self.rows));
+        Ok(report)
+    }
+}
+
+/// Values of Int64 column `index` of all batches
+fn int_column(batches: &[RecordBatch], index: usize) -> Result<Vec<i64>> {
+    let mut values = Vec::new();
+    for batch in batches {
+        let column = batch
+            .column(index)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .ok_or_else(|| fuzzer_err("Collation query did not return Int64 ids"))?;
+        values.extend(column.iter().flatten());
+    }
+    Ok(values)
+}
+
+/// The values of a `MIN(s), MAX(s)` result
+fn min_max_strings(batches: &[RecordBatch]) -> Result<(Option<String>, Option<String>)> {
+    let batch = batches
+        .iter()
+        .find(|batch| batch.num_rows() > 0)
+        .ok_or_else(|| fuzzer_err("MIN/MAX returned no rows"))?;
+    let string_at = |index: usize| -> Result<Option<String>> {
+        let value = ScalarValue::try_from_array(batch.column(index), 0)?;
+        let string = value
+            .try_as_str()
+            .ok_or_else(|| fuzzer_err(&format!("MIN/MAX returned a non-string {}", value)))?;
+        Ok(string.map(str::to_string))
+    };
+    Ok((string_at(0)?, string_at(1)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_nul_strings_as_binary_literals() {
+        assert_eq!(string_sql("a\0b"), "CAST(X'610062' AS VARCHAR)");
+        assert_eq!(string_sql("a\u{301}"), "'a\u{301}'");
+        assert_eq!(string_sql("it's"), "'it''s'");
+    }
+
+    #[test]
+    fn generates_three_queries_over_the_same_table() {
+        let ctx = Arc::new(crate::fuzz_context::GlobalContext::default());
+        for seed in 0..16 {
+            let mut oracle = CollationOracle::new(seed, Arc::clone(&ctx));
+            let query_group = oracle.generate_query_group().unwrap();
+            assert_eq!(query_group.len(), 3);
+            assert!(query_group[0].query.ends_with("ORDER BY s, id"));
+            assert!(oracle.rows.len() >= 2);
+        }
+    }
+
+    #[test]
+    fn order_must_agree_with_less_than() {
+        let mut oracle =
+            CollationOracle::new(1, Arc::new(crate::fuzz_context::GlobalContext::default()));
+        oracle.rows = vec![Some("b"), Some("a"), Some("b")];
+
+        let less: HashSet<(i64, i64)> = [(1, 0), (1, 2)].into_iter().collect();
+        assert!(oracle.check_order(&[1, 0, 2], &less).is_ok());
+
+        let err = oracle.check_order(&[0, 1, 2], &less).unwrap_err();
+        assert!(err.to_string().contains("sorts before"));
+
+        // Different strings neither ordered by `<`
+        let err = oracle.check_order(&[1, 0, 2], &HashSet::new()).unwrap_err();
+        assert!(err.to_string().contains("neither is < the other"));
+    }
+}
@@ -0,0 +1,292 @@
+use std::sync::Arc;
+
+use rand::Rng;
+use rand::rngs::StdRng;
+
+use crate::common::rng::rng_from_seed;
+use crate::common::{InclusionConfig, Result, fuzzer_err};
+use crate::engine::concurrent_engine::ConcurrentBatch;
+use crate::oracle::{Oracle, QueryContext, QueryExecutionResult, oracle_common};
+use crate::query_generator::stmt_select_def::SelectStatementBuilder;
+
+/// Number of times the DDL task creates and drops its table
+const MIN_DDL_CYCLES: usize = 3;
+const MAX_DDL_CYCLES: usize = 6;
+
+/// Number of tasks querying while the DDL task runs
+const MIN_QUERY_TASKS: usize = 2;
+const MAX_QUERY_TASKS: usize = 3;
+
+/// Maximum number of rows of the created table
+const MAX_TABLE_ROWS: usize = 5;
+
+/// Whether an error is the graceful outcome of a query on a table or view
+/// dropped, or not created yet, by the DDL task
+fn is_missing_table_error(error: &str) -> bool {
+    error.contains("table '") && error.contains("' not found")
+}
+
+/// Concurrent DDL stress oracle.
+///
+/// One task creates, replaces and drops a table and a view over it, again
+/// and again, while 2 or 3 other tasks query them (and a generated query on
+/// the generated tables) in the same session. The queries race with the
+/// catalog changes, so they may not find the table or view: that error is
+/// expected, any other error or panic is reported by the runner like for any
+/// other query. Once all tasks are done, `information_schema.tables` must not
+/// list the dropped objects.
+///
+/// ### Example:
+///
+/// CREATE TABLE ddl_t1 (c0 BIGINT) AS VALUES (1), (NULL);   -- DDL task
+/// CREATE VIEW ddl_v1 AS SELECT c0 FROM ddl_t1 WHERE c0 > 0;
+/// DROP VIEW IF EXISTS ddl_v1;
+/// DROP TABLE ddl_t1;
+///
+/// SELECT count(*) FROM ddl_t1;   -- query tasks, at the same time
+/// SELECT c0 FROM ddl_v1 ORDER BY c0;
+pub struct ConcurrentDdlOracle {
+    seed: u64,
+    ctx: Arc<crate::fuzz_context::GlobalContext>,
+    /// Names of the table and view of the DDL task, set when generating
+    objects: Option<(String, String)>,
+}
+
+impl ConcurrentDdlOracle {
+    pub fn new(seed: u64, ctx: Arc<crate::fuzz_context::GlobalContext>) -> Self {
+        Self {
+            seed,
+            ctx,
+            objects: None,
+        }
+    }
+}
+
+/// `VALUES` list of `1..=MAX_TABLE_ROWS` rows
+fn random_values(rng: &mut StdRng) -> String {
+    let rows: Vec<String> = (0..rng.random_range(1..=MAX_TABLE_ROWS))
+        .map(|_| {
+            if rng.random_bool(0.2) {
+                "(NULL)".to_string()
+            } else {
+                format!("({})", rng.random_range(-100..=100))
+            }
+        })
+        .collect();
+    rows.join(", ")
+}
+
+#[async_trait::async_trait]
+impl Oracle for ConcurrentDdlOracle {
+    fn name(&self) -> &'static str {
+        "ConcurrentDdlOracle"
+    }
+
+    fn generate_query_group(&mut self) -> Result<Vec<QueryContext>> {
+        let mut rng = rng_from_seed(self.seed);
+        let table = format!("ddl_t{}", self.seed % 10_000);
+        let view = format!("ddl_v{}", self.seed % 10_000);
+
+        // Objects left by an earlier run of the batch, cancelled by a timeout,
+        // are dropped first
+        let mut ddl_script = vec![
+            format!("DROP VIEW IF EXISTS {}", view),
+            format!("DROP TABLE IF EXISTS {}", table),
+        ];
+        for _ in 0..rng.random_range(MIN_DDL_CYCLES..=MAX_DDL_CYCLES) {
+            ddl_script.push(format!(
+                "CREATE TABLE {} (c0 BIGINT) AS VALUES {}",
+                table,
+                random_values(&mut rng)
+            ));
+            if rng.random_bool(0.7) {
+                ddl_script.push(format!(
+                    "CREATE VIEW {} AS SELECT c0 FROM {} WHERE c0 > {}",
+                    view,
+                    table,
+                    rng.random_range(-100..=100)
+                ));
+            }
+            if rng.random_bool(0.5) {
+                ddl_script.push(format!(
+                    "CREATE OR REPLACE TABLE {} (c0 BIGINT) AS VALUES {}",
+                    table,
+                    random_values(&mut rng)
+                ));
+            }
+            ddl_script.push(format!("DROP VIEW IF EXISTS {}", view));
+            ddl_script.push(format!("DROP TABLE {}", table));
+        }
+
+        let queries_per_task = ddl_script.len();
+        let num_query_tasks = rng.random_range(MIN_QUERY_TASKS..=MAX_QUERY_TASKS);
+        let mut query_scripts = Vec::with_capacity(num_query_tasks);
+        for _ in 0..num_query_tasks {
+            let mut stmt_builder = SelectStatementBuilder::new(
+                rng.random(),
+                Arc::clone(&self.ctx),
+                InclusionConfig::Maybe(0.5),
+                InclusionConfig::Maybe(0.5),
+            )
+            .with_allow_derived_tables(false);
+            let generated_sql = stmt_builder.generate_stmt()?.to_sql_string()?;
+            let script: Vec<String> = (0..queries_per_task)
+                .map(|_| match rng.random_range(0..5) {
+                    0 => format!("SELECT count(*) FROM {}", table),
+                    1 => format!("SELECT c0 FROM {} ORDER BY c0", view),
+                    2 => format!(
+                        "SELECT {0}.c0 FROM {0} JOIN {1} ON {0}.c0 = {1}.c0",
+                        table, view
+                    ),
+                    3 => "SELECT table_name FROM information_schema.tables ORDER BY table_name"
+                        .to_string(),
+                    _ => generated_sql.clone(),
+                })
+                .collect();
+            query_scripts.push(script);
+        }
+
+        let session_context = self.ctx.runtime_context.get_session_context();
+        let scripts: Vec<Vec<String>> = std::iter::once(ddl_script).chain(query_scripts).collect();
+        let descriptions = std::iter::once("DDL task".to_string()).chain(
+            (1..=num_query_tasks).map(|task| format!("Query task {}/{}", task, num_query_tasks)),
+        );
+        let queries: Vec<String> = scripts.iter().map(|script| script.join(";\n")).collect();
+        let batch = ConcurrentBatch::with_scripts(
+            Arc::clone(&session_context),
+            scripts,
+            is_missing_table_error,
+        );
+        let mut query_group: Vec<QueryContext> = queries
+            .into_iter()
+            .zip(descriptions)
+            .enumerate()
+            .map(|(index, (query, description))| {
+                QueryContext::with_description(query, Arc::clone(&session_context), description)
+                    .with_engine(Arc::new(batch.engine(index)))
+            })
+            .collect();
+
+        query_group.push(QueryContext::with_description(
+            format!(
+                "SELECT count(*)\nFROM information_schema.tables\nWHERE table_name IN ('{}', '{}')",
+                table, view
+            ),
+            session_context,
+            "Main session, after the tasks".to_string(),
+        ));
+        self.objects = Some((table, view));
+
+        Ok(query_group)
+    }
+
+    async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
+        let (table, view) = self
+            .objects
+            .as_ref()
+            .ok_or_else(|| fuzzer_err("ConcurrentDdl oracle validated before generating"))?;
+
+        // Errors of the tasks are checked by the runner like for any other
+        // query, only the catalog left behind is checked here
+        let listed = results
+            .last()
+            .ok_or_else(|| fuzzer_err("ConcurrentDdl expects query results"))?;
+        let Ok(batches) = &listed.result else {
+            return Ok(());
+        };
+        let count = oracle_common::count_value(batches)?;
+        if count != 0 {
+            return Err(fuzzer_err(&format!(
+                "information_schema.tables still lists {} of {} and {} after the DDL task dropped them",
+                count, table, view
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
+        let mut report = String::new();
+        report.push_str("Concurrent DDL Oracle Test Failed\n");
+        report.push_str("=================================\n\n");
+
+        let labels: Vec<String> = results
+            .iter()
+            .map(|result| {
+                result
+                    .query_context
+                    .context_description
+                    .clone()
+                    .unwrap_or_else(|| "query".to_string())
+            })
+            .collect();
+        let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+        oracle_common::append_labeled_query_results(&mut report, results, &labels);
+
+        report.push_str(
+            "Expected: queries racing with CREATE and DROP only fail to find the table or \
+             view, and the catalog doesn't list the dropped objects afterwards\n",
+        );
+
+        Ok(report)
+    }
+
+    fn requires_consistent_outcomes(&self) -> bool {
+        // The tasks run different statements
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuzz_context::GlobalContext;
+    use crate::oracle::test_helpers::{self, register_int_table, run_group};
+
+    #[tokio::test]
+    async fn queries_race_with_ddl_gracefully() {
+        let ctx = Arc::new(GlobalContext::default());
+        register_int_table(&ctx, &[Some(2), None, Some(-3)]).await;
+        let session_context = ctx.runtime_context.get_session_context();
+        let missing = session_context
+            .sql("SELECT * FROM ddl_t0")
+            .await
+            .unwrap_err();
+        assert!(is_missing_table_error(&missing.to_string()));
+
+        for seed in 0..4 {
+            let mut oracle = ConcurrentDdlOracle::new(seed, Arc::clone(&ctx));
+            let query_group = oracle.generate_query_group().unwrap();
+            assert!(query_group.len() >= MIN_QUERY_TASKS + 2);
+
+            let results = run_group(query_group).await;
+            // Generated queries of the query tasks may fail with whitelisted
+            // errors, the DDL task can't fail
+            assert!(
+                results[0].result.is_ok(),
+                "DDL task failed: {:?}",
+                results[0].result
+            );
+            oracle.validate_consistency(&results).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn reports_objects_left_after_the_tasks() {
+        let mut oracle = ConcurrentDdlOracle::new(5, Arc::new(GlobalContext::default()));
+        oracle.objects = Some(("ddl_t5".to_string(), "ddl_v5".to_string()));
+        let mut results = vec![test_helpers::make_success_result(
+            "listed",
+            "count(*)",
+            vec![0],
+        )];
+        assert!(oracle.validate_consistency(&results).await.is_ok());
+
+        results[0] = test_helpers::make_success_result("listed", "count(*)", vec![1]);
+        let err = oracle.validate_consistency(&results).await.unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("still lists 1 of ddl_t5 and ddl_v5")
+        );
+    }
+}
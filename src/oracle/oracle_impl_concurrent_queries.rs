@@ -0,0 +1,239 @@
+use std::sync::Arc;
+
+use rand::Rng;
+
+use crate::common::rng::rng_from_seed;
+use crate::common::{InclusionConfig, Result, fuzzer_err};
+use crate::engine::concurrent_engine::ConcurrentBatch;
+use crate::oracle::{ConfiguredOracle, Oracle, QueryContext, QueryExecutionResult, oracle_common};
+use crate::query_generator::stmt_select_def::SelectStatementBuilder;
+use crate::triage::{FailureCategory, classify_error};
+
+/// Number of queries run at the same time
+const MIN_CONCURRENT_QUERIES: usize = 2;
+const MAX_CONCURRENT_QUERIES: usize = 4;
+
+/// Concurrent queries oracle.
+///
+/// Runs 2 to 4 generated queries at the same time in the embedded session,
+/// each on a task of its own, then runs them again one after the other. Each
+/// query must return the same rows both ways. Concurrent queries share the
+/// session's catalog, caches, memory pool and runtime, so races on that
+/// shared state show up as different rows, errors or panics. Sometimes the
+/// same query runs several times at once.
+///
+/// A query may run out of memory or time only when run concurrently, since it
+/// competes with the others for both.
+///
+/// ### Example:
+///
+/// SELECT t0.c0 FROM t0 WHERE t0.c1 > 2;        -- concurrently with
+/// SELECT count(*) FROM t1 JOIN t0 ON ...;      -- this query
+///
+/// SELECT t0.c0 FROM t0 WHERE t0.c1 > 2;        -- serially, then
+/// SELECT count(*) FROM t1 JOIN t0 ON ...;
+pub struct ConcurrentQueriesOracle {
+    seed: u64,
+    ctx: Arc<crate::fuzz_context::GlobalContext>,
+    /// Number of queries of the group run concurrently, set when generating
+    concurrent_queries: usize,
+}
+
+impl ConcurrentQueriesOracle {
+    pub fn new(seed: u64, ctx: Arc<crate::fuzz_context::GlobalContext>) -> Self {
+        Self {
+            seed,
+            ctx,
+            concurrent_queries: 0,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Oracle for ConcurrentQueriesOracle {
+    fn name(&self) -> &'static str {
+        "ConcurrentQueriesOracle"
+    }
+
+    fn generate_query_group(&mut self) -> Result<Vec<QueryContext>> {
+        let mut rng = rng_from_seed(self.seed);
+        let num_queries = rng.random_range(MIN_CONCURRENT_QUERIES..=MAX_CONCURRENT_QUERIES);
+        let repeated = rng.random_bool(0.3);
+
+        let mut queries = Vec::with_capacity(num_queries);
+        for _ in 0..num_queries {
+            if repeated && let Some(first) = queries.first() {
+                queries.push(String::clone(first));
+                continue;
+            }
+            let mut stmt_builder = SelectStatementBuilder::new(
+                rng.random(),
+                Arc::clone(&self.ctx),
+                InclusionConfig::Maybe(0.5),
+                InclusionConfig::Maybe(0.5),
+            )
+            .with_allow_derived_tables(false);
+            queries.push(stmt_builder.generate_stmt()?.to_sql_string()?);
+        }
+
+        let session_context = self.ctx.runtime_context.get_session_context();
+        let batch = ConcurrentBatch::new(Arc::clone(&session_context), queries.clone());
+        let concurrent = queries.iter().enumerate().map(|(index, sql)| {
+            QueryContext::with_description(
+                sql.clone(),
+                Arc::clone(&session_context),
+                format!("Concurrently with {} other queries", num_queries - 1),
+            )
+            .with_engine(Arc::new(batch.engine(index)))
+        });
+        let serial = queries.iter().map(|sql| {
+            QueryContext::with_description(
+                sql.clone(),
+                Arc::clone(&session_context),
+                "Serially".to_string(),
+            )
+        });
+        let query_group = concurrent.chain(serial).collect();
+        self.concurrent_queries = num_queries;
+
+        Ok(query_group)
+    }
+
+    async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
+        let num_queries = self.concurrent_queries;
+        if num_queries == 0 || results.len() != 2 * num_queries {
+            return Err(fuzzer_err(&format!(
+                "ConcurrentQueries expects {} query results, got {}",
+                2 * num_queries,
+                results.len()
+            )));
+        }
+
+        let options = self
+            .ctx
+            .runner_config
+            .compare_options(ConfiguredOracle::ConcurrentQueries);
+        for index in 0..num_queries {
+            let serial_index = num_queries + index;
+            match (&results[index].result, &results[serial_index].result) {
+                (Ok(_), Ok(_)) => oracle_common::validate_value_equivalence(
+                    results,
+                    index,
+                    serial_index,
+                    "ConcurrentQueries",
+                    &options,
+                )?,
+                (Err(e), Ok(_)) => {
+                    // The other queries compete for memory and time
+                    if matches!(
+                        classify_error(&e.to_string()),
+                        FailureCategory::ResourceExhausted | FailureCategory::Timeout
+                    ) {
+                        continue;
+                    }
+                    return Err(fuzzer_err(&format!(
+                        "Query {} fails only when run concurrently with {} other queries: {}",
+                        index + 1,
+                        num_queries - 1,
+                        e
+                    )));
+                }
+                (Ok(_), Err(e)) => {
+                    if classify_error(&e.to_string()) == FailureCategory::Timeout {
+                        continue;
+                    }
+                    return Err(fuzzer_err(&format!(
+                        "Query {} fails only when run serially: {}",
+                        index + 1,
+                        e
+                    )));
+                }
+                (Err(_), Err(_)) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
+        let mut report = String::new();
+        report.push_str("Concurrent Queries Oracle Test Failed\n");
+        report.push_str("=====================================\n\n");
+
+        let labels: Vec<String> = results
+            .iter()
+            .map(|result| {
+                result
+                    .query_context
+                    .context_description
+                    .clone()
+                    .unwrap_or_else(|| "query".to_string())
+            })
+            .collect();
+        let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+        oracle_common::append_labeled_query_results(&mut report, results, &labels);
+
+        report.push_str(
+            "Expected: every query returns the same rows when run concurrently with the \
+             others as when run alone\n",
+        );
+
+        Ok(report)
+    }
+
+    fn requires_consistent_outcomes(&self) -> bool {
+        // Only each query's concurrent and serial runs are equivalent
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuzz_context::GlobalContext;
+    use crate::oracle::test_helpers::{self, register_int_table, run_group};
+
+    #[tokio::test]
+    async fn concurrent_and_serial_runs_agree() {
+        let ctx = Arc::new(GlobalContext::default());
+        register_int_table(&ctx, &[Some(2), None, Some(-3)]).await;
+
+        for seed in 0..4 {
+            let mut oracle = ConcurrentQueriesOracle::new(seed, Arc::clone(&ctx));
+            let query_group = oracle.generate_query_group().unwrap();
+            let num_queries = query_group.len() / 2;
+            assert!(num_queries >= MIN_CONCURRENT_QUERIES);
+            assert!(
+                query_group[..num_queries]
+                    .iter()
+                    .all(|q| q.engine.is_some())
+            );
+            assert!(
+                query_group[num_queries..]
+                    .iter()
+                    .all(|q| q.engine.is_none())
+            );
+
+            let results = run_group(query_group).await;
+            oracle.validate_consistency(&results).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn reports_errors_only_seen_concurrently() {
+        let mut oracle = ConcurrentQueriesOracle::new(1, Arc::new(GlobalContext::default()));
+        oracle.concurrent_queries = 1;
+        let mut results = vec![
+            test_helpers::make_success_result("concurrent", "c0", vec![1, 2]),
+            test_helpers::make_success_result("serial", "c0", vec![1, 2]),
+        ];
+        assert!(oracle.validate_consistency(&results).await.is_ok());
+
+        results[0].result = Err(fuzzer_err("Internal error: unexpected state"));
+        let err = oracle.validate_consistency(&results).await.unwrap_err();
+        assert!(err.to_string().contains("only when run concurrently"));
+
+        results[0].result = Err(fuzzer_err("Resources exhausted: memory pool"));
+        assert!(oracle.validate_consistency(&results).await.is_ok());
+    }
+}
@@ -0,0 +1,351 @@
+use std::sync::Arc;
+
+use datafusion::arrow::array::{Array, AsArray, RecordBatch};
+use datafusion::arrow::datatypes::{DataType, Int64Type};
+use rand::{Rng, RngCore};
+
+use crate::common::rng::rng_from_seed;
+use crate::common::util::to_sql_string;
+use crate::common::{Result, fuzzer_err};
+use crate::oracle::ground_truth::column_expr;
+use crate::oracle::{Oracle, QueryContext, QueryExecutionResult, oracle_common};
+use crate::query_generator::expr_gen::ExprGenerator;
+
+/// Probability that the counted rows are filtered by a generated predicate
+const WHERE_PROBABILITY: f64 = 0.7;
+
+/// The aggregates counted by each query, in order
+const COUNT_LABELS: [&str; 5] = [
+    "COUNT(*)",
+    "COUNT(col)",
+    "COUNT(DISTINCT col)",
+    "COUNT(*) WHERE col IS NULL",
+    "all counts in one query",
+];
+
+/// COUNT consistency oracle.
+///
+/// Counts the rows of a table, optionally filtered by a generated predicate,
+/// in separate queries with `COUNT(*)`, `COUNT(col)` and
+/// `COUNT(DISTINCT col)`, and counts the NULL values of the column with
+/// `IS NULL`. A last query computes the first three counts at once.
+///
+/// ### Example:
+///
+/// SELECT COUNT(*) FROM t1 WHERE p;
+/// SELECT COUNT(t1.c0) FROM t1 WHERE p;
+/// SELECT COUNT(DISTINCT t1.c0) FROM t1 WHERE p;
+/// SELECT COUNT(*) FROM t1 WHERE (p) AND t1.c0 IS NULL;
+/// SELECT COUNT(*), COUNT(t1.c0), COUNT(DISTINCT t1.c0) FROM t1 WHERE p;
+///
+/// The counts must satisfy `COUNT(*) >= COUNT(col) >= COUNT(DISTINCT col)`,
+/// `COUNT(*) - COUNT(col)` must be the number of NULL values, there must be a
+/// distinct value as soon as there is a non-NULL one, and the last query must
+/// return the counts of the separate ones.
+pub struct CountConsistencyOracle {
+    seed: u64,
+    ctx: Arc<crate::fuzz_context::GlobalContext>,
+}
+
+impl CountConsistencyOracle {
+    pub fn new(seed: u64, ctx: Arc<crate::fuzz_context::GlobalContext>) -> Self {
+        Self { seed, ctx }
+    }
+}
+
+#[async_trait::async_trait]
+impl Oracle for CountConsistencyOracle {
+    fn name(&self) -> &'static str {
+        "CountConsistencyOracle"
+    }
+
+    fn generate_query_group(&mut self) -> Result<Vec<QueryContext>> {
+        let tables: Vec<_> = self
+            .ctx
+            .runtime_context
+            .tables()
+            .values()
+            .filter(|table| !table.columns.is_empty())
+            .cloned()
+            .collect();
+        if tables.is_empty() {
+            return Err(fuzzer_err(
+                "COUNT consistency oracle requires a table with columns",
+            ));
+        }
+
+        let mut rng = rng_from_seed(self.seed);
+        let table = &tables[rng.random_range(0..tables.len())];
+        let column = &table.columns[rng.random_range(0..table.columns.len())];
+        let column_sql = to_sql_string(&column_expr(table, column))?;
+
+        let predicate = if rng.random_bool(WHERE_PROBABILITY) {
+            let mut expr_gen = ExprGenerator::new(rng.next_u64(), Arc::clone(&self.ctx))
+                .with_src_columns(Arc::new(ExprGenerator::tables_to_columns(
+                    std::slice::from_ref(table),
+                    &self.ctx,
+                )));
+            Some(to_sql_string(
+                &expr_gen.generate_random_expr(DataType::Boolean, 0),
+            )?)
+        } else {
+            None
+        };
+        let source = |condition: Option<&str>| {
+            let mut sql = format!("FROM {}", table.sql_name());
+            match (&predicate, condition) {
+                (Some(predicate), Some(condition)) => {
+                    sql.push_str(&format!("\nWHERE ({}) AND {}", predicate, condition))
+                }
+                (Some(predicate), None) => sql.push_str(&format!("\nWHERE {}", predicate)),
+                (None, Some(condition)) => sql.push_str(&format!("\nWHERE {}", condition)),
+                (None, None) => {}
+            }
+            sql
+        };
+
+        let queries = [
+            format!("SELECT COUNT(*)\n{}", source(None)),
+            format!("SELECT COUNT({})\n{}", column_sql, source(None)),
+            format!("SELECT COUNT(DISTINCT {})\n{}", column_sql, source(None)),
+            format!(
+                "SELECT COUNT(*)\n{}",
+                source(Some(&format!("{} IS NULL", column_sql)))
+            ),
+            format!(
+                "SELECT COUNT(*), COUNT({}), COUNT(DISTINCT {})\n{}",
+                column_sql,
+                column_sql,
+                source(None)
+            ),
+        ];
+
+        let session_context = self.ctx.runtime_context.get_session_context();
+        Ok(queries
+            .into_iter()
+            .zip(COUNT_LABELS)
+            .map(|(query, label)| {
+                QueryContext::with_description(
+                    query,
+                    Arc::clone(&session_context),
+                    label.to_string(),
+                )
+            })
+            .collect())
+    }
+
+    async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
+        if results.len() != COUNT_LABELS.len() {
+            return Err(fuzzer_err(&format!(
+                "COUNT consistency oracle expects {} query results, got {}",
+                COUNT_LABELS.len(),
+                results.len()
+            )));
+        }
+        // Only successful queries are compared, e.g. COUNT(DISTINCT) fails
+        // alone on types without DISTINCT support
+        let count = |index: usize| -> Result<Option<i64>> {
+            match &results[index].result {
+                Ok(batches) => Ok(Some(oracle_common::count_value(batches)?)),
+                Err(_) => Ok(None),
+            }
+        };
+        let count_star = count(0)?;
+        let count_col = count(1)?;
+        let count_distinct = count(2)?;
+        let null_count = count(3)?;
+
+        let violated = |relation: &str| {
+            let show = |count: Option<i64>| count.map_or("error".to_string(), |c| c.to_string());
+            Err(fuzzer_err(&format!(
+                "COUNT consistency violated: {} (COUNT(*) = {}, COUNT(col) = {}, COUNT(DISTINCT col) = {}, NULL values = {})",
+                relation,
+                show(count_star),
+                show(count_col),
+                show(count_distinct),
+                show(null_count)
+            )))
+        };
+        if let (Some(count_star), Some(count_col)) = (count_star, count_col) {
+            if count_col > count_star {
+                return violated("COUNT(col) > COUNT(*)");
+            }
+            if null_count.is_some_and(|null_count| count_star - count_col != null_count) {
+                return violated("COUNT(*) - COUNT(col) differs from the number of NULL values");
+            }
+        }
+        if let (Some(count_col), Some(count_distinct)) = (count_col, count_distinct) {
+            if count_distinct > count_col {
+                return violated("COUNT(DISTINCT col) > COUNT(col)");
+            }
+            if count_col > 0 && count_distinct == 0 {
+                return violated("COUNT(DISTINCT col) is 0 with non-NULL values");
+            }
+        }
+
+        if let Ok(batches) = &results[4].result {
+            let combined = row_counts(batches)?;
+            let separate = [count_star, count_col, count_distinct];
+            let differs = combined
+                .iter()
+                .zip(separate)
+                .any(|(combined, separate)| separate.is_some_and(|separate| separate != *combined));
+            if combined.len() != separate.len() || differs {
+                return Err(fuzzer_err(&format!(
+                    "COUNT consistency violated: computing the counts in one query returned {:?}, the separate queries {:?}",
+                    combined, separate
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
+        let mut report = String::new();
+        report.push_str("COUNT Consistency Oracle Test Failed\n");
+        report.push_str("====================================\n\n");
+
+        oracle_common::append_labeled_query_results(&mut report, results, &COUNT_LABELS);
+        report.push_str(
+            "Expected: COUNT(*) >= COUNT(col) >= COUNT(DISTINCT col), COUNT(*) - COUNT(col) = NULL values, and the same counts from the combined query\n",
+        );
+
+        Ok(report)
+    }
+
+    fn requires_consistent_outcomes(&self) -> bool {
+        // COUNT(DISTINCT) may fail alone
+        false
+    }
+}
+
+/// The `Int64` values of the single row of a result
+fn row_counts(batches: &[RecordBatch]) -> Result<Vec<i64>> {
+    let mut rows = batches.iter().filter(|batch| batch.num_rows() > 0);
+    let (Some(batch), None) = (rows.next(), rows.next()) else {
+        return Err(fuzzer_err("Expected a single row of counts"));
+    };
+    if batch.num_rows() != 1 {
+        return Err(fuzzer_err("Expected a single row of counts"));
+    }
+    batch
+        .columns()
+        .iter()
+        .map(|column| {
+            column
+                .as_primitive_opt::<Int64Type>()
+                .filter(|array| array.is_valid(0))
+                .map(|array| array.value(0))
+                .ok_or_else(|| fuzzer_err("Expected non-NULL Int64 counts"))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::init_available_data_types;
+    use crate::oracle::test_helpers;
+    use crate::oracle::test_helpers::table_with_int_values;
+    use datafusion::arrow::array::Int64Array;
+    use datafusion::arrow::datatypes::{Field, Schema};
+
+    #[test]
+    fn counts_the_same_rows_in_every_query() {
+        init_available_data_types();
+        let ctx = Arc::new(crate::fuzz_context::GlobalContext::default());
+        ctx.runtime_context
+            .register_table(Arc::new(table_with_int_values(&[Some(1), None, Some(1)])));
+
+        for seed in 0..16 {
+            let mut oracle = CountConsistencyOracle::new(seed, Arc::clone(&ctx));
+            let query_group = oracle.generate_query_group().unwrap();
+            assert_eq!(query_group.len(), COUNT_LABELS.len());
+            assert!(
+                query_group[2]
+                    .query
+                    .starts_with("SELECT COUNT(DISTINCT t1.c0)\nFROM t1")
+            );
+            let source = query_group[0]
+                .query
+                .strip_prefix("SELECT COUNT(*)\n")
+                .unwrap();
+            assert!(query_group[4].query.ends_with(source));
+            assert!(query_group[3].query.ends_with("t1.c0 IS NULL"));
+        }
+    }
+
+    #[tokio::test]
+    async fn counts_must_be_consistent() {
+        let oracle =
+            CountConsistencyOracle::new(1, Arc::new(crate::fuzz_context::GlobalContext::default()));
+        let results = |counts: [i64; 4], combined: [i64; 3]| {
+            let mut results: Vec<QueryExecutionResult> = counts
+                .iter()
+                .zip(COUNT_LABELS)
+                .map(|(count, label)| {
+                    test_helpers::make_success_result(label, "count", vec![*count])
+                })
+                .collect();
+            let schema = Arc::new(Schema::new(
+                ["count(*)", "count(c0)", "count(DISTINCT c0)"]
+                    .map(|name| Field::new(name, DataType::Int64, false))
+                    .to_vec(),
+            ));
+            let columns = combined
+                .map(|count| Arc::new(Int64Array::from(vec![count])) as Arc<dyn Array>)
+                .to_vec();
+            results.push(QueryExecutionResult {
+                result: Ok(vec![RecordBatch::try_new(schema, columns).unwrap()]),
+                ..test_helpers::make_error_result("combined")
+            });
+            results
+        };
+
+        assert!(
+            oracle
+                .validate_consistency(&results([5, 3, 2, 2], [5, 3, 2]))
+                .await
+                .is_ok()
+        );
+
+        let err = oracle
+            .validate_consistency(&results([5, 3, 2, 1], [5, 3, 2]))
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("differs from the number of NULL values")
+        );
+
+        let err = oracle
+            .validate_consistency(&results([5, 3, 4, 2], [5, 3, 4]))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("COUNT(DISTINCT col) > COUNT(col)"));
+
+        let err = oracle
+            .validate_consistency(&results([5, 3, 2, 2], [5, 3, 3]))
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("computing the counts in one query")
+        );
+
+        let mut failed = results([5, 3, 4, 2], [5, 3, 4]);
+        failed[2] = test_helpers::make_error_result("distinct");
+        failed[4] = test_helpers::make_error_result("combined");
+        assert!(oracle.validate_consistency(&failed).await.is_ok());
+
+        // The queries that succeeded are still compared
+        let mut failed = results([5, 3, 2, 1], [5, 3, 2]);
+        failed[2] = test_helpers::make_error_result("distinct");
+        failed[4] = test_helpers::make_error_result("combined");
+        let err = oracle.validate_consistency(&failed).await.unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("differs from the number of NULL values")
+        );
+    }
+}
@@ -0,0 +1,128 @@
+use crate::common::{Result, fuzzer_err};
+use crate::engine::dataframe_engine::DataFrameEngine;
+use crate::oracle::{ConfiguredOracle, Oracle, QueryContext, QueryExecutionResult, oracle_common};
+use crate::query_generator::plan_builder::LogicalPlanGenerator;
+use std::sync::Arc;
+
+/// DataFrame API oracle.
+///
+/// Generates a query as a `LogicalPlan` with `LogicalPlanBuilder` instead of
+/// SQL text, and runs it twice in the embedded session: unparsed into SQL and
+/// planned again by the SQL planner, and directly through the `DataFrame` API.
+/// Both must return the same rows, so bugs in the unparser (SQL that means
+/// something else than the plan) and plans the SQL planner never produces
+/// both show up.
+///
+/// ### Example:
+///
+/// SELECT t0.c0 + 1 AS c0 FROM t0 WHERE t0.c1 > 2;   -- unparsed SQL
+///
+/// Projection: t0.c0 + 1 AS c0                        -- DataFrame API
+///   Filter: t0.c1 > 2
+///     TableScan: t0
+pub struct DataFrameOracle {
+    seed: u64,
+    ctx: Arc<crate::fuzz_context::GlobalContext>,
+}
+
+impl DataFrameOracle {
+    pub fn new(seed: u64, ctx: Arc<crate::fuzz_context::GlobalContext>) -> Self {
+        Self { seed, ctx }
+    }
+}
+
+#[async_trait::async_trait]
+impl Oracle for DataFrameOracle {
+    fn name(&self) -> &'static str {
+        "DataFrameOracle"
+    }
+
+    fn generate_query_group(&mut self) -> Result<Vec<QueryContext>> {
+        let plan = LogicalPlanGenerator::new(self.seed, Arc::clone(&self.ctx)).generate_plan()?;
+        let sql = plan.to_sql()?;
+
+        let session_context = self.ctx.runtime_context.get_session_context();
+        let engine = Arc::new(DataFrameEngine::new(Arc::clone(&session_context), plan));
+        Ok(vec![
+            QueryContext::with_description(
+                sql.clone(),
+                Arc::clone(&session_context),
+                "Unparsed SQL".to_string(),
+            ),
+            QueryContext::with_description(sql, session_context, "DataFrame API".to_string())
+                .with_engine(engine),
+        ])
+    }
+
+    async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
+        if results.len() != 2 {
+            return Err(fuzzer_err(&format!(
+                "DataFrameOracle expects 2 query results, got {}",
+                results.len()
+            )));
+        }
+
+        // Both succeeding or both failing is checked by the runner beforehand
+        if results.iter().any(|result| result.result.is_err()) {
+            return Ok(());
+        }
+
+        let options = self
+            .ctx
+            .runner_config
+            .compare_options(ConfiguredOracle::DataFrame);
+        oracle_common::validate_value_equivalence(results, 0, 1, "DataFrame", &options)
+    }
+
+    fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
+        let mut report = String::new();
+        report.push_str("DataFrame API Oracle Test Failed\n");
+        report.push_str("================================\n\n");
+
+        oracle_common::append_labeled_query_results(
+            &mut report,
+            results,
+            &["unparsed SQL", "DataFrame API"],
+        );
+
+        report.push_str(
+            "Expected: the unparsed SQL of a logical plan returns the same rows as the plan\n",
+        );
+        report
+            .push_str("The DataFrame API query runs the logical plan the SQL was unparsed from.\n");
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuzz_context::GlobalContext;
+    use crate::oracle::test_helpers::{self, register_int_table, run_group};
+
+    #[tokio::test]
+    async fn runs_the_plan_as_sql_and_as_dataframe() {
+        let ctx = Arc::new(GlobalContext::default());
+        register_int_table(&ctx, &[Some(2), None, Some(-3)]).await;
+
+        let mut oracle = DataFrameOracle::new(3, Arc::clone(&ctx));
+        let query_group = oracle.generate_query_group().unwrap();
+        assert_eq!(query_group.len(), 2);
+        assert_eq!(query_group[0].query, query_group[1].query);
+        assert!(query_group[0].engine.is_none());
+
+        let results = run_group(query_group).await;
+        assert!(oracle.validate_consistency(&results).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn reports_different_rows() {
+        let oracle = DataFrameOracle::new(1, Arc::new(GlobalContext::default()));
+        let results = vec![
+            test_helpers::make_success_result("sql", "c0", vec![1, 2]),
+            test_helpers::make_success_result("dataframe", "c0", vec![1]),
+        ];
+        assert!(oracle.validate_consistency(&results).await.is_err());
+    }
+}
@@ -0,0 +1,126 @@
+use crate::common::{Result, fuzzer_err};
+use crate::engine::dataframe_engine::DataFrameEngine;
+use crate::oracle::{ConfiguredOracle, Oracle, QueryContext, QueryExecutionResult, oracle_common};
+use crate::query_generator::dataframe_chain::DataFrameChainGenerator;
+use std::sync::Arc;
+
+/// DataFrame operation chain oracle.
+///
+/// Generates a random chain of `DataFrame` operations (filter, select,
+/// aggregate, sort with limit, join) starting from a registered table, and
+/// runs it through the `DataFrame` API and as the equivalent nested SQL query.
+/// Programs build queries this way without ever going through the SQL
+/// planner, so the chain must neither panic nor return other rows than the
+/// SQL query.
+///
+/// ### Example:
+///
+/// ctx.table("t0")?.filter(c0 > 1)?.select([c0 + 1 AS s2_0])?   -- DataFrame API
+///
+/// SELECT c0 + 1 AS s2_0
+/// FROM (SELECT * FROM (SELECT * FROM t0) AS q1 WHERE c0 > 1) AS q2   -- SQL
+pub struct DataFrameChainOracle {
+    seed: u64,
+    ctx: Arc<crate::fuzz_context::GlobalContext>,
+}
+
+impl DataFrameChainOracle {
+    pub fn new(seed: u64, ctx: Arc<crate::fuzz_context::GlobalContext>) -> Self {
+        Self { seed, ctx }
+    }
+}
+
+#[async_trait::async_trait]
+impl Oracle for DataFrameChainOracle {
+    fn name(&self) -> &'static str {
+        "DataFrameChainOracle"
+    }
+
+    fn generate_query_group(&mut self) -> Result<Vec<QueryContext>> {
+        let chain =
+            DataFrameChainGenerator::new(self.seed, Arc::clone(&self.ctx)).generate_chain()?;
+        let sql = chain.to_sql()?;
+
+        let session_context = self.ctx.runtime_context.get_session_context();
+        let engine = Arc::new(DataFrameEngine::new(Arc::clone(&session_context), chain));
+        Ok(vec![
+            QueryContext::with_description(
+                sql.clone(),
+                Arc::clone(&session_context),
+                "Equivalent SQL".to_string(),
+            ),
+            QueryContext::with_description(
+                sql,
+                session_context,
+                "DataFrame operations".to_string(),
+            )
+            .with_engine(engine),
+        ])
+    }
+
+    async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
+        if results.len() != 2 {
+            return Err(fuzzer_err(&format!(
+                "DataFrameChainOracle expects 2 query results, got {}",
+                results.len()
+            )));
+        }
+
+        // Both succeeding or both failing is checked by the runner beforehand
+        if results.iter().any(|result| result.result.is_err()) {
+            return Ok(());
+        }
+
+        let options = self
+            .ctx
+            .runner_config
+            .compare_options(ConfiguredOracle::DataFrameChain);
+        oracle_common::validate_value_equivalence(results, 0, 1, "DataFrameChain", &options)
+    }
+
+    fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
+        let mut report = String::new();
+        report.push_str("DataFrame Chain Oracle Test Failed\n");
+        report.push_str("==================================\n\n");
+
+        oracle_common::append_labeled_query_results(
+            &mut report,
+            results,
+            &["SQL", "DataFrame operations"],
+        );
+
+        report.push_str(
+            "Expected: a chain of DataFrame operations returns the same rows as the equivalent SQL\n",
+        );
+        report.push_str("Each nesting level of the SQL is one DataFrame operation.\n");
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuzz_context::GlobalContext;
+    use crate::oracle::test_helpers::{register_int_table, run_group};
+
+    #[tokio::test]
+    async fn runs_chains_as_dataframes_and_as_sql() {
+        let ctx = Arc::new(GlobalContext::default());
+        register_int_table(&ctx, &[Some(4), None, Some(-1)]).await;
+
+        let mut compared = 0;
+        for seed in 0..16 {
+            let mut oracle = DataFrameChainOracle::new(seed, Arc::clone(&ctx));
+            let query_group = oracle.generate_query_group().unwrap();
+            assert_eq!(query_group.len(), 2);
+
+            let results = run_group(query_group).await;
+            if results.iter().all(|result| result.result.is_ok()) {
+                oracle.validate_consistency(&results).await.unwrap();
+                compared += 1;
+            }
+        }
+        assert!(compared > 0);
+    }
+}
@@ -0,0 +1,246 @@
+use std::sync::Arc;
+
+use rand::Rng;
+
+use crate::common::rng::rng_from_seed;
+use crate::common::util::quote_identifier;
+use crate::common::{Result, fuzzer_err};
+use crate::engine::planning_time_engine::PlanningTimeEngine;
+use crate::fuzz_context::deep_recursion_session_context;
+use crate::oracle::{ConfiguredOracle, Oracle, QueryContext, QueryExecutionResult, oracle_common};
+use crate::query_generator::deep_nesting::NestingShape;
+
+/// Parser recursion levels allowed per nesting level in the deep session, an
+/// expression or subquery level takes a few
+const PARSER_RECURSION_PER_LEVEL: usize = 4;
+
+/// Number of queries of a group: three planning times, the deepest query, its
+/// flat equivalent and the deepest query with the default recursion limit
+const QUERY_GROUP_SIZE: usize = 6;
+
+/// Deep nesting stress oracle.
+///
+/// Nests one shape (arithmetic, function calls, CASE, NOT, OR, parentheses,
+/// derived tables or scalar subqueries) over a generated table thousands of
+/// levels deep, up to `max_nesting_depth`, to find stack overflows in the
+/// parser, planner, optimizer and expression evaluation. The parser recursion
+/// limit is raised so the queries get past the parser.
+///
+/// The same shape is planned at a quarter, half and all of the depth: planning
+/// time must grow about linearly with the depth. The deepest query must return
+/// the same rows as its flat equivalent. With the default recursion limit it
+/// must return them too, or be rejected with a (whitelisted) recursion limit
+/// error.
+///
+/// ### Example:
+///
+/// SELECT count(*) AS v FROM t1 WHERE NOT (NOT (NOT (... t1.c0 IS NULL)))
+/// SELECT count(*) AS v FROM t1 WHERE NOT (t1.c0 IS NULL)
+pub struct DeepNestingOracle {
+    seed: u64,
+    ctx: Arc<crate::fuzz_context::GlobalContext>,
+    /// Depths of the planned queries, set when generating
+    depths: Option<[usize; 3]>,
+}
+
+impl DeepNestingOracle {
+    pub fn new(seed: u64, ctx: Arc<crate::fuzz_context::GlobalContext>) -> Self {
+        Self {
+            seed,
+            ctx,
+            depths: None,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Oracle for DeepNestingOracle {
+    fn name(&self) -> &'static str {
+        "DeepNestingOracle"
+    }
+
+    fn generate_query_group(&mut self) -> Result<Vec<QueryContext>> {
+        let tables = self.ctx.runtime_context.tables();
+        let tables: Vec<_> = tables
+            .values()
+            .filter(|table| !table.columns.is_empty())
+            .collect();
+        if tables.is_empty() {
+            return Err(fuzzer_err(
+                "DeepNesting oracle requires a table with columns",
+            ));
+        }
+
+        let mut rng = rng_from_seed(self.seed);
+        let table = tables[rng.random_range(0..tables.len())];
+        let column = &table.columns[rng.random_range(0..table.columns.len())];
+        let table_name = table.sql_name();
+        let predicate = format!("{}.{} IS NULL", table_name, quote_identifier(&column.name));
+        let shape = NestingShape::random(&mut rng);
+        let max_depth = self.ctx.runner_config.max_nesting_depth as usize;
+        let depth = rng.random_range(max_depth / 2..=max_depth);
+        let depths = [(depth / 4).max(1), (depth / 2).max(1), depth];
+
+        let main_context = self.ctx.runtime_context.get_session_context();
+        let deep_context =
+            deep_recursion_session_context(&main_context, depth * PARSER_RECURSION_PER_LEVEL + 64);
+
+        let mut query_group: Vec<QueryContext> = depths
+            .iter()
+            .map(|&planned_depth| {
+                QueryContext::with_description(
+                    shape.nested_query(&table_name, &predicate, planned_depth),
+                    Arc::clone(&deep_context),
+                    format!("Planning time, {} nested {} deep", shape, planned_depth),
+                )
+                .with_engine(Arc::new(PlanningTimeEngine::new(Arc::clone(&deep_context))))
+            })
+            .collect();
+        let nested_sql = shape.nested_query(&table_name, &predicate, depth);
+        query_group.push(QueryContext::with_description(
+            nested_sql.clone(),
+            deep_context,
+            format!("{} nested {} deep", shape, depth),
+        ));
+        query_group.push(QueryContext::with_description(
+            shape.flat_query(&table_name, &predicate, depth),
+            Arc::clone(&main_context),
+            "Without nesting".to_string(),
+        ));
+        query_group.push(QueryContext::with_description(
+            nested_sql,
+            main_context,
+            format!("{} nested {} deep, default recursion limit", shape, depth),
+        ));
+        self.depths = Some(depths);
+
+        Ok(query_group)
+    }
+
+    async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
+        let depths = self
+            .depths
+            .ok_or_else(|| fuzzer_err("DeepNesting oracle validated before generating"))?;
+        if results.len() != QUERY_GROUP_SIZE {
+            return Err(fuzzer_err(&format!(
+                "DeepNesting expects {} query results, got {}",
+                QUERY_GROUP_SIZE,
+                results.len()
+            )));
+        }
+
+        oracle_common::validate_planning_time_growth(
+            results,
+            0,
+            2,
+            (depths[0], depths[2]),
+            "nesting depth",
+        )?;
+
+        let options = self
+            .ctx
+            .runner_config
+            .compare_options(ConfiguredOracle::DeepNesting);
+        for nested_index in [3, 5] {
+            if results[nested_index].result.is_ok() && results[4].result.is_ok() {
+                oracle_common::validate_value_equivalence(
+                    results,
+                    nested_index,
+                    4,
+                    "DeepNesting",
+                    &options,
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
+        let mut report = String::new();
+        report.push_str("Deep Nesting Oracle Test Failed\n");
+        report.push_str("===============================\n\n");
+
+        let labels: Vec<String> = results
+            .iter()
+            .map(|result| {
+                result
+                    .query_context
+                    .context_description
+                    .clone()
+                    .unwrap_or_else(|| "query".to_string())
+            })
+            .collect();
+        let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+        oracle_common::append_labeled_query_results(&mut report, results, &labels);
+        oracle_common::append_planning_times(&mut report, results, &[0, 1, 2]);
+
+        report.push_str(
+            "Expected: planning time grows about linearly with the nesting depth, and deeply \
+             nested queries return the same rows as without nesting\n",
+        );
+
+        Ok(report)
+    }
+
+    fn requires_consistent_outcomes(&self) -> bool {
+        // The default recursion limit may reject the deepest query only
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuzz_context::{GlobalContext, RunnerConfig};
+    use crate::oracle::test_helpers::{self, context_with_config, register_int_table, run_group};
+
+    fn context_with_max_depth(max_nesting_depth: u32) -> Arc<GlobalContext> {
+        context_with_config(RunnerConfig {
+            max_nesting_depth,
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn deep_queries_match_their_flat_equivalents() {
+        let ctx = context_with_max_depth(64);
+        register_int_table(&ctx, &[Some(2), None, Some(-3)]).await;
+
+        for seed in 0..16 {
+            let mut oracle = DeepNestingOracle::new(seed, Arc::clone(&ctx));
+            let results = run_group(oracle.generate_query_group().unwrap()).await;
+            for result in &results[..5] {
+                assert!(
+                    result.result.is_ok(),
+                    "{}: {:?}",
+                    result.query_context.query,
+                    result.result
+                );
+            }
+            if let Err(e) = &results[5].result {
+                assert!(e.to_string().contains("RecursionLimitExceeded"), "{}", e);
+            }
+            oracle.validate_consistency(&results).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn reports_planning_time_growing_non_linearly() {
+        let mut oracle = DeepNestingOracle::new(0, context_with_max_depth(2000));
+        oracle.depths = Some([400, 800, 1600]);
+        let mut results = vec![
+            test_helpers::make_success_result("quarter", "planning_us", vec![50_000]),
+            test_helpers::make_success_result("half", "planning_us", vec![100_000]),
+            test_helpers::make_success_result("all", "planning_us", vec![300_000]),
+            test_helpers::make_success_result("nested", "v", vec![1]),
+            test_helpers::make_success_result("flat", "v", vec![1]),
+            test_helpers::make_error_result("default limit"),
+        ];
+        assert!(oracle.validate_consistency(&results).await.is_ok());
+
+        results[2] = test_helpers::make_success_result("all", "planning_us", vec![1_000_000]);
+        let err = oracle.validate_consistency(&results).await.unwrap_err();
+        assert!(err.to_string().contains("grows non-linearly"));
+    }
+}
@@ -0,0 +1,223 @@
+use crate::common::rng::rng_from_seed;
+use crate::common::{InclusionConfig, Result, fuzzer_err};
+use crate::engine::EngineKind;
+use crate::oracle::{ConfiguredOracle, Oracle, QueryContext, QueryExecutionResult, oracle_common};
+use crate::query_generator::stmt_select_def::SelectStatementBuilder;
+use rand::Rng;
+use std::sync::Arc;
+
+/// Differential oracle across engines.
+///
+/// Runs the same query in the embedded DataFusion session and on another
+/// engine under test, picked from the engines configured with
+/// `flight_sql_endpoint` and `reference_engines`, which hold a mirror of the
+/// generated tables. Both must return the same rows.
+///
+/// - A remote DataFusion instance over Flight SQL must also fail exactly when
+///   the embedded session fails, so bugs in the server deployment or in the
+///   Flight SQL layer (e.g. type conversions or lost batches) show up.
+/// - DuckDB and SQLite speak other SQL dialects and reject many generated
+///   queries, only queries both engines run successfully are compared.
+///
+/// The FlightSql oracle is this oracle restricted to the Flight SQL engine.
+///
+/// ### Example:
+///
+/// SELECT t0.c0 + 1 FROM t0;   -- embedded
+///
+/// SELECT t0.c0 + 1 FROM t0;   -- on DuckDB
+pub struct EngineDiffOracle {
+    seed: u64,
+    ctx: Arc<crate::fuzz_context::GlobalContext>,
+    /// Only compare with engines of this kind
+    kind_filter: Option<EngineKind>,
+    /// Kind of the engine the generated query group runs on
+    engine_kind: Option<EngineKind>,
+}
+
+impl EngineDiffOracle {
+    pub fn new(seed: u64, ctx: Arc<crate::fuzz_context::GlobalContext>) -> Self {
+        Self {
+            seed,
+            ctx,
+            kind_filter: None,
+            engine_kind: None,
+        }
+    }
+
+    /// Only compare with the remote DataFusion instance over Flight SQL
+    pub fn flight_sql(seed: u64, ctx: Arc<crate::fuzz_context::GlobalContext>) -> Self {
+        Self {
+            kind_filter: Some(EngineKind::FlightSql),
+            ..Self::new(seed, ctx)
+        }
+    }
+
+    fn configured_oracle(&self) -> ConfiguredOracle {
+        match self.kind_filter {
+            Some(EngineKind::FlightSql) => ConfiguredOracle::FlightSql,
+            _ => ConfiguredOracle::EngineDiff,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Oracle for EngineDiffOracle {
+    fn name(&self) -> &'static str {
+        match self.configured_oracle() {
+            ConfiguredOracle::FlightSql => "FlightSqlOracle",
+            _ => "EngineDiffOracle",
+        }
+    }
+
+    fn generate_query_group(&mut self) -> Result<Vec<QueryContext>> {
+        let candidates: Vec<_> = self
+            .ctx
+            .engines
+            .iter()
+            .filter(|engine| self.kind_filter.is_none_or(|kind| engine.kind() == kind))
+            .collect();
+        if candidates.is_empty() {
+            return Err(fuzzer_err(match self.configured_oracle() {
+                ConfiguredOracle::FlightSql => {
+                    "FlightSql oracle requires flight_sql_endpoint to be configured"
+                }
+                _ => {
+                    "EngineDiff oracle requires flight_sql_endpoint or reference_engines to be configured"
+                }
+            }));
+        }
+        let mut rng = rng_from_seed(self.seed);
+        let engine = Arc::clone(candidates[rng.random_range(0..candidates.len())]);
+        self.engine_kind = Some(engine.kind());
+
+        let mut stmt_builder = SelectStatementBuilder::new(
+            self.seed,
+            Arc::clone(&self.ctx),
+            InclusionConfig::Maybe(0.9),
+            InclusionConfig::Maybe(0.5),
+        )
+        .with_allow_derived_tables(false);
+        let stmt = stmt_builder.generate_stmt()?;
+        let sql = stmt.to_sql_string()?;
+
+        let session_context = self.ctx.runtime_context.get_session_context();
+        let engine_description = format!("{:?} engine {}", engine.kind(), engine.name());
+        Ok(vec![
+            QueryContext::with_description(
+                sql.clone(),
+                Arc::clone(&session_context),
+                "Embedded".to_string(),
+            ),
+            QueryContext::with_description(sql, session_context, engine_description)
+                .with_engine(engine),
+        ])
+    }
+
+    async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
+        if results.len() != 2 {
+            return Err(fuzzer_err(&format!(
+                "{} expects 2 query results, got {}",
+                self.name(),
+                results.len()
+            )));
+        }
+
+        // Both succeeding or both failing is checked by the runner beforehand
+        // for DataFusion engines, other dialects may reject either query
+        if results.iter().any(|result| result.result.is_err()) {
+            return Ok(());
+        }
+
+        let configured_oracle = self.configured_oracle();
+        let options = self.ctx.runner_config.compare_options(configured_oracle);
+        oracle_common::validate_value_equivalence(
+            results,
+            0,
+            1,
+            &format!("{:?}", configured_oracle),
+            &options,
+        )
+    }
+
+    fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
+        let engine_label = self
+            .engine_kind
+            .map(|kind| format!("{:?}", kind))
+            .unwrap_or_else(|| "other engine".to_string());
+
+        let mut report = String::new();
+        report.push_str("Engine Differential Oracle Test Failed\n");
+        report.push_str("======================================\n\n");
+
+        oracle_common::append_labeled_query_results(
+            &mut report,
+            results,
+            &["embedded", engine_label.as_str()],
+        );
+
+        report.push_str("Expected: the other engine returns the same rows as the embedded one\n");
+
+        Ok(report)
+    }
+
+    fn requires_consistent_outcomes(&self) -> bool {
+        self.engine_kind
+            .is_none_or(EngineKind::speaks_datafusion_sql)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{EmbeddedDataFusion, EngineUnderTest};
+    use crate::fuzz_context::GlobalContext;
+    use crate::oracle::test_helpers;
+    use datafusion::prelude::SessionContext;
+
+    #[tokio::test]
+    async fn compares_embedded_and_other_engine_rows() {
+        let oracle = EngineDiffOracle::flight_sql(1, Arc::new(GlobalContext::default()));
+
+        let same_rows = vec![
+            test_helpers::make_success_result("embedded", "c", vec![1, 2]),
+            test_helpers::make_success_result("remote", "c", vec![2, 1]),
+        ];
+        assert!(oracle.validate_consistency(&same_rows).await.is_ok());
+
+        let lost_rows = vec![
+            test_helpers::make_success_result("embedded", "c", vec![1, 2]),
+            test_helpers::make_success_result("remote", "c", vec![1]),
+        ];
+        assert!(oracle.validate_consistency(&lost_rows).await.is_err());
+    }
+
+    #[test]
+    fn flight_sql_requires_an_endpoint() {
+        let mut oracle = EngineDiffOracle::flight_sql(1, Arc::new(GlobalContext::default()));
+        let err = oracle.generate_query_group().unwrap_err();
+        assert!(err.to_string().contains("flight_sql_endpoint"));
+    }
+
+    #[test]
+    fn flight_sql_ignores_other_engines() {
+        let engine: Arc<dyn EngineUnderTest> =
+            Arc::new(EmbeddedDataFusion::new(Arc::new(SessionContext::new())));
+        let ctx = Arc::new(GlobalContext::default().with_engines(vec![engine]));
+
+        let mut oracle = EngineDiffOracle::flight_sql(1, Arc::clone(&ctx));
+        assert!(oracle.generate_query_group().is_err());
+        assert_eq!(oracle.name(), "FlightSqlOracle");
+        assert_eq!(EngineDiffOracle::new(1, ctx).name(), "EngineDiffOracle");
+    }
+
+    #[test]
+    fn other_dialects_may_fail_alone() {
+        let mut oracle = EngineDiffOracle::new(1, Arc::new(GlobalContext::default()));
+        oracle.engine_kind = Some(EngineKind::FlightSql);
+        assert!(oracle.requires_consistent_outcomes());
+
+        oracle.engine_kind = Some(EngineKind::Sqlite);
+        assert!(!oracle.requires_consistent_outcomes());
+    }
+}
@@ -0,0 +1,338 @@
+use std::sync::Arc;
+
+use datafusion::arrow::array::{
+    ArrayRef, Float64Array, Int64Array, RecordBatch, StringArray, new_null_array,
+};
+use datafusion::arrow::compute::cast;
+use datafusion::arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use datafusion::datasource::MemTable;
+use datafusion::error::DataFusionError;
+use datafusion::prelude::SessionContext;
+use rand::Rng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+
+use crate::common::rng::rng_from_seed;
+use crate::common::{Result, fuzzer_err};
+use crate::datasource_generator::file_dataset::FileDataset;
+use crate::oracle::{ConfiguredOracle, Oracle, QueryContext, QueryExecutionResult, oracle_common};
+
+/// Maximum number of data columns besides `id`
+const MAX_DATA_COLUMNS: usize = 3;
+
+/// Maximum number of files of the table
+const MAX_FILES: usize = 3;
+
+/// Maximum number of rows per file
+const MAX_FILE_ROWS: usize = 5;
+
+/// A random nullable column of `row_count` values. Integers fit in `Int32`,
+/// so files can store them narrowed.
+fn random_column(rng: &mut StdRng, data_type: &DataType, row_count: usize) -> ArrayRef {
+    let is_valid = |rng: &mut StdRng| rng.random_bool(0.8);
+    match data_type {
+        DataType::Int64 => {
+            let values: Int64Array = (0..row_count)
+                .map(|_| is_valid(rng).then(|| rng.random_range(-1000..=1000)))
+                .collect();
+            Arc::new(values)
+        }
+        DataType::Float64 => {
+            let values: Float64Array = (0..row_count)
+                .map(|_| is_valid(rng).then(|| rng.random_range(-100.0..=100.0)))
+                .collect();
+            Arc::new(values)
+        }
+        _ => {
+            let values: StringArray = (0..row_count)
+                .map(|_| is_valid(rng).then(|| format!("s{}", rng.random_range(0..10))))
+                .collect();
+            Arc::new(values)
+        }
+    }
+}
+
+/// The rows of one file, as the table reads them and as the file stores them
+struct EvolvedFile {
+    /// Rows with the table's schema, with NULL for columns the file lacks
+    expected: RecordBatch,
+    /// Rows with the file's schema
+    stored: RecordBatch,
+    /// How the file's schema differs from the table's
+    changes: Vec<String>,
+}
+
+/// Build a file of `row_count` rows of the table with `schema`, whose schema
+/// randomly lacks a data column, stores integer columns as `Int32`, and has
+/// its columns in another order
+fn evolved_file(rng: &mut StdRng, schema: &SchemaRef, row_count: usize) -> Result<EvolvedFile> {
+    let ids: Int64Array = (0..row_count as i64).map(Some).collect();
+    let mut columns: Vec<ArrayRef> = vec![Arc::new(ids)];
+    for field in schema.fields().iter().skip(1) {
+        columns.push(random_column(rng, field.data_type(), row_count));
+    }
+
+    let mut changes = Vec::new();
+    let missing = rng
+        .random_bool(0.4)
+        .then(|| rng.random_range(1..schema.fields().len()));
+    let mut stored_fields = Vec::new();
+    let mut stored_columns = Vec::new();
+    for (idx, (field, column)) in schema.fields().iter().zip(columns.iter_mut()).enumerate() {
+        if missing == Some(idx) {
+            *column = new_null_array(field.data_type(), row_count);
+            changes.push(format!("missing {}", field.name()));
+            continue;
+        }
+        if field.data_type() == &DataType::Int64 && idx > 0 && rng.random_bool(0.5) {
+            let narrowed = cast(column, &DataType::Int32).map_err(DataFusionError::from)?;
+            stored_fields.push(Field::new(field.name(), DataType::Int32, true));
+            stored_columns.push(narrowed);
+            changes.push(format!("{} stored as Int32", field.name()));
+            continue;
+        }
+        stored_fields.push(field.as_ref().clone());
+        stored_columns.push(Arc::clone(column));
+    }
+
+    if rng.random_bool(0.5) {
+        let mut order: Vec<usize> = (0..stored_fields.len()).collect();
+        order.shuffle(rng);
+        stored_fields = order.iter().map(|&i| stored_fields[i].clone()).collect();
+        stored_columns = order
+            .iter()
+            .map(|&i| Arc::clone(&stored_columns[i]))
+            .collect();
+        changes.push("columns reordered".to_string());
+    }
+
+    let expected =
+        RecordBatch::try_new(Arc::clone(schema), columns).map_err(DataFusionError::from)?;
+    let stored = RecordBatch::try_new(Arc::new(Schema::new(stored_fields)), stored_columns)
+        .map_err(DataFusionError::from)?;
+    Ok(EvolvedFile {
+        expected,
+        stored,
+        changes,
+    })
+}
+
+/// Schema merging oracle for listing tables over files with different schemas.
+///
+/// Writes a Parquet table as several files whose schemas differ slightly from
+/// the table's: a missing column, an integer column stored as `Int32` instead
+/// of `Int64`, or another column order. DataFusion adapts every file to the
+/// table schema, filling missing columns with NULL. Each query runs over the
+/// listing table and over a `MemTable` holding the adapted rows, and both
+/// must return the same rows. Not to be confused with `schema_evolution`,
+/// which changes the set of tables between rounds.
+///
+/// ### Example:
+///
+/// Files `(id, c0 Int32)` and `(c1, id)` of a table `(id, c0 Int64, c1)`:
+///
+/// SELECT c0, id FROM t WHERE c1 IS NOT NULL;
+///
+/// must return the rows of the second file with NULL for `c0`.
+pub struct FileSchemaEvolutionOracle {
+    seed: u64,
+    ctx: Arc<crate::fuzz_context::GlobalContext>,
+    /// Files of the table, with how their schemas differ from the table's
+    files: Option<(FileDataset, Vec<Vec<String>>)>,
+}
+
+impl FileSchemaEvolutionOracle {
+    pub fn new(seed: u64, ctx: Arc<crate::fuzz_context::GlobalContext>) -> Self {
+        Self {
+            seed,
+            ctx,
+            files: None,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Oracle for FileSchemaEvolutionOracle {
+    fn name(&self) -> &'static str {
+        "FileSchemaEvolutionOracle"
+    }
+
+    fn generate_query_group(&mut self) -> Result<Vec<QueryContext>> {
+        let mut rng = rng_from_seed(self.seed);
+        let data_types = [DataType::Int64, DataType::Float64, DataType::Utf8];
+        let mut fields = vec![Field::new("id", DataType::Int64, false)];
+        for i in 0..rng.random_range(2..=MAX_DATA_COLUMNS) {
+            let data_type = data_types[rng.random_range(0..data_types.len())].clone();
+            fields.push(Field::new(format!("c{}", i), data_type, true));
+        }
+        let schema = Arc::new(Schema::new(fields));
+
+        let files = (0..rng.random_range(2..=MAX_FILES))
+            .map(|_| {
+                let row_count = rng.random_range(0..=MAX_FILE_ROWS);
+                evolved_file(&mut rng, &schema, row_count)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let stored: Vec<RecordBatch> = files.iter().map(|file| file.stored.clone()).collect();
+        let dataset = FileDataset::write_parquet_files(
+            &mut rng,
+            self.ctx.dataset_dir().as_deref(),
+            "t",
+            Arc::clone(&schema),
+            &stored,
+        )?;
+
+        // Both tables only live in these sessions, which keep the fuzzer's
+        // options. Filter pushdown adapts file schemas in the Parquet reader.
+        let base_config = self
+            .ctx
+            .runtime_context
+            .get_session_context()
+            .copied_config();
+        let mut files_config = base_config.clone();
+        files_config
+            .options_mut()
+            .execution
+            .parquet
+            .pushdown_filters = rng.random_bool(0.5);
+        let files_session = dataset.session(files_config, "t", true)?;
+        let memory_session = Arc::new(SessionContext::new_with_config(base_config));
+        let expected: Vec<RecordBatch> = files.iter().map(|file| file.expected.clone()).collect();
+        memory_session.register_table(
+            "t",
+            Arc::new(MemTable::try_new(Arc::clone(&schema), vec![expected])?),
+        )?;
+
+        let column =
+            |rng: &mut StdRng| format!("c{}", rng.random_range(0..schema.fields().len() - 1));
+        let (projected, filtered, aggregated) =
+            (column(&mut rng), column(&mut rng), column(&mut rng));
+        let queries = [
+            "SELECT * FROM t".to_string(),
+            format!(
+                "SELECT {}, id\nFROM t\nWHERE {} IS NOT NULL",
+                projected, filtered
+            ),
+            format!(
+                "SELECT COUNT(*), COUNT({c}), MIN({c}), MAX({c})\nFROM t",
+                c = aggregated
+            ),
+        ];
+
+        let mut query_group = Vec::with_capacity(queries.len() * 2);
+        for query in &queries {
+            for (session_context, description) in [
+                (&files_session, "over the files"),
+                (&memory_session, "over the adapted rows in memory"),
+            ] {
+                query_group.push(QueryContext::with_description(
+                    query.clone(),
+                    Arc::clone(session_context),
+                    description.to_string(),
+                ));
+            }
+        }
+
+        let changes = files.into_iter().map(|file| file.changes).collect();
+        self.files = Some((dataset, changes));
+        Ok(query_group)
+    }
+
+    async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
+        if results.len() != 6 {
+            return Err(fuzzer_err(&format!(
+                "FileSchemaEvolution oracle expects 6 query results, got {}",
+                results.len()
+            )));
+        }
+
+        let options = self
+            .ctx
+            .runner_config
+            .compare_options(ConfiguredOracle::FileSchemaEvolution);
+        for over_files in [0, 2, 4] {
+            let in_memory = over_files + 1;
+            if results[over_files].result.is_err() || results[in_memory].result.is_err() {
+                continue;
+            }
+            oracle_common::validate_value_equivalence(
+                results,
+                over_files,
+                in_memory,
+                "FileSchemaEvolution",
+                &options,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
+        let mut report = String::new();
+        report.push_str("FileSchemaEvolution Oracle Test Failed\n");
+        report.push_str("======================================\n\n");
+
+        oracle_common::append_labeled_query_results(
+            &mut report,
+            results,
+            &[
+                "select *, over the files",
+                "select *, in memory",
+                "filtered projection, over the files",
+                "filtered projection, in memory",
+                "aggregates, over the files",
+                "aggregates, in memory",
+            ],
+        );
+
+        if let Some((dataset, changes)) = &self.files {
+            report.push_str(&format!(
+                "Table files: {} ({:?} compression, {:?} writer statistics)\n",
+                dataset.dir.display(),
+                dataset.compression,
+                dataset.statistics
+            ));
+            report.push_str(&format!("Table schema: {}\n", dataset.schema));
+            for (i, changes) in changes.iter().enumerate() {
+                report.push_str(&format!("part-{}.parquet: {:?}\n", i, changes));
+            }
+        }
+        report.push_str("Expected: the same rows over the files and in memory\n");
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn adapts_files_with_different_schemas() {
+        let ctx = Arc::new(crate::fuzz_context::GlobalContext::default());
+        let mut changed_files = 0;
+        for seed in 0..8 {
+            let mut oracle = FileSchemaEvolutionOracle::new(seed, Arc::clone(&ctx));
+            let mut results = Vec::new();
+            for query_context in oracle.generate_query_group().unwrap() {
+                let result = query_context
+                    .context
+                    .sql(&query_context.query)
+                    .await
+                    .unwrap()
+                    .collect()
+                    .await
+                    .map_err(Into::into);
+                results.push(QueryExecutionResult {
+                    query_context: Arc::new(query_context),
+                    result,
+                    truncated: None,
+                });
+            }
+            oracle.validate_consistency(&results).await.unwrap();
+
+            let (dataset, changes) = oracle.files.as_ref().unwrap();
+            changed_files += changes.iter().filter(|changes| !changes.is_empty()).count();
+            std::fs::remove_dir_all(&dataset.dir).unwrap();
+        }
+        assert!(changed_files > 0);
+    }
+}
@@ -0,0 +1,229 @@
+use std::sync::Arc;
+
+use rand::Rng;
+
+use crate::common::rng::rng_from_seed;
+use crate::common::util::{quote_identifier, to_sql_string};
+use crate::common::{LogicalTable, Result, fuzzer_err};
+use crate::oracle::ground_truth::{column_expr, generate_predicate, parquet_backed_tables};
+use crate::oracle::{ConfiguredOracle, Oracle, QueryContext, QueryExecutionResult, oracle_common};
+
+/// Parquet filter pushdown oracle.
+///
+/// Filters a Parquet-backed table (see `dataset_format`) twice: once with
+/// `pushdown_filters` and `reorder_filters`, where the Parquet reader
+/// evaluates the predicates while decoding and only materializes the
+/// remaining rows of the other columns, and once with the filters evaluated
+/// after the scan. One query projects only some columns, so predicates on
+/// the other columns are evaluated on columns that are never output.
+///
+/// ### Example:
+///
+/// SELECT c1 FROM t1 WHERE (c2 > 5 OR c3 IS NULL) AND c1 <> 'a';
+///
+/// must return the same rows with and without filter pushdown.
+pub struct FilterPushdownOracle {
+    seed: u64,
+    ctx: Arc<crate::fuzz_context::GlobalContext>,
+    table: Option<Arc<LogicalTable>>,
+}
+
+impl FilterPushdownOracle {
+    pub fn new(seed: u64, ctx: Arc<crate::fuzz_context::GlobalContext>) -> Self {
+        Self {
+            seed,
+            ctx,
+            table: None,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Oracle for FilterPushdownOracle {
+    fn name(&self) -> &'static str {
+        "FilterPushdownOracle"
+    }
+
+    fn generate_query_group(&mut self) -> Result<Vec<QueryContext>> {
+        let tables = parquet_backed_tables(&self.ctx);
+        if tables.is_empty() {
+            return Err(fuzzer_err(
+                "FilterPushdown oracle requires a Parquet-backed table, see dataset_format",
+            ));
+        }
+
+        let mut rng = rng_from_seed(self.seed);
+        let table = Arc::clone(&tables[rng.random_range(0..tables.len())]);
+        let files = table
+            .files
+            .as_ref()
+            .ok_or_else(|| fuzzer_err("FilterPushdown oracle picked an in-memory table"))?;
+
+        let projected: Vec<String> = table
+            .columns
+            .iter()
+            .filter(|_| rng.random_bool(0.5))
+            .map(|column| to_sql_string(&column_expr(&table, column)))
+            .collect::<Result<_>>()?;
+        let projection = if projected.is_empty() {
+            to_sql_string(&column_expr(&table, &table.columns[0]))?
+        } else {
+            projected.join(", ")
+        };
+
+        let table_name = quote_identifier(&table.name);
+        let queries = [
+            format!(
+                "SELECT *\nFROM {}\nWHERE {}",
+                table_name,
+                to_sql_string(&generate_predicate(&mut rng, &table))?
+            ),
+            format!(
+                "SELECT {}\nFROM {}\nWHERE ({}) AND ({})",
+                projection,
+                table_name,
+                to_sql_string(&generate_predicate(&mut rng, &table))?,
+                to_sql_string(&generate_predicate(&mut rng, &table))?
+            ),
+        ];
+
+        let base_config = self
+            .ctx
+            .runtime_context
+            .get_session_context()
+            .copied_config();
+        let mut sessions = Vec::with_capacity(2);
+        for pushdown in [true, false] {
+            let mut config = base_config.clone();
+            let parquet = &mut config.options_mut().execution.parquet;
+            parquet.pushdown_filters = pushdown;
+            parquet.reorder_filters = pushdown;
+            sessions.push(files.session(config, &table.name, true)?);
+        }
+
+        let mut query_group = Vec::with_capacity(queries.len() * sessions.len());
+        for query in &queries {
+            for (session_context, description) in sessions
+                .iter()
+                .zip(["with filter pushdown", "without filter pushdown"])
+            {
+                query_group.push(QueryContext::with_description(
+                    query.clone(),
+                    Arc::clone(session_context),
+                    format!("{}, {:?}", description, files.layout),
+                ));
+            }
+        }
+
+        self.table = Some(table);
+        Ok(query_group)
+    }
+
+    async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
+        if results.len() != 4 {
+            return Err(fuzzer_err(&format!(
+                "FilterPushdown oracle expects 4 query results, got {}",
+                results.len()
+            )));
+        }
+
+        let options = self
+            .ctx
+            .runner_config
+            .compare_options(ConfiguredOracle::FilterPushdown);
+        for with_pushdown in [0, 2] {
+            let without_pushdown = with_pushdown + 1;
+            if results[with_pushdown].result.is_err() || results[without_pushdown].result.is_err() {
+                continue;
+            }
+            oracle_common::validate_value_equivalence(
+                results,
+                with_pushdown,
+                without_pushdown,
+                "FilterPushdown",
+                &options,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
+        let mut report = String::new();
+        report.push_str("FilterPushdown Oracle Test Failed\n");
+        report.push_str("=================================\n\n");
+
+        oracle_common::append_labeled_query_results(
+            &mut report,
+            results,
+            &[
+                "with filter pushdown",
+                "without filter pushdown",
+                "projection, with filter pushdown",
+                "projection, without filter pushdown",
+            ],
+        );
+
+        if let Some(files) = self.table.as_ref().and_then(|table| table.files.as_ref()) {
+            report.push_str(&format!(
+                "Table files: {} ({:?} writer statistics, {:?})\n",
+                files.dir.display(),
+                files.statistics,
+                files.layout
+            ));
+        }
+        report.push_str("Expected: the same rows with and without filter pushdown\n");
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::init_available_data_types;
+    use crate::datasource_generator::dataset_generator::DatasetGenerator;
+    use crate::datasource_generator::file_dataset::DatasetFormat;
+    use crate::fuzz_context::RunnerConfig;
+    use crate::oracle::test_helpers::context_with_config;
+
+    #[tokio::test]
+    async fn compares_parquet_scans_with_and_without_filter_pushdown() {
+        init_available_data_types();
+        let ctx = context_with_config(RunnerConfig {
+            dataset_format: DatasetFormat::Parquet,
+            ..Default::default()
+        });
+
+        for seed in 0..4 {
+            DatasetGenerator::new(seed, Arc::clone(&ctx))
+                .generate_dataset()
+                .await
+                .unwrap();
+        }
+        if parquet_backed_tables(&ctx).is_empty() {
+            // Every table had an interval column
+            return;
+        }
+
+        for seed in 0..4 {
+            let mut oracle = FilterPushdownOracle::new(seed, Arc::clone(&ctx));
+            let query_group = oracle.generate_query_group().unwrap();
+            assert_eq!(query_group.len(), 4);
+            assert_eq!(query_group[2].query, query_group[3].query);
+            assert!(query_group[2].query.contains(") AND ("));
+
+            let mut results = Vec::new();
+            for query_context in query_group {
+                let result = match query_context.context.sql(&query_context.query).await {
+                    Ok(df) => df.collect().await.map_err(Into::into),
+                    Err(e) => Err(e.into()),
+                };
+                results.push(QueryExecutionResult {
+                    query_context: Arc::new(query_context),
+                    result,
+                    truncated: None,
+                });
+            }
+            oracle.validate_consistency(&results).await.unwrap();
+        }
+    }
+}
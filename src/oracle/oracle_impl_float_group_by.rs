@@ -0,0 +1,273 @@
+use std::sync::Arc;
+
+use datafusion::arrow::array::{ArrayRef, Float64Array, Int64Array, RecordBatch};
+use datafusion::arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use datafusion::datasource::MemTable;
+use datafusion::error::DataFusionError;
+use datafusion::prelude::{SessionConfig, SessionContext};
+use datafusion::scalar::ScalarValue;
+use rand::Rng;
+
+use crate::common::rng::rng_from_seed;
+use crate::common::{Result, fuzzer_err};
+use crate::oracle::{Oracle, QueryContext, QueryExecutionResult, oracle_common};
+
+/// Group keys hash aggregation is known to handle inconsistently: NaN with
+/// and without the sign bit, both zeros, infinities, and values that are
+/// only equal after rounding. `None` is NULL.
+const FLOAT_KEYS: [Option<f64>; 11] = [
+    Some(f64::NAN),
+    Some(-f64::NAN),
+    Some(-0.0),
+    Some(0.0),
+    None,
+    Some(1.0),
+    Some(-1.0),
+    Some(f64::INFINITY),
+    Some(f64::NEG_INFINITY),
+    Some(0.1 + 0.2),
+    Some(0.3),
+];
+
+/// `(target_partitions, batch_size)` of the sessions the query runs in. The
+/// first executes in a single partition, the others split the table into one
+/// partition per target partition and aggregate in parallel.
+const EXECUTION_CONFIGS: [(usize, usize); 3] = [(1, 8192), (4, 2), (3, 1)];
+
+/// Float GROUP BY oracle.
+///
+/// Groups a table by a `DOUBLE` column full of NaN, -0.0/+0.0 and NULL keys,
+/// once in a single partition and once in several partitions, where rows of
+/// one group are partially aggregated in different partitions and merged
+/// after repartitioning by the hash of the key. Both must find the same
+/// groups: if NaN or the two zeros hash differently than they compare, a
+/// group can be split in two.
+///
+/// ### Example:
+///
+/// SELECT k, COUNT(*), COUNT(k), SUM(v) FROM t GROUP BY k;
+///
+/// The key printed for a group is its first row's value, which depends on
+/// the order rows arrive in, so NaNs and zeros are compared regardless of
+/// their sign.
+pub struct FloatGroupByOracle {
+    seed: u64,
+    ctx: Arc<crate::fuzz_context::GlobalContext>,
+}
+
+impl FloatGroupByOracle {
+    pub fn new(seed: u64, ctx: Arc<crate::fuzz_context::GlobalContext>) -> Self {
+        Self { seed, ctx }
+    }
+}
+
+/// A session like `base_config` with `target_partitions` partitions, and
+/// table `t` holding `batch` split into `target_partitions` partitions of
+/// `batch_size` rows
+fn partitioned_session(
+    base_config: SessionConfig,
+    batch: &RecordBatch,
+    target_partitions: usize,
+    batch_size: usize,
+) -> Result<Arc<SessionContext>> {
+    let config = base_config
+        .with_target_partitions(target_partitions)
+        .with_batch_size(batch_size);
+    let session_context = SessionContext::new_with_config(config);
+
+    let mut partitions = vec![Vec::new(); target_partitions];
+    for (idx, offset) in (0..batch.num_rows()).step_by(batch_size).enumerate() {
+        let length = batch_size.min(batch.num_rows() - offset);
+        partitions[idx % target_partitions].push(batch.slice(offset, length));
+    }
+    let table = MemTable::try_new(batch.schema(), partitions)?;
+    session_context.register_table("t", Arc::new(table))?;
+    Ok(Arc::new(session_context))
+}
+
+#[async_trait::async_trait]
+impl Oracle for FloatGroupByOracle {
+    fn name(&self) -> &'static str {
+        "FloatGroupByOracle"
+    }
+
+    fn generate_query_group(&mut self) -> Result<Vec<QueryContext>> {
+        let mut rng = rng_from_seed(self.seed);
+        let row_count = rng.random_range(8..=40);
+        let keys: Float64Array = (0..row_count)
+            .map(|_| FLOAT_KEYS[rng.random_range(0..FLOAT_KEYS.len())])
+            .collect();
+        let groups: Int64Array = (0..row_count)
+            .map(|_| Some(rng.random_range(0..2)))
+            .collect();
+        let values: Int64Array = (0..row_count)
+            .map(|_| Some(rng.random_range(-10..=10)))
+            .collect();
+
+        let schema: SchemaRef = Arc::new(Schema::new(vec![
+            Field::new("k", DataType::Float64, true),
+            Field::new("g", DataType::Int64, false),
+            Field::new("v", DataType::Int64, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(keys) as ArrayRef,
+                Arc::new(groups),
+                Arc::new(values),
+            ],
+        )
+        .map_err(DataFusionError::from)?;
+
+        let group_by = if rng.random_bool(0.5) { "k" } else { "k, g" };
+        let query =
+            format!("SELECT {group_by}, COUNT(*), COUNT(k), SUM(v)\nFROM t\nGROUP BY {group_by}");
+
+        // The table only lives in these sessions, which keep the fuzzer's
+        // options besides partitioning
+        let base_config = self
+            .ctx
+            .runtime_context
+            .get_session_context()
+            .copied_config();
+        EXECUTION_CONFIGS
+            .iter()
+            .map(|&(target_partitions, batch_size)| {
+                Ok(QueryContext::with_description(
+                    query.clone(),
+                    partitioned_session(
+                        base_config.clone(),
+                        &batch,
+                        target_partitions,
+                        batch_size,
+                    )?,
+                    format!(
+                        "target_partitions = {}, batch_size = {}",
+                        target_partitions, batch_size
+                    ),
+                ))
+            })
+            .collect()
+    }
+
+    async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
+        let Some((single, rest)) = results.split_first() else {
+            return Err(fuzzer_err("No query results to validate"));
+        };
+        let Ok(single_batches) = &single.result else {
+            return Ok(());
+        };
+        let expected = normalized_groups(single_batches)?;
+
+        for result in rest {
+            let Ok(batches) = &result.result else {
+                continue;
+            };
+            let actual = normalized_groups(batches)?;
+            if actual != expected {
+                return Err(fuzzer_err(&format!(
+                    "Float GROUP BY inconsistent: single partition found groups {:?}, {} found {:?}",
+                    expected,
+                    result
+                        .query_context
+                        .context_description
+                        .as_deref()
+                        .unwrap_or("multiple partitions"),
+                    actual
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
+        let mut report = String::new();
+        report.push_str("Float GROUP BY Oracle Test Failed\n");
+        report.push_str("=================================\n\n");
+
+        oracle_common::append_labeled_query_results(
+            &mut report,
+            results,
+            &["single partition", "4 partitions", "3 partitions"],
+        );
+
+        report.push_str("Expected: the same groups in every execution\n");
+        Ok(report)
+    }
+}
+
+/// Rows of a GROUP BY result rendered as strings and sorted, with the sign of
+/// NaN and zero keys dropped
+fn normalized_groups(batches: &[RecordBatch]) -> Result<Vec<Vec<String>>> {
+    let mut rows = Vec::new();
+    for batch in batches {
+        for row in 0..batch.num_rows() {
+            let values = batch
+                .columns()
+                .iter()
+                .map(|column| -> Result<String> {
+                    Ok(match ScalarValue::try_from_array(column, row)? {
+                        ScalarValue::Float64(Some(v)) if v.is_nan() => "NaN".to_string(),
+                        ScalarValue::Float64(Some(v)) if v == 0.0 => "0".to_string(),
+                        value => value.to_string(),
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            rows.push(values);
+        }
+    }
+    rows.sort();
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracle::test_helpers;
+
+    #[test]
+    fn normalizes_nan_and_zero_keys() {
+        let schema = Arc::new(Schema::new(vec![Field::new("k", DataType::Float64, true)]));
+        let keys = Float64Array::from(vec![Some(-0.0), Some(-f64::NAN), None, Some(1.5)]);
+        let batch = RecordBatch::try_new(schema, vec![Arc::new(keys) as ArrayRef]).unwrap();
+
+        let rows = normalized_groups(&[batch]).unwrap();
+        let keys: Vec<&str> = rows.iter().map(|row| row[0].as_str()).collect();
+        assert_eq!(keys, vec!["0", "1.5", "NULL", "NaN"]);
+    }
+
+    #[test]
+    fn splits_the_table_into_partitions() {
+        let ctx = Arc::new(crate::fuzz_context::GlobalContext::default());
+        for seed in 0..8 {
+            let mut oracle = FloatGroupByOracle::new(seed, Arc::clone(&ctx));
+            let query_group = oracle.generate_query_group().unwrap();
+            assert_eq!(query_group.len(), EXECUTION_CONFIGS.len());
+            for (query_context, (target_partitions, _)) in query_group.iter().zip(EXECUTION_CONFIGS)
+            {
+                assert!(query_context.query.starts_with("SELECT k"));
+                let options = query_context.context.copied_config();
+                assert_eq!(options.target_partitions(), target_partitions);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn groups_must_match_the_single_partition() {
+        let oracle =
+            FloatGroupByOracle::new(1, Arc::new(crate::fuzz_context::GlobalContext::default()));
+
+        let same = vec![
+            test_helpers::make_success_result("single", "count", vec![3, 1]),
+            test_helpers::make_success_result("multi", "count", vec![1, 3]),
+        ];
+        assert!(oracle.validate_consistency(&same).await.is_ok());
+
+        let split = vec![
+            test_helpers::make_success_result("single", "count", vec![3, 1]),
+            test_helpers::make_success_result("multi", "count", vec![2, 1, 1]),
+        ];
+        let err = oracle.validate_consistency(&split).await.unwrap_err();
+        assert!(err.to_string().contains("Float GROUP BY inconsistent"));
+    }
+}
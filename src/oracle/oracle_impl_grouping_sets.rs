@@ -0,0 +1,369 @@
+use std::sync::Arc;
+
+use datafusion::arrow::array::{BooleanArray, Int64Array, RecordBatch};
+use datafusion::arrow::compute::kernels::cmp::eq;
+use datafusion::arrow::compute::{and, cast, filter_record_batch};
+use datafusion::arrow::datatypes::DataType;
+use datafusion::error::DataFusionError;
+use rand::seq::IndexedRandom;
+use rand::{Rng, RngCore};
+
+use crate::common::rng::rng_from_seed;
+use crate::common::util::to_sql_string;
+use crate::common::{Result, fuzzer_err};
+use crate::oracle::ground_truth::{column_expr, is_orderable};
+use crate::oracle::result_compare::compare_batches;
+use crate::oracle::{ConfiguredOracle, Oracle, QueryContext, QueryExecutionResult, oracle_common};
+use crate::query_generator::expr_gen::ExprGenerator;
+use crate::query_generator::grouping_sets::GroupingSets;
+
+/// Probability that the grouped rows are filtered by a generated predicate
+const WHERE_PROBABILITY: f64 = 0.7;
+
+/// Largest number of grouping keys, `CUBE` expands to 2^n grouping sets
+const MAX_KEYS: usize = 3;
+
+/// GROUPING SETS oracle.
+///
+/// Groups the rows of a table, optionally filtered by a generated predicate,
+/// by a random `ROLLUP`, `CUBE` or `GROUPING SETS` clause, and computes every
+/// grouping set again with a standalone `GROUP BY`. The grouping query
+/// selects the `GROUPING` flag of every key, and the standalone queries the
+/// flags of their set as constants, next to NULL for the keys left out.
+///
+/// ### Example:
+///
+/// SELECT t1.c0, t1.c1, GROUPING(t1.c0), GROUPING(t1.c1), COUNT(*), COUNT(t1.c2)
+/// FROM t1 WHERE p GROUP BY ROLLUP(t1.c0, t1.c1);
+/// SELECT t1.c0, t1.c1, 0, 0, COUNT(*), COUNT(t1.c2) FROM t1 WHERE p GROUP BY t1.c0, t1.c1;
+/// SELECT t1.c0, NULL, 0, 1, COUNT(*), COUNT(t1.c2) FROM t1 WHERE p GROUP BY t1.c0;
+/// SELECT NULL, NULL, 1, 1, COUNT(*), COUNT(t1.c2) FROM t1 WHERE p;
+///
+/// The rows of the grouping query with the flags of a grouping set must be
+/// the rows of its standalone query.
+pub struct GroupingSetsOracle {
+    seed: u64,
+    ctx: Arc<crate::fuzz_context::GlobalContext>,
+    /// Grouping sets of the generated grouping query
+    grouping_sets: Option<GroupingSets>,
+}
+
+impl GroupingSetsOracle {
+    pub fn new(seed: u64, ctx: Arc<crate::fuzz_context::GlobalContext>) -> Self {
+        Self {
+            seed,
+            ctx,
+            grouping_sets: None,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Oracle for GroupingSetsOracle {
+    fn name(&self) -> &'static str {
+        "GroupingSetsOracle"
+    }
+
+    fn generate_query_group(&mut self) -> Result<Vec<QueryContext>> {
+        let tables: Vec<_> = self
+            .ctx
+            .runtime_context
+            .tables()
+            .values()
+            .filter(|table| !table.columns.is_empty())
+            .cloned()
+            .collect();
+        if tables.is_empty() {
+            return Err(fuzzer_err(
+                "GROUPING SETS oracle requires a table with columns",
+            ));
+        }
+
+        let mut rng = rng_from_seed(self.seed);
+        let table = &tables[rng.random_range(0..tables.len())];
+        let key_count = rng.random_range(1..=table.columns.len().min(MAX_KEYS));
+        let keys = table
+            .columns
+            .choose_multiple(&mut rng, key_count)
+            .map(|column| to_sql_string(&column_expr(table, column)))
+            .collect::<Result<Vec<_>>>()?;
+        let grouping_sets = GroupingSets::random(keys, &mut rng);
+
+        let column = &table.columns[rng.random_range(0..table.columns.len())];
+        let column_sql = to_sql_string(&column_expr(table, column))?;
+        let mut aggregates = format!("COUNT(*), COUNT({})", column_sql);
+        if is_orderable(&column.data_type) {
+            aggregates.push_str(&format!(", MIN({}), MAX({})", column_sql, column_sql));
+        }
+
+        let mut source = format!("FROM {}", table.sql_name());
+        if rng.random_bool(WHERE_PROBABILITY) {
+            let mut expr_gen = ExprGenerator::new(rng.next_u64(), Arc::clone(&self.ctx))
+                .with_src_columns(Arc::new(ExprGenerator::tables_to_columns(
+                    std::slice::from_ref(table),
+                    &self.ctx,
+                )));
+            let predicate = expr_gen.generate_random_expr(DataType::Boolean, 0);
+            source.push_str(&format!("\nWHERE {}", to_sql_string(&predicate)?));
+        }
+
+        let session_context = self.ctx.runtime_context.get_session_context();
+        let mut query_group = Vec::with_capacity(grouping_sets.sets.len() + 1);
+        let flags: Vec<String> = grouping_sets
+            .keys
+            .iter()
+            .map(|key| format!("GROUPING({})", key))
+            .collect();
+        query_group.push(QueryContext::with_description(
+            format!(
+                "SELECT {}, {}, {}\n{}\nGROUP BY {}",
+                grouping_sets.keys.join(", "),
+                flags.join(", "),
+                aggregates,
+                source,
+                grouping_sets.clause
+            ),
+            Arc::clone(&session_context),
+            grouping_sets.clause.clone(),
+        ));
+        for set in &grouping_sets.sets {
+            let set_keys = keys_of_set(&grouping_sets.keys, set);
+            let projected_keys: Vec<&str> = grouping_sets
+                .keys
+                .iter()
+                .zip(set)
+                .map(|(key, included)| if *included { key.as_str() } else { "NULL" })
+                .collect();
+            let set_flags: Vec<&str> = set
+                .iter()
+                .map(|included| if *included { "0" } else { "1" })
+                .collect();
+            let mut query = format!(
+                "SELECT {}, {}, {}\n{}",
+                projected_keys.join(", "),
+                set_flags.join(", "),
+                aggregates,
+                source
+            );
+            if !set_keys.is_empty() {
+                query.push_str(&format!("\nGROUP BY {}", set_keys.join(", ")));
+            }
+            query_group.push(QueryContext::with_description(
+                query,
+                Arc::clone(&session_context),
+                set_label(&grouping_sets.keys, set),
+            ));
+        }
+
+        self.grouping_sets = Some(grouping_sets);
+        Ok(query_group)
+    }
+
+    async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
+        let grouping_sets = self
+            .grouping_sets
+            .as_ref()
+            .ok_or_else(|| fuzzer_err("GROUPING SETS oracle validated before generating"))?;
+        let Some((grouping_result, set_results)) = results.split_first() else {
+            return Err(fuzzer_err("GROUPING SETS oracle expects query results"));
+        };
+        if set_results.len() != grouping_sets.sets.len() {
+            return Err(fuzzer_err(&format!(
+                "GROUPING SETS oracle expects {} query results, got {}",
+                grouping_sets.sets.len() + 1,
+                results.len()
+            )));
+        }
+        let Ok(grouping_batches) = &grouping_result.result else {
+            return Ok(());
+        };
+        // Truncated results only hold some rows of each grouping set
+        if grouping_result.truncated.is_some() {
+            return Ok(());
+        }
+
+        let options = self
+            .ctx
+            .runner_config
+            .compare_options(ConfiguredOracle::GroupingSets);
+        for (set, set_result) in grouping_sets.sets.iter().zip(set_results) {
+            let (Ok(set_batches), None) = (&set_result.result, &set_result.truncated) else {
+                continue;
+            };
+            let rows = rows_of_set(grouping_batches, grouping_sets.keys.len(), set)?;
+            if let Some(diff) = compare_batches(&rows, set_batches, &options)? {
+                return Err(fuzzer_err(&format!(
+                    "GROUPING SETS violated: the rows of {} for the grouping set of {} differ from the standalone query:\n{}",
+                    grouping_sets.clause,
+                    set_label(&grouping_sets.keys, set),
+                    diff
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
+        let mut report = String::new();
+        report.push_str("GROUPING SETS Oracle Test Failed\n");
+        report.push_str("================================\n\n");
+
+        if let Some(grouping_sets) = &self.grouping_sets {
+            let mut labels = vec![grouping_sets.clause.clone()];
+            labels.extend(
+                grouping_sets
+                    .sets
+                    .iter()
+                    .map(|set| set_label(&grouping_sets.keys, set)),
+            );
+            let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+            oracle_common::append_labeled_query_results(&mut report, results, &labels);
+        }
+        report.push_str(
+            "Expected: the rows of every grouping set equal to its standalone GROUP BY query\n",
+        );
+
+        Ok(report)
+    }
+}
+
+/// The keys of `keys` that are part of the grouping set `set`
+fn keys_of_set<'a>(keys: &'a [String], set: &[bool]) -> Vec<&'a str> {
+    keys.iter()
+        .zip(set)
+        .filter(|(_, included)| **included)
+        .map(|(key, _)| key.as_str())
+        .collect()
+}
+
+/// Describes the standalone query of the grouping set `set`
+fn set_label(keys: &[String], set: &[bool]) -> String {
+    format!("GROUP BY ({})", keys_of_set(keys, set).join(", "))
+}
+
+/// The rows of a grouping query whose `GROUPING` flags, the `key_count`
+/// columns after the keys, match the grouping set `set`
+fn rows_of_set(
+    batches: &[RecordBatch],
+    key_count: usize,
+    set: &[bool],
+) -> Result<Vec<RecordBatch>> {
+    batches
+        .iter()
+        .map(|batch| {
+            if batch.num_columns() < key_count * 2 {
+                return Err(fuzzer_err("Grouping query must return keys and flags"));
+            }
+            let mut mask = BooleanArray::from(vec![true; batch.num_rows()]);
+            for (key, included) in set.iter().enumerate() {
+                let flag = cast(batch.column(key_count + key), &DataType::Int64)
+                    .map_err(DataFusionError::from)?;
+                let expected = Int64Array::new_scalar(if *included { 0 } else { 1 });
+                let matches = eq(&flag, &expected).map_err(DataFusionError::from)?;
+                mask = and(&mask, &matches).map_err(DataFusionError::from)?;
+            }
+            Ok(filter_record_batch(batch, &mask).map_err(DataFusionError::from)?)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::init_available_data_types;
+    use crate::oracle::test_helpers;
+    use crate::oracle::test_helpers::table_with_int_values;
+    use datafusion::arrow::array::{Array, Int32Array};
+    use datafusion::arrow::datatypes::{Field, Schema};
+
+    /// A result with a key column, its `GROUPING` flag and a count
+    fn grouped_result(label: &str, rows: &[(Option<i64>, i32, i64)]) -> QueryExecutionResult {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("c0", DataType::Int64, true),
+            Field::new("grouping", DataType::Int32, false),
+            Field::new("count", DataType::Int64, false),
+        ]));
+        let columns: Vec<Arc<dyn Array>> = vec![
+            Arc::new(Int64Array::from(
+                rows.iter().map(|row| row.0).collect::<Vec<_>>(),
+            )),
+            Arc::new(Int32Array::from(
+                rows.iter().map(|row| row.1).collect::<Vec<_>>(),
+            )),
+            Arc::new(Int64Array::from(
+                rows.iter().map(|row| row.2).collect::<Vec<_>>(),
+            )),
+        ];
+        QueryExecutionResult {
+            result: Ok(vec![RecordBatch::try_new(schema, columns).unwrap()]),
+            ..test_helpers::make_error_result(label)
+        }
+    }
+
+    #[test]
+    fn computes_every_grouping_set_alone() {
+        init_available_data_types();
+        let ctx = Arc::new(crate::fuzz_context::GlobalContext::default());
+        ctx.runtime_context
+            .register_table(Arc::new(table_with_int_values(&[Some(1), None, Some(1)])));
+
+        for seed in 0..16 {
+            let mut oracle = GroupingSetsOracle::new(seed, Arc::clone(&ctx));
+            let query_group = oracle.generate_query_group().unwrap();
+            let grouping_sets = oracle.grouping_sets.as_ref().unwrap();
+            assert_eq!(query_group.len(), grouping_sets.sets.len() + 1);
+            assert!(
+                query_group[0]
+                    .query
+                    .starts_with("SELECT t1.c0, GROUPING(t1.c0), ")
+            );
+            assert!(
+                query_group[0]
+                    .query
+                    .ends_with(&format!("GROUP BY {}", grouping_sets.clause))
+            );
+            for (set, query) in grouping_sets.sets.iter().zip(&query_group[1..]) {
+                if set[0] {
+                    assert!(query.query.starts_with("SELECT t1.c0, 0, "));
+                    assert!(query.query.ends_with("GROUP BY t1.c0"));
+                } else {
+                    assert!(query.query.starts_with("SELECT NULL, 1, "));
+                    assert!(!query.query.contains("GROUP BY"));
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn grouping_sets_must_match_standalone_queries() {
+        let mut oracle =
+            GroupingSetsOracle::new(1, Arc::new(crate::fuzz_context::GlobalContext::default()));
+        oracle.grouping_sets = Some(GroupingSets::rollup(vec!["t1.c0".to_string()]));
+
+        let rollup = || grouped_result("rollup", &[(Some(1), 0, 2), (None, 0, 1), (None, 1, 3)]);
+        let matching = vec![
+            rollup(),
+            grouped_result("by c0", &[(None, 0, 1), (Some(1), 0, 2)]),
+            grouped_result("total", &[(None, 1, 3)]),
+        ];
+        assert!(oracle.validate_consistency(&matching).await.is_ok());
+
+        let wrong = vec![
+            rollup(),
+            grouped_result("by c0", &[(None, 0, 1), (Some(1), 0, 2)]),
+            grouped_result("total", &[(None, 1, 4)]),
+        ];
+        let err = oracle.validate_consistency(&wrong).await.unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("the rows of ROLLUP(t1.c0) for the grouping set of GROUP BY ()")
+        );
+
+        let failed = vec![
+            test_helpers::make_error_result("rollup"),
+            grouped_result("by c0", &[]),
+            grouped_result("total", &[]),
+        ];
+        assert!(oracle.validate_consistency(&failed).await.is_ok());
+    }
+}
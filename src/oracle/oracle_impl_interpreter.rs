@@ -0,0 +1,208 @@
+use std::sync::Arc;
+
+use datafusion::scalar::ScalarValue;
+use rand::Rng;
+
+use crate::common::rng::rng_from_seed;
+use crate::common::util::to_sql_string;
+use crate::common::{Result, fuzzer_err};
+use crate::oracle::expr_interpreter::{InterpretedExprGenerator, Value, ValueType, column_name};
+use crate::oracle::{Oracle, QueryContext, QueryExecutionResult, oracle_common};
+
+/// Number of columns of the input row
+const INPUT_COLUMN_COUNT: usize = 4;
+
+/// Reference interpreter oracle.
+///
+/// Generates an expression over `BIGINT` and `BOOLEAN` values from the
+/// fuzzer's `BaseExpr`s, evaluates it with the interpreter in
+/// `expr_interpreter` and checks DataFusion's evaluation of the same
+/// expression over a one-row table. The interpreter documents the semantics
+/// it checks; expressions that overflow or divide by zero are only run.
+///
+/// The row is an inline `VALUES` list, so the optimizer may fold the
+/// expression into a constant: both the simplifier and the scalar kernels are
+/// checked.
+///
+/// ### Example:
+///
+/// SELECT ((c0 % c1) > -3) AND c2
+/// FROM (VALUES (CAST(7 AS BIGINT), CAST(-2 AS BIGINT), CAST(NULL AS BOOLEAN), ...)) AS t(c0, c1, c2, c3);
+///
+/// must return NULL.
+pub struct InterpreterOracle {
+    seed: u64,
+    ctx: Arc<crate::fuzz_context::GlobalContext>,
+    /// Result of the interpreter, `None` if the expression is undefined
+    expected: Option<Value>,
+}
+
+impl InterpreterOracle {
+    pub fn new(seed: u64, ctx: Arc<crate::fuzz_context::GlobalContext>) -> Self {
+        Self {
+            seed,
+            ctx,
+            expected: None,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Oracle for InterpreterOracle {
+    fn name(&self) -> &'static str {
+        "InterpreterOracle"
+    }
+
+    fn generate_query_group(&mut self) -> Result<Vec<QueryContext>> {
+        let mut rng = rng_from_seed(self.seed);
+        let column_types: Vec<ValueType> = (0..INPUT_COLUMN_COUNT)
+            .map(|_| {
+                if rng.random_bool(0.7) {
+                    ValueType::Int64
+                } else {
+                    ValueType::Boolean
+                }
+            })
+            .collect();
+        let result_type = if rng.random_bool(0.5) {
+            ValueType::Int64
+        } else {
+            ValueType::Boolean
+        };
+
+        let value_config = &self.ctx.runtime_context.value_generation_config;
+        let mut generator = InterpretedExprGenerator {
+            rng: &mut rng,
+            columns: &column_types,
+            value_config,
+            max_level: self.ctx.runner_config.max_expr_level,
+            three_valued: false,
+        };
+        let row: Vec<Value> = column_types
+            .iter()
+            .map(|value_type| generator.generate_value(*value_type))
+            .collect();
+        let expr = generator.generate_expr(result_type, 0);
+        self.expected = expr.evaluate(&row);
+
+        let row_sql = row
+            .iter()
+            .zip(&column_types)
+            .map(|(value, value_type)| {
+                Ok(format!(
+                    "CAST({} AS {})",
+                    to_sql_string(&datafusion::prelude::lit(value.to_scalar_value()))?,
+                    value_type.sql_type()
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let column_names: Vec<String> = (0..column_types.len()).map(column_name).collect();
+        let query = format!(
+            "SELECT {}\nFROM (VALUES ({})) AS t({})",
+            to_sql_string(&expr.to_expr())?,
+            row_sql.join(", "),
+            column_names.join(", ")
+        );
+
+        let description = match self.expected {
+            Some(value) => format!("Interpreter result {}", value.to_scalar_value()),
+            None => "Interpreter result undefined".to_string(),
+        };
+        Ok(vec![QueryContext::with_description(
+            query,
+            self.ctx.runtime_context.get_session_context(),
+            description,
+        )])
+    }
+
+    async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
+        let [result] = results else {
+            return Err(fuzzer_err(&format!(
+                "Interpreter oracle expects 1 query result, got {}",
+                results.len()
+            )));
+        };
+        let (Some(expected), Ok(batches)) = (self.expected, &result.result) else {
+            return Ok(());
+        };
+
+        let total_rows: usize = batches.iter().map(|batch| batch.num_rows()).sum();
+        let Some(batch) = batches
+            .iter()
+            .find(|batch| batch.num_rows() > 0)
+            .filter(|_| total_rows == 1)
+        else {
+            return Err(fuzzer_err(&format!(
+                "Interpreter query returned {} rows instead of 1",
+                total_rows
+            )));
+        };
+        let actual = ScalarValue::try_from_array(batch.column(0), 0)?;
+
+        let expected = expected.to_scalar_value();
+        let matches = if expected.is_null() {
+            actual.is_null()
+        } else {
+            actual.cast_to(&expected.data_type()).ok().as_ref() == Some(&expected)
+        };
+        if !matches {
+            return Err(fuzzer_err(&format!(
+                "Interpreter ground truth violated: DataFusion returned {}, the interpreter {}",
+                actual, expected
+            )));
+        }
+        Ok(())
+    }
+
+    fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
+        let mut report = String::new();
+        report.push_str("Interpreter Oracle Test Failed\n");
+        report.push_str("==============================\n\n");
+
+        oracle_common::append_labeled_query_results(&mut report, results, &["expression"]);
+
+        if let Some(expected) = self.expected {
+            report.push_str(&format!(
+                "Expected: {}, computed by the reference interpreter\n",
+                expected.to_scalar_value()
+            ));
+        }
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracle::test_helpers;
+
+    #[test]
+    fn generates_one_row_queries() {
+        let ctx = Arc::new(crate::fuzz_context::GlobalContext::default());
+        for seed in 0..16 {
+            let mut oracle = InterpreterOracle::new(seed, Arc::clone(&ctx));
+            let query_group = oracle.generate_query_group().unwrap();
+            assert_eq!(query_group.len(), 1);
+            assert!(query_group[0].query.contains("FROM (VALUES (CAST("));
+            assert!(query_group[0].query.ends_with("AS t(c0, c1, c2, c3)"));
+        }
+    }
+
+    #[tokio::test]
+    async fn result_must_match_the_interpreter() {
+        let mut oracle =
+            InterpreterOracle::new(1, Arc::new(crate::fuzz_context::GlobalContext::default()));
+        oracle.expected = Some(Value::Int64(Some(5)));
+
+        let matching = vec![test_helpers::make_success_result("expr", "e", vec![5])];
+        assert!(oracle.validate_consistency(&matching).await.is_ok());
+
+        let wrong = vec![test_helpers::make_success_result("expr", "e", vec![6])];
+        let err = oracle.validate_consistency(&wrong).await.unwrap_err();
+        assert!(err.to_string().contains("the interpreter 5"));
+
+        oracle.expected = None;
+        assert!(oracle.validate_consistency(&wrong).await.is_ok());
+    }
+}
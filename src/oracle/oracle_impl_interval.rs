@@ -0,0 +1,355 @@
+use std::sync::Arc;
+
+use datafusion::arrow::array::RecordBatch;
+use datafusion::scalar::ScalarValue;
+use rand::Rng;
+use rand::rngs::StdRng;
+
+use crate::common::rng::rng_from_seed;
+use crate::common::{Result, fuzzer_err};
+use crate::oracle::{Oracle, QueryContext, QueryExecutionResult, oracle_common};
+
+/// Timestamps the intervals are added to: month ends, a leap day, a DST
+/// transition in `America/New_York` and `Europe/Berlin`, and a year end
+const BASE_TIMESTAMPS: [&str; 6] = [
+    "2024-01-31 12:00:00",
+    "2024-02-29 00:00:00",
+    "2023-03-31 23:30:00",
+    "2021-03-14 01:30:00",
+    "2021-10-30 02:30:00",
+    "1999-12-31 23:59:59.999999999",
+];
+
+/// Zones of the base timestamps whose days are always 24 hours long
+const FIXED_ZONES: [Option<&str>; 4] = [None, Some("UTC"), Some("+05:45"), Some("-03:30")];
+
+/// Zones with DST, where a day can be 23 or 25 hours long
+const DST_ZONES: [&str; 3] = ["America/New_York", "Europe/Berlin", "Australia/Lord_Howe"];
+
+/// Interval units whose conversions are exact
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IntervalClass {
+    /// Years and months, both stored as months
+    Months,
+    /// Weeks and days, both stored as days
+    Days,
+    /// Days, hours, minutes, seconds and milliseconds, equal only where
+    /// every day has 24 hours
+    FixedDuration,
+}
+
+impl IntervalClass {
+    fn random(rng: &mut StdRng) -> Self {
+        match rng.random_range(0..3) {
+            0 => Self::Months,
+            1 => Self::Days,
+            _ => Self::FixedDuration,
+        }
+    }
+
+    /// Equivalent ways to write one random interval of this class
+    fn representations(self, rng: &mut StdRng) -> Vec<String> {
+        match self {
+            Self::Months => {
+                let months: i64 = rng.random_range(1..=30);
+                vec![
+                    format!("{} months", months),
+                    join_units(&[(months / 12, "years"), (months % 12, "months")]),
+                ]
+            }
+            Self::Days => {
+                let days: i64 = rng.random_range(1..=20);
+                vec![
+                    format!("{} days", days),
+                    join_units(&[(days / 7, "weeks"), (days % 7, "days")]),
+                ]
+            }
+            Self::FixedDuration => {
+                let seconds: i64 = rng.random_range(1..=3 * 86400);
+                vec![
+                    format!("{} seconds", seconds),
+                    format!("{} milliseconds", seconds * 1000),
+                    join_units(&[(seconds / 60, "minutes"), (seconds % 60, "seconds")]),
+                    join_units(&[
+                        (seconds / 86400, "days"),
+                        (seconds % 86400 / 3600, "hours"),
+                        (seconds % 3600 / 60, "minutes"),
+                        (seconds % 60, "seconds"),
+                    ]),
+                ]
+            }
+        }
+    }
+
+    /// A zone for the base timestamps where the representations are equal
+    fn random_zone(self, rng: &mut StdRng) -> Option<&'static str> {
+        match self {
+            Self::FixedDuration => FIXED_ZONES[rng.random_range(0..FIXED_ZONES.len())],
+            Self::Months | Self::Days => {
+                let index = rng.random_range(0..FIXED_ZONES.len() + DST_ZONES.len());
+                match index.checked_sub(FIXED_ZONES.len()) {
+                    Some(dst_index) => Some(DST_ZONES[dst_index]),
+                    None => FIXED_ZONES[index],
+                }
+            }
+        }
+    }
+}
+
+/// `amount unit` pairs joined with spaces, zero amounts are skipped
+fn join_units(parts: &[(i64, &str)]) -> String {
+    parts
+        .iter()
+        .filter(|(amount, _)| *amount != 0)
+        .map(|(amount, unit)| format!("{} {}", amount, unit))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Interval normalization oracle.
+///
+/// Writes one interval in several equivalent ways, e.g. `1 day 2 hours`,
+/// `1560 minutes` and `93600 seconds`, and adds and subtracts each of them
+/// from the same timestamps. All representations must give the same results.
+///
+/// Some intervals that look equal legitimately give different results, and
+/// are never generated:
+///
+/// - `1 month` and `30 days`: months have different lengths.
+/// - `1 day` and `24 hours` in a zone with DST: a day is a calendar day in
+///   the zone of the timestamp, 23 or 25 hours long across a transition. Days
+///   and hours are only mixed for timestamps without a zone or with a fixed
+///   offset.
+/// - `INTERVAL '1 day' = INTERVAL '24 hours'` is false: DataFusion compares
+///   the month, day and nanosecond fields separately.
+///
+/// ### Example:
+///
+/// SELECT id, ts + INTERVAL '1 day 2 hours', ts - INTERVAL '1 day 2 hours'
+/// FROM (VALUES (0, CAST('2024-02-29 00:00:00' AS TIMESTAMP)), ...) AS t(id, ts)
+/// ORDER BY id;
+///
+/// must return the same rows as the query with `INTERVAL '93600 seconds'`.
+pub struct IntervalOracle {
+    seed: u64,
+    ctx: Arc<crate::fuzz_context::GlobalContext>,
+}
+
+impl IntervalOracle {
+    pub fn new(seed: u64, ctx: Arc<crate::fuzz_context::GlobalContext>) -> Self {
+        Self { seed, ctx }
+    }
+}
+
+#[async_trait::async_trait]
+impl Oracle for IntervalOracle {
+    fn name(&self) -> &'static str {
+        "IntervalOracle"
+    }
+
+    fn generate_query_group(&mut self) -> Result<Vec<QueryContext>> {
+        let mut rng = rng_from_seed(self.seed);
+        let class = IntervalClass::random(&mut rng);
+        let representations = class.representations(&mut rng);
+        let zone = class.random_zone(&mut rng);
+
+        let base_rows: Vec<String> = BASE_TIMESTAMPS
+            .iter()
+            .enumerate()
+            .map(|(id, timestamp)| {
+                let base = match zone {
+                    Some(zone) => format!(
+                        "arrow_cast('{}', 'Timestamp(Nanosecond, Some(\"{}\"))')",
+                        timestamp, zone
+                    ),
+                    None => format!("CAST('{}' AS TIMESTAMP)", timestamp),
+                };
+                format!("({}, {})", id, base)
+            })
+            .collect();
+
+        let session_context = self.ctx.runtime_context.get_session_context();
+        Ok(representations
+            .into_iter()
+            .map(|interval| {
+                let query = format!(
+                    "SELECT id, ts + INTERVAL '{interval}', ts - INTERVAL '{interval}'\nFROM (VALUES {}) AS t(id, ts)\nORDER BY id",
+                    base_rows.join(", ")
+                );
+                QueryContext::with_description(
+                    query,
+                    Arc::clone(&session_context),
+                    format!("INTERVAL '{}' in zone {}", interval, zone.unwrap_or("none")),
+                )
+            })
+            .collect())
+    }
+
+    async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
+        let Some((first, rest)) = results.split_first() else {
+            return Err(fuzzer_err("No query results to validate"));
+        };
+        let Ok(first_batches) = &first.result else {
+            return Ok(());
+        };
+        let expected = rendered_rows(first_batches)?;
+
+        for result in rest {
+            let Ok(batches) = &result.result else {
+                continue;
+            };
+            let actual = rendered_rows(batches)?;
+            if let Some((expected_row, actual_row)) = expected
+                .iter()
+                .zip(&actual)
+                .find(|(expected_row, actual_row)| expected_row != actual_row)
+            {
+                return Err(fuzzer_err(&format!(
+                    "Interval normalization violated: {} gives {:?}, {} gives {:?}",
+                    description(first),
+                    expected_row,
+                    description(result),
+                    actual_row
+                )));
+            }
+            if actual.len() != expected.len() {
+                return Err(fuzzer_err(&format!(
+                    "Interval normalization violated: {} returned {} rows, {} returned {}",
+                    description(first),
+                    expected.len(),
+                    description(result),
+                    actual.len()
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
+        let mut report = String::new();
+        report.push_str("Interval Oracle Test Failed\n");
+        report.push_str("===========================\n\n");
+
+        let labels: Vec<String> = results.iter().map(description).collect();
+        let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+        oracle_common::append_labeled_query_results(&mut report, results, &labels);
+
+        report.push_str("Expected: the same rows for every representation of the interval\n");
+        Ok(report)
+    }
+}
+
+fn description(result: &QueryExecutionResult) -> String {
+    result
+        .query_context
+        .context_description
+        .clone()
+        .unwrap_or_else(|| "query".to_string())
+}
+
+/// All rows of a result, with values rendered as strings
+fn rendered_rows(batches: &[RecordBatch]) -> Result<Vec<Vec<String>>> {
+    let mut rows = Vec::new();
+    for batch in batches {
+        for row in 0..batch.num_rows() {
+            rows.push(
+                batch
+                    .columns()
+                    .iter()
+                    .map(|column| -> Result<String> {
+                        Ok(ScalarValue::try_from_array(column, row)?.to_string())
+                    })
+                    .collect::<Result<Vec<_>>>()?,
+            );
+        }
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracle::test_helpers;
+
+    #[test]
+    fn representations_are_equivalent() {
+        assert_eq!(join_units(&[(1, "years"), (0, "months")]), "1 years");
+        assert_eq!(
+            join_units(&[(1, "days"), (2, "hours"), (0, "minutes"), (5, "seconds")]),
+            "1 days 2 hours 5 seconds"
+        );
+
+        let mut rng = rng_from_seed(7);
+        for _ in 0..32 {
+            let representations = IntervalClass::FixedDuration.representations(&mut rng);
+            let seconds: i64 = representations[0]
+                .strip_suffix(" seconds")
+                .unwrap()
+                .parse()
+                .unwrap();
+            let total: i64 = representations[3]
+                .split(' ')
+                .collect::<Vec<_>>()
+                .chunks(2)
+                .map(|part| {
+                    let amount: i64 = part[0].parse().unwrap();
+                    amount
+                        * match part[1] {
+                            "days" => 86400,
+                            "hours" => 3600,
+                            "minutes" => 60,
+                            _ => 1,
+                        }
+                })
+                .sum();
+            assert_eq!(seconds, total);
+        }
+    }
+
+    #[test]
+    fn days_and_hours_are_not_mixed_in_dst_zones() {
+        let mut rng = rng_from_seed(1);
+        for _ in 0..64 {
+            let zone = IntervalClass::FixedDuration.random_zone(&mut rng);
+            assert!(FIXED_ZONES.contains(&zone));
+        }
+    }
+
+    #[test]
+    fn generates_one_query_per_representation() {
+        let ctx = Arc::new(crate::fuzz_context::GlobalContext::default());
+        for seed in 0..16 {
+            let mut oracle = IntervalOracle::new(seed, Arc::clone(&ctx));
+            let query_group = oracle.generate_query_group().unwrap();
+            assert!(query_group.len() >= 2);
+            for query_context in &query_group {
+                assert!(
+                    query_context
+                        .query
+                        .starts_with("SELECT id, ts + INTERVAL '")
+                );
+                assert!(query_context.query.ends_with("AS t(id, ts)\nORDER BY id"));
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn representations_must_agree() {
+        let oracle =
+            IntervalOracle::new(1, Arc::new(crate::fuzz_context::GlobalContext::default()));
+
+        let same = vec![
+            test_helpers::make_success_result("days", "v", vec![1, 2]),
+            test_helpers::make_success_result("hours", "v", vec![1, 2]),
+            test_helpers::make_error_result("minutes"),
+        ];
+        assert!(oracle.validate_consistency(&same).await.is_ok());
+
+        let different = vec![
+            test_helpers::make_success_result("days", "v", vec![1, 2]),
+            test_helpers::make_success_result("hours", "v", vec![1, 3]),
+        ];
+        let err = oracle.validate_consistency(&different).await.unwrap_err();
+        assert!(err.to_string().contains("Interval normalization violated"));
+    }
+}
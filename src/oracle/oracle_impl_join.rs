@@ -0,0 +1,392 @@
+use std::sync::Arc;
+
+use datafusion::arrow::array::RecordBatch;
+use datafusion::scalar::ScalarValue;
+use rand::Rng;
+
+use crate::common::rng::rng_from_seed;
+use crate::common::util::to_sql_string;
+use crate::common::{LogicalTable, Result, fuzzer_err};
+use crate::oracle::ground_truth::{column_expr, compare, is_comparable, tables_with_known_values};
+use crate::oracle::{Oracle, QueryContext, QueryExecutionResult, oracle_common};
+
+/// Maximum number of mismatching rows listed in a report
+const MAX_REPORTED_ROWS: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum JoinType {
+    Inner,
+    Left,
+    Right,
+    Full,
+}
+
+impl JoinType {
+    fn to_sql(self) -> &'static str {
+        match self {
+            Self::Inner => "INNER JOIN",
+            Self::Left => "LEFT JOIN",
+            Self::Right => "RIGHT JOIN",
+            Self::Full => "FULL JOIN",
+        }
+    }
+}
+
+/// Two-table equi-join over columns with known values
+struct JoinSpec {
+    join_type: JoinType,
+    left: Arc<LogicalTable>,
+    left_key: usize,
+    right: Arc<LogicalTable>,
+    right_key: usize,
+}
+
+/// Join ground-truth oracle.
+///
+/// Joins two tables whose inserted values are known on a pair of columns of
+/// the same type, and compares DataFusion's result (from a hash, merge or
+/// nested loop join, whatever the planner picks) with the rows of a nested
+/// loop join over the inserted values in Rust.
+///
+/// Only columns whose values compare the same in Rust and in SQL are
+/// selected, so rows can be compared by their rendered values.
+///
+/// ### Example:
+///
+/// SELECT t1.c0, t1.c1, t2.c0
+/// FROM t1
+/// LEFT JOIN t2 ON t1.c0 = t2.c0;
+pub struct JoinOracle {
+    seed: u64,
+    ctx: Arc<crate::fuzz_context::GlobalContext>,
+    /// Rows of the join computed in Rust, rendered like `result_rows`
+    expected_rows: Vec<Vec<String>>,
+}
+
+impl JoinOracle {
+    pub fn new(seed: u64, ctx: Arc<crate::fuzz_context::GlobalContext>) -> Self {
+        Self {
+            seed,
+            ctx,
+            expected_rows: Vec::new(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Oracle for JoinOracle {
+    fn name(&self) -> &'static str {
+        "JoinOracle"
+    }
+
+    fn generate_query_group(&mut self) -> Result<Vec<QueryContext>> {
+        let tables = tables_with_known_values(&self.ctx);
+        let mut key_pairs = Vec::new();
+        for left in &tables {
+            for right in tables.iter().filter(|right| right.name != left.name) {
+                for (left_key, left_column) in left.columns.iter().enumerate() {
+                    for (right_key, right_column) in right.columns.iter().enumerate() {
+                        if left_column.data_type == right_column.data_type
+                            && is_comparable(&left_column.data_type)
+                        {
+                            key_pairs.push((
+                                Arc::clone(left),
+                                left_key,
+                                Arc::clone(right),
+                                right_key,
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+        if key_pairs.is_empty() {
+            return Err(fuzzer_err(
+                "Join oracle requires two tables with known values and columns of the same type",
+            ));
+        }
+
+        let mut rng = rng_from_seed(self.seed);
+        let (left, left_key, right, right_key) =
+            key_pairs.swap_remove(rng.random_range(0..key_pairs.len()));
+        let join_types = [
+            JoinType::Inner,
+            JoinType::Left,
+            JoinType::Right,
+            JoinType::Full,
+        ];
+        let spec = JoinSpec {
+            join_type: join_types[rng.random_range(0..join_types.len())],
+            left,
+            left_key,
+            right,
+            right_key,
+        };
+
+        self.expected_rows = nested_loop_join(&spec)?;
+        Ok(vec![QueryContext::with_description(
+            join_sql(&spec)?,
+            self.ctx.runtime_context.get_session_context(),
+            "Join computed by nested loops in Rust".to_string(),
+        )])
+    }
+
+    async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
+        let [result] = results else {
+            return Err(fuzzer_err(&format!(
+                "Join oracle expects 1 query result, got {}",
+                results.len()
+            )));
+        };
+        let Ok(batches) = &result.result else {
+            return Ok(());
+        };
+
+        let mut actual_rows = result_rows(batches)?;
+        actual_rows.sort();
+        let mut expected_rows = self.expected_rows.clone();
+        expected_rows.sort();
+        if actual_rows != expected_rows {
+            return Err(fuzzer_err(&format!(
+                "Join ground truth violated: DataFusion returned {} rows, the nested loop join {} rows{}",
+                actual_rows.len(),
+                expected_rows.len(),
+                if actual_rows.len() == expected_rows.len() {
+                    " with different values"
+                } else {
+                    ""
+                }
+            )));
+        }
+        Ok(())
+    }
+
+    fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
+        let mut report = String::new();
+        report.push_str("Join Oracle Test Failed\n");
+        report.push_str("=======================\n\n");
+
+        oracle_common::append_labeled_query_results(&mut report, results, &["join"]);
+
+        let mut expected_rows = self.expected_rows.clone();
+        expected_rows.sort();
+        report.push_str(&format!(
+            "Expected {} rows from the nested loop join:\n",
+            expected_rows.len()
+        ));
+        for row in expected_rows.iter().take(MAX_REPORTED_ROWS) {
+            report.push_str(&format!("  ({})\n", row.join(", ")));
+        }
+        if expected_rows.len() > MAX_REPORTED_ROWS {
+            report.push_str(&format!(
+                "  ... {} more\n",
+                expected_rows.len() - MAX_REPORTED_ROWS
+            ));
+        }
+
+        Ok(report)
+    }
+}
+
+/// Columns in the select list: the comparable columns of both tables
+fn selected_columns(table: &LogicalTable) -> Vec<usize> {
+    (0..table.columns.len())
+        .filter(|&idx| is_comparable(&table.columns[idx].data_type))
+        .collect()
+}
+
+fn join_sql(spec: &JoinSpec) -> Result<String> {
+    let mut select_list = Vec::new();
+    for table in [&spec.left, &spec.right] {
+        for idx in selected_columns(table) {
+            select_list.push(to_sql_string(&column_expr(table, &table.columns[idx]))?);
+        }
+    }
+    let condition = column_expr(&spec.left, &spec.left.columns[spec.left_key]).eq(column_expr(
+        &spec.right,
+        &spec.right.columns[spec.right_key],
+    ));
+
+    Ok(format!(
+        "SELECT {}\nFROM {}\n{} {} ON {}",
+        select_list.join(", "),
+        spec.left.sql_name(),
+        spec.join_type.to_sql(),
+        spec.right.sql_name(),
+        to_sql_string(&condition)?
+    ))
+}
+
+/// Values of the inserted rows of `table`, row by row
+fn table_rows(table: &LogicalTable) -> Result<Vec<Vec<&ScalarValue>>> {
+    let columns = table
+        .columns
+        .iter()
+        .map(|column| {
+            column
+                .value_summary
+                .as_deref()
+                .map(|summary| &summary.values)
+                .ok_or_else(|| fuzzer_err(&format!("Values of {} are unknown", column.name)))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let row_count = table.row_count().unwrap_or_default() as usize;
+
+    Ok((0..row_count)
+        .map(|row| columns.iter().map(|values| &values[row]).collect())
+        .collect())
+}
+
+/// The rows of the join, computed by comparing every pair of rows
+fn nested_loop_join(spec: &JoinSpec) -> Result<Vec<Vec<String>>> {
+    let left_rows = table_rows(&spec.left)?;
+    let right_rows = table_rows(&spec.right)?;
+    let left_columns = selected_columns(&spec.left);
+    let right_columns = selected_columns(&spec.right);
+
+    let render = |row: Option<&Vec<&ScalarValue>>, columns: &[usize]| -> Vec<String> {
+        columns
+            .iter()
+            .map(|&idx| match row {
+                Some(row) => row[idx].to_string(),
+                None => ScalarValue::Null.to_string(),
+            })
+            .collect()
+    };
+    let joined = |left: Option<&Vec<&ScalarValue>>, right: Option<&Vec<&ScalarValue>>| {
+        let mut row = render(left, &left_columns);
+        row.extend(render(right, &right_columns));
+        row
+    };
+
+    let mut rows = Vec::new();
+    let mut right_matched = vec![false; right_rows.len()];
+    for left_row in &left_rows {
+        let mut left_matched = false;
+        for (right_idx, right_row) in right_rows.iter().enumerate() {
+            // NULL keys never match
+            let is_match = compare(left_row[spec.left_key], right_row[spec.right_key])?
+                == Some(std::cmp::Ordering::Equal);
+            if is_match {
+                left_matched = true;
+                right_matched[right_idx] = true;
+                rows.push(joined(Some(left_row), Some(right_row)));
+            }
+        }
+        if !left_matched && matches!(spec.join_type, JoinType::Left | JoinType::Full) {
+            rows.push(joined(Some(left_row), None));
+        }
+    }
+    if matches!(spec.join_type, JoinType::Right | JoinType::Full) {
+        for (right_row, matched) in right_rows.iter().zip(right_matched) {
+            if !matched {
+                rows.push(joined(None, Some(right_row)));
+            }
+        }
+    }
+
+    Ok(rows)
+}
+
+/// Rows of a query result, each value rendered like a `ScalarValue`
+fn result_rows(batches: &[RecordBatch]) -> Result<Vec<Vec<String>>> {
+    let mut rows = Vec::new();
+    for batch in batches {
+        for row in 0..batch.num_rows() {
+            rows.push(
+                batch
+                    .columns()
+                    .iter()
+                    .map(|column| Ok(ScalarValue::try_from_array(column, row)?.to_string()))
+                    .collect::<Result<Vec<_>>>()?,
+            );
+        }
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::value_summary::ColumnValueSummary;
+    use crate::common::{FuzzerDataType, LogicalColumn};
+    use crate::oracle::test_helpers;
+
+    fn table(name: &str, keys: &[Option<i64>]) -> Arc<LogicalTable> {
+        let mut summary = ColumnValueSummary::default();
+        for key in keys {
+            summary.record(ScalarValue::Int64(*key));
+        }
+        Arc::new(LogicalTable::with_columns(
+            name.to_string(),
+            vec![LogicalColumn {
+                name: "k".to_string(),
+                data_type: FuzzerDataType::Int64,
+                value_summary: Some(Arc::new(summary)),
+            }],
+        ))
+    }
+
+    fn join(join_type: JoinType) -> Vec<Vec<String>> {
+        let spec = JoinSpec {
+            join_type,
+            left: table("t1", &[Some(1), Some(2), None]),
+            left_key: 0,
+            right: table("t2", &[Some(1), Some(1), Some(3)]),
+            right_key: 0,
+        };
+        let mut rows = nested_loop_join(&spec).unwrap();
+        rows.sort();
+        rows
+    }
+
+    fn row(left: &str, right: &str) -> Vec<String> {
+        vec![left.to_string(), right.to_string()]
+    }
+
+    #[test]
+    fn nested_loop_join_extends_unmatched_rows_with_nulls() {
+        assert_eq!(join(JoinType::Inner), vec![row("1", "1"), row("1", "1")]);
+        assert_eq!(
+            join(JoinType::Left),
+            vec![
+                row("1", "1"),
+                row("1", "1"),
+                row("2", "NULL"),
+                row("NULL", "NULL")
+            ]
+        );
+        assert_eq!(
+            join(JoinType::Right),
+            vec![row("1", "1"), row("1", "1"), row("NULL", "3")]
+        );
+        assert_eq!(join(JoinType::Full).len(), 5);
+    }
+
+    #[test]
+    fn generates_join_of_two_tables() {
+        let ctx = Arc::new(crate::fuzz_context::GlobalContext::default());
+        ctx.runtime_context.register_table(table("t1", &[Some(1)]));
+        ctx.runtime_context.register_table(table("t2", &[Some(1)]));
+
+        let mut oracle = JoinOracle::new(7, ctx);
+        let query_group = oracle.generate_query_group().unwrap();
+        assert!(query_group[0].query.contains("JOIN"));
+        assert!(query_group[0].query.contains(" ON "));
+        assert!(!oracle.expected_rows.is_empty());
+    }
+
+    #[tokio::test]
+    async fn rows_are_compared_as_multisets() {
+        let mut oracle =
+            JoinOracle::new(1, Arc::new(crate::fuzz_context::GlobalContext::default()));
+        oracle.expected_rows = vec![vec!["2".to_string()], vec!["1".to_string()]];
+
+        let same_rows = vec![test_helpers::make_success_result("join", "k", vec![1, 2])];
+        assert!(oracle.validate_consistency(&same_rows).await.is_ok());
+
+        let lost_row = vec![test_helpers::make_success_result("join", "k", vec![1])];
+        let err = oracle.validate_consistency(&lost_row).await.unwrap_err();
+        assert!(err.to_string().contains("Join ground truth violated"));
+    }
+}
@@ -0,0 +1,200 @@
+use crate::common::{InclusionConfig, Result, fuzzer_err};
+use crate::fuzz_context::{memory_limited_session_context, spilling_session_context};
+use crate::oracle::{ConfiguredOracle, Oracle, QueryContext, QueryExecutionResult, oracle_common};
+use crate::query_generator::stmt_select_def::SelectStatementBuilder;
+use crate::triage::{FailureCategory, classify_error};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::sync::Arc;
+
+/// Memory pool sizes the limited query is run with, small enough for sorts,
+/// joins and aggregations over generated tables to hit them
+const MEMORY_LIMITS_KB: [usize; 4] = [64, 256, 1024, 4096];
+
+/// Memory-limit oracle.
+///
+/// Runs the same query without and with a tight memory limit. Running out of
+/// memory must be graceful: the limited query either returns the same rows as
+/// the unlimited one, or fails with a `Resources exhausted` error. Any other
+/// failure (e.g. an internal error or a panic while reserving memory) is a bug.
+///
+/// ### Example:
+///
+/// SELECT t0.c0, count(*) FROM t0 GROUP BY t0.c0;   -- unlimited
+///
+/// SELECT t0.c0, count(*) FROM t0 GROUP BY t0.c0;   -- 256 KiB memory pool
+///
+/// With `spill_fuzzing` the limited query is tuned to spill to disk, so this
+/// also checks that spilled results match in-memory results.
+pub struct MemoryLimitOracle {
+    seed: u64,
+    ctx: Arc<crate::fuzz_context::GlobalContext>,
+    limit_kb: usize,
+}
+
+impl MemoryLimitOracle {
+    pub fn new(seed: u64, ctx: Arc<crate::fuzz_context::GlobalContext>) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let limit_kb = MEMORY_LIMITS_KB[rng.random_range(0..MEMORY_LIMITS_KB.len())];
+        Self {
+            seed,
+            ctx,
+            limit_kb,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Oracle for MemoryLimitOracle {
+    fn name(&self) -> &'static str {
+        "MemoryLimitOracle"
+    }
+
+    fn generate_query_group(&mut self) -> Result<Vec<QueryContext>> {
+        let mut stmt_builder = SelectStatementBuilder::new(
+            self.seed,
+            Arc::clone(&self.ctx),
+            InclusionConfig::Maybe(0.5),
+            InclusionConfig::Maybe(0.5),
+        )
+        .with_allow_derived_tables(false);
+        let stmt = stmt_builder.generate_stmt()?;
+        let sql = stmt.to_sql_string()?;
+
+        let session_context = self.ctx.runtime_context.get_session_context();
+        let limit_bytes = self.limit_kb * 1024;
+        let (limited_context, limited_description) = if self.ctx.runner_config.spill_fuzzing {
+            (
+                spilling_session_context(
+                    &session_context,
+                    limit_bytes,
+                    self.ctx.spill_dir().as_deref(),
+                )?,
+                format!("Memory limit: {} KiB, spilling", self.limit_kb),
+            )
+        } else {
+            (
+                memory_limited_session_context(&session_context, limit_bytes)?,
+                format!("Memory limit: {} KiB", self.limit_kb),
+            )
+        };
+
+        Ok(vec![
+            QueryContext::with_description(
+                sql.clone(),
+                session_context,
+                "Memory limit: none".to_string(),
+            ),
+            QueryContext::with_description(sql, limited_context, limited_description),
+        ])
+    }
+
+    async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
+        let [unlimited, limited] = results else {
+            return Err(fuzzer_err(&format!(
+                "MemoryLimit expects 2 query results, got {}",
+                results.len()
+            )));
+        };
+
+        // Errors of the unlimited query are checked against the whitelist by the runner
+        if unlimited.result.is_err() {
+            return Ok(());
+        }
+
+        match &limited.result {
+            Ok(_) => {
+                let options = self
+                    .ctx
+                    .runner_config
+                    .compare_options(ConfiguredOracle::MemoryLimit);
+                oracle_common::validate_value_equivalence(results, 0, 1, "MemoryLimit", &options)
+            }
+            Err(e) if classify_error(&e.to_string()) == FailureCategory::ResourceExhausted => {
+                Ok(())
+            }
+            Err(e) => Err(fuzzer_err(&format!(
+                "MemoryLimit: query succeeded without a memory limit but failed with a {} KiB limit \
+                 instead of running out of memory gracefully: {}",
+                self.limit_kb, e
+            ))),
+        }
+    }
+
+    fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
+        let mut report = String::new();
+        report.push_str("Memory-Limit Oracle Test Failed\n");
+        report.push_str("===============================\n\n");
+
+        let limited_label = format!("{} KiB limit", self.limit_kb);
+        let labels = ["no limit", limited_label.as_str()];
+        oracle_common::append_labeled_query_results(&mut report, results, &labels);
+
+        report.push_str(
+            "Expected: the memory-limited query returns the same rows or fails with \
+             'Resources exhausted'\n",
+        );
+
+        Ok(report)
+    }
+
+    fn requires_consistent_outcomes(&self) -> bool {
+        // Only the limited query may fail, which is checked in `validate_consistency`
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracle::test_helpers;
+
+    fn make_oracle() -> MemoryLimitOracle {
+        MemoryLimitOracle::new(1, Arc::new(crate::fuzz_context::GlobalContext::default()))
+    }
+
+    fn make_resources_exhausted_result(label: &str) -> QueryExecutionResult {
+        QueryExecutionResult {
+            query_context: test_helpers::make_query_context(label),
+            result: Err(fuzzer_err(
+                "Query execution failed: Resources exhausted: Failed to allocate additional 1 MB",
+            )),
+            truncated: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn memory_limit_accepts_same_rows_or_graceful_oom() {
+        let oracle = make_oracle();
+
+        let same_rows = vec![
+            test_helpers::make_success_result("unlimited", "c", vec![1, 2]),
+            test_helpers::make_success_result("limited", "c", vec![2, 1]),
+        ];
+        assert!(oracle.validate_consistency(&same_rows).await.is_ok());
+
+        let out_of_memory = vec![
+            test_helpers::make_success_result("unlimited", "c", vec![1, 2]),
+            make_resources_exhausted_result("limited"),
+        ];
+        assert!(oracle.validate_consistency(&out_of_memory).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn memory_limit_rejects_other_failures_and_wrong_rows() {
+        let oracle = make_oracle();
+
+        let other_error = vec![
+            test_helpers::make_success_result("unlimited", "c", vec![1, 2]),
+            test_helpers::make_error_result("limited"),
+        ];
+        let err = oracle.validate_consistency(&other_error).await.unwrap_err();
+        assert!(err.to_string().contains("instead of running out of memory"));
+
+        let wrong_rows = vec![
+            test_helpers::make_success_result("unlimited", "c", vec![1, 2]),
+            test_helpers::make_success_result("limited", "c", vec![1]),
+        ];
+        assert!(oracle.validate_consistency(&wrong_rows).await.is_err());
+    }
+}
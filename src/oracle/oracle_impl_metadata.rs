@@ -0,0 +1,371 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{ArrayRef, Float64Array, Int64Array, RecordBatch, StringArray};
+use datafusion::arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use datafusion::datasource::MemTable;
+use datafusion::error::DataFusionError;
+use datafusion::prelude::SessionContext;
+use rand::Rng;
+use rand::rngs::StdRng;
+
+use crate::common::rng::rng_from_seed;
+use crate::common::{Result, fuzzer_err};
+use crate::oracle::{Oracle, QueryContext, QueryExecutionResult, oracle_common};
+
+/// Metadata keys, including an empty and a non-ASCII one
+const METADATA_KEYS: [&str; 5] = ["comment", "origin", "", "a.b.c", "schl\u{fc}ssel"];
+
+/// Metadata values, including an empty, a multi-line and a non-ASCII one
+const METADATA_VALUES: [&str; 5] = ["", "x", "line 1\nline 2", "\u{503c}", "{\"k\": [1, 2]}"];
+
+/// Maximum number of data columns besides `id`
+const MAX_DATA_COLUMNS: usize = 3;
+
+/// Random metadata with up to 3 entries
+fn random_metadata(rng: &mut StdRng) -> HashMap<String, String> {
+    (0..rng.random_range(0..=3))
+        .map(|_| {
+            let key = METADATA_KEYS[rng.random_range(0..METADATA_KEYS.len())];
+            let value = METADATA_VALUES[rng.random_range(0..METADATA_VALUES.len())];
+            (key.to_string(), value.to_string())
+        })
+        .collect()
+}
+
+/// A random column of `row_count` values, without nulls so filters keep
+/// every row
+fn random_column(rng: &mut StdRng, row_count: usize) -> (DataType, ArrayRef) {
+    match rng.random_range(0..3) {
+        0 => {
+            let values: Int64Array = (0..row_count)
+                .map(|_| Some(rng.random_range(-100..=100)))
+                .collect();
+            (DataType::Int64, Arc::new(values))
+        }
+        1 => {
+            let values: Float64Array = (0..row_count)
+                .map(|_| Some(rng.random_range(-100.0..=100.0)))
+                .collect();
+            (DataType::Float64, Arc::new(values))
+        }
+        _ => {
+            let values: StringArray = (0..row_count)
+                .map(|_| Some(format!("s{}", rng.random_range(0..10))))
+                .collect();
+            (DataType::Utf8, Arc::new(values))
+        }
+    }
+}
+
+/// A query of the group, with the table column each output column is a plain
+/// reference to, or `None` for computed columns
+struct MetadataQuery {
+    sql: String,
+    description: &'static str,
+    sources: Vec<Option<usize>>,
+}
+
+/// Schema and field metadata propagation oracle.
+///
+/// Registers a `MemTable` whose schema and fields carry random Arrow
+/// metadata and runs projections, aliases, filters, sorts, limits, a
+/// subquery and computed expressions over it. Every query must plan and
+/// run, the result must keep the schema metadata, and output columns that
+/// are plain (possibly renamed) column references must keep the metadata of
+/// their field.
+///
+/// ### Example:
+///
+/// SELECT c0 AS renamed, id FROM t ORDER BY id;
+///
+/// where `c0` has the metadata `{"comment": "x"}` must return a `renamed`
+/// field with the metadata `{"comment": "x"}`. The metadata of computed
+/// columns like `id + 1` isn't specified and only has to plan.
+pub struct MetadataOracle {
+    seed: u64,
+    ctx: Arc<crate::fuzz_context::GlobalContext>,
+    schema: Option<SchemaRef>,
+    /// Output column sources of each query, see [`MetadataQuery`]
+    sources: Vec<Vec<Option<usize>>>,
+}
+
+impl MetadataOracle {
+    pub fn new(seed: u64, ctx: Arc<crate::fuzz_context::GlobalContext>) -> Self {
+        Self {
+            seed,
+            ctx,
+            schema: None,
+            sources: Vec::new(),
+        }
+    }
+
+    /// Check the metadata of one query's result batches
+    fn check_result(
+        &self,
+        schema: &Schema,
+        sources: &[Option<usize>],
+        result: &QueryExecutionResult,
+    ) -> Result<()> {
+        let Ok(batches) = &result.result else {
+            return Ok(());
+        };
+        let description = result
+            .query_context
+            .context_description
+            .as_deref()
+            .unwrap_or("query");
+
+        for batch in batches {
+            let output = batch.schema();
+            if output.metadata() != schema.metadata() {
+                return Err(fuzzer_err(&format!(
+                    "Schema metadata lost in {}: expected {:?}, got {:?}",
+                    description,
+                    schema.metadata(),
+                    output.metadata()
+                )));
+            }
+            if output.fields().len() != sources.len() {
+                return Err(fuzzer_err(&format!(
+                    "{} returned {} columns, expected {}",
+                    description,
+                    output.fields().len(),
+                    sources.len()
+                )));
+            }
+            for (field, source) in output.fields().iter().zip(sources) {
+                let Some(source) = source else {
+                    continue;
+                };
+                let expected = schema.field(*source).metadata();
+                if field.metadata() != expected {
+                    return Err(fuzzer_err(&format!(
+                        "Field metadata lost in {}: column {} should have {:?} of column {}, got {:?}",
+                        description,
+                        field.name(),
+                        expected,
+                        schema.field(*source).name(),
+                        field.metadata()
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The queries over table `t`, whose columns are `id` and `c0`, `c1`, ...
+fn metadata_queries(data_columns: usize, row_count: usize) -> Vec<MetadataQuery> {
+    let all_columns: Vec<Option<usize>> = (0..=data_columns).map(Some).collect();
+    // `id` is column 0, `c0` column 1
+    let last = data_columns - 1;
+    vec![
+        MetadataQuery {
+            sql: "SELECT * FROM t".to_string(),
+            description: "SELECT *",
+            sources: all_columns.clone(),
+        },
+        MetadataQuery {
+            sql: format!("SELECT c{last} AS renamed, id, c0\nFROM t\nORDER BY id"),
+            description: "aliased projection",
+            sources: vec![Some(data_columns), Some(0), Some(1)],
+        },
+        MetadataQuery {
+            sql: "SELECT *\nFROM (SELECT * FROM t WHERE id >= 0) AS s\nORDER BY id DESC"
+                .to_string(),
+            description: "filtered subquery",
+            sources: all_columns.clone(),
+        },
+        MetadataQuery {
+            sql: format!("SELECT *\nFROM t\nORDER BY id\nLIMIT {}", row_count),
+            description: "limit",
+            sources: all_columns,
+        },
+        MetadataQuery {
+            sql: "SELECT id + 1, CAST(c0 AS VARCHAR), c0 IS NULL, id\nFROM t".to_string(),
+            description: "computed columns",
+            sources: vec![None, None, None, Some(0)],
+        },
+    ]
+}
+
+#[async_trait::async_trait]
+impl Oracle for MetadataOracle {
+    fn name(&self) -> &'static str {
+        "MetadataOracle"
+    }
+
+    fn generate_query_group(&mut self) -> Result<Vec<QueryContext>> {
+        let mut rng = rng_from_seed(self.seed);
+        let row_count = rng.random_range(1..=8);
+        let data_columns = rng.random_range(1..=MAX_DATA_COLUMNS);
+
+        let ids: Int64Array = (0..row_count as i64).map(Some).collect();
+        let mut fields =
+            vec![Field::new("id", DataType::Int64, false).with_metadata(random_metadata(&mut rng))];
+        let mut columns: Vec<ArrayRef> = vec![Arc::new(ids)];
+        for i in 0..data_columns {
+            let (data_type, values) = random_column(&mut rng, row_count);
+            fields.push(
+                Field::new(format!("c{}", i), data_type, false)
+                    .with_metadata(random_metadata(&mut rng)),
+            );
+            columns.push(values);
+        }
+        let schema = Arc::new(Schema::new_with_metadata(fields, random_metadata(&mut rng)));
+        let batch =
+            RecordBatch::try_new(Arc::clone(&schema), columns).map_err(DataFusionError::from)?;
+
+        // The table only lives in this session, which keeps the fuzzer's
+        // options
+        let session_context = Arc::new(SessionContext::new_with_config(
+            self.ctx
+                .runtime_context
+                .get_session_context()
+                .copied_config(),
+        ));
+        let table = MemTable::try_new(Arc::clone(&schema), vec![vec![batch]])?;
+        session_context.register_table("t", Arc::new(table))?;
+
+        let queries = metadata_queries(data_columns, row_count);
+        self.schema = Some(schema);
+        self.sources = queries.iter().map(|query| query.sources.clone()).collect();
+        Ok(queries
+            .into_iter()
+            .map(|query| {
+                QueryContext::with_description(
+                    query.sql,
+                    Arc::clone(&session_context),
+                    query.description.to_string(),
+                )
+            })
+            .collect())
+    }
+
+    async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
+        let schema = self
+            .schema
+            .as_ref()
+            .ok_or_else(|| fuzzer_err("Metadata oracle validated before generating"))?;
+        if results.len() != self.sources.len() {
+            return Err(fuzzer_err(&format!(
+                "Metadata oracle expects {} query results, got {}",
+                self.sources.len(),
+                results.len()
+            )));
+        }
+
+        for (result, sources) in results.iter().zip(&self.sources) {
+            self.check_result(schema, sources, result)?;
+        }
+        Ok(())
+    }
+
+    fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
+        let mut report = String::new();
+        report.push_str("Metadata Oracle Test Failed\n");
+        report.push_str("===========================\n\n");
+
+        oracle_common::append_labeled_query_results(
+            &mut report,
+            results,
+            &[
+                "select *",
+                "aliased projection",
+                "filtered subquery",
+                "limit",
+                "computed columns",
+            ],
+        );
+
+        if let Some(schema) = &self.schema {
+            report.push_str(&format!("Schema metadata: {:?}\n", schema.metadata()));
+            for field in schema.fields() {
+                report.push_str(&format!(
+                    "Field {} metadata: {:?}\n",
+                    field.name(),
+                    field.metadata()
+                ));
+            }
+        }
+        report.push_str("Expected: the metadata above on every result and referenced column\n");
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queries_reference_existing_columns() {
+        let ctx = Arc::new(crate::fuzz_context::GlobalContext::default());
+        for seed in 0..16 {
+            let mut oracle = MetadataOracle::new(seed, Arc::clone(&ctx));
+            let query_group = oracle.generate_query_group().unwrap();
+            let schema = oracle.schema.as_ref().unwrap();
+            assert_eq!(query_group.len(), oracle.sources.len());
+            for sources in &oracle.sources {
+                assert!(
+                    sources
+                        .iter()
+                        .flatten()
+                        .all(|&idx| idx < schema.fields().len())
+                );
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn datafusion_keeps_metadata() {
+        let ctx = Arc::new(crate::fuzz_context::GlobalContext::default());
+        for seed in 0..8 {
+            let mut oracle = MetadataOracle::new(seed, Arc::clone(&ctx));
+            let mut results = Vec::new();
+            for query_context in oracle.generate_query_group().unwrap() {
+                let result = query_context
+                    .context
+                    .sql(&query_context.query)
+                    .await
+                    .unwrap()
+                    .collect()
+                    .await
+                    .map_err(Into::into);
+                results.push(QueryExecutionResult {
+                    query_context: Arc::new(query_context),
+                    result,
+                    truncated: None,
+                });
+            }
+            oracle.validate_consistency(&results).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn detects_dropped_field_metadata() {
+        let mut oracle =
+            MetadataOracle::new(1, Arc::new(crate::fuzz_context::GlobalContext::default()));
+        oracle.generate_query_group().unwrap();
+        let schema = Arc::clone(oracle.schema.as_ref().unwrap());
+
+        let field = Field::new("id", DataType::Int64, false)
+            .with_metadata(HashMap::from([("other".to_string(), "v".to_string())]));
+        let output = Arc::new(Schema::new_with_metadata(
+            vec![field],
+            schema.metadata().clone(),
+        ));
+        let batch =
+            RecordBatch::try_new(output, vec![Arc::new(Int64Array::from(vec![0]))]).unwrap();
+        let result = QueryExecutionResult {
+            query_context: crate::oracle::test_helpers::make_query_context("id"),
+            result: Ok(vec![batch]),
+            truncated: None,
+        };
+
+        let err = oracle
+            .check_result(&schema, &[Some(0)], &result)
+            .unwrap_err();
+        assert!(err.to_string().contains("Field metadata lost"));
+    }
+}
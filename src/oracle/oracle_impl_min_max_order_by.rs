@@ -0,0 +1,230 @@
+use std::sync::Arc;
+
+use datafusion::arrow::array::RecordBatch;
+use datafusion::scalar::ScalarValue;
+use rand::Rng;
+
+use crate::common::rng::rng_from_seed;
+use crate::common::util::{count_total_rows, to_sql_string};
+use crate::common::{Result, fuzzer_err};
+use crate::oracle::ground_truth::{column_expr, is_orderable};
+use crate::oracle::result_compare::compare_batches;
+use crate::oracle::{ConfiguredOracle, Oracle, QueryContext, QueryExecutionResult, oracle_common};
+
+/// MIN/MAX vs ORDER BY oracle.
+///
+/// Picks a column of an orderable type and computes its smallest and
+/// largest values twice: with the `MIN` and `MAX` aggregates, and by sorting
+/// the non-NULL values and keeping the first one.
+///
+/// ### Example:
+///
+/// SELECT MIN(t1.c0) FROM t1;
+/// SELECT t1.c0 FROM t1 WHERE t1.c0 IS NOT NULL ORDER BY t1.c0 ASC LIMIT 1;
+/// SELECT MAX(t1.c0) FROM t1;
+/// SELECT t1.c0 FROM t1 WHERE t1.c0 IS NOT NULL ORDER BY t1.c0 DESC LIMIT 1;
+///
+/// Each aggregate must return the value of its sorted query, or NULL if the
+/// sorted query returns no rows. Floats, decimals and timestamps are the
+/// interesting cases: NaN, -0.0 and values of different precision must be
+/// ordered the same way by the aggregate and the sort.
+pub struct MinMaxOrderByOracle {
+    seed: u64,
+    ctx: Arc<crate::fuzz_context::GlobalContext>,
+}
+
+impl MinMaxOrderByOracle {
+    pub fn new(seed: u64, ctx: Arc<crate::fuzz_context::GlobalContext>) -> Self {
+        Self { seed, ctx }
+    }
+}
+
+#[async_trait::async_trait]
+impl Oracle for MinMaxOrderByOracle {
+    fn name(&self) -> &'static str {
+        "MinMaxOrderByOracle"
+    }
+
+    fn generate_query_group(&mut self) -> Result<Vec<QueryContext>> {
+        let columns: Vec<_> = self
+            .ctx
+            .runtime_context
+            .tables()
+            .values()
+            .flat_map(|table| {
+                table
+                    .columns
+                    .iter()
+                    .filter(|column| is_orderable(&column.data_type))
+                    .map(move |column| (column_expr(table, column), table.sql_name()))
+            })
+            .collect();
+        if columns.is_empty() {
+            return Err(fuzzer_err(
+                "MIN/MAX vs ORDER BY oracle requires a column of an orderable type",
+            ));
+        }
+
+        let mut rng = rng_from_seed(self.seed);
+        let (column, table_sql) = &columns[rng.random_range(0..columns.len())];
+        let column_sql = to_sql_string(column)?;
+
+        let mut query_group = Vec::with_capacity(4);
+        let session_context = self.ctx.runtime_context.get_session_context();
+        for (aggregate, direction) in [("MIN", "ASC"), ("MAX", "DESC")] {
+            query_group.push(QueryContext::with_description(
+                format!("SELECT {}({})\nFROM {}", aggregate, column_sql, table_sql),
+                Arc::clone(&session_context),
+                format!("{} aggregate", aggregate),
+            ));
+            query_group.push(QueryContext::with_description(
+                format!(
+                    "SELECT {}\nFROM {}\nWHERE {} IS NOT NULL\nORDER BY {} {}\nLIMIT 1",
+                    column_sql, table_sql, column_sql, column_sql, direction
+                ),
+                Arc::clone(&session_context),
+                format!("ORDER BY {} LIMIT 1", direction),
+            ));
+        }
+        Ok(query_group)
+    }
+
+    async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
+        if results.len() != 4 {
+            return Err(fuzzer_err(&format!(
+                "MIN/MAX vs ORDER BY oracle expects 4 query results, got {}",
+                results.len()
+            )));
+        }
+
+        let options = self
+            .ctx
+            .runner_config
+            .compare_options(ConfiguredOracle::MinMaxOrderBy)
+            .with_ordered(true);
+        for (aggregate_idx, aggregate, direction) in [(0, "MIN", "ASC"), (2, "MAX", "DESC")] {
+            let (Ok(aggregate_batches), Ok(sorted_batches)) = (
+                &results[aggregate_idx].result,
+                &results[aggregate_idx + 1].result,
+            ) else {
+                continue;
+            };
+
+            // Without non-NULL values there is no first sorted row
+            if count_total_rows(sorted_batches) == 0 {
+                let value = aggregate_value(aggregate_batches)?;
+                if !value.is_null() {
+                    return Err(fuzzer_err(&format!(
+                        "MIN/MAX vs ORDER BY violated: {} returned {}, but the column has no non-NULL values",
+                        aggregate, value
+                    )));
+                }
+                continue;
+            }
+
+            if let Some(diff) = compare_batches(aggregate_batches, sorted_batches, &options)? {
+                return Err(fuzzer_err(&format!(
+                    "MIN/MAX vs ORDER BY violated: {} differs from the first row of ORDER BY {} LIMIT 1:\n{}",
+                    aggregate, direction, diff
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
+        let mut report = String::new();
+        report.push_str("MIN/MAX vs ORDER BY Oracle Test Failed\n");
+        report.push_str("======================================\n\n");
+
+        oracle_common::append_labeled_query_results(
+            &mut report,
+            results,
+            &[
+                "MIN",
+                "ORDER BY ASC LIMIT 1",
+                "MAX",
+                "ORDER BY DESC LIMIT 1",
+            ],
+        );
+        report.push_str(
+            "Expected: MIN and MAX equal to the first non-NULL value sorted ascending and descending\n",
+        );
+
+        Ok(report)
+    }
+}
+
+/// The value of the single row of an aggregate result
+fn aggregate_value(batches: &[RecordBatch]) -> Result<ScalarValue> {
+    let batch = batches
+        .iter()
+        .find(|batch| batch.num_rows() > 0)
+        .ok_or_else(|| fuzzer_err("MIN/MAX aggregate returned no rows"))?;
+    ScalarValue::try_from_array(batch.column(0), 0).map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracle::test_helpers;
+    use crate::oracle::test_helpers::table_with_int_values;
+
+    #[test]
+    fn sorts_the_aggregated_column() {
+        let ctx = Arc::new(crate::fuzz_context::GlobalContext::default());
+        ctx.runtime_context
+            .register_table(Arc::new(table_with_int_values(&[Some(3), None, Some(-1)])));
+
+        let mut oracle = MinMaxOrderByOracle::new(1, ctx);
+        let query_group = oracle.generate_query_group().unwrap();
+        let queries: Vec<&str> = query_group
+            .iter()
+            .map(|query| query.query.as_str())
+            .collect();
+        assert_eq!(
+            queries,
+            vec![
+                "SELECT MIN(t1.c0)\nFROM t1",
+                "SELECT t1.c0\nFROM t1\nWHERE t1.c0 IS NOT NULL\nORDER BY t1.c0 ASC\nLIMIT 1",
+                "SELECT MAX(t1.c0)\nFROM t1",
+                "SELECT t1.c0\nFROM t1\nWHERE t1.c0 IS NOT NULL\nORDER BY t1.c0 DESC\nLIMIT 1",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn aggregates_must_match_the_first_sorted_row() {
+        let oracle =
+            MinMaxOrderByOracle::new(1, Arc::new(crate::fuzz_context::GlobalContext::default()));
+
+        let matching = vec![
+            test_helpers::make_nullable_result("min", "min", vec![Some(-1)]),
+            test_helpers::make_success_result("asc", "c0", vec![-1]),
+            test_helpers::make_nullable_result("max", "max", vec![Some(3)]),
+            test_helpers::make_error_result("desc"),
+        ];
+        assert!(oracle.validate_consistency(&matching).await.is_ok());
+
+        let no_values = vec![
+            test_helpers::make_nullable_result("min", "min", vec![None]),
+            test_helpers::make_success_result("asc", "c0", vec![]),
+            test_helpers::make_nullable_result("max", "max", vec![Some(3)]),
+            test_helpers::make_success_result("desc", "c0", vec![]),
+        ];
+        let err = oracle.validate_consistency(&no_values).await.unwrap_err();
+        assert!(err.to_string().contains("MAX returned 3"));
+
+        let wrong = vec![
+            test_helpers::make_nullable_result("min", "min", vec![Some(0)]),
+            test_helpers::make_success_result("asc", "c0", vec![-1]),
+            test_helpers::make_nullable_result("max", "max", vec![Some(3)]),
+            test_helpers::make_success_result("desc", "c0", vec![3]),
+        ];
+        let err = oracle.validate_consistency(&wrong).await.unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("MIN differs from the first row of ORDER BY ASC LIMIT 1")
+        );
+    }
+}
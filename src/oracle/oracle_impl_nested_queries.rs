@@ -1,5 +1,5 @@
 use crate::common::{InclusionConfig, Result};
-use crate::oracle::{Oracle, QueryContext, QueryExecutionResult};
+use crate::oracle::{Oracle, QueryContext, QueryExecutionResult, oracle_common};
 use crate::query_generator::stmt_select_def::SelectStatementBuilder;
 use std::sync::Arc;
 
@@ -34,9 +34,12 @@ impl Oracle for NestedQueriesOracle {
             InclusionConfig::Maybe(0.2),
         )
         // Enable derived tables (views/subqueries) for nested query testing
-        .with_allow_derived_tables(true)
-        // Avoid huge joins to slow down fuzzing
-        .with_max_table_count(3);
+        .with_allow_derived_tables(true);
+        // Avoid huge joins to slow down fuzzing, `join_row_budget` bounds them
+        // more precisely
+        if self.ctx.runner_config.join_row_budget.is_none() {
+            stmt_builder = stmt_builder.with_max_table_count(3);
+        }
         let stmt = stmt_builder.generate_stmt()?;
         let sql = stmt.to_sql_string()?;
 
@@ -72,24 +75,17 @@ impl Oracle for NestedQueriesOracle {
         report.push_str("Nested Queries Oracle Test Failed\n");
         report.push_str("==================================\n\n");
 
-        if !results.is_empty() {
-            let query_result = &results[0];
-            let query_context = &*query_result.query_context;
-
-            report.push_str(&format!(
-                "Nested query that caused inconsistency or error:\n{}\n\n",
-                query_context.query
-            ));
-            report.push_str(&format!(
-                "Context: {}\n\n",
-                query_context.display_description()
-            ));
-
-            // Include the specific error if available
-            if let Err(e) = &query_result.result {
-                report.push_str(&format!("Error details: {}\n\n", e));
-            }
-        }
+        let labels: Vec<&str> = results
+            .iter()
+            .map(|result| {
+                result
+                    .query_context
+                    .context_description
+                    .as_deref()
+                    .unwrap_or("nested query")
+            })
+            .collect();
+        oracle_common::append_labeled_query_results(&mut report, results, &labels);
 
         report.push_str(
             "Expected: Nested queries with views/subqueries should execute consistently\n",
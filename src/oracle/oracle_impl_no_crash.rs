@@ -21,8 +21,6 @@ impl NoCrashOracle {
     }
 }
 
-// TODO: QPS drops significantly when using this oracle interface, instead of letting
-// random queries be executed directly. Need to investigate why.
 #[async_trait::async_trait]
 impl Oracle for NoCrashOracle {
     fn name(&self) -> &'static str {
@@ -38,7 +36,9 @@ impl Oracle for NoCrashOracle {
             InclusionConfig::Maybe(0.5),
         )
         // Views/subqueries are tested by other oracles
-        .with_allow_derived_tables(false);
+        .with_allow_derived_tables(false)
+        // Grouped queries are rare, `coverage_targets` can make them common
+        .with_enable_group_by_clause(InclusionConfig::Maybe(0.1));
         let stmt = stmt_builder.generate_stmt()?;
         let sql = stmt.to_sql_string()?;
 
@@ -0,0 +1,405 @@
+use std::sync::Arc;
+
+use datafusion::arrow::array::RecordBatch;
+use datafusion::scalar::ScalarValue;
+use rand::Rng;
+
+use crate::common::rng::rng_from_seed;
+use crate::common::{Result, fuzzer_err};
+use crate::oracle::{Oracle, QueryContext, QueryExecutionResult, oracle_common};
+
+/// A scalar function and a non-NULL value of each of its arguments
+struct ScalarFunction {
+    name: &'static str,
+    /// `(value, SQL type)` of each argument
+    args: &'static [(&'static str, &'static str)],
+    /// Arguments that may be NULL without making the result NULL, the
+    /// documented exception of null-aware functions. The result must then be
+    /// non-NULL, as the other arguments aren't.
+    null_aware_args: &'static [usize],
+}
+
+const SCALAR_FUNCTIONS: &[ScalarFunction] = &[
+    ScalarFunction {
+        name: "abs",
+        args: &[("-7", "BIGINT")],
+        null_aware_args: &[],
+    },
+    ScalarFunction {
+        name: "sqrt",
+        args: &[("4.0", "DOUBLE")],
+        null_aware_args: &[],
+    },
+    ScalarFunction {
+        name: "power",
+        args: &[("2.0", "DOUBLE"), ("3.0", "DOUBLE")],
+        null_aware_args: &[],
+    },
+    ScalarFunction {
+        name: "log",
+        args: &[("2.0", "DOUBLE"), ("8.0", "DOUBLE")],
+        null_aware_args: &[],
+    },
+    ScalarFunction {
+        name: "round",
+        args: &[("2.567", "DOUBLE"), ("1", "BIGINT")],
+        null_aware_args: &[],
+    },
+    ScalarFunction {
+        name: "gcd",
+        args: &[("12", "BIGINT"), ("18", "BIGINT")],
+        null_aware_args: &[],
+    },
+    ScalarFunction {
+        name: "to_hex",
+        args: &[("255", "BIGINT")],
+        null_aware_args: &[],
+    },
+    ScalarFunction {
+        name: "upper",
+        args: &[("'abc'", "VARCHAR")],
+        null_aware_args: &[],
+    },
+    ScalarFunction {
+        name: "character_length",
+        args: &[("'abc'", "VARCHAR")],
+        null_aware_args: &[],
+    },
+    ScalarFunction {
+        name: "reverse",
+        args: &[("'abc'", "VARCHAR")],
+        null_aware_args: &[],
+    },
+    ScalarFunction {
+        name: "md5",
+        args: &[("'abc'", "VARCHAR")],
+        null_aware_args: &[],
+    },
+    ScalarFunction {
+        name: "substr",
+        args: &[
+            ("'datafusion'", "VARCHAR"),
+            ("2", "BIGINT"),
+            ("3", "BIGINT"),
+        ],
+        null_aware_args: &[],
+    },
+    ScalarFunction {
+        name: "replace",
+        args: &[("'abc'", "VARCHAR"), ("'b'", "VARCHAR"), ("'x'", "VARCHAR")],
+        null_aware_args: &[],
+    },
+    ScalarFunction {
+        name: "translate",
+        args: &[("'abc'", "VARCHAR"), ("'b'", "VARCHAR"), ("'x'", "VARCHAR")],
+        null_aware_args: &[],
+    },
+    ScalarFunction {
+        name: "regexp_replace",
+        args: &[
+            ("'abc'", "VARCHAR"),
+            ("'b+'", "VARCHAR"),
+            ("'x'", "VARCHAR"),
+        ],
+        null_aware_args: &[],
+    },
+    ScalarFunction {
+        name: "lpad",
+        args: &[("'ab'", "VARCHAR"), ("5", "BIGINT"), ("'x'", "VARCHAR")],
+        null_aware_args: &[],
+    },
+    ScalarFunction {
+        name: "repeat",
+        args: &[("'ab'", "VARCHAR"), ("3", "BIGINT")],
+        null_aware_args: &[],
+    },
+    ScalarFunction {
+        name: "split_part",
+        args: &[("'a,b,c'", "VARCHAR"), ("','", "VARCHAR"), ("2", "BIGINT")],
+        null_aware_args: &[],
+    },
+    ScalarFunction {
+        name: "strpos",
+        args: &[("'abc'", "VARCHAR"), ("'b'", "VARCHAR")],
+        null_aware_args: &[],
+    },
+    ScalarFunction {
+        name: "starts_with",
+        args: &[("'abc'", "VARCHAR"), ("'a'", "VARCHAR")],
+        null_aware_args: &[],
+    },
+    // Null-aware functions
+    ScalarFunction {
+        name: "coalesce",
+        args: &[("1", "BIGINT"), ("2", "BIGINT")],
+        null_aware_args: &[0, 1],
+    },
+    ScalarFunction {
+        name: "nvl",
+        args: &[("1", "BIGINT"), ("2", "BIGINT")],
+        null_aware_args: &[0, 1],
+    },
+    // `nullif(a, b)` is `a` unless `a = b`
+    ScalarFunction {
+        name: "nullif",
+        args: &[("1", "BIGINT"), ("2", "BIGINT")],
+        null_aware_args: &[1],
+    },
+    // `nvl2(a, b, c)` is `b` if `a` is not NULL, `c` otherwise
+    ScalarFunction {
+        name: "nvl2",
+        args: &[("1", "BIGINT"), ("2", "BIGINT"), ("3", "BIGINT")],
+        null_aware_args: &[0, 2],
+    },
+    ScalarFunction {
+        name: "greatest",
+        args: &[("1", "BIGINT"), ("2", "BIGINT")],
+        null_aware_args: &[0, 1],
+    },
+    ScalarFunction {
+        name: "least",
+        args: &[("1", "BIGINT"), ("2", "BIGINT")],
+        null_aware_args: &[0, 1],
+    },
+    ScalarFunction {
+        name: "concat",
+        args: &[("'a'", "VARCHAR"), ("'b'", "VARCHAR"), ("'c'", "VARCHAR")],
+        null_aware_args: &[0, 1, 2],
+    },
+    // NULL values are skipped, but a NULL separator makes the result NULL
+    ScalarFunction {
+        name: "concat_ws",
+        args: &[("','", "VARCHAR"), ("'a'", "VARCHAR"), ("'b'", "VARCHAR")],
+        null_aware_args: &[1, 2],
+    },
+];
+
+/// Null propagation oracle.
+///
+/// Picks a scalar function from `SCALAR_FUNCTIONS` and calls it once per
+/// argument, with NULL in that argument and non-NULL values in the others,
+/// either on constants, which the optimizer folds, or on the columns of a
+/// one-row inline table, which the function's kernel evaluates.
+///
+/// ### Example:
+///
+/// SELECT substr(CAST(NULL AS VARCHAR), CAST(2 AS BIGINT), CAST(3 AS BIGINT));
+/// SELECT substr(CAST('datafusion' AS VARCHAR), CAST(NULL AS BIGINT), CAST(3 AS BIGINT));
+/// SELECT substr(CAST('datafusion' AS VARCHAR), CAST(2 AS BIGINT), CAST(NULL AS BIGINT));
+///
+/// Every call must return NULL, except for the null-aware arguments of a
+/// function (e.g. any argument of `coalesce`, the values of `concat_ws`),
+/// where it must return a non-NULL value. Panics and errors are checked by
+/// the runner like for any other query.
+pub struct NullPropagationOracle {
+    seed: u64,
+    ctx: Arc<crate::fuzz_context::GlobalContext>,
+    /// Index of the function in `SCALAR_FUNCTIONS`
+    function: usize,
+}
+
+impl NullPropagationOracle {
+    pub fn new(seed: u64, ctx: Arc<crate::fuzz_context::GlobalContext>) -> Self {
+        Self {
+            seed,
+            ctx,
+            function: 0,
+        }
+    }
+
+    fn function(&self) -> &'static ScalarFunction {
+        &SCALAR_FUNCTIONS[self.function]
+    }
+}
+
+#[async_trait::async_trait]
+impl Oracle for NullPropagationOracle {
+    fn name(&self) -> &'static str {
+        "NullPropagationOracle"
+    }
+
+    fn generate_query_group(&mut self) -> Result<Vec<QueryContext>> {
+        let mut rng = rng_from_seed(self.seed);
+        self.function = rng.random_range(0..SCALAR_FUNCTIONS.len());
+        let function = self.function();
+        let columnar = rng.random_bool(0.5);
+
+        let session_context = self.ctx.runtime_context.get_session_context();
+        Ok((0..function.args.len())
+            .map(|null_arg| {
+                let args: Vec<String> = function
+                    .args
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (value, sql_type))| {
+                        let value = if i == null_arg { "NULL" } else { value };
+                        format!("CAST({} AS {})", value, sql_type)
+                    })
+                    .collect();
+                let query = if columnar {
+                    let columns: Vec<String> = (0..args.len()).map(|i| format!("c{}", i)).collect();
+                    format!(
+                        "SELECT {}({})\nFROM (VALUES ({})) AS t({})",
+                        function.name,
+                        columns.join(", "),
+                        args.join(", "),
+                        columns.join(", ")
+                    )
+                } else {
+                    format!("SELECT {}({})", function.name, args.join(", "))
+                };
+                QueryContext::with_description(
+                    query,
+                    Arc::clone(&session_context),
+                    format!("{} with NULL as argument {}", function.name, null_arg + 1),
+                )
+            })
+            .collect())
+    }
+
+    async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
+        let function = self.function();
+        if results.len() != function.args.len() {
+            return Err(fuzzer_err(&format!(
+                "Null propagation oracle expects {} query results, got {}",
+                function.args.len(),
+                results.len()
+            )));
+        }
+
+        for (null_arg, result) in results.iter().enumerate() {
+            let Ok(batches) = &result.result else {
+                continue;
+            };
+            let value = single_value(batches)?;
+            let null_aware = function.null_aware_args.contains(&null_arg);
+            if value.is_null() == null_aware {
+                let expected = if null_aware {
+                    "a non-NULL value, the argument is null-aware"
+                } else {
+                    "NULL"
+                };
+                return Err(fuzzer_err(&format!(
+                    "Null propagation violated: {} returned {} with NULL as argument {}, expected {}",
+                    function.name,
+                    value,
+                    null_arg + 1,
+                    expected
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
+        let mut report = String::new();
+        report.push_str("Null Propagation Oracle Test Failed\n");
+        report.push_str("===================================\n\n");
+
+        let labels: Vec<String> = (1..=results.len())
+            .map(|arg| format!("NULL argument {}", arg))
+            .collect();
+        let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+        oracle_common::append_labeled_query_results(&mut report, results, &labels);
+
+        let function = self.function();
+        report.push_str(&format!(
+            "Expected: NULL from {} unless the NULL argument is null-aware, null-aware arguments: {:?}\n",
+            function.name,
+            function
+                .null_aware_args
+                .iter()
+                .map(|arg| arg + 1)
+                .collect::<Vec<_>>()
+        ));
+
+        Ok(report)
+    }
+}
+
+/// The value of a one-row, one-column result
+fn single_value(batches: &[RecordBatch]) -> Result<ScalarValue> {
+    let mut rows = batches
+        .iter()
+        .flat_map(|batch| (0..batch.num_rows()).map(move |row| (batch, row)));
+    match (rows.next(), rows.next()) {
+        (Some((batch, row)), None) => {
+            ScalarValue::try_from_array(batch.column(0), row).map_err(Into::into)
+        }
+        _ => Err(fuzzer_err(
+            "Null propagation query must return exactly one row",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracle::test_helpers;
+
+    fn int_result(label: &str, value: Option<i64>) -> QueryExecutionResult {
+        test_helpers::make_nullable_result(label, "v", vec![value])
+    }
+
+    #[test]
+    fn puts_null_in_every_argument() {
+        for function in SCALAR_FUNCTIONS {
+            assert!(
+                function
+                    .null_aware_args
+                    .iter()
+                    .all(|arg| *arg < function.args.len()),
+                "{}",
+                function.name
+            );
+        }
+
+        let ctx = Arc::new(crate::fuzz_context::GlobalContext::default());
+        for seed in 0..16 {
+            let mut oracle = NullPropagationOracle::new(seed, Arc::clone(&ctx));
+            let query_group = oracle.generate_query_group().unwrap();
+            assert_eq!(query_group.len(), oracle.function().args.len());
+            for (null_arg, query) in query_group.iter().enumerate() {
+                assert_eq!(query.query.matches("CAST(NULL AS").count(), 1);
+                let args_start = query.query.find('(').unwrap();
+                let null_position = query.query.find("CAST(NULL AS").unwrap();
+                assert_eq!(
+                    query.query[args_start..null_position]
+                        .matches("CAST(")
+                        .count(),
+                    null_arg
+                );
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn only_null_aware_arguments_give_non_null_results() {
+        let mut oracle =
+            NullPropagationOracle::new(1, Arc::new(crate::fuzz_context::GlobalContext::default()));
+        oracle.function = SCALAR_FUNCTIONS
+            .iter()
+            .position(|function| function.name == "nullif")
+            .unwrap();
+
+        let expected = vec![int_result("a", None), int_result("b", Some(1))];
+        assert!(oracle.validate_consistency(&expected).await.is_ok());
+
+        let errors = vec![
+            test_helpers::make_error_result("a"),
+            test_helpers::make_error_result("b"),
+        ];
+        assert!(oracle.validate_consistency(&errors).await.is_ok());
+
+        let non_null = vec![int_result("a", Some(2)), int_result("b", Some(1))];
+        let err = oracle.validate_consistency(&non_null).await.unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("with NULL as argument 1, expected NULL")
+        );
+
+        let null = vec![int_result("a", None), int_result("b", None)];
+        let err = oracle.validate_consistency(&null).await.unwrap_err();
+        assert!(err.to_string().contains("the argument is null-aware"));
+    }
+}
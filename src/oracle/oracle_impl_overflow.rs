@@ -0,0 +1,352 @@
+use std::sync::Arc;
+
+use datafusion::arrow::array::RecordBatch;
+use datafusion::scalar::ScalarValue;
+use rand::Rng;
+use rand::rngs::StdRng;
+
+use crate::common::rng::rng_from_seed;
+use crate::common::{Result, fuzzer_err};
+use crate::oracle::{Oracle, QueryContext, QueryExecutionResult, oracle_common};
+
+/// Scale of the `DECIMAL(38, s)` operands, precision 38 is the widest
+/// Decimal128 so results can't be widened out of the overflow
+const DECIMAL_SCALES: [u8; 3] = [0, 2, 18];
+
+/// Operand types whose arithmetic can overflow
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OverflowType {
+    Int32,
+    Int64,
+    UInt32,
+    UInt64,
+    Decimal { scale: u8 },
+}
+
+impl OverflowType {
+    fn random(rng: &mut StdRng) -> Self {
+        match rng.random_range(0..5) {
+            0 => Self::Int32,
+            1 => Self::Int64,
+            2 => Self::UInt32,
+            3 => Self::UInt64,
+            _ => Self::Decimal {
+                scale: DECIMAL_SCALES[rng.random_range(0..DECIMAL_SCALES.len())],
+            },
+        }
+    }
+
+    fn sql_type(self) -> String {
+        match self {
+            Self::Int32 => "INT".to_string(),
+            Self::Int64 => "BIGINT".to_string(),
+            Self::UInt32 => "INT UNSIGNED".to_string(),
+            Self::UInt64 => "BIGINT UNSIGNED".to_string(),
+            Self::Decimal { scale } => format!("DECIMAL(38, {})", scale),
+        }
+    }
+
+    /// Range of integer types, `None` for decimals
+    fn integer_range(self) -> Option<(i128, i128)> {
+        match self {
+            Self::Int32 => Some((i32::MIN as i128, i32::MAX as i128)),
+            Self::Int64 => Some((i64::MIN as i128, i64::MAX as i128)),
+            Self::UInt32 => Some((0, u32::MAX as i128)),
+            Self::UInt64 => Some((0, u64::MAX as i128)),
+            Self::Decimal { .. } => None,
+        }
+    }
+
+    /// Values at and around the edges of the type, and small values to
+    /// combine them with
+    fn boundary_values(self) -> Vec<String> {
+        if let Some((min, max)) = self.integer_range() {
+            // Squares of `sqrt_max` overflow multiplication
+            let sqrt_max = (max as f64).sqrt() as i128 + 1;
+            let mut values: Vec<i128> = vec![0, 1, 2, max - 1, max, sqrt_max];
+            if min < 0 {
+                values.extend([-1, -2, min, min + 1, -sqrt_max]);
+            }
+            return values.into_iter().map(|v| v.to_string()).collect();
+        }
+
+        let Self::Decimal { scale } = self else {
+            unreachable!("integer types return above")
+        };
+        let scale = scale as usize;
+        let integer_digits = "9".repeat(38 - scale);
+        let max = if scale == 0 {
+            integer_digits
+        } else {
+            format!("{}.{}", integer_digits, "9".repeat(scale))
+        };
+        let mut values = vec![
+            "0".to_string(),
+            "1".to_string(),
+            "-1".to_string(),
+            "2".to_string(),
+            format!("-{}", max),
+            max,
+        ];
+        if scale > 0 {
+            values.push(format!("0.{}1", "0".repeat(scale - 1)));
+        }
+        values
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OverflowOp {
+    Add,
+    Sub,
+    Mul,
+}
+
+impl OverflowOp {
+    fn symbol(self) -> &'static str {
+        match self {
+            Self::Add => "+",
+            Self::Sub => "-",
+            Self::Mul => "*",
+        }
+    }
+
+    fn apply(self, left: i128, right: i128) -> Option<i128> {
+        match self {
+            Self::Add => left.checked_add(right),
+            Self::Sub => left.checked_sub(right),
+            Self::Mul => left.checked_mul(right),
+        }
+    }
+}
+
+/// Overflow consistency oracle.
+///
+/// Combines values at the edges of `INT`, `BIGINT`, their unsigned variants
+/// and `DECIMAL(38, s)` with `+`, `-` or `*`, and evaluates the operation
+/// twice: on constants, which the optimizer folds, and on columns of an
+/// inline table, which the arrow kernels evaluate.
+///
+/// ### Example:
+///
+/// SELECT CAST('2147483647' AS INT) + CAST('1' AS INT);
+///
+/// SELECT c0 + c1
+/// FROM (VALUES (CAST('2147483647' AS INT), CAST('1' AS INT), 0), (CAST('0' AS INT), CAST('0' AS INT), 1)) AS t(c0, c1, id)
+/// ORDER BY id;
+///
+/// Both must fail, or both must return the same value. For integers whose
+/// exact result fits the type, that value must also be the exact result.
+/// The second row keeps the columnar query from being a single constant row.
+pub struct OverflowOracle {
+    seed: u64,
+    ctx: Arc<crate::fuzz_context::GlobalContext>,
+    /// Exact result, `None` if it doesn't fit the type or the type is a decimal
+    expected: Option<i128>,
+}
+
+impl OverflowOracle {
+    pub fn new(seed: u64, ctx: Arc<crate::fuzz_context::GlobalContext>) -> Self {
+        Self {
+            seed,
+            ctx,
+            expected: None,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Oracle for OverflowOracle {
+    fn name(&self) -> &'static str {
+        "OverflowOracle"
+    }
+
+    fn generate_query_group(&mut self) -> Result<Vec<QueryContext>> {
+        let mut rng = rng_from_seed(self.seed);
+        let value_type = OverflowType::random(&mut rng);
+        let op = [OverflowOp::Add, OverflowOp::Sub, OverflowOp::Mul][rng.random_range(0..3)];
+        let values = value_type.boundary_values();
+        let left = values[rng.random_range(0..values.len())].clone();
+        let right = values[rng.random_range(0..values.len())].clone();
+
+        self.expected = value_type.integer_range().and_then(|(min, max)| {
+            let exact = op.apply(left.parse().ok()?, right.parse().ok()?)?;
+            (min..=max).contains(&exact).then_some(exact)
+        });
+
+        let sql_type = value_type.sql_type();
+        let cast = |value: &str| format!("CAST('{}' AS {})", value, sql_type);
+        let scalar_query = format!("SELECT {} {} {}", cast(&left), op.symbol(), cast(&right));
+        let columnar_query = format!(
+            "SELECT c0 {} c1\nFROM (VALUES ({}, {}, 0), ({}, {}, 1)) AS t(c0, c1, id)\nORDER BY id",
+            op.symbol(),
+            cast(&left),
+            cast(&right),
+            cast("0"),
+            cast("0")
+        );
+
+        let session_context = self.ctx.runtime_context.get_session_context();
+        let description = format!("{} {} {} as {}", left, op.symbol(), right, sql_type);
+        Ok(vec![
+            QueryContext::with_description(
+                scalar_query,
+                Arc::clone(&session_context),
+                format!("Constant folded {}", description),
+            ),
+            QueryContext::with_description(
+                columnar_query,
+                session_context,
+                format!("Columnar {}", description),
+            ),
+        ])
+    }
+
+    async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
+        let [scalar, columnar] = results else {
+            return Err(fuzzer_err(&format!(
+                "Overflow oracle expects 2 query results, got {}",
+                results.len()
+            )));
+        };
+
+        let (scalar_value, columnar_value) = match (&scalar.result, &columnar.result) {
+            (Ok(scalar_batches), Ok(columnar_batches)) => {
+                (first_value(scalar_batches)?, first_value(columnar_batches)?)
+            }
+            // Failing on overflow is fine as long as both paths fail
+            (Err(_), Err(_)) => return Ok(()),
+            (Ok(batches), Err(e)) | (Err(e), Ok(batches)) => {
+                let (failed, succeeded) = if scalar.result.is_err() {
+                    ("constant folded", "columnar")
+                } else {
+                    ("columnar", "constant folded")
+                };
+                return Err(fuzzer_err(&format!(
+                    "Overflow behavior inconsistent: the {} query failed with '{}', the {} query returned {}",
+                    failed,
+                    e,
+                    succeeded,
+                    first_value(batches)?
+                )));
+            }
+        };
+
+        if scalar_value != columnar_value {
+            return Err(fuzzer_err(&format!(
+                "Overflow behavior inconsistent: the constant folded query returned {}, the columnar query {}",
+                scalar_value, columnar_value
+            )));
+        }
+        if let Some(expected) = self.expected
+            && scalar_value.to_string() != expected.to_string()
+        {
+            return Err(fuzzer_err(&format!(
+                "Arithmetic result wrong: both queries returned {}, the exact result is {}",
+                scalar_value, expected
+            )));
+        }
+        Ok(())
+    }
+
+    fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
+        let mut report = String::new();
+        report.push_str("Overflow Oracle Test Failed\n");
+        report.push_str("===========================\n\n");
+
+        oracle_common::append_labeled_query_results(
+            &mut report,
+            results,
+            &["constant folded", "columnar"],
+        );
+
+        match self.expected {
+            Some(expected) => {
+                report.push_str(&format!("Expected: {}, the exact result\n", expected));
+            }
+            None => report.push_str("Expected: the same overflow behavior from both queries\n"),
+        }
+
+        Ok(report)
+    }
+}
+
+/// Value of the first row of a result
+fn first_value(batches: &[RecordBatch]) -> Result<ScalarValue> {
+    let batch = batches
+        .iter()
+        .find(|batch| batch.num_rows() > 0)
+        .ok_or_else(|| fuzzer_err("Overflow query returned no rows"))?;
+    ScalarValue::try_from_array(batch.column(0), 0).map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracle::test_helpers;
+
+    #[test]
+    fn boundary_values_stay_in_range() {
+        for value_type in [
+            OverflowType::Int32,
+            OverflowType::Int64,
+            OverflowType::UInt32,
+            OverflowType::UInt64,
+        ] {
+            let (min, max) = value_type.integer_range().unwrap();
+            for value in value_type.boundary_values() {
+                let value: i128 = value.parse().unwrap();
+                assert!((min..=max).contains(&value), "{value} as {value_type:?}");
+            }
+        }
+
+        let decimal = OverflowType::Decimal { scale: 2 }.boundary_values();
+        assert!(decimal.contains(&format!("{}.99", "9".repeat(36))));
+        assert!(decimal.contains(&"0.01".to_string()));
+    }
+
+    #[test]
+    fn generates_scalar_and_columnar_queries() {
+        let ctx = Arc::new(crate::fuzz_context::GlobalContext::default());
+        for seed in 0..16 {
+            let mut oracle = OverflowOracle::new(seed, Arc::clone(&ctx));
+            let query_group = oracle.generate_query_group().unwrap();
+            assert_eq!(query_group.len(), 2);
+            assert!(query_group[0].query.starts_with("SELECT CAST('"));
+            assert!(
+                query_group[1]
+                    .query
+                    .ends_with("AS t(c0, c1, id)\nORDER BY id")
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn both_paths_must_agree() {
+        let mut oracle =
+            OverflowOracle::new(1, Arc::new(crate::fuzz_context::GlobalContext::default()));
+
+        let same = vec![
+            test_helpers::make_success_result("scalar", "v", vec![-2147483648]),
+            test_helpers::make_success_result("columnar", "v", vec![-2147483648, 0]),
+        ];
+        assert!(oracle.validate_consistency(&same).await.is_ok());
+
+        let both_failed = vec![
+            test_helpers::make_error_result("scalar"),
+            test_helpers::make_error_result("columnar"),
+        ];
+        assert!(oracle.validate_consistency(&both_failed).await.is_ok());
+
+        let one_wrapped = vec![
+            test_helpers::make_error_result("scalar"),
+            test_helpers::make_success_result("columnar", "v", vec![-2147483648, 0]),
+        ];
+        let err = oracle.validate_consistency(&one_wrapped).await.unwrap_err();
+        assert!(err.to_string().contains("the constant folded query failed"));
+
+        oracle.expected = Some(3);
+        let err = oracle.validate_consistency(&same).await.unwrap_err();
+        assert!(err.to_string().contains("the exact result is 3"));
+    }
+}
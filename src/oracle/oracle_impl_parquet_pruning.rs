@@ -0,0 +1,227 @@
+use std::sync::Arc;
+
+use rand::Rng;
+
+use crate::common::rng::rng_from_seed;
+use crate::common::util::{quote_identifier, to_sql_string};
+use crate::common::{LogicalTable, Result, fuzzer_err};
+use crate::oracle::ground_truth::{generate_predicate, parquet_backed_tables};
+use crate::oracle::{ConfiguredOracle, Oracle, QueryContext, QueryExecutionResult, oracle_common};
+
+/// Parquet pruning oracle.
+///
+/// Filters a Parquet-backed table (see `dataset_format`) twice: once with
+/// row group pruning, page index pruning and bloom filters enabled, and once
+/// with all of them disabled. Pruning may only skip row groups and pages
+/// that can't match, so both scans must return the same rows. With
+/// `parquet_pruning_stress` the files are sorted by a column and split into
+/// row groups and pages of a few rows, and one predicate only references
+/// that column, so pruning actually skips data.
+///
+/// ### Example:
+///
+/// SELECT * FROM t1 WHERE c > 5;
+///
+/// must return the same rows with and without pruning.
+pub struct ParquetPruningOracle {
+    seed: u64,
+    ctx: Arc<crate::fuzz_context::GlobalContext>,
+    table: Option<Arc<LogicalTable>>,
+}
+
+impl ParquetPruningOracle {
+    pub fn new(seed: u64, ctx: Arc<crate::fuzz_context::GlobalContext>) -> Self {
+        Self {
+            seed,
+            ctx,
+            table: None,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Oracle for ParquetPruningOracle {
+    fn name(&self) -> &'static str {
+        "ParquetPruningOracle"
+    }
+
+    fn generate_query_group(&mut self) -> Result<Vec<QueryContext>> {
+        let tables = parquet_backed_tables(&self.ctx);
+        if tables.is_empty() {
+            return Err(fuzzer_err(
+                "ParquetPruning oracle requires a Parquet-backed table, see dataset_format",
+            ));
+        }
+
+        let mut rng = rng_from_seed(self.seed);
+        let table = Arc::clone(&tables[rng.random_range(0..tables.len())]);
+        let files = table
+            .files
+            .as_ref()
+            .ok_or_else(|| fuzzer_err("ParquetPruning oracle picked an in-memory table"))?;
+
+        // Predicates on the sort column select contiguous row groups and
+        // pages, which is where pruning skips the most
+        let sort_column = match files.layout.sort_column {
+            Some(idx) => idx,
+            None => rng.random_range(0..table.columns.len()),
+        };
+        let clustered = LogicalTable::with_columns(
+            table.name.clone(),
+            vec![table.columns[sort_column].clone()],
+        );
+        let predicates = [
+            to_sql_string(&generate_predicate(&mut rng, &clustered))?,
+            to_sql_string(&generate_predicate(&mut rng, &table))?,
+        ];
+
+        let base_config = self
+            .ctx
+            .runtime_context
+            .get_session_context()
+            .copied_config();
+        let mut sessions = Vec::with_capacity(2);
+        for pruning in [true, false] {
+            let mut config = base_config.clone();
+            let parquet = &mut config.options_mut().execution.parquet;
+            parquet.pruning = pruning;
+            parquet.enable_page_index = pruning;
+            parquet.bloom_filter_on_read = pruning;
+            sessions.push(files.session(config, &table.name, true)?);
+        }
+
+        let mut query_group = Vec::with_capacity(predicates.len() * sessions.len());
+        for predicate in &predicates {
+            let query = format!(
+                "SELECT *\nFROM {}\nWHERE {}",
+                quote_identifier(&table.name),
+                predicate
+            );
+            for (session_context, description) in
+                sessions.iter().zip(["with pruning", "without pruning"])
+            {
+                query_group.push(QueryContext::with_description(
+                    query.clone(),
+                    Arc::clone(session_context),
+                    format!("{}, {:?}", description, files.layout),
+                ));
+            }
+        }
+
+        self.table = Some(table);
+        Ok(query_group)
+    }
+
+    async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
+        if results.len() != 4 {
+            return Err(fuzzer_err(&format!(
+                "ParquetPruning oracle expects 4 query results, got {}",
+                results.len()
+            )));
+        }
+
+        let options = self
+            .ctx
+            .runner_config
+            .compare_options(ConfiguredOracle::ParquetPruning);
+        for with_pruning in [0, 2] {
+            let without_pruning = with_pruning + 1;
+            if results[with_pruning].result.is_err() || results[without_pruning].result.is_err() {
+                continue;
+            }
+            oracle_common::validate_value_equivalence(
+                results,
+                with_pruning,
+                without_pruning,
+                "ParquetPruning",
+                &options,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
+        let mut report = String::new();
+        report.push_str("ParquetPruning Oracle Test Failed\n");
+        report.push_str("=================================\n\n");
+
+        oracle_common::append_labeled_query_results(
+            &mut report,
+            results,
+            &[
+                "sort column predicate, with pruning",
+                "sort column predicate, without pruning",
+                "predicate, with pruning",
+                "predicate, without pruning",
+            ],
+        );
+
+        if let Some(files) = self.table.as_ref().and_then(|table| table.files.as_ref()) {
+            report.push_str(&format!(
+                "Table files: {} ({:?} writer statistics, {:?})\n",
+                files.dir.display(),
+                files.statistics,
+                files.layout
+            ));
+        }
+        report.push_str("Expected: the same rows with and without pruning\n");
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::init_available_data_types;
+    use crate::datasource_generator::dataset_generator::DatasetGenerator;
+    use crate::datasource_generator::file_dataset::DatasetFormat;
+    use crate::fuzz_context::RunnerConfig;
+    use crate::oracle::test_helpers::context_with_config;
+
+    #[tokio::test]
+    async fn compares_stressed_parquet_tables_with_and_without_pruning() {
+        init_available_data_types();
+        let ctx = context_with_config(RunnerConfig {
+            dataset_format: DatasetFormat::Parquet,
+            parquet_pruning_stress: true,
+            ..Default::default()
+        });
+
+        for seed in 0..4 {
+            DatasetGenerator::new(seed, Arc::clone(&ctx))
+                .generate_dataset()
+                .await
+                .unwrap();
+        }
+        let tables = parquet_backed_tables(&ctx);
+        for table in &tables {
+            let layout = table.files.as_ref().unwrap().layout;
+            assert!(layout.sort_column.is_some());
+            assert!(layout.max_page_rows.is_some());
+        }
+        if tables.is_empty() {
+            // Every table had an interval column
+            return;
+        }
+
+        let mut oracle = ParquetPruningOracle::new(1, Arc::clone(&ctx));
+        let query_group = oracle.generate_query_group().unwrap();
+        assert_eq!(query_group.len(), 4);
+        assert_eq!(query_group[0].query, query_group[1].query);
+        assert_eq!(query_group[2].query, query_group[3].query);
+
+        let mut results = Vec::new();
+        for query_context in query_group {
+            let result = match query_context.context.sql(&query_context.query).await {
+                Ok(df) => df.collect().await.map_err(Into::into),
+                Err(e) => Err(e.into()),
+            };
+            results.push(QueryExecutionResult {
+                query_context: Arc::new(query_context),
+                result,
+                truncated: None,
+            });
+        }
+        oracle.validate_consistency(&results).await.unwrap();
+    }
+}
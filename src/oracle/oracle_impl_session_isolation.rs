@@ -0,0 +1,288 @@
+use std::sync::Arc;
+
+use datafusion::prelude::SessionContext;
+use rand::Rng;
+
+use crate::common::rng::rng_from_seed;
+use crate::common::util::quote_identifier;
+use crate::common::{Result, fuzzer_err};
+use crate::fuzz_context::adversarial_batches::TINY_BATCH_MAX_ROWS;
+use crate::fuzz_context::{memory_limited_session_context, tiny_batch_session_context};
+use crate::oracle::{Oracle, QueryContext, QueryExecutionResult, oracle_common};
+
+/// Memory limit of the configuration-diff session, large enough for the
+/// checked `count(*)` queries
+const DIFF_MEMORY_LIMIT_BYTES: usize = 16 * 1024 * 1024;
+
+/// Maximum number of rows of the session-scoped object
+const MAX_OBJECT_ROWS: usize = 5;
+
+/// Session-scoped table and view oracle.
+///
+/// DataFusion has no temporary tables: a table or view lives in the catalog
+/// of the session that created it. The oracle creates one in an isolated
+/// session with the fuzzer's configuration, half of the time named after a
+/// generated table it then shadows, and counts its rows. The main session and
+/// a session derived from it with another configuration (as configuration-diff
+/// oracles use) must not see it: they must count the rows of the generated
+/// table, or not list the name in `information_schema.tables`. Dropping the
+/// object must only drop it from the isolated session.
+///
+/// ### Example:
+///
+/// CREATE TABLE t1 (c0 BIGINT) AS VALUES (1), (NULL);   -- isolated session
+///
+/// SELECT count(*) FROM t1;   -- isolated session: 2 rows
+///
+/// SELECT count(*) FROM t1;   -- main and derived sessions: the generated rows
+///
+/// DROP TABLE t1;   -- isolated session, the generated t1 is still there
+pub struct SessionIsolationOracle {
+    seed: u64,
+    ctx: Arc<crate::fuzz_context::GlobalContext>,
+    /// Name of the session-scoped object and the count expected from each
+    /// checked query, by position in the group
+    expected: Option<(String, Vec<(usize, i64)>)>,
+}
+
+impl SessionIsolationOracle {
+    pub fn new(seed: u64, ctx: Arc<crate::fuzz_context::GlobalContext>) -> Self {
+        Self {
+            seed,
+            ctx,
+            expected: None,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Oracle for SessionIsolationOracle {
+    fn name(&self) -> &'static str {
+        "SessionIsolationOracle"
+    }
+
+    fn generate_query_group(&mut self) -> Result<Vec<QueryContext>> {
+        let mut rng = rng_from_seed(self.seed);
+        let snapshot = self.ctx.runtime_context.snapshot();
+
+        // Generated tables of the default schema with a known row count can
+        // be shadowed
+        let shadowable: Vec<_> = snapshot
+            .tables
+            .values()
+            .filter(|table| table.namespace.is_none() && table.row_count().is_some())
+            .collect();
+        let shadowed = (!shadowable.is_empty() && rng.random_bool(0.5))
+            .then(|| shadowable[rng.random_range(0..shadowable.len())]);
+        let name = match shadowed {
+            Some(table) => table.name.clone(),
+            None => format!("tmp_{}", self.seed % 10_000),
+        };
+        let quoted = quote_identifier(&name);
+        let literal = name.replace('\'', "''");
+
+        // The object gets a row count the shadowed table doesn't have
+        let shadowed_rows = shadowed.and_then(|table| table.row_count());
+        let mut object_rows = rng.random_range(1..=MAX_OBJECT_ROWS);
+        if shadowed_rows == Some(object_rows as u64) {
+            object_rows += 1;
+        }
+        let values: Vec<String> = (0..object_rows)
+            .map(|_| {
+                if rng.random_bool(0.2) {
+                    "(NULL)".to_string()
+                } else {
+                    format!("({})", rng.random_range(-100..=100))
+                }
+            })
+            .collect();
+        let (kind, create_sql) = if rng.random_bool(0.5) {
+            (
+                "TABLE",
+                format!(
+                    "CREATE TABLE {} (c0 BIGINT) AS VALUES {}",
+                    quoted,
+                    values.join(", ")
+                ),
+            )
+        } else {
+            (
+                "VIEW",
+                format!("CREATE VIEW {} AS VALUES {}", quoted, values.join(", ")),
+            )
+        };
+
+        let main_context = Arc::clone(&snapshot.session_context);
+        let isolated_context = Arc::new(SessionContext::new_with_config(
+            main_context.copied_config(),
+        ));
+        let (diff_context, diff_description) = if rng.random_bool(0.5) {
+            (
+                tiny_batch_session_context(&main_context, self.seed),
+                format!("Main session, batches of 1-{} rows", TINY_BATCH_MAX_ROWS),
+            )
+        } else {
+            (
+                memory_limited_session_context(&main_context, DIFF_MEMORY_LIMIT_BYTES)?,
+                format!(
+                    "Main session, {} MiB memory limit",
+                    DIFF_MEMORY_LIMIT_BYTES / (1024 * 1024)
+                ),
+            )
+        };
+
+        // Outside the isolated session, the name refers to the generated
+        // table or to nothing
+        let count_sql = format!("SELECT count(*) FROM {}", quoted);
+        let listed_sql = format!(
+            "SELECT count(*)\nFROM information_schema.tables\nWHERE table_name = '{}'",
+            literal
+        );
+        let (outside_sql, outside_count) = match shadowed_rows {
+            Some(rows) => (count_sql.clone(), rows as i64),
+            None => (listed_sql.clone(), 0),
+        };
+
+        let query = |sql: &str, context: &Arc<SessionContext>, description: &str| {
+            QueryContext::with_description(
+                sql.to_string(),
+                Arc::clone(context),
+                description.to_string(),
+            )
+        };
+        let isolated = "Isolated session";
+        let query_group = vec![
+            query(&create_sql, &isolated_context, isolated),
+            query(&count_sql, &isolated_context, isolated),
+            query(&outside_sql, &main_context, "Main session"),
+            query(&outside_sql, &diff_context, &diff_description),
+            query(
+                &format!("DROP {} {}", kind, quoted),
+                &isolated_context,
+                isolated,
+            ),
+            query(&listed_sql, &isolated_context, isolated),
+            query(&outside_sql, &main_context, "Main session"),
+        ];
+        self.expected = Some((
+            name,
+            vec![
+                (1, object_rows as i64),
+                (2, outside_count),
+                (3, outside_count),
+                (5, 0),
+                (6, outside_count),
+            ],
+        ));
+
+        Ok(query_group)
+    }
+
+    async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
+        let (name, expected_counts) = self
+            .expected
+            .as_ref()
+            .ok_or_else(|| fuzzer_err("SessionIsolation oracle validated before generating"))?;
+
+        for &(index, expected) in expected_counts {
+            let result = results.get(index).ok_or_else(|| {
+                fuzzer_err(&format!(
+                    "SessionIsolation expects at least {} query results, got {}",
+                    index + 1,
+                    results.len()
+                ))
+            })?;
+            let Ok(batches) = &result.result else {
+                continue;
+            };
+            let actual = oracle_common::count_value(batches)?;
+            if actual != expected {
+                return Err(fuzzer_err(&format!(
+                    "{} counts {}, expected {}: the session-scoped object {} is not isolated",
+                    result.query_context.display_description(),
+                    actual,
+                    expected,
+                    name
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
+        let mut report = String::new();
+        report.push_str("Session Isolation Oracle Test Failed\n");
+        report.push_str("====================================\n\n");
+
+        let labels: Vec<String> = results
+            .iter()
+            .map(|result| {
+                result
+                    .query_context
+                    .context_description
+                    .clone()
+                    .unwrap_or_else(|| "query".to_string())
+            })
+            .collect();
+        let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+        oracle_common::append_labeled_query_results(&mut report, results, &labels);
+
+        report.push_str(
+            "Expected: a table or view created in one session is only visible in that \
+             session, and dropping it leaves the tables of other sessions alone\n",
+        );
+
+        Ok(report)
+    }
+
+    fn reuses_identical_results(&self) -> bool {
+        // The main session is checked again after the drop
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuzz_context::GlobalContext;
+    use crate::oracle::test_helpers::{register_int_table, run_group};
+
+    #[tokio::test]
+    async fn session_scoped_objects_stay_in_their_session() {
+        let ctx = Arc::new(GlobalContext::default());
+        register_int_table(&ctx, &[Some(1), None]).await;
+
+        let mut shadowed = 0;
+        for seed in 0..8 {
+            let mut oracle = SessionIsolationOracle::new(seed, Arc::clone(&ctx));
+            let results = run_group(oracle.generate_query_group().unwrap()).await;
+            for result in &results {
+                assert!(
+                    result.result.is_ok(),
+                    "{}: {:?}",
+                    result.query_context.query,
+                    result.result
+                );
+            }
+            oracle.validate_consistency(&results).await.unwrap();
+            if results[0].query_context.query.contains("t1") {
+                shadowed += 1;
+            }
+        }
+        assert!(shadowed > 0);
+    }
+
+    #[tokio::test]
+    async fn reports_objects_leaking_into_the_main_session() {
+        let ctx = Arc::new(GlobalContext::default());
+        let mut oracle = SessionIsolationOracle::new(3, Arc::clone(&ctx));
+        let mut query_group = oracle.generate_query_group().unwrap();
+        // Create the object where every session sees it
+        query_group[0].context = ctx.runtime_context.get_session_context();
+
+        let results = run_group(query_group).await;
+        let err = oracle.validate_consistency(&results).await.unwrap_err();
+        assert!(err.to_string().contains("is not isolated"));
+    }
+}
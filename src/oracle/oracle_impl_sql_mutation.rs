@@ -0,0 +1,177 @@
+use std::sync::Arc;
+
+use rand::Rng;
+
+use crate::common::rng::rng_from_seed;
+use crate::common::{InclusionConfig, Result, fuzzer_err};
+use crate::oracle::{Oracle, QueryContext, QueryExecutionResult, oracle_common};
+use crate::query_generator::sql_mutation::mutate_sql;
+use crate::query_generator::stmt_select_def::SelectStatementBuilder;
+use crate::triage::{FailureCategory, classify_error};
+
+/// Number of corrupted copies of the generated query
+const MIN_MUTANTS: usize = 3;
+const MAX_MUTANTS: usize = 6;
+
+/// Maximum number of corruptions applied to one copy
+const MAX_MUTATIONS: usize = 3;
+
+/// Whether an error of corrupted SQL is the parser or planner rejecting it.
+/// Panics, internal errors and planning that doesn't finish are bugs,
+/// whatever the SQL.
+fn is_graceful_rejection(error: &str) -> bool {
+    !matches!(
+        classify_error(error),
+        FailureCategory::Panic | FailureCategory::InternalError | FailureCategory::Timeout
+    )
+}
+
+/// SQL text mutation oracle, fuzzing the parser and planner.
+///
+/// Corrupts copies of a valid generated query: truncation, swapped, deleted
+/// or repeated tokens, unbalanced parentheses, unusual unicode characters and
+/// stray punctuation. Each copy is planned, but not executed, with `EXPLAIN`.
+/// The parser and planner may reject it with any error, but must not panic,
+/// report an internal error or hang.
+///
+/// ### Example:
+///
+/// SELECT t0.c0 FROM t0 WHERE (t0.c0 > 1);   -- generated
+///
+/// EXPLAIN SELECT t0.c0 FROM t0 WHERE ((t0.c0 > 1);   -- unbalanced paren
+/// EXPLAIN SELECT t0.c0 FROM WHERE t0 (t0.c0 > 1);    -- swapped tokens
+pub struct SqlMutationOracle {
+    seed: u64,
+    ctx: Arc<crate::fuzz_context::GlobalContext>,
+}
+
+impl SqlMutationOracle {
+    pub fn new(seed: u64, ctx: Arc<crate::fuzz_context::GlobalContext>) -> Self {
+        Self { seed, ctx }
+    }
+}
+
+#[async_trait::async_trait]
+impl Oracle for SqlMutationOracle {
+    fn name(&self) -> &'static str {
+        "SqlMutationOracle"
+    }
+
+    fn generate_query_group(&mut self) -> Result<Vec<QueryContext>> {
+        let mut rng = rng_from_seed(self.seed);
+        let mut stmt_builder = SelectStatementBuilder::new(
+            rng.random(),
+            Arc::clone(&self.ctx),
+            InclusionConfig::Maybe(0.5),
+            InclusionConfig::Maybe(0.5),
+        )
+        .with_allow_derived_tables(false);
+        let sql = stmt_builder.generate_stmt()?.to_sql_string()?;
+
+        let session_context = self.ctx.runtime_context.get_session_context();
+        let query_group = (0..rng.random_range(MIN_MUTANTS..=MAX_MUTANTS))
+            .map(|_| {
+                let (mutated, mutations) = mutate_sql(&sql, MAX_MUTATIONS, &mut rng);
+                let mutations: Vec<String> = mutations.iter().map(ToString::to_string).collect();
+                QueryContext::with_description(
+                    format!("EXPLAIN {}", mutated),
+                    Arc::clone(&session_context),
+                    format!("Mutations: {}", mutations.join(", ")),
+                )
+                .with_tolerated_error(is_graceful_rejection)
+            })
+            .collect();
+
+        Ok(query_group)
+    }
+
+    async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
+        for result in results {
+            if let Err(e) = &result.result
+                && !is_graceful_rejection(&e.to_string())
+            {
+                return Err(fuzzer_err(&format!(
+                    "Corrupted SQL ({}) isn't rejected gracefully: {}",
+                    result
+                        .query_context
+                        .context_description
+                        .as_deref()
+                        .unwrap_or("no mutation"),
+                    e
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
+        let mut report = String::new();
+        report.push_str("SQL Mutation Oracle Test Failed\n");
+        report.push_str("===============================\n\n");
+
+        let labels: Vec<String> = results
+            .iter()
+            .map(|result| {
+                result
+                    .query_context
+                    .context_description
+                    .clone()
+                    .unwrap_or_else(|| "query".to_string())
+            })
+            .collect();
+        let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+        oracle_common::append_labeled_query_results(&mut report, results, &labels);
+
+        report.push_str(
+            "Expected: the parser and planner reject corrupted SQL with an error, without \
+             panicking or reporting an internal error\n",
+        );
+
+        Ok(report)
+    }
+
+    fn requires_consistent_outcomes(&self) -> bool {
+        // Some corrupted copies may still be valid SQL
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuzz_context::GlobalContext;
+    use crate::oracle::test_helpers::{self, register_int_table, run_group};
+
+    #[tokio::test]
+    async fn corrupted_queries_are_rejected_gracefully() {
+        let ctx = Arc::new(GlobalContext::default());
+        register_int_table(&ctx, &[Some(2), None, Some(-3)]).await;
+
+        for seed in 0..8 {
+            let mut oracle = SqlMutationOracle::new(seed, Arc::clone(&ctx));
+            let query_group = oracle.generate_query_group().unwrap();
+            assert!(query_group.len() >= MIN_MUTANTS);
+            assert!(query_group.iter().all(|q| q.query.starts_with("EXPLAIN ")));
+
+            let results = run_group(query_group).await;
+            oracle.validate_consistency(&results).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn reports_internal_errors() {
+        let oracle = SqlMutationOracle::new(1, Arc::new(GlobalContext::default()));
+        let mut results = vec![test_helpers::make_success_result("mutant", "plan", vec![1])];
+        assert!(oracle.validate_consistency(&results).await.is_ok());
+
+        results[0].result = Err(fuzzer_err(
+            "Query planning failed: SQL error: ParserError(\"Expected: ), found: EOF\")",
+        ));
+        assert!(oracle.validate_consistency(&results).await.is_ok());
+
+        results[0].result = Err(fuzzer_err("Query planning failed: Internal error: oops"));
+        let err = oracle.validate_consistency(&results).await.unwrap_err();
+        assert!(err.to_string().contains("isn't rejected gracefully"));
+    }
+}
@@ -0,0 +1,308 @@
+use std::sync::Arc;
+
+use datafusion::prelude::SessionContext;
+use rand::Rng;
+use rand::rngs::StdRng;
+
+use crate::common::rng::rng_from_seed;
+use crate::common::{Result, fuzzer_err};
+use crate::engine::statement_batch_engine::StatementBatchEngine;
+use crate::oracle::{ConfiguredOracle, Oracle, QueryContext, QueryExecutionResult, oracle_common};
+
+/// Objects the statements create, in sessions of their own
+const TABLE: &str = "batch_t0";
+const COPY: &str = "batch_t1";
+const VIEW: &str = "batch_v0";
+
+/// Number of statements between the first `CREATE TABLE` and the final
+/// `SELECT`
+const MIN_MIDDLE_STATEMENTS: usize = 2;
+const MAX_MIDDLE_STATEMENTS: usize = 5;
+
+/// Maximum number of rows of a `VALUES` list
+const MAX_VALUES_ROWS: usize = 4;
+
+/// String values, with statement separators and comment markers the statement
+/// splitter must leave alone inside literals
+const STRING_VALUES: &[&str] = &["a", "a;b", ";", "-- c", "/* d */", "';'", ""];
+
+/// Separators between statements, including an empty statement
+const SEPARATORS: &[&str] = &[";\n", "; ", ";", ";\n\n", " ;\n", ";;\n"];
+
+/// Statement batch (multi-statement SQL) oracle.
+///
+/// Generates a script of DDL, DML and queries on a table of its own and runs
+/// it as one SQL string, split into statements by DataFusion's parser, in a
+/// fresh session. The statements also run one by one in another fresh
+/// session. The last statement must return the same rows both ways, and the
+/// tables and views left behind must have the same rows. The script separates
+/// statements in various ways, with comments and empty statements, and its
+/// string literals contain `;` and comment markers.
+///
+/// ### Example:
+///
+/// CREATE TABLE batch_t0 (c0 BIGINT, c1 VARCHAR) AS VALUES (1, 'a;b');
+/// -- statement 2
+/// INSERT INTO batch_t0 SELECT c0 + 1, c1 FROM batch_t0 WHERE c0 > 0;;
+/// SELECT c0, c1 FROM batch_t0 ORDER BY c0, c1
+pub struct StatementBatchOracle {
+    seed: u64,
+    ctx: Arc<crate::fuzz_context::GlobalContext>,
+    /// Number of statements of the script and of objects left behind, set
+    /// when generating
+    layout: Option<(usize, usize)>,
+}
+
+impl StatementBatchOracle {
+    pub fn new(seed: u64, ctx: Arc<crate::fuzz_context::GlobalContext>) -> Self {
+        Self {
+            seed,
+            ctx,
+            layout: None,
+        }
+    }
+}
+
+/// `VALUES` list of `1..=MAX_VALUES_ROWS` rows of `(c0, c1)`
+fn random_values(rng: &mut StdRng) -> String {
+    let rows: Vec<String> = (0..rng.random_range(1..=MAX_VALUES_ROWS))
+        .map(|_| {
+            let c0 = if rng.random_bool(0.2) {
+                "NULL".to_string()
+            } else {
+                rng.random_range(-100..=100).to_string()
+            };
+            let c1 = STRING_VALUES[rng.random_range(0..STRING_VALUES.len())];
+            format!("({}, '{}')", c0, c1.replace('\'', "''"))
+        })
+        .collect();
+    rows.join(", ")
+}
+
+#[async_trait::async_trait]
+impl Oracle for StatementBatchOracle {
+    fn name(&self) -> &'static str {
+        "StatementBatchOracle"
+    }
+
+    fn generate_query_group(&mut self) -> Result<Vec<QueryContext>> {
+        let mut rng = rng_from_seed(self.seed);
+
+        let mut statements = vec![format!(
+            "CREATE TABLE {} (c0 BIGINT, c1 VARCHAR) AS VALUES {}",
+            TABLE,
+            random_values(&mut rng)
+        )];
+        let (mut copy_exists, mut view_exists) = (false, false);
+        for _ in 0..rng.random_range(MIN_MIDDLE_STATEMENTS..=MAX_MIDDLE_STATEMENTS) {
+            let statement = match rng.random_range(0..5) {
+                0 => format!("INSERT INTO {} VALUES {}", TABLE, random_values(&mut rng)),
+                1 => format!(
+                    "INSERT INTO {0} SELECT c0 + 1, c1 FROM {0} WHERE c0 > {1}",
+                    TABLE,
+                    rng.random_range(-100..=100)
+                ),
+                2 if copy_exists => {
+                    copy_exists = false;
+                    format!("DROP TABLE {}", COPY)
+                }
+                2 => {
+                    copy_exists = true;
+                    format!(
+                        "CREATE TABLE {} AS SELECT c0, c1 FROM {} WHERE c0 IS NOT NULL",
+                        COPY, TABLE
+                    )
+                }
+                3 if view_exists => {
+                    view_exists = false;
+                    format!("DROP VIEW {}", VIEW)
+                }
+                3 => {
+                    view_exists = true;
+                    format!(
+                        "CREATE VIEW {} AS SELECT c1, count(*) AS n FROM {} GROUP BY c1",
+                        VIEW, TABLE
+                    )
+                }
+                _ => format!("SELECT count(*), sum(c0) FROM {}", TABLE),
+            };
+            statements.push(statement);
+        }
+        statements.push(format!("SELECT c0, c1 FROM {} ORDER BY c0, c1", TABLE));
+
+        let mut batch_sql = String::new();
+        for (index, statement) in statements.iter().enumerate() {
+            if rng.random_bool(0.2) {
+                batch_sql.push_str(&format!("-- statement {}\n", index + 1));
+            }
+            batch_sql.push_str(statement);
+            // The last statement may go without a separator
+            if index + 1 < statements.len() || rng.random_bool(0.5) {
+                batch_sql.push_str(SEPARATORS[rng.random_range(0..SEPARATORS.len())]);
+            }
+        }
+
+        let main_context = self.ctx.runtime_context.get_session_context();
+        let batch_context = Arc::new(SessionContext::new_with_config(
+            main_context.copied_config(),
+        ));
+        let serial_context = Arc::new(SessionContext::new_with_config(
+            main_context.copied_config(),
+        ));
+
+        let mut query_group = vec![
+            QueryContext::with_description(
+                batch_sql,
+                Arc::clone(&batch_context),
+                "Statement batch".to_string(),
+            )
+            .with_engine(Arc::new(StatementBatchEngine::new(Arc::clone(
+                &batch_context,
+            )))),
+        ];
+        let num_statements = statements.len();
+        for (index, statement) in statements.into_iter().enumerate() {
+            query_group.push(QueryContext::with_description(
+                statement,
+                Arc::clone(&serial_context),
+                format!("Statement {}/{}", index + 1, num_statements),
+            ));
+        }
+
+        // Objects left behind, compared between both sessions
+        let mut objects = vec![TABLE];
+        if copy_exists {
+            objects.push(COPY);
+        }
+        if view_exists {
+            objects.push(VIEW);
+        }
+        for object in &objects {
+            let sql = format!("SELECT * FROM {} ORDER BY 1, 2", object);
+            query_group.push(QueryContext::with_description(
+                sql.clone(),
+                Arc::clone(&batch_context),
+                "Batch session".to_string(),
+            ));
+            query_group.push(QueryContext::with_description(
+                sql,
+                Arc::clone(&serial_context),
+                "Serial session".to_string(),
+            ));
+        }
+        self.layout = Some((num_statements, objects.len()));
+
+        Ok(query_group)
+    }
+
+    async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
+        let (num_statements, num_objects) = self
+            .layout
+            .ok_or_else(|| fuzzer_err("StatementBatch oracle validated before generating"))?;
+        let expected_results = 1 + num_statements + 2 * num_objects;
+        if results.len() != expected_results {
+            return Err(fuzzer_err(&format!(
+                "StatementBatch expects {} query results, got {}",
+                expected_results,
+                results.len()
+            )));
+        }
+
+        // The batch against its last statement, then the objects of both
+        // sessions
+        let pairs = std::iter::once((0, num_statements)).chain((0..num_objects).map(|object| {
+            let index = 1 + num_statements + 2 * object;
+            (index, index + 1)
+        }));
+        let options = self
+            .ctx
+            .runner_config
+            .compare_options(ConfiguredOracle::StatementBatch);
+        for (batch_index, serial_index) in pairs {
+            if results[batch_index].result.is_ok() && results[serial_index].result.is_ok() {
+                oracle_common::validate_value_equivalence(
+                    results,
+                    batch_index,
+                    serial_index,
+                    "StatementBatch",
+                    &options,
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
+        let mut report = String::new();
+        report.push_str("Statement Batch Oracle Test Failed\n");
+        report.push_str("==================================\n\n");
+
+        let labels: Vec<String> = results
+            .iter()
+            .map(|result| {
+                result
+                    .query_context
+                    .context_description
+                    .clone()
+                    .unwrap_or_else(|| "query".to_string())
+            })
+            .collect();
+        let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+        oracle_common::append_labeled_query_results(&mut report, results, &labels);
+
+        report.push_str(
+            "Expected: a script run as one SQL string has the same effect and returns the \
+             same rows as its statements run one by one\n",
+        );
+
+        Ok(report)
+    }
+
+    fn reuses_identical_results(&self) -> bool {
+        // A statement repeated later in the script sees other rows
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuzz_context::GlobalContext;
+    use crate::oracle::test_helpers::run_group;
+
+    #[tokio::test]
+    async fn batches_have_the_effect_of_their_statements() {
+        let ctx = Arc::new(GlobalContext::default());
+        for seed in 0..8 {
+            let mut oracle = StatementBatchOracle::new(seed, Arc::clone(&ctx));
+            let results = run_group(oracle.generate_query_group().unwrap()).await;
+            for result in &results {
+                assert!(
+                    result.result.is_ok(),
+                    "{}: {:?}",
+                    result.query_context.query,
+                    result.result
+                );
+            }
+            oracle.validate_consistency(&results).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn reports_batches_diverging_from_their_statements() {
+        let mut oracle = StatementBatchOracle::new(2, Arc::new(GlobalContext::default()));
+        let mut query_group = oracle.generate_query_group().unwrap();
+        // A statement only the batch runs
+        query_group[0].query = format!(
+            "{};\nINSERT INTO {} VALUES (1000, 'x');\nSELECT c0, c1 FROM {} ORDER BY c0, c1",
+            query_group[0].query.trim_end_matches([';', ' ', '\n']),
+            TABLE,
+            TABLE
+        );
+
+        let results = run_group(query_group).await;
+        let err = oracle.validate_consistency(&results).await.unwrap_err();
+        assert!(err.to_string().contains("value equivalence violated"));
+    }
+}
@@ -0,0 +1,216 @@
+use std::sync::Arc;
+
+use rand::Rng;
+
+use crate::common::rng::rng_from_seed;
+use crate::common::util::{quote_identifier, to_sql_string};
+use crate::common::{LogicalTable, Result, fuzzer_err};
+use crate::oracle::ground_truth::{column_expr, generate_predicate, parquet_backed_tables};
+use crate::oracle::{ConfiguredOracle, Oracle, QueryContext, QueryExecutionResult, oracle_common};
+
+/// Statistics-based optimization oracle.
+///
+/// Runs aggregates over a Parquet-backed table (see `dataset_format`) twice:
+/// once where DataFusion collects the statistics in the file footers and
+/// prunes row groups with them, and once without statistics or pruning.
+/// Statistics may answer `COUNT(*)`, `MIN` and `MAX` without reading any
+/// rows, or skip row groups for a predicate, but must never change results.
+/// The files are written with no, exact or truncated (inexact) statistics.
+///
+/// ### Example:
+///
+/// SELECT COUNT(*), COUNT(c), MIN(c), MAX(c) FROM t1;
+/// SELECT COUNT(*), COUNT(c), MIN(c), MAX(c) FROM t1 WHERE c > 5;
+///
+/// must return the same rows with and without statistics.
+pub struct StatisticsOracle {
+    seed: u64,
+    ctx: Arc<crate::fuzz_context::GlobalContext>,
+    table: Option<Arc<LogicalTable>>,
+}
+
+impl StatisticsOracle {
+    pub fn new(seed: u64, ctx: Arc<crate::fuzz_context::GlobalContext>) -> Self {
+        Self {
+            seed,
+            ctx,
+            table: None,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Oracle for StatisticsOracle {
+    fn name(&self) -> &'static str {
+        "StatisticsOracle"
+    }
+
+    fn generate_query_group(&mut self) -> Result<Vec<QueryContext>> {
+        let tables = parquet_backed_tables(&self.ctx);
+        if tables.is_empty() {
+            return Err(fuzzer_err(
+                "Statistics oracle requires a Parquet-backed table, see dataset_format",
+            ));
+        }
+
+        let mut rng = rng_from_seed(self.seed);
+        let table = Arc::clone(&tables[rng.random_range(0..tables.len())]);
+        let files = table
+            .files
+            .as_ref()
+            .ok_or_else(|| fuzzer_err("Statistics oracle picked an in-memory table"))?;
+        let column = &table.columns[rng.random_range(0..table.columns.len())];
+        let column_sql = to_sql_string(&column_expr(&table, column))?;
+        let predicate = to_sql_string(&generate_predicate(&mut rng, &table))?;
+
+        let aggregates = format!(
+            "SELECT COUNT(*), COUNT({c}), MIN({c}), MAX({c})\nFROM {}",
+            quote_identifier(&table.name),
+            c = column_sql
+        );
+        let queries = [
+            aggregates.clone(),
+            format!("{}\nWHERE {}", aggregates, predicate),
+        ];
+
+        let base_config = self
+            .ctx
+            .runtime_context
+            .get_session_context()
+            .copied_config();
+        let mut sessions = Vec::with_capacity(2);
+        for use_statistics in [true, false] {
+            let mut config = base_config.clone();
+            config.options_mut().execution.collect_statistics = use_statistics;
+            config.options_mut().execution.parquet.pruning = use_statistics;
+            sessions.push(files.session(config, &table.name, use_statistics)?);
+        }
+
+        let mut query_group = Vec::with_capacity(queries.len() * sessions.len());
+        for query in &queries {
+            for (session_context, description) in sessions
+                .iter()
+                .zip(["with statistics", "without statistics"])
+            {
+                query_group.push(QueryContext::with_description(
+                    query.clone(),
+                    Arc::clone(session_context),
+                    format!("{}, {:?} writer statistics", description, files.statistics),
+                ));
+            }
+        }
+
+        self.table = Some(table);
+        Ok(query_group)
+    }
+
+    async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
+        if results.len() != 4 {
+            return Err(fuzzer_err(&format!(
+                "Statistics oracle expects 4 query results, got {}",
+                results.len()
+            )));
+        }
+
+        let options = self
+            .ctx
+            .runner_config
+            .compare_options(ConfiguredOracle::Statistics);
+        for with_statistics in [0, 2] {
+            let without_statistics = with_statistics + 1;
+            if results[with_statistics].result.is_err()
+                || results[without_statistics].result.is_err()
+            {
+                continue;
+            }
+            oracle_common::validate_value_equivalence(
+                results,
+                with_statistics,
+                without_statistics,
+                "Statistics",
+                &options,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
+        let mut report = String::new();
+        report.push_str("Statistics Oracle Test Failed\n");
+        report.push_str("=============================\n\n");
+
+        oracle_common::append_labeled_query_results(
+            &mut report,
+            results,
+            &[
+                "with statistics",
+                "without statistics",
+                "filtered, with statistics",
+                "filtered, without statistics",
+            ],
+        );
+
+        if let Some(files) = self.table.as_ref().and_then(|table| table.files.as_ref()) {
+            report.push_str(&format!(
+                "Table files: {} ({:?} writer statistics)\n",
+                files.dir.display(),
+                files.statistics
+            ));
+        }
+        report.push_str("Expected: the same rows with and without statistics\n");
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::init_available_data_types;
+    use crate::datasource_generator::dataset_generator::DatasetGenerator;
+    use crate::datasource_generator::file_dataset::DatasetFormat;
+    use crate::fuzz_context::RunnerConfig;
+    use crate::oracle::test_helpers::context_with_config;
+
+    #[tokio::test]
+    async fn compares_parquet_tables_with_and_without_statistics() {
+        init_available_data_types();
+        let ctx = context_with_config(RunnerConfig {
+            dataset_format: DatasetFormat::Parquet,
+            ..Default::default()
+        });
+
+        let mut without_tables = StatisticsOracle::new(1, Arc::clone(&ctx));
+        assert!(without_tables.generate_query_group().is_err());
+
+        for seed in 0..4 {
+            DatasetGenerator::new(seed, Arc::clone(&ctx))
+                .generate_dataset()
+                .await
+                .unwrap();
+        }
+        if parquet_backed_tables(&ctx).is_empty() {
+            // Every table had an interval column
+            return;
+        }
+
+        let mut oracle = StatisticsOracle::new(1, Arc::clone(&ctx));
+        let query_group = oracle.generate_query_group().unwrap();
+        assert_eq!(query_group.len(), 4);
+        assert_eq!(query_group[0].query, query_group[1].query);
+        assert!(query_group[2].query.contains("\nWHERE "));
+
+        let mut results = Vec::new();
+        for query_context in query_group {
+            let result = match query_context.context.sql(&query_context.query).await {
+                Ok(df) => df.collect().await.map_err(Into::into),
+                Err(e) => Err(e.into()),
+            };
+            results.push(QueryExecutionResult {
+                query_context: Arc::new(query_context),
+                result,
+                truncated: None,
+            });
+        }
+        oracle.validate_consistency(&results).await.unwrap();
+    }
+}
@@ -0,0 +1,246 @@
+use std::sync::Arc;
+
+use datafusion::arrow::array::RecordBatch;
+use datafusion::arrow::datatypes::DataType;
+use datafusion::scalar::ScalarValue;
+use rand::{Rng, RngCore};
+
+use crate::common::rng::rng_from_seed;
+use crate::common::util::to_sql_string;
+use crate::common::{Result, fuzzer_err};
+use crate::oracle::ground_truth::{column_expr, is_numeric};
+use crate::oracle::result_compare::average_matches;
+use crate::oracle::{ConfiguredOracle, Oracle, QueryContext, QueryExecutionResult, oracle_common};
+use crate::query_generator::expr_gen::ExprGenerator;
+
+/// SUM/AVG oracle.
+///
+/// Aggregates a numeric column filtered by a generated predicate twice: once
+/// with `AVG`, and once with `SUM` and `COUNT`, along with the sum of the
+/// absolute values, which bounds the rounding error of float sums.
+///
+/// ### Example:
+///
+/// SELECT AVG(t1.c0) FROM t1 WHERE p;
+/// SELECT SUM(t1.c0), COUNT(t1.c0), SUM(ABS(CAST(t1.c0 AS DOUBLE))) FROM t1 WHERE p;
+///
+/// `AVG(x)` must be `SUM(x) / COUNT(x)` within a tolerance that depends on
+/// the type, see [`average_matches`], and both must be NULL without non-NULL
+/// values.
+pub struct SumAvgOracle {
+    seed: u64,
+    ctx: Arc<crate::fuzz_context::GlobalContext>,
+}
+
+impl SumAvgOracle {
+    pub fn new(seed: u64, ctx: Arc<crate::fuzz_context::GlobalContext>) -> Self {
+        Self { seed, ctx }
+    }
+}
+
+#[async_trait::async_trait]
+impl Oracle for SumAvgOracle {
+    fn name(&self) -> &'static str {
+        "SumAvgOracle"
+    }
+
+    fn generate_query_group(&mut self) -> Result<Vec<QueryContext>> {
+        let columns: Vec<_> = self
+            .ctx
+            .runtime_context
+            .tables()
+            .values()
+            .flat_map(|table| {
+                table
+                    .columns
+                    .iter()
+                    .filter(|column| is_numeric(&column.data_type))
+                    .map(move |column| (Arc::clone(table), column_expr(table, column)))
+            })
+            .collect();
+        if columns.is_empty() {
+            return Err(fuzzer_err("SUM/AVG oracle requires a numeric column"));
+        }
+
+        let mut rng = rng_from_seed(self.seed);
+        let (table, column) = &columns[rng.random_range(0..columns.len())];
+        let column_sql = to_sql_string(column)?;
+        let mut expr_gen =
+            ExprGenerator::new(rng.next_u64(), Arc::clone(&self.ctx)).with_src_columns(Arc::new(
+                ExprGenerator::tables_to_columns(std::slice::from_ref(table), &self.ctx),
+            ));
+        let predicate_sql = to_sql_string(&expr_gen.generate_random_expr(DataType::Boolean, 0))?;
+        let source = format!("FROM {}\nWHERE {}", table.sql_name(), predicate_sql);
+
+        let session_context = self.ctx.runtime_context.get_session_context();
+        Ok(vec![
+            QueryContext::with_description(
+                format!("SELECT AVG({})\n{}", column_sql, source),
+                Arc::clone(&session_context),
+                "AVG".to_string(),
+            ),
+            QueryContext::with_description(
+                format!(
+                    "SELECT SUM({}), COUNT({}), SUM(ABS(CAST({} AS DOUBLE)))\n{}",
+                    column_sql, column_sql, column_sql, source
+                ),
+                session_context,
+                "SUM and COUNT".to_string(),
+            ),
+        ])
+    }
+
+    async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
+        let [avg_result, sum_result] = results else {
+            return Err(fuzzer_err(&format!(
+                "SUM/AVG oracle expects 2 query results, got {}",
+                results.len()
+            )));
+        };
+        let (Ok(avg_batches), Ok(sum_batches)) = (&avg_result.result, &sum_result.result) else {
+            return Ok(());
+        };
+
+        let [avg] = <[ScalarValue; 1]>::try_from(single_row(avg_batches)?)
+            .map_err(|_| fuzzer_err("SUM/AVG oracle expects 1 average"))?;
+        let [sum, count, abs_sum] = <[ScalarValue; 3]>::try_from(single_row(sum_batches)?)
+            .map_err(|_| fuzzer_err("SUM/AVG oracle expects 3 aggregates"))?;
+        let ScalarValue::Int64(Some(count)) = count else {
+            return Err(fuzzer_err(&format!(
+                "COUNT returned {}, expected a non-NULL Int64",
+                count
+            )));
+        };
+        let abs_sum = match abs_sum {
+            ScalarValue::Float64(abs_sum) => abs_sum.unwrap_or_default(),
+            other => {
+                return Err(fuzzer_err(&format!(
+                    "SUM of DOUBLE returned {}, expected a Float64",
+                    other
+                )));
+            }
+        };
+
+        let options = self
+            .ctx
+            .runner_config
+            .compare_options(ConfiguredOracle::SumAvg);
+        if !average_matches(&avg, &sum, count as u64, abs_sum, &options)? {
+            return Err(fuzzer_err(&format!(
+                "SUM/AVG relationship violated: AVG returned {}, but SUM / COUNT is {} / {}",
+                avg, sum, count
+            )));
+        }
+        Ok(())
+    }
+
+    fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
+        let mut report = String::new();
+        report.push_str("SUM/AVG Oracle Test Failed\n");
+        report.push_str("==========================\n\n");
+
+        oracle_common::append_labeled_query_results(&mut report, results, &["avg", "sum"]);
+        report.push_str(
+            "Expected: AVG(x) = SUM(x) / COUNT(x) within the rounding error of the type\n",
+        );
+
+        Ok(report)
+    }
+}
+
+/// The values of the single row of an aggregate result
+fn single_row(batches: &[RecordBatch]) -> Result<Vec<ScalarValue>> {
+    let mut rows = batches.iter().filter(|batch| batch.num_rows() > 0);
+    match (rows.next(), rows.next()) {
+        (Some(batch), None) if batch.num_rows() == 1 => batch
+            .columns()
+            .iter()
+            .map(|column| ScalarValue::try_from_array(column, 0).map_err(Into::into))
+            .collect(),
+        _ => Err(fuzzer_err("Aggregate query must return exactly one row")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::init_available_data_types;
+    use crate::oracle::test_helpers;
+    use crate::oracle::test_helpers::table_with_int_values;
+    use datafusion::arrow::array::{Array, Float64Array, Int64Array};
+    use datafusion::arrow::datatypes::{Field, Schema};
+
+    fn sum_result(sum: Option<i64>, count: i64, abs_sum: f64) -> QueryExecutionResult {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("sum", DataType::Int64, true),
+            Field::new("count", DataType::Int64, false),
+            Field::new("abs_sum", DataType::Float64, true),
+        ]));
+        let columns: Vec<Arc<dyn Array>> = vec![
+            Arc::new(Int64Array::from(vec![sum])),
+            Arc::new(Int64Array::from(vec![count])),
+            Arc::new(Float64Array::from(vec![abs_sum])),
+        ];
+        QueryExecutionResult {
+            result: Ok(vec![RecordBatch::try_new(schema, columns).unwrap()]),
+            ..test_helpers::make_error_result("sum")
+        }
+    }
+
+    fn avg_result(avg: Option<f64>) -> QueryExecutionResult {
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "avg",
+            DataType::Float64,
+            true,
+        )]));
+        let array = Arc::new(Float64Array::from(vec![avg])) as Arc<dyn Array>;
+        QueryExecutionResult {
+            result: Ok(vec![RecordBatch::try_new(schema, vec![array]).unwrap()]),
+            ..test_helpers::make_error_result("avg")
+        }
+    }
+
+    #[test]
+    fn filters_both_aggregates_alike() {
+        init_available_data_types();
+        let ctx = Arc::new(crate::fuzz_context::GlobalContext::default());
+        ctx.runtime_context
+            .register_table(Arc::new(table_with_int_values(&[Some(1), None, Some(4)])));
+
+        for seed in 0..16 {
+            let mut oracle = SumAvgOracle::new(seed, Arc::clone(&ctx));
+            let query_group = oracle.generate_query_group().unwrap();
+            assert_eq!(query_group.len(), 2);
+            let source = query_group[0]
+                .query
+                .strip_prefix("SELECT AVG(t1.c0)\n")
+                .unwrap();
+            assert!(query_group[1].query.ends_with(source));
+            assert!(source.starts_with("FROM t1\nWHERE "));
+        }
+    }
+
+    #[tokio::test]
+    async fn avg_must_be_sum_over_count() {
+        let oracle = SumAvgOracle::new(1, Arc::new(crate::fuzz_context::GlobalContext::default()));
+
+        let matching = vec![avg_result(Some(2.5)), sum_result(Some(5), 2, 5.0)];
+        assert!(oracle.validate_consistency(&matching).await.is_ok());
+
+        let empty = vec![avg_result(None), sum_result(None, 0, 0.0)];
+        assert!(oracle.validate_consistency(&empty).await.is_ok());
+
+        let wrong = vec![avg_result(Some(2.0)), sum_result(Some(5), 2, 5.0)];
+        let err = oracle.validate_consistency(&wrong).await.unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("AVG returned 2, but SUM / COUNT is 5 / 2")
+        );
+
+        let overflow = vec![
+            avg_result(Some(2.0)),
+            test_helpers::make_error_result("sum"),
+        ];
+        assert!(oracle.validate_consistency(&overflow).await.is_ok());
+    }
+}
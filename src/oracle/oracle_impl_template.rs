@@ -0,0 +1,139 @@
+use std::sync::Arc;
+
+use rand::{Rng, RngCore};
+
+use crate::common::rng::rng_from_seed;
+use crate::common::{Result, fuzzer_err};
+use crate::oracle::{Oracle, QueryContext, QueryExecutionResult};
+use crate::query_generator::query_template::QueryTemplate;
+
+/// Runs queries of user-provided templates (`query_templates`) whose holes are
+/// filled with generated tables, columns, expressions and predicates, so a
+/// campaign can target specific DataFusion features with the fuzzer's
+/// generators. Like the NoCrash oracle, the queries must not crash or fail
+/// with a non-whitelisted error.
+///
+/// ### Example:
+///
+/// Template: SELECT {expr} FROM {table} WHERE {pred} ORDER BY 1 LIMIT 3
+/// Query:    SELECT (t2.c0 + 3) FROM t2 WHERE (t2.c1 < 'a') ORDER BY 1 LIMIT 3
+pub struct TemplateOracle {
+    seed: u64,
+    ctx: Arc<crate::fuzz_context::GlobalContext>,
+    /// Template the query was generated from, set when generating
+    template: Option<String>,
+}
+
+impl TemplateOracle {
+    pub fn new(seed: u64, ctx: Arc<crate::fuzz_context::GlobalContext>) -> Self {
+        Self {
+            seed,
+            ctx,
+            template: None,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Oracle for TemplateOracle {
+    fn name(&self) -> &'static str {
+        "TemplateOracle"
+    }
+
+    fn generate_query_group(&mut self) -> Result<Vec<QueryContext>> {
+        let templates = &self.ctx.runner_config.query_templates;
+        if templates.is_empty() {
+            return Err(fuzzer_err("Template oracle requires query_templates"));
+        }
+
+        let mut rng = rng_from_seed(self.seed);
+        let index = rng.random_range(0..templates.len());
+        let template = &templates[index];
+        let sql = QueryTemplate::parse(template)?.fill(rng.next_u64(), &self.ctx)?;
+        self.template = Some(template.clone());
+
+        Ok(vec![QueryContext::with_description(
+            sql,
+            self.ctx.runtime_context.get_session_context(),
+            format!("Query template {}", index + 1),
+        )])
+    }
+
+    async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
+        if results.is_empty() {
+            return Err(fuzzer_err("No query results to validate"));
+        }
+
+        // Errors are checked against the whitelist in the runner, like for
+        // the NoCrash oracle
+        Ok(())
+    }
+
+    fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
+        let mut report = String::new();
+        report.push_str("Template Oracle Test Failed\n");
+        report.push_str("===========================\n\n");
+
+        if let Some(template) = &self.template {
+            report.push_str(&format!("Template:\n{}\n\n", template));
+        }
+        if let Some(query_result) = results.first() {
+            report.push_str(&format!(
+                "Query that caused non-whitelisted error/crash:\n{}\n\n",
+                query_result.query_context.query
+            ));
+            if let Err(e) = &query_result.result {
+                report.push_str(&format!("Error details: {}\n\n", e));
+            }
+        }
+
+        report.push_str(
+            "Expected: Query should execute without crashing or return a whitelisted error\n",
+        );
+        report.push_str("Actual: Query crashed or returned a non-whitelisted error\n");
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::init_available_data_types;
+    use crate::fuzz_context::RunnerConfig;
+    use crate::oracle::test_helpers::{self, context_with_config, table_with_int_values};
+
+    #[tokio::test]
+    async fn fills_configured_templates() {
+        init_available_data_types();
+        let config = RunnerConfig {
+            query_templates: vec![
+                "SELECT count(*) FROM {table} WHERE {pred}".to_string(),
+                "SELECT {column} FROM {table} ORDER BY 1 LIMIT 1".to_string(),
+            ],
+            ..Default::default()
+        };
+        let ctx = context_with_config(config);
+        ctx.runtime_context
+            .register_table(Arc::new(table_with_int_values(&[Some(1), None])));
+
+        let mut used = [false, false];
+        for seed in 0..20 {
+            let mut oracle = TemplateOracle::new(seed, Arc::clone(&ctx));
+            let query_group = oracle.generate_query_group().unwrap();
+            assert_eq!(query_group.len(), 1);
+            let query = &query_group[0].query;
+            if query.starts_with("SELECT count(*) FROM t1 WHERE ") {
+                used[0] = true;
+            } else {
+                assert_eq!(query, "SELECT t1.c0 FROM t1 ORDER BY 1 LIMIT 1");
+                used[1] = true;
+            }
+
+            let results = vec![test_helpers::make_error_result(query)];
+            let report = oracle.create_error_report(&results).unwrap();
+            assert!(report.contains("Template:\nSELECT "), "{}", report);
+        }
+        assert_eq!(used, [true, true]);
+    }
+}
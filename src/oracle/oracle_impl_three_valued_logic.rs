@@ -0,0 +1,277 @@
+use std::sync::Arc;
+
+use datafusion::arrow::array::{Array, Int64Array, RecordBatch};
+use datafusion::prelude::{Expr, lit};
+use rand::Rng;
+
+use crate::common::rng::rng_from_seed;
+use crate::common::util::to_sql_string;
+use crate::common::value_generator::ValueGenerationConfig;
+use crate::common::{Result, fuzzer_err};
+use crate::oracle::expr_interpreter::{InterpretedExprGenerator, Value, ValueType, column_name};
+use crate::oracle::{Oracle, QueryContext, QueryExecutionResult, oracle_common};
+
+/// Number of nullable columns of the input table
+const INPUT_COLUMN_COUNT: usize = 4;
+/// Number of rows of the input table
+const INPUT_ROW_COUNT: usize = 8;
+/// NULL probability of the input values, higher than the fuzzer's default so
+/// most predicates see NULL operands
+const INPUT_NULL_PROBABILITY: f64 = 0.3;
+
+/// Three-valued-logic oracle.
+///
+/// Generates a predicate with NOT, IS NULL and explicit NULL literals over
+/// nullable columns, and evaluates it on every row of a small inline table
+/// with the reference interpreter in `expr_interpreter`. The rows are split
+/// into the ones where the predicate is TRUE, FALSE and NULL, and each part
+/// is queried with its own `WHERE` clause:
+///
+/// SELECT id FROM t WHERE p;
+/// SELECT id FROM t WHERE NOT (p);
+/// SELECT id FROM t WHERE (p) IS NULL;
+///
+/// DataFusion must return exactly the ids the interpreter expects: a `WHERE`
+/// clause only keeps TRUE rows, and `NOT` maps NULL to NULL. Predicates that
+/// overflow or divide by zero on any row are only run.
+pub struct ThreeValuedLogicOracle {
+    seed: u64,
+    ctx: Arc<crate::fuzz_context::GlobalContext>,
+    /// Expected ids of each query, `None` if the predicate is undefined
+    expected: Option<[Vec<i64>; 3]>,
+}
+
+impl ThreeValuedLogicOracle {
+    pub fn new(seed: u64, ctx: Arc<crate::fuzz_context::GlobalContext>) -> Self {
+        Self {
+            seed,
+            ctx,
+            expected: None,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Oracle for ThreeValuedLogicOracle {
+    fn name(&self) -> &'static str {
+        "ThreeValuedLogicOracle"
+    }
+
+    fn generate_query_group(&mut self) -> Result<Vec<QueryContext>> {
+        let mut rng = rng_from_seed(self.seed);
+        let column_types: Vec<ValueType> = (0..INPUT_COLUMN_COUNT)
+            .map(|_| {
+                if rng.random_bool(0.6) {
+                    ValueType::Int64
+                } else {
+                    ValueType::Boolean
+                }
+            })
+            .collect();
+
+        let value_config = ValueGenerationConfig {
+            nullable: true,
+            null_probability: INPUT_NULL_PROBABILITY,
+            ..self.ctx.runtime_context.value_generation_config.clone()
+        };
+        let mut generator = InterpretedExprGenerator {
+            rng: &mut rng,
+            columns: &column_types,
+            value_config: &value_config,
+            max_level: self.ctx.runner_config.max_expr_level,
+            three_valued: true,
+        };
+        let rows: Vec<Vec<Value>> = (0..INPUT_ROW_COUNT)
+            .map(|_| {
+                column_types
+                    .iter()
+                    .map(|value_type| generator.generate_value(*value_type))
+                    .collect()
+            })
+            .collect();
+        let predicate = generator.generate_expr(ValueType::Boolean, 0);
+
+        // Ids of the TRUE, FALSE and NULL rows
+        let mut expected: [Vec<i64>; 3] = Default::default();
+        self.expected = rows
+            .iter()
+            .enumerate()
+            .try_for_each(|(id, row)| {
+                let part = match predicate.evaluate(row)? {
+                    Value::Boolean(Some(true)) => 0,
+                    Value::Boolean(Some(false)) => 1,
+                    Value::Boolean(None) => 2,
+                    Value::Int64(_) => return None,
+                };
+                expected[part].push(id as i64);
+                Some(())
+            })
+            .map(|_| expected);
+
+        let table_sql = input_table_sql(&rows, &column_types)?;
+        let predicate_sql = to_sql_string(&predicate.to_expr())?;
+        let queries = [
+            predicate_sql.clone(),
+            format!("NOT ({})", predicate_sql),
+            format!("({}) IS NULL", predicate_sql),
+        ];
+
+        let session_context = self.ctx.runtime_context.get_session_context();
+        Ok(queries
+            .into_iter()
+            .zip(["TRUE", "FALSE", "NULL"])
+            .map(|(filter, truth_value)| {
+                QueryContext::with_description(
+                    format!("SELECT id\nFROM {}\nWHERE {}", table_sql, filter),
+                    Arc::clone(&session_context),
+                    format!("Rows where the predicate is {}", truth_value),
+                )
+            })
+            .collect())
+    }
+
+    async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
+        if results.len() != 3 {
+            return Err(fuzzer_err(&format!(
+                "Three-valued logic oracle expects 3 query results, got {}",
+                results.len()
+            )));
+        }
+        let Some(expected) = &self.expected else {
+            return Ok(());
+        };
+
+        for ((result, expected_ids), truth_value) in
+            results.iter().zip(expected).zip(["TRUE", "FALSE", "NULL"])
+        {
+            let Ok(batches) = &result.result else {
+                continue;
+            };
+            let actual_ids = sorted_ids(batches)?;
+            if &actual_ids != expected_ids {
+                return Err(fuzzer_err(&format!(
+                    "Three-valued logic violated: the predicate is {} for rows {:?}, but the query returned {:?}",
+                    truth_value, expected_ids, actual_ids
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
+        let mut report = String::new();
+        report.push_str("Three-Valued Logic Oracle Test Failed\n");
+        report.push_str("=====================================\n\n");
+
+        oracle_common::append_labeled_query_results(
+            &mut report,
+            results,
+            &["true rows", "false rows", "null rows"],
+        );
+
+        if let Some([true_ids, false_ids, null_ids]) = &self.expected {
+            report.push_str(&format!(
+                "Expected ids: TRUE {:?}, FALSE {:?}, NULL {:?}, computed by the reference interpreter\n",
+                true_ids, false_ids, null_ids
+            ));
+        }
+
+        Ok(report)
+    }
+}
+
+/// `(VALUES (...), ...) AS t(c0, ..., id)` with every value cast to its type
+fn input_table_sql(rows: &[Vec<Value>], column_types: &[ValueType]) -> Result<String> {
+    let cast = |value: Expr, sql_type: &str| -> Result<String> {
+        Ok(format!("CAST({} AS {})", to_sql_string(&value)?, sql_type))
+    };
+
+    let rows_sql = rows
+        .iter()
+        .enumerate()
+        .map(|(id, row)| {
+            let mut values = row
+                .iter()
+                .zip(column_types)
+                .map(|(value, value_type)| {
+                    cast(lit(value.to_scalar_value()), value_type.sql_type())
+                })
+                .collect::<Result<Vec<_>>>()?;
+            values.push(cast(lit(id as i64), ValueType::Int64.sql_type())?);
+            Ok(format!("({})", values.join(", ")))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut column_names: Vec<String> = (0..column_types.len()).map(column_name).collect();
+    column_names.push("id".to_string());
+    Ok(format!(
+        "(VALUES {}) AS t({})",
+        rows_sql.join(", "),
+        column_names.join(", ")
+    ))
+}
+
+/// The ids returned by a query, sorted
+fn sorted_ids(batches: &[RecordBatch]) -> Result<Vec<i64>> {
+    let mut ids = Vec::new();
+    for batch in batches {
+        let column = batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .ok_or_else(|| fuzzer_err("Three-valued logic query did not return an Int64 id"))?;
+        if column.null_count() > 0 {
+            return Err(fuzzer_err("Three-valued logic query returned a NULL id"));
+        }
+        ids.extend(column.values().iter().copied());
+    }
+    ids.sort_unstable();
+    Ok(ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracle::test_helpers;
+
+    #[test]
+    fn partitions_rows_by_truth_value() {
+        let ctx = Arc::new(crate::fuzz_context::GlobalContext::default());
+        for seed in 0..16 {
+            let mut oracle = ThreeValuedLogicOracle::new(seed, Arc::clone(&ctx));
+            let query_group = oracle.generate_query_group().unwrap();
+            assert_eq!(query_group.len(), 3);
+            assert!(query_group[1].query.contains("WHERE NOT ("));
+            assert!(query_group[2].query.ends_with(") IS NULL"));
+
+            if let Some(expected) = &oracle.expected {
+                let mut ids: Vec<i64> = expected.concat();
+                ids.sort_unstable();
+                assert_eq!(ids, (0..INPUT_ROW_COUNT as i64).collect::<Vec<_>>());
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn ids_must_match_the_interpreter() {
+        let mut oracle =
+            ThreeValuedLogicOracle::new(1, Arc::new(crate::fuzz_context::GlobalContext::default()));
+        oracle.expected = Some([vec![0, 2], vec![1], vec![]]);
+
+        let matching = vec![
+            test_helpers::make_success_result("true", "id", vec![2, 0]),
+            test_helpers::make_success_result("false", "id", vec![1]),
+            test_helpers::make_success_result("null", "id", vec![]),
+        ];
+        assert!(oracle.validate_consistency(&matching).await.is_ok());
+
+        // `NOT` must not turn NULL into TRUE
+        let wrong = vec![
+            test_helpers::make_success_result("true", "id", vec![0, 2]),
+            test_helpers::make_success_result("false", "id", vec![1, 3]),
+            test_helpers::make_error_result("null"),
+        ];
+        let err = oracle.validate_consistency(&wrong).await.unwrap_err();
+        assert!(err.to_string().contains("the predicate is FALSE"));
+    }
+}
@@ -0,0 +1,289 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, FixedOffset};
+use datafusion::arrow::array::RecordBatch;
+use datafusion::scalar::ScalarValue;
+use rand::Rng;
+use rand::rngs::StdRng;
+
+use crate::common::rng::rng_from_seed;
+use crate::common::value_generator::{DST_TIMEZONES, OFFSET_TIMEZONES};
+use crate::common::{Result, fuzzer_err};
+use crate::oracle::{Oracle, QueryContext, QueryExecutionResult, oracle_common};
+
+/// Number of representations of the same timestamps compared
+const REPRESENTATION_COUNT: usize = 3;
+
+/// DST transitions (seconds since epoch) the timestamps are generated around:
+/// US and EU spring forward and fall back in 2021, and both 30 minute
+/// transitions of `Australia/Lord_Howe`
+const DST_TRANSITIONS: [i64; 6] = [
+    1615705200, 1636264800, 1616893200, 1635642000, 1617462000, 1633188600,
+];
+
+/// Offsets the timestamp literals are written in, with their value in seconds
+const LITERAL_OFFSETS: [(&str, i32); 8] = [
+    ("Z", 0),
+    ("+00:00", 0),
+    ("+05:45", 5 * 3600 + 45 * 60),
+    ("-03:30", -(3 * 3600 + 30 * 60)),
+    ("+12:45", 12 * 3600 + 45 * 60),
+    ("+14:00", 14 * 3600),
+    ("-12:00", -12 * 3600),
+    ("+0530", 5 * 3600 + 30 * 60),
+];
+
+/// Two instants, and the fixed duration added to the first
+#[derive(Debug, Clone, PartialEq)]
+struct TimezoneCase {
+    /// Nanoseconds since epoch of `a` and `b`
+    a: i64,
+    b: i64,
+    /// Seconds added to `a`, a fixed duration in every zone
+    interval_seconds: i64,
+}
+
+impl TimezoneCase {
+    fn random(rng: &mut StdRng) -> Self {
+        let transition = DST_TRANSITIONS[rng.random_range(0..DST_TRANSITIONS.len())];
+        let a_seconds = transition + rng.random_range(-7200..=7200);
+        let a = a_seconds * 1_000_000_000 + rng.random_range(0..1_000_000_000);
+        let b = if rng.random_bool(0.2) {
+            a
+        } else {
+            a + rng.random_range(-10800..=10800) * 1_000_000_000
+        };
+        Self {
+            a,
+            b,
+            interval_seconds: rng.random_range(-7200..=7200),
+        }
+    }
+
+    fn shifted_a(&self) -> i64 {
+        self.a + self.interval_seconds * 1_000_000_000
+    }
+
+    /// Results of the query in every representation
+    fn expected_row(&self) -> Vec<String> {
+        vec![
+            self.a.to_string(),
+            self.shifted_a().to_string(),
+            (self.a < self.b).to_string(),
+            (self.a == self.b).to_string(),
+            (self.shifted_a() >= self.b).to_string(),
+        ]
+    }
+}
+
+/// Literal of the instant `nanos` as wall clock time in `offset`, with the
+/// offset appended
+fn timestamp_literal(nanos: i64, (suffix, offset_seconds): (&str, i32)) -> String {
+    let offset = FixedOffset::east_opt(offset_seconds).expect("offsets are within a day");
+    let wall_clock = DateTime::from_timestamp_nanos(nanos)
+        .with_timezone(&offset)
+        .format("%Y-%m-%d %H:%M:%S%.9f");
+    format!("'{}{}'", wall_clock, suffix)
+}
+
+/// The instant `nanos` as a timestamp in `zone`, or a timestamp without time
+/// zone for `None`
+fn timestamp_expr(nanos: i64, literal_offset: (&str, i32), zone: Option<&str>) -> String {
+    let literal = timestamp_literal(nanos, literal_offset);
+    match zone {
+        Some(zone) => format!(
+            "arrow_cast({}, 'Timestamp(Nanosecond, Some(\"{}\"))')",
+            literal, zone
+        ),
+        None => format!("CAST({} AS TIMESTAMP)", literal),
+    }
+}
+
+/// Time zone equivalence oracle.
+///
+/// Generates two timestamps near a DST transition and writes each of them
+/// several times: as literals with different UTC offsets, cast to timestamps
+/// in different zones (zones with DST, offsets with minutes, or none). All
+/// representations are the same instants, so comparisons, the instants
+/// themselves and adding a fixed number of seconds must give the same
+/// results, which are also computed in Rust.
+///
+/// ### Example:
+///
+/// SELECT CAST(a AS BIGINT), CAST(a + INTERVAL '600 seconds' AS BIGINT), a < b, a = b, a + INTERVAL '600 seconds' >= b
+/// FROM (SELECT arrow_cast('2021-03-14 12:30:00.000000000+05:45', 'Timestamp(Nanosecond, Some("America/New_York"))') AS a,
+///              arrow_cast('2021-03-14 03:45:00.000000000-03:30', 'Timestamp(Nanosecond, Some("America/New_York"))') AS b) AS t;
+///
+/// must return the same row when `America/New_York` is replaced by
+/// `Australia/Lord_Howe`, or the literals are written with other offsets.
+pub struct TimezoneOracle {
+    seed: u64,
+    ctx: Arc<crate::fuzz_context::GlobalContext>,
+    case: Option<TimezoneCase>,
+}
+
+impl TimezoneOracle {
+    pub fn new(seed: u64, ctx: Arc<crate::fuzz_context::GlobalContext>) -> Self {
+        Self {
+            seed,
+            ctx,
+            case: None,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Oracle for TimezoneOracle {
+    fn name(&self) -> &'static str {
+        "TimezoneOracle"
+    }
+
+    fn generate_query_group(&mut self) -> Result<Vec<QueryContext>> {
+        let mut rng = rng_from_seed(self.seed);
+        let case = TimezoneCase::random(&mut rng);
+
+        let zones: Vec<Option<&str>> = std::iter::once(None)
+            .chain(
+                DST_TIMEZONES
+                    .iter()
+                    .chain(&OFFSET_TIMEZONES)
+                    .map(|z| Some(*z)),
+            )
+            .collect();
+        let session_context = self.ctx.runtime_context.get_session_context();
+        let mut query_group = Vec::with_capacity(REPRESENTATION_COUNT);
+        for _ in 0..REPRESENTATION_COUNT {
+            let zone = zones[rng.random_range(0..zones.len())];
+            let mut literal_offset = || LITERAL_OFFSETS[rng.random_range(0..LITERAL_OFFSETS.len())];
+            let a = timestamp_expr(case.a, literal_offset(), zone);
+            let b = timestamp_expr(case.b, literal_offset(), zone);
+
+            let shifted = format!("a + INTERVAL '{} seconds'", case.interval_seconds);
+            let query = format!(
+                "SELECT CAST(a AS BIGINT), CAST({shifted} AS BIGINT), a < b, a = b, {shifted} >= b\nFROM (SELECT {a} AS a, {b} AS b) AS t"
+            );
+            query_group.push(QueryContext::with_description(
+                query,
+                Arc::clone(&session_context),
+                format!("Timestamps in zone {}", zone.unwrap_or("none")),
+            ));
+        }
+
+        self.case = Some(case);
+        Ok(query_group)
+    }
+
+    async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
+        let case = self
+            .case
+            .as_ref()
+            .ok_or_else(|| fuzzer_err("Timezone oracle validated before generating"))?;
+        let expected = case.expected_row();
+
+        for result in results {
+            let Ok(batches) = &result.result else {
+                continue;
+            };
+            let actual = first_row(batches)?;
+            if actual != expected {
+                return Err(fuzzer_err(&format!(
+                    "Timezone equivalence violated: {} returned {:?}, expected {:?}",
+                    result
+                        .query_context
+                        .context_description
+                        .as_deref()
+                        .unwrap_or("query"),
+                    actual,
+                    expected
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
+        let mut report = String::new();
+        report.push_str("Timezone Oracle Test Failed\n");
+        report.push_str("===========================\n\n");
+
+        oracle_common::append_labeled_query_results(
+            &mut report,
+            results,
+            &["representation 1", "representation 2", "representation 3"],
+        );
+
+        if let Some(case) = &self.case {
+            report.push_str(&format!(
+                "Expected: {:?} for a = {}ns, b = {}ns since epoch, computed in Rust\n",
+                case.expected_row(),
+                case.a,
+                case.b
+            ));
+        }
+
+        Ok(report)
+    }
+}
+
+/// Values of the first row of a result, rendered as strings
+fn first_row(batches: &[RecordBatch]) -> Result<Vec<String>> {
+    let batch = batches
+        .iter()
+        .find(|batch| batch.num_rows() > 0)
+        .ok_or_else(|| fuzzer_err("Timezone query returned no rows"))?;
+    batch
+        .columns()
+        .iter()
+        .map(|column| -> Result<String> { Ok(ScalarValue::try_from_array(column, 0)?.to_string()) })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn literals_are_the_same_instant_in_any_offset() {
+        // 2021-03-14 07:00:00Z, US spring forward
+        let nanos = 1615705200 * 1_000_000_000;
+        assert_eq!(
+            timestamp_literal(nanos, ("Z", 0)),
+            "'2021-03-14 07:00:00.000000000Z'"
+        );
+        assert_eq!(
+            timestamp_literal(nanos, LITERAL_OFFSETS[2]),
+            "'2021-03-14 12:45:00.000000000+05:45'"
+        );
+        assert_eq!(
+            timestamp_literal(nanos, LITERAL_OFFSETS[6]),
+            "'2021-03-13 19:00:00.000000000-12:00'"
+        );
+    }
+
+    #[test]
+    fn generates_equivalent_representations() {
+        let ctx = Arc::new(crate::fuzz_context::GlobalContext::default());
+        for seed in 0..16 {
+            let mut oracle = TimezoneOracle::new(seed, Arc::clone(&ctx));
+            let query_group = oracle.generate_query_group().unwrap();
+            assert_eq!(query_group.len(), REPRESENTATION_COUNT);
+            for query_context in &query_group {
+                assert!(query_context.query.starts_with("SELECT CAST(a AS BIGINT)"));
+                assert!(query_context.query.ends_with(" AS b) AS t"));
+            }
+        }
+    }
+
+    #[test]
+    fn expected_row_uses_fixed_durations() {
+        let case = TimezoneCase {
+            a: 10_000_000_000,
+            b: 20_000_000_000,
+            interval_seconds: 10,
+        };
+        assert_eq!(
+            case.expected_row(),
+            vec!["10000000000", "20000000000", "true", "false", "true"]
+        );
+    }
+}
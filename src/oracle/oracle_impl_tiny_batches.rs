@@ -0,0 +1,130 @@
+use crate::common::{InclusionConfig, Result, fuzzer_err};
+use crate::fuzz_context::adversarial_batches::TINY_BATCH_MAX_ROWS;
+use crate::fuzz_context::tiny_batch_session_context;
+use crate::oracle::{ConfiguredOracle, Oracle, QueryContext, QueryExecutionResult, oracle_common};
+use crate::query_generator::stmt_select_def::SelectStatementBuilder;
+use std::sync::Arc;
+
+/// Tiny-batch oracle.
+///
+/// Runs the same query normally and with every operator reading its input in
+/// batches of 1 to 3 rows (and the occasional empty batch). Streaming
+/// operators carry state across batches, e.g. partial aggregates, join probe
+/// positions or window frames; with tiny batches nearly every row crosses a
+/// batch boundary, so bugs in that state show up as different rows.
+///
+/// ### Example:
+///
+/// SELECT t0.c0, count(*) FROM t0 GROUP BY t0.c0;   -- normal batches
+///
+/// SELECT t0.c0, count(*) FROM t0 GROUP BY t0.c0;   -- batches of 1-3 rows
+pub struct TinyBatchesOracle {
+    seed: u64,
+    ctx: Arc<crate::fuzz_context::GlobalContext>,
+}
+
+impl TinyBatchesOracle {
+    pub fn new(seed: u64, ctx: Arc<crate::fuzz_context::GlobalContext>) -> Self {
+        Self { seed, ctx }
+    }
+}
+
+#[async_trait::async_trait]
+impl Oracle for TinyBatchesOracle {
+    fn name(&self) -> &'static str {
+        "TinyBatchesOracle"
+    }
+
+    fn generate_query_group(&mut self) -> Result<Vec<QueryContext>> {
+        let mut stmt_builder = SelectStatementBuilder::new(
+            self.seed,
+            Arc::clone(&self.ctx),
+            InclusionConfig::Maybe(0.5),
+            InclusionConfig::Maybe(0.5),
+        )
+        .with_allow_derived_tables(false);
+        let stmt = stmt_builder.generate_stmt()?;
+        let sql = stmt.to_sql_string()?;
+
+        let session_context = self.ctx.runtime_context.get_session_context();
+        let tiny_context = tiny_batch_session_context(&session_context, self.seed);
+
+        Ok(vec![
+            QueryContext::with_description(
+                sql.clone(),
+                session_context,
+                "Batches: normal".to_string(),
+            ),
+            QueryContext::with_description(
+                sql,
+                tiny_context,
+                format!("Batches: 1-{} rows", TINY_BATCH_MAX_ROWS),
+            ),
+        ])
+    }
+
+    async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
+        if results.len() != 2 {
+            return Err(fuzzer_err(&format!(
+                "TinyBatches expects 2 query results, got {}",
+                results.len()
+            )));
+        }
+
+        // Both succeeding or both failing is checked by the runner beforehand
+        if results.iter().any(|result| result.result.is_err()) {
+            return Ok(());
+        }
+
+        let options = self
+            .ctx
+            .runner_config
+            .compare_options(ConfiguredOracle::TinyBatches);
+        oracle_common::validate_value_equivalence(results, 0, 1, "TinyBatches", &options)
+    }
+
+    fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
+        let mut report = String::new();
+        report.push_str("Tiny-Batches Oracle Test Failed\n");
+        report.push_str("===============================\n\n");
+
+        let tiny_label = format!("batches of 1-{} rows", TINY_BATCH_MAX_ROWS);
+        let labels = ["normal batches", tiny_label.as_str()];
+        oracle_common::append_labeled_query_results(&mut report, results, &labels);
+
+        report.push_str("Expected: the query returns the same rows for any batch size\n");
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuzz_context::GlobalContext;
+    use crate::oracle::test_helpers::{self, register_int_table, run_group};
+
+    #[tokio::test]
+    async fn runs_the_query_with_tiny_batches() {
+        let ctx = Arc::new(GlobalContext::default());
+        register_int_table(&ctx, &[Some(2), None, Some(-3), Some(2), Some(7)]).await;
+
+        let mut oracle = TinyBatchesOracle::new(5, Arc::clone(&ctx));
+        let query_group = oracle.generate_query_group().unwrap();
+        assert_eq!(query_group.len(), 2);
+        assert_eq!(query_group[0].query, query_group[1].query);
+
+        let results = run_group(query_group).await;
+        assert!(oracle.validate_consistency(&results).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn reports_different_rows() {
+        let oracle = TinyBatchesOracle::new(1, Arc::new(GlobalContext::default()));
+        let results = vec![
+            test_helpers::make_success_result("normal", "c0", vec![1, 2]),
+            test_helpers::make_success_result("tiny", "c0", vec![1, 1]),
+        ];
+        assert!(oracle.validate_consistency(&results).await.is_err());
+    }
+}
@@ -1,6 +1,5 @@
 use crate::common::{InclusionConfig, Result, fuzzer_err};
-use crate::oracle::oracle_common;
-use crate::oracle::{Oracle, QueryContext, QueryExecutionResult};
+use crate::oracle::{ConfiguredOracle, Oracle, QueryContext, QueryExecutionResult, oracle_common};
 use crate::query_generator::stmt_select_def::SelectStatementBuilder;
 use std::sync::Arc;
 
@@ -104,7 +103,11 @@ impl Oracle for TlpHavingOracle {
     }
 
     async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
-        oracle_common::validate_binary_tlp_consistency(results, "TLP-HAVING")
+        let options = self
+            .ctx
+            .runner_config
+            .compare_options(ConfiguredOracle::TlpHaving);
+        oracle_common::validate_binary_tlp_consistency(results, "TLP-HAVING", &options)
     }
 
     fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
@@ -190,10 +193,12 @@ mod tests {
             QueryExecutionResult {
                 query_context: test_helpers::make_query_context("all"),
                 result: Ok(vec![one_col_batch.clone()]),
+                truncated: None,
             },
             QueryExecutionResult {
                 query_context: test_helpers::make_query_context("partition_union"),
                 result: Ok(vec![two_col_batch]),
+                truncated: None,
             },
         ];
 
@@ -206,19 +211,14 @@ mod tests {
         init_available_data_types();
         let ctx = Arc::new(crate::fuzz_context::GlobalContext::default());
         ctx.runtime_context
-            .registered_tables
-            .write()
-            .unwrap()
-            .insert(
+            .register_table(Arc::new(LogicalTable::with_columns(
                 "t0".to_string(),
-                Arc::new(LogicalTable::with_columns(
-                    "t0".to_string(),
-                    vec![LogicalColumn {
-                        name: "c0".to_string(),
-                        data_type: FuzzerDataType::Int64,
-                    }],
-                )),
-            );
+                vec![LogicalColumn {
+                    name: "c0".to_string(),
+                    data_type: FuzzerDataType::Int64,
+                    value_summary: None,
+                }],
+            )));
 
         let mut oracle = TlpHavingOracle::new(123, Arc::clone(&ctx));
         let query_group = oracle.generate_query_group().unwrap();
@@ -1,6 +1,5 @@
 use crate::common::{InclusionConfig, Result, fuzzer_err};
-use crate::oracle::oracle_common;
-use crate::oracle::{Oracle, QueryContext, QueryExecutionResult};
+use crate::oracle::{ConfiguredOracle, Oracle, QueryContext, QueryExecutionResult, oracle_common};
 use crate::query_generator::stmt_select_def::SelectStatementBuilder;
 use std::sync::Arc;
 
@@ -80,7 +79,11 @@ impl Oracle for TlpWhereOracle {
     }
 
     async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
-        oracle_common::validate_binary_tlp_consistency(results, "TLP-WHERE")
+        let options = self
+            .ctx
+            .runner_config
+            .compare_options(ConfiguredOracle::TlpWhere);
+        oracle_common::validate_binary_tlp_consistency(results, "TLP-WHERE", &options)
     }
 
     fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
@@ -166,10 +169,12 @@ mod tests {
             QueryExecutionResult {
                 query_context: test_helpers::make_query_context("all"),
                 result: Ok(vec![one_col_batch.clone()]),
+                truncated: None,
             },
             QueryExecutionResult {
                 query_context: test_helpers::make_query_context("partition_union"),
                 result: Ok(vec![two_col_batch]),
+                truncated: None,
             },
         ];
 
@@ -177,18 +182,27 @@ mod tests {
         assert!(err.to_string().contains("value equivalence violated"));
     }
 
+    #[test]
+    fn tlp_where_error_report_shows_side_by_side_diff() {
+        let oracle =
+            TlpWhereOracle::new(1, Arc::new(crate::fuzz_context::GlobalContext::default()));
+        let results = vec![
+            test_helpers::make_success_result("all", "cnt", vec![1, 2]),
+            test_helpers::make_success_result("partition_union", "cnt", vec![1, 3]),
+        ];
+
+        let report = oracle.create_error_report(&results).unwrap();
+        assert!(report.contains("Row counts: all=2, partition_union=2"));
+        assert!(report.contains("# | all | partition_union"));
+        assert!(report.contains("1 | (2) | (3)"));
+    }
+
     #[test]
     fn tlp_where_generates_expected_query_group_shape() {
         init_available_data_types();
         let ctx = Arc::new(crate::fuzz_context::GlobalContext::default());
         ctx.runtime_context
-            .registered_tables
-            .write()
-            .unwrap()
-            .insert(
-                "t0".to_string(),
-                Arc::new(LogicalTable::new("t0".to_string())),
-            );
+            .register_table(Arc::new(LogicalTable::new("t0".to_string())));
 
         let mut oracle = TlpWhereOracle::new(123, Arc::clone(&ctx));
         let query_group = oracle.generate_query_group().unwrap();
@@ -0,0 +1,208 @@
+use std::sync::Arc;
+
+use rand::Rng;
+
+use crate::common::rng::rng_from_seed;
+use crate::common::{InclusionConfig, Result, fuzzer_err};
+use crate::oracle::{ConfiguredOracle, Oracle, QueryContext, QueryExecutionResult, oracle_common};
+use crate::query_generator::stmt_select_def::SelectStatementBuilder;
+use crate::query_generator::unparser_dialect::UnparserDialect;
+use crate::triage::{FailureCategory, classify_error};
+
+/// Maximum number of other dialects a query is rendered in
+const MAX_DIALECTS: usize = 3;
+
+/// Whether an error of SQL rendered in another dialect is DataFusion
+/// rejecting the SQL, e.g. a type name or function it doesn't know, rather
+/// than failing on it
+fn is_graceful_rejection(error: &str) -> bool {
+    classify_error(error) == FailureCategory::PlannerError
+        || error.contains("This feature is not implemented")
+}
+
+/// Unparser dialect oracle.
+///
+/// Renders a generated query with DataFusion's unparser in the default
+/// dialect and in 1 to 3 others: the built-in PostgreSQL, MySQL, SQLite and
+/// DuckDB dialects, or a custom dialect with random quote style, type names,
+/// interval style, date field extraction and string length function. Each
+/// rendering runs in the embedded session. DataFusion may reject SQL meant for
+/// another dialect with a planning error, but SQL it accepts must return the
+/// same rows as the default rendering.
+///
+/// ### Example:
+///
+/// SELECT CAST(t0.c0 AS BIGINT) FROM t0;   -- default dialect
+///
+/// SELECT CAST(`t0`.`c0` AS SIGNED) FROM t0;   -- MySQL, may be rejected
+pub struct UnparserDialectOracle {
+    seed: u64,
+    ctx: Arc<crate::fuzz_context::GlobalContext>,
+}
+
+impl UnparserDialectOracle {
+    pub fn new(seed: u64, ctx: Arc<crate::fuzz_context::GlobalContext>) -> Self {
+        Self { seed, ctx }
+    }
+}
+
+#[async_trait::async_trait]
+impl Oracle for UnparserDialectOracle {
+    fn name(&self) -> &'static str {
+        "UnparserDialectOracle"
+    }
+
+    fn generate_query_group(&mut self) -> Result<Vec<QueryContext>> {
+        let mut rng = rng_from_seed(self.seed);
+        let mut stmt_builder = SelectStatementBuilder::new(
+            rng.random(),
+            Arc::clone(&self.ctx),
+            InclusionConfig::Maybe(0.5),
+            InclusionConfig::Maybe(0.5),
+        )
+        .with_allow_derived_tables(false);
+        let stmt = stmt_builder.generate_stmt()?;
+        let default_sql = stmt.to_sql_string()?;
+
+        let session_context = self.ctx.runtime_context.get_session_context();
+        let mut query_group = vec![QueryContext::with_description(
+            default_sql,
+            Arc::clone(&session_context),
+            "Unparser dialect: default".to_string(),
+        )];
+        for _ in 0..rng.random_range(1..=MAX_DIALECTS) {
+            let dialect = UnparserDialect::random(&mut rng);
+            // The dialect may not be able to render every expression, and
+            // renderings the group already has don't need to run again
+            let Ok(sql) = stmt.to_sql_string_with_dialect(dialect.dialect.as_ref()) else {
+                continue;
+            };
+            if query_group.iter().any(|query| query.query == sql) {
+                continue;
+            }
+            query_group.push(
+                QueryContext::with_description(
+                    sql,
+                    Arc::clone(&session_context),
+                    format!("Unparser dialect: {}", dialect.description),
+                )
+                .with_tolerated_error(is_graceful_rejection),
+            );
+        }
+
+        Ok(query_group)
+    }
+
+    async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
+        if results.is_empty() {
+            return Err(fuzzer_err("UnparserDialect expects query results"));
+        }
+
+        let options = self
+            .ctx
+            .runner_config
+            .compare_options(ConfiguredOracle::UnparserDialect);
+        for (index, result) in results.iter().enumerate().skip(1) {
+            match (&results[0].result, &result.result) {
+                (Ok(_), Ok(_)) => oracle_common::validate_value_equivalence(
+                    results,
+                    0,
+                    index,
+                    "UnparserDialect",
+                    &options,
+                )?,
+                (Ok(_), Err(e)) if !is_graceful_rejection(&e.to_string()) => {
+                    return Err(fuzzer_err(&format!(
+                        "SQL rendered in the {} fails instead of being rejected: {}",
+                        result
+                            .query_context
+                            .context_description
+                            .as_deref()
+                            .unwrap_or("other dialect"),
+                        e
+                    )));
+                }
+                // Rejected renderings, or failures of the default rendering
+                // the runner checks like for any other query
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
+        let mut report = String::new();
+        report.push_str("Unparser Dialect Oracle Test Failed\n");
+        report.push_str("===================================\n\n");
+
+        let labels: Vec<String> = results
+            .iter()
+            .map(|result| {
+                result
+                    .query_context
+                    .context_description
+                    .clone()
+                    .unwrap_or_else(|| "query".to_string())
+            })
+            .collect();
+        let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+        oracle_common::append_labeled_query_results(&mut report, results, &labels);
+
+        report.push_str(
+            "Expected: SQL rendered in another dialect is rejected with a planning error, \
+             or returns the same rows as the default rendering\n",
+        );
+
+        Ok(report)
+    }
+
+    fn requires_consistent_outcomes(&self) -> bool {
+        // Other dialects may be rejected
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuzz_context::GlobalContext;
+    use crate::oracle::test_helpers::{self, register_int_table, run_group};
+
+    #[tokio::test]
+    async fn accepted_renderings_return_the_default_rows() {
+        let ctx = Arc::new(GlobalContext::default());
+        register_int_table(&ctx, &[Some(2), None, Some(-3)]).await;
+
+        for seed in 0..8 {
+            let mut oracle = UnparserDialectOracle::new(seed, Arc::clone(&ctx));
+            let query_group = oracle.generate_query_group().unwrap();
+            assert!(query_group[1..].iter().all(|q| q.tolerated_error.is_some()));
+
+            let results = run_group(query_group).await;
+            oracle.validate_consistency(&results).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn reports_renderings_failing_at_execution() {
+        let oracle = UnparserDialectOracle::new(1, Arc::new(GlobalContext::default()));
+        let mut results = vec![
+            test_helpers::make_success_result("default", "c0", vec![1, 2]),
+            test_helpers::make_success_result("other", "c0", vec![1, 2]),
+        ];
+        assert!(oracle.validate_consistency(&results).await.is_ok());
+
+        results[1].result = Err(fuzzer_err(
+            "Query planning failed: Error during planning: Unsupported SQL type SIGNED",
+        ));
+        assert!(oracle.validate_consistency(&results).await.is_ok());
+
+        results[1].result = Err(fuzzer_err("Query execution failed: Internal error: oops"));
+        let err = oracle.validate_consistency(&results).await.unwrap_err();
+        assert!(err.to_string().contains("fails instead of being rejected"));
+
+        results[1] = test_helpers::make_success_result("other", "c0", vec![1]);
+        assert!(oracle.validate_consistency(&results).await.is_err());
+    }
+}
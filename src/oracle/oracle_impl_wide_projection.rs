@@ -0,0 +1,312 @@
+use std::sync::Arc;
+
+use rand::Rng;
+
+use crate::common::rng::rng_from_seed;
+use crate::common::util::quote_identifier;
+use crate::common::{Result, fuzzer_err};
+use crate::engine::planning_time_engine::PlanningTimeEngine;
+use crate::oracle::{ConfiguredOracle, Oracle, QueryContext, QueryExecutionResult, oracle_common};
+use crate::query_generator::wide_projection::{wide_group_by_keys, wide_select_list};
+
+/// Number of queries of a group: four planning times, the wide SELECT list
+/// with and without a derived table, and the wide and narrow GROUP BY
+const QUERY_GROUP_SIZE: usize = 8;
+
+/// Results whose planning is timed
+const PLANNING_TIME_RESULTS: [usize; 4] = [0, 1, 2, 3];
+
+/// Wide projection stress oracle.
+///
+/// Selects thousands of expressions (up to `max_projection_width`) over the
+/// columns of a generated table, and groups it by hundreds of keys (up to
+/// `max_group_by_width`), to probe the scalability of
+/// the planner and of schema handling.
+///
+/// Both queries are planned with a quarter and all of their width: planning
+/// time must grow about linearly with the width, the planning times are part
+/// of the finding otherwise. The wide SELECT list must return the same rows
+/// selected through a derived table. Every column is a GROUP BY key and the
+/// others are functions of a column, so the wide GROUP BY must find the same
+/// groups as grouping by the columns.
+///
+/// ### Example:
+///
+/// SELECT t1.c0 AS e0, t1.c1 IS NULL AS e1, 2 AS e2, ... FROM t1
+/// SELECT * FROM (SELECT t1.c0 AS e0, t1.c1 IS NULL AS e1, 2 AS e2, ... FROM t1) AS wide
+pub struct WideProjectionOracle {
+    seed: u64,
+    ctx: Arc<crate::fuzz_context::GlobalContext>,
+    /// Widths of the planned SELECT lists and GROUP BY lists, set when
+    /// generating
+    widths: Option<[usize; 4]>,
+}
+
+impl WideProjectionOracle {
+    pub fn new(seed: u64, ctx: Arc<crate::fuzz_context::GlobalContext>) -> Self {
+        Self {
+            seed,
+            ctx,
+            widths: None,
+        }
+    }
+}
+
+/// `count(*)` of the groups of `table` grouped by `keys`, and the number of
+/// rows they hold
+fn grouped_query(table: &str, keys: &[String]) -> String {
+    let select_list: Vec<String> = keys
+        .iter()
+        .enumerate()
+        .map(|(index, key)| format!("{} AS k{}", key, index))
+        .collect();
+    format!(
+        "SELECT count(*) AS num_groups, sum(n) AS num_rows FROM (SELECT {}, count(*) AS n FROM {} GROUP BY {}) AS grouped",
+        select_list.join(", "),
+        table,
+        keys.join(", ")
+    )
+}
+
+#[async_trait::async_trait]
+impl Oracle for WideProjectionOracle {
+    fn name(&self) -> &'static str {
+        "WideProjectionOracle"
+    }
+
+    fn generate_query_group(&mut self) -> Result<Vec<QueryContext>> {
+        let tables = self.ctx.runtime_context.tables();
+        let tables: Vec<_> = tables
+            .values()
+            .filter(|table| !table.columns.is_empty())
+            .collect();
+        if tables.is_empty() {
+            return Err(fuzzer_err(
+                "WideProjection oracle requires a table with columns",
+            ));
+        }
+
+        let mut rng = rng_from_seed(self.seed);
+        let table = tables[rng.random_range(0..tables.len())];
+        let table_name = table.sql_name();
+        let columns: Vec<String> = table
+            .columns
+            .iter()
+            .map(|column| format!("{}.{}", table_name, quote_identifier(&column.name)))
+            .collect();
+
+        let max_projection_width = self.ctx.runner_config.max_projection_width as usize;
+        let projection_width = rng.random_range(max_projection_width / 2..=max_projection_width);
+        let max_group_by_width = self.ctx.runner_config.max_group_by_width as usize;
+        let group_by_width = rng.random_range(max_group_by_width / 2..=max_group_by_width);
+        let select_list = wide_select_list(&columns, projection_width, &mut rng);
+        let keys = wide_group_by_keys(&columns, group_by_width, &mut rng);
+        let widths = [
+            (select_list.len() / 4).max(1),
+            select_list.len(),
+            (keys.len() / 4).max(1),
+            keys.len(),
+        ];
+
+        let wide_select = |width: usize| {
+            format!(
+                "SELECT {} FROM {}",
+                select_list[..width].join(", "),
+                table_name
+            )
+        };
+        let session_context = self.ctx.runtime_context.get_session_context();
+        let planned = [
+            (wide_select(widths[0]), "SELECT list"),
+            (wide_select(widths[1]), "SELECT list"),
+            (grouped_query(&table_name, &keys[..widths[2]]), "GROUP BY"),
+            (grouped_query(&table_name, &keys), "GROUP BY"),
+        ];
+        let mut query_group: Vec<QueryContext> = planned
+            .into_iter()
+            .zip(widths)
+            .map(|((sql, list), width)| {
+                QueryContext::with_description(
+                    sql,
+                    Arc::clone(&session_context),
+                    format!("Planning time, {} of {}", list, width),
+                )
+                .with_engine(Arc::new(PlanningTimeEngine::new(Arc::clone(
+                    &session_context,
+                ))))
+            })
+            .collect();
+
+        let wide_sql = wide_select(widths[1]);
+        query_group.push(QueryContext::with_description(
+            wide_sql.clone(),
+            Arc::clone(&session_context),
+            format!("SELECT list of {}", widths[1]),
+        ));
+        query_group.push(QueryContext::with_description(
+            format!("SELECT * FROM ({}) AS wide", wide_sql),
+            Arc::clone(&session_context),
+            "Derived table".to_string(),
+        ));
+        query_group.push(QueryContext::with_description(
+            grouped_query(&table_name, &keys),
+            Arc::clone(&session_context),
+            format!("GROUP BY of {}", widths[3]),
+        ));
+        query_group.push(QueryContext::with_description(
+            format!(
+                "SELECT count(*) AS num_groups, sum(n) AS num_rows FROM (SELECT count(*) AS n FROM {} GROUP BY {}) AS grouped",
+                table_name,
+                columns.join(", ")
+            ),
+            session_context,
+            "GROUP BY the columns".to_string(),
+        ));
+        self.widths = Some(widths);
+
+        Ok(query_group)
+    }
+
+    async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
+        let widths = self
+            .widths
+            .ok_or_else(|| fuzzer_err("WideProjection oracle validated before generating"))?;
+        if results.len() != QUERY_GROUP_SIZE {
+            return Err(fuzzer_err(&format!(
+                "WideProjection expects {} query results, got {}",
+                QUERY_GROUP_SIZE,
+                results.len()
+            )));
+        }
+
+        oracle_common::validate_planning_time_growth(
+            results,
+            0,
+            1,
+            (widths[0], widths[1]),
+            "SELECT list width",
+        )?;
+        oracle_common::validate_planning_time_growth(
+            results,
+            2,
+            3,
+            (widths[2], widths[3]),
+            "GROUP BY width",
+        )?;
+
+        let options = self
+            .ctx
+            .runner_config
+            .compare_options(ConfiguredOracle::WideProjection);
+        for (left, right) in [(4, 5), (6, 7)] {
+            if results[left].result.is_ok() && results[right].result.is_ok() {
+                oracle_common::validate_value_equivalence(
+                    results,
+                    left,
+                    right,
+                    "WideProjection",
+                    &options,
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
+        let mut report = String::new();
+        report.push_str("Wide Projection Oracle Test Failed\n");
+        report.push_str("==================================\n\n");
+
+        let labels: Vec<String> = results
+            .iter()
+            .map(|result| {
+                result
+                    .query_context
+                    .context_description
+                    .clone()
+                    .unwrap_or_else(|| "query".to_string())
+            })
+            .collect();
+        let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+        oracle_common::append_labeled_query_results(&mut report, results, &labels);
+        oracle_common::append_planning_times(&mut report, results, &PLANNING_TIME_RESULTS);
+
+        report.push_str(
+            "Expected: planning time grows about linearly with the width of SELECT and GROUP BY \
+             lists, wide SELECT lists return the same rows through a derived table, and \
+             keys that are functions of the other keys don't change the groups\n",
+        );
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuzz_context::{GlobalContext, RunnerConfig};
+    use crate::oracle::test_helpers::{self, context_with_config, register_int_table, run_group};
+
+    fn context_with_widths(
+        max_projection_width: u32,
+        max_group_by_width: u32,
+    ) -> Arc<GlobalContext> {
+        context_with_config(RunnerConfig {
+            max_projection_width,
+            max_group_by_width,
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn wide_lists_plan_and_agree() {
+        let ctx = context_with_widths(200, 40);
+        register_int_table(&ctx, &[Some(2), None, Some(-3), None]).await;
+
+        for seed in 0..4 {
+            let mut oracle = WideProjectionOracle::new(seed, Arc::clone(&ctx));
+            let results = run_group(oracle.generate_query_group().unwrap()).await;
+            for result in &results {
+                assert!(
+                    result.result.is_ok(),
+                    "{}: {:?}",
+                    result.query_context.query,
+                    result.result
+                );
+            }
+            oracle.validate_consistency(&results).await.unwrap();
+            let report = oracle.create_error_report(&results).unwrap();
+            assert!(report.contains("Planning time of Q4"));
+        }
+    }
+
+    #[tokio::test]
+    async fn reports_groups_lost_by_repeated_keys() {
+        let mut oracle = WideProjectionOracle::new(0, context_with_widths(2000, 200));
+        oracle.widths = Some([500, 2000, 50, 200]);
+        let mut results: Vec<QueryExecutionResult> = [10_000, 40_000, 1_000, 4_000]
+            .into_iter()
+            .map(|us| test_helpers::make_success_result("planning", "planning_us", vec![us]))
+            .collect();
+        results.push(test_helpers::make_success_result("wide", "e0", vec![1, 2]));
+        results.push(test_helpers::make_success_result(
+            "derived",
+            "e0",
+            vec![2, 1],
+        ));
+        results.push(test_helpers::make_success_result(
+            "wide keys",
+            "num_groups",
+            vec![2],
+        ));
+        results.push(test_helpers::make_success_result(
+            "columns",
+            "num_groups",
+            vec![3],
+        ));
+
+        let err = oracle.validate_consistency(&results).await.unwrap_err();
+        assert!(err.to_string().contains("value equivalence violated"));
+    }
+}
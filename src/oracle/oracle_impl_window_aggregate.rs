@@ -0,0 +1,342 @@
+use std::sync::Arc;
+
+use datafusion::arrow::array::{Array, AsArray, RecordBatch};
+use datafusion::arrow::compute::cast;
+use datafusion::arrow::datatypes::{DataType, UInt64Type};
+use datafusion::error::DataFusionError;
+use datafusion::scalar::ScalarValue;
+use rand::{Rng, RngCore};
+
+use crate::common::rng::rng_from_seed;
+use crate::common::util::{count_total_rows, to_sql_string};
+use crate::common::{Result, fuzzer_err};
+use crate::oracle::ground_truth::{column_expr, is_numeric};
+use crate::oracle::result_compare::sums_match;
+use crate::oracle::{ConfiguredOracle, Oracle, QueryContext, QueryExecutionResult, oracle_common};
+use crate::query_generator::expr_gen::ExprGenerator;
+
+/// Probability that the rows are filtered by a generated predicate
+const WHERE_PROBABILITY: f64 = 0.7;
+
+const QUERY_LABELS: [&str; 4] = [
+    "SUM window",
+    "SUM aggregate",
+    "ROW_NUMBER window",
+    "COUNT(*) aggregate",
+];
+
+/// Window-vs-aggregate oracle.
+///
+/// Computes the sum and the number of the rows of a table, optionally
+/// filtered by a generated predicate, with window functions over the whole
+/// table and with aggregates. The windows are written in equivalent ways:
+/// `OVER ()`, or ordered with a frame covering the whole partition.
+///
+/// ### Example:
+///
+/// SELECT SUM(t1.c0) OVER () FROM t1 WHERE p;
+/// SELECT SUM(t1.c0), SUM(ABS(CAST(t1.c0 AS DOUBLE))) FROM t1 WHERE p;
+/// SELECT ROW_NUMBER() OVER (ORDER BY t1.c0) FROM t1 WHERE p;
+/// SELECT COUNT(*) FROM t1 WHERE p;
+///
+/// Every row of the `SUM` window must hold the aggregated sum, and the row
+/// numbers must be exactly 1 to `COUNT(*)`. Float sums may differ by their
+/// rounding error, bounded by the sum of the absolute values.
+pub struct WindowAggregateOracle {
+    seed: u64,
+    ctx: Arc<crate::fuzz_context::GlobalContext>,
+}
+
+impl WindowAggregateOracle {
+    pub fn new(seed: u64, ctx: Arc<crate::fuzz_context::GlobalContext>) -> Self {
+        Self { seed, ctx }
+    }
+}
+
+#[async_trait::async_trait]
+impl Oracle for WindowAggregateOracle {
+    fn name(&self) -> &'static str {
+        "WindowAggregateOracle"
+    }
+
+    fn generate_query_group(&mut self) -> Result<Vec<QueryContext>> {
+        let columns: Vec<_> = self
+            .ctx
+            .runtime_context
+            .tables()
+            .values()
+            .flat_map(|table| {
+                table
+                    .columns
+                    .iter()
+                    .filter(|column| is_numeric(&column.data_type))
+                    .map(move |column| (Arc::clone(table), column_expr(table, column)))
+            })
+            .collect();
+        if columns.is_empty() {
+            return Err(fuzzer_err(
+                "Window-vs-aggregate oracle requires a numeric column",
+            ));
+        }
+
+        let mut rng = rng_from_seed(self.seed);
+        let (table, column) = &columns[rng.random_range(0..columns.len())];
+        let column_sql = to_sql_string(column)?;
+        let mut source = format!("FROM {}", table.sql_name());
+        if rng.random_bool(WHERE_PROBABILITY) {
+            let mut expr_gen = ExprGenerator::new(rng.next_u64(), Arc::clone(&self.ctx))
+                .with_src_columns(Arc::new(ExprGenerator::tables_to_columns(
+                    std::slice::from_ref(table),
+                    &self.ctx,
+                )));
+            let predicate = expr_gen.generate_random_expr(DataType::Boolean, 0);
+            source.push_str(&format!("\nWHERE {}", to_sql_string(&predicate)?));
+        }
+
+        let sum_window = if rng.random_bool(0.5) {
+            "OVER ()".to_string()
+        } else {
+            format!(
+                "OVER (ORDER BY {} ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING)",
+                column_sql
+            )
+        };
+        let row_number_window = if rng.random_bool(0.5) {
+            "OVER ()".to_string()
+        } else {
+            format!("OVER (ORDER BY {})", column_sql)
+        };
+        let queries = [
+            format!("SELECT SUM({}) {}\n{}", column_sql, sum_window, source),
+            format!(
+                "SELECT SUM({}), SUM(ABS(CAST({} AS DOUBLE)))\n{}",
+                column_sql, column_sql, source
+            ),
+            format!("SELECT ROW_NUMBER() {}\n{}", row_number_window, source),
+            format!("SELECT COUNT(*)\n{}", source),
+        ];
+
+        let session_context = self.ctx.runtime_context.get_session_context();
+        Ok(queries
+            .into_iter()
+            .zip(QUERY_LABELS)
+            .map(|(query, label)| {
+                QueryContext::with_description(
+                    query,
+                    Arc::clone(&session_context),
+                    label.to_string(),
+                )
+            })
+            .collect())
+    }
+
+    async fn validate_consistency(&self, results: &[QueryExecutionResult]) -> Result<()> {
+        let [
+            sum_window,
+            sum_aggregate,
+            row_number_window,
+            count_aggregate,
+        ] = results
+        else {
+            return Err(fuzzer_err(&format!(
+                "Window-vs-aggregate oracle expects {} query results, got {}",
+                QUERY_LABELS.len(),
+                results.len()
+            )));
+        };
+
+        if let (Ok(window_batches), Ok(aggregate_batches)) =
+            (&sum_window.result, &sum_aggregate.result)
+        {
+            let (sum, abs_sum) = sum_with_abs_sum(aggregate_batches)?;
+            let options = self
+                .ctx
+                .runner_config
+                .compare_options(ConfiguredOracle::WindowAggregate);
+            for batch in window_batches {
+                for row in 0..batch.num_rows() {
+                    let value = ScalarValue::try_from_array(batch.column(0), row)?;
+                    if !sums_match(&value, &sum, abs_sum, &options) {
+                        return Err(fuzzer_err(&format!(
+                            "Window-vs-aggregate violated: SUM(x) OVER returned {}, the SUM(x) aggregate {}",
+                            value, sum
+                        )));
+                    }
+                }
+            }
+        }
+
+        let (Ok(window_batches), Ok(count_batches)) =
+            (&row_number_window.result, &count_aggregate.result)
+        else {
+            return Ok(());
+        };
+        let count = oracle_common::count_value(count_batches)?;
+        let mut row_numbers = row_numbers(window_batches)?;
+        row_numbers.sort_unstable();
+        if !row_numbers.iter().copied().eq(1..=count as u64) {
+            let shown: Vec<u64> = row_numbers.iter().copied().take(10).collect();
+            return Err(fuzzer_err(&format!(
+                "Window-vs-aggregate violated: ROW_NUMBER() returned {} rows numbered {:?}{}, but COUNT(*) is {}",
+                row_numbers.len(),
+                shown,
+                if row_numbers.len() > shown.len() {
+                    "..."
+                } else {
+                    ""
+                },
+                count
+            )));
+        }
+        if let Ok(window_batches) = &sum_window.result
+            && count_total_rows(window_batches) as i64 != count
+        {
+            return Err(fuzzer_err(&format!(
+                "Window-vs-aggregate violated: SUM(x) OVER returned {} rows, but COUNT(*) is {}",
+                count_total_rows(window_batches),
+                count
+            )));
+        }
+        Ok(())
+    }
+
+    fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String> {
+        let mut report = String::new();
+        report.push_str("Window-vs-Aggregate Oracle Test Failed\n");
+        report.push_str("======================================\n\n");
+
+        oracle_common::append_labeled_query_results(&mut report, results, &QUERY_LABELS);
+        report.push_str(
+            "Expected: the aggregated sum on every row of the SUM window, and row numbers 1 to COUNT(*)\n",
+        );
+
+        Ok(report)
+    }
+}
+
+/// The sum and the sum of the absolute values of the aggregate query
+fn sum_with_abs_sum(batches: &[RecordBatch]) -> Result<(ScalarValue, f64)> {
+    let batch = batches
+        .iter()
+        .find(|batch| batch.num_rows() > 0)
+        .ok_or_else(|| fuzzer_err("SUM aggregate returned no rows"))?;
+    if batch.num_columns() != 2 {
+        return Err(fuzzer_err("SUM aggregate query expects 2 columns"));
+    }
+    let sum = ScalarValue::try_from_array(batch.column(0), 0)?;
+    let abs_sum = match ScalarValue::try_from_array(batch.column(1), 0)? {
+        ScalarValue::Float64(abs_sum) => abs_sum.unwrap_or_default(),
+        other => {
+            return Err(fuzzer_err(&format!(
+                "SUM of DOUBLE returned {}, expected a Float64",
+                other
+            )));
+        }
+    };
+    Ok((sum, abs_sum))
+}
+
+/// The values of a `ROW_NUMBER()` result
+fn row_numbers(batches: &[RecordBatch]) -> Result<Vec<u64>> {
+    let mut row_numbers = Vec::new();
+    for batch in batches {
+        let column = cast(batch.column(0), &DataType::UInt64).map_err(DataFusionError::from)?;
+        let column = column
+            .as_primitive_opt::<UInt64Type>()
+            .ok_or_else(|| fuzzer_err("ROW_NUMBER() did not return integers"))?;
+        if column.null_count() > 0 {
+            return Err(fuzzer_err("ROW_NUMBER() returned NULL"));
+        }
+        row_numbers.extend(column.values().iter().copied());
+    }
+    Ok(row_numbers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::init_available_data_types;
+    use crate::oracle::test_helpers;
+    use crate::oracle::test_helpers::table_with_int_values;
+    use datafusion::arrow::array::{Float64Array, Int64Array};
+    use datafusion::arrow::datatypes::{Field, Schema};
+
+    #[test]
+    fn windows_cover_the_aggregated_rows() {
+        init_available_data_types();
+        let ctx = Arc::new(crate::fuzz_context::GlobalContext::default());
+        ctx.runtime_context
+            .register_table(Arc::new(table_with_int_values(&[Some(1), None, Some(4)])));
+
+        for seed in 0..16 {
+            let mut oracle = WindowAggregateOracle::new(seed, Arc::clone(&ctx));
+            let query_group = oracle.generate_query_group().unwrap();
+            assert_eq!(query_group.len(), QUERY_LABELS.len());
+            let source = query_group[3]
+                .query
+                .strip_prefix("SELECT COUNT(*)\n")
+                .unwrap();
+            assert!(source.starts_with("FROM t1"));
+            assert!(
+                query_group
+                    .iter()
+                    .all(|query| query.query.ends_with(source))
+            );
+            assert!(query_group[0].query.starts_with("SELECT SUM(t1.c0) OVER ("));
+            assert!(
+                query_group[2]
+                    .query
+                    .starts_with("SELECT ROW_NUMBER() OVER (")
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn windows_must_match_the_aggregates() {
+        let oracle =
+            WindowAggregateOracle::new(1, Arc::new(crate::fuzz_context::GlobalContext::default()));
+        let sum_aggregate = |sum: i64| {
+            let schema = Arc::new(Schema::new(vec![
+                Field::new("sum", DataType::Int64, false),
+                Field::new("abs_sum", DataType::Float64, false),
+            ]));
+            let columns: Vec<Arc<dyn Array>> = vec![
+                Arc::new(Int64Array::from(vec![sum])),
+                Arc::new(Float64Array::from(vec![sum as f64])),
+            ];
+            QueryExecutionResult {
+                result: Ok(vec![RecordBatch::try_new(schema, columns).unwrap()]),
+                ..test_helpers::make_error_result("sum aggregate")
+            }
+        };
+        let results = |sums: Vec<i64>, sum: i64, row_numbers: Vec<i64>, count: i64| {
+            vec![
+                test_helpers::make_success_result("sum window", "sum", sums),
+                sum_aggregate(sum),
+                test_helpers::make_success_result("row number", "row_number", row_numbers),
+                test_helpers::make_success_result("count", "count", vec![count]),
+            ]
+        };
+
+        let matching = results(vec![5, 5, 5], 5, vec![2, 1, 3], 3);
+        assert!(oracle.validate_consistency(&matching).await.is_ok());
+
+        let wrong_sum = results(vec![5, 4, 5], 5, vec![1, 2, 3], 3);
+        let err = oracle.validate_consistency(&wrong_sum).await.unwrap_err();
+        assert!(err.to_string().contains("SUM(x) OVER returned 4"));
+
+        let duplicate_row_number = results(vec![5, 5, 5], 5, vec![1, 2, 2], 3);
+        let err = oracle
+            .validate_consistency(&duplicate_row_number)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("numbered [1, 2, 2]"));
+
+        let missing_rows = results(vec![5, 5], 5, vec![1, 2, 3], 3);
+        let err = oracle
+            .validate_consistency(&missing_rows)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("SUM(x) OVER returned 2 rows"));
+    }
+}
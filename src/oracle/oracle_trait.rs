@@ -1,4 +1,6 @@
 use crate::common::Result;
+use crate::engine::result_spool::SpooledResult;
+use crate::engine::{EmbeddedDataFusion, EngineUnderTest};
 use datafusion::arrow::array::RecordBatch;
 use datafusion::prelude::SessionContext;
 use std::sync::Arc;
@@ -34,6 +36,16 @@ use std::sync::Arc;
 pub struct QueryExecutionResult {
     pub query_context: Arc<QueryContext>,
     pub result: Result<Vec<RecordBatch>>,
+    /// Set when `result` only holds the first `max_result_rows` rows
+    pub truncated: Option<TruncatedResult>,
+}
+
+/// What is known about the whole result of a query beyond its first rows
+#[derive(Debug, Clone)]
+pub struct TruncatedResult {
+    pub total_rows: usize,
+    /// The whole result on disk, with `spool_results`
+    pub spooled: Option<Arc<SpooledResult>>,
 }
 
 #[async_trait::async_trait]
@@ -45,6 +57,12 @@ pub trait Oracle {
     fn generate_query_group(&mut self) -> Result<Vec<QueryContext>>;
 
     /// Validate the consistency of a group of query execution results
+    ///
+    /// Errors of the queries are checked by the runner like for any other
+    /// query: against the error whitelist, and for mixed outcomes across the
+    /// group unless `requires_consistent_outcomes` is overridden. Oracles only
+    /// compare the results of the queries that succeeded, and skip the checks
+    /// involving a failed one.
     /// # Parameters
     /// * `results` - Query execution results in the same order as the query group
     /// # Returns
@@ -55,6 +73,26 @@ pub trait Oracle {
     /// After one test run failed in `validate_consistency`, this function will be called
     /// to create a detailed error report.
     fn create_error_report(&self, results: &[QueryExecutionResult]) -> Result<String>;
+
+    /// Whether all queries of a group are expected to either all succeed or all fail.
+    ///
+    /// The runner checks this before `validate_consistency`, so a group where one
+    /// variant errors and another silently returns results is reported even if the
+    /// oracle only compares successful results. Override for oracles whose queries
+    /// are intentionally not equivalent.
+    fn requires_consistent_outcomes(&self) -> bool {
+        true
+    }
+
+    /// Whether a query repeating an earlier query of the group in the same
+    /// session reuses the earlier result instead of executing again.
+    ///
+    /// Oracles comparing one baseline against many rewrites or configurations
+    /// can list the baseline repeatedly at no cost. Override for oracles that
+    /// compare repeated executions, e.g. to check determinism.
+    fn reuses_identical_results(&self) -> bool {
+        true
+    }
 }
 
 impl std::fmt::Display for dyn Oracle + Send {
@@ -72,6 +110,11 @@ pub struct QueryContext {
     pub context: Arc<SessionContext>,
     /// Optional description of the context configuration for debugging
     pub context_description: Option<String>,
+    /// Engine executing the query instead of `context`, e.g. a remote server
+    pub engine: Option<Arc<dyn EngineUnderTest>>,
+    /// Errors that are an expected outcome of the query, which the runner
+    /// doesn't report. The oracle still sees them in the result.
+    pub tolerated_error: Option<fn(&str) -> bool>,
 }
 
 impl QueryContext {
@@ -81,6 +124,8 @@ impl QueryContext {
             query,
             context,
             context_description: None,
+            engine: None,
+            tolerated_error: None,
         }
     }
 
@@ -94,6 +139,30 @@ impl QueryContext {
             query,
             context,
             context_description: Some(description),
+            engine: None,
+            tolerated_error: None,
+        }
+    }
+
+    /// Execute the query on `engine` instead of the session context
+    pub fn with_engine(mut self, engine: Arc<dyn EngineUnderTest>) -> Self {
+        self.engine = Some(engine);
+        self
+    }
+
+    /// Don't report errors of the query `tolerated_error` accepts, e.g. SQL
+    /// that DataFusion may reject
+    pub fn with_tolerated_error(mut self, tolerated_error: fn(&str) -> bool) -> Self {
+        self.tolerated_error = Some(tolerated_error);
+        self
+    }
+
+    /// The engine executing the query: `engine` if set, the session context
+    /// otherwise
+    pub fn engine_under_test(&self) -> Arc<dyn EngineUnderTest> {
+        match &self.engine {
+            Some(engine) => Arc::clone(engine),
+            None => Arc::new(EmbeddedDataFusion::new(Arc::clone(&self.context))),
         }
     }
 
@@ -0,0 +1,1337 @@
+//! Result comparison shared by the consistency oracles.
+//!
+//! Query results are normalized row by row before being compared, so that
+//! representation-only differences are not reported as inconsistencies:
+//! - integers of different widths compare by value
+//! - floats compare with a tolerance, `NaN` equals `NaN`, `-0.0` equals `0.0`
+//! - decimals are rescaled to a canonical scale (`1.50` equals `1.5`)
+//! - timestamps compare by their UTC instant, regardless of unit and timezone
+//! - `NULL` equals `NULL`, whatever its type
+//!
+//! For very large results, [`CompareMode::Checksum`] only compares row counts
+//! and per-column checksums, without materializing and sorting every row.
+//! [`compare_batch_streams`] compares results read batch by batch (e.g. from
+//! spool files) the same way, or position by position if ordered.
+//! [`average_matches`] checks an average against its sum and count, and
+//! [`sums_match`] two sums of the same values, with a tolerance that depends
+//! on the type of the summed values.
+
+use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use datafusion::arrow::array::{ArrayRef, RecordBatch};
+use datafusion::common::utils::get_row_at_idx;
+use datafusion::scalar::ScalarValue;
+
+use crate::common::{Result, fuzzer_err};
+
+/// Number of mismatched rows shown by the `Display` impl of [`ResultDiff`]
+const DEFAULT_DIFF_ROWS: usize = 20;
+/// Mismatched rows collected by [`compare_batch_streams`], which never holds
+/// whole results
+const MAX_STREAM_MISMATCHES: usize = 100;
+/// Cells of a side-by-side diff wider than this are truncated
+const MAX_SIDE_BY_SIDE_CELL_WIDTH: usize = 60;
+const MISSING_ROW: &str = "<missing>";
+
+/// Strategy used to compare two query results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareMode {
+    /// Materialize and compare every row
+    Full,
+    /// Compare row counts and per-column checksums once either result has at
+    /// least `min_rows` rows; smaller results are still compared row by row so
+    /// their diffs stay readable. Row order is ignored in this mode.
+    Checksum { min_rows: usize },
+}
+
+/// How two query results should be compared.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CompareOptions {
+    pub mode: CompareMode,
+    /// Compare rows position by position instead of as multisets
+    pub ordered: bool,
+    /// Two floats are equal if they differ by at most this amount
+    pub float_abs_tolerance: f64,
+    /// Two floats are equal if they differ by at most this fraction of the
+    /// larger magnitude
+    pub float_rel_tolerance: f64,
+}
+
+impl Default for CompareOptions {
+    fn default() -> Self {
+        Self {
+            mode: CompareMode::Full,
+            ordered: false,
+            float_abs_tolerance: 1e-12,
+            float_rel_tolerance: 1e-9,
+        }
+    }
+}
+
+impl CompareOptions {
+    pub fn with_mode(mut self, mode: CompareMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn with_ordered(mut self, ordered: bool) -> Self {
+        self.ordered = ordered;
+        self
+    }
+
+    pub fn with_float_tolerance(mut self, abs_tolerance: f64, rel_tolerance: f64) -> Self {
+        self.float_abs_tolerance = abs_tolerance;
+        self.float_rel_tolerance = rel_tolerance;
+        self
+    }
+
+    fn floats_match(&self, left: f64, right: f64) -> bool {
+        if left.is_nan() && right.is_nan() {
+            return true;
+        }
+        if left == right {
+            return true;
+        }
+        if !left.is_finite() || !right.is_finite() {
+            return false;
+        }
+
+        let diff = (left - right).abs();
+        diff <= self.float_abs_tolerance
+            || diff <= self.float_rel_tolerance * left.abs().max(right.abs())
+    }
+}
+
+/// One row that differs between the two compared results.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowMismatch {
+    /// Row position, only set for ordered comparisons
+    pub position: Option<usize>,
+    /// Formatted row from the left result, `None` if the row is missing there
+    pub left: Option<String>,
+    /// Formatted row from the right result, `None` if the row is missing there
+    pub right: Option<String>,
+}
+
+/// Differences found by [`compare_batches`] and [`compare_batch_streams`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResultDiff {
+    pub ordered: bool,
+    pub left_row_count: usize,
+    pub right_row_count: usize,
+    pub mismatches: Vec<RowMismatch>,
+    /// Differing checksums, only set when the results were compared in
+    /// [`CompareMode::Checksum`] or as unordered streams
+    pub checksum_mismatches: Vec<String>,
+}
+
+impl ResultDiff {
+    /// Format the diff, showing at most `max_rows` mismatched rows.
+    pub fn format(&self, max_rows: usize) -> String {
+        let mut lines = vec![format!(
+            "Row counts: left={}, right={}",
+            self.left_row_count, self.right_row_count
+        )];
+
+        if !self.checksum_mismatches.is_empty() {
+            lines.push("Compared by per-column checksums:".to_string());
+            lines.extend(self.checksum_mismatches.iter().cloned());
+        }
+
+        for mismatch in self.mismatches.iter().take(max_rows) {
+            let left = mismatch.left.as_deref().unwrap_or(MISSING_ROW);
+            let right = mismatch.right.as_deref().unwrap_or(MISSING_ROW);
+            match mismatch.position {
+                Some(position) => lines.push(format!(
+                    "row {}: left={}, right={}",
+                    position + 1,
+                    left,
+                    right
+                )),
+                None if mismatch.left.is_some() => lines.push(format!("only in left:  {}", left)),
+                None => lines.push(format!("only in right: {}", right)),
+            }
+        }
+
+        if self.mismatches.len() > max_rows {
+            lines.push(format!(
+                "... {} more mismatched rows",
+                self.mismatches.len() - max_rows
+            ));
+        }
+
+        lines.join("\n")
+    }
+
+    /// Format the first `max_rows` mismatched rows as a two-column table.
+    ///
+    /// For unordered comparisons, rows only present in the left result are
+    /// paired with rows only present in the right result in sorted order, so
+    /// that a row with a single differing value usually ends up next to its
+    /// counterpart.
+    pub fn format_side_by_side(
+        &self,
+        left_label: &str,
+        right_label: &str,
+        max_rows: usize,
+    ) -> String {
+        let mut lines = vec![format!(
+            "Row counts: {}={}, {}={}",
+            left_label, self.left_row_count, right_label, self.right_row_count
+        )];
+
+        if !self.checksum_mismatches.is_empty() {
+            lines.push("Compared by per-column checksums, no rows to show:".to_string());
+            lines.extend(self.checksum_mismatches.iter().map(|m| format!("  {}", m)));
+            return lines.join("\n");
+        }
+
+        let pairs = self.side_by_side_pairs();
+        let shown = &pairs[..pairs.len().min(max_rows)];
+
+        let position_header = if self.ordered { "row" } else { "#" };
+        let cell = |text: Option<&str>| truncate_cell(text.unwrap_or(MISSING_ROW));
+        let rows = shown
+            .iter()
+            .enumerate()
+            .map(|(idx, (position, left, right))| {
+                (
+                    position.unwrap_or(idx) + 1,
+                    cell(left.as_deref()),
+                    cell(right.as_deref()),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let position_width = rows
+            .iter()
+            .map(|(position, _, _)| position.to_string().len())
+            .chain([position_header.len()])
+            .max()
+            .unwrap_or_default();
+        let left_width = rows
+            .iter()
+            .map(|(_, left, _)| left.chars().count())
+            .chain([left_label.chars().count()])
+            .max()
+            .unwrap_or_default();
+
+        lines.push(format!(
+            "{:>pw$} | {:<lw$} | {}",
+            position_header,
+            left_label,
+            right_label,
+            pw = position_width,
+            lw = left_width
+        ));
+        lines.push(format!(
+            "{}-+-{}-+-{}",
+            "-".repeat(position_width),
+            "-".repeat(left_width),
+            "-".repeat(right_label.chars().count().max(3))
+        ));
+        for (position, left, right) in &rows {
+            lines.push(format!(
+                "{:>pw$} | {:<lw$} | {}",
+                position,
+                left,
+                right,
+                pw = position_width,
+                lw = left_width
+            ));
+        }
+
+        if pairs.len() > max_rows {
+            lines.push(format!(
+                "... {} more mismatched rows",
+                pairs.len() - max_rows
+            ));
+        }
+
+        lines.join("\n")
+    }
+
+    fn side_by_side_pairs(&self) -> Vec<(Option<usize>, Option<String>, Option<String>)> {
+        if self.ordered {
+            return self
+                .mismatches
+                .iter()
+                .map(|m| (m.position, m.left.clone(), m.right.clone()))
+                .collect();
+        }
+
+        let mut lefts = self.mismatches.iter().filter_map(|m| m.left.clone());
+        let mut rights = self.mismatches.iter().filter_map(|m| m.right.clone());
+        let mut pairs = Vec::new();
+        loop {
+            match (lefts.next(), rights.next()) {
+                (None, None) => break,
+                (left, right) => pairs.push((None, left, right)),
+            }
+        }
+        pairs
+    }
+}
+
+fn truncate_cell(text: &str) -> String {
+    if text.chars().count() <= MAX_SIDE_BY_SIDE_CELL_WIDTH {
+        return text.to_string();
+    }
+    let truncated = text
+        .chars()
+        .take(MAX_SIDE_BY_SIDE_CELL_WIDTH - 3)
+        .collect::<String>();
+    format!("{}...", truncated)
+}
+
+impl fmt::Display for ResultDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.format(DEFAULT_DIFF_ROWS))
+    }
+}
+
+/// Compare two query results, returning `None` if they are equivalent under
+/// `options`.
+pub fn compare_batches(
+    left: &[RecordBatch],
+    right: &[RecordBatch],
+    options: &CompareOptions,
+) -> Result<Option<ResultDiff>> {
+    if let CompareMode::Checksum { min_rows } = options.mode
+        && count_total_rows(left).max(count_total_rows(right)) >= min_rows
+    {
+        return compare_checksums(left, right, options);
+    }
+
+    let mut left_rows = normalize_batches(left)?;
+    let mut right_rows = normalize_batches(right)?;
+
+    let mismatches = if options.ordered {
+        diff_ordered(&left_rows, &right_rows, options)
+    } else {
+        left_rows.sort_by(|a, b| compare_rows(&a.values, &b.values));
+        right_rows.sort_by(|a, b| compare_rows(&a.values, &b.values));
+        diff_unordered(&left_rows, &right_rows, options)
+    };
+
+    if mismatches.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(ResultDiff {
+        ordered: options.ordered,
+        left_row_count: left_rows.len(),
+        right_row_count: right_rows.len(),
+        mismatches,
+        checksum_mismatches: Vec::new(),
+    }))
+}
+
+/// Compare two query results read one batch at a time, returning `None` if
+/// they are equivalent under `options`. Ordered results are compared
+/// position by position; unordered results can't be sorted without holding
+/// them, so they are compared by row count and per-column checksums.
+pub fn compare_batch_streams(
+    left: impl Iterator<Item = Result<RecordBatch>>,
+    right: impl Iterator<Item = Result<RecordBatch>>,
+    options: &CompareOptions,
+) -> Result<Option<ResultDiff>> {
+    if !options.ordered {
+        let (left_checksums, left_row_count) = stream_checksums(left)?;
+        let (right_checksums, right_row_count) = stream_checksums(right)?;
+        return Ok(checksum_diff(
+            &left_checksums,
+            &right_checksums,
+            left_row_count,
+            right_row_count,
+            options,
+        ));
+    }
+
+    let mut left = BatchCursor::new(left);
+    let mut right = BatchCursor::new(right);
+    let chunk_options = options.with_mode(CompareMode::Full);
+    let mut mismatches = Vec::new();
+    let mut position = 0;
+    // Compare the overlapping rows of the current batches of both sides
+    while let (Some(l), Some(r)) = (left.current()?, right.current()?) {
+        let rows = l.num_rows().min(r.num_rows());
+        let chunk_diff = compare_batches(&[l.slice(0, rows)], &[r.slice(0, rows)], &chunk_options)?;
+        if let Some(chunk_diff) = chunk_diff {
+            let remaining = MAX_STREAM_MISMATCHES.saturating_sub(mismatches.len());
+            mismatches.extend(chunk_diff.mismatches.into_iter().take(remaining).map(
+                |mut mismatch| {
+                    mismatch.position = mismatch.position.map(|p| p + position);
+                    mismatch
+                },
+            ));
+        }
+        left.advance(rows);
+        right.advance(rows);
+        position += rows;
+    }
+
+    let left_row_count = left.count_remaining()?;
+    let right_row_count = right.count_remaining()?;
+    if mismatches.is_empty() && left_row_count == right_row_count {
+        return Ok(None);
+    }
+
+    Ok(Some(ResultDiff {
+        ordered: true,
+        left_row_count,
+        right_row_count,
+        mismatches,
+        checksum_mismatches: Vec::new(),
+    }))
+}
+
+/// Whether `avg` is `sum / count` within the rounding error of both
+/// aggregates, e.g. `AVG(x)` against `SUM(x)` and `COUNT(x)`.
+///
+/// The tolerance depends on the type: float sums (and the float sums behind
+/// averages of integers) accumulated in any order are off by at most
+/// `f64::EPSILON` times `abs_sum`, the sum of the absolute values, and decimal
+/// averages are rounded to the scale of `avg`. Without counted values both
+/// aggregates must be NULL.
+pub fn average_matches(
+    avg: &ScalarValue,
+    sum: &ScalarValue,
+    count: u64,
+    abs_sum: f64,
+    options: &CompareOptions,
+) -> Result<bool> {
+    if count == 0 || avg.is_null() || sum.is_null() {
+        return Ok(count == 0 && avg.is_null() && sum.is_null());
+    }
+
+    let as_f64 = |value: &ScalarValue| match NormalizedValue::from_scalar(value) {
+        NormalizedValue::Integer(v) => Ok(v as f64),
+        NormalizedValue::Float(v) => Ok(v),
+        NormalizedValue::Decimal { mantissa, scale } => Ok(decimal_to_f64(mantissa, scale)),
+        _ => Err(fuzzer_err(&format!(
+            "Expected a numeric aggregate, got {}",
+            value
+        ))),
+    };
+    let actual = as_f64(avg)?;
+    let expected = as_f64(sum)? / count as f64;
+    if !actual.is_finite() || !expected.is_finite() {
+        return Ok(options.floats_match(actual, expected));
+    }
+
+    let rounding = match avg {
+        ScalarValue::Decimal128(_, _, scale) | ScalarValue::Decimal256(_, _, scale) => {
+            10f64.powi(-(*scale as i32))
+        }
+        _ => 0.0,
+    };
+    let tolerance = options.float_abs_tolerance
+        + options.float_rel_tolerance * expected.abs()
+        + sum_rounding_error(abs_sum)
+        + rounding;
+    Ok((actual - expected).abs() <= tolerance)
+}
+
+/// Whether two sums of the same values match, e.g. `SUM(x)` and
+/// `SUM(x) OVER ()`. Float sums accumulated in different orders may differ
+/// by the rounding error bounded by `abs_sum`, see [`average_matches`]; other
+/// values compare like in [`compare_batches`].
+pub fn sums_match(
+    left: &ScalarValue,
+    right: &ScalarValue,
+    abs_sum: f64,
+    options: &CompareOptions,
+) -> bool {
+    let left = NormalizedValue::from_scalar(left);
+    let right = NormalizedValue::from_scalar(right);
+    match (&left, &right) {
+        (NormalizedValue::Float(a), NormalizedValue::Float(b)) => {
+            options.floats_match(*a, *b) || (a - b).abs() <= sum_rounding_error(abs_sum)
+        }
+        _ => left.matches(&right, options),
+    }
+}
+
+/// Bound of the rounding error of a float sum of values whose absolute values
+/// sum to `abs_sum`, accumulated in any order
+fn sum_rounding_error(abs_sum: f64) -> f64 {
+    2.0 * f64::EPSILON * abs_sum
+}
+
+/// Position in a stream of batches
+struct BatchCursor<I> {
+    batches: I,
+    current: Option<RecordBatch>,
+    /// Rows of the batches read so far
+    rows_read: usize,
+}
+
+impl<I: Iterator<Item = Result<RecordBatch>>> BatchCursor<I> {
+    fn new(batches: I) -> Self {
+        Self {
+            batches,
+            current: None,
+            rows_read: 0,
+        }
+    }
+
+    /// The rows of the current batch not compared yet, `None` at the end
+    fn current(&mut self) -> Result<Option<RecordBatch>> {
+        while self.current.as_ref().is_none_or(|b| b.num_rows() == 0) {
+            match self.batches.next().transpose()? {
+                Some(batch) => {
+                    self.rows_read += batch.num_rows();
+                    self.current = Some(batch);
+                }
+                None => return Ok(None),
+            }
+        }
+        Ok(self.current.clone())
+    }
+
+    fn advance(&mut self, rows: usize) {
+        if let Some(batch) = self.current.take() {
+            self.current = Some(batch.slice(rows, batch.num_rows() - rows));
+        }
+    }
+
+    /// Read the rest of the stream, returning the row count of the whole stream
+    fn count_remaining(&mut self) -> Result<usize> {
+        for batch in self.batches.by_ref() {
+            self.rows_read += batch?.num_rows();
+        }
+        Ok(self.rows_read)
+    }
+}
+
+fn compare_checksums(
+    left: &[RecordBatch],
+    right: &[RecordBatch],
+    options: &CompareOptions,
+) -> Result<Option<ResultDiff>> {
+    let left_checksums = column_checksums(left)?;
+    let right_checksums = column_checksums(right)?;
+    Ok(checksum_diff(
+        &left_checksums,
+        &right_checksums,
+        count_total_rows(left),
+        count_total_rows(right),
+        options,
+    ))
+}
+
+fn checksum_diff(
+    left_checksums: &[ColumnChecksum],
+    right_checksums: &[ColumnChecksum],
+    left_row_count: usize,
+    right_row_count: usize,
+    options: &CompareOptions,
+) -> Option<ResultDiff> {
+    let mut checksum_mismatches = Vec::new();
+    if left_checksums.len() != right_checksums.len() {
+        checksum_mismatches.push(format!(
+            "column count: left={}, right={}",
+            left_checksums.len(),
+            right_checksums.len()
+        ));
+    }
+    for (idx, (l, r)) in left_checksums.iter().zip(right_checksums).enumerate() {
+        checksum_mismatches.extend(
+            l.differences(r, options)
+                .into_iter()
+                .map(|difference| format!("column {}: {}", idx + 1, difference)),
+        );
+    }
+
+    if left_row_count == right_row_count && checksum_mismatches.is_empty() {
+        return None;
+    }
+
+    Some(ResultDiff {
+        ordered: false,
+        left_row_count,
+        right_row_count,
+        mismatches: Vec::new(),
+        checksum_mismatches,
+    })
+}
+
+fn count_total_rows(batches: &[RecordBatch]) -> usize {
+    batches.iter().map(RecordBatch::num_rows).sum()
+}
+
+/// Order-independent summary of one column.
+///
+/// Non-float values are hashed after normalization and folded with both xor
+/// and wrapping sum; floats are summed instead, since values within the float
+/// tolerance do not hash alike.
+#[derive(Debug, Default)]
+struct ColumnChecksum {
+    null_count: usize,
+    hash_xor: u64,
+    hash_sum: u64,
+    float_sum: f64,
+    float_abs_sum: f64,
+    /// Counts of NaN, +inf and -inf, which are kept out of the float sums
+    special_floats: [usize; 3],
+}
+
+impl ColumnChecksum {
+    fn update(&mut self, value: &NormalizedValue) {
+        match value {
+            NormalizedValue::Null => self.null_count += 1,
+            NormalizedValue::Float(v) if v.is_nan() => self.special_floats[0] += 1,
+            NormalizedValue::Float(v) if *v == f64::INFINITY => self.special_floats[1] += 1,
+            NormalizedValue::Float(v) if *v == f64::NEG_INFINITY => self.special_floats[2] += 1,
+            NormalizedValue::Float(v) => {
+                self.float_sum += v;
+                self.float_abs_sum += v.abs();
+            }
+            other => {
+                let mut hasher = DefaultHasher::new();
+                other.hash_exact(&mut hasher);
+                let hash = hasher.finish();
+                self.hash_xor ^= hash;
+                self.hash_sum = self.hash_sum.wrapping_add(hash);
+            }
+        }
+    }
+
+    fn differences(&self, other: &Self, options: &CompareOptions) -> Vec<String> {
+        let mut differences = Vec::new();
+        if self.null_count != other.null_count {
+            differences.push(format!(
+                "null count left={}, right={}",
+                self.null_count, other.null_count
+            ));
+        }
+        if self.hash_xor != other.hash_xor || self.hash_sum != other.hash_sum {
+            differences.push("value hash differs".to_string());
+        }
+        if self.special_floats != other.special_floats {
+            differences.push(format!(
+                "NaN/+inf/-inf counts left={:?}, right={:?}",
+                self.special_floats, other.special_floats
+            ));
+        }
+
+        // Accumulated rounding error grows with the magnitude of the summed values
+        let tolerance = options.float_abs_tolerance
+            + options.float_rel_tolerance * self.float_abs_sum.max(other.float_abs_sum);
+        if (self.float_sum - other.float_sum).abs() > tolerance {
+            differences.push(format!(
+                "float sum left={}, right={}",
+                self.float_sum, other.float_sum
+            ));
+        }
+
+        differences
+    }
+}
+
+fn column_checksums(batches: &[RecordBatch]) -> Result<Vec<ColumnChecksum>> {
+    let mut checksums: Option<Vec<ColumnChecksum>> = None;
+    for batch in batches {
+        update_column_checksums(&mut checksums, batch)?;
+    }
+    Ok(checksums.unwrap_or_default())
+}
+
+/// Column checksums and row count of a stream of batches
+fn stream_checksums(
+    batches: impl Iterator<Item = Result<RecordBatch>>,
+) -> Result<(Vec<ColumnChecksum>, usize)> {
+    let mut checksums: Option<Vec<ColumnChecksum>> = None;
+    let mut row_count = 0;
+    for batch in batches {
+        let batch = batch?;
+        row_count += batch.num_rows();
+        update_column_checksums(&mut checksums, &batch)?;
+    }
+    Ok((checksums.unwrap_or_default(), row_count))
+}
+
+fn update_column_checksums(
+    checksums: &mut Option<Vec<ColumnChecksum>>,
+    batch: &RecordBatch,
+) -> Result<()> {
+    let checksums = checksums.get_or_insert_with(|| {
+        (0..batch.num_columns())
+            .map(|_| ColumnChecksum::default())
+            .collect()
+    });
+    if batch.num_columns() != checksums.len() {
+        return Err(fuzzer_err(&format!(
+            "Mismatched column count across batches: expected {}, got {}",
+            checksums.len(),
+            batch.num_columns()
+        )));
+    }
+
+    for (column, checksum) in batch.columns().iter().zip(checksums.iter_mut()) {
+        update_column_checksum(checksum, column)?;
+    }
+    Ok(())
+}
+
+fn update_column_checksum(checksum: &mut ColumnChecksum, column: &ArrayRef) -> Result<()> {
+    for row_idx in 0..column.len() {
+        let value = ScalarValue::try_from_array(column, row_idx)
+            .map_err(|e| fuzzer_err(&format!("Failed to extract row {}: {}", row_idx, e)))?;
+        checksum.update(&NormalizedValue::from_scalar(&value));
+    }
+    Ok(())
+}
+
+/// Type-independent representation of a single value.
+#[derive(Debug, Clone)]
+enum NormalizedValue {
+    Null,
+    Boolean(bool),
+    Integer(i128),
+    Float(f64),
+    /// Decimal with trailing zeros stripped from the mantissa
+    Decimal {
+        mantissa: i128,
+        scale: i8,
+    },
+    /// Nanoseconds since the epoch, in UTC
+    Timestamp(i128),
+    Utf8(String),
+    Binary(Vec<u8>),
+    /// Fallback for types without a dedicated normalization
+    Other(String),
+}
+
+impl NormalizedValue {
+    fn from_scalar(value: &ScalarValue) -> Self {
+        if value.is_null() {
+            return Self::Null;
+        }
+
+        match value {
+            ScalarValue::Boolean(Some(v)) => Self::Boolean(*v),
+            ScalarValue::Int8(Some(v)) => Self::Integer(*v as i128),
+            ScalarValue::Int16(Some(v)) => Self::Integer(*v as i128),
+            ScalarValue::Int32(Some(v)) => Self::Integer(*v as i128),
+            ScalarValue::Int64(Some(v)) => Self::Integer(*v as i128),
+            ScalarValue::UInt8(Some(v)) => Self::Integer(*v as i128),
+            ScalarValue::UInt16(Some(v)) => Self::Integer(*v as i128),
+            ScalarValue::UInt32(Some(v)) => Self::Integer(*v as i128),
+            ScalarValue::UInt64(Some(v)) => Self::Integer(*v as i128),
+            ScalarValue::Float16(Some(v)) => Self::Float(v.to_f64()),
+            ScalarValue::Float32(Some(v)) => Self::Float(*v as f64),
+            ScalarValue::Float64(Some(v)) => Self::Float(*v),
+            ScalarValue::Decimal128(Some(v), _, scale) => Self::decimal(*v, *scale),
+            ScalarValue::Decimal256(Some(v), _, scale) => match v.to_i128() {
+                Some(v) => Self::decimal(v, *scale),
+                None => Self::Other(value.to_string()),
+            },
+            ScalarValue::TimestampSecond(Some(v), _) => Self::Timestamp(*v as i128 * 1_000_000_000),
+            ScalarValue::TimestampMillisecond(Some(v), _) => {
+                Self::Timestamp(*v as i128 * 1_000_000)
+            }
+            ScalarValue::TimestampMicrosecond(Some(v), _) => Self::Timestamp(*v as i128 * 1_000),
+            ScalarValue::TimestampNanosecond(Some(v), _) => Self::Timestamp(*v as i128),
+            ScalarValue::Utf8(Some(v))
+            | ScalarValue::LargeUtf8(Some(v))
+            | ScalarValue::Utf8View(Some(v)) => Self::Utf8(v.clone()),
+            ScalarValue::Binary(Some(v))
+            | ScalarValue::LargeBinary(Some(v))
+            | ScalarValue::BinaryView(Some(v))
+            | ScalarValue::FixedSizeBinary(_, Some(v)) => Self::Binary(v.clone()),
+            ScalarValue::Dictionary(_, inner) => Self::from_scalar(inner),
+            other => Self::Other(other.to_string()),
+        }
+    }
+
+    fn decimal(mut mantissa: i128, mut scale: i8) -> Self {
+        while scale > 0 && mantissa % 10 == 0 {
+            mantissa /= 10;
+            scale -= 1;
+        }
+        if mantissa == 0 {
+            scale = 0;
+        }
+        Self::Decimal { mantissa, scale }
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            Self::Null => 0,
+            Self::Boolean(_) => 1,
+            Self::Integer(_) => 2,
+            Self::Float(_) => 3,
+            Self::Decimal { .. } => 4,
+            Self::Timestamp(_) => 5,
+            Self::Utf8(_) => 6,
+            Self::Binary(_) => 7,
+            Self::Other(_) => 8,
+        }
+    }
+
+    /// Total order used to sort rows before an unordered comparison.
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Boolean(a), Self::Boolean(b)) => a.cmp(b),
+            (Self::Integer(a), Self::Integer(b)) => a.cmp(b),
+            (Self::Float(a), Self::Float(b)) => a.total_cmp(b),
+            (
+                Self::Decimal {
+                    mantissa: a,
+                    scale: a_scale,
+                },
+                Self::Decimal {
+                    mantissa: b,
+                    scale: b_scale,
+                },
+            ) => decimal_to_f64(*a, *a_scale)
+                .total_cmp(&decimal_to_f64(*b, *b_scale))
+                .then(a_scale.cmp(b_scale))
+                .then(a.cmp(b)),
+            (Self::Timestamp(a), Self::Timestamp(b)) => a.cmp(b),
+            (Self::Utf8(a), Self::Utf8(b)) => a.cmp(b),
+            (Self::Binary(a), Self::Binary(b)) => a.cmp(b),
+            (Self::Other(a), Self::Other(b)) => a.cmp(b),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+
+    /// Hash non-float values; floats are handled separately by [`ColumnChecksum`].
+    fn hash_exact<H: Hasher>(&self, state: &mut H) {
+        self.rank().hash(state);
+        match self {
+            Self::Null => {}
+            Self::Boolean(v) => v.hash(state),
+            Self::Integer(v) | Self::Timestamp(v) => v.hash(state),
+            Self::Float(v) => v.to_bits().hash(state),
+            Self::Decimal { mantissa, scale } => {
+                mantissa.hash(state);
+                scale.hash(state);
+            }
+            Self::Utf8(v) | Self::Other(v) => v.hash(state),
+            Self::Binary(v) => v.hash(state),
+        }
+    }
+
+    fn matches(&self, other: &Self, options: &CompareOptions) -> bool {
+        match (self, other) {
+            (Self::Float(a), Self::Float(b)) => options.floats_match(*a, *b),
+            _ => self.total_cmp(other) == Ordering::Equal,
+        }
+    }
+}
+
+fn decimal_to_f64(mantissa: i128, scale: i8) -> f64 {
+    mantissa as f64 / 10f64.powi(scale as i32)
+}
+
+struct NormalizedRow {
+    values: Vec<NormalizedValue>,
+    display: String,
+}
+
+fn normalize_batches(batches: &[RecordBatch]) -> Result<Vec<NormalizedRow>> {
+    let mut rows = Vec::new();
+    let mut expected_num_cols: Option<usize> = None;
+
+    for batch in batches {
+        match expected_num_cols {
+            Some(expected) if batch.num_columns() != expected => {
+                return Err(fuzzer_err(&format!(
+                    "Mismatched column count across batches: expected {}, got {}",
+                    expected,
+                    batch.num_columns()
+                )));
+            }
+            Some(_) => {}
+            None => expected_num_cols = Some(batch.num_columns()),
+        }
+
+        for row_idx in 0..batch.num_rows() {
+            let row = get_row_at_idx(batch.columns(), row_idx)
+                .map_err(|e| fuzzer_err(&format!("Failed to extract row {}: {}", row_idx, e)))?;
+            rows.push(NormalizedRow {
+                values: row.iter().map(NormalizedValue::from_scalar).collect(),
+                display: format_row(&row),
+            });
+        }
+    }
+
+    Ok(rows)
+}
+
+fn format_row(row: &[ScalarValue]) -> String {
+    let values = row
+        .iter()
+        .map(|v| {
+            if v.is_null() {
+                "NULL".to_string()
+            } else {
+                v.to_string()
+            }
+        })
+        .collect::<Vec<_>>();
+    format!("({})", values.join(", "))
+}
+
+fn compare_rows(left: &[NormalizedValue], right: &[NormalizedValue]) -> Ordering {
+    left.iter()
+        .zip(right)
+        .map(|(l, r)| l.total_cmp(r))
+        .find(|ord| ord.is_ne())
+        .unwrap_or_else(|| left.len().cmp(&right.len()))
+}
+
+fn rows_match(
+    left: &[NormalizedValue],
+    right: &[NormalizedValue],
+    options: &CompareOptions,
+) -> bool {
+    left.len() == right.len() && left.iter().zip(right).all(|(l, r)| l.matches(r, options))
+}
+
+fn diff_ordered(
+    left: &[NormalizedRow],
+    right: &[NormalizedRow],
+    options: &CompareOptions,
+) -> Vec<RowMismatch> {
+    let mut mismatches = Vec::new();
+    for position in 0..left.len().max(right.len()) {
+        let l = left.get(position);
+        let r = right.get(position);
+        let equal = match (l, r) {
+            (Some(l), Some(r)) => rows_match(&l.values, &r.values, options),
+            _ => false,
+        };
+        if !equal {
+            mismatches.push(RowMismatch {
+                position: Some(position),
+                left: l.map(|row| row.display.clone()),
+                right: r.map(|row| row.display.clone()),
+            });
+        }
+    }
+    mismatches
+}
+
+/// Merge two sorted row lists, collecting rows that only appear on one side.
+fn diff_unordered(
+    left: &[NormalizedRow],
+    right: &[NormalizedRow],
+    options: &CompareOptions,
+) -> Vec<RowMismatch> {
+    let only_left = |row: &NormalizedRow| RowMismatch {
+        position: None,
+        left: Some(row.display.clone()),
+        right: None,
+    };
+    let only_right = |row: &NormalizedRow| RowMismatch {
+        position: None,
+        left: None,
+        right: Some(row.display.clone()),
+    };
+
+    let mut mismatches = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < left.len() && j < right.len() {
+        if rows_match(&left[i].values, &right[j].values, options) {
+            i += 1;
+            j += 1;
+        } else if compare_rows(&left[i].values, &right[j].values) == Ordering::Less {
+            mismatches.push(only_left(&left[i]));
+            i += 1;
+        } else {
+            mismatches.push(only_right(&right[j]));
+            j += 1;
+        }
+    }
+    mismatches.extend(left[i..].iter().map(only_left));
+    mismatches.extend(right[j..].iter().map(only_right));
+
+    mismatches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracle::test_helpers;
+    use datafusion::arrow::array::{
+        Array, Decimal128Array, Float64Array, Int32Array, TimestampMillisecondArray,
+        TimestampSecondArray,
+    };
+    use datafusion::arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+    use std::sync::Arc;
+
+    fn single_column_batch(data_type: DataType, array: Arc<dyn Array>) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![Field::new("c1", data_type, true)]));
+        RecordBatch::try_new(schema, vec![array]).unwrap()
+    }
+
+    #[test]
+    fn unordered_compare_ignores_row_order() {
+        let left = test_helpers::make_one_col_batch(vec![1, 2, 3]);
+        let right = test_helpers::make_one_col_batch(vec![3, 1, 2]);
+
+        let diff = compare_batches(&[left], &[right], &CompareOptions::default()).unwrap();
+        assert!(diff.is_none());
+    }
+
+    #[test]
+    fn ordered_compare_reports_positions() {
+        let left = test_helpers::make_one_col_batch(vec![1, 2]);
+        let right = test_helpers::make_one_col_batch(vec![2, 1]);
+
+        let options = CompareOptions::default().with_ordered(true);
+        let diff = compare_batches(&[left], &[right], &options)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(diff.mismatches.len(), 2);
+        assert!(diff.to_string().contains("row 1: left=(1), right=(2)"));
+    }
+
+    #[test]
+    fn unordered_compare_reports_multiset_difference() {
+        let left = test_helpers::make_one_col_batch(vec![1, 2]);
+        let right = test_helpers::make_one_col_batch(vec![1, 2, 2]);
+
+        let diff = compare_batches(&[left], &[right], &CompareOptions::default())
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(diff.left_row_count, 2);
+        assert_eq!(diff.right_row_count, 3);
+        assert_eq!(
+            diff.mismatches,
+            vec![RowMismatch {
+                position: None,
+                left: None,
+                right: Some("(2)".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn side_by_side_pairs_unmatched_rows() {
+        let left = test_helpers::make_one_col_batch(vec![1, 2, 3]);
+        let right = test_helpers::make_one_col_batch(vec![1, 4, 5, 6]);
+
+        let diff = compare_batches(&[left], &[right], &CompareOptions::default())
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(
+            diff.format_side_by_side("all", "union", 2),
+            [
+                "Row counts: all=3, union=4",
+                "# | all | union",
+                "--+-----+------",
+                "1 | (2) | (4)",
+                "2 | (3) | (5)",
+                "... 1 more mismatched rows",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn side_by_side_shows_positions_for_ordered_diffs() {
+        let left = test_helpers::make_one_col_batch(vec![1, 2, 3]);
+        let right = test_helpers::make_one_col_batch(vec![1, 3]);
+
+        let options = CompareOptions::default().with_ordered(true);
+        let diff = compare_batches(&[left], &[right], &options)
+            .unwrap()
+            .unwrap();
+
+        let table = diff.format_side_by_side("left", "right", 10);
+        assert!(table.contains("row | left | right"));
+        assert!(table.contains("  2 | (2)  | (3)"));
+        assert!(table.contains("  3 | (3)  | <missing>"));
+    }
+
+    #[test]
+    fn floats_compare_with_tolerance() {
+        let left = single_column_batch(
+            DataType::Float64,
+            Arc::new(Float64Array::from(vec![
+                Some(0.1 + 0.2),
+                Some(f64::NAN),
+                Some(-0.0),
+                None,
+            ])),
+        );
+        let right = single_column_batch(
+            DataType::Float64,
+            Arc::new(Float64Array::from(vec![
+                Some(0.3),
+                Some(f64::NAN),
+                Some(0.0),
+                None,
+            ])),
+        );
+        assert!(
+            compare_batches(
+                &[left.clone()],
+                &[right.clone()],
+                &CompareOptions::default()
+            )
+            .unwrap()
+            .is_none()
+        );
+
+        let exact = CompareOptions::default().with_float_tolerance(0.0, 0.0);
+        assert!(
+            compare_batches(&[left], &[right], &exact)
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn decimals_compare_after_rescaling() {
+        let left = single_column_batch(
+            DataType::Decimal128(10, 2),
+            Arc::new(
+                Decimal128Array::from(vec![150, 0])
+                    .with_precision_and_scale(10, 2)
+                    .unwrap(),
+            ),
+        );
+        let right = single_column_batch(
+            DataType::Decimal128(12, 4),
+            Arc::new(
+                Decimal128Array::from(vec![15000, 0])
+                    .with_precision_and_scale(12, 4)
+                    .unwrap(),
+            ),
+        );
+
+        assert!(
+            compare_batches(&[left], &[right], &CompareOptions::default())
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn averages_match_sums_within_type_tolerance() {
+        let options = CompareOptions::default();
+        let avg = ScalarValue::Float64(Some(0.1 + 0.2));
+        assert!(average_matches(&avg, &ScalarValue::Float64(Some(0.9)), 3, 0.9, &options).unwrap());
+        let avg = ScalarValue::Float64(Some(0.4));
+        assert!(
+            !average_matches(&avg, &ScalarValue::Float64(Some(0.9)), 3, 0.9, &options).unwrap()
+        );
+
+        // Cancellation: the error is relative to the summed magnitudes
+        let avg = ScalarValue::Float64(Some(1.0));
+        let sum = ScalarValue::Float64(Some(0.0));
+        assert!(average_matches(&avg, &sum, 2, 1e17, &options).unwrap());
+        assert!(!average_matches(&avg, &sum, 2, 2.0, &options).unwrap());
+
+        // AVG of DECIMAL(10, 2) is rounded to 6 digits: 10 / 3 = 3.333333
+        let avg = ScalarValue::Decimal128(Some(3_333_333), 14, 6);
+        let sum = ScalarValue::Decimal128(Some(1000), 20, 2);
+        assert!(average_matches(&avg, &sum, 3, 10.0, &options).unwrap());
+        let avg = ScalarValue::Decimal128(Some(3_333_400), 14, 6);
+        assert!(!average_matches(&avg, &sum, 3, 10.0, &options).unwrap());
+
+        let null = ScalarValue::Float64(None);
+        assert!(average_matches(&null, &ScalarValue::Int64(None), 0, 0.0, &options).unwrap());
+        assert!(!average_matches(&null, &ScalarValue::Int64(Some(1)), 1, 1.0, &options).unwrap());
+        assert!(
+            average_matches(&avg, &ScalarValue::Utf8(Some("a".into())), 1, 1.0, &options).is_err()
+        );
+    }
+
+    #[test]
+    fn sums_match_within_the_rounding_error() {
+        let options = CompareOptions::default();
+        let left = ScalarValue::Float64(Some(1.0));
+        let right = ScalarValue::Float64(Some(0.0));
+        assert!(sums_match(&left, &right, 1e17, &options));
+        assert!(!sums_match(&left, &right, 2.0, &options));
+
+        // Integer and decimal sums are exact
+        let left = ScalarValue::Int64(Some(3));
+        assert!(sums_match(
+            &left,
+            &ScalarValue::Int32(Some(3)),
+            1e17,
+            &options
+        ));
+        assert!(!sums_match(
+            &left,
+            &ScalarValue::Int64(Some(4)),
+            1e17,
+            &options
+        ));
+        assert!(sums_match(
+            &ScalarValue::Int64(None),
+            &ScalarValue::Int64(None),
+            0.0,
+            &options
+        ));
+    }
+
+    #[test]
+    fn timestamps_compare_by_instant() {
+        let left = single_column_batch(
+            DataType::Timestamp(TimeUnit::Second, Some("+08:00".into())),
+            Arc::new(TimestampSecondArray::from(vec![1_000]).with_timezone("+08:00")),
+        );
+        let right = single_column_batch(
+            DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into())),
+            Arc::new(TimestampMillisecondArray::from(vec![1_000_000]).with_timezone("UTC")),
+        );
+
+        assert!(
+            compare_batches(&[left], &[right], &CompareOptions::default())
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn checksum_mode_detects_value_and_count_differences() {
+        let options = CompareOptions::default().with_mode(CompareMode::Checksum { min_rows: 1 });
+
+        let left = test_helpers::make_one_col_batch(vec![1, 2, 3]);
+        let shuffled = test_helpers::make_one_col_batch(vec![3, 2, 1]);
+        assert!(
+            compare_batches(&[left.clone()], &[shuffled], &options)
+                .unwrap()
+                .is_none()
+        );
+
+        let changed = test_helpers::make_one_col_batch(vec![1, 2, 4]);
+        let diff = compare_batches(&[left.clone()], &[changed], &options)
+            .unwrap()
+            .unwrap();
+        assert!(diff.mismatches.is_empty());
+        assert_eq!(
+            diff.checksum_mismatches,
+            vec!["column 1: value hash differs".to_string()]
+        );
+
+        let longer = test_helpers::make_one_col_batch(vec![1, 2, 3, 3]);
+        let diff = compare_batches(&[left], &[longer], &options)
+            .unwrap()
+            .unwrap();
+        assert_eq!((diff.left_row_count, diff.right_row_count), (3, 4));
+    }
+
+    #[test]
+    fn ordered_streams_compare_across_batch_boundaries() {
+        let options = CompareOptions::default().with_ordered(true);
+        let batches = |chunks: Vec<Vec<i64>>| {
+            chunks
+                .into_iter()
+                .map(|values| Ok(test_helpers::make_one_col_batch(values)))
+                .collect::<Vec<_>>()
+                .into_iter()
+        };
+
+        let left = || batches(vec![vec![1, 2, 3], vec![4, 5]]);
+        let rechunked = batches(vec![vec![1], vec![], vec![2, 3, 4], vec![5]]);
+        assert!(
+            compare_batch_streams(left(), rechunked, &options)
+                .unwrap()
+                .is_none()
+        );
+
+        let changed = batches(vec![vec![1, 2], vec![3, 9, 5]]);
+        let diff = compare_batch_streams(left(), changed, &options)
+            .unwrap()
+            .unwrap();
+        assert_eq!(diff.mismatches.len(), 1);
+        assert_eq!(diff.mismatches[0].position, Some(3));
+
+        let longer = batches(vec![vec![1, 2, 3, 4, 5], vec![6]]);
+        let diff = compare_batch_streams(left(), longer, &options)
+            .unwrap()
+            .unwrap();
+        assert!(diff.mismatches.is_empty());
+        assert_eq!((diff.left_row_count, diff.right_row_count), (5, 6));
+    }
+
+    #[test]
+    fn unordered_streams_compare_checksums() {
+        let options = CompareOptions::default();
+        let stream =
+            |values: Vec<i64>| std::iter::once(Ok(test_helpers::make_one_col_batch(values)));
+
+        let shuffled =
+            compare_batch_streams(stream(vec![1, 2, 3]), stream(vec![3, 1, 2]), &options);
+        assert!(shuffled.unwrap().is_none());
+
+        let diff = compare_batch_streams(stream(vec![1, 2, 3]), stream(vec![1, 2, 4]), &options)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            diff.checksum_mismatches,
+            vec!["column 1: value hash differs".to_string()]
+        );
+    }
+
+    #[test]
+    fn checksum_mode_falls_back_to_full_compare_for_small_results() {
+        let options = CompareOptions::default().with_mode(CompareMode::Checksum { min_rows: 100 });
+        let left = test_helpers::make_one_col_batch(vec![1, 2]);
+        let right = test_helpers::make_one_col_batch(vec![1, 3]);
+
+        let diff = compare_batches(&[left], &[right], &options)
+            .unwrap()
+            .unwrap();
+        assert!(diff.checksum_mismatches.is_empty());
+        assert_eq!(diff.mismatches.len(), 2);
+    }
+
+    #[test]
+    fn checksum_mode_sums_floats_with_tolerance() {
+        let options = CompareOptions::default().with_mode(CompareMode::Checksum { min_rows: 1 });
+        let left = single_column_batch(
+            DataType::Float64,
+            Arc::new(Float64Array::from(vec![Some(0.1), Some(0.2), None])),
+        );
+        let right = single_column_batch(
+            DataType::Float64,
+            Arc::new(Float64Array::from(vec![
+                None,
+                Some(0.30000000000000004),
+                Some(0.0),
+            ])),
+        );
+
+        assert!(
+            compare_batches(&[left], &[right], &options)
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn nulls_of_different_types_are_equal() {
+        let left = single_column_batch(
+            DataType::Int32,
+            Arc::new(Int32Array::from(vec![None, Some(1)])),
+        );
+        let right = single_column_batch(
+            DataType::Float64,
+            Arc::new(Float64Array::from(vec![None, Some(1.0)])),
+        );
+
+        let diff = compare_batches(&[left], &[right], &CompareOptions::default())
+            .unwrap()
+            .unwrap();
+        // NULLs match, but an integer never equals a float
+        assert_eq!(diff.mismatches.len(), 2);
+        assert!(diff.to_string().contains("only in left:  (1)"));
+    }
+}
@@ -1,9 +1,13 @@
-use crate::common::fuzzer_err;
+use crate::common::value_summary::ColumnValueSummary;
+use crate::common::{FuzzerDataType, LogicalColumn, LogicalTable, fuzzer_err};
+use crate::fuzz_context::{GlobalContext, RunnerConfig, RuntimeContext};
+use crate::fuzz_runner::FuzzerStats;
 use crate::oracle::{QueryContext, QueryExecutionResult};
 use datafusion::arrow::array::{Array, Int64Array, RecordBatch};
 use datafusion::arrow::datatypes::{DataType, Field, Schema};
 use datafusion::prelude::SessionContext;
-use std::sync::Arc;
+use datafusion::scalar::ScalarValue;
+use std::sync::{Arc, Mutex};
 
 pub(crate) fn make_query_context(label: &str) -> Arc<QueryContext> {
     Arc::new(QueryContext::new(
@@ -28,6 +32,28 @@ pub(crate) fn make_success_result(
     QueryExecutionResult {
         query_context: make_query_context(label),
         result: Ok(vec![batch]),
+        truncated: None,
+    }
+}
+
+/// [`make_success_result`] with a nullable column
+pub(crate) fn make_nullable_result(
+    label: &str,
+    column_name: &str,
+    values: Vec<Option<i64>>,
+) -> QueryExecutionResult {
+    let schema = Arc::new(Schema::new(vec![Field::new(
+        column_name,
+        DataType::Int64,
+        true,
+    )]));
+    let array = Arc::new(Int64Array::from(values)) as Arc<dyn Array>;
+    let batch = RecordBatch::try_new(schema, vec![array]).unwrap();
+
+    QueryExecutionResult {
+        query_context: make_query_context(label),
+        result: Ok(vec![batch]),
+        truncated: None,
     }
 }
 
@@ -35,6 +61,7 @@ pub(crate) fn make_error_result(label: &str) -> QueryExecutionResult {
     QueryExecutionResult {
         query_context: make_query_context(label),
         result: Err(fuzzer_err("expected execution error in test")),
+        truncated: None,
     }
 }
 
@@ -61,3 +88,66 @@ pub(crate) fn make_two_col_batch(col1: Vec<i64>, col2: Vec<i64>) -> RecordBatch
     )
     .unwrap()
 }
+
+/// Table `t1` with an `Int64` column `c0` holding `values`, as if generated
+pub(crate) fn table_with_int_values(values: &[Option<i64>]) -> LogicalTable {
+    let mut summary = ColumnValueSummary::default();
+    for value in values {
+        summary.record(ScalarValue::Int64(*value));
+    }
+    LogicalTable::with_columns(
+        "t1".to_string(),
+        vec![LogicalColumn {
+            name: "c0".to_string(),
+            data_type: FuzzerDataType::Int64,
+            value_summary: Some(Arc::new(summary)),
+        }],
+    )
+}
+
+/// Context of a fuzzer run with `config` and no tables yet
+pub(crate) fn context_with_config(config: RunnerConfig) -> Arc<GlobalContext> {
+    let fuzzer_stats = Arc::new(Mutex::new(FuzzerStats::new(config.rounds)));
+    Arc::new(GlobalContext::new(
+        config,
+        RuntimeContext::default(),
+        fuzzer_stats,
+    ))
+}
+
+/// Register [`table_with_int_values`] in the fuzzer, and create it with the
+/// same rows in the session
+pub(crate) async fn register_int_table(ctx: &GlobalContext, values: &[Option<i64>]) {
+    ctx.runtime_context
+        .register_table(Arc::new(table_with_int_values(values)));
+    let rows = values
+        .iter()
+        .map(|value| match value {
+            Some(value) => format!("({value})"),
+            None => "(NULL)".to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    ctx.runtime_context
+        .get_session_context()
+        .sql(&format!("CREATE TABLE t1(c0 BIGINT) AS VALUES {rows}"))
+        .await
+        .unwrap();
+}
+
+/// Execute the queries of a group one after the other on their engine
+pub(crate) async fn run_group(query_group: Vec<QueryContext>) -> Vec<QueryExecutionResult> {
+    let mut results = Vec::new();
+    for query_context in query_group {
+        let result = query_context
+            .engine_under_test()
+            .execute_sql(&query_context.query)
+            .await;
+        results.push(QueryExecutionResult {
+            query_context: Arc::new(query_context),
+            result,
+            truncated: None,
+        });
+    }
+    results
+}
@@ -0,0 +1,475 @@
+//! Random chains of `DataFrame` operations and their equivalent SQL.
+//!
+//! A chain starts from a registered table and applies filters, projections,
+//! aggregations, sorts with limits and joins one after another, like programs
+//! using the `DataFrame` API do. Each operation corresponds to one level of
+//! nested SQL over the previous one:
+//!
+//! ```text
+//! ctx.table("t0")?.filter(c0 > 1)?.aggregate([c1], [count(c0)])?
+//!
+//! SELECT c1, count(c0) AS a2_0
+//! FROM (SELECT * FROM (SELECT * FROM t0) AS q1 WHERE c0 > 1) AS q2
+//! GROUP BY c1
+//! ```
+//!
+//! Columns are referenced by unqualified names, which stay unique: output
+//! columns of a step are aliased by the step number, and the columns of a
+//! joined table are renamed.
+
+use std::sync::Arc;
+
+use datafusion::arrow::datatypes::DataType;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::common::Column;
+use datafusion::functions_aggregate::expr_fn::{count, max, min};
+use datafusion::logical_expr::JoinType;
+use datafusion::prelude::{Expr, SessionContext};
+use rand::{Rng, RngCore, rngs::StdRng};
+
+use crate::common::util::{quote_identifier, to_sql_string};
+use crate::common::{LogicalTable, Result, fuzzer_err, rng::rng_from_seed};
+use crate::engine::dataframe_engine::DataFrameQuery;
+use crate::fuzz_context::GlobalContext;
+
+use super::expr_gen::ExprGenerator;
+use super::stmt_select_def::worst_case_join_rows;
+
+/// Maximum number of operations of a chain
+const MAX_CHAIN_LENGTH: usize = 5;
+
+/// Maximum number of joins of a chain, when `join_row_budget` doesn't bound
+/// them
+const MAX_CHAIN_JOINS: usize = 2;
+
+/// Join types of the chains. Semi and anti joins are left out, they drop the
+/// columns of one side later operations may reference.
+const JOIN_TYPES: [JoinType; 4] = [
+    JoinType::Inner,
+    JoinType::Left,
+    JoinType::Right,
+    JoinType::Full,
+];
+
+/// One operation of a [`DataFrameChain`]
+#[derive(Debug, Clone)]
+pub enum DataFrameOp {
+    /// `DataFrame::filter`
+    Filter(Expr),
+    /// `DataFrame::select` of aliased expressions
+    Select(Vec<(Expr, String)>),
+    /// `DataFrame::aggregate` of aliased aggregates, grouped by columns
+    Aggregate {
+        group_by: Vec<String>,
+        aggregates: Vec<(Expr, String)>,
+    },
+    /// `DataFrame::sort` by all `columns`, ascending with NULLs first, then
+    /// `DataFrame::limit` to `fetch` rows. Sorting by every column makes the
+    /// rows kept by the limit deterministic.
+    Sort {
+        columns: Vec<String>,
+        fetch: Option<usize>,
+    },
+    /// `DataFrame::join_on` with `table`, its columns renamed
+    /// (`(column, alias)`) to keep names unique
+    Join {
+        table: Arc<LogicalTable>,
+        renamed: Vec<(String, String)>,
+        join_type: JoinType,
+        on: Expr,
+    },
+}
+
+/// A table and the `DataFrame` operations applied to it
+#[derive(Debug, Clone)]
+pub struct DataFrameChain {
+    table: Arc<LogicalTable>,
+    ops: Vec<DataFrameOp>,
+}
+
+impl DataFrameChain {
+    /// The equivalent SQL: one nested query per operation
+    pub fn to_sql(&self) -> Result<String> {
+        let mut sql = format!("SELECT * FROM {}", self.table.sql_name());
+        for (step, op) in self.ops.iter().enumerate() {
+            let from = format!("({}) AS q{}", sql, step + 1);
+            sql = match op {
+                DataFrameOp::Filter(predicate) => {
+                    format!("SELECT * FROM {} WHERE {}", from, to_sql_string(predicate)?)
+                }
+                DataFrameOp::Select(exprs) => {
+                    format!("SELECT {} FROM {}", aliased_sql(exprs)?, from)
+                }
+                DataFrameOp::Aggregate {
+                    group_by,
+                    aggregates,
+                } => {
+                    let group_by: Vec<String> =
+                        group_by.iter().map(|name| quote_identifier(name)).collect();
+                    let mut select_list = group_by.clone();
+                    select_list.push(aliased_sql(aggregates)?);
+                    let mut sql = format!("SELECT {} FROM {}", select_list.join(", "), from);
+                    if !group_by.is_empty() {
+                        sql.push_str(&format!(" GROUP BY {}", group_by.join(", ")));
+                    }
+                    sql
+                }
+                DataFrameOp::Sort { columns, fetch } => {
+                    let order_by: Vec<String> = columns
+                        .iter()
+                        .map(|name| format!("{} ASC NULLS FIRST", quote_identifier(name)))
+                        .collect();
+                    let mut sql =
+                        format!("SELECT * FROM {} ORDER BY {}", from, order_by.join(", "));
+                    if let Some(fetch) = fetch {
+                        sql.push_str(&format!(" LIMIT {}", fetch));
+                    }
+                    sql
+                }
+                DataFrameOp::Join {
+                    table,
+                    renamed,
+                    join_type,
+                    on,
+                } => {
+                    let renamed_sql: Vec<String> = renamed
+                        .iter()
+                        .map(|(name, alias)| {
+                            format!("{} AS {}", quote_identifier(name), quote_identifier(alias))
+                        })
+                        .collect();
+                    format!(
+                        "SELECT * FROM {} {} (SELECT {} FROM {}) AS r{} ON {}",
+                        from,
+                        join_keyword(*join_type),
+                        renamed_sql.join(", "),
+                        table.sql_name(),
+                        step + 1,
+                        to_sql_string(on)?
+                    )
+                }
+            };
+        }
+        Ok(sql)
+    }
+}
+
+#[async_trait::async_trait]
+impl DataFrameQuery for DataFrameChain {
+    async fn execute(&self, session_context: &SessionContext) -> Result<Vec<RecordBatch>> {
+        let mut df = session_context.table(self.table.table_reference()).await?;
+        for op in &self.ops {
+            df = match op {
+                DataFrameOp::Filter(predicate) => df.filter(predicate.clone())?,
+                DataFrameOp::Select(exprs) => df.select(aliased(exprs))?,
+                DataFrameOp::Aggregate {
+                    group_by,
+                    aggregates,
+                } => df.aggregate(
+                    group_by.iter().map(|name| column(name)).collect(),
+                    aliased(aggregates),
+                )?,
+                DataFrameOp::Sort { columns, fetch } => {
+                    let sorted = df.sort(
+                        columns
+                            .iter()
+                            .map(|name| column(name).sort(true, true))
+                            .collect(),
+                    )?;
+                    match fetch {
+                        Some(fetch) => sorted.limit(0, Some(*fetch))?,
+                        None => sorted,
+                    }
+                }
+                DataFrameOp::Join {
+                    table,
+                    renamed,
+                    join_type,
+                    on,
+                } => {
+                    let right = session_context
+                        .table(table.table_reference())
+                        .await?
+                        .select(
+                            renamed
+                                .iter()
+                                .map(|(name, alias)| column(name).alias(alias))
+                                .collect::<Vec<_>>(),
+                        )?;
+                    df.join_on(right, *join_type, vec![on.clone()])?
+                }
+            };
+        }
+        Ok(df.collect().await?)
+    }
+}
+
+fn column(name: &str) -> Expr {
+    Expr::Column(Column::new_unqualified(name))
+}
+
+fn aliased(exprs: &[(Expr, String)]) -> Vec<Expr> {
+    exprs
+        .iter()
+        .map(|(expr, alias)| expr.clone().alias(alias))
+        .collect()
+}
+
+fn aliased_sql(exprs: &[(Expr, String)]) -> Result<String> {
+    let exprs: Result<Vec<String>> = exprs
+        .iter()
+        .map(|(expr, alias)| {
+            Ok(format!(
+                "{} AS {}",
+                to_sql_string(expr)?,
+                quote_identifier(alias)
+            ))
+        })
+        .collect();
+    Ok(exprs?.join(", "))
+}
+
+fn join_keyword(join_type: JoinType) -> &'static str {
+    match join_type {
+        JoinType::Left => "LEFT JOIN",
+        JoinType::Right => "RIGHT JOIN",
+        JoinType::Full => "FULL JOIN",
+        _ => "JOIN",
+    }
+}
+
+/// Generates [`DataFrameChain`]s over the tables of the registry
+pub struct DataFrameChainGenerator {
+    rng: StdRng,
+    ctx: Arc<GlobalContext>,
+}
+
+impl DataFrameChainGenerator {
+    pub fn new(seed: u64, ctx: Arc<GlobalContext>) -> Self {
+        Self {
+            rng: rng_from_seed(seed),
+            ctx,
+        }
+    }
+
+    pub fn generate_chain(&mut self) -> Result<DataFrameChain> {
+        // The registry is ordered by name, so the choice is deterministic
+        let tables: Vec<Arc<LogicalTable>> = self
+            .ctx
+            .runtime_context
+            .tables()
+            .values()
+            .filter(|table| !table.columns.is_empty())
+            .cloned()
+            .collect();
+        if tables.is_empty() {
+            return Err(fuzzer_err(
+                "No available tables registered inside fuzzer context.",
+            ));
+        }
+
+        let table = Arc::clone(&tables[self.rng.random_range(0..tables.len())]);
+        let mut columns: Vec<(String, DataType)> = table
+            .columns
+            .iter()
+            .map(|column| (column.name.clone(), column.data_type.to_datafusion_type()))
+            .collect();
+        let mut joined_tables = vec![Arc::clone(&table)];
+
+        let chain_length = self.rng.random_range(1..=MAX_CHAIN_LENGTH);
+        let mut ops = Vec::with_capacity(chain_length);
+        for step in 1..=chain_length {
+            let op = match self.rng.random_range(0..10) {
+                0..=2 => DataFrameOp::Filter(
+                    self.expr_generator(&columns)
+                        .generate_random_expr(DataType::Boolean, 0),
+                ),
+                3..=4 => self.generate_select(step, &mut columns),
+                5..=6 => self.generate_aggregate(step, &mut columns),
+                7 => DataFrameOp::Sort {
+                    columns: columns.iter().map(|(name, _)| name.clone()).collect(),
+                    fetch: self
+                        .rng
+                        .random_bool(0.7)
+                        .then(|| self.rng.random_range(0..=10)),
+                },
+                _ => {
+                    let join_table = Arc::clone(&tables[self.rng.random_range(0..tables.len())]);
+                    if !self.may_join(&joined_tables, &join_table) {
+                        continue;
+                    }
+                    joined_tables.push(Arc::clone(&join_table));
+                    self.generate_join(step, join_table, &mut columns)
+                }
+            };
+            ops.push(op);
+        }
+
+        Ok(DataFrameChain { table, ops })
+    }
+
+    fn expr_generator(&mut self, columns: &[(String, DataType)]) -> ExprGenerator {
+        let typed_columns = columns
+            .iter()
+            .map(|(name, data_type)| (Column::new_unqualified(name), data_type.clone()))
+            .collect();
+        ExprGenerator::new(self.rng.next_u64(), Arc::clone(&self.ctx))
+            .with_typed_columns(typed_columns)
+    }
+
+    /// Whether joining `join_table` keeps the chain within `join_row_budget`,
+    /// or `MAX_CHAIN_JOINS` without a budget
+    fn may_join(
+        &self,
+        joined_tables: &[Arc<LogicalTable>],
+        join_table: &Arc<LogicalTable>,
+    ) -> bool {
+        let config = &self.ctx.runner_config;
+        match config.join_row_budget {
+            Some(budget) => {
+                let mut tables = joined_tables.to_vec();
+                tables.push(Arc::clone(join_table));
+                worst_case_join_rows(&tables, config.max_row_count) <= budget
+            }
+            None => joined_tables.len() <= MAX_CHAIN_JOINS,
+        }
+    }
+
+    /// 1 to 3 expressions of random types over the current columns, which
+    /// replace them
+    fn generate_select(
+        &mut self,
+        step: usize,
+        columns: &mut Vec<(String, DataType)>,
+    ) -> DataFrameOp {
+        let mut expr_gen = self.expr_generator(columns);
+        let available_types = self.ctx.generated_data_types();
+        let num_exprs = self.rng.random_range(1..=3);
+
+        let mut exprs = Vec::with_capacity(num_exprs);
+        let mut output_columns = Vec::with_capacity(num_exprs);
+        for i in 0..num_exprs {
+            let data_type = available_types[self.rng.random_range(0..available_types.len())]
+                .to_datafusion_type();
+            let alias = format!("s{}_{}", step, i);
+            exprs.push((
+                expr_gen.generate_random_expr(data_type.clone(), 0),
+                alias.clone(),
+            ));
+            output_columns.push((alias, data_type));
+        }
+
+        *columns = output_columns;
+        DataFrameOp::Select(exprs)
+    }
+
+    /// `COUNT`, `MIN` and `MAX` of random columns, grouped by up to
+    /// `max_group_by_count` of the current columns
+    fn generate_aggregate(
+        &mut self,
+        step: usize,
+        columns: &mut Vec<(String, DataType)>,
+    ) -> DataFrameOp {
+        let max_group_by = columns
+            .len()
+            .min(self.ctx.runner_config.max_group_by_count as usize);
+        let num_group_by = self.rng.random_range(0..=max_group_by);
+        let mut group_by_columns: Vec<(String, DataType)> = Vec::with_capacity(num_group_by);
+        while group_by_columns.len() < num_group_by {
+            let candidate = &columns[self.rng.random_range(0..columns.len())];
+            if !group_by_columns
+                .iter()
+                .any(|(name, _)| *name == candidate.0)
+            {
+                group_by_columns.push(candidate.clone());
+            }
+        }
+
+        let num_aggregates = self.rng.random_range(1..=3);
+        let mut aggregates = Vec::with_capacity(num_aggregates);
+        let mut output_columns = group_by_columns.clone();
+        for i in 0..num_aggregates {
+            let (name, data_type) = columns[self.rng.random_range(0..columns.len())].clone();
+            let alias = format!("a{}_{}", step, i);
+            let (aggregate, output_type) = match self.rng.random_range(0..3) {
+                0 => (count(column(&name)), DataType::Int64),
+                1 => (min(column(&name)), data_type),
+                _ => (max(column(&name)), data_type),
+            };
+            aggregates.push((aggregate, alias.clone()));
+            output_columns.push((alias, output_type));
+        }
+
+        *columns = output_columns;
+        DataFrameOp::Aggregate {
+            group_by: group_by_columns.into_iter().map(|(name, _)| name).collect(),
+            aggregates,
+        }
+    }
+
+    /// Join `table` with its columns renamed `j<step>_<column>`, on a
+    /// predicate over the columns of both sides
+    fn generate_join(
+        &mut self,
+        step: usize,
+        table: Arc<LogicalTable>,
+        columns: &mut Vec<(String, DataType)>,
+    ) -> DataFrameOp {
+        let mut renamed = Vec::with_capacity(table.columns.len());
+        for table_column in &table.columns {
+            let alias = format!("j{}_{}", step, table_column.name);
+            columns.push((alias.clone(), table_column.data_type.to_datafusion_type()));
+            renamed.push((table_column.name.clone(), alias));
+        }
+
+        let join_type = JOIN_TYPES[self.rng.random_range(0..JOIN_TYPES.len())];
+        let on = self
+            .expr_generator(columns)
+            .generate_random_expr(DataType::Boolean, 0);
+        DataFrameOp::Join {
+            table,
+            renamed,
+            join_type,
+            on,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracle::test_helpers::table_with_int_values;
+    use datafusion::prelude::lit;
+
+    #[test]
+    fn nests_one_query_per_operation() {
+        let table = Arc::new(table_with_int_values(&[Some(1)]));
+        let chain = DataFrameChain {
+            table: Arc::clone(&table),
+            ops: vec![
+                DataFrameOp::Filter(column("c0").gt(lit(0i64))),
+                DataFrameOp::Join {
+                    table,
+                    renamed: vec![("c0".to_string(), "j2_c0".to_string())],
+                    join_type: JoinType::Left,
+                    on: column("c0").eq(column("j2_c0")),
+                },
+                DataFrameOp::Sort {
+                    columns: vec!["c0".to_string(), "j2_c0".to_string()],
+                    fetch: Some(2),
+                },
+            ],
+        };
+
+        let sql = chain.to_sql().unwrap();
+        let filter = "SELECT * FROM (SELECT * FROM t1) AS q1 WHERE ";
+        let join = ") AS q2 LEFT JOIN (SELECT c0 AS j2_c0 FROM t1) AS r2 ON ";
+        let sort = ") AS q3 ORDER BY c0 ASC NULLS FIRST, j2_c0 ASC NULLS FIRST LIMIT 2";
+        assert!(
+            sql.starts_with(&format!("SELECT * FROM (SELECT * FROM ({}", filter)),
+            "{sql}"
+        );
+        assert!(sql.contains(join), "{sql}");
+        assert!(sql.ends_with(sort), "{sql}");
+    }
+}
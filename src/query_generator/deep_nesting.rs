@@ -0,0 +1,167 @@
+//! Pathologically deep expressions and subqueries, to find stack overflows and
+//! planning time growing faster than the nesting depth in the parser, planner
+//! and optimizer.
+
+use rand::{Rng, rngs::StdRng};
+
+/// What a deep query nests
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NestingShape {
+    /// `((0 + 1) + 1) + ...`
+    Arithmetic,
+    /// `abs(abs(...abs(-7)))`
+    FunctionCalls,
+    /// `CASE WHEN p THEN 0 ELSE CASE WHEN p THEN 0 ELSE ... 1 END END`
+    Case,
+    /// `NOT (NOT (... p))`
+    Not,
+    /// `(p OR (p OR (... p)))`
+    Or,
+    /// `((((p))))`
+    Parentheses,
+    /// `FROM (SELECT * FROM (SELECT * FROM ... t) AS nested) AS nested`
+    DerivedTables,
+    /// `SELECT (SELECT (SELECT ... 1))`
+    ScalarSubqueries,
+}
+
+impl NestingShape {
+    const ALL: [NestingShape; 8] = [
+        Self::Arithmetic,
+        Self::FunctionCalls,
+        Self::Case,
+        Self::Not,
+        Self::Or,
+        Self::Parentheses,
+        Self::DerivedTables,
+        Self::ScalarSubqueries,
+    ];
+
+    pub fn random(rng: &mut StdRng) -> Self {
+        Self::ALL[rng.random_range(0..Self::ALL.len())]
+    }
+
+    /// A query over `table` nesting this shape `depth` levels deep. `predicate`
+    /// is a boolean expression over `table`, e.g. `t0.c0 IS NULL`.
+    pub fn nested_query(self, table: &str, predicate: &str, depth: usize) -> String {
+        match self {
+            Self::Arithmetic => format!(
+                "SELECT {}0{} AS v FROM {}",
+                "(".repeat(depth),
+                " + 1)".repeat(depth),
+                table
+            ),
+            Self::FunctionCalls => format!(
+                "SELECT {}-7{} AS v FROM {}",
+                "abs(".repeat(depth),
+                ")".repeat(depth),
+                table
+            ),
+            Self::Case => format!(
+                "SELECT {}1{} AS v FROM {}",
+                format!("CASE WHEN {} THEN 0 ELSE ", predicate).repeat(depth),
+                " END".repeat(depth),
+                table
+            ),
+            Self::Not => format!(
+                "SELECT count(*) AS v FROM {} WHERE {}{}{}",
+                table,
+                "NOT (".repeat(depth),
+                predicate,
+                ")".repeat(depth)
+            ),
+            Self::Or => format!(
+                "SELECT count(*) AS v FROM {} WHERE {}{}{}",
+                table,
+                format!("({} OR ", predicate).repeat(depth),
+                predicate,
+                ")".repeat(depth)
+            ),
+            Self::Parentheses => format!(
+                "SELECT count(*) AS v FROM {} WHERE {}{}{}",
+                table,
+                "(".repeat(depth),
+                predicate,
+                ")".repeat(depth)
+            ),
+            Self::DerivedTables => format!(
+                "SELECT count(*) AS v FROM {}{}{}",
+                "(SELECT * FROM ".repeat(depth),
+                table,
+                ") AS nested".repeat(depth)
+            ),
+            Self::ScalarSubqueries => format!(
+                "SELECT {}1{} AS v",
+                "(SELECT ".repeat(depth),
+                ")".repeat(depth)
+            ),
+        }
+    }
+
+    /// A query without nesting returning the same rows as
+    /// [`Self::nested_query`] with the same arguments
+    pub fn flat_query(self, table: &str, predicate: &str, depth: usize) -> String {
+        match self {
+            Self::Arithmetic => format!("SELECT {} AS v FROM {}", depth, table),
+            Self::FunctionCalls => format!("SELECT 7 AS v FROM {}", table),
+            Self::Case => format!(
+                "SELECT CASE WHEN {} THEN 0 ELSE 1 END AS v FROM {}",
+                predicate, table
+            ),
+            Self::Not if depth % 2 == 1 => format!(
+                "SELECT count(*) AS v FROM {} WHERE NOT ({})",
+                table, predicate
+            ),
+            Self::Not | Self::Or | Self::Parentheses => {
+                format!("SELECT count(*) AS v FROM {} WHERE {}", table, predicate)
+            }
+            Self::DerivedTables => format!("SELECT count(*) AS v FROM {}", table),
+            Self::ScalarSubqueries => "SELECT 1 AS v".to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for NestingShape {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Arithmetic => "arithmetic",
+            Self::FunctionCalls => "function calls",
+            Self::Case => "CASE",
+            Self::Not => "NOT",
+            Self::Or => "OR",
+            Self::Parentheses => "parentheses",
+            Self::DerivedTables => "derived tables",
+            Self::ScalarSubqueries => "scalar subqueries",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::arrow::util::pretty::pretty_format_batches;
+    use datafusion::prelude::SessionContext;
+
+    #[tokio::test]
+    async fn nested_and_flat_queries_agree() {
+        let ctx = SessionContext::new();
+        ctx.sql("CREATE TABLE t0 (c0 BIGINT) AS VALUES (1), (NULL), (3)")
+            .await
+            .unwrap();
+        for shape in NestingShape::ALL {
+            for depth in [1, 2, 5] {
+                let nested = shape.nested_query("t0", "t0.c0 IS NULL", depth);
+                let flat = shape.flat_query("t0", "t0.c0 IS NULL", depth);
+                let nested_rows = ctx.sql(&nested).await.unwrap().collect().await.unwrap();
+                let flat_rows = ctx.sql(&flat).await.unwrap().collect().await.unwrap();
+                assert_eq!(
+                    pretty_format_batches(&nested_rows).unwrap().to_string(),
+                    pretty_format_batches(&flat_rows).unwrap().to_string(),
+                    "{}",
+                    nested
+                );
+            }
+        }
+    }
+}
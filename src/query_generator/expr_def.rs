@@ -196,6 +196,21 @@ impl BaseExpr {
             BaseExpr::Today => Box::new(TodayExpr),
         }
     }
+
+    /// Whether the expression compares the values of its two operands
+    pub fn is_comparison(&self) -> bool {
+        matches!(
+            self,
+            BaseExpr::Eq
+                | BaseExpr::NotEq
+                | BaseExpr::Lt
+                | BaseExpr::LtEq
+                | BaseExpr::Gt
+                | BaseExpr::GtEq
+                | BaseExpr::IsDistinctFrom
+                | BaseExpr::IsNotDistinctFrom
+        )
+    }
 }
 pub trait BaseExprWithInfo {
     fn describe(&self) -> ExprWrapper;
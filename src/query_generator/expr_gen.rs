@@ -1,11 +1,16 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::sync::Arc;
 
-use datafusion::{arrow::datatypes::DataType, common::Column, prelude::Expr, sql::TableReference};
+use datafusion::{
+    arrow::datatypes::DataType, common::Column, prelude::Expr, scalar::ScalarValue,
+    sql::TableReference,
+};
 use rand::{Rng, rngs::StdRng};
 
 use crate::{
-    common::{FuzzerDataType, LogicalTable, rng::rng_from_seed},
-    fuzz_context::GlobalContext,
+    common::{FuzzerDataType, LogicalTable, rng::rng_from_seed, value_summary::ColumnValueSummary},
+    fuzz_context::{GlobalContext, swarm::SwarmFeatures},
 };
 
 use super::{
@@ -20,21 +25,64 @@ pub struct ExprGenerator {
 
     /// All possible column references that can be used in the generated expressions.
     src_columns: Arc<Vec<Column>>,
+    /// `src_columns` grouped by data type, built once instead of scanning the
+    /// table registry for every leaf expression
+    columns_by_type: HashMap<DataType, Arc<Vec<Column>>>,
+    /// Summaries of the values stored in `src_columns`, for the columns
+    /// whose values are known, in the order of `src_columns`
+    column_summaries: Vec<(Column, Arc<ColumnValueSummary>)>,
+    /// Registry version `columns_by_type` was built from
+    indexed_tables_version: u64,
+    /// `columns_by_type` was given with `with_typed_columns` instead of
+    /// built from the registry
+    fixed_column_types: bool,
+    /// Feature families of the round with swarm testing, taken once instead
+    /// of locking the context for every expression
+    swarm_features: Option<SwarmFeatures>,
 }
 
 impl ExprGenerator {
     pub fn new(seed: u64, context: Arc<GlobalContext>) -> Self {
         let max_level = context.runner_config.max_expr_level;
+        let swarm_features = context.swarm_features();
         Self {
             rng: rng_from_seed(seed),
             ctx: context,
             max_level,
             src_columns: Arc::new(Vec::new()),
+            columns_by_type: HashMap::new(),
+            column_summaries: Vec::new(),
+            indexed_tables_version: 0,
+            fixed_column_types: false,
+            swarm_features,
         }
     }
 
     pub fn with_src_columns(mut self, src_columns: Arc<Vec<Column>>) -> Self {
         self.src_columns = src_columns;
+        self.rebuild_column_index();
+        self
+    }
+
+    /// Reference `columns` of the given types instead of columns of registered
+    /// tables, e.g. the output columns of a derived relation. Their values
+    /// are unknown.
+    pub fn with_typed_columns(mut self, columns: Vec<(Column, DataType)>) -> Self {
+        let mut columns_by_type: HashMap<DataType, Vec<Column>> = HashMap::new();
+        for (column, data_type) in &columns {
+            columns_by_type
+                .entry(data_type.clone())
+                .or_default()
+                .push(column.clone());
+        }
+
+        self.src_columns = Arc::new(columns.into_iter().map(|(column, _)| column).collect());
+        self.columns_by_type = columns_by_type
+            .into_iter()
+            .map(|(data_type, columns)| (data_type, Arc::new(columns)))
+            .collect();
+        self.column_summaries = Vec::new();
+        self.fixed_column_types = true;
         self
     }
 
@@ -68,6 +116,11 @@ impl ExprGenerator {
         let expr_with_return_type: Vec<Arc<ExprWrapper>> = exprs
             .iter()
             .filter(|expr| expr.return_type.contains(&target_type))
+            .filter(|expr| {
+                self.swarm_features
+                    .as_ref()
+                    .is_none_or(|features| features.allows_expr(&expr.expr))
+            })
             .map(|expr| Arc::clone(expr))
             .collect();
 
@@ -93,10 +146,13 @@ impl ExprGenerator {
         if let Some(random_expr) = self.pick_random_expr_with_return_type(target_type.clone()) {
             let child_signature = random_expr.pick_child_signature(target_type, &mut self.rng);
 
-            let child_exprs: Vec<Expr> = child_signature
+            let mut child_exprs: Vec<Expr> = child_signature
                 .iter()
                 .map(|dt| self.generate_random_expr(dt.clone(), cur_level + 1))
                 .collect();
+            if random_expr.expr.is_comparison() {
+                self.inject_sampled_literal(&mut child_exprs);
+            }
 
             self.build_with_childs(random_expr.expr.clone(), &child_exprs)
         } else {
@@ -107,8 +163,14 @@ impl ExprGenerator {
 
     // Generate either a constant value or a column reference
     fn generate_leaf_expr(&mut self, target_type: DataType) -> Expr {
+        if target_type == DataType::Boolean
+            && let Some(predicate) = self.generate_value_predicate()
+        {
+            return predicate;
+        }
+
         // For certain chance: try to generate a column reference if available
-        let columns = self.get_all_columns_of_type(target_type.clone());
+        let columns = self.get_all_columns_of_type(&target_type);
         if !columns.is_empty() && self.rng.random_bool(0.5) {
             let column = columns[self.rng.random_range(0..columns.len())].clone();
             return Expr::Column(column);
@@ -119,41 +181,153 @@ impl ExprGenerator {
             let scalar_value = generate_scalar_literal(&self.ctx, &mut self.rng, &fuzzer_type);
             Expr::Literal(scalar_value, None)
         } else {
-            // Fallback to a simple boolean literal for unsupported types
-            let scalar_value =
-                generate_scalar_literal(&self.ctx, &mut self.rng, &FuzzerDataType::Boolean);
+            // Types without a fuzzer counterpart get a typed NULL, a literal of
+            // another type would change the signature of the parent expression
+            let scalar_value = ScalarValue::try_from(&target_type).unwrap_or(ScalarValue::Null);
             Expr::Literal(scalar_value, None)
         }
     }
 
-    fn get_all_columns_of_type(&self, target_type: DataType) -> Vec<Column> {
-        // Use the actual column information from the tables
-        let mut matching_columns = Vec::new();
+    /// Random literals rarely match any stored value, so `column = literal`
+    /// is almost always false and the rest of the query sees no rows. With
+    /// probability `predicate_literal_bias`, replace the literal compared with
+    /// a column by a value stored in that column.
+    fn inject_sampled_literal(&mut self, child_exprs: &mut [Expr]) {
+        let bias = self.ctx.runner_config.predicate_literal_bias;
+        // Don't consume randomness if disabled, to keep generated queries stable
+        if bias <= 0.0 {
+            return;
+        }
+
+        let (column, literal) = match child_exprs {
+            [Expr::Column(column), literal @ Expr::Literal(..)]
+            | [literal @ Expr::Literal(..), Expr::Column(column)] => (column, literal),
+            _ => return,
+        };
+        self.refresh_column_index();
+        let Some(summary) = self
+            .column_summaries
+            .iter()
+            .find(|(summarized, _)| summarized == &*column)
+            .map(|(_, summary)| Arc::clone(summary))
+        else {
+            return;
+        };
+
+        if !summary.samples.is_empty() && self.rng.random_bool(bias) {
+            *literal = Expr::Literal(self.pick_sample(&summary), None);
+        }
+    }
+
+    /// With probability `predicate_literal_bias / 2`, generate
+    /// `column BETWEEN low AND high` or `column IN (...)` from values stored in
+    /// a column, so the predicate selects part of the rows.
+    fn generate_value_predicate(&mut self) -> Option<Expr> {
+        let bias = self.ctx.runner_config.predicate_literal_bias;
+        // Don't consume randomness if disabled, to keep generated queries stable
+        if bias <= 0.0 {
+            return None;
+        }
+
+        self.refresh_column_index();
+        let candidates: Vec<_> = self
+            .column_summaries
+            .iter()
+            .filter(|(_, summary)| !summary.samples.is_empty())
+            .cloned()
+            .collect();
+        if candidates.is_empty() || !self.rng.random_bool(bias / 2.0) {
+            return None;
+        }
+        let (column, summary) = candidates[self.rng.random_range(0..candidates.len())].clone();
+        let column = Expr::Column(column);
+
+        if self.rng.random_bool(0.5) {
+            // Bounds are stored values or the extremes of the column
+            let mut low = self.pick_bound(&summary);
+            let mut high = self.pick_bound(&summary);
+            if low.partial_cmp(&high) == Some(Ordering::Greater) {
+                std::mem::swap(&mut low, &mut high);
+            }
+            Some(column.between(Expr::Literal(low, None), Expr::Literal(high, None)))
+        } else {
+            let list_len = self.rng.random_range(1..=summary.samples.len().min(3));
+            let list = (0..list_len)
+                .map(|_| Expr::Literal(self.pick_sample(&summary), None))
+                .collect();
+            Some(column.in_list(list, false))
+        }
+    }
+
+    /// A random value of `summary.samples`, which must not be empty
+    fn pick_sample(&mut self, summary: &ColumnValueSummary) -> ScalarValue {
+        summary.samples[self.rng.random_range(0..summary.samples.len())].clone()
+    }
+
+    fn pick_bound(&mut self, summary: &ColumnValueSummary) -> ScalarValue {
+        let extreme = match self.rng.random_range(0..4) {
+            0 => summary.min.clone(),
+            1 => summary.max.clone(),
+            _ => None,
+        };
+        extreme.unwrap_or_else(|| self.pick_sample(summary))
+    }
+
+    fn refresh_column_index(&mut self) {
+        if !self.fixed_column_types
+            && self.indexed_tables_version != self.ctx.runtime_context.tables_version()
+        {
+            self.rebuild_column_index();
+        }
+    }
+
+    fn get_all_columns_of_type(&mut self, target_type: &DataType) -> Arc<Vec<Column>> {
+        self.refresh_column_index();
+
+        self.columns_by_type
+            .get(target_type)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Group `src_columns` by the data type of their table column, keeping
+    /// their order. Columns of unregistered tables are left out.
+    fn rebuild_column_index(&mut self) {
+        let mut columns_by_type: HashMap<DataType, Vec<Column>> = HashMap::new();
+        let mut column_summaries = Vec::new();
+        // Read the version first: a concurrent change makes the index stale
+        // and it is rebuilt again on next use
+        self.indexed_tables_version = self.ctx.runtime_context.tables_version();
+        let tables = self.ctx.runtime_context.tables();
 
         for column in self.src_columns.as_ref() {
-            // Try to find the table this column belongs to
-            if let Some(table_ref) = &column.relation {
-                let table_name = table_ref.to_string();
-
-                // Get the table from the fuzzer context
-                let tables_lock = self.ctx.runtime_context.registered_tables.read().unwrap();
-                if let Some(logical_table) = tables_lock.get(&table_name) {
-                    // Find the matching logical column
-                    for logical_column in &logical_table.columns {
-                        if logical_column.name == column.name {
-                            // Check if the data type matches
-                            let column_df_type = logical_column.data_type.to_datafusion_type();
-                            if column_df_type == target_type {
-                                matching_columns.push(column.clone());
-                            }
-                            break;
-                        }
-                    }
+            let Some(table_ref) = &column.relation else {
+                continue;
+            };
+            let Some(logical_table) = tables.get(&table_ref.to_string()) else {
+                continue;
+            };
+
+            if let Some(logical_column) = logical_table
+                .columns
+                .iter()
+                .find(|logical_column| logical_column.name == column.name)
+            {
+                columns_by_type
+                    .entry(logical_column.data_type.to_datafusion_type())
+                    .or_default()
+                    .push(column.clone());
+                if let Some(summary) = &logical_column.value_summary {
+                    column_summaries.push((column.clone(), Arc::clone(summary)));
                 }
             }
         }
+        self.column_summaries = column_summaries;
 
-        matching_columns
+        self.columns_by_type = columns_by_type
+            .into_iter()
+            .map(|(data_type, columns)| (data_type, Arc::new(columns)))
+            .collect();
     }
 
     /// If the number of childs is not correct, it will try to fix automatically.
@@ -165,3 +339,161 @@ impl ExprGenerator {
         expr_impl.build_expr(child_exprs)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::LogicalColumn;
+    use crate::oracle::test_helpers::context_with_config;
+
+    #[test]
+    fn column_index_follows_table_registry() {
+        let ctx = Arc::new(GlobalContext::default());
+        let src_columns = vec![
+            Column::new(Some(TableReference::bare("t0")), "c0"),
+            Column::new(Some(TableReference::bare("t0")), "c1"),
+        ];
+        let mut expr_gen =
+            ExprGenerator::new(1, Arc::clone(&ctx)).with_src_columns(Arc::new(src_columns));
+        assert!(
+            expr_gen
+                .get_all_columns_of_type(&DataType::Int64)
+                .is_empty()
+        );
+
+        ctx.runtime_context
+            .register_table(Arc::new(LogicalTable::with_columns(
+                "t0".to_string(),
+                vec![
+                    LogicalColumn {
+                        name: "c0".to_string(),
+                        data_type: FuzzerDataType::Int64,
+                        value_summary: None,
+                    },
+                    LogicalColumn {
+                        name: "c1".to_string(),
+                        data_type: FuzzerDataType::Boolean,
+                        value_summary: None,
+                    },
+                ],
+            )));
+
+        let int_columns = expr_gen.get_all_columns_of_type(&DataType::Int64);
+        assert_eq!(
+            int_columns.as_ref(),
+            &vec![Column::new(Some(TableReference::bare("t0")), "c0")]
+        );
+        assert_eq!(
+            expr_gen.get_all_columns_of_type(&DataType::Boolean).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn typed_columns_ignore_the_table_registry() {
+        let ctx = Arc::new(GlobalContext::default());
+        let mut expr_gen = ExprGenerator::new(1, Arc::clone(&ctx)).with_typed_columns(vec![
+            (Column::new_unqualified("s1_0"), DataType::Int64),
+            (Column::new_unqualified("a2_0"), DataType::Int64),
+        ]);
+
+        ctx.runtime_context
+            .register_table(Arc::new(LogicalTable::new("t0".to_string())));
+        assert_eq!(
+            expr_gen.get_all_columns_of_type(&DataType::Int64).as_ref(),
+            &vec![
+                Column::new_unqualified("s1_0"),
+                Column::new_unqualified("a2_0")
+            ]
+        );
+        assert!(
+            expr_gen
+                .get_all_columns_of_type(&DataType::Boolean)
+                .is_empty()
+        );
+    }
+
+    fn context_with_summary(predicate_literal_bias: f64) -> Arc<GlobalContext> {
+        let ctx = context_with_config(crate::fuzz_context::RunnerConfig {
+            predicate_literal_bias,
+            ..Default::default()
+        });
+        ctx.runtime_context
+            .register_table(Arc::new(LogicalTable::with_columns(
+                "t0".to_string(),
+                vec![LogicalColumn {
+                    name: "c0".to_string(),
+                    data_type: FuzzerDataType::Int64,
+                    value_summary: Some(Arc::new(ColumnValueSummary {
+                        row_count: 1,
+                        null_count: 0,
+                        min: Some(ScalarValue::Int64(Some(42))),
+                        max: Some(ScalarValue::Int64(Some(42))),
+                        samples: vec![ScalarValue::Int64(Some(42))],
+                        values: vec![ScalarValue::Int64(Some(42))],
+                    })),
+                }],
+            )));
+        ctx
+    }
+
+    #[test]
+    fn comparison_literals_are_sampled_from_the_column() {
+        let column = Column::new(Some(TableReference::bare("t0")), "c0");
+        let random_literal = Expr::Literal(ScalarValue::Int64(Some(-7)), None);
+        let sampled_literal = Expr::Literal(ScalarValue::Int64(Some(42)), None);
+
+        let ctx = context_with_summary(1.0);
+        let mut expr_gen =
+            ExprGenerator::new(1, ctx).with_src_columns(Arc::new(vec![column.clone()]));
+        let mut child_exprs = vec![random_literal.clone(), Expr::Column(column.clone())];
+        expr_gen.inject_sampled_literal(&mut child_exprs);
+        assert_eq!(
+            child_exprs,
+            vec![sampled_literal.clone(), Expr::Column(column.clone())]
+        );
+
+        // Only column-literal comparisons are rewritten
+        let mut child_exprs = vec![random_literal.clone(), random_literal.clone()];
+        expr_gen.inject_sampled_literal(&mut child_exprs);
+        assert_eq!(
+            child_exprs,
+            vec![random_literal.clone(), random_literal.clone()]
+        );
+
+        let ctx = context_with_summary(0.0);
+        let mut expr_gen =
+            ExprGenerator::new(1, ctx).with_src_columns(Arc::new(vec![column.clone()]));
+        let mut child_exprs = vec![Expr::Column(column.clone()), random_literal.clone()];
+        expr_gen.inject_sampled_literal(&mut child_exprs);
+        assert_eq!(child_exprs, vec![Expr::Column(column), random_literal]);
+    }
+
+    #[test]
+    fn value_predicates_use_stored_values() {
+        let column = Column::new(Some(TableReference::bare("t0")), "c0");
+        let sampled_literal = Expr::Literal(ScalarValue::Int64(Some(42)), None);
+
+        let ctx = context_with_summary(1.0);
+        let mut expr_gen =
+            ExprGenerator::new(1, ctx).with_src_columns(Arc::new(vec![column.clone()]));
+        let predicates: Vec<Expr> = (0..64)
+            .filter_map(|_| expr_gen.generate_value_predicate())
+            .collect();
+
+        assert!(!predicates.is_empty());
+        for predicate in predicates {
+            let expected = [
+                Expr::Column(column.clone())
+                    .between(sampled_literal.clone(), sampled_literal.clone()),
+                Expr::Column(column.clone()).in_list(vec![sampled_literal.clone()], false),
+            ];
+            assert!(expected.contains(&predicate), "{predicate}");
+        }
+
+        let ctx = context_with_summary(0.0);
+        let mut expr_gen =
+            ExprGenerator::new(1, ctx).with_src_columns(Arc::new(vec![column.clone()]));
+        assert!(expr_gen.generate_value_predicate().is_none());
+    }
+}
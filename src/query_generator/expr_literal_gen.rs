@@ -25,6 +25,30 @@ pub fn generate_scalar_literal(
 mod tests {
     use super::*;
     use crate::common::rng::rng_from_seed;
+    use crate::common::{get_available_data_types, init_available_data_types};
+
+    #[test]
+    fn generates_literals_of_every_data_type() {
+        init_available_data_types();
+        let ctx = Arc::new(GlobalContext::default());
+        let mut rng = rng_from_seed(7);
+
+        for fuzzer_type in get_available_data_types() {
+            for _ in 0..20 {
+                let scalar_value = generate_scalar_literal(&ctx, &mut rng, fuzzer_type);
+                if scalar_value.is_null() {
+                    continue;
+                }
+                assert_eq!(
+                    FuzzerDataType::from_datafusion_type(&scalar_value.data_type()).as_ref(),
+                    Some(fuzzer_type),
+                    "literal {:?} generated for {:?}",
+                    scalar_value,
+                    fuzzer_type
+                );
+            }
+        }
+    }
 
     #[test]
     fn test_interval_scalar_literal_generation() {
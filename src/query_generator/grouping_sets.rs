@@ -0,0 +1,122 @@
+//! GROUP BY clauses with several grouping sets: `ROLLUP`, `CUBE` and explicit
+//! `GROUPING SETS`, along with the grouping sets they expand to.
+
+use rand::seq::IndexedRandom;
+use rand::{Rng, rngs::StdRng};
+
+/// Largest number of sets in a generated `GROUPING SETS` list
+const MAX_EXPLICIT_SETS: usize = 4;
+
+/// A GROUP BY clause over `keys` with several grouping sets
+#[derive(Debug, Clone)]
+pub struct GroupingSets {
+    /// SQL of the grouping keys
+    pub keys: Vec<String>,
+    /// SQL of the clause, without the leading `GROUP BY`
+    pub clause: String,
+    /// For each grouping set, whether each key is part of it
+    pub sets: Vec<Vec<bool>>,
+}
+
+impl GroupingSets {
+    /// `ROLLUP(k0, k1, ...)`: the sets `(k0, k1, ...)`, ..., `(k0)`, `()`
+    pub fn rollup(keys: Vec<String>) -> Self {
+        let sets = (0..=keys.len())
+            .rev()
+            .map(|len| (0..keys.len()).map(|key| key < len).collect())
+            .collect();
+        Self {
+            clause: format!("ROLLUP({})", keys.join(", ")),
+            keys,
+            sets,
+        }
+    }
+
+    /// `CUBE(k0, k1, ...)`: every subset of the keys
+    pub fn cube(keys: Vec<String>) -> Self {
+        let sets = (0..1usize << keys.len())
+            .map(|mask| (0..keys.len()).map(|key| mask & (1 << key) != 0).collect())
+            .collect();
+        Self {
+            clause: format!("CUBE({})", keys.join(", ")),
+            keys,
+            sets,
+        }
+    }
+
+    /// `GROUPING SETS (...)` listing `sets`
+    pub fn explicit(keys: Vec<String>, sets: Vec<Vec<bool>>) -> Self {
+        let set_list: Vec<String> = sets
+            .iter()
+            .map(|set| {
+                let set_keys: Vec<&str> = keys
+                    .iter()
+                    .zip(set)
+                    .filter(|(_, included)| **included)
+                    .map(|(key, _)| key.as_str())
+                    .collect();
+                format!("({})", set_keys.join(", "))
+            })
+            .collect();
+        Self {
+            clause: format!("GROUPING SETS ({})", set_list.join(", ")),
+            keys,
+            sets,
+        }
+    }
+
+    /// A random `ROLLUP`, `CUBE` or `GROUPING SETS` clause over `keys`.
+    /// Explicit lists have at least two distinct sets, like the other two.
+    pub fn random(keys: Vec<String>, rng: &mut StdRng) -> Self {
+        match rng.random_range(0..3) {
+            0 => Self::rollup(keys),
+            1 => Self::cube(keys),
+            _ => {
+                let all_sets = Self::cube(keys.clone()).sets;
+                let count = rng.random_range(2..=all_sets.len().min(MAX_EXPLICIT_SETS));
+                let sets = all_sets.choose_multiple(rng, count).cloned().collect();
+                Self::explicit(keys, sets)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::rng::rng_from_seed;
+
+    fn keys() -> Vec<String> {
+        vec!["t0.c0".to_string(), "t0.c1".to_string()]
+    }
+
+    #[test]
+    fn expands_rollup_and_cube() {
+        let rollup = GroupingSets::rollup(keys());
+        assert_eq!(rollup.clause, "ROLLUP(t0.c0, t0.c1)");
+        assert_eq!(
+            rollup.sets,
+            vec![vec![true, true], vec![true, false], vec![false, false]]
+        );
+
+        let cube = GroupingSets::cube(keys());
+        assert_eq!(cube.clause, "CUBE(t0.c0, t0.c1)");
+        assert_eq!(cube.sets.len(), 4);
+        assert!(cube.sets.contains(&vec![false, true]));
+
+        let explicit = GroupingSets::explicit(keys(), vec![vec![false, true], vec![false, false]]);
+        assert_eq!(explicit.clause, "GROUPING SETS ((t0.c1), ())");
+    }
+
+    #[test]
+    fn random_sets_are_distinct() {
+        let mut rng = rng_from_seed(5);
+        for _ in 0..32 {
+            let grouping_sets = GroupingSets::random(keys(), &mut rng);
+            assert!(grouping_sets.sets.len() >= 2);
+            let distinct: std::collections::HashSet<&Vec<bool>> =
+                grouping_sets.sets.iter().collect();
+            assert_eq!(distinct.len(), grouping_sets.sets.len());
+        }
+    }
+}
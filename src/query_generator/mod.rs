@@ -1,7 +1,14 @@
+pub mod dataframe_chain;
+pub mod deep_nesting;
 pub mod expr_def;
 pub mod expr_gen;
 pub mod expr_impl;
 pub mod expr_literal_gen;
+pub mod grouping_sets;
+pub mod plan_builder;
+pub mod query_template;
+pub mod sql_mutation;
 pub mod stmt_select_def;
-pub mod stmt_select_gen;
 pub mod stmt_select_join;
+pub mod unparser_dialect;
+pub mod wide_projection;
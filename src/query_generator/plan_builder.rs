@@ -0,0 +1,340 @@
+//! Query generation without SQL text: random `LogicalPlan`s built with
+//! `LogicalPlanBuilder`.
+//!
+//! The SQL path always goes through the SQL planner, so plans it can't
+//! produce (or produces differently) are never tested. A [`GeneratedPlan`]
+//! records the random choices of a query (tables, joins, filter and output)
+//! and builds the plan from them over any table sources: placeholder sources
+//! from the fuzzer's table registry to unparse the plan into SQL, or the
+//! providers of a session to execute it as a `DataFrame`.
+
+use std::sync::Arc;
+
+use datafusion::arrow::datatypes::{DataType, Field, Schema};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::common::Column;
+use datafusion::dataframe::DataFrame;
+use datafusion::datasource::provider_as_source;
+use datafusion::functions_aggregate::expr_fn::{count, max, min};
+use datafusion::logical_expr::{
+    JoinType, LogicalPlan, LogicalPlanBuilder, LogicalTableSource, TableSource,
+};
+use datafusion::prelude::{Expr, SessionContext};
+use datafusion::sql::unparser::plan_to_sql;
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore, rngs::StdRng};
+
+use crate::common::{InclusionConfig, LogicalTable, Result, fuzzer_err, rng::rng_from_seed};
+use crate::engine::dataframe_engine::DataFrameQuery;
+use crate::fuzz_context::GlobalContext;
+
+use super::expr_gen::ExprGenerator;
+use super::stmt_select_def::SelectStatementBuilder;
+
+/// Join types of the generated plans. Semi and anti joins are left out, they
+/// drop the columns of one side the rest of the plan may reference.
+const JOIN_TYPES: [JoinType; 4] = [
+    JoinType::Inner,
+    JoinType::Left,
+    JoinType::Right,
+    JoinType::Full,
+];
+
+/// What a generated plan returns
+#[derive(Debug, Clone)]
+enum PlanOutput {
+    /// Expressions over the joined rows, aliased `c0`, `c1`, ...
+    Projection { exprs: Vec<Expr>, distinct: bool },
+    /// Aggregates aliased `a0`, `a1`, ... grouped by columns
+    Aggregate {
+        group_by: Vec<Expr>,
+        aggregates: Vec<Expr>,
+    },
+}
+
+/// Random choices of a query generated as a logical plan:
+///
+/// Scan(tables[0])
+///   [JOIN Scan(tables[i]) ON join_on | CROSS JOIN Scan(tables[i])]...
+///   [Filter(filter)]
+///   (Projection [Distinct] | Aggregate + Projection)
+#[derive(Debug, Clone)]
+pub struct GeneratedPlan {
+    tables: Vec<Arc<LogicalTable>>,
+    /// Join of `tables[i + 1]` with the tables before it, a cross join
+    /// without an ON condition
+    joins: Vec<(JoinType, Option<Expr>)>,
+    filter: Option<Expr>,
+    output: PlanOutput,
+}
+
+impl GeneratedPlan {
+    /// Tables the plan scans
+    pub fn tables(&self) -> &[Arc<LogicalTable>] {
+        &self.tables
+    }
+
+    /// Build the plan, scanning each table from `source_of(table)`
+    pub fn build(
+        &self,
+        source_of: &dyn Fn(&LogicalTable) -> Result<Arc<dyn TableSource>>,
+    ) -> Result<LogicalPlan> {
+        let scan = |table: &Arc<LogicalTable>| -> Result<LogicalPlanBuilder> {
+            Ok(LogicalPlanBuilder::scan(
+                table.table_reference(),
+                source_of(table)?,
+                None,
+            )?)
+        };
+
+        let mut builder = scan(&self.tables[0])?;
+        for (table, (join_type, join_on)) in self.tables[1..].iter().zip(&self.joins) {
+            let right = scan(table)?.build()?;
+            builder = match join_on {
+                Some(join_on) => builder.join_on(right, *join_type, vec![join_on.clone()])?,
+                None => builder.cross_join(right)?,
+            };
+        }
+
+        if let Some(filter) = &self.filter {
+            builder = builder.filter(filter.clone())?;
+        }
+
+        builder = match &self.output {
+            PlanOutput::Projection { exprs, distinct } => {
+                let aliased = exprs
+                    .iter()
+                    .enumerate()
+                    .map(|(i, expr)| expr.clone().alias(format!("c{}", i)));
+                let builder = builder.project(aliased)?;
+                if *distinct {
+                    builder.distinct()?
+                } else {
+                    builder
+                }
+            }
+            PlanOutput::Aggregate {
+                group_by,
+                aggregates,
+            } => {
+                let aliased: Vec<Expr> = aggregates
+                    .iter()
+                    .enumerate()
+                    .map(|(i, expr)| expr.clone().alias(format!("a{}", i)))
+                    .collect();
+                let builder = builder.aggregate(group_by.clone(), aliased)?;
+                // Name the output columns, so the unparser emits a plain
+                // SELECT list
+                let columns: Vec<Expr> = builder
+                    .schema()
+                    .columns()
+                    .into_iter()
+                    .map(Expr::Column)
+                    .collect();
+                builder.project(columns)?
+            }
+        };
+
+        Ok(builder.build()?)
+    }
+
+    /// The plan as SQL, built over the schemas of the table registry and
+    /// unparsed with DataFusion's unparser
+    pub fn to_sql(&self) -> Result<String> {
+        let plan = self.build(&placeholder_source)?;
+        Ok(plan_to_sql(&plan)?.to_string())
+    }
+}
+
+#[async_trait::async_trait]
+impl DataFrameQuery for GeneratedPlan {
+    /// Build the plan over the tables of `session_context` and execute it
+    /// through the `DataFrame` API
+    async fn execute(&self, session_context: &SessionContext) -> Result<Vec<RecordBatch>> {
+        let mut sources = Vec::with_capacity(self.tables.len());
+        for table in &self.tables {
+            let provider = session_context
+                .table_provider(table.table_reference())
+                .await?;
+            sources.push((table.name.clone(), provider_as_source(provider)));
+        }
+        let plan = self.build(&|table| {
+            sources
+                .iter()
+                .find(|(name, _)| *name == table.name)
+                .map(|(_, source)| Arc::clone(source))
+                .ok_or_else(|| fuzzer_err(&format!("Table {} not found", table.name)))
+        })?;
+
+        Ok(DataFrame::new(session_context.state(), plan)
+            .collect()
+            .await?)
+    }
+}
+
+/// Source with the schema the registry records for `table`, enough to build
+/// and unparse a plan but not to execute it
+fn placeholder_source(table: &LogicalTable) -> Result<Arc<dyn TableSource>> {
+    let fields: Vec<Field> = table
+        .columns
+        .iter()
+        .map(|column| Field::new(&column.name, column.data_type.to_datafusion_type(), true))
+        .collect();
+    Ok(Arc::new(LogicalTableSource::new(Arc::new(Schema::new(
+        fields,
+    )))))
+}
+
+/// Generates [`GeneratedPlan`]s over the tables of the registry
+pub struct LogicalPlanGenerator {
+    rng: StdRng,
+    ctx: Arc<GlobalContext>,
+}
+
+impl LogicalPlanGenerator {
+    pub fn new(seed: u64, ctx: Arc<GlobalContext>) -> Self {
+        Self {
+            rng: rng_from_seed(seed),
+            ctx,
+        }
+    }
+
+    pub fn generate_plan(&mut self) -> Result<GeneratedPlan> {
+        // ==== Pick tables like the SQL path, within the join row budget ====
+        let mut table_picker = SelectStatementBuilder::new(
+            self.rng.next_u64(),
+            Arc::clone(&self.ctx),
+            InclusionConfig::Always(false),
+            InclusionConfig::Always(false),
+        );
+        if self.ctx.runner_config.join_row_budget.is_none() {
+            table_picker = table_picker.with_max_table_count(3);
+        }
+        let tables = table_picker.pick_src_tables()?;
+
+        // ==== Joins, each ON condition sees the tables joined so far ====
+        let mut joins = Vec::with_capacity(tables.len() - 1);
+        for joined in 2..=tables.len() {
+            let join_type = JOIN_TYPES[self.rng.random_range(0..JOIN_TYPES.len())];
+            let join_on = if self.rng.random_bool(0.1) {
+                None
+            } else {
+                let mut expr_gen = self.expr_generator(&tables[..joined]);
+                Some(expr_gen.generate_random_expr(DataType::Boolean, 0))
+            };
+            joins.push((join_type, join_on));
+        }
+
+        // ==== Filter and output over all columns ====
+        let mut expr_gen = self.expr_generator(&tables);
+        let filter = self
+            .rng
+            .random_bool(0.7)
+            .then(|| expr_gen.generate_random_expr(DataType::Boolean, 0));
+
+        let columns = ExprGenerator::tables_to_columns(&tables, &self.ctx);
+        let output = if !columns.is_empty() && self.rng.random_bool(0.3) {
+            self.generate_aggregate(columns)
+        } else {
+            let available_types = self.ctx.generated_data_types();
+            let num_exprs = self.rng.random_range(1..=3);
+            let exprs = (0..num_exprs)
+                .map(|_| {
+                    let fuzzer_type =
+                        &available_types[self.rng.random_range(0..available_types.len())];
+                    expr_gen.generate_random_expr(fuzzer_type.to_datafusion_type(), 0)
+                })
+                .collect();
+            PlanOutput::Projection {
+                exprs,
+                distinct: self.rng.random_bool(0.2),
+            }
+        };
+
+        Ok(GeneratedPlan {
+            tables,
+            joins,
+            filter,
+            output,
+        })
+    }
+
+    fn expr_generator(&mut self, tables: &[Arc<LogicalTable>]) -> ExprGenerator {
+        let columns = ExprGenerator::tables_to_columns(tables, &self.ctx);
+        ExprGenerator::new(self.rng.next_u64(), Arc::clone(&self.ctx))
+            .with_src_columns(Arc::new(columns))
+    }
+
+    /// Group by up to `max_group_by_count` columns, with `COUNT`, `MIN` and
+    /// `MAX` of random columns
+    fn generate_aggregate(&mut self, mut columns: Vec<Column>) -> PlanOutput {
+        columns.shuffle(&mut self.rng);
+        let max_group_by = columns
+            .len()
+            .min(self.ctx.runner_config.max_group_by_count as usize);
+        let num_group_by = self.rng.random_range(0..=max_group_by);
+        let group_by = columns[..num_group_by]
+            .iter()
+            .cloned()
+            .map(Expr::Column)
+            .collect();
+
+        let num_aggregates = self.rng.random_range(1..=3);
+        let aggregates = (0..num_aggregates)
+            .map(|_| {
+                let column = Expr::Column(columns[self.rng.random_range(0..columns.len())].clone());
+                match self.rng.random_range(0..3) {
+                    0 => count(column),
+                    1 => min(column),
+                    _ => max(column),
+                }
+            })
+            .collect();
+
+        PlanOutput::Aggregate {
+            group_by,
+            aggregates,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracle::test_helpers::register_int_table;
+
+    #[tokio::test]
+    async fn unparsed_plans_run_like_the_plans() {
+        let ctx = Arc::new(GlobalContext::default());
+        register_int_table(&ctx, &[Some(1), None, Some(5)]).await;
+        let session_context = ctx.runtime_context.get_session_context();
+
+        let mut executed = 0;
+        for seed in 0..16 {
+            let plan = LogicalPlanGenerator::new(seed, Arc::clone(&ctx))
+                .generate_plan()
+                .unwrap();
+            assert_eq!(plan.tables().len(), 1);
+            let sql = plan.to_sql().unwrap();
+            assert!(sql.starts_with("SELECT"), "{sql}");
+            assert!(sql.contains("t1"), "{sql}");
+
+            let Ok(batches) = plan.execute(&session_context).await else {
+                continue;
+            };
+            let from_sql = session_context
+                .sql(&sql)
+                .await
+                .unwrap()
+                .collect()
+                .await
+                .unwrap();
+            let rows = |batches: &[RecordBatch]| {
+                batches.iter().map(|batch| batch.num_rows()).sum::<usize>()
+            };
+            assert_eq!(rows(&batches), rows(&from_sql), "{sql}");
+            executed += 1;
+        }
+        assert!(executed > 0);
+    }
+}
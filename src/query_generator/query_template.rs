@@ -0,0 +1,253 @@
+//! User-provided query skeletons with holes filled by the generator.
+//!
+//! A template is SQL with holes in braces, e.g.
+//! `SELECT {expr} FROM {table} WHERE {pred}`:
+//!
+//! - `{table}`: a generated table, a different one for every `{table}` hole
+//! - `{column}`: a column of the tables of the template
+//! - `{expr}`: an expression of a generated type over those columns
+//! - `{pred}`: a boolean expression over those columns
+//!
+//! `{{` and `}}` are literal braces. The tables are picked before the other
+//! holes are filled, so expressions can reference tables of later holes.
+
+use std::sync::Arc;
+
+use datafusion::arrow::datatypes::DataType;
+use datafusion::prelude::Expr;
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore};
+
+use super::expr_gen::ExprGenerator;
+use crate::common::rng::rng_from_seed;
+use crate::common::util::to_sql_string;
+use crate::common::{Result, fuzzer_err};
+use crate::fuzz_context::GlobalContext;
+
+/// A hole of a template, filled by the generator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemplateHole {
+    Table,
+    Column,
+    Expr,
+    Pred,
+}
+
+impl TemplateHole {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "table" => Some(Self::Table),
+            "column" => Some(Self::Column),
+            "expr" => Some(Self::Expr),
+            "pred" => Some(Self::Pred),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Sql(String),
+    Hole(TemplateHole),
+}
+
+/// A parsed query template, see the module documentation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryTemplate {
+    segments: Vec<Segment>,
+}
+
+impl QueryTemplate {
+    pub fn parse(template: &str) -> Result<Self> {
+        let mut segments = Vec::new();
+        let mut sql = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    sql.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    sql.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    let mut closed = false;
+                    for c in chars.by_ref() {
+                        if c == '}' {
+                            closed = true;
+                            break;
+                        }
+                        name.push(c);
+                    }
+                    if !closed {
+                        return Err(fuzzer_err(&format!(
+                            "Unclosed {{{} in query template, use {{{{ for a brace",
+                            name
+                        )));
+                    }
+                    let hole = TemplateHole::from_name(&name).ok_or_else(|| {
+                        fuzzer_err(&format!(
+                            "Unknown hole {{{}}} in query template, expected {{table}}, \
+                             {{column}}, {{expr}} or {{pred}} ({{{{ and }}}} for braces)",
+                            name
+                        ))
+                    })?;
+                    if !sql.is_empty() {
+                        segments.push(Segment::Sql(std::mem::take(&mut sql)));
+                    }
+                    segments.push(Segment::Hole(hole));
+                }
+                '}' => {
+                    return Err(fuzzer_err(
+                        "Unmatched } in query template, use }} for a brace",
+                    ));
+                }
+                _ => sql.push(c),
+            }
+        }
+        if !sql.is_empty() {
+            segments.push(Segment::Sql(sql));
+        }
+
+        let template = Self { segments };
+        if template.table_count() == 0 && template.holes().any(|hole| hole == TemplateHole::Column)
+        {
+            return Err(fuzzer_err(
+                "Query template has a {column} hole but no {table} hole to take it from",
+            ));
+        }
+        Ok(template)
+    }
+
+    fn holes(&self) -> impl Iterator<Item = TemplateHole> + '_ {
+        self.segments.iter().filter_map(|segment| match segment {
+            Segment::Hole(hole) => Some(*hole),
+            Segment::Sql(_) => None,
+        })
+    }
+
+    /// Number of `{table}` holes, each filled with a different table
+    pub fn table_count(&self) -> usize {
+        self.holes()
+            .filter(|hole| *hole == TemplateHole::Table)
+            .count()
+    }
+
+    /// The template with its holes filled from the tables of the round
+    pub fn fill(&self, seed: u64, ctx: &Arc<GlobalContext>) -> Result<String> {
+        let mut rng = rng_from_seed(seed);
+
+        // The registry is ordered by name, so the choice is deterministic
+        let mut tables: Vec<_> = ctx.runtime_context.tables().values().cloned().collect();
+        let table_count = self.table_count();
+        if tables.len() < table_count {
+            return Err(fuzzer_err(&format!(
+                "Query template needs {} tables, the round has {}",
+                table_count,
+                tables.len()
+            )));
+        }
+        tables.shuffle(&mut rng);
+        tables.truncate(table_count);
+
+        let columns = ExprGenerator::tables_to_columns(&tables, ctx);
+        let data_types = ctx.generated_data_types();
+        let mut expr_gen = ExprGenerator::new(rng.next_u64(), Arc::clone(ctx))
+            .with_src_columns(Arc::new(columns.clone()));
+
+        let mut tables = tables.iter();
+        let mut sql = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Sql(text) => sql.push_str(text),
+                Segment::Hole(TemplateHole::Table) => {
+                    // One table was picked for every `{table}` hole
+                    let table = tables.next().expect("a table for every {table} hole");
+                    sql.push_str(&table.sql_name());
+                }
+                Segment::Hole(TemplateHole::Column) => {
+                    if columns.is_empty() {
+                        return Err(fuzzer_err(
+                            "The tables picked for the query template have no columns",
+                        ));
+                    }
+                    let column = columns[rng.random_range(0..columns.len())].clone();
+                    sql.push_str(&to_sql_string(&Expr::Column(column))?);
+                }
+                Segment::Hole(TemplateHole::Expr) => {
+                    let data_type =
+                        data_types[rng.random_range(0..data_types.len())].to_datafusion_type();
+                    sql.push_str(&to_sql_string(
+                        &expr_gen.generate_random_expr(data_type, 0),
+                    )?);
+                }
+                Segment::Hole(TemplateHole::Pred) => {
+                    sql.push_str(&to_sql_string(
+                        &expr_gen.generate_random_expr(DataType::Boolean, 0),
+                    )?);
+                }
+            }
+        }
+        Ok(sql)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::init_available_data_types;
+    use crate::fuzz_context::RunnerConfig;
+    use crate::oracle::test_helpers::{context_with_config, register_int_table};
+
+    #[test]
+    fn parses_holes_and_escaped_braces() {
+        let template = QueryTemplate::parse("SELECT {expr}, {{'a': 1}} FROM {table}").unwrap();
+        assert_eq!(
+            template.segments,
+            vec![
+                Segment::Sql("SELECT ".to_string()),
+                Segment::Hole(TemplateHole::Expr),
+                Segment::Sql(", {'a': 1} FROM ".to_string()),
+                Segment::Hole(TemplateHole::Table),
+            ]
+        );
+        assert_eq!(template.table_count(), 1);
+
+        for (template, error) in [
+            ("SELECT {exprs} FROM t1", "Unknown hole {exprs}"),
+            ("SELECT 1 FROM {table", "Unclosed {table"),
+            ("SELECT 1 }", "Unmatched }"),
+            ("SELECT {column}", "no {table} hole"),
+        ] {
+            let message = QueryTemplate::parse(template).unwrap_err().to_string();
+            assert!(message.contains(error), "{}: {}", template, message);
+        }
+    }
+
+    #[tokio::test]
+    async fn fills_holes_with_the_tables_of_the_round() {
+        init_available_data_types();
+        let ctx = context_with_config(RunnerConfig::default());
+        register_int_table(&ctx, &[Some(1), None]).await;
+
+        let template =
+            QueryTemplate::parse("SELECT {column}, {expr} FROM {table} WHERE {pred}").unwrap();
+        for seed in 0..10 {
+            let sql = template.fill(seed, &ctx).unwrap();
+            assert!(sql.starts_with("SELECT t1.c0, "), "{}", sql);
+            assert!(sql.contains(" FROM t1 WHERE "), "{}", sql);
+            assert_eq!(sql, template.fill(seed, &ctx).unwrap());
+        }
+
+        let template = QueryTemplate::parse("SELECT * FROM {table}, {table}").unwrap();
+        let message = template.fill(0, &ctx).unwrap_err().to_string();
+        assert!(
+            message.contains("needs 2 tables, the round has 1"),
+            "{}",
+            message
+        );
+    }
+}
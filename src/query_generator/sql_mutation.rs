@@ -0,0 +1,208 @@
+//! Corruptions of valid SQL text, to check the parser and planner reject
+//! invalid SQL with an error rather than a panic.
+
+use datafusion::sql::sqlparser::dialect::GenericDialect;
+use datafusion::sql::sqlparser::tokenizer::{Token, Tokenizer};
+use rand::{Rng, rngs::StdRng};
+
+/// Characters likely to trip up a tokenizer: control and zero-width
+/// characters, byte order marks, right-to-left overrides, combining marks,
+/// multi-byte letters and emoji
+const UNICODE_CHARS: &[char] = &[
+    '\u{0}', '\u{7}', '\u{a0}', '\u{200b}', '\u{202e}', '\u{301}', '\u{feff}', '\u{ffff}', 'é',
+    'ß', 'Ω', '中', '😀',
+];
+
+/// Characters with a meaning in SQL
+const PUNCTUATION: &[char] = &[
+    '\'', '"', '`', ';', ',', '.', '*', '-', '/', '\\', '(', ')', '[', ']', '$', ':', '=', '|',
+];
+
+/// A corruption of SQL text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SqlMutation {
+    /// Cut the SQL at a random character
+    Truncate,
+    /// Swap two tokens
+    SwapTokens,
+    /// Remove a token
+    DeleteToken,
+    /// Repeat a token
+    DuplicateToken,
+    /// Insert a `(` or a `)`
+    UnbalancedParen,
+    /// Insert a character of [`UNICODE_CHARS`] or a random one
+    Unicode,
+    /// Replace a character with one of [`PUNCTUATION`]
+    Punctuation,
+}
+
+impl SqlMutation {
+    const ALL: [SqlMutation; 7] = [
+        Self::Truncate,
+        Self::SwapTokens,
+        Self::DeleteToken,
+        Self::DuplicateToken,
+        Self::UnbalancedParen,
+        Self::Unicode,
+        Self::Punctuation,
+    ];
+
+    pub fn random(rng: &mut StdRng) -> Self {
+        Self::ALL[rng.random_range(0..Self::ALL.len())]
+    }
+
+    /// Apply the corruption to `sql`. Token mutations fall back to
+    /// truncation if `sql` (e.g. corrupted before) can't be tokenized.
+    pub fn apply(self, sql: &str, rng: &mut StdRng) -> String {
+        match self {
+            Self::Truncate => {
+                let boundaries = char_boundaries(sql);
+                sql[..boundaries[rng.random_range(0..boundaries.len())]].to_string()
+            }
+            Self::SwapTokens | Self::DeleteToken | Self::DuplicateToken => {
+                let Some(mut tokens) = tokenize(sql) else {
+                    return Self::Truncate.apply(sql, rng);
+                };
+                let words: Vec<usize> = (0..tokens.len())
+                    .filter(|&index| !matches!(tokens[index], Token::Whitespace(_)))
+                    .collect();
+                if words.is_empty() {
+                    return Self::Truncate.apply(sql, rng);
+                }
+                let index = words[rng.random_range(0..words.len())];
+                match self {
+                    Self::SwapTokens => {
+                        let other = words[rng.random_range(0..words.len())];
+                        tokens.swap(index, other);
+                    }
+                    Self::DeleteToken => {
+                        tokens.remove(index);
+                    }
+                    _ => {
+                        let token = tokens[index].clone();
+                        tokens.insert(index, token);
+                    }
+                }
+                tokens.iter().map(ToString::to_string).collect()
+            }
+            Self::UnbalancedParen => {
+                let paren = if rng.random_bool(0.5) { '(' } else { ')' };
+                insert_char(sql, paren, rng)
+            }
+            Self::Unicode => {
+                let c = if rng.random_bool(0.8) {
+                    UNICODE_CHARS[rng.random_range(0..UNICODE_CHARS.len())]
+                } else {
+                    rng.random()
+                };
+                insert_char(sql, c, rng)
+            }
+            Self::Punctuation => {
+                let boundaries = char_boundaries(sql);
+                if boundaries.len() < 2 {
+                    return sql.to_string();
+                }
+                let i = rng.random_range(0..boundaries.len() - 1);
+                let c = PUNCTUATION[rng.random_range(0..PUNCTUATION.len())];
+                format!(
+                    "{}{}{}",
+                    &sql[..boundaries[i]],
+                    c,
+                    &sql[boundaries[i + 1]..]
+                )
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for SqlMutation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Truncate => "truncate",
+            Self::SwapTokens => "swap tokens",
+            Self::DeleteToken => "delete token",
+            Self::DuplicateToken => "duplicate token",
+            Self::UnbalancedParen => "unbalanced paren",
+            Self::Unicode => "unicode",
+            Self::Punctuation => "punctuation",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Byte offsets of the character boundaries of `s`, including its end
+fn char_boundaries(s: &str) -> Vec<usize> {
+    s.char_indices()
+        .map(|(offset, _)| offset)
+        .chain(std::iter::once(s.len()))
+        .collect()
+}
+
+fn insert_char(s: &str, c: char, rng: &mut StdRng) -> String {
+    let boundaries = char_boundaries(s);
+    let offset = boundaries[rng.random_range(0..boundaries.len())];
+    format!("{}{}{}", &s[..offset], c, &s[offset..])
+}
+
+fn tokenize(sql: &str) -> Option<Vec<Token>> {
+    Tokenizer::new(&GenericDialect {}, sql).tokenize().ok()
+}
+
+/// Apply 1 to `max_mutations` random corruptions to `sql`, returning the
+/// corrupted SQL and the corruptions applied
+pub fn mutate_sql(sql: &str, max_mutations: usize, rng: &mut StdRng) -> (String, Vec<SqlMutation>) {
+    let mut mutated = sql.to_string();
+    let mut mutations = Vec::new();
+    for _ in 0..rng.random_range(1..=max_mutations.max(1)) {
+        let mutation = SqlMutation::random(rng);
+        mutated = mutation.apply(&mutated, rng);
+        mutations.push(mutation);
+    }
+    (mutated, mutations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::rng::rng_from_seed;
+
+    const SQL: &str = "SELECT t0.c0, 'é' FROM t0 WHERE (t0.c0 > 1)";
+
+    fn paren_balance(sql: &str) -> i64 {
+        sql.chars()
+            .map(|c| match c {
+                '(' => 1,
+                ')' => -1,
+                _ => 0,
+            })
+            .sum()
+    }
+
+    #[test]
+    fn corrupts_the_sql_as_named() {
+        for seed in 0..16 {
+            let mut rng = rng_from_seed(seed);
+            let truncated = SqlMutation::Truncate.apply(SQL, &mut rng);
+            assert!(SQL.starts_with(&truncated));
+
+            let deleted = SqlMutation::DeleteToken.apply(SQL, &mut rng);
+            assert!(deleted.len() < SQL.len());
+            let duplicated = SqlMutation::DuplicateToken.apply(SQL, &mut rng);
+            assert!(duplicated.len() > SQL.len());
+            let swapped = SqlMutation::SwapTokens.apply(SQL, &mut rng);
+            assert_eq!(swapped.len(), SQL.len());
+
+            let unbalanced = SqlMutation::UnbalancedParen.apply(SQL, &mut rng);
+            assert_eq!(paren_balance(&unbalanced).abs(), 1);
+            let unicode = SqlMutation::Unicode.apply(SQL, &mut rng);
+            assert_eq!(unicode.chars().count(), SQL.chars().count() + 1);
+            let punctuated = SqlMutation::Punctuation.apply(SQL, &mut rng);
+            assert_eq!(punctuated.chars().count(), SQL.chars().count());
+        }
+
+        let mut rng = rng_from_seed(0);
+        let (_, mutations) = mutate_sql(SQL, 3, &mut rng);
+        assert!((1..=3).contains(&mutations.len()));
+    }
+}
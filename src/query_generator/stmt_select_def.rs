@@ -1,21 +1,36 @@
 use std::sync::Arc;
 
 // use datafusion::sqlparser::ast;
+use datafusion::sql::unparser::dialect::{DefaultDialect, Dialect};
 use datafusion::{arrow::datatypes::DataType, common::Column, prelude::Expr};
 // Removed unused import: IndexedRandom
 use rand::{Rng, RngCore, rngs::StdRng, seq::SliceRandom};
 
 use crate::{
     common::{
-        InclusionConfig, LogicalTable, Result, fuzzer_err, get_available_data_types,
-        rng::rng_from_seed,
+        InclusionConfig, LogicalTable, Result, fuzzer_err, rng::rng_from_seed,
+        util::to_sql_string_with_dialect,
     },
-    fuzz_context::GlobalContext,
+    fuzz_context::{GlobalContext, swarm::SwarmFeature},
 };
 
 use super::expr_gen::ExprGenerator;
 use super::stmt_select_join::{JoinClause, JoinType};
 
+/// Number of times the source tables are picked again when their worst-case
+/// join size exceeds `join_row_budget`, before the largest ones are dropped
+const MAX_BUDGET_REPICKS: usize = 3;
+
+/// Upper bound of the rows a join of `tables` can produce: the size of their
+/// cartesian product. Empty tables count as one row, because outer joins keep
+/// the rows of the other side. Tables with unknown row counts (e.g. views)
+/// count as `unknown_rows`.
+pub fn worst_case_join_rows(tables: &[Arc<LogicalTable>], unknown_rows: u64) -> u64 {
+    tables.iter().fold(1u64, |rows, table| {
+        rows.saturating_mul(table.row_count().unwrap_or(unknown_rows).max(1))
+    })
+}
+
 // ================
 // Select Statement
 // ================
@@ -35,6 +50,10 @@ pub struct SelectStatement {
 impl SelectStatement {
     /// Formats the SELECT clause as SQL.
     pub fn to_select_sql(&self) -> Result<String> {
+        self.select_sql(&DefaultDialect {})
+    }
+
+    fn select_sql(&self, dialect: &dyn Dialect) -> Result<String> {
         if self.select_exprs.is_empty() {
             return Ok("SELECT *".to_string());
         }
@@ -42,7 +61,7 @@ impl SelectStatement {
         let expr_strings: Result<Vec<String>> = self
             .select_exprs
             .iter()
-            .map(crate::common::util::to_sql_string)
+            .map(|expr| to_sql_string_with_dialect(expr, dialect))
             .collect();
         Ok(format!("SELECT {}", expr_strings?.join(", ")))
     }
@@ -51,11 +70,11 @@ impl SelectStatement {
         self.from_clause
             .from_list
             .iter()
-            .map(|(table, alias)| {
+            .map(|(table_sql, alias)| {
                 if let Some(alias_name) = alias {
-                    format!("{} AS {}", table.name, alias_name)
+                    format!("{} AS {}", table_sql, alias_name)
                 } else {
-                    table.name.clone()
+                    table_sql.clone()
                 }
             })
             .collect::<Vec<String>>()
@@ -64,9 +83,13 @@ impl SelectStatement {
 
     /// Formats the FROM and optional JOIN section as SQL.
     pub fn to_from_join_sql(&self) -> Result<String> {
+        self.from_join_sql(&DefaultDialect {})
+    }
+
+    fn from_join_sql(&self, dialect: &dyn Dialect) -> Result<String> {
         let mut sql = format!("FROM {}", self.format_from_tables_sql());
         for join_clause in &self.join_clauses {
-            let join_string = join_clause.to_sql_string()?;
+            let join_string = join_clause.to_sql_string_with_dialect(dialect)?;
             sql.push_str(&format!("\n{}", join_string));
         }
         Ok(sql)
@@ -79,6 +102,10 @@ impl SelectStatement {
 
     /// Returns GROUP BY expressions as SQL (comma-separated) if present.
     pub fn to_group_by_sql(&self) -> Result<Option<String>> {
+        self.group_by_sql(&DefaultDialect {})
+    }
+
+    fn group_by_sql(&self, dialect: &dyn Dialect) -> Result<Option<String>> {
         if self.group_by_exprs.is_empty() {
             return Ok(None);
         }
@@ -86,7 +113,7 @@ impl SelectStatement {
         let group_by_strings: Result<Vec<String>> = self
             .group_by_exprs
             .iter()
-            .map(crate::common::util::to_sql_string)
+            .map(|expr| to_sql_string_with_dialect(expr, dialect))
             .collect();
         Ok(Some(group_by_strings?.join(", ")))
     }
@@ -103,28 +130,35 @@ impl SelectStatement {
 
     /// Formats the SELECT statement as a SQL string with pretty formatting
     pub fn to_sql_string(&self) -> Result<String> {
+        self.to_sql_string_with_dialect(&DefaultDialect {})
+    }
+
+    /// [`Self::to_sql_string`] with the expressions rendered in another
+    /// unparser dialect, e.g. with other quotes and type names. Table names
+    /// are quoted the same way in every dialect.
+    pub fn to_sql_string_with_dialect(&self, dialect: &dyn Dialect) -> Result<String> {
         // ==== SELECT clause ====
-        let mut sql = self.to_select_sql()?;
+        let mut sql = self.select_sql(dialect)?;
 
         // ==== FROM/JOIN clauses ====
         sql.push('\n');
-        sql.push_str(&self.to_from_join_sql()?);
+        sql.push_str(&self.from_join_sql(dialect)?);
 
         // ==== WHERE clause ====
         // Add WHERE clause if present
         if let Some(where_expr) = &self.where_clause {
-            let where_string = crate::common::util::to_sql_string(where_expr)?;
+            let where_string = to_sql_string_with_dialect(where_expr, dialect)?;
             sql.push_str(&format!("\nWHERE {}", where_string));
         }
 
         // ==== GROUP BY clause ====
-        if let Some(group_by_sql) = self.to_group_by_sql()? {
+        if let Some(group_by_sql) = self.group_by_sql(dialect)? {
             sql.push_str(&format!("\nGROUP BY {}", group_by_sql));
         }
 
         // ==== HAVING clause ====
         if let Some(having_expr) = &self.having_clause {
-            let having_string = crate::common::util::to_sql_string(having_expr)?;
+            let having_string = to_sql_string_with_dialect(having_expr, dialect)?;
             sql.push_str(&format!("\nHAVING {}", having_string));
         }
 
@@ -133,8 +167,8 @@ impl SelectStatement {
 }
 
 struct FromClause {
-    // vector of (table, alias)
-    from_list: Vec<(Arc<LogicalTable>, Option<String>)>,
+    // vector of (table as SQL, alias)
+    from_list: Vec<(String, Option<String>)>,
 }
 
 // ================
@@ -251,7 +285,7 @@ impl SelectStatementBuilder {
         let mut expr_gen = expr_gen.with_src_columns(src_columns.clone());
 
         // Build SELECT clause: generate expression list
-        let select_exprs = self.generate_select_exprs(&mut expr_gen)?;
+        let mut select_exprs = self.generate_select_exprs(&mut expr_gen)?;
 
         // Build WHERE clause (optional)
         let where_clause = self.generate_where_clause(&mut expr_gen)?;
@@ -259,17 +293,20 @@ impl SelectStatementBuilder {
         // Build GROUP BY and HAVING clauses (optional)
         let group_by_exprs = self.generate_group_by_exprs(&src_columns)?;
         let having_clause = self.generate_having_clause(&group_by_exprs)?;
+        // Other columns can't be selected without being aggregated
+        if !group_by_exprs.is_empty() {
+            select_exprs = group_by_exprs.clone();
+        }
 
         // Build FROM clause
+        let from_tables = self.from_tables.clone();
+        let from_list = from_tables
+            .iter()
+            .map(|table| (self.table_sql(table), None))
+            .collect();
         Ok(SelectStatement {
             select_exprs,
-            from_clause: FromClause {
-                from_list: self
-                    .from_tables
-                    .iter()
-                    .map(|table| (table.clone(), None))
-                    .collect(),
-            },
+            from_clause: FromClause { from_list },
             join_clauses: self.join_clauses.clone(),
             where_clause,
             group_by_exprs,
@@ -286,14 +323,24 @@ impl SelectStatementBuilder {
         let cfg_max_table_count = self
             .max_table_count
             .unwrap_or(self.ctx.runner_config.max_table_count);
-        let num_src_tables = self.rng.random_range(1..=cfg_max_table_count);
+        // Without joins in the round's swarm, FROM can't cross join either
+        let num_src_tables = if matches!(self.enable_join_clause, InclusionConfig::Maybe(_))
+            && !self.ctx.swarm_allows(SwarmFeature::Joins)
+        {
+            1
+        } else {
+            self.rng.random_range(1..=cfg_max_table_count)
+        };
 
         // Get all available tables, filtered by allow_derived_tables setting
-        let tables_lock = self.ctx.runtime_context.registered_tables.read().unwrap();
-        let mut available_tables: Vec<Arc<LogicalTable>> = tables_lock.values().cloned().collect();
-
-        // Sort tables by name to ensure deterministic ordering
-        available_tables.sort_by(|a, b| a.name.cmp(&b.name));
+        // The registry is ordered by name, so the choice is deterministic
+        let available_tables: Vec<Arc<LogicalTable>> = self
+            .ctx
+            .runtime_context
+            .tables()
+            .values()
+            .cloned()
+            .collect();
 
         if available_tables.is_empty() {
             return Err(fuzzer_err(
@@ -304,9 +351,42 @@ impl SelectStatementBuilder {
         // Determine how many tables to pick (bounded by available tables)
         let num_tables = std::cmp::min(num_src_tables, available_tables.len() as u32) as usize;
 
+        let mut selected_tables = self.sample_tables(&available_tables, num_tables);
+
+        // ==== Keep the worst-case join size within the row budget ====
+        // Large cartesian products mostly end in timeouts, which test nothing
+        if let Some(budget) = self.ctx.runner_config.join_row_budget {
+            let unknown_rows = self.ctx.runner_config.max_row_count;
+            let mut repicks = 0;
+            while worst_case_join_rows(&selected_tables, unknown_rows) > budget
+                && repicks < MAX_BUDGET_REPICKS
+            {
+                selected_tables = self.sample_tables(&available_tables, num_tables);
+                repicks += 1;
+            }
+
+            while selected_tables.len() > 1
+                && worst_case_join_rows(&selected_tables, unknown_rows) > budget
+            {
+                let largest = (0..selected_tables.len())
+                    .max_by_key(|&i| selected_tables[i].row_count().unwrap_or(unknown_rows))
+                    .unwrap_or_default();
+                selected_tables.remove(largest);
+            }
+        }
+
+        Ok(selected_tables)
+    }
+
+    /// Pick `num_tables` distinct tables of `available_tables` in random order
+    fn sample_tables(
+        &mut self,
+        available_tables: &[Arc<LogicalTable>],
+        num_tables: usize,
+    ) -> Vec<Arc<LogicalTable>> {
         // Use sample API for more elegant random selection without replacement
         // to avoid duplicate tables in the FROM clause
-        let mut available_tables_clone = available_tables.clone();
+        let mut available_tables_clone = available_tables.to_vec();
         let mut selected_tables = Vec::new();
 
         for _ in 0..num_tables {
@@ -318,7 +398,7 @@ impl SelectStatementBuilder {
             selected_tables.push(Arc::clone(&table));
         }
 
-        Ok(selected_tables)
+        selected_tables
     }
 
     /// Partition source tables into FROM tables and JOIN clauses
@@ -343,7 +423,7 @@ impl SelectStatementBuilder {
         src_tables.shuffle(&mut self.rng);
 
         // If JOIN generation is disabled, place all tables in FROM and return no JOINs
-        if !self.enable_join_clause.should_enable(Some(&mut self.rng)) {
+        if !self.should_generate(self.enable_join_clause, SwarmFeature::Joins) {
             return Ok((src_tables, Vec::new()));
         }
 
@@ -399,7 +479,7 @@ impl SelectStatementBuilder {
             };
 
             join_clauses.push(Arc::new(JoinClause {
-                join_table: Arc::clone(&join_table),
+                join_table_sql: self.table_sql(&join_table),
                 join_type,
                 join_on_expr,
             }));
@@ -409,10 +489,34 @@ impl SelectStatementBuilder {
         Ok((from_tables, join_clauses))
     }
 
+    /// Whether to generate an optional clause. Swarm testing can turn off
+    /// clauses that are only `Maybe` generated, never the ones an oracle
+    /// requires, and `coverage_targets` can generate them more often.
+    fn should_generate(&mut self, inclusion: InclusionConfig, feature: SwarmFeature) -> bool {
+        let inclusion = match inclusion {
+            InclusionConfig::Maybe(_) if !self.ctx.swarm_allows(feature) => return false,
+            InclusionConfig::Maybe(probability) => {
+                InclusionConfig::Maybe(self.ctx.steered_probability(feature, probability))
+            }
+            always => always,
+        };
+        inclusion.should_enable(Some(&mut self.rng))
+    }
+
+    /// `table` as it appears in FROM and JOIN clauses, qualified at random
+    /// with `multi_schema`
+    fn table_sql(&mut self, table: &LogicalTable) -> String {
+        if self.ctx.runner_config.multi_schema {
+            table.random_sql_name(&mut self.rng)
+        } else {
+            table.sql_name()
+        }
+    }
+
     /// Generate a random WHERE clause expression (returns None for no WHERE clause)
     fn generate_where_clause(&mut self, expr_gen: &mut ExprGenerator) -> Result<Option<Expr>> {
         // Decide if the WHERE clause should be generated
-        if self.enable_where_clause.should_enable(Some(&mut self.rng)) {
+        if self.should_generate(self.enable_where_clause, SwarmFeature::Where) {
             // Generate a boolean expression for the WHERE clause
             let where_expr =
                 expr_gen.generate_random_expr(datafusion::arrow::datatypes::DataType::Boolean, 0);
@@ -424,9 +528,7 @@ impl SelectStatementBuilder {
 
     /// Generate GROUP BY expressions from source columns.
     fn generate_group_by_exprs(&mut self, src_columns: &Arc<Vec<Column>>) -> Result<Vec<Expr>> {
-        if !self
-            .enable_group_by_clause
-            .should_enable(Some(&mut self.rng))
+        if !self.should_generate(self.enable_group_by_clause, SwarmFeature::GroupBy)
             || src_columns.is_empty()
         {
             return Ok(Vec::new());
@@ -482,7 +584,7 @@ impl SelectStatementBuilder {
         let cfg_max_select_exprs = self.ctx.runner_config.max_expr_level as usize;
         let num_select_exprs = self.rng.random_range(1..=cfg_max_select_exprs);
 
-        let available_types = get_available_data_types();
+        let available_types = self.ctx.generated_data_types();
         let select_exprs = (0..num_select_exprs)
             .map(|_| {
                 // Pick a random type from available types instead of hardcoded Int64
@@ -495,3 +597,96 @@ impl SelectStatementBuilder {
         Ok(select_exprs)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::value_summary::ColumnValueSummary;
+    use crate::common::{FuzzerDataType, LogicalColumn};
+    use crate::fuzz_context::RunnerConfig;
+    use crate::oracle::test_helpers::context_with_config;
+    use std::collections::BTreeMap;
+
+    fn table_with_rows(name: &str, rows: u64) -> Arc<LogicalTable> {
+        Arc::new(LogicalTable::with_columns(
+            name.to_string(),
+            vec![LogicalColumn {
+                name: "c0".to_string(),
+                data_type: FuzzerDataType::Int64,
+                value_summary: Some(Arc::new(ColumnValueSummary {
+                    row_count: rows,
+                    ..Default::default()
+                })),
+            }],
+        ))
+    }
+
+    #[test]
+    fn worst_case_join_rows_multiplies_row_counts() {
+        let view = Arc::new(LogicalTable::new("v0".to_string()));
+        let tables = [
+            table_with_rows("t0", 10),
+            table_with_rows("t1", 0),
+            table_with_rows("t2", 20),
+        ];
+        assert_eq!(worst_case_join_rows(&tables, 100), 200);
+        assert_eq!(worst_case_join_rows(&[tables[0].clone(), view], 100), 1000);
+        assert_eq!(
+            worst_case_join_rows(&[table_with_rows("t3", u64::MAX), tables[2].clone()], 1),
+            u64::MAX
+        );
+    }
+
+    #[test]
+    fn picked_tables_fit_the_join_row_budget() {
+        let ctx = context_with_config(RunnerConfig {
+            max_table_count: 4,
+            join_row_budget: Some(1000),
+            ..Default::default()
+        });
+        for (i, rows) in [5, 50, 500, 5000].into_iter().enumerate() {
+            ctx.runtime_context
+                .register_table(table_with_rows(&format!("t{}", i), rows));
+        }
+
+        for seed in 0..32 {
+            let mut builder = SelectStatementBuilder::new(
+                seed,
+                Arc::clone(&ctx),
+                InclusionConfig::Always(false),
+                InclusionConfig::Always(false),
+            );
+            let tables = builder.pick_src_tables().unwrap();
+            assert!(!tables.is_empty());
+            assert!(tables.len() == 1 || worst_case_join_rows(&tables, 100) <= 1000);
+        }
+    }
+    #[test]
+    fn boosted_clauses_are_generated_more_often() {
+        crate::common::init_available_data_types();
+        let ctx = context_with_config(RunnerConfig {
+            coverage_boosts: BTreeMap::from([(SwarmFeature::GroupBy, 1.0)]),
+            ..Default::default()
+        });
+        ctx.runtime_context.register_table(table_with_rows("t0", 5));
+        let generate = |seed| {
+            SelectStatementBuilder::new(
+                seed,
+                Arc::clone(&ctx),
+                InclusionConfig::Always(false),
+                InclusionConfig::Always(false),
+            )
+            .with_enable_group_by_clause(InclusionConfig::Maybe(0.0))
+            .generate_stmt()
+            .unwrap()
+        };
+
+        for seed in 0..16 {
+            let stmt = generate(seed);
+            assert!(!stmt.group_by_exprs.is_empty());
+            assert_eq!(stmt.select_exprs, stmt.group_by_exprs);
+        }
+        ctx.set_coverage_boosts(BTreeMap::new());
+        assert!(generate(0).group_by_exprs.is_empty());
+    }
+}
@@ -2,9 +2,10 @@
 use std::sync::Arc;
 
 use datafusion::prelude::Expr;
+use datafusion::sql::unparser::dialect::{DefaultDialect, Dialect};
 use rand::{Rng, rngs::StdRng};
 
-use crate::common::{LogicalTable, Result};
+use crate::common::Result;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub(crate) enum JoinType {
@@ -75,7 +76,8 @@ impl std::fmt::Display for JoinType {
 ///   likely to fail. It's possible to support this in the future.
 /// - All other join types support join expression
 pub(crate) struct JoinClause {
-    pub(crate) join_table: Arc<LogicalTable>,
+    /// The joined table as it appears in the query, possibly qualified
+    pub(crate) join_table_sql: String,
     pub(crate) join_type: JoinType,
     pub(crate) join_on_expr: Option<Arc<Expr>>,
 }
@@ -85,12 +87,18 @@ impl JoinClause {
     /// `JOIN t1 ON t0.v1 = t1.v1`
     /// If `join_on_expr` is None, omit the ON clause.
     pub fn to_sql_string(&self) -> Result<String> {
-        let base = format!("{} {}", self.join_type, self.join_table.name);
+        self.to_sql_string_with_dialect(&DefaultDialect {})
+    }
+
+    /// [`Self::to_sql_string`] with the ON expression rendered in another
+    /// unparser dialect
+    pub fn to_sql_string_with_dialect(&self, dialect: &dyn Dialect) -> Result<String> {
+        let base = format!("{} {}", self.join_type, self.join_table_sql);
         if let Some(expr) = &self.join_on_expr {
             Ok(format!(
                 "{} ON {}",
                 base,
-                crate::common::util::to_sql_string(expr)?
+                crate::common::util::to_sql_string_with_dialect(expr, dialect)?
             ))
         } else {
             Ok(base)
@@ -0,0 +1,128 @@
+//! Dialects of DataFusion's unparser generated expressions can be rendered
+//! with, besides the default one.
+
+use datafusion::sql::sqlparser::ast;
+use datafusion::sql::unparser::dialect::{
+    CharacterLengthStyle, CustomDialectBuilder, DateFieldExtractStyle, Dialect, DuckDBDialect,
+    IntervalStyle, MySqlDialect, PostgreSqlDialect, SqliteDialect,
+};
+use rand::{Rng, rngs::StdRng};
+
+/// An unparser dialect, with a description for logs and reports
+pub struct UnparserDialect {
+    pub description: String,
+    pub dialect: Box<dyn Dialect>,
+}
+
+impl UnparserDialect {
+    /// One of the built-in dialects, or a custom dialect with random settings
+    pub fn random(rng: &mut StdRng) -> Self {
+        let (description, dialect): (&str, Box<dyn Dialect>) = match rng.random_range(0..6) {
+            0 => ("PostgreSQL", Box::new(PostgreSqlDialect {})),
+            1 => ("MySQL", Box::new(MySqlDialect {})),
+            2 => ("SQLite", Box::new(SqliteDialect {})),
+            3 => ("DuckDB", Box::new(DuckDBDialect::new())),
+            _ => return Self::random_custom(rng),
+        };
+        Self {
+            description: description.to_string(),
+            dialect,
+        }
+    }
+
+    /// A custom dialect, each setting picked at random
+    fn random_custom(rng: &mut StdRng) -> Self {
+        let quote_style = if rng.random_bool(0.5) { '"' } else { '`' };
+        let (interval_style, interval_name) = match rng.random_range(0..3) {
+            0 => (IntervalStyle::PostgresVerbose, "PostgreSQL verbose"),
+            1 => (IntervalStyle::SQLStandard, "SQL standard"),
+            _ => (IntervalStyle::MySQL, "MySQL"),
+        };
+        let (extract_style, extract_name) = match rng.random_range(0..3) {
+            0 => (DateFieldExtractStyle::DatePart, "date_part"),
+            1 => (DateFieldExtractStyle::Extract, "EXTRACT"),
+            _ => (DateFieldExtractStyle::Strftime, "strftime"),
+        };
+        let (length_style, length_name) = if rng.random_bool(0.5) {
+            (CharacterLengthStyle::Length, "length")
+        } else {
+            (CharacterLengthStyle::CharacterLength, "character_length")
+        };
+        let (utf8_type, utf8_name) = match rng.random_range(0..3) {
+            0 => (ast::DataType::Varchar(None), "VARCHAR"),
+            1 => (ast::DataType::Text, "TEXT"),
+            _ => (ast::DataType::Char(None), "CHAR"),
+        };
+        let (int64_type, int64_name) = if rng.random_bool(0.5) {
+            (ast::DataType::BigInt(None), "BIGINT")
+        } else {
+            (ast::DataType::Int64, "INT64")
+        };
+        let (float64_type, float64_name) = if rng.random_bool(0.5) {
+            (ast::DataType::DoublePrecision, "DOUBLE PRECISION")
+        } else {
+            (ast::DataType::Float64, "FLOAT64")
+        };
+        let nulls_first = rng.random_bool(0.5);
+        let timestamp_for_date64 = rng.random_bool(0.5);
+
+        let dialect = CustomDialectBuilder::new()
+            .with_identifier_quote_style(quote_style)
+            .with_interval_style(interval_style)
+            .with_date_field_extract_style(extract_style)
+            .with_character_length_style(length_style)
+            .with_utf8_cast_dtype(utf8_type)
+            .with_int64_cast_dtype(int64_type)
+            .with_float64_ast_dtype(float64_type)
+            .with_supports_nulls_first_in_sort(nulls_first)
+            .with_use_timestamp_for_date64(timestamp_for_date64)
+            .build();
+        let description = format!(
+            "custom: {} quotes, {} intervals, {} for date fields, {} for string length, \
+             {} / {} / {} casts, NULLS FIRST {}, Date64 as {}",
+            quote_style,
+            interval_name,
+            extract_name,
+            length_name,
+            utf8_name,
+            int64_name,
+            float64_name,
+            if nulls_first {
+                "supported"
+            } else {
+                "unsupported"
+            },
+            if timestamp_for_date64 {
+                "TIMESTAMP"
+            } else {
+                "DATETIME"
+            },
+        );
+        Self {
+            description,
+            dialect: Box::new(dialect),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::rng::rng_from_seed;
+    use crate::common::util::to_sql_string_with_dialect;
+    use datafusion::prelude::{col, lit};
+
+    #[test]
+    fn dialects_render_identifiers_their_way() {
+        let expr = col("c0").eq(lit(1));
+        let mut rng = rng_from_seed(7);
+        let mut quoted = false;
+        for _ in 0..32 {
+            let dialect = UnparserDialect::random(&mut rng);
+            let sql = to_sql_string_with_dialect(&expr, dialect.dialect.as_ref()).unwrap();
+            assert!(sql.contains("c0") && sql.ends_with('1'), "{}", sql);
+            quoted |= sql.starts_with('`');
+        }
+        assert!(quoted);
+    }
+}
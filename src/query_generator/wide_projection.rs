@@ -0,0 +1,69 @@
+//! SELECT lists of thousands of expressions and GROUP BY lists of hundreds of
+//! keys, to probe the scalability of the planner and of schema handling.
+
+use rand::seq::SliceRandom;
+use rand::{Rng, rngs::StdRng};
+
+/// `width` SELECT list items over `columns` (SQL names, qualified if needed),
+/// aliased `e0`, `e1`, ... so their names are unique
+pub fn wide_select_list(columns: &[String], width: usize, rng: &mut StdRng) -> Vec<String> {
+    (0..width)
+        .map(|index| {
+            let column = &columns[rng.random_range(0..columns.len())];
+            let expr = match rng.random_range(0..6) {
+                0 => column.clone(),
+                1 => format!("{} IS NULL", column),
+                2 => format!("{} IS NOT NULL", column),
+                3 => format!("coalesce({0}, {0})", column),
+                4 => index.to_string(),
+                _ => format!(
+                    "CASE WHEN {} IS NULL THEN {} ELSE -{} END",
+                    column, index, index
+                ),
+            };
+            format!("{} AS e{}", expr, index)
+        })
+        .collect()
+}
+
+/// `width` distinct GROUP BY keys over `columns`, or one per column if there
+/// are more columns. Every column is a key and the other keys are functions of
+/// a column, so grouping by the keys finds the same groups as grouping by the
+/// columns.
+pub fn wide_group_by_keys(columns: &[String], width: usize, rng: &mut StdRng) -> Vec<String> {
+    let mut keys = columns.to_vec();
+    for index in keys.len()..width {
+        let column = &columns[rng.random_range(0..columns.len())];
+        keys.push(format!(
+            "CASE WHEN {} IS NULL THEN {} ELSE -{} END",
+            column, index, index
+        ));
+    }
+    keys.shuffle(rng);
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::rng::rng_from_seed;
+
+    #[test]
+    fn group_by_keys_cover_every_column() {
+        let columns = vec!["t0.c0".to_string(), "t0.c1".to_string()];
+        let mut rng = rng_from_seed(3);
+
+        let select_list = wide_select_list(&columns, 1000, &mut rng);
+        assert_eq!(select_list.len(), 1000);
+        assert!(select_list[999].ends_with(" AS e999"));
+
+        let keys = wide_group_by_keys(&columns, 100, &mut rng);
+        assert_eq!(keys.len(), 100);
+        for column in &columns {
+            assert!(keys.contains(column));
+        }
+        let distinct: std::collections::HashSet<&String> = keys.iter().collect();
+        assert_eq!(distinct.len(), keys.len());
+        assert_eq!(wide_group_by_keys(&columns, 1, &mut rng).len(), 2);
+    }
+}
@@ -0,0 +1,134 @@
+// Triage module - classifies fuzzer findings by severity
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Category of a fuzzer finding, ordered roughly by severity.
+///
+/// Errors are classified from their message, since both DataFusion errors and
+/// fuzzer-side failures (timeouts, task failures) only reach the runner as text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FailureCategory {
+    /// The query task panicked
+    Panic,
+    /// `DataFusionError::Internal`, the engine reported a bug in itself
+    InternalError,
+    /// The query ran out of memory (`Resources exhausted`), expected under a
+    /// memory limit
+    ResourceExhausted,
+    /// Arrow kernel failure, e.g. during casting or arithmetic
+    ArrowComputeError,
+    /// SQL parsing, planning or optimization failure
+    PlannerError,
+    /// The query did not finish planning within `plan_timeout_ms`
+    PlanningTimeout,
+    /// The query did not finish within the timeout
+    Timeout,
+    /// An oracle consistency check failed
+    WrongResult,
+    /// Equivalent queries of a group disagree on success vs. error
+    InconsistentOutcome,
+    /// Process memory grew round after round, see `memory_leak_rounds`
+    MemoryLeak,
+    /// A query took longer than `planning_time_threshold_ms` to plan
+    SlowPlanning,
+    /// Any other execution error
+    Other,
+}
+
+impl FailureCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FailureCategory::Panic => "Panic",
+            FailureCategory::InternalError => "InternalError",
+            FailureCategory::ResourceExhausted => "ResourceExhausted",
+            FailureCategory::ArrowComputeError => "ArrowComputeError",
+            FailureCategory::PlannerError => "PlannerError",
+            FailureCategory::PlanningTimeout => "PlanningTimeout",
+            FailureCategory::Timeout => "Timeout",
+            FailureCategory::WrongResult => "WrongResult",
+            FailureCategory::InconsistentOutcome => "InconsistentOutcome",
+            FailureCategory::MemoryLeak => "MemoryLeak",
+            FailureCategory::SlowPlanning => "SlowPlanning",
+            FailureCategory::Other => "Other",
+        }
+    }
+}
+
+impl fmt::Display for FailureCategory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Classify a non-whitelisted query error by its message.
+///
+/// The checks go from most to least severe, so e.g. an internal error raised
+/// while planning is reported as `InternalError` rather than `PlannerError`.
+pub fn classify_error(error_msg: &str) -> FailureCategory {
+    if error_msg.contains("panicked") || error_msg.contains("Query task failed") {
+        FailureCategory::Panic
+    } else if error_msg.contains("Internal error") {
+        FailureCategory::InternalError
+    } else if error_msg.contains("Resources exhausted") {
+        FailureCategory::ResourceExhausted
+    } else if error_msg.contains("Query planning timed out") {
+        FailureCategory::PlanningTimeout
+    } else if error_msg.contains("timed out") {
+        FailureCategory::Timeout
+    } else if error_msg.contains("Arrow error") {
+        FailureCategory::ArrowComputeError
+    } else if error_msg.contains("Query planning failed")
+        || error_msg.contains("Error during planning")
+        || error_msg.contains("SQL error")
+        || error_msg.contains("Schema error")
+        || error_msg.contains("Optimizer rule")
+    {
+        FailureCategory::PlannerError
+    } else {
+        FailureCategory::Other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_error_messages() {
+        let cases = [
+            ("Query task failed", FailureCategory::Panic),
+            (
+                "Query execution failed: thread 'tokio-runtime-worker' panicked at src/lib.rs:1:1",
+                FailureCategory::Panic,
+            ),
+            (
+                "Query planning failed: Internal error: Physical input schema should be the same.",
+                FailureCategory::InternalError,
+            ),
+            (
+                "Query execution failed: Resources exhausted: Failed to allocate additional 1024.0 KB for ExternalSorter[0]",
+                FailureCategory::ResourceExhausted,
+            ),
+            ("Query planning timed out", FailureCategory::PlanningTimeout),
+            ("Query execution timed out", FailureCategory::Timeout),
+            (
+                "Query execution failed: Arrow error: Compute error: Overflow happened on: 1 + 2",
+                FailureCategory::ArrowComputeError,
+            ),
+            (
+                "Query planning failed: Error during planning: Cannot coerce",
+                FailureCategory::PlannerError,
+            ),
+            (
+                "Query execution failed: Execution error: something else",
+                FailureCategory::Other,
+            ),
+        ];
+
+        for (message, expected) in cases {
+            assert_eq!(classify_error(message), expected, "message: {}", message);
+        }
+    }
+}
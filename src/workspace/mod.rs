@@ -0,0 +1,113 @@
+//! Per-run temporary directory for the files a run generates.
+//!
+//! Tables of file-backed datasets, spill files, spooled query results and
+//! `datafusion-cli` reproducers go to subdirectories of one directory per
+//! run, unless `dataset_dir`, `spill_dir` or the log directory say otherwise.
+//! The directory is deleted after a run without findings, and kept after a
+//! failed run or with `keep_artifacts` so the files can be inspected.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::common::Result;
+
+/// Directory holding the generated files of a run, see the module docs
+#[derive(Debug)]
+pub struct Workspace {
+    root: PathBuf,
+    keep_artifacts: bool,
+}
+
+impl Workspace {
+    /// Create `datafusion-fuzzer-run-<pid>-<millis>` with its subdirectories
+    /// in `base_dir`, or the OS temp directory
+    pub fn create(base_dir: Option<&Path>, keep_artifacts: bool) -> Result<Self> {
+        let base_dir = base_dir.map_or_else(std::env::temp_dir, Path::to_path_buf);
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis())
+            .unwrap_or_default();
+        let workspace = Self {
+            root: base_dir.join(format!(
+                "datafusion-fuzzer-run-{}-{}",
+                std::process::id(),
+                millis
+            )),
+            keep_artifacts,
+        };
+
+        for dir in [
+            workspace.datasets_dir(),
+            workspace.spill_dir(),
+            workspace.spooled_results_dir(),
+            workspace.reproducers_dir(),
+        ] {
+            fs::create_dir_all(dir)?;
+        }
+        Ok(workspace)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Files of file-backed tables
+    pub fn datasets_dir(&self) -> PathBuf {
+        self.root.join("datasets")
+    }
+
+    /// Spill files of queries running out of memory
+    pub fn spill_dir(&self) -> PathBuf {
+        self.root.join("spill")
+    }
+
+    /// Large query results written to disk, see `spool_results`
+    pub fn spooled_results_dir(&self) -> PathBuf {
+        self.root.join("results")
+    }
+
+    /// `datafusion-cli` reproducers of findings
+    pub fn reproducers_dir(&self) -> PathBuf {
+        self.root.join("reproducers")
+    }
+
+    /// Delete the workspace after a `successful` run, unless artifacts are
+    /// kept. Returns whether the workspace was kept.
+    pub fn finish(&self, successful: bool) -> Result<bool> {
+        if successful && !self.keep_artifacts {
+            fs::remove_dir_all(&self.root)?;
+            return Ok(false);
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deletes_the_workspace_after_a_successful_run() {
+        let base_dir = tempfile::tempdir().unwrap();
+        let workspace = Workspace::create(Some(base_dir.path()), false).unwrap();
+        assert!(workspace.datasets_dir().is_dir());
+        assert!(workspace.spill_dir().is_dir());
+        fs::write(workspace.datasets_dir().join("t.parquet"), b"x").unwrap();
+
+        assert!(!workspace.finish(true).unwrap());
+        assert!(!workspace.root().exists());
+    }
+
+    #[test]
+    fn keeps_the_workspace_after_a_failed_run_or_when_asked() {
+        let base_dir = tempfile::tempdir().unwrap();
+        let failed = Workspace::create(Some(base_dir.path()), false).unwrap();
+        assert!(failed.finish(false).unwrap());
+        assert!(failed.reproducers_dir().is_dir());
+
+        let kept = Workspace::create(Some(&base_dir.path().join("kept")), true).unwrap();
+        assert!(kept.finish(true).unwrap());
+        assert!(kept.root().is_dir());
+    }
+}
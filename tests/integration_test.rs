@@ -21,62 +21,62 @@ fn full_run_logs_expected_queries_and_stats_for_no_crash_oracle() -> Result<(),
     let run_output = run_fuzzer_once(&config_path)?;
 
     insta::assert_snapshot!(run_output.query_log, @r#"
-    === round=1 query=1 oracle=NoCrashOracle query_seed=310304 ===
+    === id=310104-r1-q1 round=1 query=1 oracle=NoCrashOracle query_seed=310304 ===
     --- statement=1 context=Random Query No-Crash Test ---
     SELECT (31905.000000000000 + 42185.000000000000000000000), NULL, ((26.72593219656791 * (-31.000798999170783 + -47.79515993907295)) % 8.375943795966606)
     FROM t0, t2, t1
     WHERE true
 
-    === round=1 query=2 oracle=NoCrashOracle query_seed=310305 ===
+    === id=310104-r1-q2 round=1 query=2 oracle=NoCrashOracle query_seed=310305 ===
     --- statement=1 context=Random Query No-Crash Test ---
     SELECT ((current_time() + current_time()) < -55), (65.706448177695 * ((NULL + -0.06514454367550115) + -85.93427693961893))
     FROM t0, t2, t1
     WHERE true
 
-    === round=1 query=3 oracle=NoCrashOracle query_seed=310306 ===
+    === id=310104-r1-q3 round=1 query=3 oracle=NoCrashOracle query_seed=310306 ===
     --- statement=1 context=Random Query No-Crash Test ---
     SELECT NULL
     FROM t2
     WHERE false
 
-    === round=1 query=4 oracle=NoCrashOracle query_seed=310307 ===
+    === id=310104-r1-q4 round=1 query=4 oracle=NoCrashOracle query_seed=310307 ===
     --- statement=1 context=Random Query No-Crash Test ---
     SELECT (NULL + ((t0.col_t0_2_float32 % t0.col_t0_2_float32) / t0.col_t0_2_float32)), -28
     FROM t0
     WHERE false
 
-    === round=1 query=5 oracle=NoCrashOracle query_seed=310308 ===
+    === id=310104-r1-q5 round=1 query=5 oracle=NoCrashOracle query_seed=310308 ===
     --- statement=1 context=Random Query No-Crash Test ---
     SELECT (to_char((CAST('05:08:41.131473326' AS TIME) + NULL), '7{"kwxZt~K:1-?59') ~~* ';g o|<e1t5PeUZgc1*<DsT[MNI;W=ly5GZ9::]%%=y'), (((72 + NULL) % (110 % 131)) / 68), (49.672875494573475 / (-53.663513437485165 + NULL))
     FROM t2, t0, t1
     WHERE (t2.col_t2_1_string ~* 'xTd4vs>d>OZr?2F')
 
-    === round=2 query=1 oracle=NoCrashOracle query_seed=311304 ===
+    === id=310104-r2-q1 round=2 query=1 oracle=NoCrashOracle query_seed=311304 ===
     --- statement=1 context=Random Query No-Crash Test ---
     SELECT (t1.col_t1_3_decimal128 * ((97585.0000000000 * -29056.0000000000000000000000) - 42588.00000000000000000000000000000)), (((192 - 122) - (NULL % 70)) - (136 * (179 % 20))), (((53 / 91) - (59 - 22)) % 80)
     FROM t1, t0
     WHERE (CAST('17:49:46.025384417' AS TIME) = ((-36.406612 / -26.527832) / 96.30975))
 
-    === round=2 query=2 oracle=NoCrashOracle query_seed=311305 ===
+    === id=310104-r2-q2 round=2 query=2 oracle=NoCrashOracle query_seed=311305 ===
     --- statement=1 context=Random Query No-Crash Test ---
     SELECT t0.col_t0_2_time64_nanosecond, 146
     FROM t2, t0
     RIGHT SEMI JOIN t1 ON ('/*L/' !~ to_char((t0.col_t0_4_interval_month_day_nano - INTERVAL '4 MONS 29 DAYS -0.138771504 SECS'), '3v3F=3`'))
     WHERE (to_char(t0.col_t0_4_interval_month_day_nano, NULL) !~ to_char(NULL, 'yUANHM J(=="$M@XZkO9lj$9qO>]!2v7EoH8@mjXdBV'))
 
-    === round=2 query=3 oracle=NoCrashOracle query_seed=311306 ===
+    === id=310104-r2-q3 round=2 query=3 oracle=NoCrashOracle query_seed=311306 ===
     --- statement=1 context=Random Query No-Crash Test ---
     SELECT (118 % 25), -56, (((54 + -74) / (-45 * -27)) + 23)
     FROM t2
     WHERE (-30 > (175 * NULL))
 
-    === round=2 query=4 oracle=NoCrashOracle query_seed=311307 ===
+    === id=310104-r2-q4 round=2 query=4 oracle=NoCrashOracle query_seed=311307 ===
     --- statement=1 context=Random Query No-Crash Test ---
     SELECT to_char(t0.col_t0_5_timestamp, '64kk&7.OusV5v6J"/nN,9z"c >$+qIc}I'), (-43 * ((98 % 80) / (-50 % 94))), CAST('2000-12-22' AS DATE)
     FROM t0, t1
     WHERE (37.643173 < (NULL + (NULL % 47)))
 
-    === round=2 query=5 oracle=NoCrashOracle query_seed=311308 ===
+    === id=310104-r2-q5 round=2 query=5 oracle=NoCrashOracle query_seed=311308 ===
     --- statement=1 context=Random Query No-Crash Test ---
     SELECT (-24 / (94 - NULL))
     FROM t2
@@ -106,7 +106,7 @@ fn full_run_logs_expected_queries_for_tlp_where_oracle() -> Result<(), Box<dyn E
     let run_output = run_fuzzer_once(&config_path)?;
 
     insta::assert_snapshot!(run_output.query_log, @r#"
-    === round=1 query=1 oracle=TlpWhereOracle query_seed=310304 ===
+    === id=310104-r1-q1 round=1 query=1 oracle=TlpWhereOracle query_seed=310304 ===
     --- statement=1 context=TLP-WHERE all ---
     SELECT *
     FROM t0
@@ -124,7 +124,7 @@ fn full_run_logs_expected_queries_for_tlp_where_oracle() -> Result<(), Box<dyn E
     FROM t0
     WHERE (((NULL > NULL) OR (CAST('13:24:10.016648859' AS TIME) > (-44 + -91)))) IS NULL
 
-    === round=1 query=2 oracle=TlpWhereOracle query_seed=310305 ===
+    === id=310104-r1-q2 round=1 query=2 oracle=TlpWhereOracle query_seed=310305 ===
     --- statement=1 context=TLP-WHERE all ---
     SELECT *
     FROM t1
@@ -142,7 +142,7 @@ fn full_run_logs_expected_queries_for_tlp_where_oracle() -> Result<(), Box<dyn E
     FROM t1
     WHERE (false) IS NULL
 
-    === round=1 query=3 oracle=TlpWhereOracle query_seed=310306 ===
+    === id=310104-r1-q3 round=1 query=3 oracle=TlpWhereOracle query_seed=310306 ===
     --- statement=1 context=TLP-WHERE all ---
     SELECT *
     FROM t2
@@ -160,7 +160,7 @@ fn full_run_logs_expected_queries_for_tlp_where_oracle() -> Result<(), Box<dyn E
     FROM t2
     WHERE (NULL) IS NULL
 
-    === round=1 query=5 oracle=TlpWhereOracle query_seed=310308 ===
+    === id=310104-r1-q5 round=1 query=5 oracle=TlpWhereOracle query_seed=310308 ===
     --- statement=1 context=TLP-WHERE all ---
     SELECT *
     FROM t0
@@ -178,7 +178,7 @@ fn full_run_logs_expected_queries_for_tlp_where_oracle() -> Result<(), Box<dyn E
     FROM t0
     WHERE ((to_char(CAST('2052-04-28' AS DATE), '=B  2v') !~* to_char(INTERVAL '1 MONS -11 DAYS -0.658344865 SECS', to_char(CAST('2056-06-17 08:39:22.305135405 -09:00' AS TIMESTAMP), '9L4l6.-bG6dPLWk-7 ~9azH0^V;7q0S#|%@?MyX"')))) IS NULL
 
-    === round=2 query=1 oracle=TlpWhereOracle query_seed=311304 ===
+    === id=310104-r2-q1 round=2 query=1 oracle=TlpWhereOracle query_seed=311304 ===
     --- statement=1 context=TLP-WHERE all ---
     SELECT *
     FROM t0
@@ -196,7 +196,7 @@ fn full_run_logs_expected_queries_for_tlp_where_oracle() -> Result<(), Box<dyn E
     FROM t0
     WHERE (false) IS NULL
 
-    === round=2 query=2 oracle=TlpWhereOracle query_seed=311305 ===
+    === id=310104-r2-q2 round=2 query=2 oracle=TlpWhereOracle query_seed=311305 ===
     --- statement=1 context=TLP-WHERE all ---
     SELECT *
     FROM t0
@@ -214,7 +214,7 @@ fn full_run_logs_expected_queries_for_tlp_where_oracle() -> Result<(), Box<dyn E
     FROM t0
     WHERE (true) IS NULL
 
-    === round=2 query=3 oracle=TlpWhereOracle query_seed=311306 ===
+    === id=310104-r2-q3 round=2 query=3 oracle=TlpWhereOracle query_seed=311306 ===
     --- statement=1 context=TLP-WHERE all ---
     SELECT *
     FROM t2
@@ -232,7 +232,7 @@ fn full_run_logs_expected_queries_for_tlp_where_oracle() -> Result<(), Box<dyn E
     FROM t2
     WHERE (false) IS NULL
 
-    === round=2 query=4 oracle=TlpWhereOracle query_seed=311307 ===
+    === id=310104-r2-q4 round=2 query=4 oracle=TlpWhereOracle query_seed=311307 ===
     --- statement=1 context=TLP-WHERE all ---
     SELECT *
     FROM t1
@@ -250,7 +250,7 @@ fn full_run_logs_expected_queries_for_tlp_where_oracle() -> Result<(), Box<dyn E
     FROM t1
     WHERE ((96 >= ((-7.129738 - 23.446228) % (47.11673 / 88.10098)))) IS NULL
 
-    === round=2 query=5 oracle=TlpWhereOracle query_seed=311308 ===
+    === id=310104-r2-q5 round=2 query=5 oracle=TlpWhereOracle query_seed=311308 ===
     --- statement=1 context=TLP-WHERE all ---
     SELECT *
     FROM t2
@@ -291,7 +291,7 @@ fn full_run_logs_expected_queries_for_tlp_having_oracle() -> Result<(), Box<dyn
     let run_output = run_fuzzer_once(&config_path)?;
 
     insta::assert_snapshot!(run_output.query_log, @r#"
-    === round=1 query=1 oracle=TlpHavingOracle query_seed=310304 ===
+    === id=310104-r1-q1 round=1 query=1 oracle=TlpHavingOracle query_seed=310304 ===
     --- statement=1 context=TLP-HAVING all groups ---
     SELECT t0.col_t0_2_float32, t0.col_t0_1_decimal128
     FROM t0
@@ -313,7 +313,7 @@ fn full_run_logs_expected_queries_for_tlp_having_oracle() -> Result<(), Box<dyn
     GROUP BY t0.col_t0_2_float32, t0.col_t0_1_decimal128
     HAVING (false) IS NULL
 
-    === round=1 query=2 oracle=TlpHavingOracle query_seed=310305 ===
+    === id=310104-r1-q2 round=1 query=2 oracle=TlpHavingOracle query_seed=310305 ===
     --- statement=1 context=TLP-HAVING all groups ---
     SELECT t1.col_t1_1_int64, t1.col_t1_3_date32, t1.col_t1_2_int64
     FROM t1
@@ -335,7 +335,7 @@ fn full_run_logs_expected_queries_for_tlp_having_oracle() -> Result<(), Box<dyn
     GROUP BY t1.col_t1_1_int64, t1.col_t1_3_date32, t1.col_t1_2_int64
     HAVING (true) IS NULL
 
-    === round=1 query=3 oracle=TlpHavingOracle query_seed=310306 ===
+    === id=310104-r1-q3 round=1 query=3 oracle=TlpHavingOracle query_seed=310306 ===
     --- statement=1 context=TLP-HAVING all groups ---
     SELECT t2.col_t2_1_string
     FROM t2
@@ -361,7 +361,7 @@ fn full_run_logs_expected_queries_for_tlp_having_oracle() -> Result<(), Box<dyn
     GROUP BY t2.col_t2_1_string
     HAVING (true) IS NULL
 
-    === round=1 query=5 oracle=TlpHavingOracle query_seed=310308 ===
+    === id=310104-r1-q5 round=1 query=5 oracle=TlpHavingOracle query_seed=310308 ===
     --- statement=1 context=TLP-HAVING all groups ---
     SELECT t0.col_t0_2_float32, t0.col_t0_3_date32, t0.col_t0_1_decimal128
     FROM t0
@@ -387,7 +387,7 @@ fn full_run_logs_expected_queries_for_tlp_having_oracle() -> Result<(), Box<dyn
     GROUP BY t0.col_t0_2_float32, t0.col_t0_3_date32, t0.col_t0_1_decimal128
     HAVING ((to_char(INTERVAL '-7 MONS 29 DAYS -0.000000001 SECS', '%X `B') !~* '0SsYa@-p]yc`qTL8PvF #c;Tei9))DXs:^wgv[')) IS NULL
 
-    === round=2 query=1 oracle=TlpHavingOracle query_seed=311304 ===
+    === id=310104-r2-q1 round=2 query=1 oracle=TlpHavingOracle query_seed=311304 ===
     --- statement=1 context=TLP-HAVING all groups ---
     SELECT t0.col_t0_2_time64_nanosecond, t0.col_t0_5_timestamp, t0.col_t0_4_interval_month_day_nano
     FROM t0
@@ -413,7 +413,7 @@ fn full_run_logs_expected_queries_for_tlp_having_oracle() -> Result<(), Box<dyn
     GROUP BY t0.col_t0_2_time64_nanosecond, t0.col_t0_5_timestamp, t0.col_t0_4_interval_month_day_nano
     HAVING (false) IS NULL
 
-    === round=2 query=3 oracle=TlpHavingOracle query_seed=311306 ===
+    === id=310104-r2-q3 round=2 query=3 oracle=TlpHavingOracle query_seed=311306 ===
     --- statement=1 context=TLP-HAVING all groups ---
     SELECT t2.col_t2_1_float32
     FROM t2
@@ -439,7 +439,7 @@ fn full_run_logs_expected_queries_for_tlp_having_oracle() -> Result<(), Box<dyn
     GROUP BY t2.col_t2_1_float32
     HAVING (false) IS NULL
 
-    === round=2 query=4 oracle=TlpHavingOracle query_seed=311307 ===
+    === id=310104-r2-q4 round=2 query=4 oracle=TlpHavingOracle query_seed=311307 ===
     --- statement=1 context=TLP-HAVING all groups ---
     SELECT t1.col_t1_4_date32
     FROM t1
@@ -461,7 +461,7 @@ fn full_run_logs_expected_queries_for_tlp_having_oracle() -> Result<(), Box<dyn
     GROUP BY t1.col_t1_4_date32
     HAVING (true) IS NULL
 
-    === round=2 query=5 oracle=TlpHavingOracle query_seed=311308 ===
+    === id=310104-r2-q5 round=2 query=5 oracle=TlpHavingOracle query_seed=311308 ===
     --- statement=1 context=TLP-HAVING all groups ---
     SELECT t2.col_t2_1_float32
     FROM t2
@@ -498,6 +498,32 @@ fn full_run_logs_expected_queries_for_tlp_having_oracle() -> Result<(), Box<dyn
     Ok(())
 }
 
+/// Generating queries ahead of execution must not change the tests or their queries
+#[test]
+fn pipelined_run_logs_same_queries_as_sequential_run() -> Result<(), Box<dyn Error>> {
+    let mut query_logs = Vec::new();
+    for pipeline_depth in [0, 2] {
+        let log_dir = make_temp_log_dir(&format!("pipeline-{pipeline_depth}"))?;
+        let config_path = log_dir.join("integration.toml");
+        let config = RunnerConfig {
+            seed: 310104,
+            rounds: 2,
+            queries_per_round: 5,
+            pipeline_depth,
+            log_path: Some(log_dir.clone()),
+            enable_tui: false,
+            oracles: vec![ConfiguredOracle::NoCrash, ConfiguredOracle::TlpWhere],
+            ..RunnerConfig::default()
+        };
+        fs::write(&config_path, toml::to_string(&config)?)?;
+
+        query_logs.push(run_fuzzer_once(&config_path)?.query_log);
+    }
+
+    assert_eq!(query_logs[0], query_logs[1]);
+    Ok(())
+}
+
 struct RunOutput {
     query_log: String,
     stats_summary: String,
@@ -550,6 +576,8 @@ fn generate_default_config_with_oracles(
 ) -> Result<PathBuf, Box<dyn Error>> {
     let config_path = log_dir.join("integration.toml");
     let config = RunnerConfig {
+        // The snapshots were recorded with this seed
+        seed: 310104,
         rounds: 2,
         queries_per_round: 5,
         log_path: Some(log_dir.to_path_buf()),